@@ -23,19 +23,23 @@ use std::{
 	fs,
 	io::{self, Write},
 	path::PathBuf,
+	str::FromStr,
 	sync::Arc,
 };
 
 use codec::Encode;
 use sc_chain_spec::ChainSpec;
-use sc_cli::RpcEndpoint;
+use sc_cli::{BlockNumberOrHash, RpcEndpoint};
 use sc_client_api::HeaderBackend;
 use sc_service::{
 	config::{PrometheusConfig, RpcBatchRequestConfig, TelemetryEndpoints},
 	BasePath, TransactionPoolOptions,
 };
 use sp_core::hexdisplay::HexDisplay;
-use sp_runtime::traits::{Block as BlockT, Zero};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, Zero},
+};
 use url::Url;
 
 /// The `purge-chain` command used to remove the whole chain: the parachain and the relay chain.
@@ -202,6 +206,84 @@ impl sc_cli::CliConfiguration for ExportGenesisHeadCommand {
 	}
 }
 
+/// Command for exporting the head data of a block, e.g. the last block of a solochain that
+/// migrates to a parachain.
+///
+/// The head data is registered as the genesis head of the parachain, the first parachain block
+/// then builds on top of it. The hash and the state root of the block are printed to stderr, to
+/// cross-check them against the first parachain block.
+#[derive(Debug, clap::Parser)]
+pub struct ExportHeadCommand {
+	/// Output file name or stdout if unspecified.
+	#[arg()]
+	pub output: Option<PathBuf>,
+
+	/// Number or hash of the block to export the head of. Defaults to the finalized block.
+	#[arg(long, value_name = "BLOCK")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// Write output in binary. Default is to write in hex.
+	#[arg(short, long)]
+	pub raw: bool,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[command(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+impl ExportHeadCommand {
+	/// Run the export-head command
+	pub fn run<B, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		C: HeaderBackend<B> + 'static,
+		<B::Hash as FromStr>::Err: std::fmt::Debug,
+		NumberFor<B>: FromStr,
+		<NumberFor<B> as FromStr>::Err: std::fmt::Debug,
+	{
+		let hash = match self.at.as_ref().map(|at| at.parse::<B>()).transpose()? {
+			Some(BlockId::Hash(hash)) => hash,
+			Some(BlockId::Number(number)) =>
+				client.hash(number)?.ok_or_else(|| format!("Block #{number} doesn't exist"))?,
+			None => client.info().finalized_hash,
+		};
+		let header = client.header(hash)?.ok_or_else(|| format!("Block {hash:?} doesn't exist"))?;
+
+		let raw_header = header.encode();
+		let output_buf = if self.raw {
+			raw_header
+		} else {
+			format!("0x{:?}", HexDisplay::from(&raw_header)).into_bytes()
+		};
+
+		if let Some(output) = &self.output {
+			fs::write(output, output_buf)?;
+		} else {
+			io::stdout().write_all(&output_buf)?;
+		}
+
+		eprintln!("Block number: {}", header.number());
+		eprintln!("Block hash: {hash:?}");
+		eprintln!("State root: {:?}", header.state_root());
+
+		Ok(())
+	}
+}
+
+impl sc_cli::CliConfiguration for ExportHeadCommand {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
 /// Command for exporting the genesis wasm file.
 #[derive(Debug, clap::Parser)]
 pub struct ExportGenesisWasmCommand {
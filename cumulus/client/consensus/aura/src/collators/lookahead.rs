@@ -47,7 +47,7 @@ use polkadot_primitives::{
 	vstaging::DEFAULT_CLAIM_QUEUE_OFFSET, CollatorPair, Id as ParaId, OccupiedCoreAssumption,
 };
 
-use crate::{collator as collator_util, export_pov_to_path};
+use crate::{collator as collator_util, collators::LastRefusedBuildAttempt, export_pov_to_path};
 use futures::prelude::*;
 use sc_client_api::{backend::AuxStore, BlockBackend, BlockOf};
 use sc_consensus::BlockImport;
@@ -98,6 +98,8 @@ pub struct Params<BI, CIDP, Client, Backend, RClient, CHP, Proposer, CS> {
 	/// The maximum percentage of the maximum PoV size that the collator can use.
 	/// It will be removed once <https://github.com/paritytech/polkadot-sdk/issues/6020> is fixed.
 	pub max_pov_percentage: Option<u32>,
+	/// When set, the last attempt to build a block that was refused by the runtime is kept here.
+	pub refused_build_attempt: Option<LastRefusedBuildAttempt>,
 }
 
 /// Run async-backing-friendly Aura.
@@ -306,6 +308,7 @@ where
 					included_block,
 					para_client,
 					&keystore,
+					params.refused_build_attempt.as_ref(),
 				))
 			};
 
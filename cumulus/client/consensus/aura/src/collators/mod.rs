@@ -22,7 +22,7 @@
 //! builds on parachain blocks which have not yet been included in the relay chain.
 
 use crate::collator::SlotClaim;
-use codec::Codec;
+use codec::{Codec, Decode, Encode};
 use cumulus_client_consensus_common::{self as consensus_common, ParentSearchParams};
 use cumulus_primitives_aura::{AuraUnincludedSegmentApi, Slot};
 use cumulus_primitives_core::{relay_chain::Hash as ParaHash, BlockT, ClaimQueueOffset};
@@ -38,6 +38,7 @@ use sp_api::{ApiExt, ProvideRuntimeApi, RuntimeApiInfo};
 use sp_core::Pair;
 use sp_keystore::KeystorePtr;
 use sp_timestamp::Timestamp;
+use std::sync::Arc;
 
 pub mod basic;
 pub mod lookahead;
@@ -185,6 +186,7 @@ async fn can_build_upon<Block: BlockT, Client, P>(
 	included_block: Block::Hash,
 	client: &Client,
 	keystore: &KeystorePtr,
+	refused_build_attempt: Option<&LastRefusedBuildAttempt>,
 ) -> Option<SlotClaim<P::Public>>
 where
 	Client: ProvideRuntimeApi<Block>,
@@ -213,10 +215,64 @@ where
 
 	let slot = if api_version > 1 { relay_slot } else { para_slot };
 
-	runtime_api
-		.can_build_upon(parent_hash, included_block, slot)
-		.ok()?
-		.then(|| SlotClaim::unchecked::<P>(author_pub, para_slot, timestamp))
+	if !runtime_api.can_build_upon(parent_hash, included_block, slot).ok()? {
+		tracing::debug!(
+			target: crate::LOG_TARGET,
+			?parent_hash,
+			?included_block,
+			?relay_slot,
+			"Runtime refused to build upon the parent"
+		);
+		if let Some(refused_build_attempt) = refused_build_attempt {
+			refused_build_attempt.set(&RefusedBuildAttempt {
+				parent_hash,
+				included_hash: included_block,
+				relay_slot,
+				para_slot,
+			});
+		}
+		return None
+	}
+
+	Some(SlotClaim::unchecked::<P>(author_pub, para_slot, timestamp))
+}
+
+/// The last attempt to build a block that was refused by the runtime, i.e. for which
+/// [`AuraUnincludedSegmentApi::can_build_upon`] returned `false`.
+///
+/// The reason can be queried with [`cumulus_primitives_core::UnincludedSegmentApi::build_refusal`]
+/// at the `parent_hash`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct RefusedBuildAttempt<Hash> {
+	/// The block the collator tried to build upon.
+	pub parent_hash: Hash,
+	/// The most recently included block as of the relay parent.
+	pub included_hash: Hash,
+	/// The relay chain slot of the relay parent.
+	pub relay_slot: Slot,
+	/// The parachain slot claimed for the block.
+	pub para_slot: Slot,
+}
+
+/// The last [`RefusedBuildAttempt`] of a collator, kept in memory.
+///
+/// Clones share the same attempt, so a clone can be handed to the collator while another one is
+/// used to report the attempt, e.g. over RPC. The attempt is kept encoded, so that the handle
+/// does not depend on the block type.
+#[derive(Clone, Default)]
+pub struct LastRefusedBuildAttempt(Arc<parking_lot::Mutex<Option<Vec<u8>>>>);
+
+impl LastRefusedBuildAttempt {
+	/// Replace the last refused build attempt.
+	pub fn set<Hash: Encode>(&self, attempt: &RefusedBuildAttempt<Hash>) {
+		*self.0.lock() = Some(attempt.encode());
+	}
+
+	/// The last refused build attempt, if any.
+	pub fn get<Hash: Decode>(&self) -> Option<RefusedBuildAttempt<Hash>> {
+		let encoded = self.0.lock().clone()?;
+		RefusedBuildAttempt::decode(&mut &encoded[..]).ok()
+	}
 }
 
 /// Use [`cumulus_client_consensus_common::find_potential_parents`] to find parachain blocks that
@@ -272,10 +328,10 @@ where
 
 #[cfg(test)]
 mod tests {
-	use crate::collators::can_build_upon;
+	use crate::collators::{can_build_upon, LastRefusedBuildAttempt, RefusedBuildAttempt};
 	use codec::Encode;
 	use cumulus_primitives_aura::Slot;
-	use cumulus_primitives_core::BlockT;
+	use cumulus_primitives_core::{BlockT, BuildRefusal, UnincludedSegmentApi};
 	use cumulus_relay_chain_interface::PHash;
 	use cumulus_test_client::{
 		runtime::{Block, Hash},
@@ -285,6 +341,7 @@ mod tests {
 	use cumulus_test_relay_sproof_builder::RelayStateSproofBuilder;
 	use polkadot_primitives::HeadData;
 	use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy};
+	use sp_api::ProvideRuntimeApi;
 	use sp_consensus::BlockOrigin;
 	use sp_keystore::{Keystore, KeystorePtr};
 	use sp_timestamp::Timestamp;
@@ -359,6 +416,7 @@ mod tests {
 			genesis_hash,
 			&*client,
 			&keystore,
+			None,
 		)
 		.await
 		.is_some()
@@ -377,8 +435,54 @@ mod tests {
 			last_hash,
 			&*client,
 			&keystore,
+			None,
 		)
 		.await;
 		assert!(result.is_some());
 	}
+
+	#[tokio::test]
+	async fn refused_build_attempt_is_recorded() {
+		let (client, keystore) = set_up_components();
+
+		let genesis_hash = client.chain_info().genesis_hash;
+		let mut last_hash = genesis_hash;
+		let refused_build_attempt = LastRefusedBuildAttempt::default();
+		assert_eq!(refused_build_attempt.get::<Hash>(), None);
+
+		while can_build_upon::<_, _, sp_consensus_aura::sr25519::AuthorityPair>(
+			Slot::from(u64::MAX),
+			Slot::from(u64::MAX),
+			Timestamp::default(),
+			last_hash,
+			genesis_hash,
+			&*client,
+			&keystore,
+			Some(&refused_build_attempt),
+		)
+		.await
+		.is_some()
+		{
+			let block = build_and_import_block(&client, genesis_hash).await;
+			last_hash = block.header().hash();
+		}
+
+		let attempt = refused_build_attempt.get::<Hash>().unwrap();
+		assert_eq!(
+			attempt,
+			RefusedBuildAttempt {
+				parent_hash: last_hash,
+				included_hash: genesis_hash,
+				relay_slot: Slot::from(u64::MAX),
+				para_slot: Slot::from(u64::MAX),
+			}
+		);
+
+		// The runtime explains why it refused the attempt.
+		let refusal = client
+			.runtime_api()
+			.build_refusal(attempt.parent_hash, attempt.included_hash, attempt.relay_slot)
+			.unwrap();
+		assert!(matches!(refusal, Some(BuildRefusal::UnincludedSegmentFull { .. })));
+	}
 }
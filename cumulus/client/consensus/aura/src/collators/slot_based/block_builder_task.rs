@@ -36,6 +36,7 @@ use crate::{
 			relay_chain_data_cache::{RelayChainData, RelayChainDataCache},
 			slot_timer::SlotTimer,
 		},
+		LastRefusedBuildAttempt,
 	},
 	LOG_TARGET,
 };
@@ -103,6 +104,8 @@ pub struct BuilderTaskParams<
 	/// The maximum percentage of the maximum PoV size that the collator can use.
 	/// It will be removed once https://github.com/paritytech/polkadot-sdk/issues/6020 is fixed.
 	pub max_pov_percentage: Option<u32>,
+	/// When set, the last attempt to build a block that was refused by the runtime is kept here.
+	pub refused_build_attempt: Option<LastRefusedBuildAttempt>,
 }
 
 /// Run block-builder.
@@ -152,6 +155,7 @@ where
 			para_backend,
 			slot_offset,
 			max_pov_percentage,
+			refused_build_attempt,
 		} = params;
 
 		let mut slot_timer = SlotTimer::<_, _, P>::new_with_offset(
@@ -274,6 +278,7 @@ where
 				included_block,
 				&*para_client,
 				&keystore,
+				refused_build_attempt.as_ref(),
 			)
 			.await
 			{
@@ -0,0 +1,806 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus. If not, see <https://www.gnu.org/licenses/>.
+
+//! Acquisition of on-demand cores when the parachain is congested.
+//!
+//! The task follows the best relay chain block and measures the pressure on the parachain: the
+//! number of ready transactions in the pool and the number of blocks in the unincluded segment.
+//! When either exceeds its threshold, it places an order with the on-demand pallet of the relay
+//! chain, as long as the configured budget allows it.
+//!
+//! The orders are signed by an [`OrderSigner`], e.g. a [`KeystoreOrderSigner`] paying with an
+//! account key of the collator's keystore.
+
+use async_trait::async_trait;
+use codec::{Compact, Decode, Encode};
+use cumulus_relay_chain_interface::{call_runtime_api, RelayChainInterface, RelayChainResult};
+use futures::StreamExt;
+use polkadot_primitives::{
+	AccountId, Balance, BlockId, BlockNumber as RelayBlockNumber, Hash as RelayHash, Id as ParaId,
+	Nonce, OccupiedCoreAssumption,
+};
+use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, U64};
+use sc_transaction_pool_api::TransactionPool;
+use sp_blockchain::HeaderBackend;
+use sp_core::{blake2_256, crypto::key_types, sr25519};
+use sp_keystore::KeystorePtr;
+use sp_runtime::{
+	generic::Era,
+	traits::{Block as BlockT, Header as HeaderT, Saturating, UniqueSaturatedInto},
+	MultiAddress, MultiSignature,
+};
+use std::{error::Error, sync::Arc};
+
+const LOG_TARGET: &str = "aura::cumulus::core-acquisition";
+
+/// The call index of `place_order_keep_alive` in the on-demand pallet.
+const PLACE_ORDER_KEEP_ALIVE_CALL_INDEX: u8 = 1;
+
+/// The number of relay chain blocks an order extrinsic stays valid for.
+const ORDER_MORTALITY: u64 = 64;
+
+/// The version of the signed extrinsics built by [`KeystoreOrderSigner`].
+const SIGNED_EXTRINSIC_VERSION: u8 = 4 | 0b1000_0000;
+
+/// Signs the relay chain extrinsics that place on-demand orders.
+///
+/// The format of signed extrinsics depends on the relay chain runtime, so it is up to the node to
+/// sign them, e.g. with an account funded with the coretime budget of the collator.
+#[async_trait]
+pub trait OrderSigner: Send + Sync {
+	/// Create a signed extrinsic dispatching the SCALE encoded `call`, valid at `relay_parent`.
+	async fn sign(
+		&self,
+		relay_parent: RelayHash,
+		call: Vec<u8>,
+	) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// An [`OrderSigner`] paying with the first sr25519 key of type [`key_types::ACCOUNT`] in the
+/// keystore.
+///
+/// The extrinsics are built for the transaction extensions of the Rococo and Westend runtimes,
+/// are mortal and pay no tip. The nonce is read at the relay parent, so the order cooldown should
+/// leave enough time for an order to be included before the next one is signed.
+pub struct KeystoreOrderSigner<RClient> {
+	keystore: KeystorePtr,
+	public: sr25519::Public,
+	relay_client: RClient,
+}
+
+impl<RClient> KeystoreOrderSigner<RClient> {
+	/// Create a signer with the account key of `keystore`.
+	///
+	/// Returns `None` if the keystore holds no such key.
+	pub fn new(keystore: KeystorePtr, relay_client: RClient) -> Option<Self> {
+		let public = keystore.sr25519_public_keys(key_types::ACCOUNT).into_iter().next()?;
+		Some(Self { keystore, public, relay_client })
+	}
+
+	/// The account paying for the orders.
+	pub fn account(&self) -> AccountId {
+		self.public.into()
+	}
+}
+
+#[async_trait]
+impl<RClient: RelayChainInterface> OrderSigner for KeystoreOrderSigner<RClient> {
+	async fn sign(
+		&self,
+		relay_parent: RelayHash,
+		call: Vec<u8>,
+	) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+		let relay_client = &self.relay_client;
+		let relay_parent_number = relay_client
+			.header(BlockId::Hash(relay_parent))
+			.await?
+			.ok_or("Unknown relay parent")?
+			.number;
+		let genesis_hash = relay_client
+			.header(BlockId::Number(0))
+			.await?
+			.ok_or("Unknown relay chain genesis block")?
+			.hash();
+		let version = relay_client.version(relay_parent).await?;
+		let nonce: Nonce = call_runtime_api(
+			relay_client,
+			"AccountNonceApi_account_nonce",
+			relay_parent,
+			self.account(),
+		)
+		.await?;
+
+		// `CheckMortality`, `CheckNonce`, `ChargeTransactionPayment` and `CheckMetadataHash`, with
+		// the metadata hash check disabled.
+		let era = Era::mortal(ORDER_MORTALITY, relay_parent_number.into());
+		let explicit = (era, Compact(nonce), Compact::<Balance>(0), 0u8);
+		// `CheckSpecVersion`, `CheckTxVersion`, `CheckGenesis`, `CheckMortality` and
+		// `CheckMetadataHash`.
+		let implicit = (
+			version.spec_version,
+			version.transaction_version,
+			genesis_hash,
+			relay_parent,
+			None::<[u8; 32]>,
+		);
+
+		let mut payload = call.clone();
+		explicit.encode_to(&mut payload);
+		implicit.encode_to(&mut payload);
+		// Payloads longer than 256 bytes are signed by their hash.
+		let message = if payload.len() > 256 { blake2_256(&payload).to_vec() } else { payload };
+		let signature = self
+			.keystore
+			.sr25519_sign(key_types::ACCOUNT, &self.public, &message)?
+			.ok_or("The account key is no longer in the keystore")?;
+
+		let mut extrinsic = vec![SIGNED_EXTRINSIC_VERSION];
+		MultiAddress::<AccountId, ()>::Id(self.account()).encode_to(&mut extrinsic);
+		MultiSignature::Sr25519(signature).encode_to(&mut extrinsic);
+		explicit.encode_to(&mut extrinsic);
+		extrinsic.extend(call);
+		Ok(extrinsic.encode())
+	}
+}
+
+/// When and how much to spend on on-demand cores.
+#[derive(Debug, Clone)]
+pub struct CoreAcquisitionConfig {
+	/// The index of the on-demand pallet in the relay chain runtime.
+	pub on_demand_pallet_index: u8,
+	/// Order a core once this many transactions are ready in the pool.
+	pub ready_transactions_threshold: usize,
+	/// Order a core once this many blocks are waiting in the unincluded segment.
+	pub unincluded_blocks_threshold: u32,
+	/// Don't order a core while the claim queue holds this many claims for the parachain.
+	pub max_claims: u32,
+	/// The maximum price to pay for a single order.
+	pub max_amount_per_order: Balance,
+	/// The amount that may be spent in each budget period.
+	pub budget: Balance,
+	/// The length of a budget period, in relay chain blocks.
+	pub budget_period: RelayBlockNumber,
+	/// The minimum number of relay chain blocks between two orders, to give a placed order the
+	/// time to show up in the claim queue.
+	pub order_cooldown: RelayBlockNumber,
+}
+
+/// Parameters for [`run_core_acquisition_task`].
+pub struct CoreAcquisitionParams<Client, RClient, Pool> {
+	/// The para's ID.
+	pub para_id: ParaId,
+	/// The underlying para client.
+	pub para_client: Arc<Client>,
+	/// A handle to the relay-chain client, used to submit the orders.
+	pub relay_client: RClient,
+	/// The transaction pool of the parachain.
+	pub transaction_pool: Arc<Pool>,
+	/// Signs the order extrinsics.
+	pub order_signer: Arc<dyn OrderSigner>,
+	/// When and how much to spend on on-demand cores.
+	pub config: CoreAcquisitionConfig,
+	/// The prometheus registry to register the metrics in.
+	pub prometheus_registry: Option<Registry>,
+}
+
+/// Measurements of the pressure on the parachain at a relay chain block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pressure {
+	/// The number of ready transactions in the pool.
+	ready_transactions: usize,
+	/// The number of para blocks built on top of the included block.
+	unincluded_blocks: u32,
+	/// The number of cores scheduled for the parachain at the relay chain block.
+	scheduled_cores: u32,
+	/// The number of claims for the parachain in the whole claim queue.
+	claims: u32,
+	/// The number of candidates of the parachain pending availability.
+	occupied_cores: u32,
+}
+
+/// Decides when to place orders, within the budget.
+struct OrderPolicy {
+	config: CoreAcquisitionConfig,
+	period_start: RelayBlockNumber,
+	spent: Balance,
+	last_order: Option<RelayBlockNumber>,
+}
+
+impl OrderPolicy {
+	fn new(config: CoreAcquisitionConfig) -> Self {
+		Self { config, period_start: 0, spent: 0, last_order: None }
+	}
+
+	/// Whether to order a core at the relay chain block `now`.
+	fn should_order(&mut self, now: RelayBlockNumber, pressure: &Pressure) -> bool {
+		if now >= self.period_start.saturating_add(self.config.budget_period) {
+			self.period_start = now;
+			self.spent = 0;
+		}
+
+		let congested = pressure.ready_transactions >= self.config.ready_transactions_threshold ||
+			pressure.unincluded_blocks >= self.config.unincluded_blocks_threshold;
+		let cooled_down = self
+			.last_order
+			.map_or(true, |last| now >= last.saturating_add(self.config.order_cooldown));
+		let within_budget =
+			self.spent.saturating_add(self.config.max_amount_per_order) <= self.config.budget;
+
+		congested && pressure.claims < self.config.max_claims && cooled_down && within_budget
+	}
+
+	/// Note an order attempted at the relay chain block `now`.
+	///
+	/// Orders are accounted with their maximum price, as the actual price is only known on the
+	/// relay chain.
+	fn note_order(&mut self, now: RelayBlockNumber, placed: bool) {
+		self.last_order = Some(now);
+		if placed {
+			self.spent = self.spent.saturating_add(self.config.max_amount_per_order);
+		}
+	}
+}
+
+/// Encode a call of `place_order_keep_alive` of the on-demand pallet.
+fn place_order_call(pallet_index: u8, max_amount: Balance, para_id: ParaId) -> Vec<u8> {
+	(pallet_index, PLACE_ORDER_KEEP_ALIVE_CALL_INDEX, max_amount, para_id).encode()
+}
+
+/// Metrics of the core acquisition task.
+#[derive(Clone)]
+struct Metrics {
+	scheduled_cores: Gauge<U64>,
+	occupied_cores: Gauge<U64>,
+	claims: Gauge<U64>,
+	ready_transactions: Gauge<U64>,
+	unincluded_blocks: Gauge<U64>,
+	orders_placed: Counter<U64>,
+	orders_failed: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			scheduled_cores: register(
+				Gauge::new(
+					"parachain_core_acquisition_scheduled_cores",
+					"Number of cores scheduled for the parachain at the best relay chain block",
+				)?,
+				registry,
+			)?,
+			occupied_cores: register(
+				Gauge::new(
+					"parachain_core_acquisition_occupied_cores",
+					"Number of candidates of the parachain pending availability",
+				)?,
+				registry,
+			)?,
+			claims: register(
+				Gauge::new(
+					"parachain_core_acquisition_claims",
+					"Number of claims for the parachain in the claim queue",
+				)?,
+				registry,
+			)?,
+			ready_transactions: register(
+				Gauge::new(
+					"parachain_core_acquisition_ready_transactions",
+					"Number of ready transactions in the transaction pool",
+				)?,
+				registry,
+			)?,
+			unincluded_blocks: register(
+				Gauge::new(
+					"parachain_core_acquisition_unincluded_blocks",
+					"Number of blocks in the unincluded segment",
+				)?,
+				registry,
+			)?,
+			orders_placed: register(
+				Counter::new(
+					"parachain_core_acquisition_orders_placed_total",
+					"Number of on-demand orders submitted to the relay chain",
+				)?,
+				registry,
+			)?,
+			orders_failed: register(
+				Counter::new(
+					"parachain_core_acquisition_orders_failed_total",
+					"Number of on-demand orders that could not be submitted",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	fn observe(&self, pressure: &Pressure) {
+		self.scheduled_cores.set(pressure.scheduled_cores.into());
+		self.occupied_cores.set(pressure.occupied_cores.into());
+		self.claims.set(pressure.claims.into());
+		self.ready_transactions.set(pressure.ready_transactions as u64);
+		self.unincluded_blocks.set(pressure.unincluded_blocks.into());
+	}
+}
+
+/// Measure the pressure on the parachain at the relay chain block `relay_parent`.
+async fn measure_pressure<Block, Client, RClient, Pool>(
+	para_id: ParaId,
+	para_client: &Client,
+	relay_client: &RClient,
+	transaction_pool: &Pool,
+	relay_parent: RelayHash,
+) -> RelayChainResult<Pressure>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block>,
+	RClient: RelayChainInterface,
+	Pool: TransactionPool,
+{
+	let claim_queue = relay_client.claim_queue(relay_parent).await?;
+	let scheduled_cores =
+		claim_queue.values().filter(|claims| claims.front() == Some(&para_id)).count();
+	let claims = claim_queue.values().flatten().filter(|claim| **claim == para_id).count();
+	let occupied_cores =
+		relay_client.candidates_pending_availability(relay_parent, para_id).await?.len();
+
+	let included_number = relay_client
+		.persisted_validation_data(relay_parent, para_id, OccupiedCoreAssumption::TimedOut)
+		.await?
+		.and_then(|validation_data| {
+			Block::Header::decode(&mut &validation_data.parent_head.0[..]).ok()
+		})
+		.map(|header| *header.number());
+	let best_number = para_client.info().best_number;
+	let unincluded_blocks = included_number.map_or(0, |included_number| {
+		best_number.saturating_sub(included_number).unique_saturated_into()
+	});
+
+	Ok(Pressure {
+		ready_transactions: transaction_pool.status().ready,
+		unincluded_blocks,
+		scheduled_cores: scheduled_cores as u32,
+		claims: claims as u32,
+		occupied_cores: occupied_cores as u32,
+	})
+}
+
+/// Order on-demand cores for the parachain while it is congested.
+///
+/// Runs until the relay chain best block stream ends.
+pub async fn run_core_acquisition_task<Block, Client, RClient, Pool>(
+	params: CoreAcquisitionParams<Client, RClient, Pool>,
+) where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + Send + Sync + 'static,
+	RClient: RelayChainInterface + Clone + 'static,
+	Pool: TransactionPool + 'static,
+{
+	let CoreAcquisitionParams {
+		para_id,
+		para_client,
+		relay_client,
+		transaction_pool,
+		order_signer,
+		config,
+		prometheus_registry,
+	} = params;
+
+	let metrics = prometheus_registry.as_ref().and_then(|registry| {
+		Metrics::register(registry)
+			.inspect_err(|error| {
+				tracing::warn!(target: LOG_TARGET, ?error, "Failed to register metrics");
+			})
+			.ok()
+	});
+	let mut new_best_notifications = match relay_client.new_best_notification_stream().await {
+		Ok(stream) => stream,
+		Err(error) => {
+			tracing::error!(target: LOG_TARGET, ?error, "Failed to follow the relay chain");
+			return
+		},
+	};
+	let mut policy = OrderPolicy::new(config);
+
+	while let Some(relay_header) = new_best_notifications.next().await {
+		let relay_parent = relay_header.hash();
+		let pressure = match measure_pressure(
+			para_id,
+			&*para_client,
+			&relay_client,
+			&*transaction_pool,
+			relay_parent,
+		)
+		.await
+		{
+			Ok(pressure) => pressure,
+			Err(error) => {
+				tracing::debug!(
+					target: LOG_TARGET,
+					?error,
+					?relay_parent,
+					"Failed to measure the pressure on the parachain",
+				);
+				continue
+			},
+		};
+		if let Some(metrics) = &metrics {
+			metrics.observe(&pressure);
+		}
+
+		if !policy.should_order(relay_header.number, &pressure) {
+			continue
+		}
+
+		let call = place_order_call(
+			policy.config.on_demand_pallet_index,
+			policy.config.max_amount_per_order,
+			para_id,
+		);
+		let result = match order_signer.sign(relay_parent, call).await {
+			Ok(extrinsic) => relay_client.submit_extrinsic(extrinsic).await.map_err(Into::into),
+			Err(error) => Err(error),
+		};
+		match result {
+			Ok(extrinsic_hash) => {
+				tracing::info!(
+					target: LOG_TARGET,
+					?extrinsic_hash,
+					ready_transactions = pressure.ready_transactions,
+					unincluded_blocks = pressure.unincluded_blocks,
+					"Placed an on-demand order",
+				);
+				if let Some(metrics) = &metrics {
+					metrics.orders_placed.inc();
+				}
+			},
+			Err(error) => {
+				tracing::warn!(target: LOG_TARGET, ?error, "Failed to place an on-demand order");
+				if let Some(metrics) = &metrics {
+					metrics.orders_failed.inc();
+				}
+			},
+		}
+		policy.note_order(relay_header.number, result.is_ok());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use cumulus_relay_chain_interface::{
+		CommittedCandidateReceipt, CoreIndex, CoreState, InboundDownwardMessage,
+		InboundHrmpMessage, OverseerHandle, PHash, PHeader, PersistedValidationData, SessionIndex,
+		StorageValue, ValidationCodeHash, ValidatorId,
+	};
+	use cumulus_test_client::{TestClientBuilder, TestClientBuilderExt};
+	use futures::{stream, Stream};
+	use sc_transaction_pool::BasicPool;
+	use sp_core::Pair;
+	use sp_keystore::{testing::MemoryKeystore, Keystore};
+	use sp_version::RuntimeVersion;
+	use std::{
+		collections::{BTreeMap, VecDeque},
+		pin::Pin,
+		sync::Mutex,
+	};
+	use substrate_test_runtime_transaction_pool::TestApi;
+
+	const NONCE: Nonce = 7;
+
+	/// A relay chain announcing `best_heads` and recording the submitted extrinsics.
+	#[derive(Default)]
+	struct Relaychain {
+		best_heads: Vec<PHeader>,
+		submitted: Mutex<Vec<Vec<u8>>>,
+	}
+
+	fn relay_header(number: RelayBlockNumber) -> PHeader {
+		PHeader {
+			parent_hash: PHash::repeat_byte(number as u8),
+			number,
+			state_root: PHash::zero(),
+			extrinsics_root: PHash::zero(),
+			digest: Default::default(),
+		}
+	}
+
+	#[async_trait]
+	impl RelayChainInterface for Relaychain {
+		async fn validators(&self, _: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn finalized_block_hash(&self) -> RelayChainResult<PHash> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn retrieve_dmq_contents(
+			&self,
+			_: ParaId,
+			_: PHash,
+		) -> RelayChainResult<Vec<InboundDownwardMessage>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn retrieve_all_inbound_hrmp_channel_contents(
+			&self,
+			_: ParaId,
+			_: PHash,
+		) -> RelayChainResult<BTreeMap<ParaId, Vec<InboundHrmpMessage>>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn persisted_validation_data(
+			&self,
+			_: PHash,
+			_: ParaId,
+			_: OccupiedCoreAssumption,
+		) -> RelayChainResult<Option<PersistedValidationData>> {
+			Ok(None)
+		}
+
+		async fn validation_code_hash(
+			&self,
+			_: PHash,
+			_: ParaId,
+			_: OccupiedCoreAssumption,
+		) -> RelayChainResult<Option<ValidationCodeHash>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn candidate_pending_availability(
+			&self,
+			_: PHash,
+			_: ParaId,
+		) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn candidates_pending_availability(
+			&self,
+			_: PHash,
+			_: ParaId,
+		) -> RelayChainResult<Vec<CommittedCandidateReceipt>> {
+			Ok(Vec::new())
+		}
+
+		async fn session_index_for_child(&self, _: PHash) -> RelayChainResult<SessionIndex> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn import_notification_stream(
+			&self,
+		) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn finality_notification_stream(
+			&self,
+		) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+			unimplemented!("Not needed for test")
+		}
+
+		fn overseer_handle(&self) -> RelayChainResult<OverseerHandle> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn get_storage_by_key(
+			&self,
+			_: PHash,
+			_: &[u8],
+		) -> RelayChainResult<Option<StorageValue>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn prove_read(
+			&self,
+			_: PHash,
+			_: &Vec<Vec<u8>>,
+		) -> RelayChainResult<sc_client_api::StorageProof> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn wait_for_block(&self, _: PHash) -> RelayChainResult<()> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn new_best_notification_stream(
+			&self,
+		) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+			Ok(stream::iter(self.best_heads.clone()).boxed())
+		}
+
+		async fn header(&self, block_id: BlockId) -> RelayChainResult<Option<PHeader>> {
+			Ok(match block_id {
+				BlockId::Hash(hash) =>
+					self.best_heads.iter().find(|header| header.hash() == hash).cloned(),
+				BlockId::Number(number) => Some(relay_header(number)),
+			})
+		}
+
+		async fn availability_cores(
+			&self,
+			_: PHash,
+		) -> RelayChainResult<Vec<CoreState<PHash, RelayBlockNumber>>> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn version(&self, _: PHash) -> RelayChainResult<RuntimeVersion> {
+			Ok(RuntimeVersion {
+				spec_version: 1_017_001,
+				transaction_version: 27,
+				..Default::default()
+			})
+		}
+
+		async fn claim_queue(
+			&self,
+			_: PHash,
+		) -> RelayChainResult<BTreeMap<CoreIndex, VecDeque<ParaId>>> {
+			Ok(BTreeMap::new())
+		}
+
+		async fn call_runtime_api(
+			&self,
+			method_name: &'static str,
+			_: PHash,
+			_: &[u8],
+		) -> RelayChainResult<Vec<u8>> {
+			assert_eq!(method_name, "AccountNonceApi_account_nonce");
+			Ok(NONCE.encode())
+		}
+
+		async fn scheduling_lookahead(&self, _: PHash) -> RelayChainResult<u32> {
+			unimplemented!("Not needed for test")
+		}
+
+		async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> RelayChainResult<PHash> {
+			let hash = blake2_256(&extrinsic).into();
+			self.submitted.lock().unwrap().push(extrinsic);
+			Ok(hash)
+		}
+	}
+
+	fn config() -> CoreAcquisitionConfig {
+		CoreAcquisitionConfig {
+			on_demand_pallet_index: 66,
+			ready_transactions_threshold: 100,
+			unincluded_blocks_threshold: 3,
+			max_claims: 2,
+			max_amount_per_order: 10,
+			budget: 20,
+			budget_period: 100,
+			order_cooldown: 5,
+		}
+	}
+
+	fn pressure(ready_transactions: usize, unincluded_blocks: u32) -> Pressure {
+		Pressure {
+			ready_transactions,
+			unincluded_blocks,
+			scheduled_cores: 1,
+			claims: 1,
+			occupied_cores: 1,
+		}
+	}
+
+	#[test]
+	fn orders_only_when_congested() {
+		let mut policy = OrderPolicy::new(config());
+
+		assert!(!policy.should_order(1, &pressure(99, 2)));
+		assert!(policy.should_order(1, &pressure(100, 0)));
+		assert!(policy.should_order(1, &pressure(0, 3)));
+		assert!(!policy.should_order(1, &Pressure { claims: 2, ..pressure(100, 3) }));
+	}
+
+	#[test]
+	fn orders_respect_cooldown_and_budget() {
+		let mut policy = OrderPolicy::new(config());
+		let congested = pressure(100, 3);
+
+		assert!(policy.should_order(1, &congested));
+		policy.note_order(1, true);
+		assert!(!policy.should_order(5, &congested));
+
+		// Failed orders don't count against the budget, but still wait for the cooldown.
+		assert!(policy.should_order(6, &congested));
+		policy.note_order(6, false);
+		assert!(!policy.should_order(10, &congested));
+
+		assert!(policy.should_order(11, &congested));
+		policy.note_order(11, true);
+		assert!(!policy.should_order(50, &congested), "budget of the period is spent");
+
+		assert!(policy.should_order(100, &congested), "budget is renewed every period");
+	}
+
+	#[test]
+	fn place_order_call_encoding() {
+		let call = place_order_call(66, 1_000, ParaId::from(2000));
+
+		let mut expected = vec![66, PLACE_ORDER_KEEP_ALIVE_CALL_INDEX];
+		expected.extend(1_000u128.to_le_bytes());
+		expected.extend(2000u32.to_le_bytes());
+		assert_eq!(call, expected);
+	}
+
+	#[tokio::test]
+	async fn congestion_submits_signed_orders() {
+		let relay_client = Arc::new(Relaychain {
+			best_heads: (1..=6).map(relay_header).collect(),
+			..Default::default()
+		});
+		let keystore = Arc::new(MemoryKeystore::new());
+		let public = keystore.sr25519_generate_new(key_types::ACCOUNT, Some("//Alice")).unwrap();
+		let order_signer = KeystoreOrderSigner::new(keystore, relay_client.clone()).unwrap();
+
+		let pool_api = Arc::new(TestApi::empty());
+		let genesis_hash = pool_api.genesis_hash();
+		let (transaction_pool, _) =
+			BasicPool::new_test(pool_api, genesis_hash, genesis_hash, Default::default());
+
+		run_core_acquisition_task::<cumulus_test_client::runtime::Block, _, _, _>(
+			CoreAcquisitionParams {
+				para_id: ParaId::from(2000),
+				para_client: Arc::new(TestClientBuilder::new().build()),
+				relay_client: relay_client.clone(),
+				transaction_pool: Arc::new(transaction_pool),
+				order_signer: Arc::new(order_signer),
+				// The empty unincluded segment is enough to be congested.
+				config: CoreAcquisitionConfig { unincluded_blocks_threshold: 0, ..config() },
+				prometheus_registry: None,
+			},
+		)
+		.await;
+
+		// Orders are placed at block 1 and, after the cooldown, at block 6.
+		let submitted = relay_client.submitted.lock().unwrap().clone();
+		assert_eq!(submitted.len(), 2);
+
+		let extrinsic = Vec::<u8>::decode(&mut &submitted[0][..]).unwrap();
+		let call = place_order_call(66, 10, ParaId::from(2000));
+		let (header, rest) = extrinsic.split_at(1);
+		assert_eq!(header, [SIGNED_EXTRINSIC_VERSION]);
+
+		let input = &mut &rest[..];
+		let address = MultiAddress::<AccountId, ()>::decode(input).unwrap();
+		assert_eq!(address, MultiAddress::Id(public.into()));
+		let MultiSignature::Sr25519(signature) = MultiSignature::decode(input).unwrap() else {
+			panic!("Orders are signed with sr25519")
+		};
+		let explicit =
+			(Era::mortal(ORDER_MORTALITY, 1), Compact(NONCE), Compact::<Balance>(0), 0u8);
+		let decoded: (Era, Compact<Nonce>, Compact<Balance>, u8) = Decode::decode(input).unwrap();
+		assert_eq!(decoded, explicit);
+		assert_eq!(*input, &call[..]);
+
+		let mut payload = call.clone();
+		explicit.encode_to(&mut payload);
+		(1_017_001u32, 27u32, relay_header(0).hash(), relay_header(1).hash(), None::<[u8; 32]>)
+			.encode_to(&mut payload);
+		assert!(sr25519::Pair::verify(&signature, &payload, &public));
+	}
+}
@@ -65,8 +65,16 @@
 //!
 //! 1. Block compression
 //! 2. Submission to the collation-generation subsystem
+//!
+//! # On-demand Core Acquisition
+//!
+//! Optionally, [`run_core_acquisition_task`] can be spawned next to the collator to order
+//! on-demand cores from the relay chain when the transaction pool or the unincluded segment of the
+//! parachain is congested. Orders are signed by an [`OrderSigner`] provided by the node, like the
+//! [`KeystoreOrderSigner`], and are limited by the budget in the [`CoreAcquisitionConfig`].
 
 use self::{block_builder_task::run_block_builder, collation_task::run_collation_task};
+use super::LastRefusedBuildAttempt;
 use codec::Codec;
 use consensus_common::ParachainCandidate;
 use cumulus_client_collator::service::ServiceInterface as CollatorServiceInterface;
@@ -94,10 +102,15 @@ use sp_runtime::traits::{Block as BlockT, Member, NumberFor, One};
 use std::{path::PathBuf, sync::Arc, time::Duration};
 
 pub use block_import::{SlotBasedBlockImport, SlotBasedBlockImportHandle};
+pub use core_acquisition::{
+	run_core_acquisition_task, CoreAcquisitionConfig, CoreAcquisitionParams, KeystoreOrderSigner,
+	OrderSigner,
+};
 
 mod block_builder_task;
 mod block_import;
 mod collation_task;
+mod core_acquisition;
 mod relay_chain_data_cache;
 
 mod slot_timer;
@@ -146,6 +159,8 @@ pub struct Params<Block, BI, CIDP, Client, Backend, RClient, CHP, Proposer, CS,
 	/// The maximum percentage of the maximum PoV size that the collator can use.
 	/// It will be removed once <https://github.com/paritytech/polkadot-sdk/issues/6020> is fixed.
 	pub max_pov_percentage: Option<u32>,
+	/// When set, the last attempt to build a block that was refused by the runtime is kept here.
+	pub refused_build_attempt: Option<LastRefusedBuildAttempt>,
 }
 
 /// Run aura-based block building and collation task.
@@ -197,6 +212,7 @@ pub fn run<Block, P, BI, CIDP, Client, Backend, RClient, CHP, Proposer, CS, Spaw
 		export_pov,
 		relay_chain_slot_duration,
 		max_pov_percentage,
+		refused_build_attempt,
 	} = params;
 
 	let (tx, rx) = tracing_unbounded("mpsc_builder_to_collator", 100);
@@ -229,6 +245,7 @@ pub fn run<Block, P, BI, CIDP, Client, Backend, RClient, CHP, Proposer, CS, Spaw
 		relay_chain_slot_duration,
 		slot_offset,
 		max_pov_percentage,
+		refused_build_attempt,
 	};
 
 	let block_builder_fut =
@@ -0,0 +1,671 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{mock::ParachainSystemName, MockXcmConfig};
+use async_trait::async_trait;
+use codec::Decode;
+use cumulus_primitives_core::{
+	relay_chain::{self, AsyncBackingParams, BlockId, HeadData, Slot, UpgradeGoAhead},
+	AbridgedHostConfiguration,
+};
+use cumulus_primitives_parachain_inherent::MessageQueueChain;
+use cumulus_relay_chain_interface::{
+	BlockNumber, CommittedCandidateReceipt, CoreIndex, CoreState, InboundDownwardMessage,
+	InboundHrmpMessage, OccupiedCoreAssumption, OverseerHandle, PHash, PHeader, ParaId,
+	PersistedValidationData, RelayChainError, RelayChainInterface, RelayChainResult, SessionIndex,
+	StorageValue, ValidationCodeHash, ValidatorId,
+};
+use futures::{channel::mpsc, Stream};
+use sc_client_api::{Backend, StorageProof, StorageProvider};
+use sp_crypto_hashing::twox_128;
+use sp_runtime::traits::{BlakeTwo256, Block, Header as _};
+use sp_version::RuntimeVersion;
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+	pin::Pin,
+	sync::{Arc, Mutex},
+};
+
+/// The number of relay blocks per epoch.
+const BLOCKS_PER_EPOCH: u32 = 10;
+
+/// The scheduling lookahead of the emulated relay chain.
+const SCHEDULING_LOOKAHEAD: u32 = 3;
+
+/// The maximum PoV size in the emulated validation data.
+const MAX_POV_SIZE: u32 = 10 * 1024 * 1024;
+
+/// What the next emulated relay block is built for.
+pub struct RelayBlockParams {
+	/// The number of the para block the next para block is built on.
+	pub para_parent_number: u32,
+	/// The head of the para block the next para block is built on.
+	pub para_parent_head: HeadData,
+	/// The message queue heads of the para block the next para block is built on.
+	pub xcm_config: MockXcmConfig,
+	/// The relay parent number of the para block the next para block is built on, see
+	/// [`last_relay_chain_block_number`].
+	///
+	/// The messages delivered up to this relay block were processed by the parachain.
+	pub last_relay_parent_number: BlockNumber,
+	/// Whether the relay chain signals the parachain to go ahead with a code upgrade.
+	pub upgrade_go_ahead: Option<UpgradeGoAhead>,
+}
+
+/// Read the relay parent number of a para block from its state.
+pub fn last_relay_chain_block_number<B: Block, BE: Backend<B>, C: StorageProvider<B, BE>>(
+	client: &C,
+	para_block: B::Hash,
+	parachain_system_name: ParachainSystemName,
+) -> BlockNumber {
+	client
+		.storage(
+			para_block,
+			&sp_storage::StorageKey(
+				[twox_128(&parachain_system_name.0), twox_128(b"LastRelayChainBlockNumber")]
+					.concat()
+					.to_vec(),
+			),
+		)
+		.expect("We should be able to read storage from the parent block.")
+		.map(|ref mut raw_data| {
+			Decode::decode(&mut &raw_data.0[..]).expect("Stored data should decode correctly")
+		})
+		.unwrap_or_default()
+}
+
+struct EmulatedBlock {
+	header: PHeader,
+	proof: StorageProof,
+	validation_data: PersistedValidationData,
+	downward_messages: Vec<InboundDownwardMessage>,
+	horizontal_messages: BTreeMap<ParaId, Vec<InboundHrmpMessage>>,
+}
+
+#[derive(Default)]
+struct EmulatorState {
+	blocks: HashMap<PHash, EmulatedBlock>,
+	hashes_by_number: BTreeMap<BlockNumber, PHash>,
+	best_hash: PHash,
+	// Messages are delivered in every relay block until they are processed by the parachain, with
+	// the number of the first relay block which delivered them.
+	pending_downward_messages: Vec<(Option<BlockNumber>, Vec<u8>)>,
+	pending_horizontal_messages: Vec<(ParaId, Option<BlockNumber>, Vec<u8>)>,
+	inbound_channels: BTreeSet<ParaId>,
+	outbound_channels: BTreeSet<ParaId>,
+	// Heads of the latest para blocks, to delay their inclusion.
+	para_heads: VecDeque<HeadData>,
+	notification_sinks: Vec<mpsc::UnboundedSender<PHeader>>,
+}
+
+/// A relay chain emulated in memory, for developing parachains without running a relay chain.
+///
+/// Each para block is built on a new relay block, which is built by [`Self::build_relay_block`].
+/// The relay block contains a valid storage proof of the relay chain state relevant for the
+/// parachain, as well as the downward and horizontal messages not yet processed by the para
+/// block it is built for. Messages stay pending until a para block built on a relay block which
+/// delivered them is imported, so that they are not lost if that para block is discarded. Para
+/// blocks are included `inclusion_delay` para blocks after they were built, so that the
+/// unincluded segment of the parachain grows like with asynchronous backing.
+///
+/// All blocks are final immediately and the emulated relay chain has no validators, availability
+/// cores or runtime.
+#[derive(Clone)]
+pub struct RelayChainEmulator {
+	para_id: ParaId,
+	inclusion_delay: u32,
+	state: Arc<Mutex<EmulatorState>>,
+}
+
+impl RelayChainEmulator {
+	/// Create an emulated relay chain for the given parachain.
+	///
+	/// `inclusion_delay` must be lower than the unincluded segment capacity of the parachain
+	/// runtime, `0` includes each para block right away.
+	pub fn new(para_id: ParaId, inclusion_delay: u32) -> Self {
+		let emulator =
+			Self { para_id, inclusion_delay, state: Arc::new(Mutex::new(Default::default())) };
+		emulator.import_block(0, Default::default(), Default::default(), None, Default::default());
+		emulator
+	}
+
+	/// Queue a downward message, delivered from the next relay block on.
+	pub fn send_downward_message(&self, message: Vec<u8>) {
+		self.state().pending_downward_messages.push((None, message));
+	}
+
+	/// Queue a horizontal message from the `sender` parachain, delivered from the next relay block
+	/// on.
+	///
+	/// Opens the inbound HRMP channel from the `sender` if needed.
+	pub fn send_horizontal_message(&self, sender: ParaId, message: Vec<u8>) {
+		let mut state = self.state();
+		state.inbound_channels.insert(sender);
+		state.pending_horizontal_messages.push((sender, None, message));
+	}
+
+	/// Open an outbound HRMP channel to the `recipient` parachain.
+	pub fn open_outbound_channel(&self, recipient: ParaId) {
+		self.state().outbound_channels.insert(recipient);
+	}
+
+	/// Build a new relay block for the next para block and return its hash.
+	///
+	/// The number of the relay block is the number of the next para block, so that the relay
+	/// parent advances with every para block, also across restarts of the node.
+	///
+	/// The messages delivered up to `last_relay_parent_number` are dropped, as the para block they
+	/// were delivered to was imported.
+	pub fn build_relay_block(&self, params: RelayBlockParams) -> PHash {
+		let RelayBlockParams {
+			para_parent_number,
+			para_parent_head,
+			xcm_config,
+			last_relay_parent_number,
+			upgrade_go_ahead,
+		} = params;
+		let number = {
+			let mut state = self.state();
+			let best_number = state.blocks[&state.best_hash].header.number;
+
+			let processed = |sent_at: &Option<BlockNumber>| {
+				sent_at.is_some_and(|n| n <= last_relay_parent_number)
+			};
+			state.pending_downward_messages.retain(|(sent_at, _)| !processed(sent_at));
+			state.pending_horizontal_messages.retain(|(_, sent_at, _)| !processed(sent_at));
+
+			state.para_heads.push_back(para_parent_head.clone());
+			while state.para_heads.len() > self.inclusion_delay as usize + 1 {
+				state.para_heads.pop_front();
+			}
+
+			(para_parent_number + 1).max(best_number + 1)
+		};
+
+		self.import_block(
+			number,
+			para_parent_head,
+			xcm_config,
+			upgrade_go_ahead,
+			Default::default(),
+		)
+	}
+
+	fn import_block(
+		&self,
+		number: BlockNumber,
+		para_parent_head: HeadData,
+		xcm_config: MockXcmConfig,
+		upgrade_go_ahead: Option<UpgradeGoAhead>,
+		extrinsics_root: PHash,
+	) -> PHash {
+		let mut state = self.state();
+		let mut sproof_builder = cumulus_test_relay_sproof_builder::RelayStateSproofBuilder {
+			para_id: self.para_id,
+			host_config: host_config(self.inclusion_delay),
+			current_slot: Slot::from(number as u64),
+			current_epoch: (number / BLOCKS_PER_EPOCH).into(),
+			randomness: epoch_randomness(number / BLOCKS_PER_EPOCH),
+			upgrade_go_ahead,
+			included_para_head: state.para_heads.front().cloned(),
+			hrmp_ingress_channel_index: Some(Vec::new()),
+			hrmp_egress_channel_index: Some(Vec::new()),
+			..Default::default()
+		};
+		sproof_builder.relay_dispatch_queue_remaining_capacity = Some((
+			sproof_builder.host_config.max_upward_queue_count,
+			sproof_builder.host_config.max_upward_queue_size,
+		));
+
+		let mut dmq_mqc = MessageQueueChain::new(xcm_config.starting_dmq_mqc_head);
+		let downward_messages: Vec<_> = state
+			.pending_downward_messages
+			.iter_mut()
+			.map(|(sent_at, msg)| InboundDownwardMessage {
+				sent_at: *sent_at.get_or_insert(number),
+				msg: msg.clone(),
+			})
+			.inspect(|message| {
+				dmq_mqc.extend_downward(message);
+			})
+			.collect();
+		sproof_builder.dmq_mqc_head = Some(dmq_mqc.head());
+
+		let mut horizontal_messages: BTreeMap<_, Vec<_>> =
+			state.inbound_channels.iter().map(|sender| (*sender, Vec::new())).collect();
+		for (sender, sent_at, data) in state.pending_horizontal_messages.iter_mut() {
+			let message =
+				InboundHrmpMessage { sent_at: *sent_at.get_or_insert(number), data: data.clone() };
+			horizontal_messages.entry(*sender).or_default().push(message);
+		}
+		for (sender, messages) in &horizontal_messages {
+			let mut channel_mqc = MessageQueueChain::new(
+				xcm_config.starting_hrmp_mqc_heads.get(sender).copied().unwrap_or_default(),
+			);
+			for message in messages {
+				channel_mqc.extend_hrmp(message);
+			}
+			sproof_builder.upsert_inbound_channel(*sender).mqc_head = Some(channel_mqc.head());
+		}
+		for recipient in &state.outbound_channels {
+			let channel = sproof_builder.upsert_outbound_channel(*recipient);
+			channel.max_capacity = 1024;
+			channel.max_total_size = 1024 * 1024;
+			channel.max_message_size = 100 * 1024;
+		}
+
+		let (storage_root, proof) = sproof_builder.into_state_root_and_proof();
+		let parent_hash = state.hashes_by_number.values().next_back().copied().unwrap_or_default();
+		let header = PHeader {
+			parent_hash,
+			number,
+			state_root: storage_root,
+			extrinsics_root,
+			digest: Default::default(),
+		};
+		let hash = header.hash();
+
+		tracing::debug!(
+			target: crate::LOG_TARGET,
+			relay_parent = ?hash,
+			number,
+			downward_messages = downward_messages.len(),
+			horizontal_messages = horizontal_messages.values().map(Vec::len).sum::<usize>(),
+			"Emulated relay block built.",
+		);
+
+		state
+			.notification_sinks
+			.retain(|sink| sink.unbounded_send(header.clone()).is_ok());
+		state.blocks.insert(
+			hash,
+			EmulatedBlock {
+				header,
+				proof,
+				validation_data: PersistedValidationData {
+					parent_head: para_parent_head,
+					relay_parent_number: number,
+					relay_parent_storage_root: storage_root,
+					max_pov_size: MAX_POV_SIZE,
+				},
+				downward_messages,
+				horizontal_messages,
+			},
+		);
+		state.hashes_by_number.insert(number, hash);
+		state.best_hash = hash;
+
+		hash
+	}
+
+	fn state(&self) -> std::sync::MutexGuard<'_, EmulatorState> {
+		self.state.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn with_block<T>(
+		&self,
+		hash: PHash,
+		f: impl FnOnce(&EmulatedBlock) -> T,
+	) -> RelayChainResult<T> {
+		self.state()
+			.blocks
+			.get(&hash)
+			.map(f)
+			.ok_or_else(|| RelayChainError::GenericError(format!("Unknown relay block {hash:?}")))
+	}
+
+	fn notification_stream(&self) -> Pin<Box<dyn Stream<Item = PHeader> + Send>> {
+		let (sink, stream) = mpsc::unbounded();
+		self.state().notification_sinks.push(sink);
+		Box::pin(stream)
+	}
+}
+
+fn host_config(inclusion_delay: u32) -> AbridgedHostConfiguration {
+	AbridgedHostConfiguration {
+		max_code_size: 3 * 1024 * 1024,
+		max_head_data_size: 20 * 1024,
+		max_upward_queue_count: 174762,
+		max_upward_queue_size: 1024 * 1024,
+		max_upward_message_size: 65531,
+		max_upward_message_num_per_candidate: 16,
+		hrmp_max_message_num_per_candidate: 10,
+		validation_upgrade_cooldown: 20,
+		validation_upgrade_delay: 10,
+		async_backing_params: AsyncBackingParams {
+			max_candidate_depth: inclusion_delay,
+			allowed_ancestry_len: inclusion_delay,
+		},
+	}
+}
+
+fn epoch_randomness(epoch: u32) -> relay_chain::Hash {
+	let mut randomness = [0u8; 32];
+	randomness[..4].copy_from_slice(&epoch.to_be_bytes());
+	randomness.into()
+}
+
+#[async_trait]
+impl RelayChainInterface for RelayChainEmulator {
+	async fn get_storage_by_key(
+		&self,
+		relay_parent: PHash,
+		key: &[u8],
+	) -> RelayChainResult<Option<StorageValue>> {
+		let (root, proof) =
+			self.with_block(relay_parent, |block| (block.header.state_root, block.proof.clone()))?;
+		let mut values = sp_state_machine::read_proof_check::<BlakeTwo256, _>(root, proof, [key])
+			.map_err(|e| RelayChainError::GenericError(e.to_string()))?;
+
+		Ok(values.remove(key).flatten())
+	}
+
+	async fn validators(&self, _: PHash) -> RelayChainResult<Vec<ValidatorId>> {
+		Ok(Vec::new())
+	}
+
+	async fn best_block_hash(&self) -> RelayChainResult<PHash> {
+		Ok(self.state().best_hash)
+	}
+
+	async fn header(&self, block_id: BlockId) -> RelayChainResult<Option<PHeader>> {
+		let state = self.state();
+		let hash = match block_id {
+			BlockId::Hash(hash) => Some(hash),
+			BlockId::Number(number) => state.hashes_by_number.get(&number).copied(),
+		};
+
+		Ok(hash.and_then(|hash| state.blocks.get(&hash)).map(|block| block.header.clone()))
+	}
+
+	async fn finalized_block_hash(&self) -> RelayChainResult<PHash> {
+		Ok(self.state().best_hash)
+	}
+
+	async fn call_runtime_api(
+		&self,
+		method_name: &'static str,
+		_: PHash,
+		_: &[u8],
+	) -> RelayChainResult<Vec<u8>> {
+		Err(RelayChainError::GenericError(format!(
+			"The emulated relay chain has no runtime to call `{method_name}`"
+		)))
+	}
+
+	async fn retrieve_dmq_contents(
+		&self,
+		para_id: ParaId,
+		relay_parent: PHash,
+	) -> RelayChainResult<Vec<InboundDownwardMessage>> {
+		self.with_block(relay_parent, |block| {
+			if para_id == self.para_id {
+				block.downward_messages.clone()
+			} else {
+				Vec::new()
+			}
+		})
+	}
+
+	async fn retrieve_all_inbound_hrmp_channel_contents(
+		&self,
+		para_id: ParaId,
+		relay_parent: PHash,
+	) -> RelayChainResult<BTreeMap<ParaId, Vec<InboundHrmpMessage>>> {
+		self.with_block(relay_parent, |block| {
+			if para_id == self.para_id {
+				block.horizontal_messages.clone()
+			} else {
+				BTreeMap::new()
+			}
+		})
+	}
+
+	async fn persisted_validation_data(
+		&self,
+		block_id: PHash,
+		para_id: ParaId,
+		_: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<PersistedValidationData>> {
+		self.with_block(block_id, |block| {
+			(para_id == self.para_id).then(|| block.validation_data.clone())
+		})
+	}
+
+	async fn candidate_pending_availability(
+		&self,
+		_: PHash,
+		_: ParaId,
+	) -> RelayChainResult<Option<CommittedCandidateReceipt>> {
+		Ok(None)
+	}
+
+	async fn session_index_for_child(&self, _: PHash) -> RelayChainResult<SessionIndex> {
+		Ok(0)
+	}
+
+	async fn import_notification_stream(
+		&self,
+	) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+		Ok(self.notification_stream())
+	}
+
+	async fn new_best_notification_stream(
+		&self,
+	) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+		Ok(self.notification_stream())
+	}
+
+	async fn wait_for_block(&self, hash: PHash) -> RelayChainResult<()> {
+		self.with_block(hash, |_| ())
+	}
+
+	async fn finality_notification_stream(
+		&self,
+	) -> RelayChainResult<Pin<Box<dyn Stream<Item = PHeader> + Send>>> {
+		Ok(self.notification_stream())
+	}
+
+	async fn is_major_syncing(&self) -> RelayChainResult<bool> {
+		Ok(false)
+	}
+
+	fn overseer_handle(&self) -> RelayChainResult<OverseerHandle> {
+		Err(RelayChainError::GenericError("The emulated relay chain has no overseer".into()))
+	}
+
+	async fn prove_read(
+		&self,
+		relay_parent: PHash,
+		_: &Vec<Vec<u8>>,
+	) -> RelayChainResult<StorageProof> {
+		// The proof of an emulated block only contains the state relevant for the parachain.
+		self.with_block(relay_parent, |block| block.proof.clone())
+	}
+
+	async fn validation_code_hash(
+		&self,
+		_: PHash,
+		_: ParaId,
+		_: OccupiedCoreAssumption,
+	) -> RelayChainResult<Option<ValidationCodeHash>> {
+		Ok(None)
+	}
+
+	async fn candidates_pending_availability(
+		&self,
+		_: PHash,
+		_: ParaId,
+	) -> RelayChainResult<Vec<CommittedCandidateReceipt>> {
+		Ok(Vec::new())
+	}
+
+	async fn version(&self, _: PHash) -> RelayChainResult<RuntimeVersion> {
+		Err(RelayChainError::GenericError("The emulated relay chain has no runtime".into()))
+	}
+
+	async fn availability_cores(
+		&self,
+		_: PHash,
+	) -> RelayChainResult<Vec<CoreState<PHash, BlockNumber>>> {
+		Ok(Vec::new())
+	}
+
+	async fn claim_queue(
+		&self,
+		_: PHash,
+	) -> RelayChainResult<BTreeMap<CoreIndex, VecDeque<ParaId>>> {
+		let claims = vec![self.para_id; SCHEDULING_LOOKAHEAD as usize];
+		Ok(BTreeMap::from([(CoreIndex(0), claims.into())]))
+	}
+
+	async fn scheduling_lookahead(&self, _: PHash) -> RelayChainResult<u32> {
+		Ok(SCHEDULING_LOOKAHEAD)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ParachainInherentDataProvider;
+	use futures::executor::block_on;
+
+	const PARA_ID: ParaId = ParaId::new(2000);
+	const SIBLING: ParaId = ParaId::new(2001);
+
+	// Build a relay block for a para block on top of para block `number`, which was built on
+	// relay block `last_relay_parent_number`.
+	fn build_on(
+		emulator: &RelayChainEmulator,
+		number: u32,
+		last_relay_parent_number: u32,
+	) -> PHash {
+		emulator.build_relay_block(RelayBlockParams {
+			para_parent_number: number,
+			para_parent_head: HeadData(number.to_le_bytes().to_vec()),
+			xcm_config: Default::default(),
+			last_relay_parent_number,
+			upgrade_go_ahead: None,
+		})
+	}
+
+	fn build(emulator: &RelayChainEmulator, number: u32) -> PHash {
+		build_on(emulator, number, number)
+	}
+
+	fn delivered(
+		emulator: &RelayChainEmulator,
+		relay_parent: PHash,
+	) -> (Vec<InboundDownwardMessage>, Vec<InboundHrmpMessage>) {
+		let downward = block_on(emulator.retrieve_dmq_contents(PARA_ID, relay_parent)).unwrap();
+		let horizontal =
+			block_on(emulator.retrieve_all_inbound_hrmp_channel_contents(PARA_ID, relay_parent))
+				.unwrap()
+				.remove(&SIBLING)
+				.unwrap_or_default();
+		(downward, horizontal)
+	}
+
+	#[test]
+	fn messages_are_delivered_in_the_next_relay_block() {
+		let emulator = RelayChainEmulator::new(PARA_ID, 0);
+		emulator.send_downward_message(vec![1]);
+		emulator.send_horizontal_message(SIBLING, vec![2]);
+
+		let relay_parent = build(&emulator, 0);
+		let validation_data = block_on(emulator.persisted_validation_data(
+			relay_parent,
+			PARA_ID,
+			OccupiedCoreAssumption::TimedOut,
+		))
+		.unwrap()
+		.unwrap();
+		assert_eq!(validation_data.relay_parent_number, 1);
+
+		let inherent = block_on(ParachainInherentDataProvider::create_at(
+			relay_parent,
+			&emulator,
+			&validation_data,
+			PARA_ID,
+		))
+		.unwrap();
+		assert_eq!(
+			inherent.downward_messages,
+			vec![InboundDownwardMessage { sent_at: 1, msg: vec![1] }]
+		);
+		assert_eq!(
+			inherent.horizontal_messages,
+			BTreeMap::from([(SIBLING, vec![InboundHrmpMessage { sent_at: 1, data: vec![2] }])])
+		);
+
+		// The para block was imported, the channel stays open, but the messages are not delivered
+		// again.
+		let relay_parent = build(&emulator, 1);
+		assert!(block_on(emulator.retrieve_dmq_contents(PARA_ID, relay_parent))
+			.unwrap()
+			.is_empty());
+		assert_eq!(
+			block_on(emulator.retrieve_all_inbound_hrmp_channel_contents(PARA_ID, relay_parent))
+				.unwrap(),
+			BTreeMap::from([(SIBLING, Vec::new())])
+		);
+	}
+
+	#[test]
+	fn messages_stay_pending_until_processed() {
+		let emulator = RelayChainEmulator::new(PARA_ID, 0);
+		emulator.send_downward_message(vec![1]);
+		emulator.send_horizontal_message(SIBLING, vec![2]);
+		let expected = (
+			vec![InboundDownwardMessage { sent_at: 1, msg: vec![1] }],
+			vec![InboundHrmpMessage { sent_at: 1, data: vec![2] }],
+		);
+
+		let relay_parent = build_on(&emulator, 0, 0);
+		assert_eq!(delivered(&emulator, relay_parent), expected);
+
+		// The para block built on the first relay block was discarded, the messages are delivered
+		// again, along with the new ones.
+		emulator.send_downward_message(vec![3]);
+		let relay_parent = build_on(&emulator, 0, 0);
+		let mut expected_with_new = expected.clone();
+		expected_with_new.0.push(InboundDownwardMessage { sent_at: 2, msg: vec![3] });
+		assert_eq!(delivered(&emulator, relay_parent), expected_with_new);
+
+		// The para block built on the second relay block was imported.
+		let relay_parent = build_on(&emulator, 1, 2);
+		assert_eq!(delivered(&emulator, relay_parent), (Vec::new(), Vec::new()));
+	}
+
+	#[test]
+	fn para_blocks_are_included_after_the_delay() {
+		let emulator = RelayChainEmulator::new(PARA_ID, 2);
+		let included_head = |relay_parent| {
+			block_on(emulator.get_storage_by_key(
+				relay_parent,
+				&relay_chain::well_known_keys::para_head(PARA_ID),
+			))
+			.unwrap()
+			.map(|raw| <HeadData as codec::Decode>::decode(&mut &raw[..]).unwrap())
+		};
+
+		let heads: Vec<_> = (0..4).map(|number| included_head(build(&emulator, number))).collect();
+		assert_eq!(
+			heads,
+			[0, 0, 0, 1].map(|number: u32| Some(HeadData(number.to_le_bytes().to_vec())))
+		);
+	}
+}
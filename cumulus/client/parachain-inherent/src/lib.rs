@@ -23,10 +23,12 @@ use cumulus_primitives_core::{
 };
 use cumulus_relay_chain_interface::RelayChainInterface;
 
+mod emulator;
 mod mock;
 
 pub use cumulus_primitives_parachain_inherent::{ParachainInherentData, INHERENT_IDENTIFIER};
-pub use mock::{MockValidationDataInherentDataProvider, MockXcmConfig};
+pub use emulator::{last_relay_chain_block_number, RelayBlockParams, RelayChainEmulator};
+pub use mock::{MockValidationDataInherentDataProvider, MockXcmConfig, ParachainSystemName};
 
 const LOG_TARGET: &str = "parachain-inherent";
 
@@ -15,16 +15,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Cumulus. If not, see <https://www.gnu.org/licenses/>.
 
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 use polkadot_node_primitives::PoV;
 use polkadot_node_subsystem::messages::AvailabilityRecoveryMessage;
+use polkadot_primitives::vstaging::CandidateReceiptV2 as CandidateReceipt;
 
 use futures::{channel::oneshot, stream::FuturesUnordered, Future, FutureExt, StreamExt};
 
 use std::{pin::Pin, sync::Arc};
 
-use crate::RecoveryHandle;
+use crate::{PoVSource, RecoveryHandle};
 
 /// The active candidate recovery.
 ///
@@ -34,11 +35,18 @@ pub(crate) struct ActiveCandidateRecovery<Block: BlockT> {
 	recoveries:
 		FuturesUnordered<Pin<Box<dyn Future<Output = (Block::Hash, Option<Arc<PoV>>)> + Send>>>,
 	recovery_handle: Box<dyn RecoveryHandle>,
+	/// The sources to fetch PoVs from if the availability recovery fails.
+	pov_sources: Arc<[Arc<dyn PoVSource<Block>>]>,
 }
 
 impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 	pub fn new(recovery_handle: Box<dyn RecoveryHandle>) -> Self {
-		Self { recoveries: Default::default(), recovery_handle }
+		Self { recoveries: Default::default(), recovery_handle, pov_sources: Arc::new([]) }
+	}
+
+	/// Set the sources to fetch PoVs from if the availability recovery fails.
+	pub fn set_pov_sources(&mut self, pov_sources: Vec<Arc<dyn PoVSource<Block>>>) {
+		self.pov_sources = pov_sources.into();
 	}
 
 	/// Recover the given `candidate`.
@@ -62,10 +70,13 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 			)
 			.await;
 
+		let pov_sources = self.pov_sources.clone();
+		let block_number = candidate.block_number;
+		let receipt = candidate.receipt.clone();
 		self.recoveries.push(
 			async move {
 				match rx.await {
-					Ok(Ok(res)) => (block_hash, Some(res.pov)),
+					Ok(Ok(res)) => return (block_hash, Some(res.pov)),
 					Ok(Err(error)) => {
 						tracing::debug!(
 							target: crate::LOG_TARGET,
@@ -73,16 +84,18 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 							?block_hash,
 							"Availability recovery failed",
 						);
-						(block_hash, None)
 					},
 					Err(_) => {
 						tracing::debug!(
 							target: crate::LOG_TARGET,
 							"Availability recovery oneshot channel closed",
 						);
-						(block_hash, None)
 					},
 				}
+
+				let pov =
+					fetch_from_sources(&pov_sources, block_hash, block_number, &receipt).await;
+				(block_hash, pov)
 			}
 			.boxed(),
 		);
@@ -101,3 +114,34 @@ impl<Block: BlockT> ActiveCandidateRecovery<Block> {
 		}
 	}
 }
+
+/// Fetch the PoV of the candidate from the first source that has a PoV matching the candidate.
+async fn fetch_from_sources<Block: BlockT>(
+	pov_sources: &[Arc<dyn PoVSource<Block>>],
+	block_hash: Block::Hash,
+	block_number: NumberFor<Block>,
+	receipt: &CandidateReceipt,
+) -> Option<Arc<PoV>> {
+	for source in pov_sources {
+		let Some(pov) = source.fetch_pov(block_hash, block_number, receipt).await else { continue };
+
+		if pov.hash() == receipt.descriptor.pov_hash() {
+			tracing::debug!(
+				target: crate::LOG_TARGET,
+				?block_hash,
+				source = source.name(),
+				"Fetched PoV from fallback source",
+			);
+			return Some(Arc::new(pov))
+		}
+
+		tracing::warn!(
+			target: crate::LOG_TARGET,
+			?block_hash,
+			source = source.name(),
+			"PoV from fallback source doesn't match the candidate",
+		);
+	}
+
+	None
+}
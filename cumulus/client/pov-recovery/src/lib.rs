@@ -44,6 +44,9 @@
 //! 4b. Since we are trying to recover pending candidates, availability is not guaranteed. If the
 //! block     PoV is not yet available, we retry.
 //!
+//! 4c. If the PoV can not be recovered from the relay chain, e.g. because the availability data was
+//! already pruned, we try to fetch it from the configured [`PoVSource`]s.
+//!
 //! If we need to recover multiple PoV blocks (which should hopefully not happen in real life), we
 //! make sure that the blocks are imported in the correct order.
 
@@ -87,6 +90,9 @@ mod tests;
 mod active_candidate_recovery;
 use active_candidate_recovery::ActiveCandidateRecovery;
 
+mod pov_source;
+pub use pov_source::{PoVDirectory, PoVSource};
+
 const LOG_TARGET: &str = "cumulus-pov-recovery";
 
 /// Test-friendly wrapper trait for the overseer handle.
@@ -272,6 +278,13 @@ where
 		}
 	}
 
+	/// Fetch PoVs from the given `pov_sources`, in order, when they can not be recovered from the
+	/// relay chain.
+	pub fn with_pov_sources(mut self, pov_sources: Vec<Arc<dyn PoVSource<Block>>>) -> Self {
+		self.active_candidate_recovery.set_pov_sources(pov_sources);
+		self
+	}
+
 	/// Handle a new pending candidate.
 	fn handle_pending_candidate(
 		&mut self,
@@ -0,0 +1,102 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus. If not, see <https://www.gnu.org/licenses/>.
+
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+use polkadot_node_primitives::PoV;
+use polkadot_primitives::vstaging::CandidateReceiptV2 as CandidateReceipt;
+
+use codec::Decode;
+
+use std::{io::ErrorKind, path::PathBuf};
+
+/// A source of PoVs besides the relay chain validators.
+///
+/// The PoV recovery falls back to these sources when a PoV can not be recovered from the relay
+/// chain, e.g. because the availability data was already pruned. The PoVs returned by a source
+/// are checked against the candidate receipt, so sources don't need to be trusted.
+#[async_trait::async_trait]
+pub trait PoVSource<Block: BlockT>: Send + Sync {
+	/// A short name of the source, used in logs.
+	fn name(&self) -> &'static str;
+
+	/// Fetch the PoV of the `candidate` whose last block is `block_hash`.
+	///
+	/// Returns `None` if the source doesn't have the PoV.
+	async fn fetch_pov(
+		&self,
+		block_hash: Block::Hash,
+		block_number: NumberFor<Block>,
+		candidate: &CandidateReceipt,
+	) -> Option<PoV>;
+}
+
+/// Reads PoVs from a directory filled by the `--export-pov-to-path` option of a collator.
+///
+/// The directory holds one `<block_hash>_<block_number>.pov` file per PoV, containing the SCALE
+/// encoded PoV followed by the persisted validation data of the candidate.
+pub struct PoVDirectory {
+	path: PathBuf,
+}
+
+impl PoVDirectory {
+	/// Create a source reading PoVs from the directory at `path`.
+	pub fn new(path: impl Into<PathBuf>) -> Self {
+		Self { path: path.into() }
+	}
+}
+
+#[async_trait::async_trait]
+impl<Block: BlockT> PoVSource<Block> for PoVDirectory {
+	fn name(&self) -> &'static str {
+		"pov-directory"
+	}
+
+	async fn fetch_pov(
+		&self,
+		block_hash: Block::Hash,
+		block_number: NumberFor<Block>,
+		_: &CandidateReceipt,
+	) -> Option<PoV> {
+		let path = self.path.join(format!("{block_hash:?}_{block_number}.pov"));
+		let data = match std::fs::read(&path) {
+			Ok(data) => data,
+			Err(error) if error.kind() == ErrorKind::NotFound => return None,
+			Err(error) => {
+				tracing::debug!(
+					target: crate::LOG_TARGET,
+					%error,
+					path = %path.display(),
+					"Failed to read exported PoV",
+				);
+				return None
+			},
+		};
+
+		// Only decode the PoV, the persisted validation data that follows is not needed.
+		PoV::decode(&mut &data[..])
+			.inspect_err(|error| {
+				tracing::debug!(
+					target: crate::LOG_TARGET,
+					?error,
+					path = %path.display(),
+					"Failed to decode exported PoV",
+				);
+			})
+			.ok()
+	}
+}
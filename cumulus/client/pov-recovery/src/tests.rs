@@ -525,6 +525,7 @@ fn make_candidate_chain(candidate_number_range: Range<u32>) -> Vec<CommittedCand
 		};
 
 		latest_parent_hash = head_data.hash();
+		let pov_hash = make_pov(&head_data).hash();
 
 		candidates.push(CommittedCandidateReceipt {
 			descriptor: CandidateDescriptor {
@@ -532,7 +533,7 @@ fn make_candidate_chain(candidate_number_range: Range<u32>) -> Vec<CommittedCand
 				relay_parent: PHash::zero(),
 				collator: collator.public().into(),
 				persisted_validation_data_hash: PHash::zero(),
-				pov_hash: PHash::zero(),
+				pov_hash,
 				erasure_root: PHash::zero(),
 				signature: collator.sign(&[0u8; 132]).into(),
 				para_head: PHash::zero(),
@@ -553,6 +554,14 @@ fn make_candidate_chain(candidate_number_range: Range<u32>) -> Vec<CommittedCand
 	candidates
 }
 
+fn make_pov(header: &Header) -> PoV {
+	let block_data = ParachainBlockData::<Block>::new(
+		vec![Block::new(header.clone(), vec![])],
+		CompactProof { encoded_nodes: vec![] },
+	);
+	PoV { block_data: block_data.encode().into() }
+}
+
 fn dummy_usage_info(finalized_number: u32) -> ClientInfo<Block> {
 	ClientInfo {
 		chain: Info {
@@ -915,6 +924,79 @@ async fn single_pending_candidate_recovery_retry_fails() {
 	assert_matches!(recovery_subsystem_rx.next().timeout(Duration::from_millis(100)).await, None);
 }
 
+#[tokio::test]
+async fn single_pending_candidate_recovered_from_pov_source() {
+	sp_tracing::init_for_tests();
+
+	let (recovery_subsystem_tx, mut recovery_subsystem_rx) =
+		AvailabilityRecoverySubsystemHandle::new();
+	let recovery_delay_range =
+		RecoveryDelayRange { min: Duration::from_millis(0), max: Duration::from_millis(10) };
+	let (_explicit_recovery_chan_tx, explicit_recovery_chan_rx) = mpsc::channel(10);
+	let candidates = make_candidate_chain(1..2);
+	let header = Header::decode(&mut &candidates[0].commitments.head_data.0[..]).unwrap();
+	let candidate_hash = candidates[0].hash();
+
+	// Export the PoV like a collator with `--export-pov-to-path` does.
+	let pov_dir = tempfile::tempdir().unwrap();
+	let mut exported_pov = make_pov(&header).encode();
+	dummy_pvd().encode_to(&mut exported_pov);
+	std::fs::write(pov_dir.path().join(format!("{:?}_1.pov", header.hash())), exported_pov)
+		.unwrap();
+
+	let relay_chain_client = Relaychain::new(vec![(
+		PHeader {
+			parent_hash: PHash::from_low_u64_be(0),
+			number: 1,
+			state_root: PHash::random(),
+			extrinsics_root: PHash::random(),
+			digest: Default::default(),
+		},
+		candidates,
+	)]);
+	let mut known_blocks = HashMap::new();
+	known_blocks.insert(GENESIS_HASH, BlockStatus::InChainWithState);
+	let (parachain_client, _import_notifications_tx, _finality_notifications_tx) =
+		ParachainClient::new(vec![dummy_usage_info(0)], Arc::new(Mutex::new(known_blocks)));
+	let (parachain_import_queue, mut import_requests_rx) = ParachainImportQueue::new();
+
+	let pov_recovery = PoVRecovery::<Block, _, _>::new(
+		Box::new(recovery_subsystem_tx),
+		recovery_delay_range,
+		Arc::new(parachain_client),
+		Box::new(parachain_import_queue),
+		relay_chain_client,
+		ParaId::new(1000),
+		explicit_recovery_chan_rx,
+		Arc::new(DummySyncOracle::default()),
+	)
+	.with_pov_sources(vec![Arc::new(PoVDirectory::new(pov_dir.path()))]);
+
+	task::spawn(pov_recovery.run());
+
+	// The availability data was pruned.
+	assert_matches!(
+		recovery_subsystem_rx.next().await,
+		Some(AvailabilityRecoveryMessage::RecoverAvailableData(
+			receipt,
+			_,
+			None,
+			None,
+			response_tx
+		)) => {
+			assert_eq!(receipt.hash(), candidate_hash);
+			response_tx.send(Err(RecoveryError::Unavailable)).unwrap()
+		}
+	);
+
+	// The candidate is imported from the exported PoV, without retrying.
+	assert_matches!(import_requests_rx.next().await, Some(incoming_blocks) => {
+		assert_eq!(incoming_blocks.len(), 1);
+		assert_eq!(incoming_blocks[0].header, Some(header));
+	});
+	assert_matches!(recovery_subsystem_rx.next().timeout(Duration::from_millis(100)).await, None);
+}
+
 #[tokio::test]
 async fn single_pending_candidate_recovery_irrecoverable_error() {
 	sp_tracing::init_for_tests();
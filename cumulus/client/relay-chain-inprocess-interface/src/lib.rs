@@ -42,10 +42,11 @@ use sc_client_api::{
 	blockchain::BlockStatus, Backend, BlockchainEvents, HeaderBackend, ImportNotifications,
 	StorageProof,
 };
+use sc_service::RpcHandlers;
 use sc_telemetry::TelemetryWorkerHandle;
 use sp_api::{CallApiAt, CallApiAtParams, CallContext, ProvideRuntimeApi};
 use sp_consensus::SyncOracle;
-use sp_core::Pair;
+use sp_core::{Bytes, Pair};
 use sp_state_machine::{Backend as StateBackend, StorageValue};
 
 /// The timeout in seconds after that the waiting for a block should be aborted.
@@ -59,6 +60,7 @@ pub struct RelayChainInProcessInterface {
 	backend: Arc<FullBackend>,
 	sync_oracle: Arc<dyn SyncOracle + Send + Sync>,
 	overseer_handle: Handle,
+	rpc_handlers: Option<RpcHandlers>,
 }
 
 impl RelayChainInProcessInterface {
@@ -69,7 +71,13 @@ impl RelayChainInProcessInterface {
 		sync_oracle: Arc<dyn SyncOracle + Send + Sync>,
 		overseer_handle: Handle,
 	) -> Self {
-		Self { full_client, backend, sync_oracle, overseer_handle }
+		Self { full_client, backend, sync_oracle, overseer_handle, rpc_handlers: None }
+	}
+
+	/// Submit extrinsics through the RPC handlers of the relay chain node.
+	pub fn with_rpc_handlers(mut self, rpc_handlers: RpcHandlers) -> Self {
+		self.rpc_handlers = Some(rpc_handlers);
+		self
 	}
 }
 
@@ -321,6 +329,18 @@ impl RelayChainInterface for RelayChainInProcessInterface {
 	async fn scheduling_lookahead(&self, hash: PHash) -> RelayChainResult<u32> {
 		Ok(self.full_client.runtime_api().scheduling_lookahead(hash)?)
 	}
+
+	async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> RelayChainResult<PHash> {
+		let rpc_handlers = self.rpc_handlers.as_ref().ok_or_else(|| {
+			RelayChainError::GenericError("The relay chain node has no RPC handlers".into())
+		})?;
+
+		rpc_handlers
+			.handle()
+			.call("author_submitExtrinsic", [Bytes(extrinsic)])
+			.await
+			.map_err(|e| RelayChainError::RpcCallError(e.to_string()))
+	}
 }
 
 pub enum BlockCheckStatus {
@@ -383,9 +403,12 @@ fn build_polkadot_full_node(
 			hwbench,
 			execute_workers_max_num: None,
 			prepare_workers_hard_max_num: None,
+			pvf_artifacts_cache_size_limit: None,
 			prepare_workers_soft_max_num: None,
 			enable_approval_voting_parallel: false,
 			keep_finalized_for: None,
+			overseer_recording_path: None,
+			adaptive_availability_recovery: false,
 		},
 	)?;
 
@@ -413,14 +436,17 @@ pub fn build_inprocess_relay_chain(
 	)
 	.map_err(|e| RelayChainError::Application(Box::new(e) as Box<_>))?;
 
-	let relay_chain_interface = Arc::new(RelayChainInProcessInterface::new(
-		full_node.client,
-		full_node.backend,
-		full_node.sync_service,
-		full_node.overseer_handle.clone().ok_or(RelayChainError::GenericError(
-			"Overseer not running in full node.".to_string(),
-		))?,
-	));
+	let relay_chain_interface = Arc::new(
+		RelayChainInProcessInterface::new(
+			full_node.client,
+			full_node.backend,
+			full_node.sync_service,
+			full_node.overseer_handle.clone().ok_or(RelayChainError::GenericError(
+				"Overseer not running in full node.".to_string(),
+			))?,
+		)
+		.with_rpc_handlers(full_node.rpc_handlers),
+	);
 
 	task_manager.add_child(full_node.task_manager);
 
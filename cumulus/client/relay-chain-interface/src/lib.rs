@@ -248,6 +248,17 @@ pub trait RelayChainInterface: Send + Sync {
 
 	/// Fetch the scheduling lookahead value.
 	async fn scheduling_lookahead(&self, relay_parent: PHash) -> RelayChainResult<u32>;
+
+	/// Submit a signed extrinsic to the transaction pool of the relay chain.
+	///
+	/// Returns the hash of the extrinsic. Interfaces without access to a transaction pool return
+	/// an error.
+	async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> RelayChainResult<PHash> {
+		let _ = extrinsic;
+		Err(RelayChainError::GenericError(
+			"Submitting extrinsics is not supported by this relay chain interface".into(),
+		))
+	}
 }
 
 #[async_trait]
@@ -406,6 +417,10 @@ where
 	async fn scheduling_lookahead(&self, relay_parent: PHash) -> RelayChainResult<u32> {
 		(**self).scheduling_lookahead(relay_parent).await
 	}
+
+	async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> RelayChainResult<PHash> {
+		(**self).submit_extrinsic(extrinsic).await
+	}
 }
 
 /// Helper function to call an arbitrary runtime API using a `RelayChainInterface` client.
@@ -263,6 +263,7 @@ async fn new_minimal_relay_chain<Block: BlockT, Network: NetworkBackend<RelayBlo
 		req_protocol_names: request_protocol_names,
 		peerset_protocol_names,
 		notification_services,
+		adaptive_availability_recovery: false,
 	};
 
 	let overseer_handle =
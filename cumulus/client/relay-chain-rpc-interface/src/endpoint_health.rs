@@ -0,0 +1,263 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// Cumulus is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Cumulus is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Cumulus. If not, see <https://www.gnu.org/licenses/>.
+
+//! Health scoring of the relay chain RPC endpoints.
+//!
+//! Every endpoint is probed periodically for its best and finalized block and the latency of its
+//! responses. Endpoints also report the hash of a finalized block, which is cross-checked between
+//! them: finalized blocks can not differ between honest endpoints, so an endpoint disagreeing with
+//! the majority serves wrong data or is stuck on a fork.
+
+use cumulus_primitives_core::relay_chain::{BlockNumber as RelayNumber, Hash as RelayHash};
+use std::{collections::HashMap, time::Duration};
+
+const LOG_TARGET: &str = "reconnecting-websocket-client";
+
+/// Number of consecutive failed probes after which an endpoint is unhealthy.
+const MAX_FAILED_PROBES: u32 = 2;
+/// Number of blocks the best block of a healthy endpoint may lag behind the highest one.
+const MAX_BEST_LAG: RelayNumber = 3;
+/// Number of blocks the finalized block of a healthy endpoint may lag behind the highest one.
+const MAX_FINALIZED_LAG: RelayNumber = 10;
+/// Penalty, in milliseconds of latency, for each block an endpoint lags behind.
+const LAG_PENALTY_MS: u64 = 500;
+/// How much better, in milliseconds of latency, another endpoint must score before the worker
+/// switches to it away from a healthy active endpoint.
+const SWITCH_THRESHOLD_MS: u64 = 1000;
+
+/// The state of an endpoint observed by a probe.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Probe {
+	/// The number of the best block.
+	pub best_number: RelayNumber,
+	/// The number of the finalized block.
+	pub finalized_number: RelayNumber,
+	/// The time it took to fetch the best block.
+	pub latency: Duration,
+	/// The hash of the block at [`HealthTracker::check_number`], if the endpoint knows it.
+	pub check_hash: Option<RelayHash>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct EndpointHealth {
+	best_number: RelayNumber,
+	finalized_number: RelayNumber,
+	/// Moving average of the latency, `None` until the endpoint answered a probe.
+	latency: Option<Duration>,
+	failed_probes: u32,
+	diverged: bool,
+}
+
+impl EndpointHealth {
+	fn is_responsive(&self) -> bool {
+		self.latency.is_some() && self.failed_probes < MAX_FAILED_PROBES && !self.diverged
+	}
+}
+
+/// Tracks the health of all endpoints, identified by their index in the list of URLs.
+#[derive(Debug)]
+pub(crate) struct HealthTracker {
+	endpoints: Vec<EndpointHealth>,
+	check_number: Option<RelayNumber>,
+}
+
+impl HealthTracker {
+	pub fn new(num_endpoints: usize) -> Self {
+		Self { endpoints: vec![Default::default(); num_endpoints], check_number: None }
+	}
+
+	/// The number of the block whose hash the next probes should cross-check.
+	pub fn check_number(&self) -> Option<RelayNumber> {
+		self.check_number
+	}
+
+	/// Record the result of a probe round, with one entry per endpoint.
+	///
+	/// `None` means that the probe of the endpoint failed.
+	pub fn note_probes(&mut self, probes: Vec<Option<Probe>>) {
+		for (index, (health, probe)) in self.endpoints.iter_mut().zip(&probes).enumerate() {
+			let Some(probe) = probe else {
+				health.failed_probes = health.failed_probes.saturating_add(1);
+				continue
+			};
+
+			health.best_number = probe.best_number;
+			health.finalized_number = probe.finalized_number;
+			health.failed_probes = 0;
+			health.latency = Some(match health.latency {
+				Some(latency) => (latency * 3 + probe.latency) / 4,
+				None => probe.latency,
+			});
+			tracing::trace!(target: LOG_TARGET, index, ?probe, "Probed RPC endpoint.");
+		}
+
+		self.cross_check(&probes);
+		self.check_number = self
+			.healthy_endpoints()
+			.into_iter()
+			.map(|index| self.endpoints[index].finalized_number)
+			.min();
+	}
+
+	/// Mark the endpoints disagreeing with the majority on the hash at the check number.
+	fn cross_check(&mut self, probes: &[Option<Probe>]) {
+		let Some(check_number) = self.check_number else { return };
+
+		let mut votes = HashMap::<RelayHash, usize>::new();
+		for hash in probes.iter().flatten().filter_map(|probe| probe.check_hash) {
+			*votes.entry(hash).or_default() += 1;
+		}
+
+		let Some(max_votes) = votes.values().max().copied() else { return };
+		let mut leaders = votes.iter().filter(|(_, count)| **count == max_votes);
+		let (Some((majority, _)), None) = (leaders.next(), leaders.next()) else {
+			tracing::warn!(
+				target: LOG_TARGET,
+				check_number,
+				"Relay chain RPC endpoints disagree on a finalized block without a majority."
+			);
+			return
+		};
+
+		for (index, probe) in probes.iter().enumerate() {
+			let Some(hash) = probe.as_ref().and_then(|probe| probe.check_hash) else { continue };
+			let diverged = hash != *majority;
+			if diverged && !self.endpoints[index].diverged {
+				tracing::warn!(
+					target: LOG_TARGET,
+					index,
+					check_number,
+					?hash,
+					expected = ?majority,
+					"Relay chain RPC endpoint disagrees with the other endpoints on a finalized block."
+				);
+			}
+			self.endpoints[index].diverged = diverged;
+		}
+	}
+
+	/// The score of the endpoint at `index`, lower is better.
+	///
+	/// Returns `None` if the endpoint is unhealthy.
+	fn score(&self, index: usize) -> Option<u64> {
+		let responsive = self.endpoints.iter().filter(|health| health.is_responsive());
+		let highest_best = responsive.clone().map(|health| health.best_number).max()?;
+		let highest_finalized = responsive.map(|health| health.finalized_number).max()?;
+
+		let health = self.endpoints.get(index).filter(|health| health.is_responsive())?;
+		let best_lag = highest_best.saturating_sub(health.best_number);
+		let finalized_lag = highest_finalized.saturating_sub(health.finalized_number);
+		if best_lag > MAX_BEST_LAG || finalized_lag > MAX_FINALIZED_LAG {
+			return None
+		}
+
+		let latency: u64 = health.latency?.as_millis().try_into().unwrap_or(u64::MAX);
+		Some(latency.saturating_add(u64::from(best_lag) * LAG_PENALTY_MS))
+	}
+
+	/// The indices of the healthy endpoints, the healthiest first.
+	pub fn healthy_endpoints(&self) -> Vec<usize> {
+		let mut scored = (0..self.endpoints.len())
+			.filter_map(|index| Some((self.score(index)?, index)))
+			.collect::<Vec<_>>();
+		scored.sort();
+		scored.into_iter().map(|(_, index)| index).collect()
+	}
+
+	/// The endpoint requests should be routed to instead of the `active` one, if any.
+	///
+	/// The worker only switches away from a healthy endpoint if another one is significantly
+	/// healthier, to avoid flapping between endpoints of similar health.
+	pub fn better_endpoint(&self, active: usize) -> Option<usize> {
+		let healthiest = *self.healthy_endpoints().first()?;
+		if healthiest == active {
+			return None
+		}
+
+		match (self.score(active), self.score(healthiest)) {
+			(Some(active_score), Some(score)) if active_score < score + SWITCH_THRESHOLD_MS => None,
+			_ => Some(healthiest),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn probe(best_number: RelayNumber, latency_ms: u64, check_hash: Option<u8>) -> Option<Probe> {
+		Some(Probe {
+			best_number,
+			finalized_number: best_number.saturating_sub(2),
+			latency: Duration::from_millis(latency_ms),
+			check_hash: check_hash.map(RelayHash::repeat_byte),
+		})
+	}
+
+	#[test]
+	fn lagging_and_failing_endpoints_are_unhealthy() {
+		let mut tracker = HealthTracker::new(3);
+		assert!(tracker.healthy_endpoints().is_empty());
+
+		tracker.note_probes(vec![probe(100, 50, None), probe(90, 10, None), probe(100, 20, None)]);
+		assert_eq!(tracker.healthy_endpoints(), vec![2, 0]);
+		assert_eq!(tracker.check_number(), Some(98));
+
+		// A single failed probe is tolerated, two in a row are not.
+		tracker.note_probes(vec![probe(101, 50, None), probe(101, 10, None), None]);
+		assert_eq!(tracker.healthy_endpoints(), vec![1, 0, 2]);
+		tracker.note_probes(vec![probe(102, 50, None), probe(102, 10, None), None]);
+		assert_eq!(tracker.healthy_endpoints(), vec![1, 0]);
+	}
+
+	#[test]
+	fn endpoint_disagreeing_with_majority_is_unhealthy() {
+		let mut tracker = HealthTracker::new(3);
+		tracker.note_probes(vec![probe(100, 50, None), probe(100, 10, None), probe(100, 20, None)]);
+
+		tracker.note_probes(vec![
+			probe(101, 50, Some(1)),
+			probe(101, 10, Some(2)),
+			probe(101, 20, Some(1)),
+		]);
+		assert_eq!(tracker.healthy_endpoints(), vec![2, 0]);
+
+		// The endpoint recovers once it agrees with the others again.
+		tracker.note_probes(vec![
+			probe(102, 50, Some(3)),
+			probe(102, 10, Some(3)),
+			probe(102, 20, Some(3)),
+		]);
+		assert_eq!(tracker.healthy_endpoints(), vec![1, 2, 0]);
+	}
+
+	#[test]
+	fn switches_only_to_significantly_better_endpoint() {
+		let mut tracker = HealthTracker::new(2);
+		tracker.note_probes(vec![probe(100, 300, None), probe(100, 10, None)]);
+		assert_eq!(tracker.better_endpoint(0), None);
+		assert_eq!(tracker.better_endpoint(1), None);
+
+		tracker.note_probes(vec![probe(100, 10_000, None), probe(100, 10, None)]);
+		assert_eq!(tracker.better_endpoint(0), Some(1));
+
+		// Unhealthy endpoints are always switched away from.
+		let mut tracker = HealthTracker::new(2);
+		tracker.note_probes(vec![probe(90, 10, None), probe(100, 300, None)]);
+		assert_eq!(tracker.better_endpoint(0), Some(1));
+	}
+}
@@ -40,6 +40,7 @@ use std::{collections::btree_map::BTreeMap, pin::Pin};
 use cumulus_primitives_core::relay_chain::BlockId;
 pub use url::Url;
 
+mod endpoint_health;
 mod light_client_worker;
 mod metrics;
 mod reconnecting_ws_client;
@@ -287,4 +288,8 @@ impl RelayChainInterface for RelayChainRpcInterface {
 	async fn scheduling_lookahead(&self, relay_parent: RelayHash) -> RelayChainResult<u32> {
 		self.rpc_client.parachain_host_scheduling_lookahead(relay_parent).await
 	}
+
+	async fn submit_extrinsic(&self, extrinsic: Vec<u8>) -> RelayChainResult<RelayHash> {
+		self.rpc_client.author_submit_extrinsic(extrinsic.into()).await
+	}
 }
@@ -243,7 +243,10 @@ impl LightClientRpcWorker {
 					Some(RpcDispatcherMessage::RegisterFinalizationListener(tx)) => {
 						self.finalized_header_listeners.push(tx)
 					},
-					Some(RpcDispatcherMessage::Request(method, params, response_sender)) => {
+					// The light client verifies the responses itself, so there is no need for a
+					// quorum.
+					Some(RpcDispatcherMessage::Request(method, params, response_sender)) |
+					Some(RpcDispatcherMessage::QuorumRequest(method, params, response_sender)) => {
 						let closure_client = self.smoldot_client.clone();
 						tracing::debug!(
 							target: LOG_TARGET,
@@ -20,7 +20,7 @@ use cumulus_primitives_core::relay_chain::{
 };
 use futures::{
 	channel::{mpsc::Sender, oneshot::Sender as OneshotSender},
-	future::BoxFuture,
+	future::{join_all, BoxFuture, Fuse, FusedFuture},
 	stream::FuturesUnordered,
 	FutureExt, StreamExt,
 };
@@ -30,23 +30,40 @@ use jsonrpsee::{
 		params::ArrayParams,
 		ClientError as JsonRpseeError, JsonValue,
 	},
+	rpc_params,
 	ws_client::WsClientBuilder,
 };
 use sc_rpc_api::chain::ChainApiClient;
 use schnellru::{ByLength, LruMap};
 use sp_runtime::generic::SignedBlock;
-use std::{sync::Arc, time::Duration};
-use tokio::sync::mpsc::{
-	channel as tokio_channel, Receiver as TokioReceiver, Sender as TokioSender,
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
+use tokio::{
+	sync::mpsc::{channel as tokio_channel, Receiver as TokioReceiver, Sender as TokioSender},
+	time::MissedTickBehavior,
 };
 use url::Url;
 
-use crate::rpc_client::{distribute_header, RpcDispatcherMessage};
+use crate::{
+	endpoint_health::{HealthTracker, Probe},
+	rpc_client::{distribute_header, RpcDispatcherMessage},
+};
 
 const LOG_TARGET: &str = "reconnecting-websocket-client";
 const DEFAULT_EXTERNAL_RPC_CONN_RETRIES: usize = 5;
 const DEFAULT_SLEEP_TIME_MS_BETWEEN_RETRIES: u64 = 1000;
 const DEFAULT_SLEEP_EXP_BACKOFF_BETWEEN_RETRIES: i32 = 2;
+/// Interval between two health probes of the RPC endpoints.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(6);
+/// Time after which a connection attempt or a probe of an RPC endpoint is considered failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximum number of endpoints queried for a quorum request.
+const MAX_QUORUM_ENDPOINTS: usize = 3;
+
+/// The clients and probe results of one health probe round, by endpoint index.
+type ProbeRound = Vec<(Option<Arc<JsonRpcClient>>, Option<Probe>)>;
 
 /// Worker that should be used in combination with [`RelayChainRpcClient`].
 ///
@@ -87,8 +104,11 @@ fn url_to_string_with_port(url: Url) -> Option<String> {
 #[derive(Debug)]
 struct ClientManager {
 	urls: Vec<String>,
+	/// The clients of all endpoints, by index. `None` if the endpoint was never reached.
+	clients: Vec<Option<Arc<JsonRpcClient>>>,
 	active_client: Arc<JsonRpcClient>,
 	active_index: usize,
+	health: HealthTracker,
 }
 
 struct RelayChainSubscriptions {
@@ -150,23 +170,171 @@ async fn connect_next_available_rpc_server(
 	Err(())
 }
 
+/// Probe the health of the endpoint at `url`, connecting to it first if `client` is not
+/// connected.
+async fn probe_endpoint(
+	url: String,
+	client: Option<Arc<JsonRpcClient>>,
+	check_number: Option<RelayNumber>,
+) -> (Option<Arc<JsonRpcClient>>, Option<Probe>) {
+	let client = match client.filter(|client| client.is_connected()) {
+		Some(client) => client,
+		None => match tokio::time::timeout(PROBE_TIMEOUT, WsClientBuilder::default().build(&url))
+			.await
+		{
+			Ok(Ok(client)) => Arc::new(client),
+			Ok(Err(err)) => {
+				tracing::debug!(target: LOG_TARGET, url, ?err, "Unable to connect for probe.");
+				return (None, None)
+			},
+			Err(_) => {
+				tracing::debug!(target: LOG_TARGET, url, "Connection for probe timed out.");
+				return (None, None)
+			},
+		},
+	};
+
+	match tokio::time::timeout(PROBE_TIMEOUT, fetch_probe(&client, check_number)).await {
+		Ok(Ok(probe)) => (Some(client), Some(probe)),
+		Ok(Err(err)) => {
+			tracing::debug!(target: LOG_TARGET, url, ?err, "Probe of RPC endpoint failed.");
+			(Some(client), None)
+		},
+		Err(_) => {
+			tracing::debug!(target: LOG_TARGET, url, "Probe of RPC endpoint timed out.");
+			(Some(client), None)
+		},
+	}
+}
+
+/// Fetch the best and finalized block of an endpoint and the hash of the block at
+/// `check_number`.
+async fn fetch_probe(
+	client: &JsonRpcClient,
+	check_number: Option<RelayNumber>,
+) -> Result<Probe, JsonRpseeError> {
+	let header_not_found = || JsonRpseeError::Custom("Header not found".to_string());
+
+	let started = Instant::now();
+	let best = client
+		.request::<Option<RelayHeader>, _>("chain_getHeader", rpc_params![])
+		.await?
+		.ok_or_else(header_not_found)?;
+	let latency = started.elapsed();
+
+	let finalized_hash: RelayHash = client.request("chain_getFinalizedHead", rpc_params![]).await?;
+	let finalized = client
+		.request::<Option<RelayHeader>, _>("chain_getHeader", rpc_params![finalized_hash])
+		.await?
+		.ok_or_else(header_not_found)?;
+
+	// Blocks that are not finalized by the endpoint yet may legitimately differ.
+	let check_hash = match check_number.filter(|number| *number <= finalized.number) {
+		Some(number) => client.request("chain_getBlockHash", rpc_params![number]).await?,
+		None => None,
+	};
+
+	Ok(Probe { best_number: best.number, finalized_number: finalized.number, latency, check_hash })
+}
+
+/// Check that the responses of other endpoints agree with the `response` of the active endpoint.
+///
+/// Endpoints that failed to answer, e.g. because they did not import the block yet, are ignored.
+fn check_quorum(
+	method: &str,
+	response: Result<JsonValue, JsonRpseeError>,
+	other_responses: impl IntoIterator<Item = Result<JsonValue, JsonRpseeError>>,
+) -> Result<JsonValue, JsonRpseeError> {
+	let response = response?;
+	if other_responses.into_iter().flatten().any(|other| other != response) {
+		tracing::warn!(
+			target: LOG_TARGET,
+			method,
+			"Relay chain RPC endpoints disagree on the result of a request."
+		);
+		return Err(JsonRpseeError::Custom(format!(
+			"Relay chain RPC endpoints disagree on the result of {method}"
+		)))
+	}
+
+	Ok(response)
+}
+
 impl ClientManager {
 	pub async fn new(urls: Vec<String>) -> Result<Self, ()> {
 		if urls.is_empty() {
 			return Err(())
 		}
-		let active_client = connect_next_available_rpc_server(&urls, 0).await?;
-		Ok(Self { urls, active_client: active_client.1, active_index: active_client.0 })
+		let (active_index, active_client) = connect_next_available_rpc_server(&urls, 0).await?;
+		let mut clients = vec![None; urls.len()];
+		clients[active_index] = Some(active_client.clone());
+		let health = HealthTracker::new(urls.len());
+		Ok(Self { urls, clients, active_client, active_index, health })
 	}
 
 	pub async fn connect_to_new_rpc_server(&mut self) -> Result<(), ()> {
-		let new_active =
-			connect_next_available_rpc_server(&self.urls, self.active_index + 1).await?;
+		// Start with the healthiest of the other endpoints, if their health is known.
+		let starting_position = self
+			.health
+			.healthy_endpoints()
+			.into_iter()
+			.find(|index| *index != self.active_index)
+			.unwrap_or(self.active_index + 1);
+		let new_active = connect_next_available_rpc_server(&self.urls, starting_position).await?;
 		self.active_client = new_active.1;
 		self.active_index = new_active.0;
+		self.clients[self.active_index] = Some(self.active_client.clone());
 		Ok(())
 	}
 
+	/// Make the endpoint at `index` the active one.
+	///
+	/// Returns `false` if there is no open connection to the endpoint.
+	fn switch_to(&mut self, index: usize) -> bool {
+		let Some(client) = self.clients[index].clone().filter(|client| client.is_connected())
+		else {
+			return false
+		};
+
+		tracing::info!(
+			target: LOG_TARGET,
+			from = %self.urls[self.active_index],
+			to = %self.urls[index],
+			"Switching to a healthier external relaychain node.",
+		);
+		self.active_client = client;
+		self.active_index = index;
+		true
+	}
+
+	/// Create a future probing the health of all endpoints.
+	fn create_probe_round(&self) -> BoxFuture<'static, ProbeRound> {
+		let check_number = self.health.check_number();
+		let probes = self
+			.urls
+			.iter()
+			.cloned()
+			.zip(self.clients.iter().cloned())
+			.map(|(url, client)| probe_endpoint(url, client, check_number));
+		join_all(probes).boxed()
+	}
+
+	/// Record the results of a probe round.
+	///
+	/// Returns the index of the endpoint to switch to, if it is healthier than the active one.
+	fn note_probe_round(&mut self, round: ProbeRound) -> Option<usize> {
+		let mut probes = Vec::with_capacity(round.len());
+		for (index, (client, probe)) in round.into_iter().enumerate() {
+			if client.is_some() {
+				self.clients[index] = client;
+			}
+			probes.push(probe);
+		}
+
+		self.health.note_probes(probes);
+		self.health.better_endpoint(self.active_index)
+	}
+
 	async fn get_subscriptions(&self) -> Result<RelayChainSubscriptions, JsonRpseeError> {
 		let import_subscription = <JsonRpcClient as ChainApiClient<
 			RelayNumber,
@@ -254,11 +422,59 @@ impl ClientManager {
 		}
 		.boxed()
 	}
+
+	/// Create a request future like [`Self::create_request`], that cross-checks the response with
+	/// the healthiest other endpoints.
+	fn create_quorum_request(
+		&self,
+		method: String,
+		params: ArrayParams,
+		response_sender: OneshotSender<Result<JsonValue, JsonRpseeError>>,
+	) -> BoxFuture<'static, Result<(), RpcDispatcherMessage>> {
+		let other_clients = self
+			.health
+			.healthy_endpoints()
+			.into_iter()
+			.filter(|index| *index != self.active_index)
+			.filter_map(|index| self.clients[index].clone())
+			.filter(|client| client.is_connected());
+		let clients = std::iter::once(self.active_client.clone())
+			.chain(other_clients)
+			.take(MAX_QUORUM_ENDPOINTS)
+			.collect::<Vec<_>>();
+
+		async move {
+			let mut responses = join_all(
+				clients
+					.iter()
+					.map(|client| client.request::<JsonValue, _>(&method, params.clone())),
+			)
+			.await
+			.into_iter();
+			let resp = responses.next().expect("The active client is always queried; qed");
+
+			if let Err(JsonRpseeError::RestartNeeded(_)) = resp {
+				return Err(RpcDispatcherMessage::QuorumRequest(method, params, response_sender))
+			}
+
+			let resp = check_quorum(&method, resp, responses);
+			if let Err(err) = response_sender.send(resp) {
+				tracing::debug!(
+					target: LOG_TARGET,
+					?err,
+					"Recipient no longer interested in request result"
+				);
+			}
+			Ok(())
+		}
+		.boxed()
+	}
 }
 
 enum ConnectionStatus {
 	Connected,
 	ReconnectRequired(Option<RpcDispatcherMessage>),
+	SwitchRequired(usize),
 }
 
 impl ReconnectingWebsocketWorker {
@@ -289,7 +505,11 @@ impl ReconnectingWebsocketWorker {
 		first_failed_request: Option<RpcDispatcherMessage>,
 	) -> Result<RelayChainSubscriptions, String> {
 		let mut requests_to_retry = Vec::new();
-		if let Some(req @ RpcDispatcherMessage::Request(_, _, _)) = first_failed_request {
+		if let Some(
+			req @ (RpcDispatcherMessage::Request(_, _, _) |
+			RpcDispatcherMessage::QuorumRequest(_, _, _)),
+		) = first_failed_request
+		{
 			requests_to_retry.push(req);
 		}
 
@@ -306,13 +526,17 @@ impl ReconnectingWebsocketWorker {
 		};
 
 		for item in requests_to_retry.into_iter() {
-			if let RpcDispatcherMessage::Request(method, params, response_sender) = item {
-				pending_requests.push(client_manager.create_request(
-					method,
-					params,
-					response_sender,
-				));
-			};
+			match item {
+				RpcDispatcherMessage::Request(method, params, response_sender) => pending_requests
+					.push(client_manager.create_request(method, params, response_sender)),
+				RpcDispatcherMessage::QuorumRequest(method, params, response_sender) =>
+					pending_requests.push(client_manager.create_quorum_request(
+						method,
+						params,
+						response_sender,
+					)),
+				_ => {},
+			}
 		}
 
 		client_manager.get_subscriptions().await.map_err(|e| {
@@ -330,6 +554,8 @@ impl ReconnectingWebsocketWorker {
 	/// - Find a new valid RPC server to connect to in case the websocket connection is terminated.
 	///   If the worker is not able to connect to an RPC server from the list, the worker shuts
 	///   down.
+	/// - Periodically probe the health of all RPC servers, if there are several, and switch to a
+	///   healthier one when the active server lags behind, serves diverging data or is slow.
 	pub async fn run(mut self) {
 		let mut pending_requests = FuturesUnordered::new();
 
@@ -346,6 +572,11 @@ impl ReconnectingWebsocketWorker {
 		let mut imported_blocks_cache = LruMap::new(ByLength::new(40));
 		let mut should_reconnect = ConnectionStatus::Connected;
 		let mut last_seen_finalized_num: RelayNumber = 0;
+
+		let health_checks_enabled = client_manager.urls.len() > 1;
+		let mut health_check_interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+		health_check_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		let mut probe_round: Fuse<BoxFuture<'static, ProbeRound>> = Fuse::terminated();
 		loop {
 			// This branch is taken if the websocket connection to the current RPC server is closed.
 			if let ConnectionStatus::ReconnectRequired(maybe_failed_request) = should_reconnect {
@@ -372,6 +603,18 @@ impl ReconnectingWebsocketWorker {
 				should_reconnect = ConnectionStatus::Connected;
 			}
 
+			// This branch is taken if a healthier RPC server than the current one was found.
+			if let ConnectionStatus::SwitchRequired(index) = should_reconnect {
+				should_reconnect = ConnectionStatus::Connected;
+				if client_manager.switch_to(index) {
+					match client_manager.get_subscriptions().await {
+						Ok(new_subscriptions) => subscriptions = new_subscriptions,
+						Err(_) => should_reconnect = ConnectionStatus::ReconnectRequired(None),
+					}
+				}
+				continue
+			}
+
 			tokio::select! {
 				evt = self.client_receiver.recv() => match evt {
 					Some(RpcDispatcherMessage::RegisterBestHeadListener(tx)) => {
@@ -386,6 +629,9 @@ impl ReconnectingWebsocketWorker {
 					Some(RpcDispatcherMessage::Request(method, params, response_sender)) => {
 						pending_requests.push(client_manager.create_request(method, params, response_sender));
 					},
+					Some(RpcDispatcherMessage::QuorumRequest(method, params, response_sender)) => {
+						pending_requests.push(client_manager.create_quorum_request(method, params, response_sender));
+					},
 					None => {
 						tracing::error!(target: LOG_TARGET, "RPC client receiver closed. Stopping RPC Worker.");
 						return;
@@ -396,6 +642,14 @@ impl ReconnectingWebsocketWorker {
 						should_reconnect = ConnectionStatus::ReconnectRequired(Some(req));
 					}
 				},
+				_ = health_check_interval.tick(), if health_checks_enabled && probe_round.is_terminated() => {
+					probe_round = client_manager.create_probe_round().fuse();
+				},
+				round = &mut probe_round, if !probe_round.is_terminated() => {
+					if let Some(index) = client_manager.note_probe_round(round) {
+						should_reconnect = ConnectionStatus::SwitchRequired(index);
+					}
+				},
 				import_event = subscriptions.import_subscription.next() => {
 					match import_event {
 						Some(Ok(header)) => {
@@ -468,8 +722,9 @@ impl ReconnectingWebsocketWorker {
 mod test {
 	use std::time::Duration;
 
-	use super::{url_to_string_with_port, ClientManager};
-	use jsonrpsee::Methods;
+	use super::{check_quorum, url_to_string_with_port, ClientManager};
+	use jsonrpsee::{core::ClientError as JsonRpseeError, Methods};
+	use serde_json::json;
 	use url::Url;
 
 	const SERVER_STARTUP_DELAY_SECONDS: u64 = 10;
@@ -501,6 +756,20 @@ mod test {
 		);
 	}
 
+	#[test]
+	fn check_quorum_works() {
+		let unknown_block = || Err(JsonRpseeError::Custom("Unknown block".to_string()));
+
+		assert_eq!(check_quorum("method", Ok(json!("0x01")), []).unwrap(), json!("0x01"));
+		assert_eq!(
+			check_quorum("method", Ok(json!("0x01")), [Ok(json!("0x01")), unknown_block()])
+				.unwrap(),
+			json!("0x01")
+		);
+		assert!(check_quorum("method", Ok(json!("0x01")), [Ok(json!("0x02"))]).is_err());
+		assert!(check_quorum("method", unknown_block(), [Ok(json!("0x01"))]).is_err());
+	}
+
 	#[tokio::test]
 	// Testing the retry logic at full means increasing CI with half a minute according
 	// to the current logic, so lets test it best effort.
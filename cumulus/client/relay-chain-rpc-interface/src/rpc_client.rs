@@ -88,6 +88,12 @@ pub enum RpcDispatcherMessage {
 	/// - [`ArrayParams`] for the parameters to the RPC call
 	/// - [`OneshotSender`] for the return value of the request
 	Request(String, ArrayParams, OneshotSender<Result<JsonValue, JsonRpseeError>>),
+
+	/// Same as [`RpcDispatcherMessage::Request`], but for safety-critical requests.
+	///
+	/// Workers connected to several RPC servers send the request to the healthiest ones and only
+	/// return the result if all servers that answer agree on it.
+	QuorumRequest(String, ArrayParams, OneshotSender<Result<JsonValue, JsonRpseeError>>),
 }
 
 /// Entry point to create [`RelayChainRpcClient`] and start a worker that communicates
@@ -163,6 +169,29 @@ impl RelayChainRpcClient {
 		method_name: &str,
 		hash: RelayHash,
 		payload: &[u8],
+	) -> RelayChainResult<sp_core::Bytes> {
+		self.state_call(method_name, hash, payload, false).await
+	}
+
+	/// Same as `call_remote_runtime_function`, but cross-checks the result with several RPC
+	/// servers, see [`RpcDispatcherMessage::QuorumRequest`].
+	pub async fn call_remote_runtime_function_quorum<R: Decode>(
+		&self,
+		method_name: &str,
+		hash: RelayHash,
+		payload: Option<impl Encode>,
+	) -> RelayChainResult<R> {
+		let payload_bytes = payload.map_or(Vec::new(), |v| v.encode());
+		let res = self.state_call(method_name, hash, &payload_bytes, true).await?;
+		Decode::decode(&mut &*res.0).map_err(Into::into)
+	}
+
+	async fn state_call(
+		&self,
+		method_name: &str,
+		hash: RelayHash,
+		payload: &[u8],
+		quorum: bool,
 	) -> RelayChainResult<sp_core::Bytes> {
 		let payload = PayloadToHex(payload);
 
@@ -172,7 +201,7 @@ impl RelayChainRpcClient {
 			hash
 		};
 
-		self.request_tracing::<sp_core::Bytes, _>("state_call", params, |err| {
+		self.request_tracing::<sp_core::Bytes, _>("state_call", params, quorum, |err| {
 			tracing::trace!(
 				target: LOG_TARGET,
 				%method_name,
@@ -211,16 +240,18 @@ impl RelayChainRpcClient {
 		self.request_tracing(
 			method,
 			params,
+			false,
 			|e| tracing::trace!(target:LOG_TARGET, error = %e, %method, "Unable to complete RPC request"),
 		)
 		.await
 	}
 
-	/// Perform RPC request
+	/// Perform RPC request, with a quorum of RPC servers if `quorum` is set
 	async fn request_tracing<'a, R, OR>(
 		&self,
 		method: &'a str,
 		params: ArrayParams,
+		quorum: bool,
 		trace_error: OR,
 	) -> Result<R, RelayChainError>
 	where
@@ -231,7 +262,11 @@ impl RelayChainRpcClient {
 
 		let (tx, rx) = futures::channel::oneshot::channel();
 
-		let message = RpcDispatcherMessage::Request(method.into(), params, tx);
+		let message = if quorum {
+			RpcDispatcherMessage::QuorumRequest(method.into(), params, tx)
+		} else {
+			RpcDispatcherMessage::Request(method.into(), params, tx)
+		};
 		self.worker_channel.send(message).await.map_err(|err| {
 			RelayChainError::WorkerCommunicationError(format!(
 				"Unable to send message to RPC worker: {}",
@@ -315,6 +350,15 @@ impl RelayChainRpcClient {
 		self.request("state_getStorage", params).await
 	}
 
+	/// Submit a signed extrinsic to the transaction pool.
+	pub async fn author_submit_extrinsic(
+		&self,
+		extrinsic: sp_core::Bytes,
+	) -> Result<RelayHash, RelayChainError> {
+		let params = rpc_params![extrinsic];
+		self.request("author_submitExtrinsic", params).await
+	}
+
 	/// Get hash of the n-th block in the canon chain.
 	///
 	/// By default returns latest block hash.
@@ -400,7 +444,7 @@ impl RelayChainRpcClient {
 		para_id: ParaId,
 		occupied_core_assumption: OccupiedCoreAssumption,
 	) -> Result<Option<PersistedValidationData>, RelayChainError> {
-		self.call_remote_runtime_function(
+		self.call_remote_runtime_function_quorum(
 			"ParachainHost_persisted_validation_data",
 			at,
 			Some((para_id, occupied_core_assumption)),
@@ -526,7 +570,7 @@ impl RelayChainRpcClient {
 		para_id: ParaId,
 		occupied_core_assumption: OccupiedCoreAssumption,
 	) -> Result<Option<ValidationCodeHash>, RelayChainError> {
-		self.call_remote_runtime_function(
+		self.call_remote_runtime_function_quorum(
 			"ParachainHost_validation_code_hash",
 			at,
 			Some((para_id, occupied_core_assumption)),
@@ -50,6 +50,7 @@ use sp_core::{traits::SpawnNamed, Decode};
 use sp_runtime::traits::{Block as BlockT, BlockIdTo, Header};
 use std::{sync::Arc, time::Duration};
 
+pub use cumulus_client_pov_recovery::{PoVDirectory, PoVSource};
 pub use cumulus_primitives_proof_size_hostfunction::storage_proof_size;
 
 /// Host functions that should be used in parachain nodes.
@@ -107,6 +108,8 @@ pub struct StartRelayChainTasksParams<'a, Block: BlockT, Client, RCInterface> {
 	pub relay_chain_slot_duration: Duration,
 	pub recovery_handle: Box<dyn RecoveryHandle>,
 	pub sync_service: Arc<SyncingService<Block>>,
+	/// Sources to fetch PoVs from when they can not be recovered from the relay chain.
+	pub pov_sources: Vec<Arc<dyn PoVSource<Block>>>,
 }
 
 /// Parameters given to [`start_full_node`].
@@ -178,6 +181,7 @@ where
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service,
+		pov_sources: Vec::new(),
 	})?;
 
 	#[allow(deprecated)]
@@ -215,6 +219,7 @@ pub fn start_relay_chain_tasks<Block, Client, Backend, RCInterface>(
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service,
+		pov_sources,
 	}: StartRelayChainTasksParams<Block, Client, RCInterface>,
 ) -> sc_service::error::Result<()>
 where
@@ -277,7 +282,8 @@ where
 		para_id,
 		recovery_chan_rx,
 		sync_service,
-	);
+	)
+	.with_pov_sources(pov_sources);
 
 	task_manager
 		.spawn_essential_handle()
@@ -328,6 +334,7 @@ where
 		recovery_handle,
 		sync_service,
 		da_recovery_profile: DARecoveryProfile::FullNode,
+		pov_sources: Vec::new(),
 	})
 }
 
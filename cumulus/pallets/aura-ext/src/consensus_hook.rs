@@ -23,6 +23,7 @@ use cumulus_pallet_parachain_system::{
 	consensus_hook::{ConsensusHook, UnincludedSegmentCapacity},
 	relay_state_snapshot::RelayChainStateProof,
 };
+use cumulus_primitives_core::{BuildRefusal, ConsensusHookCapacity};
 use frame_support::pallet_prelude::*;
 use sp_consensus_aura::{Slot, SlotDuration};
 
@@ -140,27 +141,52 @@ impl<
 	/// whose state we are querying against, this must always return `true` as long as the slot
 	/// is more recent than the included block itself.
 	pub fn can_build_upon(included_hash: T::Hash, new_slot: Slot) -> bool {
+		Self::build_refusal(included_hash, new_slot).is_none()
+	}
+
+	/// The reason it is not legal to extend the chain, under the same assumptions as
+	/// [`Self::can_build_upon`].
+	///
+	/// Returns `None` if building is allowed.
+	pub fn build_refusal(included_hash: T::Hash, new_slot: Slot) -> Option<BuildRefusal> {
 		let velocity = V.max(1);
-		let (last_slot, authored_so_far) = match pallet::RelaySlotInfo::<T>::get() {
-			None => return true,
-			Some(x) => x,
-		};
+		let (last_slot, authored_so_far) = pallet::RelaySlotInfo::<T>::get()?;
 
 		let size_after_included =
 			parachain_system::Pallet::<T>::unincluded_segment_size_after(included_hash);
 
 		// can never author when the unincluded segment is full.
 		if size_after_included >= C {
-			return false
+			return Some(BuildRefusal::UnincludedSegmentFull {
+				size: size_after_included,
+				capacity: C,
+			})
 		}
 
 		// Check that we have not authored more than `V + 1` parachain blocks in the current relay
 		// chain slot.
 		if last_slot == new_slot {
-			authored_so_far < velocity + 1
+			(authored_so_far >= velocity + 1).then_some(BuildRefusal::VelocityExceeded {
+				relay_slot: new_slot,
+				authored: authored_so_far,
+				velocity,
+			})
 		} else {
 			// disallow slot from moving backwards.
-			last_slot < new_slot
+			(last_slot > new_slot).then_some(BuildRefusal::RelayParentTooOld {
+				relay_slot: new_slot,
+				last_relay_slot: last_slot,
+			})
+		}
+	}
+
+	/// The limits applied by this hook, along with the blocks authored in the last relay chain
+	/// slot.
+	pub fn consensus_hook_capacity() -> ConsensusHookCapacity {
+		ConsensusHookCapacity {
+			unincluded_segment_capacity: C.max(1),
+			velocity: V.max(1),
+			relay_slot_info: pallet::RelaySlotInfo::<T>::get(),
 		}
 	}
 }
@@ -123,6 +123,7 @@ mod test {
 	use cumulus_pallet_parachain_system::{
 		Ancestor, ConsensusHook, RelayChainStateProof, UsedBandwidth,
 	};
+	use cumulus_primitives_core::{BuildRefusal, ConsensusHookCapacity};
 	use rstest::rstest;
 	use sp_core::H256;
 
@@ -372,4 +373,44 @@ mod test {
 			assert!(Hook::can_build_upon(H256::repeat_byte(0x2), relay_slot));
 		});
 	}
+
+	#[test]
+	fn test_build_refusal_reports_reason() {
+		type Hook = FixedVelocityConsensusHook<Test, 6000, DEFAULT_TEST_VELOCITY, 2>;
+
+		new_test_ext(1).execute_with(|| {
+			let hash = H256::repeat_byte(0x2);
+			set_relay_slot(10, DEFAULT_TEST_VELOCITY + 1);
+
+			assert_eq!(
+				Hook::build_refusal(H256::repeat_byte(0x1), Slot::from(11)),
+				Some(BuildRefusal::UnincludedSegmentFull { size: 2, capacity: 2 })
+			);
+			assert_eq!(
+				Hook::build_refusal(hash, Slot::from(10)),
+				Some(BuildRefusal::VelocityExceeded {
+					relay_slot: Slot::from(10),
+					authored: DEFAULT_TEST_VELOCITY + 1,
+					velocity: DEFAULT_TEST_VELOCITY,
+				})
+			);
+			assert_eq!(
+				Hook::build_refusal(hash, Slot::from(9)),
+				Some(BuildRefusal::RelayParentTooOld {
+					relay_slot: Slot::from(9),
+					last_relay_slot: Slot::from(10),
+				})
+			);
+			assert_eq!(Hook::build_refusal(hash, Slot::from(11)), None);
+
+			assert_eq!(
+				Hook::consensus_hook_capacity(),
+				ConsensusHookCapacity {
+					unincluded_segment_capacity: 2,
+					velocity: DEFAULT_TEST_VELOCITY,
+					relay_slot_info: Some((Slot::from(10), DEFAULT_TEST_VELOCITY + 1)),
+				}
+			);
+		});
+	}
 }
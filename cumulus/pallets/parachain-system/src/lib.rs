@@ -39,8 +39,8 @@ use cumulus_primitives_core::{
 	},
 	AbridgedHostConfiguration, ChannelInfo, ChannelStatus, CollationInfo, GetChannelInfo,
 	InboundDownwardMessage, InboundHrmpMessage, ListChannelInfos, MessageSendError,
-	OutboundHrmpMessage, ParaId, PersistedValidationData, UpwardMessage, UpwardMessageSender,
-	XcmpMessageHandler, XcmpMessageSource,
+	OutboundHrmpMessage, ParaId, PersistedValidationData, SegmentAncestor, UnincludedSegmentInfo,
+	UpwardMessage, UpwardMessageSender, XcmpMessageHandler, XcmpMessageSource,
 };
 use cumulus_primitives_parachain_inherent::{MessageQueueChain, ParachainInherentData};
 use frame_support::{
@@ -563,6 +563,7 @@ pub mod pallet {
 				3 + hrmp_max_message_num_per_candidate as u64,
 				4 + hrmp_max_message_num_per_candidate as u64,
 			);
+			weight += T::OutboundXcmpMessageSource::take_outbound_messages_overhead();
 
 			// Weight for updating the last relay chain block number in `on_finalize`.
 			weight += T::DbWeight::get().reads_writes(1, 1);
@@ -1009,6 +1010,31 @@ impl<T: Config> Pallet<T> {
 		let segment = UnincludedSegment::<T>::get();
 		crate::unincluded_segment::size_after_included(included_hash, &segment)
 	}
+
+	/// Get the current unincluded segment along with the bandwidth it uses.
+	///
+	/// This is intended to be used in runtime APIs for diagnosing why blocks can not be built.
+	pub fn unincluded_segment_info() -> UnincludedSegmentInfo<T::Hash> {
+		let ancestors = UnincludedSegment::<T>::get()
+			.iter()
+			.map(|ancestor| SegmentAncestor {
+				para_head_hash: ancestor.para_head_hash().copied(),
+				used_bandwidth: ancestor.used_bandwidth().into(),
+			})
+			.collect();
+		let aggregated = AggregatedUnincludedSegment::<T>::get();
+
+		UnincludedSegmentInfo {
+			ancestors,
+			used_bandwidth: aggregated
+				.as_ref()
+				.map(|segment| segment.used_bandwidth().into())
+				.unwrap_or_default(),
+			hrmp_watermark: aggregated.and_then(|segment| segment.hrmp_watermark()),
+			relay_parent_number: LastRelayChainBlockNumber::<T>::exists()
+				.then(LastRelayChainBlockNumber::<T>::get),
+		}
+	}
 }
 
 impl<T: Config> FeeTracker for Pallet<T> {
@@ -20,7 +20,10 @@ use super::*;
 use crate::mock::*;
 
 use core::num::NonZeroU32;
-use cumulus_primitives_core::{AbridgedHrmpChannel, InboundDownwardMessage, InboundHrmpMessage};
+use cumulus_primitives_core::{
+	AbridgedHrmpChannel, HrmpBandwidth, InboundDownwardMessage, InboundHrmpMessage,
+	SegmentBandwidth,
+};
 use frame_support::{assert_ok, parameter_types, weights::Weight};
 use frame_system::RawOrigin;
 use hex_literal::hex;
@@ -303,6 +306,48 @@ fn inherent_processed_messages_are_ignored() {
 		});
 }
 
+#[test]
+fn unincluded_segment_info_reports_used_bandwidth() {
+	let recipient = ParaId::from(400);
+
+	CONSENSUS_HOOK.with(|c| {
+		*c.borrow_mut() = Box::new(|_| (Weight::zero(), NonZeroU32::new(3).unwrap().into()))
+	});
+
+	BlockTests::new()
+		.with_inclusion_delay(2)
+		.with_relay_sproof_builder(move |_, _, sproof| {
+			sproof.host_config.hrmp_max_message_num_per_candidate = 10;
+			let channel = sproof.upsert_outbound_channel(recipient);
+			channel.max_capacity = 10;
+			channel.max_total_size = 10;
+			channel.max_message_size = 10;
+		})
+		.add(1, || {})
+		.add_with_post_test(
+			2,
+			move || {
+				send_message(recipient, b"22".to_vec());
+			},
+			move || {
+				let info = ParachainSystem::unincluded_segment_info();
+				let expected = SegmentBandwidth {
+					hrmp_outgoing: vec![HrmpBandwidth { recipient, msg_count: 1, total_bytes: 2 }],
+					..Default::default()
+				};
+
+				assert_eq!(info.ancestors.len(), 2);
+				assert!(info.ancestors[0].para_head_hash.is_some());
+				assert_eq!(info.ancestors[0].used_bandwidth, SegmentBandwidth::default());
+				// The hash of the most recent block is only known to its child.
+				assert_eq!(info.ancestors[1].para_head_hash, None);
+				assert_eq!(info.ancestors[1].used_bandwidth, expected);
+				assert_eq!(info.used_bandwidth, expected);
+				assert!(info.relay_parent_number.is_some());
+			},
+		);
+}
+
 #[test]
 fn hrmp_outbound_respects_used_bandwidth() {
 	let recipient = ParaId::from(400);
@@ -24,7 +24,7 @@ use super::relay_state_snapshot::{MessagingStateSnapshot, RelayDispatchQueueRema
 use alloc::collections::btree_map::BTreeMap;
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
-use cumulus_primitives_core::{relay_chain, ParaId};
+use cumulus_primitives_core::{relay_chain, HrmpBandwidth, ParaId, SegmentBandwidth};
 use scale_info::TypeInfo;
 use sp_runtime::RuntimeDebug;
 
@@ -245,6 +245,24 @@ impl UsedBandwidth {
 	}
 }
 
+impl From<&UsedBandwidth> for SegmentBandwidth {
+	fn from(used: &UsedBandwidth) -> Self {
+		Self {
+			ump_msg_count: used.ump_msg_count,
+			ump_total_bytes: used.ump_total_bytes,
+			hrmp_outgoing: used
+				.hrmp_outgoing
+				.iter()
+				.map(|(recipient, channel)| HrmpBandwidth {
+					recipient: *recipient,
+					msg_count: channel.msg_count,
+					total_bytes: channel.total_bytes,
+				})
+				.collect(),
+		}
+	}
+}
+
 /// Ancestor of the block being currently executed, not yet included
 /// into the relay chain.
 #[derive(Encode, Decode, TypeInfo, RuntimeDebug)]
@@ -384,6 +402,11 @@ impl<H> SegmentTracker<H> {
 	pub fn consumed_go_ahead_signal(&self) -> Option<relay_chain::UpgradeGoAhead> {
 		self.consumed_go_ahead_signal
 	}
+
+	/// Return the HRMP watermark of the most recent block in the segment, if any.
+	pub fn hrmp_watermark(&self) -> Option<relay_chain::BlockNumber> {
+		self.hrmp_watermark
+	}
 }
 
 pub(crate) fn size_after_included<H: PartialEq>(included_hash: H, segment: &[Ancestor<H>]) -> u32 {
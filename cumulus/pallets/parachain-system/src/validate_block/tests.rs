@@ -96,6 +96,21 @@ fn create_test_client() -> (Client, Header) {
 	(client, genesis_header)
 }
 
+/// Create test client whose genesis state is the state of a solochain migrated to Aura.
+fn create_migrated_test_client() -> (Client, Header) {
+	let mut builder = TestClientBuilder::new();
+	builder.genesis_init_mut().migrated_from_solochain = true;
+	let client = builder.enable_import_proof_recording().build();
+
+	let genesis_header = client
+		.header(client.chain_info().genesis_hash)
+		.ok()
+		.flatten()
+		.expect("Genesis header exists; qed");
+
+	(client, genesis_header)
+}
+
 /// Create test client using the runtime with `elastic-scaling` feature enabled.
 fn create_elastic_scaling_test_client() -> (Client, Header) {
 	let mut builder = TestClientBuilder::new();
@@ -303,6 +318,66 @@ fn validate_block_returns_custom_head_data() {
 	assert_eq!(expected_header, res_header);
 }
 
+#[test]
+fn validate_first_block_on_exported_head() {
+	sp_tracing::try_init_simple();
+
+	// The genesis block of the test client stands in for the last block of a solochain that
+	// applied `MigrateToAura`, whose exported head is the genesis head of the parachain.
+	let (client, exported_head) = create_migrated_test_client();
+	let exported_head = exported_head.encode();
+
+	let TestBlockData { block, validation_data } = build_block_with_witness(
+		&client,
+		vec![transfer(&client, Alice, Bob, 69)],
+		Header::decode(&mut &exported_head[..]).unwrap(),
+		Default::default(),
+	);
+	let block = seal_block(block, &client);
+	let header = block.blocks()[0].header().clone();
+
+	let res_head = cumulus_test_client::validate_first_block(
+		&exported_head,
+		block.clone(),
+		validation_data.relay_parent_storage_root,
+		WASM_BINARY.expect("You need to build the WASM binaries to run the tests!"),
+	)
+	.expect("Validates the first block");
+	assert_eq!(header.encode(), res_head.0);
+
+	// A head with a different state root is not the parent of the first block.
+	let mut other_head = Header::decode(&mut &exported_head[..]).unwrap();
+	other_head.set_state_root(Hash::from_low_u64_be(1));
+	let error = cumulus_test_client::validate_first_block(
+		&other_head.encode(),
+		block.clone(),
+		validation_data.relay_parent_storage_root,
+		WASM_BINARY.expect("You need to build the WASM binaries to run the tests!"),
+	)
+	.unwrap_err();
+	assert!(error.contains("doesn't build on the exported head"));
+
+	// The first block builds on the exported head, but its witness is the state of the solochain
+	// before the migration.
+	let (solo_client, solo_head) = create_test_client();
+	let TestBlockData { block: solo_block, .. } = build_block_with_witness(
+		&solo_client,
+		vec![transfer(&solo_client, Alice, Bob, 69)],
+		solo_head,
+		Default::default(),
+	);
+	let (_, solo_proof) = solo_block.into_inner();
+	let (blocks, _) = block.into_inner();
+	let error = cumulus_test_client::validate_first_block(
+		&exported_head,
+		ParachainBlockData::new(blocks, solo_proof),
+		validation_data.relay_parent_storage_root,
+		WASM_BINARY.expect("You need to build the WASM binaries to run the tests!"),
+	)
+	.unwrap_err();
+	assert!(error.contains("`validate_block` failed"));
+}
+
 #[test]
 fn validate_block_invalid_parent_hash() {
 	sp_tracing::try_init_simple();
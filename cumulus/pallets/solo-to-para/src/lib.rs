@@ -18,6 +18,10 @@
 
 extern crate alloc;
 
+pub mod migration;
+#[cfg(test)]
+mod mock;
+
 use alloc::vec::Vec;
 use cumulus_pallet_parachain_system as parachain_system;
 use frame_support::pallet_prelude::*;
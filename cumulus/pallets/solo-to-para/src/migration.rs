@@ -0,0 +1,255 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migration of the consensus state of a solochain to the one of a parachain.
+//!
+//! [`MigrateToAura`] removes the state of the solochain consensus pallets, e.g. BABE and GRANDPA,
+//! upgrades the session keys to the keys of the parachain runtime and seeds the Aura authorities
+//! from the current validators.
+//!
+//! The migration has to be applied by the last block of the solochain. The head of this block is
+//! then exported with the `export-head` command and registered as the genesis head of the
+//! parachain. The first parachain block is validated against the state of this head, so the Aura
+//! authorities that verify its seal must already be part of it.
+//!
+//! ```ignore
+//! parameter_types! {
+//! 	pub SoloConsensusPallets: Vec<&'static str> = vec!["Babe", "Grandpa"];
+//! }
+//!
+//! pub struct UpgradeSessionKeys;
+//! impl cumulus_pallet_solo_to_para::migration::UpgradeSessionKeys<Runtime> for UpgradeSessionKeys {
+//! 	type OldKeys = SoloSessionKeys;
+//!
+//! 	fn upgrade(_: AccountId, old: SoloSessionKeys) -> SessionKeys {
+//! 		SessionKeys { aura: old.aura }
+//! 	}
+//! }
+//!
+//! pub type Migrations = (
+//! 	cumulus_pallet_solo_to_para::migration::MigrateToAura<
+//! 		Runtime,
+//! 		UpgradeSessionKeys,
+//! 		SoloConsensusPallets,
+//! 	>,
+//! );
+//! ```
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use frame_support::{
+	pallet_prelude::*, storage::unhashed, storage_alias, traits::OnRuntimeUpgrade, StorageHasher,
+	Twox128,
+};
+use sp_runtime::{
+	traits::{Member, OpaqueKeys},
+	RuntimeAppPublic,
+};
+
+const LOG_TARGET: &str = "runtime::solo-to-para";
+
+/// The authorities cached by `cumulus-pallet-aura-ext` to verify the seal of a block.
+#[storage_alias]
+type Authorities<T: cumulus_pallet_aura_ext::Config> = StorageValue<
+	cumulus_pallet_aura_ext::Pallet<T>,
+	BoundedVec<<T as pallet_aura::Config>::AuthorityId, <T as pallet_aura::Config>::MaxAuthorities>,
+	ValueQuery,
+>;
+
+/// Conversion of the session keys of the solochain to the session keys of the parachain.
+pub trait UpgradeSessionKeys<T: pallet_session::Config> {
+	/// The session keys of the solochain runtime.
+	type OldKeys: OpaqueKeys + Member + Decode;
+
+	/// Convert the session keys of `validator`, usually by only keeping its Aura key.
+	fn upgrade(validator: T::ValidatorId, old: Self::OldKeys) -> T::Keys;
+}
+
+/// Migrates the consensus state of a solochain to Aura.
+///
+/// - Removes all storage of the pallets named in `SoloPallets`.
+/// - Upgrades the session keys with `Upgrade`.
+/// - Seeds the authorities of `pallet-aura` and `cumulus-pallet-aura-ext` with the Aura keys of the
+///   current validators.
+///
+/// The migration does nothing if Aura already has authorities.
+pub struct MigrateToAura<T, Upgrade, SoloPallets>(PhantomData<(T, Upgrade, SoloPallets)>);
+
+impl<T, Upgrade, SoloPallets> OnRuntimeUpgrade for MigrateToAura<T, Upgrade, SoloPallets>
+where
+	T: pallet_session::Config + cumulus_pallet_aura_ext::Config,
+	Upgrade: UpgradeSessionKeys<T>,
+	SoloPallets: Get<Vec<&'static str>>,
+{
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = T::DbWeight::get();
+		if !pallet_aura::Authorities::<T>::get().is_empty() {
+			log::info!(target: LOG_TARGET, "Aura authorities are already set, skipping migration.");
+			return db_weight.reads(1);
+		}
+
+		let mut writes = 0u64;
+		for pallet in SoloPallets::get() {
+			let removed = unhashed::clear_prefix(&Twox128::hash(pallet.as_bytes()), None, None);
+			log::info!(
+				target: LOG_TARGET,
+				"Removed {} storage items of pallet {pallet}.",
+				removed.backend,
+			);
+			writes = writes.saturating_add(removed.backend.into());
+		}
+
+		pallet_session::Pallet::<T>::upgrade_keys::<Upgrade::OldKeys, _>(Upgrade::upgrade);
+
+		let validators = pallet_session::Validators::<T>::get();
+		let authorities = validators
+			.iter()
+			.filter_map(|validator| pallet_session::NextKeys::<T>::get(validator))
+			.filter_map(|keys| keys.get::<T::AuthorityId>(<T::AuthorityId as RuntimeAppPublic>::ID))
+			.take(T::MaxAuthorities::get() as usize)
+			.collect::<Vec<_>>();
+		if authorities.len() < validators.len() {
+			log::warn!(
+				target: LOG_TARGET,
+				"Only {} of {} validators have an Aura key.",
+				authorities.len(),
+				validators.len(),
+			);
+		}
+
+		pallet_aura::Pallet::<T>::initialize_authorities(&authorities);
+		Authorities::<T>::put(pallet_aura::Authorities::<T>::get());
+		log::info!(target: LOG_TARGET, "Seeded {} Aura authorities.", authorities.len());
+
+		// Upgrading the keys reads and writes the keys and their owners of every validator.
+		let validators = validators.len() as u64;
+		writes = writes.saturating_add(validators.saturating_mul(3).saturating_add(2));
+		db_weight.reads_writes(validators.saturating_mul(2).saturating_add(2), writes)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(_: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+		ensure!(!pallet_aura::Authorities::<T>::get().is_empty(), "Aura authorities should be set");
+		ensure!(
+			Authorities::<T>::get() == pallet_aura::Authorities::<T>::get(),
+			"Authorities of `cumulus-pallet-aura-ext` should match the Aura authorities"
+		);
+		for pallet in SoloPallets::get() {
+			ensure!(
+				!unhashed::contains_prefixed_key(&Twox128::hash(pallet.as_bytes())),
+				"Storage of the solochain consensus pallets should be removed"
+			);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use sp_core::crypto::key_types;
+	use sp_runtime::{traits::OpaqueKeys, StateVersion};
+
+	type Migration = MigrateToAura<Test, KeepAuraKey, SoloConsensusPallets>;
+
+	fn solo_storage_cleared() -> bool {
+		SoloConsensusPallets::get()
+			.iter()
+			.all(|pallet| !unhashed::contains_prefixed_key(&Twox128::hash(pallet.as_bytes())))
+	}
+
+	#[test]
+	fn migrates_solo_consensus_to_aura() {
+		new_test_ext(vec![1, 2, 3], &[2]).execute_with(|| {
+			assert!(!solo_storage_cleared());
+
+			Migration::on_runtime_upgrade();
+
+			assert!(solo_storage_cleared());
+			assert_eq!(pallet_session::Validators::<Test>::get(), vec![1, 2, 3]);
+			for validator in [1, 3] {
+				let old = solo_keys(validator);
+				assert_eq!(
+					pallet_session::NextKeys::<Test>::get(validator),
+					Some(SessionKeys { aura: aura_id(validator) }),
+				);
+				assert_eq!(
+					pallet_session::KeyOwner::<Test>::get((
+						key_types::BABE,
+						old.get_raw(key_types::BABE).to_vec()
+					)),
+					None,
+				);
+				assert_eq!(
+					pallet_session::KeyOwner::<Test>::get((
+						key_types::AURA,
+						old.get_raw(key_types::AURA).to_vec()
+					)),
+					Some(validator),
+				);
+			}
+			// Validator 2 has no keys, so it can't author blocks.
+			assert_eq!(pallet_session::NextKeys::<Test>::get(2), None);
+
+			let authorities = vec![aura_id(1), aura_id(3)];
+			assert_eq!(pallet_aura::Authorities::<Test>::get().into_inner(), authorities);
+			assert_eq!(Authorities::<Test>::get().into_inner(), authorities);
+		});
+	}
+
+	#[test]
+	fn authorities_are_truncated_at_max_authorities() {
+		new_test_ext((1..=5).collect(), &[]).execute_with(|| {
+			Migration::on_runtime_upgrade();
+
+			let authorities = (1..=MAX_AUTHORITIES.into()).map(aura_id).collect::<Vec<_>>();
+			assert_eq!(pallet_aura::Authorities::<Test>::get().into_inner(), authorities);
+			assert_eq!(Authorities::<Test>::get().into_inner(), authorities);
+			// The keys of all validators are upgraded nonetheless.
+			assert!(
+				(1..=5).all(|validator| pallet_session::NextKeys::<Test>::contains_key(validator))
+			);
+		});
+	}
+
+	#[test]
+	fn migration_is_idempotent() {
+		new_test_ext(vec![1, 2, 3], &[]).execute_with(|| {
+			Migration::on_runtime_upgrade();
+			let root = sp_io::storage::root(StateVersion::V1);
+
+			let weight = Migration::on_runtime_upgrade();
+
+			assert_eq!(weight, <Test as frame_system::Config>::DbWeight::get().reads(1));
+			assert_eq!(sp_io::storage::root(StateVersion::V1), root);
+		});
+	}
+
+	#[cfg(feature = "try-runtime")]
+	#[test]
+	fn post_upgrade_checks_the_migrated_state() {
+		new_test_ext(vec![1, 2, 3], &[]).execute_with(|| {
+			assert!(Migration::post_upgrade(Vec::new()).is_err());
+
+			assert!(Migration::try_on_runtime_upgrade(true).is_ok());
+
+			// `cumulus-pallet-aura-ext` would reject the seal of the first parachain block.
+			Authorities::<Test>::kill();
+			assert!(Migration::post_upgrade(Vec::new()).is_err());
+		});
+	}
+}
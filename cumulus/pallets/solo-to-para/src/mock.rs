@@ -0,0 +1,164 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::migration::UpgradeSessionKeys;
+use frame_support::{
+	derive_impl, parameter_types,
+	storage::unhashed,
+	traits::{ConstBool, ConstU32, ConstU64},
+	StorageHasher, Twox128,
+};
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::{crypto::key_types, sr25519};
+use sp_runtime::{
+	impl_opaque_keys,
+	traits::{ConvertInto, OpaqueKeys},
+	BuildStorage, KeyTypeId,
+};
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Session: pallet_session,
+		Aura: pallet_aura,
+		AuraExt: cumulus_pallet_aura_ext,
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = Aura;
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+pub const MAX_AUTHORITIES: u32 = 3;
+
+impl pallet_aura::Config for Test {
+	type AuthorityId = AuraId;
+	type DisabledValidators = ();
+	type MaxAuthorities = ConstU32<MAX_AUTHORITIES>;
+	type AllowMultipleBlocksPerSlot = ConstBool<false>;
+	type SlotDuration = ConstU64<2>;
+}
+
+impl cumulus_pallet_aura_ext::Config for Test {}
+
+impl_opaque_keys! {
+	pub struct SessionKeys {
+		pub aura: Aura,
+	}
+}
+
+parameter_types! {
+	pub const Period: u64 = 10;
+	pub const Offset: u64 = 0;
+}
+
+impl pallet_session::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = <Self as frame_system::Config>::AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
+	type SessionManager = ();
+	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = SessionKeys;
+	type DisablingStrategy = ();
+	type WeightInfo = ();
+}
+
+/// The session keys of a solochain using BABE and Aura keys.
+#[derive(Debug, Clone, codec::Encode, codec::Decode, PartialEq, Eq)]
+pub struct SoloSessionKeys {
+	pub babe: [u8; 32],
+	pub aura: AuraId,
+}
+
+impl OpaqueKeys for SoloSessionKeys {
+	type KeyTypeIdProviders = ();
+
+	fn key_ids() -> &'static [KeyTypeId] {
+		&[key_types::BABE, key_types::AURA]
+	}
+
+	fn get_raw(&self, i: KeyTypeId) -> &[u8] {
+		match i {
+			key_types::BABE => &self.babe[..],
+			key_types::AURA => self.aura.as_ref(),
+			_ => &[],
+		}
+	}
+}
+
+pub struct KeepAuraKey;
+
+impl UpgradeSessionKeys<Test> for KeepAuraKey {
+	type OldKeys = SoloSessionKeys;
+
+	fn upgrade(_: u64, old: SoloSessionKeys) -> SessionKeys {
+		SessionKeys { aura: old.aura }
+	}
+}
+
+parameter_types! {
+	pub SoloConsensusPallets: Vec<&'static str> = vec!["Babe", "Grandpa"];
+}
+
+pub fn aura_id(validator: u64) -> AuraId {
+	sr25519::Public::from_raw([validator as u8; 32]).into()
+}
+
+pub fn solo_keys(validator: u64) -> SoloSessionKeys {
+	SoloSessionKeys { babe: [validator as u8 + 100; 32], aura: aura_id(validator) }
+}
+
+/// Storage of a solochain whose `validators` have BABE and Aura keys, except the ones in
+/// `without_keys`.
+pub fn new_test_ext(validators: Vec<u64>, without_keys: &[u64]) -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| {
+		for pallet in SoloConsensusPallets::get() {
+			let prefix = Twox128::hash(pallet.as_bytes());
+			unhashed::put(&[&prefix[..], &b"Authorities"[..]].concat(), &[1u8; 64]);
+			unhashed::put(&[&prefix[..], &b"CurrentSlot"[..]].concat(), &42u64);
+		}
+
+		for validator in validators.iter().filter(|v| !without_keys.contains(v)) {
+			let keys = solo_keys(*validator);
+			// `NextKeys` holds the keys in the format of the solochain before the upgrade.
+			unhashed::put(&pallet_session::NextKeys::<Test>::hashed_key_for(validator), &keys);
+			for id in SoloSessionKeys::key_ids() {
+				pallet_session::KeyOwner::<Test>::insert(
+					(*id, keys.get_raw(*id).to_vec()),
+					validator,
+				);
+			}
+		}
+		pallet_session::Validators::<Test>::put(validators);
+	});
+	ext
+}
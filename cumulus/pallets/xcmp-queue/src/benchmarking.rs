@@ -40,6 +40,22 @@ mod benchmarks {
 		Pallet::<T>::update_resume_threshold(RawOrigin::Root, 1);
 	}
 
+	/// Overwrite the scheduling of the channels with a sibling.
+	#[benchmark]
+	fn set_channel_scheduling() {
+		let sibling = ParaId::from(123);
+		let scheduling = ChannelScheduling {
+			priority: ChannelPriority::High,
+			inbound_weight_quota: Perbill::from_percent(50),
+			max_outbound_starvation: 5,
+		};
+
+		#[extrinsic_call]
+		_(RawOrigin::Root, sibling, Some(scheduling));
+
+		assert_eq!(ChannelSchedulingConfig::<T>::get(sibling), scheduling);
+	}
+
 	/// Add a XCMP message of `n` bytes to the message queue.
 	///
 	/// The message will be added on a new page and also, the `BookState` will be added
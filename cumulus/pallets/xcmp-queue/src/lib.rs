@@ -32,6 +32,15 @@
 //! It is defined in the channel configuration.
 //! - `THRESHOLD_FACTOR` just declares which percentage of the max size is the actual threshold.
 //! If it's 2, then the threshold is half of the max size, if it's 4, it's a quarter, and so on.
+//!
+//! Governance can set a [`ChannelScheduling`] per sibling parachain, which prioritizes its
+//! channels and limits how much of the inbound weight its messages may use:
+//! - Inbound pages are enqueued in order of the priority of their sender. A sender that used up its
+//!   `inbound_weight_quota` only gets weight again after all other senders were served.
+//! - Outbound pages are sent in order of the priority of their recipient. A channel that could not
+//!   send for `max_outbound_starvation` blocks is served first, so that channels with a higher
+//!   priority can't starve it. The number of blocks the channels are starved is tracked in
+//!   [`OutboundStarvation`].
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -55,9 +64,13 @@ pub use weights_ext::WeightInfoExt;
 
 extern crate alloc;
 
-use alloc::vec::Vec;
-use bounded_collections::BoundedBTreeSet;
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec::Vec,
+};
+use bounded_collections::{BoundedBTreeMap, BoundedBTreeSet};
 use codec::{Decode, DecodeLimit, Encode, MaxEncodedLen};
+use core::cmp::Reverse;
 use cumulus_primitives_core::{
 	relay_chain::BlockNumber as RelayBlockNumber, ChannelStatus, GetChannelInfo, MessageSendError,
 	ParaId, XcmpMessageFormat, XcmpMessageHandler, XcmpMessageSource,
@@ -74,7 +87,7 @@ use polkadot_runtime_common::xcm_sender::PriceForMessageDelivery;
 use polkadot_runtime_parachains::FeeTracker;
 use scale_info::TypeInfo;
 use sp_core::MAX_POSSIBLE_ALLOCATION;
-use sp_runtime::{FixedU128, RuntimeDebug, Saturating, WeakBoundedVec};
+use sp_runtime::{FixedU128, Perbill, RuntimeDebug, Saturating, WeakBoundedVec};
 use xcm::{latest::prelude::*, VersionedLocation, VersionedXcm, WrapVersion, MAX_XCM_DECODE_DEPTH};
 use xcm_builder::InspectMessageQueues;
 use xcm_executor::traits::ConvertOrigin;
@@ -257,6 +270,27 @@ pub mod pallet {
 				data.validate::<T>()
 			})
 		}
+
+		/// Overwrites the scheduling of the channels with the `sibling` parachain.
+		///
+		/// - `origin`: Must pass `Root`.
+		/// - `sibling`: The sibling parachain.
+		/// - `scheduling`: The new scheduling, or `None` to reset it to the default.
+		#[pallet::call_index(6)]
+		#[pallet::weight((T::WeightInfo::set_channel_scheduling(), DispatchClass::Operational,))]
+		pub fn set_channel_scheduling(
+			origin: OriginFor<T>,
+			sibling: ParaId,
+			scheduling: Option<ChannelScheduling>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			match scheduling {
+				Some(scheduling) => ChannelSchedulingConfig::<T>::insert(sibling, scheduling),
+				None => ChannelSchedulingConfig::<T>::remove(sibling),
+			}
+			Ok(())
+		}
 	}
 
 	#[pallet::hooks]
@@ -290,6 +324,12 @@ pub mod pallet {
 	pub enum Event<T: Config> {
 		/// An HRMP message was sent to a sibling parachain.
 		XcmpMessageSent { message_hash: XcmHash },
+		/// Inbound messages of a sibling parachain were dropped since the weight for enqueueing
+		/// them ran out.
+		InboundChannelStarved { sender: ParaId },
+		/// The outbound channel to a sibling parachain could not send for `blocks` blocks, since
+		/// channels with a higher priority took up all slots. It is served first from now on.
+		OutboundChannelStarved { recipient: ParaId, blocks: u32 },
 	}
 
 	#[pallet::error]
@@ -356,6 +396,21 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type QueueSuspended<T: Config> = StorageValue<_, bool, ValueQuery>;
 
+	/// The scheduling of the channels with a sibling parachain.
+	///
+	/// Siblings without an entry use the [`ChannelScheduling::default`].
+	#[pallet::storage]
+	pub type ChannelSchedulingConfig<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, ChannelScheduling, ValueQuery>;
+
+	/// The number of blocks that active outbound channels could not send a message, although they
+	/// had one queued, since channels with a higher priority took up all slots.
+	///
+	/// Only contains channels which are currently starved.
+	#[pallet::storage]
+	pub type OutboundStarvation<T: Config> =
+		StorageValue<_, BoundedBTreeMap<ParaId, u32, T::MaxActiveOutboundChannels>, ValueQuery>;
+
 	/// Initialization value for the DeliveryFee factor.
 	#[pallet::type_value]
 	pub fn InitialFactor() -> FixedU128 {
@@ -453,6 +508,51 @@ impl QueueConfigData {
 	}
 }
 
+/// The priority of the channels with a sibling parachain.
+#[derive(
+	Copy,
+	Clone,
+	Default,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+pub enum ChannelPriority {
+	Low,
+	#[default]
+	Normal,
+	High,
+}
+
+/// The scheduling of the channels with a sibling parachain.
+#[derive(Copy, Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ChannelScheduling {
+	/// Channels with a higher priority are served first, for inbound and outbound messages.
+	pub priority: ChannelPriority,
+	/// The share of the weight for enqueueing inbound messages that the messages of the sibling
+	/// may use before the messages of all other siblings were enqueued.
+	pub inbound_weight_quota: Perbill,
+	/// The number of blocks the outbound channel may go without sending a queued message, before
+	/// it is served ahead of channels with a higher priority. Zero means never.
+	pub max_outbound_starvation: u32,
+}
+
+impl Default for ChannelScheduling {
+	fn default() -> Self {
+		Self {
+			priority: ChannelPriority::Normal,
+			inbound_weight_quota: Perbill::one(),
+			max_outbound_starvation: 10,
+		}
+	}
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Encode, Decode, TypeInfo)]
 pub enum ChannelSignal {
 	Suspend,
@@ -749,6 +849,93 @@ impl<T: Config> QueuePausedQuery<ParaId> for Pallet<T> {
 	}
 }
 
+impl<T: Config> Pallet<T> {
+	/// Enqueue the messages of an XCMP `page` of `sender`.
+	///
+	/// Returns an error if the weight ran out before all messages of the page were enqueued.
+	fn handle_xcmp_page(
+		sender: ParaId,
+		mut data: &[u8],
+		meter: &mut WeightMeter,
+	) -> Result<(), ()> {
+		let format = match XcmpMessageFormat::decode(&mut data) {
+			Ok(f) => f,
+			Err(_) => {
+				defensive!("Unknown XCMP message format - dropping");
+				return Ok(())
+			},
+		};
+
+		match format {
+			XcmpMessageFormat::Signals =>
+				while !data.is_empty() {
+					if meter
+						.try_consume(
+							T::WeightInfo::suspend_channel().max(T::WeightInfo::resume_channel()),
+						)
+						.is_err()
+					{
+						defensive!("Not enough weight to process signals - dropping");
+						return Err(())
+					}
+
+					match ChannelSignal::decode(&mut data) {
+						Ok(ChannelSignal::Suspend) => Self::suspend_channel(sender),
+						Ok(ChannelSignal::Resume) => Self::resume_channel(sender),
+						Err(_) => {
+							defensive!("Undecodable channel signal - dropping");
+							break
+						},
+					}
+				},
+			XcmpMessageFormat::ConcatenatedVersionedXcm => {
+				// We need to know if the current message is the first on the current XCMP page
+				// for weight metering accuracy.
+				let mut is_first_xcm_on_page = true;
+				while !data.is_empty() {
+					let Ok(xcm) = Self::take_first_concatenated_xcm(&mut data, meter) else {
+						defensive!("HRMP inbound decode stream broke; page will be dropped.",);
+						break
+					};
+
+					// For simplicity, we consider that each new XCMP page results in a new
+					// message queue page. This is not always true, but it's a good enough
+					// estimation.
+					if meter
+						.try_consume(T::WeightInfo::enqueue_xcmp_message(
+							xcm.len(),
+							is_first_xcm_on_page,
+						))
+						.is_err()
+					{
+						defensive!(
+							"Out of weight: cannot enqueue XCMP messages; dropping msg; \
+							Used weight: ",
+							meter.consumed_ratio()
+						);
+						return Err(())
+					}
+
+					if let Err(()) = Self::enqueue_xcmp_message(sender, xcm) {
+						defensive!(
+							"Could not enqueue XCMP messages. Used weight: ",
+							meter.consumed_ratio()
+						);
+						break
+					}
+
+					is_first_xcm_on_page = false;
+				}
+			},
+			XcmpMessageFormat::ConcatenatedEncodedBlob => {
+				defensive!("Blob messages are unhandled - dropping");
+			},
+		}
+
+		Ok(())
+	}
+}
+
 impl<T: Config> XcmpMessageHandler for Pallet<T> {
 	fn handle_xcmp_messages<'a, I: Iterator<Item = (ParaId, RelayBlockNumber, &'a [u8])>>(
 		iter: I,
@@ -756,85 +943,61 @@ impl<T: Config> XcmpMessageHandler for Pallet<T> {
 	) -> Weight {
 		let mut meter = WeightMeter::with_limit(max_weight);
 
-		for (sender, _sent_at, mut data) in iter {
-			let format = match XcmpMessageFormat::decode(&mut data) {
-				Ok(f) => f,
-				Err(_) => {
-					defensive!("Unknown XCMP message format - dropping");
-					continue
+		// Enqueue the pages by the priority of their sender. The sort is stable, so the pages of a
+		// sender stay in order.
+		let mut schedulings = BTreeMap::new();
+		let mut pages = Vec::new();
+		for (sender, _sent_at, data) in iter {
+			let scheduling = match schedulings.get(&sender) {
+				Some(scheduling) => *scheduling,
+				None => {
+					if meter.try_consume(T::DbWeight::get().reads(1)).is_err() {
+						defensive!(
+							"Out of weight: cannot read the channel scheduling; dropping the \
+							remaining pages"
+						);
+						break
+					}
+					let scheduling = ChannelSchedulingConfig::<T>::get(sender);
+					schedulings.insert(sender, scheduling);
+					scheduling
 				},
 			};
+			pages.push((sender, scheduling, data));
+		}
+		pages.sort_by_key(|(_, scheduling, _)| Reverse(scheduling.priority));
+
+		// The pages of a sender that used up its quota are deferred until the pages of all other
+		// senders were enqueued.
+		let mut used = BTreeMap::<ParaId, Weight>::new();
+		let mut deferred = Vec::new();
+		let mut starved = BTreeSet::new();
+		for (sender, scheduling, data) in pages {
+			let sender_used = used.entry(sender).or_default();
+			if deferred.iter().any(|(deferred, _)| *deferred == sender) ||
+				sender_used.any_gte(scheduling.inbound_weight_quota * max_weight)
+			{
+				deferred.push((sender, data));
+				continue
+			}
 
-			match format {
-				XcmpMessageFormat::Signals =>
-					while !data.is_empty() {
-						if meter
-							.try_consume(
-								T::WeightInfo::suspend_channel()
-									.max(T::WeightInfo::resume_channel()),
-							)
-							.is_err()
-						{
-							defensive!("Not enough weight to process signals - dropping");
-							break
-						}
-
-						match ChannelSignal::decode(&mut data) {
-							Ok(ChannelSignal::Suspend) => Self::suspend_channel(sender),
-							Ok(ChannelSignal::Resume) => Self::resume_channel(sender),
-							Err(_) => {
-								defensive!("Undecodable channel signal - dropping");
-								break
-							},
-						}
-					},
-				XcmpMessageFormat::ConcatenatedVersionedXcm => {
-					// We need to know if the current message is the first on the current XCMP page
-					// for weight metering accuracy.
-					let mut is_first_xcm_on_page = true;
-					while !data.is_empty() {
-						let Ok(xcm) = Self::take_first_concatenated_xcm(&mut data, &mut meter)
-						else {
-							defensive!("HRMP inbound decode stream broke; page will be dropped.",);
-							break
-						};
-
-						// For simplicity, we consider that each new XCMP page results in a new
-						// message queue page. This is not always true, but it's a good enough
-						// estimation.
-						if meter
-							.try_consume(T::WeightInfo::enqueue_xcmp_message(
-								xcm.len(),
-								is_first_xcm_on_page,
-							))
-							.is_err()
-						{
-							defensive!(
-								"Out of weight: cannot enqueue XCMP messages; dropping msg; \
-								Used weight: ",
-								meter.consumed_ratio()
-							);
-							break;
-						}
-
-						if let Err(()) = Self::enqueue_xcmp_message(sender, xcm) {
-							defensive!(
-								"Could not enqueue XCMP messages. Used weight: ",
-								meter.consumed_ratio()
-							);
-							break
-						}
+			let consumed = meter.consumed();
+			if Self::handle_xcmp_page(sender, data, &mut meter).is_err() {
+				starved.insert(sender);
+			}
+			sender_used.saturating_accrue(meter.consumed().saturating_sub(consumed));
+		}
 
-						is_first_xcm_on_page = false;
-					}
-				},
-				XcmpMessageFormat::ConcatenatedEncodedBlob => {
-					defensive!("Blob messages are unhandled - dropping");
-					continue
-				},
+		for (sender, data) in deferred {
+			if Self::handle_xcmp_page(sender, data, &mut meter).is_err() {
+				starved.insert(sender);
 			}
 		}
 
+		for sender in starved {
+			Self::deposit_event(Event::InboundChannelStarved { sender });
+		}
+
 		meter.consumed()
 	}
 }
@@ -842,11 +1005,36 @@ impl<T: Config> XcmpMessageHandler for Pallet<T> {
 impl<T: Config> XcmpMessageSource for Pallet<T> {
 	fn take_outbound_messages(maximum_channels: usize) -> Vec<(ParaId, Vec<u8>)> {
 		let mut statuses = <OutboundXcmpStatus<T>>::get();
-		let old_statuses_len = statuses.len();
 		let max_message_count = statuses.len().min(maximum_channels);
 		let mut result = Vec::with_capacity(max_message_count);
+		let old_starvation = <OutboundStarvation<T>>::get();
+		let mut starvation = old_starvation.clone().into_inner();
+
+		// Serve the channels by the priority of their recipient. Channels that were starved for too
+		// long are served first. The sort is stable, so channels of the same priority are served in
+		// the order of `statuses`.
+		let schedulings = statuses
+			.iter()
+			.map(|status| <ChannelSchedulingConfig<T>>::get(status.recipient))
+			.collect::<Vec<_>>();
+		let mut order = statuses
+			.iter()
+			.zip(&schedulings)
+			.enumerate()
+			.map(|(index, (status, scheduling))| {
+				let starved = starvation.get(&status.recipient).map_or(false, |blocks| {
+					scheduling.max_outbound_starvation > 0 &&
+						*blocks >= scheduling.max_outbound_starvation
+				});
+				(index, Reverse((starved, scheduling.priority)))
+			})
+			.collect::<Vec<_>>();
+		order.sort_by_key(|(_, key)| *key);
 
-		for status in statuses.iter_mut() {
+		let mut served = BTreeSet::new();
+		let mut starved = Vec::new();
+		for (index, _) in order {
+			let status = &mut statuses[index];
 			let OutboundChannelDetails {
 				recipient: para_id,
 				state: outbound_state,
@@ -876,7 +1064,11 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 			if result.len() == max_message_count {
 				// We check this condition in the beginning of the loop so that we don't include
 				// a message where the limit is 0.
-				break
+				if signals_exist || (outbound_state == OutboundState::Ok && last_index > first_index)
+				{
+					starved.push(index);
+				}
+				continue
 			}
 
 			let page = if signals_exist {
@@ -919,6 +1111,8 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 			} else {
 				result.push((para_id, page.into_inner()));
 			}
+			served.insert(index);
+			starvation.remove(&para_id);
 
 			let max_total_size = match T::ChannelInfo::get_channel_info(para_id) {
 				Some(channel_info) => channel_info.max_total_size,
@@ -949,30 +1143,50 @@ impl<T: Config> XcmpMessageSource for Pallet<T> {
 		// criteria requirement.
 		result.sort_by_key(|m| m.0);
 
-		// Prune hrmp channels that became empty. Additionally, because it may so happen that we
-		// only gave attention to some channels in `non_empty_hrmp_channels` it's important to
-		// change the order. Otherwise, the next `on_finalize` we will again give attention
-		// only to those channels that happen to be in the beginning, until they are emptied.
-		// This leads to "starvation" of the channels near to the end.
-		//
-		// To mitigate this we shift all processed elements towards the end of the vector using
-		// `rotate_left`. To get intuition how it works see the examples in its rustdoc.
+		for index in starved {
+			let recipient = statuses[index].recipient;
+			let blocks = starvation.entry(recipient).or_default();
+			*blocks = blocks.saturating_add(1);
+			if *blocks == schedulings[index].max_outbound_starvation {
+				log::warn!(
+					target: LOG_TARGET,
+					"Outbound XCMP channel to sibling {recipient:?} starved for {blocks} blocks.",
+				);
+				Self::deposit_event(Event::OutboundChannelStarved { recipient, blocks: *blocks });
+			}
+		}
+
+		// Move the channels that were served to the end and prune the ones that became empty.
+		// Otherwise, the next `on_finalize` we will again give attention only to those channels
+		// that happen to be in the beginning, until they are emptied. This leads to "starvation" of
+		// the channels near to the end.
+		let (mut statuses, served): (Vec<_>, Vec<_>) =
+			statuses.into_iter().enumerate().partition(|(index, _)| !served.contains(index));
+		statuses.extend(served);
+		let mut statuses = statuses.into_iter().map(|(_, status)| status).collect::<Vec<_>>();
 		statuses.retain(|x| {
 			x.state == OutboundState::Suspended || x.signals_exist || x.first_index < x.last_index
 		});
 
-		// old_status_len must be >= status.len() since we never add anything to status.
-		let pruned = old_statuses_len - statuses.len();
-		// removing an item from status implies a message being sent, so the result messages must
-		// be no less than the pruned channels.
-		let _ = statuses.try_rotate_left(result.len().saturating_sub(pruned)).defensive_proof(
-			"Could not store HRMP channels config. Some HRMP channels may be broken.",
-		);
+		starvation.retain(|recipient, _| statuses.iter().any(|s| s.recipient == *recipient));
+		let starvation = BoundedBTreeMap::try_from(starvation)
+			.defensive_proof("Only active outbound channels are starved; qed")
+			.unwrap_or_default();
+		if starvation != old_starvation {
+			<OutboundStarvation<T>>::put(starvation);
+		}
 
-		<OutboundXcmpStatus<T>>::put(statuses);
+		<OutboundXcmpStatus<T>>::put(BoundedVec::truncate_from(statuses));
 
 		result
 	}
+
+	fn take_outbound_messages_overhead() -> Weight {
+		// The scheduling config of every outbound channel is read once and `OutboundStarvation` is
+		// read and possibly written, on top of reading the length of `OutboundXcmpStatus` here.
+		let channels = <OutboundXcmpStatus<T>>::decode_len().unwrap_or(0) as u64;
+		T::DbWeight::get().reads_writes(channels.saturating_add(2), 1)
+	}
 }
 
 /// Xcm sender for sending to a sibling parachain.
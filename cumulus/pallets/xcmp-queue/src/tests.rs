@@ -943,3 +943,87 @@ fn page_not_modified_when_fragment_does_not_fit() {
 		}
 	});
 }
+
+#[test]
+fn inbound_pages_are_enqueued_by_priority_and_quota() {
+	new_test_ext().execute_with(|| {
+		let xcm = VersionedXcm::<Test>::from(Xcm::<Test>(vec![ClearOrigin])).encode();
+		let data = [ConcatenatedVersionedXcm.encode(), xcm.clone()].concat();
+		let pages = [1000, 2000, 3000].map(|para| (ParaId::from(para), 1, data.as_slice()));
+
+		assert_noop!(
+			XcmpQueue::set_channel_scheduling(Origin::signed(2), 3000.into(), None),
+			BadOrigin
+		);
+		assert_ok!(XcmpQueue::set_channel_scheduling(
+			Origin::root(),
+			3000.into(),
+			Some(ChannelScheduling { priority: ChannelPriority::High, ..Default::default() }),
+		));
+		assert_ok!(XcmpQueue::set_channel_scheduling(
+			Origin::root(),
+			1000.into(),
+			Some(ChannelScheduling { inbound_weight_quota: Perbill::zero(), ..Default::default() }),
+		));
+
+		XcmpQueue::handle_xcmp_messages(pages.into_iter(), Weight::MAX);
+
+		// The high priority sender first, the sender without quota last.
+		assert_eq!(
+			EnqueuedMessages::get(),
+			vec![(3000.into(), xcm.clone()), (2000.into(), xcm.clone()), (1000.into(), xcm)]
+		);
+	})
+}
+
+#[test]
+fn starved_outbound_channel_is_served_first() {
+	let (low, high) = (ParaId::from(HRMP_PARA_ID), ParaId::from(3000));
+	new_test_ext().execute_with(|| {
+		frame_system::Pallet::<Test>::set_block_number(1);
+		ParachainSystem::open_outbound_hrmp_channel_for_benchmarks_or_tests(high);
+		assert_ok!(XcmpQueue::set_channel_scheduling(
+			Origin::root(),
+			low,
+			Some(ChannelScheduling {
+				priority: ChannelPriority::Low,
+				max_outbound_starvation: 2,
+				..Default::default()
+			}),
+		));
+		assert_ok!(XcmpQueue::set_channel_scheduling(
+			Origin::root(),
+			high,
+			Some(ChannelScheduling { priority: ChannelPriority::High, ..Default::default() }),
+		));
+
+		let page = |index: u8| (ConcatenatedVersionedXcm, index).encode();
+		for index in 0..3 {
+			OutboundXcmpMessages::<Test>::insert(
+				high,
+				index as u16,
+				WeakBoundedVec::force_from(page(index), None),
+			);
+		}
+		OutboundXcmpMessages::<Test>::insert(low, 0, WeakBoundedVec::force_from(page(9), None));
+		let mut low_details = OutboundChannelDetails::new(low);
+		low_details.last_index = 1;
+		let mut high_details = OutboundChannelDetails::new(high);
+		high_details.last_index = 3;
+		OutboundXcmpStatus::<Test>::put(BoundedVec::truncate_from(vec![low_details, high_details]));
+
+		// The high priority channel is served while the low priority one starves.
+		assert_eq!(XcmpQueue::take_outbound_messages(1), vec![(high, page(0))]);
+		assert_eq!(OutboundStarvation::<Test>::get().get(&low), Some(&1));
+		assert_eq!(XcmpQueue::take_outbound_messages(1), vec![(high, page(1))]);
+		assert_eq!(OutboundStarvation::<Test>::get().get(&low), Some(&2));
+		frame_system::Pallet::<Test>::assert_has_event(
+			Event::OutboundChannelStarved { recipient: low, blocks: 2 }.into(),
+		);
+
+		// Until it starved for too long.
+		assert_eq!(XcmpQueue::take_outbound_messages(1), vec![(low, page(9))]);
+		assert_eq!(OutboundStarvation::<Test>::get().get(&low), None);
+		assert_eq!(XcmpQueue::take_outbound_messages(1), vec![(high, page(2))]);
+	})
+}
@@ -48,6 +48,7 @@ use core::marker::PhantomData;
 /// Weight functions needed for `cumulus_pallet_xcmp_queue`.
 pub trait WeightInfo {
 	fn set_config_with_u32() -> Weight;
+	fn set_channel_scheduling() -> Weight;
 	fn enqueue_n_bytes_xcmp_message(n: u32, ) -> Weight;
 	fn enqueue_2_empty_xcmp_messages() -> Weight;
 	fn suspend_channel() -> Weight;
@@ -71,6 +72,16 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1_u64))
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
+	/// Storage: `XcmpQueue::ChannelSchedulingConfig` (r:0 w:1)
+	/// Proof: `XcmpQueue::ChannelSchedulingConfig` (`max_values`: None, `max_size`: Some(21), added: 2496, mode: `MaxEncodedLen`)
+	fn set_channel_scheduling() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 2_124_000 picoseconds.
+		Weight::from_parts(2_374_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
 	/// Storage: `XcmpQueue::QueueConfig` (r:1 w:0)
 	/// Proof: `XcmpQueue::QueueConfig` (`max_values`: Some(1), `max_size`: Some(12), added: 507, mode: `MaxEncodedLen`)
 	/// Storage: `MessageQueue::BookStateFor` (r:1 w:1)
@@ -202,6 +213,16 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1_u64))
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
+	/// Storage: `XcmpQueue::ChannelSchedulingConfig` (r:0 w:1)
+	/// Proof: `XcmpQueue::ChannelSchedulingConfig` (`max_values`: None, `max_size`: Some(21), added: 2496, mode: `MaxEncodedLen`)
+	fn set_channel_scheduling() -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `0`
+		//  Estimated: `0`
+		// Minimum execution time: 2_124_000 picoseconds.
+		Weight::from_parts(2_374_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
 	/// Storage: `XcmpQueue::QueueConfig` (r:1 w:0)
 	/// Proof: `XcmpQueue::QueueConfig` (`max_values`: Some(1), `max_size`: Some(12), added: 507, mode: `MaxEncodedLen`)
 	/// Storage: `MessageQueue::BookStateFor` (r:1 w:1)
@@ -1165,6 +1165,10 @@ pub type Migrations = (
 		Runtime,
 		pallet_session::migrations::v1::InitOffenceSeverity<Runtime>,
 	>,
+	// unreleased
+	pallet_assets_freezer::migration::v1::MigrateV0ToV1<Runtime, AssetsFreezerInstance>,
+	pallet_assets_freezer::migration::v1::MigrateV0ToV1<Runtime, ForeignAssetsFreezerInstance>,
+	pallet_assets_freezer::migration::v1::MigrateV0ToV1<Runtime, PoolAssetsFreezerInstance>,
 	// permanent
 	pallet_xcm::migration::MigrateToLatestXcmVersion<Runtime>,
 	cumulus_pallet_aura_ext::migration::MigrateV0ToV1<Runtime>,
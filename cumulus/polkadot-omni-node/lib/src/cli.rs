@@ -20,11 +20,14 @@ use crate::{
 	chain_spec::DiskChainSpecLoader,
 	common::{
 		chain_spec::{Extensions, LoadSpec},
+		pov_profile::ProfilePovCmd,
 		NodeExtraArgs,
 	},
+	nodes::RelayEmulatorConfig,
 };
 use chain_spec_builder::ChainSpecBuilder;
 use clap::{Command, CommandFactory, FromArgMatches, ValueEnum};
+use cumulus_client_consensus_aura::collators::slot_based::CoreAcquisitionConfig;
 use sc_chain_spec::ChainSpec;
 use sc_cli::{
 	CliConfiguration, DefaultConfigurationValues, ImportParams, KeystoreParams, NetworkParams,
@@ -128,6 +131,14 @@ pub enum Subcommand {
 	/// Export the genesis wasm of the parachain.
 	ExportGenesisWasm(cumulus_client_cli::ExportGenesisWasmCommand),
 
+	/// Export the head data of a block, e.g. the last block of a solochain migrating to a
+	/// parachain, to register it as the genesis head of the parachain.
+	ExportHead(cumulus_client_cli::ExportHeadCommand),
+
+	/// Re-execute a block with proof recording and break down the size of its PoV by storage
+	/// item, pallet and extrinsic.
+	ProfilePov(ProfilePovCmd),
+
 	/// Sub-commands concerned with benchmarking.
 	/// The pallet benchmarking moved to the `pallet` sub-command.
 	#[command(subcommand)]
@@ -166,6 +177,27 @@ pub struct Cli<Config: CliConfig> {
 	#[arg(long)]
 	pub dev_block_time: Option<u64>,
 
+	/// Emulate a relay chain in dev mode, instead of mocking the relay chain state.
+	///
+	/// Each block is built on a new emulated relay block, which delivers the downward and
+	/// horizontal messages sent since the previous one. Messages can be sent with the
+	/// `relayEmulator_*` RPC methods or `--relay-emulator-messages`.
+	#[arg(long)]
+	pub relay_emulator: bool,
+
+	/// A JSON file with messages delivered in the first emulated relay block.
+	///
+	/// The file may contain `downward` messages, `horizontal` messages with a `sender` para id and
+	/// `data`, and `outboundChannels` to open, with messages given as hex strings.
+	#[arg(long, requires = "relay_emulator")]
+	pub relay_emulator_messages: Option<PathBuf>,
+
+	/// The number of blocks after which the emulated relay chain includes a block.
+	///
+	/// Must be lower than the unincluded segment capacity of the runtime.
+	#[arg(long, default_value_t = 0, requires = "relay_emulator")]
+	pub relay_emulator_inclusion_delay: u32,
+
 	/// DEPRECATED: This feature has been stabilized, pLease use `--authoring slot-based` instead.
 	///
 	/// Use slot-based collator which can handle elastic scaling.
@@ -194,6 +226,19 @@ pub struct Cli<Config: CliConfig> {
 	#[arg(long)]
 	pub export_pov_to_path: Option<PathBuf>,
 
+	/// Recover blocks from `PoVs` exported to the given folder, if they can not be recovered from
+	/// the relay chain.
+	///
+	/// The availability data of the relay chain is pruned after about a day. A folder filled with
+	/// `--export-pov-to-path` can be used to recover blocks older than that. Can be passed multiple
+	/// times, the folders are tried in order.
+	#[arg(long)]
+	pub pov_recovery_path: Vec<PathBuf>,
+
+	/// Order on-demand cores from the relay chain when the parachain is congested.
+	#[command(flatten)]
+	pub core_acquisition: CoreAcquisitionArgs,
+
 	/// Relay chain arguments
 	#[arg(raw = true)]
 	pub relay_chain_args: Vec<String>,
@@ -223,6 +268,63 @@ impl Display for AuthoringPolicy {
 	}
 }
 
+/// Options of the on-demand core acquisition of the slot-based collator.
+#[derive(Debug, Clone, clap::Args)]
+pub struct CoreAcquisitionArgs {
+	/// Order on-demand cores when the parachain is congested, spending at most this amount per
+	/// budget period.
+	///
+	/// Requires `--authoring slot-based`. The orders are paid by the first sr25519 key of type
+	/// `acco` in the keystore.
+	#[arg(long, requires = "on_demand_pallet_index", requires = "on_demand_max_amount_per_order")]
+	pub on_demand_budget: Option<u128>,
+
+	/// The index of the on-demand pallet in the relay chain runtime.
+	#[arg(long, requires = "on_demand_budget")]
+	pub on_demand_pallet_index: Option<u8>,
+
+	/// The maximum price to pay for a single on-demand order.
+	#[arg(long, requires = "on_demand_budget")]
+	pub on_demand_max_amount_per_order: Option<u128>,
+
+	/// The length of an on-demand budget period, in relay chain blocks.
+	#[arg(long, default_value_t = 14_400)]
+	pub on_demand_budget_period: u32,
+
+	/// Order an on-demand core once this many transactions are ready in the pool.
+	#[arg(long, default_value_t = 100)]
+	pub on_demand_ready_transactions_threshold: usize,
+
+	/// Order an on-demand core once this many blocks are waiting in the unincluded segment.
+	#[arg(long, default_value_t = 3)]
+	pub on_demand_unincluded_blocks_threshold: u32,
+
+	/// Don't order an on-demand core while the claim queue holds this many claims for the
+	/// parachain.
+	#[arg(long, default_value_t = 2)]
+	pub on_demand_max_claims: u32,
+
+	/// The minimum number of relay chain blocks between two on-demand orders.
+	#[arg(long, default_value_t = 5)]
+	pub on_demand_order_cooldown: u32,
+}
+
+impl CoreAcquisitionArgs {
+	/// The core acquisition config, if `--on-demand-budget` is set.
+	pub(crate) fn config(&self) -> Option<CoreAcquisitionConfig> {
+		Some(CoreAcquisitionConfig {
+			on_demand_pallet_index: self.on_demand_pallet_index?,
+			ready_transactions_threshold: self.on_demand_ready_transactions_threshold,
+			unincluded_blocks_threshold: self.on_demand_unincluded_blocks_threshold,
+			max_claims: self.on_demand_max_claims,
+			max_amount_per_order: self.on_demand_max_amount_per_order?,
+			budget: self.on_demand_budget?,
+			budget_period: self.on_demand_budget_period,
+			order_cooldown: self.on_demand_order_cooldown,
+		})
+	}
+}
+
 impl<Config: CliConfig> Cli<Config> {
 	pub(crate) fn node_extra_args(&self) -> NodeExtraArgs {
 		NodeExtraArgs {
@@ -231,9 +333,20 @@ impl<Config: CliConfig> Cli<Config> {
 				.then(|| AuthoringPolicy::SlotBased)
 				.unwrap_or(self.authoring),
 			export_pov: self.export_pov_to_path.clone(),
+			pov_recovery_paths: self.pov_recovery_path.clone(),
 			max_pov_percentage: self.run.experimental_max_pov_percentage,
+			core_acquisition: self.core_acquisition.config(),
+			extensions: Default::default(),
+			refused_build_attempt: Default::default(),
 		}
 	}
+
+	pub(crate) fn relay_emulator_config(&self) -> Option<RelayEmulatorConfig> {
+		self.relay_emulator.then(|| RelayEmulatorConfig {
+			inclusion_delay: self.relay_emulator_inclusion_delay,
+			messages: self.relay_emulator_messages.clone(),
+		})
+	}
 }
 
 impl<Config: CliConfig> SubstrateCli for Cli<Config> {
@@ -18,6 +18,7 @@ use crate::{
 	cli::{Cli, RelayChainCli, Subcommand},
 	common::{
 		chain_spec::{Extensions, LoadSpec},
+		extension::{NodeExtension, NodeExtensions},
 		runtime::{
 			AuraConsensusId, Consensus, Runtime, RuntimeResolver as RuntimeResolverT,
 			RuntimeResolver,
@@ -38,6 +39,7 @@ use sc_cli::{CliConfiguration, Result, SubstrateCli};
 use sp_runtime::traits::AccountIdConversion;
 #[cfg(feature = "runtime-benchmarks")]
 use sp_runtime::traits::HashingFor;
+use std::sync::Arc;
 
 const DEFAULT_DEV_BLOCK_TIME_MS: u64 = 3000;
 
@@ -48,6 +50,8 @@ pub struct RunConfig {
 	pub chain_spec_loader: Box<dyn LoadSpec>,
 	/// A custom runtime resolver.
 	pub runtime_resolver: Box<dyn RuntimeResolver>,
+	/// Extensions providing extra RPC methods and background tasks.
+	pub extensions: Vec<Arc<dyn NodeExtension>>,
 }
 
 impl RunConfig {
@@ -56,7 +60,16 @@ impl RunConfig {
 		runtime_resolver: Box<dyn RuntimeResolver>,
 		chain_spec_loader: Box<dyn LoadSpec>,
 	) -> Self {
-		RunConfig { chain_spec_loader, runtime_resolver }
+		RunConfig { chain_spec_loader, runtime_resolver, extensions: Vec::new() }
+	}
+
+	/// Register a node extension.
+	///
+	/// The extension is only enabled if it applies to the runtime at the best block when the node
+	/// starts.
+	pub fn with_extension(mut self, extension: impl NodeExtension + 'static) -> Self {
+		self.extensions.push(Arc::new(extension));
+		self
 	}
 }
 
@@ -176,6 +189,22 @@ pub fn run<CliConfig: crate::cli::CliConfig>(cmd_config: RunConfig) -> Result<()
 				node.run_export_genesis_head_cmd(config, cmd)
 			})
 		},
+		Some(Subcommand::ExportHead(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let node =
+					new_node_spec(&config, &cmd_config.runtime_resolver, &cli.node_extra_args())?;
+				node.run_export_head_cmd(config, cmd)
+			})
+		},
+		Some(Subcommand::ProfilePov(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let node =
+					new_node_spec(&config, &cmd_config.runtime_resolver, &cli.node_extra_args())?;
+				node.run_profile_pov_cmd(config, cmd)
+			})
+		},
 		Some(Subcommand::ExportGenesisWasm(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 			runner.sync_run(|_config| {
@@ -236,26 +265,41 @@ pub fn run<CliConfig: crate::cli::CliConfig>(cmd_config: RunConfig) -> Result<()
 			}
 
 			runner.run_node_until_exit(|config| async move {
+				let mut node_extra_args = cli.node_extra_args();
 				let node_spec =
-					new_node_spec(&config, &cmd_config.runtime_resolver, &cli.node_extra_args())?;
+					new_node_spec(&config, &cmd_config.runtime_resolver, &node_extra_args)?;
 				let para_id = ParaId::from(
 					Extensions::try_get(&*config.chain_spec)
 						.map(|e| e.para_id)
 						.ok_or("Could not find parachain extension in chain-spec.")?,
 				);
+				node_extra_args.extensions =
+					NodeExtensions::registered(cmd_config.extensions.clone());
 
 				if cli.run.base.is_dev()? {
 					// Set default dev block time to 3000ms if not set.
 					// TODO: take block time from AURA config if set.
 					let dev_block_time = cli.dev_block_time.unwrap_or(DEFAULT_DEV_BLOCK_TIME_MS);
 					return node_spec
-						.start_manual_seal_node(config, para_id, dev_block_time)
+						.start_manual_seal_node(
+							config,
+							para_id,
+							dev_block_time,
+							cli.relay_emulator_config(),
+							node_extra_args.extensions,
+						)
 						.map_err(Into::into);
 				}
 
 				if let Some(dev_block_time) = cli.dev_block_time {
 					return node_spec
-						.start_manual_seal_node(config, para_id, dev_block_time)
+						.start_manual_seal_node(
+							config,
+							para_id,
+							dev_block_time,
+							cli.relay_emulator_config(),
+							node_extra_args.extensions,
+						)
 						.map_err(Into::into);
 				}
 
@@ -331,7 +375,7 @@ pub fn run<CliConfig: crate::cli::CliConfig>(cmd_config: RunConfig) -> Result<()
 						collator_options,
 						para_id,
 						hwbench,
-						cli.node_extra_args(),
+						node_extra_args,
 					)
 					.await
 					.map_err(Into::into)
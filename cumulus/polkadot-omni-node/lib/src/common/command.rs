@@ -14,8 +14,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::common::spec::BaseNodeSpec;
-use cumulus_client_cli::ExportGenesisHeadCommand;
+use crate::common::{pov_profile::ProfilePovCmd, spec::BaseNodeSpec};
+use cumulus_client_cli::{ExportGenesisHeadCommand, ExportHeadCommand};
 use frame_benchmarking_cli::BlockCmd;
 #[cfg(any(feature = "runtime-benchmarks"))]
 use frame_benchmarking_cli::StorageCmd;
@@ -65,6 +65,18 @@ pub trait NodeCommandRunner {
 		cmd: &ExportGenesisHeadCommand,
 	) -> SyncCmdResult;
 
+	fn run_export_head_cmd(
+		self: Box<Self>,
+		config: Configuration,
+		cmd: &ExportHeadCommand,
+	) -> SyncCmdResult;
+
+	fn run_profile_pov_cmd(
+		self: Box<Self>,
+		config: Configuration,
+		cmd: &ProfilePovCmd,
+	) -> SyncCmdResult;
+
 	fn run_benchmark_block_cmd(
 		self: Box<Self>,
 		config: Configuration,
@@ -137,6 +149,24 @@ where
 		cmd.run(partial.client)
 	}
 
+	fn run_export_head_cmd(
+		self: Box<Self>,
+		config: Configuration,
+		cmd: &ExportHeadCommand,
+	) -> SyncCmdResult {
+		let partial = T::new_partial(&config).map_err(sc_cli::Error::Service)?;
+		cmd.run(partial.client)
+	}
+
+	fn run_profile_pov_cmd(
+		self: Box<Self>,
+		config: Configuration,
+		cmd: &ProfilePovCmd,
+	) -> SyncCmdResult {
+		let partial = T::new_partial(&config).map_err(sc_cli::Error::Service)?;
+		cmd.run(partial.client)
+	}
+
 	fn run_benchmark_block_cmd(
 		self: Box<Self>,
 		config: Configuration,
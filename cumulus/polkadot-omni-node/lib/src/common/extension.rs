@@ -0,0 +1,384 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Node extensions.
+//!
+//! Downstream crates can register [`NodeExtension`]s in the [`crate::RunConfig`] to add RPC
+//! methods and background tasks to the node, without maintaining a fork of the node binary.
+//! Each extension decides whether it applies to the runtime of the chain that is started, based
+//! on the metadata of the runtime at the best block when the node starts, e.g. only when a
+//! specific pallet or runtime API is present. Enablement is not re-evaluated while the node runs,
+//! so the node must be restarted for a runtime upgrade to enable or disable extensions.
+//!
+//! As the node does not know the runtime APIs of the chain, extensions talk to the runtime
+//! through the untyped [`ChainClient`] and [`ChainTransactionPool`], i.e. with SCALE encoded
+//! runtime calls, extrinsics and raw storage.
+
+#![warn(missing_docs)]
+
+use crate::common::{
+	rpc::RpcExtension,
+	runtime::MetadataInspector,
+	types::{Hash, ParachainBackend, ParachainClient},
+	ConstructNodeRuntimeApi, NodeBlock,
+};
+use codec::Decode;
+use cumulus_primitives_core::ParaId;
+use futures::{
+	future::{self, BoxFuture},
+	stream::BoxStream,
+	FutureExt, StreamExt, TryFutureExt,
+};
+use prometheus_endpoint::Registry;
+use sc_client_api::{
+	AuxStore, BlockchainEvents, CallExecutor, ExecutorProvider, StorageKey, StorageProvider,
+};
+use sc_client_db::offchain::LocalStorage;
+use sc_service::SpawnTaskHandle;
+use sc_transaction_pool::TransactionPoolHandle;
+use sc_transaction_pool_api::{TransactionPool, TransactionSource};
+use sp_core::traits::CallContext;
+use sp_runtime::traits::Block as BlockT;
+use std::sync::Arc;
+
+/// Errors returned by extensions.
+pub type ExtensionError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Access to the chain for extensions, independent of the runtime of the chain.
+pub trait ChainClient: Send + Sync {
+	/// The hash of the best block.
+	fn best_hash(&self) -> Hash;
+
+	/// The hash of the last finalized block.
+	fn finalized_hash(&self) -> Hash;
+
+	/// Read a storage value of the state at the given block.
+	fn storage(&self, at: Hash, key: &[u8]) -> Result<Option<Vec<u8>>, ExtensionError>;
+
+	/// Call a runtime API function, e.g. `MmrApi_mmr_root`, in the state at the given block.
+	///
+	/// Arguments and the result are SCALE encoded.
+	fn call(&self, at: Hash, method: &str, data: &[u8]) -> Result<Vec<u8>, ExtensionError>;
+
+	/// A stream of the hashes of imported blocks.
+	fn import_notification_stream(&self) -> BoxStream<'static, Hash>;
+}
+
+impl<Block, RuntimeApi> ChainClient for ParachainClient<Block, RuntimeApi>
+where
+	Block: NodeBlock,
+	RuntimeApi: ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>>,
+{
+	fn best_hash(&self) -> Hash {
+		self.chain_info().best_hash
+	}
+
+	fn finalized_hash(&self) -> Hash {
+		self.chain_info().finalized_hash
+	}
+
+	fn storage(&self, at: Hash, key: &[u8]) -> Result<Option<Vec<u8>>, ExtensionError> {
+		let value = StorageProvider::storage(self, at, &StorageKey(key.to_vec()))?;
+		Ok(value.map(|data| data.0))
+	}
+
+	fn call(&self, at: Hash, method: &str, data: &[u8]) -> Result<Vec<u8>, ExtensionError> {
+		Ok(self.executor().call(at, method, data, CallContext::Offchain)?)
+	}
+
+	fn import_notification_stream(&self) -> BoxStream<'static, Hash> {
+		BlockchainEvents::import_notification_stream(self)
+			.map(|notification| notification.hash)
+			.boxed()
+	}
+}
+
+/// Access to the transaction pool for extensions, with SCALE encoded extrinsics.
+pub trait ChainTransactionPool: Send + Sync {
+	/// Submit an extrinsic, validated against the state at the given block.
+	///
+	/// Returns the hash of the transaction.
+	fn submit(&self, at: Hash, extrinsic: &[u8]) -> BoxFuture<'_, Result<Hash, ExtensionError>>;
+
+	/// The number of transactions ready to be included in a block.
+	fn ready_count(&self) -> usize;
+}
+
+impl<Block, RuntimeApi> ChainTransactionPool
+	for TransactionPoolHandle<Block, ParachainClient<Block, RuntimeApi>>
+where
+	Block: NodeBlock,
+	RuntimeApi: ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>>,
+{
+	fn submit(&self, at: Hash, extrinsic: &[u8]) -> BoxFuture<'_, Result<Hash, ExtensionError>> {
+		let extrinsic = match <Block as BlockT>::Extrinsic::decode(&mut &extrinsic[..]) {
+			Ok(extrinsic) => extrinsic,
+			Err(err) => return future::ready(Err(err.into())).boxed(),
+		};
+
+		TransactionPool::submit_one(self, at, TransactionSource::Local, extrinsic)
+			.map_err(Into::into)
+			.boxed()
+	}
+
+	fn ready_count(&self) -> usize {
+		TransactionPool::status(self).ready
+	}
+}
+
+/// Access to the auxiliary storage of the node database for extensions.
+///
+/// Auxiliary values are not part of the chain state and are shared with the rest of the node, so
+/// extensions should prefix their keys with their name.
+pub trait ChainBackend: Send + Sync {
+	/// Read an auxiliary value.
+	fn get_aux(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ExtensionError>;
+
+	/// Insert and delete auxiliary values, atomically.
+	fn insert_aux(&self, insert: &[(&[u8], &[u8])], delete: &[&[u8]])
+		-> Result<(), ExtensionError>;
+}
+
+impl<Block: NodeBlock> ChainBackend for ParachainBackend<Block> {
+	fn get_aux(&self, key: &[u8]) -> Result<Option<Vec<u8>>, ExtensionError> {
+		Ok(AuxStore::get_aux(self, key)?)
+	}
+
+	fn insert_aux(
+		&self,
+		insert: &[(&[u8], &[u8])],
+		delete: &[&[u8]],
+	) -> Result<(), ExtensionError> {
+		Ok(AuxStore::insert_aux(self, insert.iter(), delete.iter())?)
+	}
+}
+
+/// The node services available to extensions.
+#[derive(Clone)]
+pub struct ExtensionContext {
+	/// Access to the parachain.
+	pub client: Arc<dyn ChainClient>,
+	/// The transaction pool of the node.
+	pub transaction_pool: Arc<dyn ChainTransactionPool>,
+	/// The auxiliary storage of the node database.
+	pub backend: Arc<dyn ChainBackend>,
+	/// The offchain storage, shared with the offchain workers of the runtime.
+	pub offchain_storage: Option<LocalStorage>,
+	/// Handle for spawning background tasks, which are stopped with the node.
+	pub spawn_handle: SpawnTaskHandle,
+	/// The Prometheus registry of the node, if metrics are enabled.
+	pub prometheus_registry: Option<Registry>,
+	/// The id of the parachain.
+	pub para_id: ParaId,
+	/// Whether the node runs in dev mode, i.e. with manual seal instead of a relay chain.
+	pub is_dev: bool,
+}
+
+/// An extension of the node, providing extra RPC methods and background tasks.
+pub trait NodeExtension: Send + Sync {
+	/// The name of the extension, used in logs.
+	fn name(&self) -> &'static str;
+
+	/// Whether the extension should be enabled for the runtime of the chain.
+	///
+	/// Called once when the node starts, with the metadata of the runtime at the best block.
+	/// `metadata` is `None` if the metadata could not be fetched, e.g. because the runtime only
+	/// provides metadata older than v14.
+	fn is_enabled(&self, metadata: Option<&MetadataInspector>) -> bool;
+
+	/// Extra RPC methods, merged into the RPC server of the node.
+	///
+	/// Method names must not clash with the ones of the node or other extensions.
+	fn rpc_module(&self, _ctx: &ExtensionContext) -> Result<Option<RpcExtension>, ExtensionError> {
+		Ok(None)
+	}
+
+	/// Spawn the background tasks of the extension, called once when the node starts.
+	fn spawn_tasks(&self, _ctx: &ExtensionContext) -> Result<(), ExtensionError> {
+		Ok(())
+	}
+}
+
+/// The extensions registered for the node, or the ones enabled for the chain the node runs.
+#[derive(Clone, Default)]
+pub(crate) struct NodeExtensions(Vec<Arc<dyn NodeExtension>>);
+
+impl NodeExtensions {
+	/// All the registered extensions, before checking which ones are enabled.
+	pub(crate) fn registered(extensions: Vec<Arc<dyn NodeExtension>>) -> Self {
+		Self(extensions)
+	}
+
+	/// Select the extensions enabled for the runtime at the best block of `client`.
+	pub(crate) fn enabled_at_best_block(&self, client: &dyn ChainClient) -> Self {
+		if self.is_empty() {
+			return Self::default()
+		}
+
+		let metadata_inspector = client
+			.storage(client.best_hash(), sp_storage::well_known_keys::CODE)
+			.and_then(|code| code.ok_or_else(|| "the state does not contain code".into()))
+			.and_then(|code| {
+				MetadataInspector::from_code(&code).map_err(|err| err.to_string().into())
+			})
+			.inspect_err(|err| {
+				log::warn!("Unable to check metadata for node extensions: {err}");
+			})
+			.ok();
+		Self::enabled_for_metadata(&self.0, metadata_inspector.as_ref())
+	}
+
+	fn enabled_for_metadata(
+		extensions: &[Arc<dyn NodeExtension>],
+		metadata_inspector: Option<&MetadataInspector>,
+	) -> Self {
+		Self(
+			extensions
+				.iter()
+				.filter(|extension| {
+					let enabled = extension.is_enabled(metadata_inspector);
+					log::info!(
+						"🧩 Node extension {}: {}",
+						extension.name(),
+						if enabled { "enabled" } else { "disabled for this runtime" }
+					);
+					enabled
+				})
+				.cloned()
+				.collect(),
+		)
+	}
+
+	/// Whether no extension is enabled.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Merge the RPC methods of the extensions into `module`.
+	pub(crate) fn merge_rpc_modules(
+		&self,
+		ctx: &ExtensionContext,
+		module: &mut RpcExtension,
+	) -> sc_service::error::Result<()> {
+		for extension in &self.0 {
+			let merge = || -> Result<(), ExtensionError> {
+				if let Some(extension_module) = extension.rpc_module(ctx)? {
+					module.merge(extension_module)?;
+				}
+				Ok(())
+			};
+			merge().map_err(|err| {
+				sc_service::Error::Application(
+					format!("Node extension {} failed to build RPC: {err}", extension.name())
+						.into(),
+				)
+			})?;
+		}
+
+		Ok(())
+	}
+
+	/// Spawn the background tasks of the extensions.
+	pub(crate) fn spawn_tasks(&self, ctx: &ExtensionContext) -> sc_service::error::Result<()> {
+		for extension in &self.0 {
+			extension.spawn_tasks(ctx).map_err(|err| {
+				sc_service::Error::Application(
+					format!("Node extension {} failed to start: {err}", extension.name()).into(),
+				)
+			})?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::common::runtime::tests::cumulus_test_runtime_metadata_inspector;
+
+	struct RequiresPallet(&'static str);
+
+	impl NodeExtension for RequiresPallet {
+		fn name(&self) -> &'static str {
+			self.0
+		}
+
+		fn is_enabled(&self, metadata: Option<&MetadataInspector>) -> bool {
+			metadata.map_or(false, |metadata| metadata.pallet_exists(self.0))
+		}
+	}
+
+	#[test]
+	fn extensions_are_enabled_by_metadata() {
+		let extensions: Vec<Arc<dyn NodeExtension>> =
+			vec![Arc::new(RequiresPallet("ParachainSystem")), Arc::new(RequiresPallet("Mmr"))];
+		let metadata_inspector = cumulus_test_runtime_metadata_inspector();
+
+		let enabled = NodeExtensions::enabled_for_metadata(&extensions, Some(&metadata_inspector));
+		assert_eq!(
+			enabled.0.iter().map(|extension| extension.name()).collect::<Vec<_>>(),
+			vec!["ParachainSystem"]
+		);
+
+		assert!(NodeExtensions::enabled_for_metadata(&extensions, None).is_empty());
+	}
+
+	/// A chain whose best block has the given runtime code.
+	struct CodeAtBestBlock(Option<Vec<u8>>);
+
+	impl ChainClient for CodeAtBestBlock {
+		fn best_hash(&self) -> Hash {
+			Hash::repeat_byte(1)
+		}
+
+		fn finalized_hash(&self) -> Hash {
+			Hash::zero()
+		}
+
+		fn storage(&self, at: Hash, key: &[u8]) -> Result<Option<Vec<u8>>, ExtensionError> {
+			assert_eq!(at, self.best_hash());
+			assert_eq!(key, sp_storage::well_known_keys::CODE);
+			Ok(self.0.clone())
+		}
+
+		fn call(&self, _: Hash, _: &str, _: &[u8]) -> Result<Vec<u8>, ExtensionError> {
+			unimplemented!()
+		}
+
+		fn import_notification_stream(&self) -> BoxStream<'static, Hash> {
+			futures::stream::empty().boxed()
+		}
+	}
+
+	#[test]
+	fn extensions_are_enabled_by_the_runtime_at_the_best_block() {
+		let extensions = NodeExtensions::registered(vec![
+			Arc::new(RequiresPallet("ParachainSystem")),
+			Arc::new(RequiresPallet("Mmr")),
+		]);
+
+		let client = CodeAtBestBlock(Some(cumulus_test_runtime::WASM_BINARY.unwrap().to_vec()));
+		let enabled = extensions.enabled_at_best_block(&client);
+		assert_eq!(
+			enabled.0.iter().map(|extension| extension.name()).collect::<Vec<_>>(),
+			vec!["ParachainSystem"]
+		);
+
+		assert!(extensions.enabled_at_best_block(&CodeAtBestBlock(None)).is_empty());
+		assert!(extensions.enabled_at_best_block(&CodeAtBestBlock(Some(vec![1, 2]))).is_empty());
+	}
+}
@@ -21,13 +21,20 @@
 pub(crate) mod aura;
 pub mod chain_spec;
 pub mod command;
+pub mod extension;
+pub mod pov_profile;
 pub mod rpc;
 pub mod runtime;
 pub mod spec;
 pub mod types;
+pub mod unincluded_segment;
 
 use crate::cli::AuthoringPolicy;
+use cumulus_client_consensus_aura::collators::{
+	slot_based::CoreAcquisitionConfig, LastRefusedBuildAttempt,
+};
 use cumulus_primitives_core::{CollectCollationInfo, GetCoreSelectorApi};
+use extension::NodeExtensions;
 use sc_client_db::DbHash;
 use sc_offchain::OffchainWorkerApi;
 use serde::de::DeserializeOwned;
@@ -113,7 +120,20 @@ pub struct NodeExtraArgs {
 	/// If set, each `PoV` build by the node will be exported to this folder.
 	pub export_pov: Option<PathBuf>,
 
+	/// Folders with exported `PoV`s, used to recover blocks that are no longer available on the
+	/// relay chain.
+	pub pov_recovery_paths: Vec<PathBuf>,
+
 	/// The maximum percentage of the maximum PoV size that the collator can use.
 	/// It will be removed once <https://github.com/paritytech/polkadot-sdk/issues/6020> is fixed.
 	pub max_pov_percentage: Option<u32>,
+
+	/// When set, the slot-based collator orders on-demand cores when the parachain is congested.
+	pub core_acquisition: Option<CoreAcquisitionConfig>,
+
+	/// The registered node extensions, the enabled ones are selected when the node starts.
+	pub(crate) extensions: NodeExtensions,
+
+	/// The last block the collator was refused to build, reported by the RPC.
+	pub(crate) refused_build_attempt: LastRefusedBuildAttempt,
 }
@@ -0,0 +1,672 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Profiling of the PoV size of parachain blocks.
+//!
+//! A block is re-executed on top of its parent with proof recording enabled, like the collator
+//! does when building it. Every node of the recorded storage proof is then attributed to the
+//! storage item whose key leads to the node in the trie, and to the phase of the block execution
+//! (initialization, an extrinsic or finalization) that first accessed it.
+
+use crate::common::NodeBlock;
+use codec::{Compact, Decode, Encode};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{BlockBackend, HeaderBackend};
+use serde::Serialize;
+use sp_api::{ApiExt, Core, Metadata, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder;
+use sp_core::{
+	hashing::twox_128, hexdisplay::HexDisplay, traits::CallContext, Bytes, Hasher, H256,
+};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, HashingFor, Header as HeaderT, UniqueSaturatedInto},
+	Digest, DigestItem, OpaqueExtrinsic,
+};
+use sp_trie::{proof_size_extension::ProofSizeExt, NodeCodec, StorageProof};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt::Write,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use trie_db::{
+	node::{Node, NodeHandle, Value},
+	NibbleSlice, NodeCodec as _,
+};
+
+/// The `profile-pov` command used to find out what contributes to the PoV size of a block.
+///
+/// The block is re-executed on top of its parent with proof recording, and the recorded storage
+/// proof is broken down by storage item, pallet and extrinsic.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ProfilePovCmd {
+	/// Hash or number of the block to re-execute.
+	#[arg(value_name = "HASH or NUMBER")]
+	pub input: BlockNumberOrHash,
+
+	/// Replace the transactions of the block with the extrinsics in the given JSON file.
+	///
+	/// The file contains an array of hex encoded extrinsics, like the result of the
+	/// `author_pendingExtrinsics` RPC method. The inherents of the block, i.e. its leading
+	/// unsigned extrinsics, are kept.
+	#[arg(long, value_name = "PATH")]
+	pub extrinsics: Option<PathBuf>,
+
+	/// Print the profile as JSON instead of a human-readable report.
+	#[arg(long)]
+	pub json: bool,
+
+	/// The number of storage items listed in the human-readable report.
+	#[arg(long, value_name = "COUNT", default_value_t = 20)]
+	pub top: usize,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl ProfilePovCmd {
+	/// Run the profile-pov command.
+	pub fn run<Block, Client>(&self, client: Arc<Client>) -> sc_cli::Result<()>
+	where
+		Block: NodeBlock,
+		Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockBackend<Block>,
+		Client::Api: BlockBuilder<Block> + Metadata<Block>,
+	{
+		let hash = match self.input.parse::<Block>()? {
+			BlockId::Hash(hash) => hash,
+			BlockId::Number(number) =>
+				client.hash(number)?.ok_or_else(|| format!("Block #{number} not found"))?,
+		};
+		let (header, block_extrinsics) = client
+			.block(hash)?
+			.ok_or_else(|| format!("Block {hash:?} not found"))?
+			.block
+			.deconstruct();
+		let parent_header = client
+			.header(*header.parent_hash())?
+			.ok_or_else(|| format!("Parent of block {hash:?} not found"))?;
+
+		let extrinsics = match &self.extrinsics {
+			Some(path) => {
+				let mut extrinsics = leading_bare_extrinsics(block_extrinsics);
+				extrinsics.extend(load_extrinsics(path)?);
+				extrinsics
+			},
+			None => block_extrinsics,
+		};
+
+		let storage_names = StorageNames::fetch::<Block, _>(&*client, parent_header.hash());
+		let profile = profile_block::<Block, _>(
+			&*client,
+			&header,
+			extrinsics,
+			&parent_header,
+			&storage_names,
+		)?;
+
+		if self.json {
+			let json = serde_json::to_string_pretty(&profile).map_err(|err| err.to_string())?;
+			println!("{json}");
+		} else {
+			print!("{}", profile.report(self.top));
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ProfilePovCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+/// The breakdown of the storage proof of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PovProfile {
+	/// The hash of the profiled block.
+	pub block_hash: H256,
+	/// The number of the profiled block.
+	pub block_number: u64,
+	/// The encoded size of the storage proof.
+	pub proof_size: usize,
+	/// The encoded size of the storage proof in the compact form used in PoVs.
+	pub compact_proof_size: Option<usize>,
+	/// The encoded size of the extrinsics of the block.
+	pub extrinsics_size: usize,
+	/// The proof bytes by storage item, the largest first.
+	pub storage: Vec<StorageUsage>,
+	/// The proof bytes by pallet, the largest first.
+	pub pallets: Vec<PalletUsage>,
+	/// The proof bytes by phase of the block execution, in execution order.
+	pub phases: Vec<PhaseUsage>,
+}
+
+/// The proof bytes of the trie nodes below a storage key prefix.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+	/// The name of the pallet, or a placeholder in angle brackets.
+	pub pallet: String,
+	/// The name of the storage item, empty if the nodes are shared by several items.
+	pub item: String,
+	/// The key prefix, at most the 32 bytes identifying a storage item.
+	pub prefix: Bytes,
+	/// The number of trie nodes.
+	pub nodes: usize,
+	/// The size of the trie nodes.
+	pub bytes: usize,
+}
+
+/// The proof bytes of the storage of a pallet.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PalletUsage {
+	/// The name of the pallet.
+	pub pallet: String,
+	/// The size of the trie nodes.
+	pub bytes: usize,
+}
+
+/// A phase of the block execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+	/// `Core_initialize_block`, including the `on_initialize` hooks.
+	Initialization,
+	/// `BlockBuilder_apply_extrinsic` of an extrinsic.
+	Extrinsic,
+	/// `BlockBuilder_finalize_block`, including the `on_finalize` hooks and the storage root.
+	Finalization,
+}
+
+/// The proof bytes first accessed in a phase of the block execution.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseUsage {
+	/// The phase of the block execution.
+	pub phase: Phase,
+	/// The index of the extrinsic in the block, for [`Phase::Extrinsic`].
+	pub extrinsic: Option<usize>,
+	/// The encoded size of the extrinsic.
+	pub encoded_size: usize,
+	/// The outcome of applying the extrinsic.
+	pub outcome: Option<String>,
+	/// The size of the trie nodes first accessed in this phase.
+	pub proof_bytes: usize,
+	/// The size of the trie nodes first accessed in this phase, by pallet.
+	pub pallets: BTreeMap<String, usize>,
+}
+
+impl PhaseUsage {
+	fn new(phase: Phase) -> Self {
+		Self {
+			phase,
+			extrinsic: None,
+			encoded_size: 0,
+			outcome: None,
+			proof_bytes: 0,
+			pallets: BTreeMap::new(),
+		}
+	}
+}
+
+impl PovProfile {
+	/// A human-readable report of the profile, listing the `top` largest storage items.
+	pub fn report(&self, top: usize) -> String {
+		let mut report = String::new();
+		let _ =
+			writeln!(report, "PoV profile of block #{} ({:?})", self.block_number, self.block_hash);
+		let _ = writeln!(report, "  Storage proof: {} bytes", self.proof_size);
+		if let Some(compact_proof_size) = self.compact_proof_size {
+			let _ = writeln!(report, "  Compact storage proof: {compact_proof_size} bytes");
+		}
+		let _ = writeln!(report, "  Extrinsics: {} bytes", self.extrinsics_size);
+
+		let _ = writeln!(report, "\nStorage items (top {top}):");
+		for usage in self.storage.iter().take(top) {
+			let name = if usage.item.is_empty() {
+				usage.pallet.clone()
+			} else {
+				format!("{}::{}", usage.pallet, usage.item)
+			};
+			let _ =
+				writeln!(report, "  {:>10} bytes {:>7} nodes  {name}", usage.bytes, usage.nodes);
+		}
+
+		let _ = writeln!(report, "\nPallets:");
+		for usage in &self.pallets {
+			let _ = writeln!(report, "  {:>10} bytes  {}", usage.bytes, usage.pallet);
+		}
+
+		let _ = writeln!(report, "\nPhases:");
+		for usage in &self.phases {
+			let phase = match (usage.phase, usage.extrinsic) {
+				(Phase::Extrinsic, Some(index)) => format!(
+					"extrinsic #{index} ({} bytes, {})",
+					usage.encoded_size,
+					usage.outcome.as_deref().unwrap_or_default()
+				),
+				(Phase::Initialization, _) => "initialization".into(),
+				_ => "finalization".into(),
+			};
+			let mut pallets = usage.pallets.iter().collect::<Vec<_>>();
+			pallets.sort_by(|a, b| b.1.cmp(a.1));
+			let pallets = pallets
+				.iter()
+				.take(3)
+				.map(|(pallet, bytes)| format!("{pallet} {bytes}"))
+				.collect::<Vec<_>>()
+				.join(", ");
+			let _ = writeln!(report, "  {:>10} bytes  {phase}  [{pallets}]", usage.proof_bytes);
+		}
+
+		report
+	}
+}
+
+/// Re-execute the block of `header` with the given extrinsics and profile its storage proof.
+fn profile_block<Block, Client>(
+	client: &Client,
+	header: &Block::Header,
+	extrinsics: Vec<Block::Extrinsic>,
+	parent_header: &Block::Header,
+	storage_names: &StorageNames,
+) -> sc_cli::Result<PovProfile>
+where
+	Block: NodeBlock,
+	Client: ProvideRuntimeApi<Block>,
+	Client::Api: BlockBuilder<Block>,
+{
+	let parent_hash = parent_header.hash();
+	let api_error = |err: sp_api::ApiError| format!("Failed to re-execute block: {err}");
+
+	// The seal is added after building the block, it is not part of the execution.
+	let logs = header
+		.digest()
+		.logs()
+		.iter()
+		.filter(|item| !matches!(item, DigestItem::Seal(..)))
+		.cloned()
+		.collect();
+	let new_header = <Block::Header as HeaderT>::new(
+		*header.number(),
+		Default::default(),
+		Default::default(),
+		parent_hash,
+		Digest { logs },
+	);
+
+	let mut api = client.runtime_api();
+	api.record_proof();
+	let recorder = api.proof_recorder().expect("Proof recording is enabled in the line above; qed");
+	api.register_extension(ProofSizeExt::new(recorder.clone()));
+	api.set_call_context(CallContext::Onchain);
+
+	let mut first_access = FirstAccess::default();
+	let mut phases = Vec::with_capacity(extrinsics.len() + 2);
+
+	api.initialize_block(parent_hash, &new_header).map_err(api_error)?;
+	first_access.note::<HashingFor<Block>>(&recorder.to_storage_proof(), phases.len());
+	phases.push(PhaseUsage::new(Phase::Initialization));
+
+	let mut extrinsics_size = 0;
+	for (index, extrinsic) in extrinsics.into_iter().enumerate() {
+		let encoded_size = extrinsic.encoded_size();
+		extrinsics_size += encoded_size;
+		let outcome = match api.apply_extrinsic(parent_hash, extrinsic).map_err(api_error)? {
+			Ok(Ok(())) => "success".to_string(),
+			Ok(Err(error)) => format!("dispatch error: {error:?}"),
+			Err(error) => format!("invalid: {error:?}"),
+		};
+
+		first_access.note::<HashingFor<Block>>(&recorder.to_storage_proof(), phases.len());
+		phases.push(PhaseUsage {
+			extrinsic: Some(index),
+			encoded_size,
+			outcome: Some(outcome),
+			..PhaseUsage::new(Phase::Extrinsic)
+		});
+	}
+
+	api.finalize_block(parent_hash).map_err(api_error)?;
+	let proof = recorder.to_storage_proof();
+	first_access.note::<HashingFor<Block>>(&proof, phases.len());
+	phases.push(PhaseUsage::new(Phase::Finalization));
+
+	let parent_state_root = *parent_header.state_root();
+	let owners = node_owners::<HashingFor<Block>>(&proof, &parent_state_root);
+	let mut storage = HashMap::<NodeOwner, (usize, usize)>::new();
+	let mut pallets = HashMap::<String, usize>::new();
+	for node in proof.iter_nodes() {
+		let hash = HashingFor::<Block>::hash(node);
+		let owner = owners.get(hash.as_ref()).cloned().unwrap_or(NodeOwner::ChildTrie);
+		let (pallet, _) = storage_names.resolve(&owner);
+
+		let usage = storage.entry(owner).or_default();
+		usage.0 += 1;
+		usage.1 += node.len();
+		*pallets.entry(pallet.clone()).or_default() += node.len();
+
+		if let Some(phase) =
+			first_access.phase(hash.as_ref()).and_then(|index| phases.get_mut(index))
+		{
+			phase.proof_bytes += node.len();
+			*phase.pallets.entry(pallet).or_default() += node.len();
+		}
+	}
+
+	let mut storage = storage
+		.into_iter()
+		.map(|(owner, (nodes, bytes))| {
+			let (pallet, item) = storage_names.resolve(&owner);
+			let prefix = match owner {
+				NodeOwner::Prefix(prefix) => prefix,
+				NodeOwner::Shared | NodeOwner::ChildTrie => Vec::new(),
+			};
+			StorageUsage { pallet, item, prefix: prefix.into(), nodes, bytes }
+		})
+		.collect::<Vec<_>>();
+	storage.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+	let mut pallets = pallets
+		.into_iter()
+		.map(|(pallet, bytes)| PalletUsage { pallet, bytes })
+		.collect::<Vec<_>>();
+	pallets.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+	Ok(PovProfile {
+		block_hash: header.hash(),
+		block_number: (*header.number()).unique_saturated_into(),
+		proof_size: proof.encoded_size(),
+		compact_proof_size: proof.encoded_compact_size::<HashingFor<Block>>(parent_state_root),
+		extrinsics_size,
+		storage,
+		pallets,
+		phases,
+	})
+}
+
+/// The phase of the block execution that first accessed each trie node, by node hash.
+#[derive(Default)]
+struct FirstAccess(HashMap<Vec<u8>, usize>);
+
+impl FirstAccess {
+	/// Note the nodes of `proof` that were not accessed before as accessed in `phase`.
+	fn note<H: Hasher>(&mut self, proof: &StorageProof, phase: usize) {
+		for node in proof.iter_nodes() {
+			self.0.entry(H::hash(node).as_ref().to_vec()).or_insert(phase);
+		}
+	}
+
+	fn phase(&self, hash: &[u8]) -> Option<usize> {
+		self.0.get(hash).copied()
+	}
+}
+
+/// The storage that trie nodes belong to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeOwner {
+	/// Nodes close to the root of the trie, on the path to the keys of several pallets.
+	Shared,
+	/// Nodes on the path to the keys starting with the given prefix, at most 32 bytes long.
+	Prefix(Vec<u8>),
+	/// Nodes that are not reachable from the state root, i.e. nodes of child tries.
+	ChildTrie,
+}
+
+impl NodeOwner {
+	/// The owner of the node at the given nibble path.
+	fn at_path(path: &[u8]) -> Self {
+		let prefix = path
+			.chunks_exact(2)
+			.take(32)
+			.map(|pair| (pair[0] << 4) | pair[1])
+			.collect::<Vec<_>>();
+		if prefix.len() < 16 && !prefix.starts_with(b":") {
+			NodeOwner::Shared
+		} else {
+			NodeOwner::Prefix(prefix)
+		}
+	}
+}
+
+/// Find the owners of the nodes of `proof`, by node hash, by walking the trie from `root`.
+fn node_owners<H: Hasher>(proof: &StorageProof, root: &H::Out) -> HashMap<Vec<u8>, NodeOwner> {
+	let nodes = proof
+		.iter_nodes()
+		.map(|node| (H::hash(node).as_ref().to_vec(), node))
+		.collect::<HashMap<_, _>>();
+
+	let mut owners = HashMap::with_capacity(nodes.len());
+	let mut stack = vec![(root.as_ref().to_vec(), Vec::new())];
+	while let Some((hash, mut path)) = stack.pop() {
+		if owners.contains_key(&hash) {
+			continue
+		}
+		let Some(node) = nodes.get(&hash).and_then(|data| NodeCodec::<H>::decode(data).ok()) else {
+			continue
+		};
+
+		let mut value = None;
+		let mut children = None;
+		match node {
+			Node::Empty => {},
+			Node::Leaf(partial, leaf_value) => {
+				extend_path(&mut path, &partial);
+				value = Some(leaf_value);
+			},
+			Node::Extension(partial, child) => {
+				extend_path(&mut path, &partial);
+				if let NodeHandle::Hash(child) = child {
+					stack.push((child.to_vec(), path.clone()));
+				}
+			},
+			Node::Branch(branch_children, branch_value) => {
+				value = branch_value;
+				children = Some(branch_children);
+			},
+			Node::NibbledBranch(partial, branch_children, branch_value) => {
+				extend_path(&mut path, &partial);
+				value = branch_value;
+				children = Some(branch_children);
+			},
+		}
+
+		for (nibble, child) in children.iter().flatten().enumerate() {
+			if let Some(NodeHandle::Hash(child)) = child {
+				let mut child_path = path.clone();
+				child_path.push(nibble as u8);
+				stack.push((child.to_vec(), child_path));
+			}
+		}
+		// Values stored outside of their node belong to the same key.
+		if let Some(Value::Node(value_hash)) = value {
+			owners.entry(value_hash.to_vec()).or_insert_with(|| NodeOwner::at_path(&path));
+		}
+		owners.insert(hash, NodeOwner::at_path(&path));
+	}
+
+	owners
+}
+
+fn extend_path(path: &mut Vec<u8>, partial: &NibbleSlice) {
+	path.extend((0..partial.len()).map(|index| partial.at(index)));
+}
+
+/// The names of the storage items of a runtime, by their hashed key prefix.
+#[derive(Default)]
+struct StorageNames {
+	pallets: HashMap<Vec<u8>, String>,
+	items: HashMap<Vec<u8>, (String, String)>,
+}
+
+impl StorageNames {
+	/// Read the names from the metadata of the runtime at `at`.
+	fn fetch<Block, Client>(client: &Client, at: Block::Hash) -> Self
+	where
+		Block: BlockT,
+		Client: ProvideRuntimeApi<Block>,
+		Client::Api: Metadata<Block>,
+	{
+		let metadata = client
+			.runtime_api()
+			.metadata_at_version(at, 15)
+			.map_err(|err| err.to_string())
+			.and_then(|metadata| metadata.ok_or_else(|| "metadata V15 not supported".to_string()))
+			.and_then(|metadata| {
+				subxt_metadata::Metadata::decode(&mut &metadata[..]).map_err(|err| err.to_string())
+			});
+
+		match metadata {
+			Ok(metadata) => Self::from_metadata(&metadata),
+			Err(err) => {
+				log::warn!(
+					"Unable to fetch the runtime metadata, storage names are unknown: {err}"
+				);
+				Self::default()
+			},
+		}
+	}
+
+	fn from_metadata(metadata: &subxt_metadata::Metadata) -> Self {
+		let mut names = Self::default();
+		for pallet in metadata.pallets() {
+			let Some(storage) = pallet.storage() else { continue };
+			let pallet_prefix = twox_128(storage.prefix().as_bytes());
+			names.pallets.insert(pallet_prefix.to_vec(), pallet.name().to_string());
+
+			for entry in storage.entries() {
+				let item_prefix = [pallet_prefix, twox_128(entry.name().as_bytes())].concat();
+				names
+					.items
+					.insert(item_prefix, (pallet.name().to_string(), entry.name().to_string()));
+			}
+		}
+
+		names
+	}
+
+	/// The pallet and storage item names of the nodes of `owner`.
+	fn resolve(&self, owner: &NodeOwner) -> (String, String) {
+		let prefix = match owner {
+			NodeOwner::Shared => return ("<shared>".into(), String::new()),
+			NodeOwner::ChildTrie => return ("<child tries>".into(), String::new()),
+			NodeOwner::Prefix(prefix) if prefix.starts_with(b":") =>
+				return ("<well-known keys>".into(), String::from_utf8_lossy(prefix).into_owned()),
+			NodeOwner::Prefix(prefix) => prefix,
+		};
+
+		if let Some((pallet, item)) = prefix.get(..32).and_then(|prefix| self.items.get(prefix)) {
+			return (pallet.clone(), item.clone())
+		}
+
+		match self.pallets.get(&prefix[..16]) {
+			Some(pallet) if prefix.len() < 32 => (pallet.clone(), String::new()),
+			Some(pallet) =>
+				(pallet.clone(), format!("<unknown 0x{}>", HexDisplay::from(&&prefix[16..]))),
+			None => (format!("<unknown 0x{}>", HexDisplay::from(&&prefix[..16])), String::new()),
+		}
+	}
+}
+
+/// The leading bare extrinsics of a block, i.e. its inherents.
+fn leading_bare_extrinsics(extrinsics: Vec<OpaqueExtrinsic>) -> Vec<OpaqueExtrinsic> {
+	extrinsics.into_iter().take_while(is_bare).collect()
+}
+
+/// Whether the extrinsic is neither signed nor a general transaction.
+fn is_bare(extrinsic: &OpaqueExtrinsic) -> bool {
+	let encoded = extrinsic.encode();
+	let mut input = &encoded[..];
+	// The version byte follows the length prefix, its two highest bits mark the extrinsic type.
+	Compact::<u32>::decode(&mut input).is_ok() &&
+		input.first().map_or(false, |version| version & 0b1100_0000 == 0)
+}
+
+/// Load hex encoded extrinsics from a JSON file.
+fn load_extrinsics(path: &Path) -> sc_cli::Result<Vec<OpaqueExtrinsic>> {
+	let file = std::fs::File::open(path)?;
+	serde_json::from_reader(std::io::BufReader::new(file))
+		.map_err(|err| format!("Invalid extrinsics file {}: {err}", path.display()).into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::traits::BlakeTwo256;
+	use sp_trie::{LayoutV1, MemoryDB, TrieDBMutBuilder, TrieMut};
+
+	#[test]
+	fn nodes_are_attributed_to_key_prefixes() {
+		let pallet_a = twox_128(b"PalletA");
+		let pallet_b = twox_128(b"PalletB");
+		let item = twox_128(b"Item");
+		let keys = [
+			[&pallet_a[..], &item, &[1]].concat(),
+			[&pallet_a[..], &item, &[2]].concat(),
+			[&pallet_b[..], &item].concat(),
+			b":code".to_vec(),
+		];
+
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = Default::default();
+		{
+			let mut trie =
+				TrieDBMutBuilder::<LayoutV1<BlakeTwo256>>::new(&mut db, &mut root).build();
+			for (index, key) in keys.iter().enumerate() {
+				// Large values are stored in separate nodes.
+				trie.insert(key, &[index as u8; 64]).unwrap();
+			}
+		}
+		let proof = StorageProof::new(db.drain().into_values().map(|(node, _)| node));
+
+		let owners = node_owners::<BlakeTwo256>(&proof, &root);
+		assert_eq!(owners.len(), proof.len());
+
+		let count = |owner: NodeOwner| owners.values().filter(|o| **o == owner).count();
+		// The branch of both keys of the item, two leaves and two value nodes.
+		assert_eq!(count(NodeOwner::Prefix([&pallet_a[..], &item].concat())), 5);
+		// A leaf and a value node.
+		assert_eq!(count(NodeOwner::Prefix([&pallet_b[..], &item].concat())), 2);
+		assert_eq!(count(NodeOwner::Prefix(b":code".to_vec())), 2);
+		// Branches close to the root.
+		assert!(count(NodeOwner::Shared) >= 1);
+		assert_eq!(count(NodeOwner::Shared), owners.len() - 9);
+	}
+
+	#[test]
+	fn bare_extrinsics_are_detected() {
+		let bare = OpaqueExtrinsic::from_bytes(&[8, 0b0000_0100, 0]).unwrap();
+		let signed = OpaqueExtrinsic::from_bytes(&[8, 0b1000_0100, 0]).unwrap();
+		let general = OpaqueExtrinsic::from_bytes(&[8, 0b0100_0101, 0]).unwrap();
+
+		assert!(is_bare(&bare));
+		assert!(!is_bare(&signed));
+		assert!(!is_bare(&general));
+		assert_eq!(leading_bare_extrinsics(vec![bare.clone(), signed, bare.clone()]), vec![bare]);
+	}
+}
@@ -20,13 +20,15 @@
 
 use crate::common::{
 	types::{AccountId, Balance, Nonce, ParachainBackend, ParachainClient},
+	unincluded_segment::{UnincludedSegment, UnincludedSegmentRpcApiServer},
 	ConstructNodeRuntimeApi,
 };
+use cumulus_client_consensus_aura::collators::LastRefusedBuildAttempt;
 use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 use sc_rpc::dev::{Dev, DevApiServer};
 use sp_runtime::traits::Block as BlockT;
 use std::{marker::PhantomData, sync::Arc};
-use substrate_frame_rpc_system::{System, SystemApiServer};
+use substrate_frame_rpc_system::{Simulation, SimulationApiServer, System, SystemApiServer};
 use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 /// A type representing all RPC extensions.
@@ -37,6 +39,7 @@ pub(crate) trait BuildRpcExtensions<Client, Backend, Pool> {
 		client: Arc<Client>,
 		backend: Arc<Backend>,
 		pool: Arc<Pool>,
+		refused_build_attempt: LastRefusedBuildAttempt,
 	) -> sc_service::error::Result<RpcExtension>;
 }
 
@@ -52,7 +55,8 @@ where
 	RuntimeApi:
 		ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>> + Send + Sync + 'static,
 	RuntimeApi::RuntimeApi: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
-		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ cumulus_primitives_core::UnincludedSegmentApi<Block>,
 {
 	fn build_rpc_extensions(
 		client: Arc<ParachainClient<Block, RuntimeApi>>,
@@ -60,13 +64,17 @@ where
 		pool: Arc<
 			sc_transaction_pool::TransactionPoolHandle<Block, ParachainClient<Block, RuntimeApi>>,
 		>,
+		refused_build_attempt: LastRefusedBuildAttempt,
 	) -> sc_service::error::Result<RpcExtension> {
 		let build = || -> Result<RpcExtension, Box<dyn std::error::Error + Send + Sync>> {
 			let mut module = RpcExtension::new(());
 
 			module.merge(System::new(client.clone(), pool).into_rpc())?;
+			module.merge(Simulation::new(client.clone()).into_rpc())?;
 			module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 			module.merge(StateMigration::new(client.clone(), backend).into_rpc())?;
+			module
+				.merge(UnincludedSegment::new(client.clone(), refused_build_attempt).into_rpc())?;
 			module.merge(Dev::new(client).into_rpc())?;
 
 			Ok(module)
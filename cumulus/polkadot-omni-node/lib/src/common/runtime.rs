@@ -133,18 +133,32 @@ impl RuntimeResolver for DefaultRuntimeResolver {
 	}
 }
 
-struct MetadataInspector(Metadata);
+/// Helper for checking the runtime metadata of a chain spec against the runtime conventions.
+pub struct MetadataInspector(Metadata);
 
 impl MetadataInspector {
-	fn new(chain_spec: &dyn ChainSpec) -> Result<MetadataInspector, sc_cli::Error> {
+	/// Fetch the metadata of the runtime in the genesis of the chain spec.
+	pub fn new(chain_spec: &dyn ChainSpec) -> Result<MetadataInspector, sc_cli::Error> {
 		MetadataInspector::fetch_metadata(chain_spec).map(MetadataInspector)
 	}
 
-	fn pallet_exists(&self, name: &str) -> bool {
+	/// Check whether the runtime contains a pallet with the given name.
+	pub fn pallet_exists(&self, name: &str) -> bool {
 		self.0.pallet_by_name(name).is_some()
 	}
 
-	fn block_number(&self) -> Option<BlockNumber> {
+	/// Check whether the runtime implements a runtime API with the given name, e.g. `MmrApi`.
+	pub fn runtime_api_exists(&self, name: &str) -> bool {
+		self.0.runtime_api_trait_by_name(name).is_some()
+	}
+
+	/// The raw runtime metadata.
+	pub fn metadata(&self) -> &Metadata {
+		&self.0
+	}
+
+	/// The block number type of the runtime, if it follows the conventions.
+	pub fn block_number(&self) -> Option<BlockNumber> {
 		let pallet_metadata = self.0.pallet_by_name(DEFAULT_FRAME_SYSTEM_PALLET_NAME);
 		pallet_metadata
 			.and_then(|inner| inner.storage())
@@ -163,11 +177,20 @@ impl MetadataInspector {
 			.top
 			.remove(sp_storage::well_known_keys::CODE)
 			.ok_or("chain spec genesis does not contain code")?;
+		Self::metadata_from_code(&code_bytes)
+	}
+
+	/// Fetch the metadata of the given runtime code.
+	pub fn from_code(code: &[u8]) -> Result<MetadataInspector, sc_cli::Error> {
+		MetadataInspector::metadata_from_code(code).map(MetadataInspector)
+	}
+
+	fn metadata_from_code(code: &[u8]) -> Result<Metadata, sc_cli::Error> {
 		let opaque_metadata = fetch_latest_metadata_from_code_blob(
 			&WasmExecutor::<ParachainHostFunctions>::builder()
 				.with_allow_missing_host_functions(true)
 				.build(),
-			sp_runtime::Cow::Borrowed(code_bytes.as_slice()),
+			sp_runtime::Cow::Borrowed(code),
 		)
 		.map_err(|err| err.to_string())?;
 
@@ -176,7 +199,7 @@ impl MetadataInspector {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 	use crate::runtime::{
 		BlockNumber, MetadataInspector, DEFAULT_FRAME_SYSTEM_PALLET_NAME,
 		DEFAULT_PARACHAIN_SYSTEM_PALLET_NAME,
@@ -198,6 +221,10 @@ mod tests {
 		subxt_metadata::Metadata::decode(&mut (*opaque_metadata).as_slice()).unwrap()
 	}
 
+	pub(crate) fn cumulus_test_runtime_metadata_inspector() -> MetadataInspector {
+		MetadataInspector(cumulus_test_runtime_metadata())
+	}
+
 	#[test]
 	fn test_pallet_exists() {
 		let metadata_inspector = MetadataInspector(cumulus_test_runtime_metadata());
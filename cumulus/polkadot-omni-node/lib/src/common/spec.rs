@@ -16,6 +16,7 @@
 
 use crate::common::{
 	command::NodeCommandRunner,
+	extension::ExtensionContext,
 	rpc::BuildRpcExtensions,
 	types::{
 		ParachainBackend, ParachainBlockImport, ParachainClient, ParachainHostFunctions,
@@ -26,7 +27,8 @@ use crate::common::{
 use cumulus_client_cli::CollatorOptions;
 use cumulus_client_service::{
 	build_network, build_relay_chain_interface, prepare_node_config, start_relay_chain_tasks,
-	BuildNetworkParams, CollatorSybilResistance, DARecoveryProfile, StartRelayChainTasksParams,
+	BuildNetworkParams, CollatorSybilResistance, DARecoveryProfile, PoVDirectory,
+	StartRelayChainTasksParams,
 };
 use cumulus_primitives_core::{BlockT, ParaId};
 use cumulus_relay_chain_interface::{OverseerHandle, RelayChainInterface};
@@ -328,17 +330,34 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 				);
 			}
 
+			let extensions = node_extra_args.extensions.enabled_at_best_block(&*client);
+			let extension_context = ExtensionContext {
+				client: client.clone(),
+				transaction_pool: transaction_pool.clone(),
+				backend: backend.clone(),
+				offchain_storage: backend.offchain_storage(),
+				spawn_handle: task_manager.spawn_handle(),
+				prometheus_registry: prometheus_registry.clone(),
+				para_id,
+				is_dev: false,
+			};
 			let rpc_builder = {
 				let client = client.clone();
 				let transaction_pool = transaction_pool.clone();
 				let backend_for_rpc = backend.clone();
+				let extensions = extensions.clone();
+				let extension_context = extension_context.clone();
+				let refused_build_attempt = node_extra_args.refused_build_attempt.clone();
 
 				Box::new(move |_| {
-					Self::BuildRpcExtensions::build_rpc_extensions(
+					let mut module = Self::BuildRpcExtensions::build_rpc_extensions(
 						client.clone(),
 						backend_for_rpc.clone(),
 						transaction_pool.clone(),
-					)
+						refused_build_attempt.clone(),
+					)?;
+					extensions.merge_rpc_modules(&extension_context, &mut module)?;
+					Ok(module)
 				})
 			};
 
@@ -357,6 +376,8 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 				telemetry: telemetry.as_mut(),
 			})?;
 
+			extensions.spawn_tasks(&extension_context)?;
+
 			if let Some(hwbench) = hwbench {
 				sc_sysinfo::print_hwbench(&hwbench);
 				if validator {
@@ -399,6 +420,11 @@ pub(crate) trait NodeSpec: BaseNodeSpec {
 				relay_chain_slot_duration,
 				recovery_handle: Box::new(overseer_handle.clone()),
 				sync_service,
+				pov_sources: node_extra_args
+					.pov_recovery_paths
+					.iter()
+					.map(|path| Arc::new(PoVDirectory::new(path)) as Arc<_>)
+					.collect(),
 			})?;
 
 			if validator {
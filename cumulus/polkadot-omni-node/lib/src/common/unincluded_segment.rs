@@ -0,0 +1,128 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! RPC methods for diagnosing why the collator does not build blocks.
+
+use cumulus_client_consensus_aura::collators::LastRefusedBuildAttempt;
+use cumulus_primitives_core::{
+	relay_chain::Slot, BuildRefusal, ConsensusHookCapacity, UnincludedSegmentApi,
+	UnincludedSegmentInfo,
+};
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::{error::ErrorObject, ErrorObjectOwned},
+};
+use sc_client_api::HeaderBackend;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_runtime::traits::Block as BlockT;
+use std::{marker::PhantomData, sync::Arc};
+
+/// The unincluded segment at some block along with the limits of the consensus hook.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnincludedSegmentStatus<Hash> {
+	/// The unincluded segment.
+	pub segment: UnincludedSegmentInfo<Hash>,
+	/// The limits of the consensus hook.
+	pub capacity: ConsensusHookCapacity,
+}
+
+/// The last build attempt refused by the runtime.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefusedBuild<Hash> {
+	/// The block the collator tried to build upon.
+	pub parent_hash: Hash,
+	/// The most recently included block as of the relay parent.
+	pub included_hash: Hash,
+	/// The relay chain slot of the relay parent.
+	pub relay_slot: Slot,
+	/// The parachain slot claimed for the block.
+	pub para_slot: Slot,
+	/// The reason reported by the runtime.
+	pub reason: Option<BuildRefusal>,
+}
+
+/// RPC methods for inspecting the unincluded segment.
+#[rpc(server)]
+pub trait UnincludedSegmentRpcApi<Hash> {
+	/// The unincluded segment and the limits of the consensus hook at the given block, or at the
+	/// best block.
+	#[method(name = "unincludedSegment_status")]
+	fn status(&self, at: Option<Hash>) -> RpcResult<UnincludedSegmentStatus<Hash>>;
+
+	/// The last attempt of this collator to build a block that was refused by the runtime.
+	#[method(name = "unincludedSegment_lastRefusal")]
+	fn last_refusal(&self) -> RpcResult<Option<RefusedBuild<Hash>>>;
+}
+
+/// Implementation of [`UnincludedSegmentRpcApiServer`].
+pub struct UnincludedSegment<Block, Client> {
+	client: Arc<Client>,
+	refused_build_attempt: LastRefusedBuildAttempt,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block, Client> UnincludedSegment<Block, Client> {
+	/// Create a new instance reporting the refused build attempts kept by the collator in
+	/// `refused_build_attempt`.
+	pub fn new(client: Arc<Client>, refused_build_attempt: LastRefusedBuildAttempt) -> Self {
+		Self { client, refused_build_attempt, _marker: PhantomData }
+	}
+}
+
+fn runtime_error(error: impl ToString) -> ErrorObjectOwned {
+	ErrorObject::owned(1, "Runtime call failed", Some(error.to_string()))
+}
+
+impl<Block, Client> UnincludedSegmentRpcApiServer<Block::Hash> for UnincludedSegment<Block, Client>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	Client::Api: UnincludedSegmentApi<Block>,
+{
+	fn status(&self, at: Option<Block::Hash>) -> RpcResult<UnincludedSegmentStatus<Block::Hash>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let api = self.client.runtime_api();
+
+		Ok(UnincludedSegmentStatus {
+			segment: api.unincluded_segment(at).map_err(runtime_error)?,
+			capacity: api.consensus_hook_capacity(at).map_err(runtime_error)?,
+		})
+	}
+
+	fn last_refusal(&self) -> RpcResult<Option<RefusedBuild<Block::Hash>>> {
+		let Some(attempt) = self.refused_build_attempt.get::<Block::Hash>() else {
+			return Ok(None)
+		};
+
+		let reason = self
+			.client
+			.runtime_api()
+			.build_refusal(attempt.parent_hash, attempt.included_hash, attempt.relay_slot)
+			.map_err(runtime_error)?;
+
+		Ok(Some(RefusedBuild {
+			parent_hash: attempt.parent_hash,
+			included_hash: attempt.included_hash,
+			relay_slot: attempt.relay_slot,
+			para_slot: attempt.para_slot,
+			reason,
+		}))
+	}
+}
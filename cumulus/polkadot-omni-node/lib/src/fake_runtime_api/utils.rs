@@ -169,6 +169,24 @@ macro_rules! impl_node_runtime_apis {
 				}
 			}
 
+			impl cumulus_primitives_core::UnincludedSegmentApi<$block> for $runtime {
+				fn unincluded_segment(
+				) -> cumulus_primitives_core::UnincludedSegmentInfo<<$block as BlockT>::Hash> {
+					unimplemented!()
+				}
+
+				fn consensus_hook_capacity() -> cumulus_primitives_core::ConsensusHookCapacity {
+					unimplemented!()
+				}
+
+				fn build_refusal(
+					_: <$block as BlockT>::Hash,
+					_: cumulus_primitives_aura::Slot,
+				) -> Option<cumulus_primitives_core::BuildRefusal> {
+					unimplemented!()
+				}
+			}
+
 			#[cfg(feature = "try-runtime")]
 			impl frame_try_runtime::TryRuntime<$block> for $runtime {
 				fn on_runtime_upgrade(
@@ -41,7 +41,10 @@ use cumulus_client_consensus_aura::collators::slot_based::{
 use cumulus_client_consensus_aura::{
 	collators::{
 		lookahead::{self as aura, Params as AuraParams},
-		slot_based::{SlotBasedBlockImport, SlotBasedBlockImportHandle},
+		slot_based::{
+			run_core_acquisition_task, CoreAcquisitionParams, KeystoreOrderSigner,
+			SlotBasedBlockImport, SlotBasedBlockImportHandle,
+		},
 	},
 	equivocation_import_queue::Verifier as EquivocationVerifier,
 };
@@ -170,7 +173,8 @@ where
 	RuntimeApi: ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>>,
 	RuntimeApi::RuntimeApi: AuraRuntimeApi<Block, AuraId>
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
-		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ cumulus_primitives_core::UnincludedSegmentApi<Block>,
 	AuraId: AuraIdT + Sync,
 	InitBlockImport: self::InitBlockImport<Block, RuntimeApi> + Send,
 	InitBlockImport::BlockImport:
@@ -190,7 +194,8 @@ where
 	RuntimeApi: ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>>,
 	RuntimeApi::RuntimeApi: AuraRuntimeApi<Block, AuraId>
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
-		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ cumulus_primitives_core::UnincludedSegmentApi<Block>,
 	AuraId: AuraIdT + Sync,
 	StartConsensus: self::StartConsensus<
 			Block,
@@ -215,7 +220,8 @@ where
 	RuntimeApi: ConstructNodeRuntimeApi<Block, ParachainClient<Block, RuntimeApi>>,
 	RuntimeApi::RuntimeApi: AuraRuntimeApi<Block, AuraId>
 		+ pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>
-		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+		+ substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>
+		+ cumulus_primitives_core::UnincludedSegmentApi<Block>,
 	AuraId: AuraIdT + Sync,
 {
 	if extra_args.authoring_policy == AuthoringPolicy::SlotBased {
@@ -325,6 +331,37 @@ where
 		node_extra_args: NodeExtraArgs,
 		block_import_handle: SlotBasedBlockImportHandle<Block>,
 	) -> Result<(), Error> {
+		if let Some(config) = node_extra_args.core_acquisition.clone() {
+			let order_signer =
+				KeystoreOrderSigner::new(keystore.clone(), relay_chain_interface.clone())
+					.ok_or_else(|| {
+						Error::Other(
+							"On-demand core acquisition requires an sr25519 `acco` key in the \
+							keystore"
+								.into(),
+						)
+					})?;
+			log::info!(
+				"Ordering on-demand cores when congested, paid by {}",
+				order_signer.account(),
+			);
+			let core_acquisition =
+				run_core_acquisition_task::<Block, _, _, _>(CoreAcquisitionParams {
+					para_id,
+					para_client: client.clone(),
+					relay_client: relay_chain_interface.clone(),
+					transaction_pool: transaction_pool.clone(),
+					order_signer: Arc::new(order_signer),
+					config,
+					prometheus_registry: prometheus_registry.cloned(),
+				});
+			task_manager.spawn_handle().spawn(
+				"core-acquisition",
+				Some("slot-based-collator"),
+				core_acquisition,
+			);
+		}
+
 		let proposer_factory = sc_basic_authorship::ProposerFactory::with_proof_recording(
 			task_manager.spawn_handle(),
 			client.clone(),
@@ -364,6 +401,7 @@ where
 			spawner: task_manager.spawn_handle(),
 			export_pov: node_extra_args.export_pov,
 			max_pov_percentage: node_extra_args.max_pov_percentage,
+			refused_build_attempt: Some(node_extra_args.refused_build_attempt),
 		};
 
 		// We have a separate function only to be able to use `docify::export` on this piece of
@@ -450,6 +488,12 @@ where
 		node_extra_args: NodeExtraArgs,
 		_: (),
 	) -> Result<(), Error> {
+		if node_extra_args.core_acquisition.is_some() {
+			return Err(Error::Other(
+				"On-demand core acquisition requires `--authoring slot-based`".into(),
+			))
+		}
+
 		let proposer_factory = sc_basic_authorship::ProposerFactory::with_proof_recording(
 			task_manager.spawn_handle(),
 			client.clone(),
@@ -489,6 +533,7 @@ where
 				authoring_duration: Duration::from_millis(2000),
 				reinitialize: false,
 				max_pov_percentage: node_extra_args.max_pov_percentage,
+				refused_build_attempt: Some(node_extra_args.refused_build_attempt),
 			},
 		};
 
@@ -15,14 +15,20 @@
 // limitations under the License.
 
 use crate::common::{
+	extension::{ExtensionContext, NodeExtensions},
 	rpc::BuildRpcExtensions as BuildRpcExtensionsT,
 	spec::{BaseNodeSpec, BuildImportQueue, ClientBlockImport, NodeSpec as NodeSpecT},
 	types::{Hash, ParachainBlockImport, ParachainClient},
 };
+use crate::nodes::relay_emulator::{RelayEmulatorApiServer, RelayEmulatorConfig, RelayEmulatorRpc};
 use codec::Encode;
-use cumulus_client_parachain_inherent::{MockValidationDataInherentDataProvider, MockXcmConfig};
+use cumulus_client_parachain_inherent::{
+	last_relay_chain_block_number, MockValidationDataInherentDataProvider, MockXcmConfig,
+	ParachainInherentData, ParachainInherentDataProvider, RelayBlockParams, RelayChainEmulator,
+};
 use cumulus_primitives_aura::AuraUnincludedSegmentApi;
 use cumulus_primitives_core::{CollectCollationInfo, ParaId};
+use cumulus_relay_chain_interface::{OccupiedCoreAssumption, RelayChainInterface};
 use futures::FutureExt;
 use polkadot_primitives::UpgradeGoAhead;
 use sc_client_api::Backend;
@@ -33,9 +39,66 @@ use sc_service::{Configuration, PartialComponents, TaskManager};
 use sc_telemetry::TelemetryHandle;
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_inherents::{InherentData, InherentDataProvider, InherentIdentifier};
 use sp_runtime::traits::Header;
 use std::{marker::PhantomData, sync::Arc};
 
+type InherentError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The parachain inherent of the dev node, either mocked or built by the relay chain emulator.
+enum DevParachainInherent {
+	Mocked(MockValidationDataInherentDataProvider),
+	Emulated(ParachainInherentData),
+}
+
+impl DevParachainInherent {
+	async fn emulated(
+		emulator: &RelayChainEmulator,
+		para_id: ParaId,
+		params: RelayBlockParams,
+	) -> Result<Self, InherentError> {
+		let relay_parent = emulator.build_relay_block(params);
+		let validation_data = emulator
+			.persisted_validation_data(relay_parent, para_id, OccupiedCoreAssumption::TimedOut)
+			.await?
+			.ok_or("Emulated relay block has no validation data")?;
+		let inherent = ParachainInherentDataProvider::create_at(
+			relay_parent,
+			emulator,
+			&validation_data,
+			para_id,
+		)
+		.await
+		.ok_or("Failed to create the parachain inherent for the emulated relay block")?;
+
+		Ok(Self::Emulated(inherent))
+	}
+}
+
+#[async_trait::async_trait]
+impl InherentDataProvider for DevParachainInherent {
+	async fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		match self {
+			Self::Mocked(mocked) => mocked.provide_inherent_data(inherent_data).await,
+			Self::Emulated(emulated) => emulated.provide_inherent_data(inherent_data).await,
+		}
+	}
+
+	async fn try_handle_error(
+		&self,
+		identifier: &InherentIdentifier,
+		error: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		match self {
+			Self::Mocked(mocked) => mocked.try_handle_error(identifier, error).await,
+			Self::Emulated(emulated) => emulated.try_handle_error(identifier, error).await,
+		}
+	}
+}
+
 pub struct ManualSealNode<NodeSpec>(PhantomData<NodeSpec>);
 
 impl<NodeSpec: NodeSpecT>
@@ -80,6 +143,8 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 		mut config: Configuration,
 		para_id: ParaId,
 		block_time: u64,
+		relay_emulator: Option<RelayEmulatorConfig>,
+		extensions: NodeExtensions,
 	) -> sc_service::error::Result<TaskManager>
 	where
 		Net: NetworkBackend<NodeSpec::Block, Hash>,
@@ -168,7 +233,16 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 				}
 			});
 
+		if let Some(config) = &relay_emulator {
+			log::info!(
+				"Emulating the relay chain, including blocks after {} blocks.",
+				config.inclusion_delay
+			);
+		}
+		let relay_emulator = relay_emulator.map(|config| config.build(para_id)).transpose()?;
+
 		let client_for_cidp = client.clone();
+		let relay_emulator_for_cidp = relay_emulator.clone();
 		let params = sc_consensus_manual_seal::ManualSealParams {
 			block_import: client.clone(),
 			env: proposer,
@@ -203,42 +277,63 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 					.unwrap_or_default();
 
 				let current_para_block_head =
-					Some(polkadot_primitives::HeadData(current_para_head.encode()));
+					polkadot_primitives::HeadData(current_para_head.encode());
 				let client_for_xcm = client_for_cidp.clone();
+				let relay_emulator = relay_emulator_for_cidp.clone();
 				async move {
 					use sp_runtime::traits::UniqueSaturatedInto;
 
-					let mocked_parachain = MockValidationDataInherentDataProvider {
-						// When using manual seal we start from block 0, and it's very unlikely to
-						// reach a block number > u32::MAX.
-						current_para_block: UniqueSaturatedInto::<u32>::unique_saturated_into(
-							*current_para_head.number(),
-						),
-						para_id,
-						current_para_block_head,
-						relay_offset: 0,
-						relay_blocks_per_para_block: requires_relay_progress
-							.then(|| 1)
-							.unwrap_or_default(),
-						para_blocks_per_relay_epoch: 10,
-						relay_randomness_config: (),
-						xcm_config: MockXcmConfig::new(&*client_for_xcm, block, Default::default()),
-						raw_downward_messages: vec![],
-						raw_horizontal_messages: vec![],
-						additional_key_values: None,
-						upgrade_go_ahead: should_send_go_ahead.then(|| {
-							log::info!(
-								"Detected pending validation code, sending go-ahead signal."
-							);
-							UpgradeGoAhead::GoAhead
-						}),
+					// When using manual seal we start from block 0, and it's very unlikely to
+					// reach a block number > u32::MAX.
+					let current_para_block = UniqueSaturatedInto::<u32>::unique_saturated_into(
+						*current_para_head.number(),
+					);
+					let xcm_config =
+						MockXcmConfig::new(&*client_for_xcm, block, Default::default());
+					let upgrade_go_ahead = should_send_go_ahead.then(|| {
+						log::info!("Detected pending validation code, sending go-ahead signal.");
+						UpgradeGoAhead::GoAhead
+					});
+
+					let parachain_inherent = match relay_emulator {
+						Some(emulator) => {
+							let params = RelayBlockParams {
+								para_parent_number: current_para_block,
+								para_parent_head: current_para_block_head,
+								xcm_config,
+								last_relay_parent_number: last_relay_chain_block_number(
+									&*client_for_xcm,
+									block,
+									Default::default(),
+								),
+								upgrade_go_ahead,
+							};
+							DevParachainInherent::emulated(&emulator, para_id, params).await?
+						},
+						None =>
+							DevParachainInherent::Mocked(MockValidationDataInherentDataProvider {
+								current_para_block,
+								para_id,
+								current_para_block_head: Some(current_para_block_head),
+								relay_offset: 0,
+								relay_blocks_per_para_block: requires_relay_progress
+									.then(|| 1)
+									.unwrap_or_default(),
+								para_blocks_per_relay_epoch: 10,
+								relay_randomness_config: (),
+								xcm_config,
+								raw_downward_messages: vec![],
+								raw_horizontal_messages: vec![],
+								additional_key_values: None,
+								upgrade_go_ahead,
+							}),
 					};
-					Ok((
+					Ok::<_, InherentError>((
 						// This is intentional, as the runtime that we expect to run against this
 						// will never receive the aura-related inherents/digests, and providing
 						// real timestamps would cause aura <> timestamp checking to fail.
 						sp_timestamp::InherentDataProvider::new(sp_timestamp::Timestamp::new(0)),
-						mocked_parachain,
+						parachain_inherent,
 					))
 				}
 			},
@@ -249,20 +344,42 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 			None,
 			authorship_future,
 		);
+		let extensions = extensions.enabled_at_best_block(&*client);
+		let extension_context = ExtensionContext {
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			backend: backend.clone(),
+			offchain_storage: backend.offchain_storage(),
+			spawn_handle: task_manager.spawn_handle(),
+			prometheus_registry: config.prometheus_registry().cloned(),
+			para_id,
+			is_dev: true,
+		};
 		let rpc_extensions_builder = {
 			let client = client.clone();
 			let transaction_pool = transaction_pool.clone();
 			let backend_for_rpc = backend.clone();
+			let extensions = extensions.clone();
+			let extension_context = extension_context.clone();
+			let relay_emulator = relay_emulator.clone();
 
 			Box::new(move |_| {
 				let mut module = NodeSpec::BuildRpcExtensions::build_rpc_extensions(
 					client.clone(),
 					backend_for_rpc.clone(),
 					transaction_pool.clone(),
+					// Blocks are not built by a collator, so there are no refused attempts.
+					Default::default(),
 				)?;
 				module
 					.merge(ManualSeal::new(manual_seal_sink.clone()).into_rpc())
 					.map_err(|e| sc_service::Error::Application(e.into()))?;
+				if let Some(emulator) = &relay_emulator {
+					module
+						.merge(RelayEmulatorRpc(emulator.clone()).into_rpc())
+						.map_err(|e| sc_service::Error::Application(e.into()))?;
+				}
+				extensions.merge_rpc_modules(&extension_context, &mut module)?;
 				Ok(module)
 			})
 		};
@@ -282,6 +399,8 @@ impl<NodeSpec: NodeSpecT> ManualSealNode<NodeSpec> {
 			telemetry: telemetry.as_mut(),
 		})?;
 
+		extensions.spawn_tasks(&extension_context)?;
+
 		Ok(task_manager)
 	}
 }
@@ -16,11 +16,16 @@
 
 pub mod aura;
 mod manual_seal;
+mod relay_emulator;
 
-use crate::common::spec::{DynNodeSpec, NodeSpec as NodeSpecT};
+use crate::common::{
+	extension::NodeExtensions,
+	spec::{DynNodeSpec, NodeSpec as NodeSpecT},
+};
 use cumulus_primitives_core::ParaId;
 use manual_seal::ManualSealNode;
 use polkadot_cli::service::IdentifyNetworkBackend;
+pub(crate) use relay_emulator::RelayEmulatorConfig;
 use sc_service::{Configuration, TaskManager};
 
 /// The current node version for cumulus official binaries, which takes the basic
@@ -38,6 +43,8 @@ pub trait DynNodeSpecExt: DynNodeSpec {
 		config: Configuration,
 		para_id: ParaId,
 		block_time: u64,
+		relay_emulator: Option<RelayEmulatorConfig>,
+		extensions: NodeExtensions,
 	) -> sc_service::error::Result<TaskManager>;
 }
 
@@ -51,6 +58,8 @@ where
 		config: Configuration,
 		para_id: ParaId,
 		block_time: u64,
+		relay_emulator: Option<RelayEmulatorConfig>,
+		extensions: NodeExtensions,
 	) -> sc_service::error::Result<TaskManager> {
 		let node = ManualSealNode::<T>::new();
 
@@ -59,9 +68,21 @@ where
 		let network_backend = config.network.network_backend.unwrap_or(default_backend);
 		match network_backend {
 			sc_network::config::NetworkBackendType::Libp2p =>
-				node.start_node::<sc_network::NetworkWorker<_, _>>(config, para_id, block_time),
+				node.start_node::<sc_network::NetworkWorker<_, _>>(
+					config,
+					para_id,
+					block_time,
+					relay_emulator,
+					extensions,
+				),
 			sc_network::config::NetworkBackendType::Litep2p =>
-				node.start_node::<sc_network::Litep2pNetworkBackend>(config, para_id, block_time),
+				node.start_node::<sc_network::Litep2pNetworkBackend>(
+					config,
+					para_id,
+					block_time,
+					relay_emulator,
+					extensions,
+				),
 		}
 	}
 }
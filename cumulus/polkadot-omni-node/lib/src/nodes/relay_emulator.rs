@@ -0,0 +1,146 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration and RPC of the relay chain emulator used by dev nodes.
+
+use cumulus_client_parachain_inherent::RelayChainEmulator;
+use cumulus_primitives_core::ParaId;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::Deserialize;
+use sp_core::Bytes;
+use std::path::{Path, PathBuf};
+
+/// Configuration of the relay chain emulator.
+#[derive(Debug, Clone)]
+pub(crate) struct RelayEmulatorConfig {
+	/// The number of para blocks after which a para block is included.
+	pub inclusion_delay: u32,
+	/// A JSON file with the messages sent to the parachain in the first relay block.
+	pub messages: Option<PathBuf>,
+}
+
+impl RelayEmulatorConfig {
+	/// Create the emulator and queue the messages of the messages file.
+	pub(crate) fn build(&self, para_id: ParaId) -> sc_service::error::Result<RelayChainEmulator> {
+		let emulator = RelayChainEmulator::new(para_id, self.inclusion_delay);
+		if let Some(path) = &self.messages {
+			MessagesFile::load(path)?.queue(&emulator);
+		}
+
+		Ok(emulator)
+	}
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MessagesFile {
+	#[serde(default)]
+	downward: Vec<Bytes>,
+	#[serde(default)]
+	horizontal: Vec<HorizontalMessage>,
+	#[serde(default)]
+	outbound_channels: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HorizontalMessage {
+	sender: u32,
+	data: Bytes,
+}
+
+impl MessagesFile {
+	fn load(path: &Path) -> sc_service::error::Result<Self> {
+		let file = std::fs::File::open(path).map_err(|e| {
+			sc_service::Error::Other(format!("Failed to open {}: {e}", path.display()))
+		})?;
+		serde_json::from_reader(std::io::BufReader::new(file)).map_err(|e| {
+			sc_service::Error::Other(format!("Invalid relay emulator messages file: {e}"))
+		})
+	}
+
+	fn queue(self, emulator: &RelayChainEmulator) {
+		for recipient in self.outbound_channels {
+			emulator.open_outbound_channel(recipient.into());
+		}
+		for message in self.downward {
+			emulator.send_downward_message(message.0);
+		}
+		for message in self.horizontal {
+			emulator.send_horizontal_message(message.sender.into(), message.data.0);
+		}
+	}
+}
+
+/// RPC methods for sending messages through the relay chain emulator.
+#[rpc(server)]
+pub trait RelayEmulatorApi {
+	/// Send a downward message, delivered in the next relay block.
+	#[method(name = "relayEmulator_sendDownwardMessage")]
+	fn send_downward_message(&self, message: Bytes) -> RpcResult<()>;
+
+	/// Send a horizontal message from the `sender` parachain, delivered in the next relay block.
+	#[method(name = "relayEmulator_sendHorizontalMessage")]
+	fn send_horizontal_message(&self, sender: u32, message: Bytes) -> RpcResult<()>;
+
+	/// Open an outbound HRMP channel to the `recipient` parachain.
+	#[method(name = "relayEmulator_openOutboundChannel")]
+	fn open_outbound_channel(&self, recipient: u32) -> RpcResult<()>;
+}
+
+/// Implementation of [`RelayEmulatorApiServer`].
+pub(crate) struct RelayEmulatorRpc(pub RelayChainEmulator);
+
+impl RelayEmulatorApiServer for RelayEmulatorRpc {
+	fn send_downward_message(&self, message: Bytes) -> RpcResult<()> {
+		self.0.send_downward_message(message.0);
+		Ok(())
+	}
+
+	fn send_horizontal_message(&self, sender: u32, message: Bytes) -> RpcResult<()> {
+		self.0.send_horizontal_message(sender.into(), message.0);
+		Ok(())
+	}
+
+	fn open_outbound_channel(&self, recipient: u32) -> RpcResult<()> {
+		self.0.open_outbound_channel(recipient.into());
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn messages_file_is_parsed() {
+		let messages: MessagesFile = serde_json::from_str(
+			r#"{
+				"downward": ["0x0102"],
+				"horizontal": [{ "sender": 2000, "data": "0x03" }],
+				"outboundChannels": [2001]
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(messages.downward, vec![Bytes(vec![1, 2])]);
+		assert_eq!(messages.horizontal[0].sender, 2000);
+		assert_eq!(messages.horizontal[0].data, Bytes(vec![3]));
+		assert_eq!(messages.outbound_channels, vec![2001]);
+
+		assert!(serde_json::from_str::<MessagesFile>(r#"{ "upward": [] }"#).is_err());
+	}
+}
@@ -27,6 +27,7 @@ use scale_info::TypeInfo;
 use sp_runtime::RuntimeDebug;
 
 pub mod parachain_block_data;
+pub mod unincluded_segment;
 
 pub use parachain_block_data::ParachainBlockData;
 pub use polkadot_core_primitives::InboundDownwardMessage;
@@ -43,6 +44,10 @@ pub use sp_runtime::{
 	traits::Block as BlockT,
 	ConsensusEngineId,
 };
+pub use unincluded_segment::{
+	BuildRefusal, ConsensusHookCapacity, HrmpBandwidth, SegmentAncestor, SegmentBandwidth,
+	UnincludedSegmentInfo,
+};
 pub use xcm::latest::prelude::*;
 
 /// A module that re-exports relevant relay chain definitions.
@@ -179,6 +184,12 @@ pub enum ChannelStatus {
 pub trait XcmpMessageSource {
 	/// Take a single XCMP message from the queue for the given `dest`, if one exists.
 	fn take_outbound_messages(maximum_channels: usize) -> Vec<(ParaId, Vec<u8>)>;
+
+	/// The weight `take_outbound_messages` consumes on top of taking the messages, e.g. for
+	/// reading the configuration of the outbound channels.
+	fn take_outbound_messages_overhead() -> Weight {
+		Weight::zero()
+	}
 }
 
 impl XcmpMessageSource for () {
@@ -381,4 +392,22 @@ sp_api::decl_runtime_apis! {
 		/// Retrieve core selector and claim queue offset for the next block.
 		fn core_selector() -> (CoreSelector, ClaimQueueOffset);
 	}
+
+	/// Runtime api to inspect the unincluded segment and the limits of the consensus hook.
+	///
+	/// It is meant for diagnostics, e.g. to find out why a collator stopped building blocks.
+	pub trait UnincludedSegmentApi {
+		/// The current unincluded segment.
+		fn unincluded_segment() -> UnincludedSegmentInfo<Block::Hash>;
+		/// The limits applied by the consensus hook.
+		fn consensus_hook_capacity() -> ConsensusHookCapacity;
+		/// The reason a block can not be built on top of this one, assuming `included_hash` is
+		/// the most recently included block and `relay_slot` the slot of the relay parent.
+		///
+		/// Returns `None` if building is allowed.
+		fn build_refusal(
+			included_hash: Block::Hash,
+			relay_slot: relay_chain::Slot,
+		) -> Option<BuildRefusal>;
+	}
 }
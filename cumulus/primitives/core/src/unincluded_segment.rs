@@ -0,0 +1,118 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Cumulus.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Diagnostic view of the unincluded segment, exposed by the [`crate::UnincludedSegmentApi`].
+
+use crate::{relay_chain, ParaId};
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+/// Bandwidth used by the messages sent to a sibling parachain.
+#[derive(
+	Clone, Debug, Default, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct HrmpBandwidth {
+	/// The recipient of the messages.
+	pub recipient: ParaId,
+	/// The number of messages sent.
+	pub msg_count: u32,
+	/// The total size of the messages sent, in bytes.
+	pub total_bytes: u32,
+}
+
+/// Bandwidth used by a block or by the whole unincluded segment.
+#[derive(
+	Clone, Debug, Default, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentBandwidth {
+	/// The number of upward messages sent.
+	pub ump_msg_count: u32,
+	/// The total size of the upward messages sent, in bytes.
+	pub ump_total_bytes: u32,
+	/// The bandwidth used per outbound HRMP channel.
+	pub hrmp_outgoing: Vec<HrmpBandwidth>,
+}
+
+/// A block of the unincluded segment.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentAncestor<Hash> {
+	/// The hash of the block head, if it is already known.
+	///
+	/// It is only known once a descendant of the block has been imported.
+	pub para_head_hash: Option<Hash>,
+	/// The bandwidth used by the block.
+	pub used_bandwidth: SegmentBandwidth,
+}
+
+/// The unincluded segment as seen by the runtime at some block.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnincludedSegmentInfo<Hash> {
+	/// The blocks of the segment, from the oldest to the most recent one.
+	pub ancestors: Vec<SegmentAncestor<Hash>>,
+	/// The bandwidth used by the whole segment.
+	pub used_bandwidth: SegmentBandwidth,
+	/// The HRMP watermark of the most recent block of the segment.
+	pub hrmp_watermark: Option<relay_chain::BlockNumber>,
+	/// The number of the relay parent of the most recent block.
+	pub relay_parent_number: Option<relay_chain::BlockNumber>,
+}
+
+/// The limits applied by the consensus hook of the runtime.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusHookCapacity {
+	/// The maximum number of blocks in the unincluded segment.
+	pub unincluded_segment_capacity: u32,
+	/// The maximum number of blocks authored per relay chain slot.
+	pub velocity: u32,
+	/// The last relay chain slot a block was authored in and the number of blocks authored in it.
+	pub relay_slot_info: Option<(relay_chain::Slot, u32)>,
+}
+
+/// The reason the runtime refuses a block to be built.
+#[derive(Clone, Debug, Encode, Decode, PartialEq, Eq, TypeInfo, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BuildRefusal {
+	/// The unincluded segment has no space left for another block.
+	UnincludedSegmentFull {
+		/// The size of the segment after the included block.
+		size: u32,
+		/// The capacity of the segment.
+		capacity: u32,
+	},
+	/// The maximum number of blocks was already authored in the relay chain slot.
+	VelocityExceeded {
+		/// The relay chain slot.
+		relay_slot: relay_chain::Slot,
+		/// The number of blocks authored in the slot.
+		authored: u32,
+		/// The maximum number of blocks per slot.
+		velocity: u32,
+	},
+	/// The relay parent is older than the one of the last authored block.
+	RelayParentTooOld {
+		/// The relay chain slot of the relay parent.
+		relay_slot: relay_chain::Slot,
+		/// The relay chain slot of the last authored block.
+		last_relay_slot: relay_chain::Slot,
+	},
+}
@@ -19,14 +19,20 @@
 mod block_builder;
 pub use block_builder::*;
 use codec::{Decode, Encode};
+use cumulus_pallet_solo_to_para::migration::{MigrateToAura, UpgradeSessionKeys};
 pub use cumulus_test_runtime as runtime;
 use cumulus_test_runtime::AuraId;
+use frame_support::{
+	parameter_types,
+	storage::{storage_prefix, unhashed},
+	traits::OnRuntimeUpgrade,
+};
 pub use polkadot_parachain_primitives::primitives::{
 	BlockData, HeadData, ValidationParams, ValidationResult,
 };
 use runtime::{
-	Balance, Block, BlockHashCount, Runtime, RuntimeCall, Signature, SignedPayload, TxExtension,
-	UncheckedExtrinsic, VERSION,
+	AccountId, Balance, Block, BlockHashCount, Runtime, RuntimeCall, SessionKeys, Signature,
+	SignedPayload, TxExtension, UncheckedExtrinsic, VERSION,
 };
 use sc_consensus_aura::{
 	find_pre_digest,
@@ -39,10 +45,15 @@ use sp_api::ProvideRuntimeApi;
 use sp_application_crypto::AppCrypto;
 use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::AuraApi;
-use sp_core::Pair;
+use sp_core::{crypto::key_types, Pair};
 use sp_io::TestExternalities;
 use sp_keystore::testing::MemoryKeystore;
-use sp_runtime::{generic::Era, traits::Header, BuildStorage, MultiAddress, SaturatedConversion};
+use sp_runtime::{
+	generic::Era,
+	traits::{Header, OpaqueKeys},
+	BuildStorage, KeyTypeId, MultiAddress, SaturatedConversion,
+};
+use sp_state_machine::BasicExternalities;
 use std::sync::Arc;
 pub use substrate_test_client::*;
 
@@ -76,11 +87,13 @@ pub type Client = client::Client<Backend, Executor, Block, runtime::RuntimeApi>;
 pub struct GenesisParameters {
 	pub endowed_accounts: Vec<cumulus_test_runtime::AccountId>,
 	pub wasm: Option<Vec<u8>>,
+	/// Start from the state of a solochain migrated with [`migrate_from_solochain`].
+	pub migrated_from_solochain: bool,
 }
 
 impl substrate_test_client::GenesisInit for GenesisParameters {
 	fn genesis_storage(&self) -> Storage {
-		cumulus_test_service::chain_spec::get_chain_spec_with_extra_endowed(
+		let mut storage = cumulus_test_service::chain_spec::get_chain_spec_with_extra_endowed(
 			None,
 			self.endowed_accounts.clone(),
 			self.wasm.as_deref().unwrap_or_else(|| {
@@ -88,10 +101,89 @@ impl substrate_test_client::GenesisInit for GenesisParameters {
 			}),
 		)
 		.build_storage()
-		.expect("Builds test runtime genesis storage")
+		.expect("Builds test runtime genesis storage");
+
+		if self.migrated_from_solochain {
+			migrate_from_solochain(&mut storage);
+		}
+		storage
+	}
+}
+
+/// The session keys of the solochain in [`migrate_from_solochain`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+struct SoloSessionKeys {
+	babe: [u8; 32],
+	aura: AuraId,
+}
+
+impl OpaqueKeys for SoloSessionKeys {
+	type KeyTypeIdProviders = ();
+
+	fn key_ids() -> &'static [KeyTypeId] {
+		&[key_types::BABE, key_types::AURA]
+	}
+
+	fn get_raw(&self, i: KeyTypeId) -> &[u8] {
+		match i {
+			key_types::BABE => &self.babe[..],
+			key_types::AURA => self.aura.as_ref(),
+			_ => &[],
+		}
+	}
+}
+
+struct KeepAuraKey;
+
+impl UpgradeSessionKeys<Runtime> for KeepAuraKey {
+	type OldKeys = SoloSessionKeys;
+
+	fn upgrade(_: AccountId, old: SoloSessionKeys) -> SessionKeys {
+		SessionKeys { aura: old.aura }
 	}
 }
 
+parameter_types! {
+	SoloConsensusPallets: Vec<&'static str> = vec!["Babe", "Grandpa"];
+}
+
+/// Turn the genesis `storage` into the state of the last block of a solochain using BABE and
+/// GRANDPA, after it applied [`MigrateToAura`].
+///
+/// The Aura authorities of the genesis config are the validators of the solochain, so the
+/// migration seeds the same authorities again.
+pub fn migrate_from_solochain(storage: &mut Storage) {
+	BasicExternalities::execute_with_storage(storage, || {
+		let authorities = pallet_aura::Authorities::<Runtime>::take();
+		unhashed::kill(&storage_prefix(b"AuraExt", b"Authorities"));
+		for pallet in SoloConsensusPallets::get() {
+			unhashed::put(&storage_prefix(pallet.as_bytes(), b"Authorities"), &authorities);
+		}
+
+		let validators = authorities
+			.into_iter()
+			.map(|aura| {
+				let validator = AccountId::from(aura.clone().into_inner());
+				let keys = SoloSessionKeys { babe: validator.clone().into(), aura };
+				unhashed::put(
+					&pallet_session::NextKeys::<Runtime>::hashed_key_for(&validator),
+					&keys,
+				);
+				for id in SoloSessionKeys::key_ids() {
+					pallet_session::KeyOwner::<Runtime>::insert(
+						(*id, keys.get_raw(*id).to_vec()),
+						&validator,
+					);
+				}
+				validator
+			})
+			.collect::<Vec<_>>();
+		pallet_session::Validators::<Runtime>::put(validators);
+
+		MigrateToAura::<Runtime, KeepAuraKey, SoloConsensusPallets>::on_runtime_upgrade();
+	});
+}
+
 /// A `test-runtime` extensions to [`TestClientBuilder`].
 pub trait TestClientBuilderExt: Sized {
 	/// Build the test client.
@@ -224,6 +316,45 @@ pub fn validate_block(
 		.map(|v| ValidationResult::decode(&mut &v[..]).expect("Decode `ValidationResult`."))
 }
 
+/// Validate the first block of a parachain that migrated from a solochain.
+///
+/// `exported_head` is the head data of the last solochain block, as written by the `export-head`
+/// command and registered as the genesis head of the parachain. Checks that the first block in
+/// `block_data` builds on top of it and calls `validate_block` with it as the parent head, which
+/// checks the storage proof of the block against the state root of the exported head.
+///
+/// Returns the head data of the validated block.
+pub fn validate_first_block(
+	exported_head: &[u8],
+	block_data: ParachainBlockData,
+	relay_parent_storage_root: runtime::Hash,
+	wasm_blob: &[u8],
+) -> Result<HeadData, String> {
+	let parent = runtime::Header::decode(&mut &exported_head[..])
+		.map_err(|e| format!("Failed to decode the exported head: {e}"))?;
+	let first = block_data.blocks().first().ok_or("No block to validate")?.header();
+	if *first.parent_hash() != parent.hash() || *first.number() != parent.number() + 1 {
+		return Err(format!(
+			"Block #{} doesn't build on the exported head #{} ({:?})",
+			first.number(),
+			parent.number(),
+			parent.hash(),
+		))
+	}
+
+	validate_block(
+		ValidationParams {
+			block_data: BlockData(block_data.encode()),
+			parent_head: HeadData(exported_head.to_vec()),
+			relay_parent_number: 1,
+			relay_parent_storage_root,
+		},
+		wasm_blob,
+	)
+	.map(|result| result.head_data)
+	.map_err(|e| format!("`validate_block` failed: {e}"))
+}
+
 fn get_keystore() -> sp_keystore::KeystorePtr {
 	let keystore = MemoryKeystore::new();
 	sp_keyring::Sr25519Keyring::iter().for_each(|key| {
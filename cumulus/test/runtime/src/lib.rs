@@ -60,7 +60,7 @@ use sp_core::{ConstBool, ConstU32, ConstU64, OpaqueMetadata};
 use cumulus_primitives_core::{ClaimQueueOffset, CoreSelector};
 use sp_runtime::{
 	generic, impl_opaque_keys,
-	traits::{BlakeTwo256, Block as BlockT, IdentifyAccount, Verify},
+	traits::{BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, OpaqueKeys, Verify},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiAddress, MultiSignature,
 };
@@ -267,6 +267,19 @@ parameter_types! {
 
 impl cumulus_pallet_aura_ext::Config for Runtime {}
 
+impl pallet_session::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionLength, Offset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionLength, Offset>;
+	type SessionManager = ();
+	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = SessionKeys;
+	type DisablingStrategy = ();
+	type WeightInfo = ();
+}
+
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
 	type Moment = u64;
@@ -374,6 +387,7 @@ construct_runtime! {
 		Aura: pallet_aura,
 		AuraExt: cumulus_pallet_aura_ext,
 		WeightReclaim: cumulus_pallet_weight_reclaim,
+		Session: pallet_session,
 	}
 }
 
@@ -571,6 +585,23 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_primitives_core::UnincludedSegmentApi<Block> for Runtime {
+		fn unincluded_segment() -> cumulus_primitives_core::UnincludedSegmentInfo<<Block as BlockT>::Hash> {
+			ParachainSystem::unincluded_segment_info()
+		}
+
+		fn consensus_hook_capacity() -> cumulus_primitives_core::ConsensusHookCapacity {
+			ConsensusHook::consensus_hook_capacity()
+		}
+
+		fn build_refusal(
+			included_hash: <Block as BlockT>::Hash,
+			relay_slot: cumulus_primitives_aura::Slot,
+		) -> Option<cumulus_primitives_core::BuildRefusal> {
+			ConsensusHook::build_refusal(included_hash, relay_slot)
+		}
+	}
+
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_state::<RuntimeGenesisConfig>(config)
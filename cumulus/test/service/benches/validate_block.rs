@@ -91,8 +91,11 @@ fn benchmark_block_validation(c: &mut Criterion) {
 	let para_id = ParaId::from(cumulus_test_runtime::PARACHAIN_ID);
 	let mut test_client_builder = TestClientBuilder::with_default_backend();
 	let genesis_init = test_client_builder.genesis_init_mut();
-	*genesis_init =
-		cumulus_test_client::GenesisParameters { endowed_accounts: account_ids, wasm: None };
+	*genesis_init = cumulus_test_client::GenesisParameters {
+		endowed_accounts: account_ids,
+		wasm: None,
+		migrated_from_solochain: false,
+	};
 	let client = test_client_builder.build_with_native_executor(None).0;
 
 	let (max_transfer_count, extrinsics) = create_extrinsics(&client, &src_accounts, &dst_accounts);
@@ -447,6 +447,7 @@ where
 		relay_chain_slot_duration,
 		recovery_handle,
 		sync_service: sync_service.clone(),
+		pov_sources: Vec::new(),
 	})?;
 
 	if let Some(collator_key) = collator_key {
@@ -509,6 +510,7 @@ where
 					spawner: task_manager.spawn_handle(),
 					export_pov: None,
 					max_pov_percentage: None,
+					refused_build_attempt: None,
 				};
 
 				slot_based::run::<Block, AuthorityPair, _, _, _, _, _, _, _, _, _>(params);
@@ -536,6 +538,7 @@ where
 					authoring_duration: Duration::from_millis(2000),
 					reinitialize: false,
 					max_pov_percentage: None,
+					refused_build_attempt: None,
 				};
 
 				let fut = aura::run::<Block, AuthorityPair, _, _, _, _, _, _, _, _>(params);
@@ -172,7 +172,9 @@
 //!   scaling (a step
 //! * Ability to run any runtime with [`--dev-block-time`] flag. This uses
 //!   [`sc_consensus_manual_seal`] under the hood, and has no restrictions on the runtime's
-//!   consensus.
+//!   consensus. With [`--relay-emulator`], the relay chain is emulated in memory, so that downward
+//!   and horizontal messages can be delivered to the runtime and para blocks are included with a
+//!   delay, like with asynchronous backing.
 //!
 //! [This](https://github.com/paritytech/polkadot-sdk/issues/5565) future improvement to OmniNode
 //! aims to make such checks automatic.
@@ -194,6 +196,7 @@
 //! [`templates`]: crate::polkadot_sdk::templates
 //! [`parachain-template`]: https://github.com/paritytech/polkadot-sdk-parachain-template
 //! [`--dev-block-time`]: polkadot_omni_node_lib::cli::Cli::dev_block_time
+//! [`--relay-emulator`]: polkadot_omni_node_lib::cli::Cli::relay_emulator
 //! [`polkadot-omni-node`]: https://crates.io/crates/polkadot-omni-node
 //! [`chain-spec-builder`]: https://crates.io/crates/staging-chain-spec-builder
 //! [`cumulus-pallet-parachain-system`]: https://docs.rs/cumulus-pallet-parachain-system/latest/cumulus_pallet_parachain_system/
@@ -56,6 +56,67 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Inspect and repair the approval-voting data of a stopped node.
+	ApprovalDb(ApprovalDbCmd),
+}
+
+/// The `approval-db` command.
+#[derive(Debug, Parser)]
+pub struct ApprovalDbCmd {
+	/// What to do with the approval-voting data.
+	#[command(subcommand)]
+	pub action: ApprovalDbAction,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+/// The actions of the `approval-db` command.
+#[derive(Debug, clap::Subcommand)]
+pub enum ApprovalDbAction {
+	/// List all blocks and the approval status of their candidates.
+	List,
+
+	/// Check the invariants between block, candidate and approval entries.
+	Check,
+
+	/// Check the invariants and repair the violated ones.
+	///
+	/// Blocks which cannot be repaired are removed together with their descendants, they will
+	/// not be finalized by this node until it sees them again.
+	Repair {
+		/// Only print what would be repaired.
+		#[arg(long)]
+		dry_run: bool,
+	},
+
+	/// Remove a block, its descendants and their approval entries.
+	PruneBlock {
+		/// The hash of the block.
+		block_hash: polkadot_service::Hash,
+	},
+
+	/// Remove a candidate from all blocks including it.
+	PruneCandidate {
+		/// The hash of the candidate.
+		candidate_hash: polkadot_service::Hash,
+	},
+}
+
+impl sc_cli::CliConfiguration for ApprovalDbCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
 }
 
 #[allow(missing_docs)]
@@ -142,6 +203,34 @@ pub struct RunCmd {
 	///  **Dangerous!** Do not touch unless explicitly advised to.
 	#[arg(long)]
 	pub prepare_workers_hard_max_num: Option<usize>,
+	/// Override the total size, in MiB, of the prepared PVF artifacts above which the least
+	/// recently needed ones are pruned. Artifacts needed in the current or the previous session
+	/// are never pruned.
+	#[arg(long, value_name = "MiB")]
+	pub pvf_artifacts_cache_size: Option<u64>,
+	/// Record the signals and messages received by the dispute-coordinator and approval-voting
+	/// subsystems, and the responses to their runtime API, chain API and availability store
+	/// requests, to rotating files in the given directory, for post-mortem debugging.
+	///
+	/// Can not be used with approval-voting-parallel, which has to be disabled with
+	/// `--enable-approval-voting-parallel=false`.
+	///
+	/// Recordings can be replayed into a single subsystem with the replay harness of
+	/// `polkadot-node-subsystem-test-helpers`.
+	#[arg(long, value_name = "PATH")]
+	pub overseer_recording_path: Option<PathBuf>,
+	/// Pick and order the availability recovery strategies based on the latency and failures
+	/// observed from the validators, instead of always trying the backers first for small PoVs.
+	///
+	/// Experimental, disabled by default.
+	#[arg(long)]
+	pub adaptive_availability_recovery: bool,
+	/// Use the portable erasure-coding implementation even if the CPU supports SIMD instructions.
+	///
+	/// Both produce the same chunks, this is only meant as a fallback. Setting
+	/// `POLKADOT_ERASURE_CODING_BACKEND=scalar` has the same effect.
+	#[arg(long)]
+	pub force_scalar_erasure_coding: bool,
 	/// TESTING ONLY: disable the version check between nodes and workers.
 	#[arg(long, hide = true)]
 	pub disable_worker_version_check: bool,
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand, NODE_VERSION};
+use crate::cli::{ApprovalDbAction, Cli, Subcommand, NODE_VERSION};
 use frame_benchmarking_cli::{
 	BenchmarkCmd, ExtrinsicFactory, SubstrateRemarkBuilder, SUBSTRATE_REFERENCE_HARDWARE,
 };
@@ -203,6 +203,15 @@ where
 
 	let secure_validator_mode = cli.run.base.validator && !cli.run.insecure_validator;
 
+	// The messages of approval-voting-parallel can't be recorded.
+	if cli.run.overseer_recording_path.is_some() && cli.run.enable_approval_voting_parallel {
+		return Err(Error::OverseerRecordingWithApprovalVotingParallel)
+	}
+
+	if cli.run.force_scalar_erasure_coding && !polkadot_erasure_coding::force_scalar_backend() {
+		log::warn!("The erasure-coding backend was already selected, ignoring the override");
+	}
+
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = (!cli.run.no_hardware_benchmarks)
 			.then(|| {
@@ -234,8 +243,14 @@ where
 				execute_workers_max_num: cli.run.execute_workers_max_num,
 				prepare_workers_hard_max_num: cli.run.prepare_workers_hard_max_num,
 				prepare_workers_soft_max_num: cli.run.prepare_workers_soft_max_num,
+				pvf_artifacts_cache_size_limit: cli
+					.run
+					.pvf_artifacts_cache_size
+					.map(|mib| mib.saturating_mul(1024 * 1024)),
 				enable_approval_voting_parallel: cli.run.enable_approval_voting_parallel,
 				keep_finalized_for: cli.run.keep_finalized_for,
+				overseer_recording_path: cli.run.overseer_recording_path,
+				adaptive_availability_recovery: cli.run.adaptive_availability_recovery,
 			},
 		)
 		.map(|full| full.task_manager)?;
@@ -463,6 +478,10 @@ pub fn run() -> Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			Ok(runner.sync_run(|config| cmd.run::<polkadot_service::Block>(&config))?)
 		},
+		Some(Subcommand::ApprovalDb(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| approval_db(&cmd.action, &config))
+		},
 	}?;
 
 	#[cfg(feature = "pyroscope")]
@@ -472,3 +491,61 @@ pub fn run() -> Result<()> {
 	}
 	Ok(())
 }
+
+fn db_error(err: impl std::fmt::Display) -> Error {
+	Error::Other(err.to_string())
+}
+
+fn approval_db(action: &ApprovalDbAction, config: &sc_service::Configuration) -> Result<()> {
+	let mut inspector = polkadot_service::approval_db_inspector(config)?;
+
+	match action {
+		ApprovalDbAction::List =>
+			for block in inspector.blocks().map_err(db_error)? {
+				println!(
+					"#{} {:?} (parent {:?}, session {}): {}",
+					block.block_number,
+					block.block_hash,
+					block.parent_hash,
+					block.session,
+					if block.approved { "approved" } else { "pending" },
+				);
+				for candidate in block.candidates {
+					println!(
+						"\t{:?} on core {}: {}, {} approvals, {} assignments",
+						candidate.candidate_hash.0,
+						candidate.core_index.0,
+						if candidate.approved { "approved" } else { "pending" },
+						candidate.approvals.map_or("no".into(), |n| n.to_string()),
+						candidate.assignments.map_or("no".into(), |n| n.to_string()),
+					);
+				}
+			},
+		ApprovalDbAction::Check => {
+			let inconsistencies = inspector.check().map_err(db_error)?;
+			for inconsistency in &inconsistencies {
+				println!("{inconsistency}");
+			}
+			if !inconsistencies.is_empty() {
+				return Err(format!("Found {} inconsistencies", inconsistencies.len()).into())
+			}
+		},
+		ApprovalDbAction::Repair { dry_run } => {
+			let inconsistencies = inspector.check().map_err(db_error)?;
+			for inconsistency in &inconsistencies {
+				println!("{inconsistency}");
+			}
+			if !dry_run {
+				inspector.repair(&inconsistencies).map_err(db_error)?;
+				println!("Repaired {} inconsistencies", inconsistencies.len());
+			}
+		},
+		ApprovalDbAction::PruneBlock { block_hash } =>
+			inspector.prune_block(*block_hash).map_err(db_error)?,
+		ApprovalDbAction::PruneCandidate { candidate_hash } => inspector
+			.prune_candidate(polkadot_service::CandidateHash(*candidate_hash))
+			.map_err(db_error)?,
+	}
+
+	Ok(())
+}
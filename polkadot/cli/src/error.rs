@@ -53,6 +53,12 @@ pub enum Error {
 
 	#[error("This subcommand is only available when compiled with `{feature}`")]
 	FeatureNotEnabled { feature: &'static str },
+
+	#[error(
+		"`--overseer-recording-path` can not be used with approval-voting-parallel, pass \
+		`--enable-approval-voting-parallel=false` to record approval voting"
+	)]
+	OverseerRecordingWithApprovalVotingParallel,
 }
 
 impl From<String> for Error {
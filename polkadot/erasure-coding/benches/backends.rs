@@ -0,0 +1,114 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use polkadot_erasure_coding::{Backend, ScalarBackend, SimdBackend};
+use std::time::Duration;
+
+fn backends() -> Vec<Box<dyn Backend>> {
+	let mut backends: Vec<Box<dyn Backend>> =
+		vec![Box::new(ScalarBackend), Box::new(SimdBackend::portable())];
+	if let Some(simd) = SimdBackend::detect() {
+		backends.push(Box::new(simd));
+	}
+
+	backends
+}
+
+fn encode_and_reconstruct_5mb_pov(c: &mut Criterion) {
+	const N_VALIDATORS: [usize; 3] = [200, 1000, 2000];
+
+	const KB: usize = 1024;
+	const MB: usize = 1024 * KB;
+
+	let pov = vec![0xfe; 5 * MB];
+
+	let mut group = c.benchmark_group("encode");
+	for backend in backends() {
+		for n_validators in N_VALIDATORS {
+			group.throughput(Throughput::Bytes(pov.len() as u64));
+			group.bench_with_input(
+				BenchmarkId::new(backend.name(), n_validators),
+				&n_validators,
+				|b, &n| {
+					b.iter(|| backend.encode(n, &pov).unwrap());
+				},
+			);
+		}
+	}
+	group.finish();
+
+	let mut group = c.benchmark_group("reconstruct_systematic");
+	for backend in backends() {
+		for n_validators in N_VALIDATORS {
+			let chunks = ScalarBackend
+				.encode(n_validators, &pov)
+				.unwrap()
+				.into_iter()
+				.take(polkadot_erasure_coding::systematic_recovery_threshold(n_validators).unwrap())
+				.collect::<Vec<_>>();
+
+			group.throughput(Throughput::Bytes(pov.len() as u64));
+			group.bench_with_input(
+				BenchmarkId::new(backend.name(), n_validators),
+				&n_validators,
+				|b, &n| {
+					b.iter(|| backend.reconstruct_from_systematic(n, chunks.clone()).unwrap());
+				},
+			);
+		}
+	}
+	group.finish();
+
+	let mut group = c.benchmark_group("reconstruct");
+	for backend in backends() {
+		for n_validators in N_VALIDATORS {
+			let threshold = polkadot_erasure_coding::recovery_threshold(n_validators).unwrap();
+			// The last chunks, so that all the systematic ones must be recovered.
+			let chunks = ScalarBackend
+				.encode(n_validators, &pov)
+				.unwrap()
+				.into_iter()
+				.enumerate()
+				.map(|(i, chunk)| (i >= n_validators - threshold).then_some(chunk))
+				.collect::<Vec<_>>();
+
+			group.throughput(Throughput::Bytes(pov.len() as u64));
+			group.bench_with_input(
+				BenchmarkId::new(backend.name(), n_validators),
+				&n_validators,
+				|b, &n| {
+					b.iter(|| backend.reconstruct(n, chunks.clone()).unwrap());
+				},
+			);
+		}
+	}
+	group.finish();
+}
+
+fn criterion_config() -> Criterion {
+	Criterion::default()
+		.sample_size(15)
+		.warm_up_time(Duration::from_millis(200))
+		.measurement_time(Duration::from_secs(3))
+}
+
+criterion_group!(
+	name = backends_group;
+	config = criterion_config();
+	targets = encode_and_reconstruct_5mb_pov,
+);
+criterion_main!(backends_group);
@@ -0,0 +1,123 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Implementations of the Reed-Solomon code.
+//!
+//! All backends must produce exactly the same chunks, as the erasure root of a candidate commits
+//! to them. The fastest backend supported by the CPU is selected at runtime, unless the scalar
+//! one is forced with [`force_scalar_backend`] or by setting [`BACKEND_ENV_VAR`] to `scalar`.
+
+use std::sync::OnceLock;
+
+use novelpoly::WrappedShard;
+
+use crate::{code_params, Error};
+
+mod simd;
+
+pub use simd::SimdBackend;
+
+/// An implementation of the Reed-Solomon code.
+///
+/// Payloads are the encoded data, padded to a whole number of chunks by `encode`. Chunk lengths
+/// are checked to be even by the callers.
+pub trait Backend: Send + Sync {
+	/// A short name of the backend, for logs and benchmarks.
+	fn name(&self) -> &'static str;
+
+	/// Encode the payload into one chunk per validator.
+	fn encode(&self, n_validators: usize, payload: &[u8]) -> Result<Vec<Vec<u8>>, Error>;
+
+	/// Reconstruct the payload from the chunks present, indexed by validator.
+	fn reconstruct(
+		&self,
+		n_validators: usize,
+		chunks: Vec<Option<Vec<u8>>>,
+	) -> Result<Vec<u8>, Error>;
+
+	/// Reconstruct the payload from the systematic chunks, in order.
+	fn reconstruct_from_systematic(
+		&self,
+		n_validators: usize,
+		chunks: Vec<Vec<u8>>,
+	) -> Result<Vec<u8>, Error>;
+}
+
+/// The portable implementation provided by `novelpoly`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarBackend;
+
+impl Backend for ScalarBackend {
+	fn name(&self) -> &'static str {
+		"scalar"
+	}
+
+	fn encode(&self, n_validators: usize, payload: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+		let shards = code_params(n_validators)?.make_encoder().encode::<WrappedShard>(payload)?;
+
+		Ok(shards.into_iter().map(|w: WrappedShard| w.into_inner()).collect())
+	}
+
+	fn reconstruct(
+		&self,
+		n_validators: usize,
+		chunks: Vec<Option<Vec<u8>>>,
+	) -> Result<Vec<u8>, Error> {
+		let shards = chunks.into_iter().map(|chunk| chunk.map(WrappedShard::new)).collect();
+
+		Ok(code_params(n_validators)?.make_encoder().reconstruct(shards)?)
+	}
+
+	fn reconstruct_from_systematic(
+		&self,
+		n_validators: usize,
+		chunks: Vec<Vec<u8>>,
+	) -> Result<Vec<u8>, Error> {
+		let shards = chunks.into_iter().map(WrappedShard::new).collect();
+
+		Ok(code_params(n_validators)?.make_encoder().reconstruct_from_systematic(shards)?)
+	}
+}
+
+/// The environment variable which forces the [`ScalarBackend`] when set to `scalar`.
+pub const BACKEND_ENV_VAR: &str = "POLKADOT_ERASURE_CODING_BACKEND";
+
+static BACKEND: OnceLock<Box<dyn Backend>> = OnceLock::new();
+
+/// The backend used by this crate, the fastest one supported by the CPU unless the scalar one is
+/// forced.
+pub fn backend() -> &'static dyn Backend {
+	BACKEND
+		.get_or_init(|| {
+			if std::env::var(BACKEND_ENV_VAR).is_ok_and(|backend| backend == "scalar") {
+				return Box::new(ScalarBackend)
+			}
+
+			match SimdBackend::detect() {
+				Some(simd) => Box::new(simd),
+				None => Box::new(ScalarBackend),
+			}
+		})
+		.as_ref()
+}
+
+/// Use the [`ScalarBackend`] even if the CPU supports SIMD instructions.
+///
+/// Must be called before the first erasure coding operation. Returns `false` if another backend
+/// was already selected.
+pub fn force_scalar_backend() -> bool {
+	BACKEND.set(Box::new(ScalarBackend)).is_ok() || backend().name() == ScalarBackend.name()
+}
@@ -0,0 +1,716 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encoder and decoder which process many rounds of the code at once with SIMD instructions.
+//!
+//! The payload is encoded in rounds of `k` 16-bit symbols, each round contributing one symbol to
+//! every chunk. The encoder follows `novelpoly`, i.e. the additive FFT over the novel polynomial
+//! basis of `GF(2^16)` by Lin, Chung and Han, but runs `LANES` rounds side by side. Field
+//! elements of all lanes are stored as planes of low and high bytes, which allows multiplying
+//! them by a constant with four nibble table lookups per byte plane, as done by Leopard-RC.
+//!
+//! Reconstruction from arbitrary chunks follows the erasure decoding of `novelpoly` in the same
+//! way. The error locator polynomial only depends on which chunks are missing, so it is evaluated
+//! once and its values are turned into multiplication tables shared by all rounds. Reconstruction
+//! from systematic chunks is a plain transposition.
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, OnceLock},
+};
+
+use super::Backend;
+use crate::{code_params, Error};
+
+const FIELD_BITS: usize = 16;
+const FIELD_SIZE: usize = 1 << FIELD_BITS;
+const ONEMASK: u16 = (FIELD_SIZE - 1) as u16;
+
+/// `x^16 + x^5 + x^3 + x^2 + 1` without the leading term.
+const GENERATOR: usize = 0x2D;
+
+/// The Cantor basis of the field, as used by `novelpoly`.
+const CANTOR_BASIS: [u16; FIELD_BITS] = [
+	0x0001, 0xACCA, 0x3C0E, 0x163E, 0xC582, 0xED2E, 0x914C, 0x4012, 0x6C98, 0x10D8, 0x6A72, 0xB900,
+	0xFDB8, 0xFB34, 0xFF38, 0x991E,
+];
+
+/// The number of rounds encoded at once.
+const LANES: usize = 32;
+
+/// Logarithm tables and FFT skew factors of `GF(2^16)` in the Cantor basis.
+struct Field {
+	log: Vec<u16>,
+	exp: Vec<u16>,
+	// Skew factors of the additive FFT, as logarithms.
+	skew: Vec<u16>,
+	// Walsh transform of the logarithm table, used to evaluate error locator polynomials.
+	log_walsh: Vec<u16>,
+}
+
+impl Field {
+	fn get() -> &'static Field {
+		static FIELD: OnceLock<Field> = OnceLock::new();
+		FIELD.get_or_init(Field::new)
+	}
+
+	fn new() -> Self {
+		let mut log = vec![0u16; FIELD_SIZE];
+		let mut exp = vec![0u16; FIELD_SIZE];
+
+		// Logarithms of the elements in the polynomial basis.
+		let mut state = 1;
+		for i in 0..ONEMASK {
+			exp[state] = i;
+			state <<= 1;
+			if state >= FIELD_SIZE {
+				state = (state - FIELD_SIZE) ^ GENERATOR;
+			}
+		}
+		exp[0] = ONEMASK;
+
+		// Elements of the Cantor basis in the polynomial basis, then their logarithms.
+		for (i, basis) in CANTOR_BASIS.iter().enumerate() {
+			for j in 0..(1 << i) {
+				log[j + (1 << i)] = log[j] ^ basis;
+			}
+		}
+		for l in log.iter_mut() {
+			*l = exp[*l as usize];
+		}
+		for (i, l) in log.iter().enumerate() {
+			exp[*l as usize] = i as u16;
+		}
+		exp[ONEMASK as usize] = exp[0];
+
+		let skew = skew_factors(&log, &exp);
+
+		let mut log_walsh = log.clone();
+		log_walsh[0] = 0;
+		walsh(&mut log_walsh);
+
+		Field { log, exp, skew, log_walsh }
+	}
+
+	fn mul(&self, a: u16, log_b: u16) -> u16 {
+		mul(&self.log, &self.exp, a, log_b)
+	}
+}
+
+fn mul(log: &[u16], exp: &[u16], a: u16, log_b: u16) -> u16 {
+	if a == 0 {
+		return 0
+	}
+
+	let sum = log[a as usize] as usize + log_b as usize;
+	exp[(sum & ONEMASK as usize) + (sum >> FIELD_BITS)]
+}
+
+// The skew factors of the additive FFT, see section IV of the paper.
+fn skew_factors(log: &[u16], exp: &[u16]) -> Vec<u16> {
+	let mut skew = vec![0u16; ONEMASK as usize];
+	let mut base = [0u16; FIELD_BITS - 1];
+	for i in 1..FIELD_BITS {
+		base[i - 1] = 1 << i;
+	}
+
+	for m in 0..(FIELD_BITS - 1) {
+		let step = 1 << (m + 1);
+		skew[(1 << m) - 1] = 0;
+		for i in m..(FIELD_BITS - 1) {
+			let s = 1 << (i + 1);
+			let mut j = (1 << m) - 1;
+			while j < s {
+				skew[j + s] = skew[j] ^ base[i];
+				j += step;
+			}
+		}
+
+		base[m] = ONEMASK - log[mul(log, exp, base[m], log[(base[m] ^ 1) as usize]) as usize];
+		for i in (m + 1)..(FIELD_BITS - 1) {
+			let log_b =
+				(log[(base[i] ^ 1) as usize] as usize + base[m] as usize) % ONEMASK as usize;
+			base[i] = mul(log, exp, base[i], log_b as u16);
+		}
+	}
+
+	skew.iter().map(|s| log[*s as usize]).collect()
+}
+
+// The Walsh-Hadamard transform of logarithms, i.e. with the arithmetic modulo `ONEMASK`.
+fn walsh(data: &mut [u16]) {
+	let size = data.len();
+	let reduce = |x: u32| ((x & ONEMASK as u32) + (x >> FIELD_BITS)) as u16;
+
+	let mut depart_no = 1;
+	while depart_no < size {
+		for j in (0..size).step_by(depart_no << 1) {
+			for i in j..j + depart_no {
+				let (a, b) = (data[i] as u32, data[i + depart_no] as u32);
+				data[i] = reduce(a + b);
+				data[i + depart_no] = reduce(a + ONEMASK as u32 - b);
+			}
+		}
+		depart_no <<= 1;
+	}
+}
+
+// The logarithms of the error locator polynomial of the `erasures`, evaluated at every point of
+// the field, as done by `novelpoly::f2e16::eval_error_polynomial`.
+fn error_locator(field: &Field, erasures: &[bool]) -> Vec<u16> {
+	let mut locator = vec![0u16; FIELD_SIZE];
+	for (l, erased) in locator.iter_mut().zip(erasures) {
+		*l = *erased as u16;
+	}
+
+	walsh(&mut locator);
+	for (l, log_walsh) in locator.iter_mut().zip(&field.log_walsh) {
+		*l = ((*l as u32 * *log_walsh as u32) % ONEMASK as u32) as u16;
+	}
+	walsh(&mut locator);
+
+	for (l, erased) in locator.iter_mut().zip(erasures) {
+		if *erased {
+			*l = ONEMASK - *l;
+		}
+	}
+
+	locator
+}
+
+/// `LANES` field elements, split into planes of low and high bytes.
+#[derive(Clone, Copy, Default)]
+#[repr(C, align(32))]
+struct Lanes {
+	lo: [u8; LANES],
+	hi: [u8; LANES],
+}
+
+/// Products of a constant with all nibbles, by position of the nibble in the element.
+struct MulTable {
+	lo: [[u8; 16]; 4],
+	hi: [[u8; 16]; 4],
+}
+
+impl MulTable {
+	fn new(field: &Field, log_c: u16) -> Self {
+		let mut table = MulTable { lo: [[0; 16]; 4], hi: [[0; 16]; 4] };
+		for position in 0..4 {
+			for nibble in 0..16 {
+				let product = field.mul((nibble << (4 * position)) as u16, log_c);
+				table.lo[position][nibble] = product as u8;
+				table.hi[position][nibble] = (product >> 8) as u8;
+			}
+		}
+
+		table
+	}
+}
+
+/// Multiplication tables of all skew factors used to encode `k` out of `n` symbols.
+struct Plan {
+	n: usize,
+	k: usize,
+	// `None` for skew factors which are zero.
+	skews: Vec<Option<MulTable>>,
+}
+
+impl Plan {
+	fn get(n: usize, k: usize) -> Arc<Plan> {
+		static PLANS: OnceLock<Mutex<HashMap<(usize, usize), Arc<Plan>>>> = OnceLock::new();
+
+		// There are only a few distinct validator counts, so the plans are never evicted.
+		let mut plans =
+			PLANS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+		plans.entry((n, k)).or_insert_with(|| Arc::new(Plan::new(n, k))).clone()
+	}
+
+	fn new(n: usize, k: usize) -> Self {
+		let field = Field::get();
+		let skews = field.skew[..n - 1]
+			.iter()
+			.map(|&skew| (skew != ONEMASK).then(|| MulTable::new(field, skew)))
+			.collect();
+
+		Plan { n, k, skews }
+	}
+}
+
+/// Vectorized arithmetic on `Lanes`.
+trait Kernel: Copy {
+	/// `dst += src`
+	fn add(self, dst: &mut Lanes, src: &Lanes);
+
+	/// `dst += src * c`, with the products of `c` in `table`.
+	fn mul_add(self, dst: &mut Lanes, src: &Lanes, table: &MulTable);
+}
+
+/// The kernel without SIMD instructions, to check the others against.
+#[derive(Clone, Copy)]
+struct Portable;
+
+impl Kernel for Portable {
+	fn add(self, dst: &mut Lanes, src: &Lanes) {
+		for (d, s) in dst.lo.iter_mut().zip(&src.lo).chain(dst.hi.iter_mut().zip(&src.hi)) {
+			*d ^= s;
+		}
+	}
+
+	fn mul_add(self, dst: &mut Lanes, src: &Lanes, table: &MulTable) {
+		for lane in 0..LANES {
+			let (lo, hi) = (src.lo[lane] as usize, src.hi[lane] as usize);
+			for (position, nibble) in
+				[lo & 0x0f, lo >> 4, hi & 0x0f, hi >> 4].into_iter().enumerate()
+			{
+				dst.lo[lane] ^= table.lo[position][nibble];
+				dst.hi[lane] ^= table.hi[position][nibble];
+			}
+		}
+	}
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+	use super::{Kernel, Lanes, MulTable};
+	use std::arch::x86_64::*;
+
+	/// The kernel using AVX2 instructions.
+	#[derive(Clone, Copy)]
+	pub(super) struct Avx2(());
+
+	impl Avx2 {
+		pub(super) fn detect() -> Option<Self> {
+			std::arch::is_x86_feature_detected!("avx2").then_some(Avx2(()))
+		}
+	}
+
+	impl Kernel for Avx2 {
+		fn add(self, dst: &mut Lanes, src: &Lanes) {
+			// SAFETY: `Avx2` is only constructed if the CPU supports AVX2.
+			unsafe { add(dst, src) }
+		}
+
+		fn mul_add(self, dst: &mut Lanes, src: &Lanes, table: &MulTable) {
+			// SAFETY: `Avx2` is only constructed if the CPU supports AVX2.
+			unsafe { mul_add(dst, src, table) }
+		}
+	}
+
+	#[target_feature(enable = "avx2")]
+	unsafe fn add(dst: &mut Lanes, src: &Lanes) {
+		for (d, s) in [(&mut dst.lo, &src.lo), (&mut dst.hi, &src.hi)] {
+			let sum = _mm256_xor_si256(
+				_mm256_loadu_si256(d.as_ptr().cast()),
+				_mm256_loadu_si256(s.as_ptr().cast()),
+			);
+			_mm256_storeu_si256(d.as_mut_ptr().cast(), sum);
+		}
+	}
+
+	#[target_feature(enable = "avx2")]
+	unsafe fn mul_add(dst: &mut Lanes, src: &Lanes, table: &MulTable) {
+		let mask = _mm256_set1_epi8(0x0f);
+		let lo = _mm256_loadu_si256(src.lo.as_ptr().cast());
+		let hi = _mm256_loadu_si256(src.hi.as_ptr().cast());
+		let nibbles = [
+			_mm256_and_si256(lo, mask),
+			_mm256_and_si256(_mm256_srli_epi64::<4>(lo), mask),
+			_mm256_and_si256(hi, mask),
+			_mm256_and_si256(_mm256_srli_epi64::<4>(hi), mask),
+		];
+
+		let mut product_lo = _mm256_loadu_si256(dst.lo.as_ptr().cast());
+		let mut product_hi = _mm256_loadu_si256(dst.hi.as_ptr().cast());
+		for (position, nibble) in nibbles.into_iter().enumerate() {
+			let table_lo =
+				_mm256_broadcastsi128_si256(_mm_loadu_si128(table.lo[position].as_ptr().cast()));
+			let table_hi =
+				_mm256_broadcastsi128_si256(_mm_loadu_si128(table.hi[position].as_ptr().cast()));
+			product_lo = _mm256_xor_si256(product_lo, _mm256_shuffle_epi8(table_lo, nibble));
+			product_hi = _mm256_xor_si256(product_hi, _mm256_shuffle_epi8(table_hi, nibble));
+		}
+		_mm256_storeu_si256(dst.lo.as_mut_ptr().cast(), product_lo);
+		_mm256_storeu_si256(dst.hi.as_mut_ptr().cast(), product_hi);
+	}
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+	use super::{Kernel, Lanes, MulTable, LANES};
+	use std::arch::aarch64::*;
+
+	/// The kernel using NEON instructions.
+	#[derive(Clone, Copy)]
+	pub(super) struct Neon(());
+
+	impl Neon {
+		pub(super) fn detect() -> Option<Self> {
+			std::arch::is_aarch64_feature_detected!("neon").then_some(Neon(()))
+		}
+	}
+
+	impl Kernel for Neon {
+		fn add(self, dst: &mut Lanes, src: &Lanes) {
+			// SAFETY: `Neon` is only constructed if the CPU supports NEON.
+			unsafe { add(dst, src) }
+		}
+
+		fn mul_add(self, dst: &mut Lanes, src: &Lanes, table: &MulTable) {
+			// SAFETY: `Neon` is only constructed if the CPU supports NEON.
+			unsafe { mul_add(dst, src, table) }
+		}
+	}
+
+	#[target_feature(enable = "neon")]
+	unsafe fn add(dst: &mut Lanes, src: &Lanes) {
+		for (d, s) in [(&mut dst.lo, &src.lo), (&mut dst.hi, &src.hi)] {
+			for offset in (0..LANES).step_by(16) {
+				let sum =
+					veorq_u8(vld1q_u8(d.as_ptr().add(offset)), vld1q_u8(s.as_ptr().add(offset)));
+				vst1q_u8(d.as_mut_ptr().add(offset), sum);
+			}
+		}
+	}
+
+	#[target_feature(enable = "neon")]
+	unsafe fn mul_add(dst: &mut Lanes, src: &Lanes, table: &MulTable) {
+		let mask = vdupq_n_u8(0x0f);
+		for offset in (0..LANES).step_by(16) {
+			let lo = vld1q_u8(src.lo.as_ptr().add(offset));
+			let hi = vld1q_u8(src.hi.as_ptr().add(offset));
+			let nibbles =
+				[vandq_u8(lo, mask), vshrq_n_u8::<4>(lo), vandq_u8(hi, mask), vshrq_n_u8::<4>(hi)];
+
+			let mut product_lo = vld1q_u8(dst.lo.as_ptr().add(offset));
+			let mut product_hi = vld1q_u8(dst.hi.as_ptr().add(offset));
+			for (position, nibble) in nibbles.into_iter().enumerate() {
+				let table_lo = vld1q_u8(table.lo[position].as_ptr());
+				let table_hi = vld1q_u8(table.hi[position].as_ptr());
+				product_lo = veorq_u8(product_lo, vqtbl1q_u8(table_lo, nibble));
+				product_hi = veorq_u8(product_hi, vqtbl1q_u8(table_hi, nibble));
+			}
+			vst1q_u8(dst.lo.as_mut_ptr().add(offset), product_lo);
+			vst1q_u8(dst.hi.as_mut_ptr().add(offset), product_hi);
+		}
+	}
+}
+
+// The additive FFT of `data` at offset `index`.
+fn afft<K: Kernel>(kernel: K, plan: &Plan, data: &mut [Lanes], index: usize) {
+	let size = data.len();
+	let mut depart_no = size >> 1;
+	while depart_no > 0 {
+		let mut j = depart_no;
+		while j < size {
+			let (low, high) = data[j - depart_no..j + depart_no].split_at_mut(depart_no);
+			if let Some(table) = &plan.skews[j + index - 1] {
+				for (l, h) in low.iter_mut().zip(high.iter()) {
+					kernel.mul_add(l, h, table);
+				}
+			}
+			for (l, h) in low.iter().zip(high.iter_mut()) {
+				kernel.add(h, l);
+			}
+			j += depart_no << 1;
+		}
+		depart_no >>= 1;
+	}
+}
+
+// The inverse of `afft`.
+fn inverse_afft<K: Kernel>(kernel: K, plan: &Plan, data: &mut [Lanes], index: usize) {
+	let size = data.len();
+	let mut depart_no = 1;
+	while depart_no < size {
+		let mut j = depart_no;
+		while j < size {
+			let (low, high) = data[j - depart_no..j + depart_no].split_at_mut(depart_no);
+			for (l, h) in low.iter().zip(high.iter_mut()) {
+				kernel.add(h, l);
+			}
+			if let Some(table) = &plan.skews[j + index - 1] {
+				for (l, h) in low.iter_mut().zip(high.iter()) {
+					kernel.mul_add(l, h, table);
+				}
+			}
+			j += depart_no << 1;
+		}
+		depart_no <<= 1;
+	}
+}
+
+// The formal derivative of a polynomial in the novel basis.
+fn formal_derivative<K: Kernel>(kernel: K, data: &mut [Lanes]) {
+	for i in 1..data.len() {
+		let length = ((i ^ (i - 1)) + 1) >> 1;
+		let (low, high) = data[i - length..i + length].split_at_mut(length);
+		for (l, h) in low.iter_mut().zip(high.iter()) {
+			kernel.add(l, h);
+		}
+	}
+}
+
+fn encode<K: Kernel>(kernel: K, plan: &Plan, n_validators: usize, payload: &[u8]) -> Vec<Vec<u8>> {
+	let (n, k) = (plan.n, plan.k);
+	let round_len = k * 2;
+	let rounds = payload.len().div_ceil(round_len);
+
+	let mut chunks = vec![vec![0u8; rounds * 2]; n_validators];
+	let mut data = vec![Lanes::default(); k];
+	let mut codeword = vec![Lanes::default(); n];
+	for first_round in (0..rounds).step_by(LANES) {
+		let batch = LANES.min(rounds - first_round);
+
+		// Symbols are big endian, the payload is padded with zeros.
+		for (i, symbol) in data.iter_mut().enumerate() {
+			*symbol = Lanes::default();
+			for lane in 0..batch {
+				let offset = (first_round + lane) * round_len + 2 * i;
+				symbol.hi[lane] = payload.get(offset).copied().unwrap_or(0);
+				symbol.lo[lane] = payload.get(offset + 1).copied().unwrap_or(0);
+			}
+		}
+
+		let (first, rest) = codeword.split_at_mut(k);
+		first.copy_from_slice(&data);
+		inverse_afft(kernel, plan, first, 0);
+		for (i, segment) in rest.chunks_exact_mut(k).enumerate() {
+			segment.copy_from_slice(first);
+			afft(kernel, plan, segment, (i + 1) * k);
+		}
+		// The code is systematic.
+		first.copy_from_slice(&data);
+
+		for (chunk, symbol) in chunks.iter_mut().zip(&codeword) {
+			for lane in 0..batch {
+				let round = first_round + lane;
+				chunk[2 * round] = symbol.hi[lane];
+				chunk[2 * round + 1] = symbol.lo[lane];
+			}
+		}
+	}
+
+	chunks
+}
+
+// Reconstruct the payload from `n` chunks, `None` for the erased ones. `locator` holds the
+// products of the error locator polynomial at every position.
+fn reconstruct<K: Kernel>(
+	kernel: K,
+	plan: &Plan,
+	chunks: &[Option<Vec<u8>>],
+	locator: &[MulTable],
+	chunk_len: usize,
+) -> Vec<u8> {
+	let (n, k) = (plan.n, plan.k);
+	let round_len = k * 2;
+	let rounds = chunk_len / 2;
+
+	let mut payload = vec![0u8; rounds * round_len];
+	let mut codeword = vec![Lanes::default(); n];
+	for first_round in (0..rounds).step_by(LANES) {
+		let batch = LANES.min(rounds - first_round);
+
+		// The received symbols times the error locator, zero for the erased ones.
+		for ((symbol, chunk), table) in codeword.iter_mut().zip(chunks).zip(locator) {
+			*symbol = Lanes::default();
+			if let Some(chunk) = chunk {
+				let mut received = Lanes::default();
+				for lane in 0..batch {
+					let round = first_round + lane;
+					received.hi[lane] = chunk[2 * round];
+					received.lo[lane] = chunk[2 * round + 1];
+				}
+				kernel.mul_add(symbol, &received, table);
+			}
+		}
+
+		inverse_afft(kernel, plan, &mut codeword, 0);
+		formal_derivative(kernel, &mut codeword);
+		afft(kernel, plan, &mut codeword, 0);
+
+		// Only the systematic chunks are needed, the erased ones are recovered.
+		for (i, chunk) in chunks[..k].iter().enumerate() {
+			let rounds = first_round..first_round + batch;
+			match chunk {
+				Some(chunk) =>
+					for round in rounds {
+						let offset = round * round_len + 2 * i;
+						let symbol = &chunk[2 * round..2 * round + 2];
+						payload[offset..offset + 2].copy_from_slice(symbol);
+					},
+				None => {
+					let mut recovered = Lanes::default();
+					kernel.mul_add(&mut recovered, &codeword[i], &locator[i]);
+					for (lane, round) in rounds.enumerate() {
+						let offset = round * round_len + 2 * i;
+						payload[offset] = recovered.hi[lane];
+						payload[offset + 1] = recovered.lo[lane];
+					}
+				},
+			}
+		}
+	}
+
+	payload
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Isa {
+	Portable,
+	#[cfg(target_arch = "x86_64")]
+	Avx2,
+	#[cfg(target_arch = "aarch64")]
+	Neon,
+}
+
+/// Encoder and decoder using SIMD instructions, producing the same chunks and payloads as
+/// [`ScalarBackend`](super::ScalarBackend).
+#[derive(Debug, Clone, Copy)]
+pub struct SimdBackend {
+	isa: Isa,
+}
+
+impl SimdBackend {
+	/// Use the SIMD instructions supported by the CPU, if any.
+	pub fn detect() -> Option<Self> {
+		#[cfg(target_arch = "x86_64")]
+		if avx2::Avx2::detect().is_some() {
+			return Some(SimdBackend { isa: Isa::Avx2 })
+		}
+
+		#[cfg(target_arch = "aarch64")]
+		if neon::Neon::detect().is_some() {
+			return Some(SimdBackend { isa: Isa::Neon })
+		}
+
+		None
+	}
+
+	/// The same algorithm without SIMD instructions, for testing.
+	pub fn portable() -> Self {
+		SimdBackend { isa: Isa::Portable }
+	}
+}
+
+impl Backend for SimdBackend {
+	fn name(&self) -> &'static str {
+		match self.isa {
+			Isa::Portable => "portable",
+			#[cfg(target_arch = "x86_64")]
+			Isa::Avx2 => "avx2",
+			#[cfg(target_arch = "aarch64")]
+			Isa::Neon => "neon",
+		}
+	}
+
+	fn encode(&self, n_validators: usize, payload: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+		let params = code_params(n_validators)?;
+		if payload.is_empty() {
+			return Err(Error::BadPayload)
+		}
+
+		let plan = Plan::get(n_validators.next_power_of_two(), params.k());
+		Ok(match self.isa {
+			Isa::Portable => encode(Portable, &plan, n_validators, payload),
+			#[cfg(target_arch = "x86_64")]
+			Isa::Avx2 => encode(
+				avx2::Avx2::detect().expect("only selected if supported; qed"),
+				&plan,
+				n_validators,
+				payload,
+			),
+			#[cfg(target_arch = "aarch64")]
+			Isa::Neon => encode(
+				neon::Neon::detect().expect("only selected if supported; qed"),
+				&plan,
+				n_validators,
+				payload,
+			),
+		})
+	}
+
+	fn reconstruct(
+		&self,
+		n_validators: usize,
+		mut chunks: Vec<Option<Vec<u8>>>,
+	) -> Result<Vec<u8>, Error> {
+		let k = code_params(n_validators)?.k();
+		let n = n_validators.next_power_of_two();
+
+		// Chunks beyond the validators are erased.
+		chunks.resize(n, None);
+		let erasures: Vec<bool> = chunks.iter().map(Option::is_none).collect();
+		if erasures.iter().filter(|erased| !**erased).count() < k {
+			return Err(Error::NotEnoughChunks)
+		}
+
+		let mut present = chunks.iter().flatten();
+		let chunk_len = present.next().map_or(0, Vec::len);
+		if chunk_len == 0 || present.any(|chunk| chunk.len() != chunk_len) {
+			return Err(Error::NonUniformChunks)
+		}
+
+		let field = Field::get();
+		let locator: Vec<_> = error_locator(field, &erasures)[..n]
+			.iter()
+			.map(|&log| MulTable::new(field, log))
+			.collect();
+
+		let plan = Plan::get(n, k);
+		Ok(match self.isa {
+			Isa::Portable => reconstruct(Portable, &plan, &chunks, &locator, chunk_len),
+			#[cfg(target_arch = "x86_64")]
+			Isa::Avx2 => reconstruct(
+				avx2::Avx2::detect().expect("only selected if supported; qed"),
+				&plan,
+				&chunks,
+				&locator,
+				chunk_len,
+			),
+			#[cfg(target_arch = "aarch64")]
+			Isa::Neon => reconstruct(
+				neon::Neon::detect().expect("only selected if supported; qed"),
+				&plan,
+				&chunks,
+				&locator,
+				chunk_len,
+			),
+		})
+	}
+
+	fn reconstruct_from_systematic(
+		&self,
+		n_validators: usize,
+		chunks: Vec<Vec<u8>>,
+	) -> Result<Vec<u8>, Error> {
+		let k = code_params(n_validators)?.k();
+		let chunks = chunks.get(..k).ok_or(Error::NotEnoughChunks)?;
+		let chunk_len = chunks[0].len();
+		if chunk_len == 0 || chunks.iter().any(|chunk| chunk.len() != chunk_len) {
+			return Err(Error::NonUniformChunks)
+		}
+
+		let mut payload = Vec::with_capacity(k * chunk_len);
+		for round in (0..chunk_len).step_by(2) {
+			for chunk in chunks {
+				payload.extend_from_slice(&chunk[round..round + 2]);
+			}
+		}
+
+		Ok(payload)
+	}
+}
@@ -23,6 +23,10 @@
 //! Each of n validators stores their piece of data. We assume `n = 3f + k`, `0 < k ≤ 3`.
 //! f is the maximum number of faulty validators in the system.
 //! The data is coded so any f+1 chunks can be used to reconstruct the full data.
+//!
+//! The code is implemented by interchangeable [`Backend`]s producing identical chunks. The
+//! fastest one supported by the CPU is selected at runtime, see [`backend()`] and
+//! [`force_scalar_backend`].
 
 use codec::{Decode, Encode};
 use polkadot_node_primitives::{AvailableData, Proof};
@@ -34,7 +38,13 @@ use sp_trie::{
 };
 use thiserror::Error;
 
-use novelpoly::{CodeParams, WrappedShard};
+use novelpoly::CodeParams;
+
+mod backend;
+
+pub use backend::{
+	backend, force_scalar_backend, Backend, ScalarBackend, SimdBackend, BACKEND_ENV_VAR,
+};
 
 // we are limited to the field order of GF(2^16), which is 65536
 const MAX_VALIDATORS: usize = novelpoly::f2e16::FIELD_SIZE;
@@ -157,8 +167,7 @@ pub fn reconstruct_from_systematic<T: Decode>(
 	n_validators: usize,
 	chunks: Vec<Vec<u8>>,
 ) -> Result<T, Error> {
-	let code_params = code_params(n_validators)?;
-	let k = code_params.k();
+	let k = code_params(n_validators)?.k();
 
 	for chunk_data in chunks.iter().take(k) {
 		if chunk_data.len() % 2 != 0 {
@@ -166,9 +175,8 @@ pub fn reconstruct_from_systematic<T: Decode>(
 		}
 	}
 
-	let bytes = code_params.make_encoder().reconstruct_from_systematic(
-		chunks.into_iter().take(k).map(|data| WrappedShard::new(data)).collect(),
-	)?;
+	let bytes = backend()
+		.reconstruct_from_systematic(n_validators, chunks.into_iter().take(k).collect())?;
 
 	Decode::decode(&mut &bytes[..]).map_err(|err| Error::Decode(err))
 }
@@ -184,19 +192,18 @@ pub fn obtain_chunks_v1(n_validators: usize, data: &AvailableData) -> Result<Vec
 ///
 /// Works only up to 65536 validators, and `n_validators` must be non-zero.
 pub fn obtain_chunks<T: Encode>(n_validators: usize, data: &T) -> Result<Vec<Vec<u8>>, Error> {
-	let params = code_params(n_validators)?;
+	code_params(n_validators)?;
 	let encoded = data.encode();
 
 	if encoded.is_empty() {
 		return Err(Error::BadPayload)
 	}
 
-	let shards = params
-		.make_encoder()
-		.encode::<WrappedShard>(&encoded[..])
+	let chunks = backend()
+		.encode(n_validators, &encoded[..])
 		.expect("Payload non-empty, shard sizes are uniform, and validator numbers checked; qed");
 
-	Ok(shards.into_iter().map(|w: WrappedShard| w.into_inner()).collect())
+	Ok(chunks)
 }
 
 /// Reconstruct the v1 available data from a set of chunks.
@@ -224,17 +231,17 @@ pub fn reconstruct<'a, I: 'a, T: Decode>(n_validators: usize, chunks: I) -> Resu
 where
 	I: IntoIterator<Item = (&'a [u8], usize)>,
 {
-	let params = code_params(n_validators)?;
-	let mut received_shards: Vec<Option<WrappedShard>> = vec![None; n_validators];
+	code_params(n_validators)?;
+	let mut received_chunks: Vec<Option<Vec<u8>>> = vec![None; n_validators];
 	for (chunk_data, chunk_idx) in chunks.into_iter().take(n_validators) {
 		if chunk_data.len() % 2 != 0 {
 			return Err(Error::UnevenLength)
 		}
 
-		received_shards[chunk_idx] = Some(WrappedShard::new(chunk_data.to_vec()));
+		received_chunks[chunk_idx] = Some(chunk_data.to_vec());
 	}
 
-	let payload_bytes = params.make_encoder().reconstruct(received_shards)?;
+	let payload_bytes = backend().reconstruct(n_validators, received_chunks)?;
 
 	Decode::decode(&mut &payload_bytes[..]).map_err(|_| Error::BadPayload)
 }
@@ -411,6 +418,149 @@ mod tests {
 		QuickCheck::new().quickcheck(property as fn(ArbitraryAvailableData, u16))
 	}
 
+	fn simd_backends() -> Vec<SimdBackend> {
+		std::iter::once(SimdBackend::portable()).chain(SimdBackend::detect()).collect()
+	}
+
+	#[test]
+	fn simd_backends_encode_like_scalar_backend() {
+		fn property(payload: Vec<u8>, n_validators: u16) {
+			let n_validators = (n_validators % 1024).max(2) as usize;
+			let payload = if payload.is_empty() { vec![0] } else { payload };
+
+			let expected = ScalarBackend.encode(n_validators, &payload).unwrap();
+			for simd in simd_backends() {
+				assert_eq!(
+					simd.encode(n_validators, &payload).unwrap(),
+					expected,
+					"{}",
+					simd.name()
+				);
+			}
+		}
+
+		QuickCheck::new().quickcheck(property as fn(Vec<u8>, u16))
+	}
+
+	#[test]
+	fn simd_backends_reconstruct_systematic_like_scalar_backend() {
+		fn property(payload: Vec<u8>, n_validators: u16) {
+			let n_validators = (n_validators % 1024).max(2) as usize;
+			let payload = if payload.is_empty() { vec![0] } else { payload };
+			let k = systematic_recovery_threshold(n_validators).unwrap();
+			let chunks: Vec<_> = ScalarBackend
+				.encode(n_validators, &payload)
+				.unwrap()
+				.into_iter()
+				.take(k)
+				.collect();
+
+			let expected = ScalarBackend.reconstruct_from_systematic(n_validators, chunks.clone());
+			assert_eq!(&expected.as_ref().unwrap()[..payload.len()], &payload[..]);
+			for simd in simd_backends() {
+				assert_eq!(
+					simd.reconstruct_from_systematic(n_validators, chunks.clone()),
+					expected,
+					"{}",
+					simd.name(),
+				);
+			}
+		}
+
+		QuickCheck::new().quickcheck(property as fn(Vec<u8>, u16))
+	}
+
+	#[test]
+	fn simd_backends_round_trip() {
+		let payload: Vec<u8> = (0..100_000u32).map(|i| (i * 7 + i / 251) as u8).collect();
+		for n_validators in [2, 3, 10, 100, 300, 1000, 2000] {
+			for simd in simd_backends() {
+				let chunks = simd.encode(n_validators, &payload).unwrap();
+				let threshold = recovery_threshold(n_validators).unwrap();
+				let present = chunks
+					.into_iter()
+					.enumerate()
+					.map(|(i, chunk)| (i >= n_validators - threshold).then_some(chunk))
+					.collect();
+
+				let reconstructed = simd.reconstruct(n_validators, present).unwrap();
+				assert_eq!(&reconstructed[..payload.len()], &payload[..], "{}", simd.name());
+			}
+		}
+	}
+
+	#[test]
+	fn simd_backends_reconstruct_like_scalar_backend() {
+		let params = |n| (usize::next_power_of_two(n), systematic_recovery_threshold(n).unwrap());
+		// All the validator counts up to 1024, and above the ones where the code parameters change.
+		let n_validators = (2..=MAX_VALIDATORS).filter(|&n| {
+			n <= 1024 ||
+				n == MAX_VALIDATORS ||
+				params(n - 1) != params(n) ||
+				params(n + 1) != params(n)
+		});
+
+		for n_validators in n_validators {
+			let k = systematic_recovery_threshold(n_validators).unwrap();
+			let threshold = recovery_threshold(n_validators).unwrap();
+			// More rounds than the SIMD backends process at once.
+			let payload: Vec<u8> =
+				(0..33 * 2 * k).map(|i| (i * 7 + i / 251 + n_validators) as u8).collect();
+			let chunks = ScalarBackend.encode(n_validators, &payload).unwrap();
+
+			// Keep a pseudo-random selection of exactly `threshold` chunks.
+			let mut order: Vec<usize> = (0..n_validators).collect();
+			order.sort_by_key(|&i| ((i ^ n_validators) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+			let mut present = vec![None; n_validators];
+			for &i in &order[..threshold] {
+				present[i] = Some(chunks[i].clone());
+			}
+
+			let expected = ScalarBackend.reconstruct(n_validators, present.clone());
+			assert_eq!(&expected.as_ref().unwrap()[..payload.len()], &payload[..]);
+			for simd in simd_backends() {
+				assert_eq!(
+					simd.reconstruct(n_validators, present.clone()),
+					expected,
+					"{} with {} validators",
+					simd.name(),
+					n_validators,
+				);
+			}
+
+			// One chunk less than the systematic threshold.
+			for &i in &order[k - 1..threshold] {
+				present[i] = None;
+			}
+			for simd in simd_backends() {
+				assert_eq!(
+					simd.reconstruct(n_validators, present.clone()),
+					Err(Error::NotEnoughChunks),
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn simd_backend_rejects_bad_systematic_chunks() {
+		let simd = SimdBackend::portable();
+		assert_eq!(
+			simd.reconstruct_from_systematic(10, vec![vec![0; 4]; 3]),
+			Err(Error::NotEnoughChunks)
+		);
+		assert_eq!(
+			simd.reconstruct_from_systematic(
+				10,
+				vec![vec![0; 4], vec![0; 2], vec![0; 4], vec![0; 4]]
+			),
+			Err(Error::NonUniformChunks)
+		);
+		assert_eq!(
+			simd.reconstruct_from_systematic(10, vec![vec![]; 4]),
+			Err(Error::NonUniformChunks)
+		);
+	}
+
 	#[test]
 	fn reconstruct_does_not_panic_on_low_validator_count() {
 		let reconstructed = reconstruct_v1(1, [].iter().cloned());
@@ -22,6 +22,7 @@ use crate::{
 		v3::*,
 	},
 	backend::{Backend, OverlayedBackend},
+	inspect::{ApprovalDbInspector, Inconsistency},
 	ops::{add_block_entry, canonicalize, force_approve, NewCandidateInfo},
 };
 use polkadot_primitives::{
@@ -622,3 +623,101 @@ fn load_all_blocks_works() {
 		vec![block_hash_a, block_hash_b, block_hash_c],
 	)
 }
+
+fn make_chain_for_inspection(db: &mut DbBackend) -> (Hash, Hash, CandidateHash, CandidateHash) {
+	let block_hash_a = Hash::repeat_byte(1);
+	let block_hash_b = Hash::repeat_byte(2);
+
+	let candidate_receipt_a = make_candidate(ParaId::from(1_u32), Hash::zero());
+	let candidate_receipt_b = make_candidate(ParaId::from(2_u32), block_hash_a);
+	let candidate_hash_a = candidate_receipt_a.hash();
+	let candidate_hash_b = candidate_receipt_b.hash();
+
+	let block_entry_a =
+		make_block_entry(block_hash_a, Hash::zero(), 1, vec![(CoreIndex(0), candidate_hash_a)]);
+	let block_entry_b = make_block_entry(
+		block_hash_b,
+		block_hash_a,
+		2,
+		vec![(CoreIndex(0), candidate_hash_a), (CoreIndex(1), candidate_hash_b)],
+	);
+
+	let mut new_candidate_info = HashMap::new();
+	new_candidate_info
+		.insert(candidate_hash_a, NewCandidateInfo::new(candidate_receipt_a, GroupIndex(0), None));
+	new_candidate_info
+		.insert(candidate_hash_b, NewCandidateInfo::new(candidate_receipt_b, GroupIndex(1), None));
+
+	let mut overlay_db = OverlayedBackend::new(&*db);
+	for block_entry in [block_entry_a, block_entry_b] {
+		add_block_entry(&mut overlay_db, block_entry.into(), 10, |h| {
+			new_candidate_info.get(h).cloned()
+		})
+		.unwrap();
+	}
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	(block_hash_a, block_hash_b, candidate_hash_a, candidate_hash_b)
+}
+
+#[test]
+fn inspector_repairs_missing_candidate_entry() {
+	let (mut db, store) = make_db();
+	let (block_hash_a, block_hash_b, candidate_hash_a, candidate_hash_b) =
+		make_chain_for_inspection(&mut db);
+
+	let mut inspector = ApprovalDbInspector::new(store.clone(), DATA_COL);
+	assert_eq!(inspector.check().unwrap(), Vec::new());
+	assert_eq!(inspector.blocks().unwrap().len(), 2);
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	overlay_db.delete_candidate_entry(&candidate_hash_b);
+	let write_ops = overlay_db.into_write_ops();
+	db.write(write_ops).unwrap();
+
+	let inconsistencies = inspector.check().unwrap();
+	assert_eq!(
+		inconsistencies,
+		vec![Inconsistency::MissingCandidateEntry {
+			block_hash: block_hash_b,
+			candidate_hash: candidate_hash_b,
+		}],
+	);
+
+	inspector.repair(&inconsistencies).unwrap();
+	assert_eq!(inspector.check().unwrap(), Vec::new());
+
+	let blocks = inspector.blocks().unwrap();
+	assert_eq!(blocks.len(), 1);
+	assert_eq!(blocks[0].block_hash, block_hash_a);
+	assert!(blocks[0].children.is_empty());
+
+	let candidate_entry_a = load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_a)
+		.unwrap()
+		.unwrap();
+	assert_eq!(candidate_entry_a.block_assignments.keys().collect::<Vec<_>>(), vec![&block_hash_a]);
+}
+
+#[test]
+fn inspector_prunes_candidate_with_including_blocks() {
+	let (mut db, store) = make_db();
+	let (block_hash_a, block_hash_b, candidate_hash_a, candidate_hash_b) =
+		make_chain_for_inspection(&mut db);
+
+	let mut inspector = ApprovalDbInspector::new(store.clone(), DATA_COL);
+	inspector.prune_candidate(candidate_hash_a).unwrap();
+
+	assert!(inspector.blocks().unwrap().is_empty());
+	assert_eq!(inspector.check().unwrap(), Vec::new());
+	assert_eq!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_a).unwrap(), None);
+	assert_eq!(load_block_entry(store.as_ref(), &TEST_CONFIG, &block_hash_b).unwrap(), None);
+	assert_eq!(
+		load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_a).unwrap(),
+		None
+	);
+	assert_eq!(
+		load_candidate_entry(store.as_ref(), &TEST_CONFIG, &candidate_hash_b).unwrap(),
+		None
+	);
+}
@@ -0,0 +1,430 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline inspection and repair of the approval-voting database.
+//!
+//! This is meant to be used on the database of a node which is not running, i.e. after a crash
+//! left it in an inconsistent state. Candidate entries are only reachable through the block
+//! entries including them, the column is not ordered and can't be iterated, so candidate entries
+//! no block refers to can't be detected.
+
+use polkadot_node_subsystem::SubsystemResult;
+use polkadot_node_subsystem_util::database::Database;
+use polkadot_primitives::{BlockNumber, CandidateHash, CoreIndex, Hash, SessionIndex};
+
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{
+	approval_db::common::{Config as DatabaseConfig, DbBackend},
+	backend::{Backend, OverlayedBackend},
+};
+
+/// The approval status of a candidate in the context of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateSummary {
+	/// The hash of the candidate.
+	pub candidate_hash: CandidateHash,
+	/// The core the candidate occupied.
+	pub core_index: CoreIndex,
+	/// Whether the candidate is approved in the context of the block.
+	pub approved: bool,
+	/// The number of validators which approved the candidate, if its entry exists.
+	pub approvals: Option<usize>,
+	/// The number of assignments in the context of the block, if its approval entry exists.
+	pub assignments: Option<usize>,
+}
+
+/// The approval status of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockSummary {
+	/// The hash of the block.
+	pub block_hash: Hash,
+	/// The number of the block.
+	pub block_number: BlockNumber,
+	/// The hash of the parent of the block.
+	pub parent_hash: Hash,
+	/// The session of the block.
+	pub session: SessionIndex,
+	/// Whether all candidates of the block are approved.
+	pub approved: bool,
+	/// The candidates included by the block.
+	pub candidates: Vec<CandidateSummary>,
+	/// The children of the block.
+	pub children: Vec<Hash>,
+}
+
+/// A violated invariant of the approval-voting database.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Inconsistency {
+	/// A block listed at some height has no block entry.
+	#[error("Block {block_hash:?} at height {height} has no block entry")]
+	MissingBlockEntry {
+		/// The height the block is listed at.
+		height: BlockNumber,
+		/// The hash of the block.
+		block_hash: Hash,
+	},
+	/// A block entry is listed at a different height than its number.
+	#[error("Block {block_hash:?} with number {block_number} is listed at height {height}")]
+	WrongHeight {
+		/// The height the block is listed at.
+		height: BlockNumber,
+		/// The number of the block according to its entry.
+		block_number: BlockNumber,
+		/// The hash of the block.
+		block_hash: Hash,
+	},
+	/// The approved bitfield of a block does not match its candidates.
+	#[error("Block {block_hash:?} has an approved bitfield of the wrong length")]
+	BitfieldLength {
+		/// The hash of the block.
+		block_hash: Hash,
+	},
+	/// A block includes a candidate without a candidate entry.
+	#[error("Candidate {candidate_hash:?} of block {block_hash:?} has no candidate entry")]
+	MissingCandidateEntry {
+		/// The hash of the block.
+		block_hash: Hash,
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+	},
+	/// A candidate entry has no approval entry for a block including it.
+	#[error("Candidate {candidate_hash:?} has no approval entry for block {block_hash:?}")]
+	MissingApprovalEntry {
+		/// The hash of the block.
+		block_hash: Hash,
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+	},
+	/// A candidate entry has an approval entry for a block which does not include it.
+	#[error(
+		"Candidate {candidate_hash:?} has an approval entry for unrelated block {block_hash:?}"
+	)]
+	DanglingApprovalEntry {
+		/// The hash of the block.
+		block_hash: Hash,
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+	},
+	/// The approved bit of a block disagrees with the approval entry of the candidate.
+	#[error("Approval of candidate {candidate_hash:?} in block {block_hash:?} is inconsistent")]
+	ApprovalMismatch {
+		/// The hash of the block.
+		block_hash: Hash,
+		/// The hash of the candidate.
+		candidate_hash: CandidateHash,
+	},
+	/// A block lists a child which does not exist or has another parent.
+	#[error("Block {block_hash:?} lists unknown child {child:?}")]
+	DanglingChild {
+		/// The hash of the block.
+		block_hash: Hash,
+		/// The hash of the child.
+		child: Hash,
+	},
+}
+
+/// Inspection and repair of an approval-voting database.
+pub struct ApprovalDbInspector {
+	backend: DbBackend,
+}
+
+impl ApprovalDbInspector {
+	/// Create a new inspector of the approval data in the given column of `db`.
+	pub fn new(db: Arc<dyn Database>, col_approval_data: u32) -> Self {
+		Self { backend: DbBackend::new(db, DatabaseConfig { col_approval_data }) }
+	}
+
+	/// All blocks in the database, ascending by height.
+	pub fn blocks(&self) -> SubsystemResult<Vec<BlockSummary>> {
+		let mut blocks = Vec::new();
+		for block_hash in self.backend.load_all_blocks()? {
+			if let Some(block) = self.block(&block_hash)? {
+				blocks.push(block);
+			}
+		}
+		Ok(blocks)
+	}
+
+	/// A single block, if it has an entry.
+	pub fn block(&self, block_hash: &Hash) -> SubsystemResult<Option<BlockSummary>> {
+		let Some(entry) = self.backend.load_block_entry(block_hash)? else { return Ok(None) };
+
+		let mut candidates = Vec::with_capacity(entry.candidates().len());
+		for (core_index, candidate_hash) in entry.candidates() {
+			let candidate_entry = self.backend.load_candidate_entry(candidate_hash)?;
+			let approvals = candidate_entry.as_ref().map(|c| c.approvals().count_ones());
+			let assignments = candidate_entry
+				.as_ref()
+				.and_then(|c| c.approval_entry(block_hash))
+				.map(|a| a.n_assignments());
+			candidates.push(CandidateSummary {
+				candidate_hash: *candidate_hash,
+				core_index: *core_index,
+				approved: entry.is_candidate_approved(candidate_hash),
+				approvals,
+				assignments,
+			});
+		}
+
+		Ok(Some(BlockSummary {
+			block_hash: *block_hash,
+			block_number: entry.block_number(),
+			parent_hash: entry.parent_hash(),
+			session: entry.session(),
+			approved: entry.is_fully_approved(),
+			candidates,
+			children: entry.children.clone(),
+		}))
+	}
+
+	/// Check the invariants between stored blocks, block entries and candidate entries.
+	pub fn check(&self) -> SubsystemResult<Vec<Inconsistency>> {
+		let mut inconsistencies = Vec::new();
+		let Some(range) = self.backend.load_stored_blocks()? else { return Ok(inconsistencies) };
+
+		let mut candidates = HashSet::new();
+		for height in range.0..range.1 {
+			for block_hash in self.backend.load_blocks_at_height(&height)? {
+				let Some(entry) = self.backend.load_block_entry(&block_hash)? else {
+					inconsistencies.push(Inconsistency::MissingBlockEntry { height, block_hash });
+					continue
+				};
+
+				if entry.block_number() != height {
+					inconsistencies.push(Inconsistency::WrongHeight {
+						height,
+						block_number: entry.block_number(),
+						block_hash,
+					});
+				}
+
+				if entry.approved_bitfield.len() != entry.candidates().len() {
+					inconsistencies.push(Inconsistency::BitfieldLength { block_hash });
+				}
+
+				for (_, candidate_hash) in entry.candidates() {
+					candidates.insert(*candidate_hash);
+					let Some(candidate_entry) =
+						self.backend.load_candidate_entry(candidate_hash)?
+					else {
+						inconsistencies.push(Inconsistency::MissingCandidateEntry {
+							block_hash,
+							candidate_hash: *candidate_hash,
+						});
+						continue
+					};
+
+					match candidate_entry.approval_entry(&block_hash) {
+						None => inconsistencies.push(Inconsistency::MissingApprovalEntry {
+							block_hash,
+							candidate_hash: *candidate_hash,
+						}),
+						Some(approval_entry) =>
+							if approval_entry.is_approved() !=
+								entry.is_candidate_approved(candidate_hash)
+							{
+								inconsistencies.push(Inconsistency::ApprovalMismatch {
+									block_hash,
+									candidate_hash: *candidate_hash,
+								});
+							},
+					}
+				}
+
+				for child in &entry.children {
+					let is_child = self
+						.backend
+						.load_block_entry(child)?
+						.map_or(false, |child| child.parent_hash() == block_hash);
+					if !is_child {
+						inconsistencies
+							.push(Inconsistency::DanglingChild { block_hash, child: *child });
+					}
+				}
+			}
+		}
+
+		for candidate_hash in candidates {
+			let Some(candidate_entry) = self.backend.load_candidate_entry(&candidate_hash)? else {
+				continue
+			};
+			for block_hash in candidate_entry.block_assignments.keys() {
+				let includes_candidate = self
+					.backend
+					.load_block_entry(block_hash)?
+					.map_or(false, |b| b.candidates().iter().any(|(_, c)| c == &candidate_hash));
+				if !includes_candidate {
+					inconsistencies.push(Inconsistency::DanglingApprovalEntry {
+						block_hash: *block_hash,
+						candidate_hash,
+					});
+				}
+			}
+		}
+
+		Ok(inconsistencies)
+	}
+
+	/// Remove a block and all its descendants, including the approval entries of their
+	/// candidates. Candidate entries left without approval entries are removed.
+	pub fn prune_block(&mut self, block_hash: Hash) -> SubsystemResult<()> {
+		let mut overlay = OverlayedBackend::new(&self.backend);
+		prune_block(&mut overlay, block_hash)?;
+		let ops = overlay.into_write_ops();
+		self.backend.write(ops)
+	}
+
+	/// Remove a candidate entry and every block including the candidate, as these can't be
+	/// approved without it.
+	pub fn prune_candidate(&mut self, candidate_hash: CandidateHash) -> SubsystemResult<()> {
+		let mut overlay = OverlayedBackend::new(&self.backend);
+		let mut including_blocks = Vec::new();
+		if let Some(range) = overlay.load_stored_blocks()? {
+			for height in range.0..range.1 {
+				for block_hash in overlay.load_blocks_at_height(&height)? {
+					let includes_candidate =
+						overlay.load_block_entry(&block_hash)?.map_or(false, |b| {
+							b.candidates().iter().any(|(_, c)| c == &candidate_hash)
+						});
+					if includes_candidate {
+						including_blocks.push(block_hash);
+					}
+				}
+			}
+		}
+
+		for block_hash in including_blocks {
+			prune_block(&mut overlay, block_hash)?;
+		}
+		overlay.delete_candidate_entry(&candidate_hash);
+
+		let ops = overlay.into_write_ops();
+		self.backend.write(ops)
+	}
+
+	/// Fix the given inconsistencies, as found by [`Self::check`].
+	///
+	/// Blocks which can't be repaired are pruned together with their descendants, they are
+	/// imported again when the node sees them as new leaves.
+	pub fn repair(&mut self, inconsistencies: &[Inconsistency]) -> SubsystemResult<()> {
+		let mut overlay = OverlayedBackend::new(&self.backend);
+		for inconsistency in inconsistencies {
+			match inconsistency {
+				Inconsistency::MissingBlockEntry { height, block_hash } => {
+					let mut blocks_at_height = overlay.load_blocks_at_height(height)?;
+					blocks_at_height.retain(|h| h != block_hash);
+					overlay.write_blocks_at_height(*height, blocks_at_height);
+				},
+				Inconsistency::WrongHeight { height, block_hash, .. } => {
+					let mut blocks_at_height = overlay.load_blocks_at_height(height)?;
+					blocks_at_height.retain(|h| h != block_hash);
+					overlay.write_blocks_at_height(*height, blocks_at_height);
+					prune_block(&mut overlay, *block_hash)?;
+				},
+				Inconsistency::BitfieldLength { block_hash } |
+				Inconsistency::MissingCandidateEntry { block_hash, .. } |
+				Inconsistency::MissingApprovalEntry { block_hash, .. } => {
+					prune_block(&mut overlay, *block_hash)?;
+				},
+				Inconsistency::DanglingApprovalEntry { block_hash, candidate_hash } => {
+					if let Some(mut candidate_entry) =
+						overlay.load_candidate_entry(candidate_hash)?
+					{
+						candidate_entry.block_assignments.remove(block_hash);
+						if candidate_entry.block_assignments.is_empty() {
+							overlay.delete_candidate_entry(candidate_hash);
+						} else {
+							overlay.write_candidate_entry(candidate_entry);
+						}
+					}
+				},
+				Inconsistency::ApprovalMismatch { block_hash, candidate_hash } => {
+					// Forget the approval on both sides, it is recomputed from the stored
+					// assignments and approvals on the next wakeup of the candidate.
+					if let Some(mut candidate_entry) =
+						overlay.load_candidate_entry(candidate_hash)?
+					{
+						if let Some(approval_entry) = candidate_entry.approval_entry_mut(block_hash)
+						{
+							approval_entry.mark_unapproved();
+						}
+						overlay.write_candidate_entry(candidate_entry);
+					}
+					if let Some(mut block_entry) = overlay.load_block_entry(block_hash)? {
+						if let Some(position) =
+							block_entry.candidates().iter().position(|(_, c)| c == candidate_hash)
+						{
+							block_entry.approved_bitfield.set(position, false);
+						}
+						overlay.write_block_entry(block_entry);
+					}
+				},
+				Inconsistency::DanglingChild { block_hash, child } => {
+					if let Some(mut block_entry) = overlay.load_block_entry(block_hash)? {
+						block_entry.children.retain(|c| c != child);
+						overlay.write_block_entry(block_entry);
+					}
+				},
+			}
+		}
+
+		let ops = overlay.into_write_ops();
+		self.backend.write(ops)
+	}
+}
+
+/// Remove a block and its descendants from the overlay.
+fn prune_block(
+	overlay: &mut OverlayedBackend<'_, impl Backend>,
+	block_hash: Hash,
+) -> SubsystemResult<()> {
+	let Some(entry) = overlay.load_block_entry(&block_hash)? else { return Ok(()) };
+
+	if let Some(mut parent) = overlay.load_block_entry(&entry.parent_hash())? {
+		parent.children.retain(|c| c != &block_hash);
+		overlay.write_block_entry(parent);
+	}
+
+	let mut stack = vec![(block_hash, entry.block_number())];
+	while let Some((block_hash, number)) = stack.pop() {
+		let mut blocks_at_height = overlay.load_blocks_at_height(&number)?;
+		blocks_at_height.retain(|h| h != &block_hash);
+		if blocks_at_height.is_empty() {
+			overlay.delete_blocks_at_height(number);
+		} else {
+			overlay.write_blocks_at_height(number, blocks_at_height);
+		}
+
+		let Some(entry) = overlay.load_block_entry(&block_hash)? else { continue };
+		overlay.delete_block_entry(&block_hash);
+
+		for (_, candidate_hash) in entry.candidates() {
+			if let Some(mut candidate_entry) = overlay.load_candidate_entry(candidate_hash)? {
+				candidate_entry.block_assignments.remove(&block_hash);
+				if candidate_entry.block_assignments.is_empty() {
+					overlay.delete_candidate_entry(candidate_hash);
+				} else {
+					overlay.write_candidate_entry(candidate_entry);
+				}
+			}
+		}
+
+		stack.extend(entry.children.into_iter().map(|h| (h, number + 1)));
+	}
+
+	Ok(())
+}
@@ -101,6 +101,7 @@ pub mod approval_db;
 mod backend;
 pub mod criteria;
 mod import;
+pub mod inspect;
 mod ops;
 mod persisted_entries;
 
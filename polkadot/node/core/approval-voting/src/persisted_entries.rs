@@ -251,6 +251,11 @@ impl ApprovalEntry {
 		self.approved = true;
 	}
 
+	/// Mark the approval entry as not approved, so that its approval is computed again.
+	pub fn mark_unapproved(&mut self) {
+		self.approved = false;
+	}
+
 	/// Access the tranches.
 	pub fn tranches(&self) -> &[TrancheEntry] {
 		&self.tranches
@@ -114,6 +114,9 @@ pub struct Config {
 	pub pvf_prepare_workers_soft_max_num: usize,
 	/// The absolute number of pvf workers that can be spawned in the pvf prepare pool.
 	pub pvf_prepare_workers_hard_max_num: usize,
+	/// The total size of the prepared artifacts, in bytes, above which the least recently needed
+	/// ones are pruned. `None` to use the default.
+	pub pvf_artifacts_cache_size_limit: Option<u64>,
 }
 
 /// The candidate validation subsystem.
@@ -317,22 +320,24 @@ async fn run<Context>(
 		pvf_execute_workers_max_num,
 		pvf_prepare_workers_soft_max_num,
 		pvf_prepare_workers_hard_max_num,
+		pvf_artifacts_cache_size_limit,
 	}: Config,
 ) -> SubsystemResult<()> {
-	let (mut validation_host, task) = polkadot_node_core_pvf::start(
-		polkadot_node_core_pvf::Config::new(
-			artifacts_cache_path,
-			node_version,
-			secure_validator_mode,
-			prep_worker_path,
-			exec_worker_path,
-			pvf_execute_workers_max_num,
-			pvf_prepare_workers_soft_max_num,
-			pvf_prepare_workers_hard_max_num,
-		),
-		pvf_metrics,
-	)
-	.await?;
+	let mut pvf_config = polkadot_node_core_pvf::Config::new(
+		artifacts_cache_path,
+		node_version,
+		secure_validator_mode,
+		prep_worker_path,
+		exec_worker_path,
+		pvf_execute_workers_max_num,
+		pvf_prepare_workers_soft_max_num,
+		pvf_prepare_workers_hard_max_num,
+	);
+	if let Some(limit) = pvf_artifacts_cache_size_limit {
+		pvf_config.artifacts_cache_size_limit = limit;
+	}
+	let (mut validation_host, task) =
+		polkadot_node_core_pvf::start(pvf_config, pvf_metrics).await?;
 	ctx.spawn_blocking("pvf-validation-host", task.boxed())?;
 
 	let mut tasks = FuturesUnordered::new();
@@ -428,6 +433,15 @@ async fn handle_active_leaves_update<Sender>(
 			(None, Some(new_index)) => Some(new_index),
 			_ => None,
 		};
+		if maybe_new_session_index.is_some() {
+			if let Err(err) = validation_host.note_new_session().await {
+				gum::warn!(
+					target: LOG_TARGET,
+					?err,
+					"cannot notify the validation host of a new session",
+				);
+			}
+		}
 		maybe_prepare_validation(
 			sender,
 			keystore.clone(),
@@ -1226,6 +1240,10 @@ trait ValidationBackend {
 
 	async fn heads_up(&mut self, active_pvfs: Vec<PvfPrepData>) -> Result<(), String>;
 
+	async fn note_new_session(&mut self) -> Result<(), String> {
+		Ok(())
+	}
+
 	async fn update_active_leaves(
 		&mut self,
 		update: ActiveLeavesUpdate,
@@ -1282,6 +1300,10 @@ impl ValidationBackend for ValidationHost {
 		self.heads_up(active_pvfs).await
 	}
 
+	async fn note_new_session(&mut self) -> Result<(), String> {
+		self.note_new_session().await
+	}
+
 	async fn update_active_leaves(
 		&mut self,
 		update: ActiveLeavesUpdate,
@@ -38,8 +38,11 @@ use crate::backend::{Backend, BackendWriteOp, OverlayedBackend};
 
 mod backend;
 mod db_backend;
+mod metrics;
 mod tree;
 
+pub use metrics::Metrics;
+
 #[cfg(test)]
 mod tests;
 
@@ -329,13 +332,14 @@ pub struct Config {
 pub struct ChainSelectionSubsystem {
 	config: Config,
 	db: Arc<dyn Database>,
+	metrics: Metrics,
 }
 
 impl ChainSelectionSubsystem {
 	/// Create a new instance of the subsystem with the given config
 	/// and key-value store.
-	pub fn new(config: Config, db: Arc<dyn Database>) -> Self {
-		ChainSelectionSubsystem { config, db }
+	pub fn new(config: Config, db: Arc<dyn Database>, metrics: Metrics) -> Self {
+		ChainSelectionSubsystem { config, db, metrics }
 	}
 
 	/// Revert to the block corresponding to the specified `hash`.
@@ -365,6 +369,7 @@ impl<Context> ChainSelectionSubsystem {
 				self.config.stagnant_check_interval,
 				self.config.stagnant_check_mode,
 				Box::new(SystemClock),
+				self.metrics,
 			)
 			.map(Ok)
 			.boxed(),
@@ -380,6 +385,7 @@ async fn run<Context, B>(
 	stagnant_check_interval: StagnantCheckInterval,
 	stagnant_check_mode: StagnantCheckMode,
 	clock: Box<dyn Clock + Send + Sync>,
+	metrics: Metrics,
 ) where
 	B: Backend,
 {
@@ -391,6 +397,7 @@ async fn run<Context, B>(
 			&stagnant_check_interval,
 			&stagnant_check_mode,
 			&*clock,
+			&metrics,
 		)
 		.await;
 		match res {
@@ -419,12 +426,15 @@ async fn run_until_error<Context, B>(
 	stagnant_check_interval: &StagnantCheckInterval,
 	stagnant_check_mode: &StagnantCheckMode,
 	clock: &(dyn Clock + Sync),
+	metrics: &Metrics,
 ) -> Result<(), Error>
 where
 	B: Backend,
 {
 	let mut stagnant_check_stream = stagnant_check_interval.timeout_stream();
 	loop {
+		// Whether the backend was written to, which may change the set of viable leaves.
+		let mut leaves_changed = false;
 		futures::select! {
 			msg = ctx.recv().fuse() => {
 				let msg = msg?;
@@ -442,14 +452,17 @@ where
 							).await?;
 
 							backend.write(write_ops)?;
+							leaves_changed = true;
 						}
 					}
 					FromOrchestra::Signal(OverseerSignal::BlockFinalized(h, n)) => {
-						handle_finalized_block(backend, h, n)?
+						handle_finalized_block(backend, h, n)?;
+						leaves_changed = true;
 					}
 					FromOrchestra::Communication { msg } => match msg {
 						ChainSelectionMessage::Approved(hash) => {
-							handle_approved_block(backend, hash)?
+							handle_approved_block(backend, hash)?;
+							leaves_changed = true;
 						}
 						ChainSelectionMessage::Leaves(tx) => {
 							let leaves = load_leaves(ctx.sender(), &*backend).await?;
@@ -471,6 +484,22 @@ where
 						ChainSelectionMessage::RevertBlocks(blocks_to_revert) => {
 							let write_ops = handle_revert_blocks(backend, blocks_to_revert)?;
 							backend.write(write_ops)?;
+							leaves_changed = true;
+						}
+						ChainSelectionMessage::SetViability(block_hash, viable, tx) => {
+							let known = handle_set_viability(backend, block_hash, viable)?;
+							leaves_changed = known;
+							metrics.on_operator_override(
+								if viable { "set_viable" } else { "set_non_viable" },
+							);
+							let _ = tx.send(known);
+						}
+						ChainSelectionMessage::CheckStagnant(tx) => {
+							gum::info!(target: LOG_TARGET, "Stagnant check triggered by operator");
+							detect_stagnant(backend, clock.timestamp_now(), MAX_STAGNANT_ENTRIES)?;
+							leaves_changed = true;
+							metrics.on_operator_override("check_stagnant");
+							let _ = tx.send(());
 						}
 					}
 				}
@@ -483,8 +512,13 @@ where
 						prune_only_stagnant(backend, now_timestamp - STAGNANT_PRUNE_DELAY, MAX_STAGNANT_ENTRIES)
 					},
 				}?;
+				leaves_changed = true;
 			}
 		}
+
+		if leaves_changed {
+			metrics.note_viable_leaves(&*backend);
+		}
 	}
 }
 
@@ -699,6 +733,40 @@ fn handle_revert_blocks(
 	Ok(overlay.into_write_ops().collect())
 }
 
+// Override the viability of a block on behalf of an operator.
+fn handle_set_viability(
+	backend: &mut impl Backend,
+	block_hash: Hash,
+	viable: bool,
+) -> Result<bool, Error> {
+	let (known, ops) = {
+		let mut overlay = OverlayedBackend::new(&*backend);
+		let known = tree::set_viability(&mut overlay, block_hash, viable)?;
+
+		(known, overlay.into_write_ops())
+	};
+
+	if known {
+		gum::info!(
+			target: LOG_TARGET,
+			?block_hash,
+			viable,
+			"Block viability overridden by operator",
+		);
+	} else {
+		gum::info!(
+			target: LOG_TARGET,
+			?block_hash,
+			viable,
+			"Operator tried to override the viability of an unknown block",
+		);
+	}
+
+	backend.write(ops)?;
+
+	Ok(known)
+}
+
 fn detect_stagnant(
 	backend: &mut impl Backend,
 	now: Timestamp,
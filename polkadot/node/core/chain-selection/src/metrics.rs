@@ -0,0 +1,93 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics definitions for the chain selection subsystem.
+
+use polkadot_node_subsystem_util::metrics::{self, prometheus};
+
+use crate::{backend::Backend, LOG_TARGET};
+
+#[derive(Clone)]
+struct MetricsInner {
+	viable_leaves: prometheus::Gauge<prometheus::U64>,
+	best_viable_leaf: prometheus::Gauge<prometheus::U64>,
+	operator_overrides: prometheus::CounterVec<prometheus::U64>,
+}
+
+/// Chain selection metrics.
+#[derive(Default, Clone)]
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	/// Update the viable leaves gauges from the backend.
+	///
+	/// Failing to load the leaves is logged and otherwise ignored, the gauges keep their
+	/// previous values.
+	pub(crate) fn note_viable_leaves(&self, backend: &impl Backend) {
+		if let Some(metrics) = &self.0 {
+			let leaves = match backend.load_leaves() {
+				Ok(leaves) => leaves,
+				Err(err) => {
+					gum::debug!(target: LOG_TARGET, ?err, "Failed to load leaves for metrics");
+					return
+				},
+			};
+			metrics.viable_leaves.set(leaves.inner.len() as u64);
+			metrics
+				.best_viable_leaf
+				.set(leaves.inner.first().map_or(0, |leaf| leaf.block_number.into()));
+		}
+	}
+
+	/// Called when an operator overrides chain selection.
+	pub(crate) fn on_operator_override(&self, action: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.operator_overrides.with_label_values(&[action]).inc();
+		}
+	}
+}
+
+impl metrics::Metrics for Metrics {
+	fn try_register(registry: &prometheus::Registry) -> Result<Self, prometheus::PrometheusError> {
+		let metrics = MetricsInner {
+			viable_leaves: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_chain_selection_viable_leaves",
+					"Number of viable leaves known to chain selection.",
+				)?,
+				registry,
+			)?,
+			best_viable_leaf: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_chain_selection_best_viable_leaf",
+					"Number of the best viable leaf, 0 if only the finalized block is viable.",
+				)?,
+				registry,
+			)?,
+			operator_overrides: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_chain_selection_operator_overrides_total",
+						"Number of operator overrides of chain selection.",
+					),
+					&["action"],
+				)?,
+				registry,
+			)?,
+		};
+		Ok(Self(Some(metrics)))
+	}
+}
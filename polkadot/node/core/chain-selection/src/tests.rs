@@ -247,6 +247,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 		StagnantCheckInterval::new(TEST_STAGNANT_INTERVAL),
 		StagnantCheckMode::CheckAndPrune,
 		Box::new(clock.clone()),
+		Metrics::default(),
 	);
 
 	let test_fut = test(backend, clock, virtual_overseer);
@@ -634,6 +635,21 @@ async fn approve_block(
 	write_rx.await.unwrap()
 }
 
+async fn set_viability(
+	virtual_overseer: &mut VirtualOverseer,
+	block_hash: Hash,
+	viable: bool,
+) -> bool {
+	let (tx, rx) = oneshot::channel();
+	virtual_overseer
+		.send(FromOrchestra::Communication {
+			msg: ChainSelectionMessage::SetViability(block_hash, viable, tx),
+		})
+		.await;
+
+	rx.await.unwrap()
+}
+
 #[test]
 fn no_op_subsystem_run() {
 	test_harness(|_, _, virtual_overseer| async move { virtual_overseer });
@@ -2149,3 +2165,96 @@ fn revert_blocks_against_finalized_is_ignored() {
 		virtual_overseer
 	})
 }
+
+#[test]
+fn operator_can_mark_subtree_non_viable_and_viable_again() {
+	test_harness(|backend, _, mut virtual_overseer| async move {
+		let finalized_number = 0;
+		let finalized_hash = Hash::repeat_byte(0);
+
+		// F <- A1 <- A2 <- A3
+
+		let (a3_hash, chain_a) =
+			construct_chain_on_base(vec![1, 2, 3], finalized_number, finalized_hash, |_| {});
+
+		let (_, a1_hash, _) = extract_info_from_chain(0, &chain_a);
+		let (_, a2_hash, _) = extract_info_from_chain(1, &chain_a);
+
+		import_blocks_into(
+			&mut virtual_overseer,
+			&backend,
+			Some((finalized_number, finalized_hash)),
+			chain_a.clone(),
+		)
+		.await;
+
+		assert_leaves(&backend, vec![a3_hash]);
+
+		assert!(set_viability(&mut virtual_overseer, a2_hash, false).await);
+		assert_leaves(&backend, vec![a1_hash]);
+		assert_leaves_query(&mut virtual_overseer, vec![a1_hash]).await;
+
+		assert!(set_viability(&mut virtual_overseer, a2_hash, true).await);
+		assert_leaves(&backend, vec![a3_hash]);
+		assert_leaves_query(&mut virtual_overseer, vec![a3_hash]).await;
+
+		assert!(!set_viability(&mut virtual_overseer, Hash::repeat_byte(42), false).await);
+		assert_leaves(&backend, vec![a3_hash]);
+
+		virtual_overseer
+	})
+}
+
+#[test]
+fn operator_marking_viable_clears_stagnant_descendants() {
+	test_harness(|backend, clock, mut virtual_overseer| async move {
+		let finalized_number = 0;
+		let finalized_hash = Hash::repeat_byte(0);
+
+		// F <- A1 <- A2
+
+		let (a2_hash, chain_a) =
+			construct_chain_on_base(vec![1, 2], finalized_number, finalized_hash, |h| {
+				salt_header(h, b"a");
+			});
+
+		let (_, a1_hash, _) = extract_info_from_chain(0, &chain_a);
+
+		import_chains_into_empty(
+			&mut virtual_overseer,
+			&backend,
+			finalized_number,
+			finalized_hash,
+			vec![chain_a.clone()],
+		)
+		.await;
+
+		{
+			let (_, write_rx) = backend.await_next_write();
+			clock.inc_by(STAGNANT_TIMEOUT);
+
+			write_rx.await.unwrap();
+		}
+
+		assert_matches!(
+			backend.load_block_entry(&a2_hash).unwrap().unwrap().viability.approval,
+			Approval::Stagnant
+		);
+		assert_leaves(&backend, vec![]);
+
+		assert!(set_viability(&mut virtual_overseer, a1_hash, true).await);
+
+		assert_matches!(
+			backend.load_block_entry(&a1_hash).unwrap().unwrap().viability.approval,
+			Approval::Unapproved
+		);
+		assert_matches!(
+			backend.load_block_entry(&a2_hash).unwrap().unwrap().viability.approval,
+			Approval::Unapproved
+		);
+		assert_leaves(&backend, vec![a2_hash]);
+		assert_leaves_query(&mut virtual_overseer, vec![a2_hash]).await;
+
+		virtual_overseer
+	})
+}
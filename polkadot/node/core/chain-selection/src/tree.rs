@@ -581,6 +581,51 @@ pub(super) fn approve_block(
 	Ok(())
 }
 
+/// Override the viability of a block on behalf of an operator.
+///
+/// Marking a block non-viable marks it as explicitly reverted, which makes all its
+/// descendants non-viable as well. Marking a block viable clears reversions and stagnation
+/// of the block and all its descendants.
+///
+/// Returns `false` if the block is unknown, e.g. because it is finalized.
+pub(super) fn set_viability(
+	backend: &mut OverlayedBackend<impl Backend>,
+	block_hash: Hash,
+	viable: bool,
+) -> Result<bool, Error> {
+	let mut entry = match backend.load_block_entry(&block_hash)? {
+		None => return Ok(false),
+		Some(entry) => entry,
+	};
+
+	if viable {
+		// Clear the descendants first, the propagation below picks them up from the overlay.
+		let mut frontier = entry.children.clone();
+		while let Some(hash) = frontier.pop() {
+			if let Some(mut descendant) = backend.load_block_entry(&hash)? {
+				clear_unviability(&mut descendant.viability);
+				frontier.extend(descendant.children.iter().cloned());
+				backend.write_block_entry(descendant);
+			}
+		}
+
+		clear_unviability(&mut entry.viability);
+	} else {
+		entry.viability.explicitly_reverted = true;
+	}
+
+	propagate_viability_update(backend, entry)?;
+
+	Ok(true)
+}
+
+fn clear_unviability(viability: &mut ViabilityCriteria) {
+	viability.explicitly_reverted = false;
+	if viability.approval.is_stagnant() {
+		viability.approval = Approval::Unapproved;
+	}
+}
+
 /// Check whether any blocks up to the given timestamp are stagnant and update
 /// accordingly.
 ///
@@ -29,8 +29,8 @@ use futures::{
 use sc_keystore::LocalKeystore;
 
 use polkadot_node_primitives::{
-	disputes::ValidCandidateVotes, CandidateVotes, DisputeStatus, SignedDisputeStatement,
-	Timestamp, DISPUTE_WINDOW,
+	disputes::ValidCandidateVotes, CandidateVotes, DisputeEvidence, DisputeStatus,
+	EvidenceStatement, SignedDisputeStatement, Timestamp, DISPUTE_WINDOW,
 };
 use polkadot_node_subsystem::{
 	messages::{
@@ -894,6 +894,11 @@ impl Initialized {
 				}
 				let _ = tx.send(query_output);
 			},
+			DisputeCoordinatorMessage::ExportEvidence(query, tx) => {
+				gum::trace!(target: LOG_TARGET, "DisputeCoordinatorMessage::ExportEvidence");
+				let evidence = self.export_evidence(ctx, overlay_db, query).await?;
+				let _ = tx.send(evidence);
+			},
 			DisputeCoordinatorMessage::IssueLocalStatement(
 				session,
 				candidate_hash,
@@ -1427,6 +1432,84 @@ impl Initialized {
 		Ok(ImportStatementsResult::ValidImport)
 	}
 
+	/// Gather everything needed to verify the given disputes offline.
+	async fn export_evidence<Context>(
+		&mut self,
+		ctx: &mut Context,
+		overlay_db: &mut OverlayedBackend<'_, impl Backend>,
+		query: Vec<(SessionIndex, CandidateHash)>,
+	) -> Result<Vec<DisputeEvidence>> {
+		let recent_disputes = overlay_db.load_recent_disputes()?.unwrap_or_default();
+		let mut evidence = Vec::with_capacity(query.len());
+		for (session_index, candidate_hash) in query {
+			let Some(status) = recent_disputes.get(&(session_index, candidate_hash)).copied()
+			else {
+				gum::debug!(
+					target: LOG_TARGET,
+					?candidate_hash,
+					session_index,
+					"Not exporting evidence for unknown dispute",
+				);
+				continue
+			};
+			let Some(votes) = overlay_db.load_candidate_votes(session_index, &candidate_hash)?
+			else {
+				gum::debug!(
+					target: LOG_TARGET,
+					?candidate_hash,
+					session_index,
+					"No votes found for disputed candidate",
+				);
+				continue
+			};
+
+			let relay_parent = self
+				.runtime_info
+				.get_block_in_session(session_index)
+				.unwrap_or_else(|| votes.candidate_receipt.descriptor.relay_parent());
+			let validators = match self
+				.runtime_info
+				.get_session_info_by_index(ctx.sender(), relay_parent, session_index)
+				.await
+			{
+				Ok(extended_session_info) => extended_session_info.session_info.validators.to_vec(),
+				Err(err) => {
+					gum::debug!(
+						target: LOG_TARGET,
+						?candidate_hash,
+						session_index,
+						?err,
+						"Could not retrieve session info for exporting dispute evidence",
+					);
+					continue
+				},
+			};
+
+			let valid = votes.valid.into_iter().map(|(kind, validator_index, signature)| {
+				EvidenceStatement {
+					validator_index,
+					statement: DisputeStatement::Valid(kind),
+					signature,
+				}
+			});
+			let invalid = votes.invalid.into_iter().map(|(kind, validator_index, signature)| {
+				EvidenceStatement {
+					validator_index,
+					statement: DisputeStatement::Invalid(kind),
+					signature,
+				}
+			});
+			evidence.push(DisputeEvidence {
+				session_index,
+				candidate_receipt: votes.candidate_receipt,
+				validators,
+				statements: valid.chain(invalid).collect(),
+				status,
+			});
+		}
+		Ok(evidence)
+	}
+
 	async fn issue_local_statement<Context>(
 		&mut self,
 		ctx: &mut Context,
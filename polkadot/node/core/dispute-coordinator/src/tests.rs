@@ -32,6 +32,7 @@ use futures::{
 use polkadot_node_subsystem_util::database::Database;
 
 use polkadot_node_primitives::{
+	disputes::{DisputeOutcome, EvidenceError},
 	DisputeMessage, DisputeStatus, SignedDisputeStatement, SignedFullStatement, Statement,
 	DISPUTE_WINDOW,
 };
@@ -1920,6 +1921,134 @@ fn supermajority_valid_dispute_may_be_finalized() {
 	});
 }
 
+#[test]
+fn exported_evidence_verifies() {
+	test_harness(|mut test_state, mut virtual_overseer| {
+		Box::pin(async move {
+			let session = 1;
+
+			test_state.handle_resume_sync(&mut virtual_overseer, session).await;
+
+			let candidate_receipt = make_valid_candidate_receipt();
+			let candidate_hash = candidate_receipt.hash();
+			let candidate_events = vec![make_candidate_backed_event(candidate_receipt.clone())];
+
+			test_state
+				.activate_leaf_at_session(&mut virtual_overseer, session, 1, candidate_events)
+				.await;
+
+			let supermajority_threshold =
+				polkadot_primitives::supermajority_threshold(test_state.validators.len());
+
+			let (valid_vote, invalid_vote) = generate_opposing_votes_pair(
+				&test_state,
+				ValidatorIndex(2),
+				ValidatorIndex(1),
+				candidate_hash,
+				session,
+				VoteType::Explicit,
+			)
+			.await;
+
+			virtual_overseer
+				.send(FromOrchestra::Communication {
+					msg: DisputeCoordinatorMessage::ImportStatements {
+						candidate_receipt: candidate_receipt.clone(),
+						session,
+						statements: vec![
+							(valid_vote, ValidatorIndex(2)),
+							(invalid_vote, ValidatorIndex(1)),
+						],
+						pending_confirmation: None,
+					},
+				})
+				.await;
+			handle_disabled_validators_queries(&mut virtual_overseer, Vec::new()).await;
+			handle_approval_vote_request(&mut virtual_overseer, &candidate_hash, HashMap::new())
+				.await;
+
+			participation_with_distribution(
+				&mut virtual_overseer,
+				&candidate_hash,
+				candidate_receipt.commitments_hash,
+			)
+			.await;
+
+			let mut statements = Vec::new();
+			for i in (0_u32..supermajority_threshold as u32 - 1).map(|i| i + 3) {
+				let vote = test_state.issue_explicit_statement_with_index(
+					ValidatorIndex(i),
+					candidate_hash,
+					session,
+					true,
+				);
+
+				statements.push((vote, ValidatorIndex(i as _)));
+			}
+
+			virtual_overseer
+				.send(FromOrchestra::Communication {
+					msg: DisputeCoordinatorMessage::ImportStatements {
+						candidate_receipt: candidate_receipt.clone(),
+						session,
+						statements,
+						pending_confirmation: None,
+					},
+				})
+				.await;
+			handle_approval_vote_request(&mut virtual_overseer, &candidate_hash, HashMap::new())
+				.await;
+
+			{
+				let (tx, rx) = oneshot::channel();
+				virtual_overseer
+					.send(FromOrchestra::Communication {
+						msg: DisputeCoordinatorMessage::ExportEvidence(
+							vec![(session, candidate_hash), (session, CandidateHash(Hash::zero()))],
+							tx,
+						),
+					})
+					.await;
+
+				let evidence = rx.await.unwrap();
+				assert_eq!(evidence.len(), 1);
+				let evidence = evidence.into_iter().next().unwrap();
+				assert_eq!(evidence.candidate_receipt, candidate_receipt);
+				assert_matches!(evidence.status, DisputeStatus::ConcludedFor(_));
+
+				let validators = test_state.validator_public.to_vec();
+				let verified = evidence.verify(&validators).unwrap();
+				assert_eq!(verified.candidate_hash, candidate_hash);
+				assert_eq!(verified.outcome, DisputeOutcome::Valid);
+				assert!(verified.invalid_votes.contains(&ValidatorIndex(1)));
+				assert_eq!(evidence.verify_against_hash(evidence.validators_hash()), Ok(verified));
+
+				// A validator set which is not the one of the session is never trusted.
+				let mut forged = evidence.clone();
+				forged.validators.swap(0, 1);
+				assert_eq!(forged.verify(&validators), Err(EvidenceError::ValidatorSetMismatch));
+				assert_eq!(
+					forged.verify_against_hash(evidence.validators_hash()),
+					Err(EvidenceError::ValidatorSetMismatch)
+				);
+
+				// Tampering with the candidate invalidates all signatures.
+				let mut tampered = evidence;
+				tampered.candidate_receipt = make_invalid_candidate_receipt();
+				assert_matches!(
+					tampered.verify(&validators),
+					Err(EvidenceError::InvalidSignature(_))
+				);
+			}
+
+			virtual_overseer.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+			assert!(virtual_overseer.try_recv().await.is_none());
+
+			test_state
+		})
+	});
+}
+
 #[test]
 fn concluded_supermajority_for_non_active_after_time() {
 	test_harness(|mut test_state, mut virtual_overseer| {
@@ -18,7 +18,9 @@
 //!
 //! # Lifecycle of an artifact
 //!
-//! 1. During node start-up, we prune all the cached artifacts, if any.
+//! 1. During node start-up, we restore the prepared artifacts listed in the persisted artifacts
+//!    index, if it was written by the same node version. All the other cached artifacts, if any,
+//!    are pruned.
 //!
 //! 2. In order to be executed, a PVF should be prepared first. This means that artifacts should
 //!    have an [`ArtifactState::Prepared`] entry for that artifact in the table. If not, the
@@ -50,20 +52,30 @@
 //!    requested artifact. If it doesn't exist it reports an internal error. A request for execution
 //!    will bump the `last_time_needed` to the current time.
 //!
-//! 7. There is a separate process for pruning the prepared artifacts whose `last_time_needed` is
-//!    older by a predefined parameter. This process is run very rarely (say, once a day). Once the
-//!    artifact is expired it is removed from disk eagerly atomically.
+//! 7. There is a separate process for pruning the prepared artifacts once their total size exceeds
+//!    the cache size limit. The least recently needed artifacts are removed first, as long as their
+//!    `last_time_needed` is older by a predefined parameter and they were not needed in the current
+//!    or the previous session. This process is run periodically and whenever a new artifact is
+//!    prepared. Once the artifact is pruned it is removed from disk eagerly atomically.
+//!
+//! 8. The metadata of the prepared artifacts is persisted in the artifacts index periodically and
+//!    on shutdown, so that the artifacts and their LRU order survive restarts.
 
-use crate::{host::PrecheckResultSender, worker_interface::WORKER_DIR_PREFIX};
+use crate::{
+	host::{PrecheckResultSender, DEFAULT_ARTIFACTS_CACHE_SIZE_LIMIT},
+	worker_interface::WORKER_DIR_PREFIX,
+	LOG_TARGET,
+};
 use always_assert::always;
+use codec::{Decode, Encode};
 use polkadot_node_core_pvf_common::{error::PrepareError, pvf::PvfPrepData};
 use polkadot_parachain_primitives::primitives::ValidationCodeHash;
 use polkadot_primitives::ExecutorParamsPrepHash;
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	fs,
 	path::{Path, PathBuf},
-	time::{Duration, SystemTime},
+	time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 /// The extension to use for cached artifacts.
@@ -72,6 +84,12 @@ const ARTIFACT_EXTENSION: &str = "pvf";
 /// The prefix that artifacts used to start with under the old naming scheme.
 const ARTIFACT_OLD_PREFIX: &str = "wasmtime_";
 
+/// The name of the file persisting the metadata of the prepared artifacts.
+const ARTIFACTS_INDEX_FILE: &str = "artifacts.index";
+
+/// The name of the file the artifacts index is written to before being moved in place.
+const ARTIFACTS_INDEX_TMP_FILE: &str = "artifacts.index.tmp";
+
 pub fn generate_artifact_path(cache_path: &Path) -> PathBuf {
 	let file_name = {
 		use array_bytes::Hex;
@@ -87,7 +105,7 @@ pub fn generate_artifact_path(cache_path: &Path) -> PathBuf {
 
 /// Identifier of an artifact. Encodes a code hash of the PVF and a hash of preparation-related
 ///  executor parameter set.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Encode, Decode)]
 pub struct ArtifactId {
 	pub(crate) code_hash: ValidationCodeHash,
 	pub(crate) executor_params_prep_hash: ExecutorParamsPrepHash,
@@ -144,6 +162,8 @@ pub enum ArtifactState {
 		last_time_needed: SystemTime,
 		/// Size in bytes
 		size: u64,
+		/// The blake3 checksum of the compiled artifact, hex-encoded.
+		checksum: String,
 	},
 	/// A task to prepare this artifact is scheduled.
 	Preparing {
@@ -167,6 +187,64 @@ pub enum ArtifactState {
 /// A container of all known artifact ids and their states.
 pub struct Artifacts {
 	inner: HashMap<ArtifactId, ArtifactState>,
+	/// Artifacts needed in the current session. These are never pruned.
+	needed_this_session: HashSet<ArtifactId>,
+	/// Artifacts needed in the previous session. These are never pruned either, as candidates of
+	/// the previous session may still need to be approved or disputed.
+	needed_last_session: HashSet<ArtifactId>,
+	/// The directory the artifacts and the artifacts index are stored in.
+	cache_path: PathBuf,
+	/// The version of the node. The artifacts index is only persisted and restored if known.
+	node_version: Option<String>,
+}
+
+/// The metadata of the prepared artifacts, persisted in the cache directory.
+#[derive(Encode, Decode)]
+struct ArtifactsIndex {
+	/// The version of the node which prepared the artifacts.
+	node_version: String,
+	artifacts: Vec<IndexedArtifact>,
+}
+
+#[derive(Encode, Decode)]
+struct IndexedArtifact {
+	id: ArtifactId,
+	/// The file name of the artifact, relative to the cache directory.
+	file_name: String,
+	/// The time when the artifact was last needed, in seconds since the UNIX epoch.
+	last_time_needed: u64,
+	/// Size in bytes
+	size: u64,
+	/// The blake3 checksum of the artifact, hex-encoded.
+	checksum: String,
+}
+
+impl ArtifactsIndex {
+	/// Reads the index from the cache directory. Returns `None` if it is missing, corrupted or was
+	/// written by another version of the node.
+	fn read(cache_path: &Path, node_version: &str) -> Option<Self> {
+		let encoded = fs::read(cache_path.join(ARTIFACTS_INDEX_FILE)).ok()?;
+		let index = Self::decode(&mut &encoded[..])
+			.map_err(|err| {
+				gum::warn!(target: LOG_TARGET, ?err, "failed to decode the PVF artifacts index");
+			})
+			.ok()?;
+
+		(index.node_version == node_version).then_some(index)
+	}
+
+	/// Atomically replaces the index in the cache directory.
+	fn write(&self, cache_path: &Path) -> std::io::Result<()> {
+		let tmp_path = cache_path.join(ARTIFACTS_INDEX_TMP_FILE);
+		fs::write(&tmp_path, self.encode())?;
+		fs::rename(tmp_path, cache_path.join(ARTIFACTS_INDEX_FILE))
+	}
+}
+
+/// Computes the blake3 checksum of the artifact at the given path, in the same format as the
+/// prepare worker does.
+fn artifact_checksum(path: &Path) -> std::io::Result<String> {
+	Ok(blake3::hash(&fs::read(path)?).to_hex().to_string())
 }
 
 /// Parameters we use to cleanup artifacts
@@ -183,14 +261,19 @@ pub struct ArtifactsCleanupConfig {
 impl Default for ArtifactsCleanupConfig {
 	fn default() -> Self {
 		Self {
-			cache_limit: 10 * 1024 * 1024 * 1024,              // 10 GiB
+			cache_limit: DEFAULT_ARTIFACTS_CACHE_SIZE_LIMIT,
 			min_stale_time: Duration::from_secs(24 * 60 * 60), // 24 hours
 		}
 	}
 }
 
-#[cfg(test)]
 impl ArtifactsCleanupConfig {
+	/// Default cleanup parameters with the given cache size limit in bytes.
+	pub fn with_cache_limit(cache_limit: u64) -> Self {
+		Self { cache_limit, ..Default::default() }
+	}
+
+	#[cfg(test)]
 	pub fn new(cache_limit: u64, min_stale_time: Duration) -> Self {
 		Self { cache_limit, min_stale_time }
 	}
@@ -199,7 +282,13 @@ impl ArtifactsCleanupConfig {
 impl Artifacts {
 	#[cfg(test)]
 	pub(crate) fn empty() -> Self {
-		Self { inner: HashMap::new() }
+		Self {
+			inner: HashMap::new(),
+			needed_this_session: HashSet::new(),
+			needed_last_session: HashSet::new(),
+			cache_path: PathBuf::new(),
+			node_version: None,
+		}
 	}
 
 	#[cfg(test)]
@@ -212,27 +301,157 @@ impl Artifacts {
 		self.inner.keys().cloned().collect()
 	}
 
-	/// Create an empty table and the cache directory on-disk if it doesn't exist.
-	pub async fn new(cache_path: &Path) -> Self {
+	/// Create the table and the cache directory on-disk if it doesn't exist.
+	///
+	/// If `node_version` is known, the artifacts listed in the artifacts index written by the same
+	/// node version are restored as prepared.
+	pub async fn new(cache_path: &Path, node_version: Option<String>) -> Self {
 		// Make sure that the cache path directory and all its parents are created.
 		let _ = tokio::fs::create_dir_all(cache_path).await;
 
-		// Delete any leftover artifacts and worker dirs from previous runs. We don't delete the
-		// entire cache directory in case the user made a mistake and set it to e.g. their home
+		let mut inner = HashMap::new();
+		let index = node_version.as_deref().and_then(|v| ArtifactsIndex::read(cache_path, v));
+		for artifact in index.map(|index| index.artifacts).into_iter().flatten() {
+			// Never follow paths outside of the cache directory.
+			if Path::new(&artifact.file_name).file_name() !=
+				Some(std::ffi::OsStr::new(&artifact.file_name))
+			{
+				continue
+			}
+			let path = cache_path.join(&artifact.file_name);
+			if path.extension().map_or(true, |ext| ext != ARTIFACT_EXTENSION) {
+				continue
+			}
+			// The size is a cheap sanity check against truncated files, the checksum catches
+			// corrupted or replaced ones. Mismatching artifacts are deleted below.
+			if fs::metadata(&path).map_or(true, |metadata| metadata.len() != artifact.size) {
+				continue
+			}
+			match artifact_checksum(&path) {
+				Ok(checksum) if checksum == artifact.checksum => {},
+				Ok(_) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						artifact_id = ?artifact.id,
+						"checksum mismatch for the PVF artifact {}, deleting it",
+						path.display(),
+					);
+					continue
+				},
+				Err(err) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						artifact_id = ?artifact.id,
+						"failed to read the PVF artifact {}: {}",
+						path.display(),
+						err,
+					);
+					continue
+				},
+			}
+
+			let last_time_needed = UNIX_EPOCH + Duration::from_secs(artifact.last_time_needed);
+			inner.insert(
+				artifact.id,
+				ArtifactState::Prepared {
+					path,
+					last_time_needed,
+					size: artifact.size,
+					checksum: artifact.checksum,
+				},
+			);
+		}
+		let restored: HashSet<_> = inner
+			.values()
+			.filter_map(|state| match state {
+				ArtifactState::Prepared { path, .. } => Some(path.clone()),
+				_ => None,
+			})
+			.collect();
+		if !restored.is_empty() {
+			gum::info!(
+				target: LOG_TARGET,
+				"restored {} PVF artifacts from the previous run",
+				restored.len(),
+			);
+		}
+
+		// Delete any other leftover artifacts and worker dirs from previous runs. We don't delete
+		// the entire cache directory in case the user made a mistake and set it to e.g. their home
 		// directory. This is a best-effort to do clean-up, so ignore any errors.
 		for entry in fs::read_dir(cache_path).into_iter().flatten().flatten() {
 			let path = entry.path();
 			let Some(file_name) = path.file_name().and_then(|f| f.to_str()) else { continue };
 			if path.is_dir() && file_name.starts_with(WORKER_DIR_PREFIX) {
 				let _ = fs::remove_dir_all(path);
-			} else if path.extension().map_or(false, |ext| ext == ARTIFACT_EXTENSION) ||
-				file_name.starts_with(ARTIFACT_OLD_PREFIX)
+			} else if (path.extension().map_or(false, |ext| ext == ARTIFACT_EXTENSION) ||
+				file_name.starts_with(ARTIFACT_OLD_PREFIX)) &&
+				!restored.contains(&path)
 			{
 				let _ = fs::remove_file(path);
 			}
 		}
 
-		Self { inner: HashMap::new() }
+		Self {
+			inner,
+			needed_this_session: HashSet::new(),
+			needed_last_session: HashSet::new(),
+			cache_path: cache_path.to_owned(),
+			node_version,
+		}
+	}
+
+	/// Persist the metadata of the prepared artifacts in the artifacts index. Does nothing if the
+	/// node version is unknown.
+	pub fn persist(&self) -> std::io::Result<()> {
+		let Some(node_version) = &self.node_version else { return Ok(()) };
+
+		let artifacts = self
+			.inner
+			.iter()
+			.filter_map(|(id, state)| match state {
+				ArtifactState::Prepared { path, last_time_needed, size, checksum } =>
+					Some(IndexedArtifact {
+						id: id.clone(),
+						file_name: path.file_name()?.to_str()?.to_owned(),
+						last_time_needed: last_time_needed
+							.duration_since(UNIX_EPOCH)
+							.map_or(0, |since_epoch| since_epoch.as_secs()),
+						size: *size,
+						checksum: checksum.clone(),
+					}),
+				_ => None,
+			})
+			.collect();
+
+		ArtifactsIndex { node_version: node_version.clone(), artifacts }.write(&self.cache_path)
+	}
+
+	/// Note that the artifact is needed in the current session, protecting it from pruning.
+	pub fn note_needed(&mut self, artifact_id: &ArtifactId) {
+		self.needed_this_session.insert(artifact_id.clone());
+	}
+
+	/// Note that a new session started. Artifacts that were not needed in the last two sessions
+	/// may be pruned from now on.
+	pub fn note_new_session(&mut self) {
+		self.needed_last_session = std::mem::take(&mut self.needed_this_session);
+	}
+
+	fn is_needed(&self, artifact_id: &ArtifactId) -> bool {
+		self.needed_this_session.contains(artifact_id) ||
+			self.needed_last_session.contains(artifact_id)
+	}
+
+	/// The total size of the prepared artifacts in bytes.
+	pub fn total_size(&self) -> u64 {
+		self.inner
+			.values()
+			.map(|state| match state {
+				ArtifactState::Prepared { size, .. } => *size,
+				_ => 0,
+			})
+			.sum()
 	}
 
 	/// Returns the state of the given artifact by its ID.
@@ -267,11 +486,12 @@ impl Artifacts {
 		path: PathBuf,
 		last_time_needed: SystemTime,
 		size: u64,
+		checksum: String,
 	) {
 		// See the precondition.
 		always!(self
 			.inner
-			.insert(artifact_id, ArtifactState::Prepared { path, last_time_needed, size })
+			.insert(artifact_id, ArtifactState::Prepared { path, last_time_needed, size, checksum })
 			.is_none());
 	}
 
@@ -283,8 +503,9 @@ impl Artifacts {
 		})
 	}
 
-	/// Remove artifacts older than the given TTL when the total artifact size reaches the limit
-	/// and return id and path of the removed ones
+	/// Remove the least recently needed artifacts older than the given TTL while the total artifact
+	/// size exceeds the limit and return id and path of the removed ones. Artifacts needed in the
+	/// current or the previous session are never removed.
 	pub fn prune(&mut self, cleanup_config: &ArtifactsCleanupConfig) -> Vec<(ArtifactId, PathBuf)> {
 		let mut to_remove = vec![];
 		let now = SystemTime::now();
@@ -306,6 +527,10 @@ impl Artifacts {
 				break
 			};
 
+			if self.is_needed(&artifact_id) {
+				continue
+			}
+
 			let used_recently = now
 				.duration_since(last_time_needed)
 				.map(|stale_time| stale_time < cleanup_config.min_stale_time)
@@ -326,6 +551,7 @@ impl Artifacts {
 #[cfg(test)]
 mod tests {
 	use crate::testing::artifact_id;
+	use assert_matches::assert_matches;
 
 	use super::*;
 
@@ -344,7 +570,7 @@ mod tests {
 		fs::write(cache_path.join("polkadot_..."), "test").unwrap();
 		fs::create_dir(cache_path.join("worker-prepare-test")).unwrap();
 
-		let artifacts = Artifacts::new(cache_path).await;
+		let artifacts = Artifacts::new(cache_path, None).await;
 
 		let entries: Vec<String> = fs::read_dir(&cache_path)
 			.unwrap()
@@ -370,7 +596,7 @@ mod tests {
 		let artifact_id2 = artifact_id(2);
 		let artifact_id3 = artifact_id(3);
 
-		let mut artifacts = Artifacts::new(cache_path).await;
+		let mut artifacts = Artifacts::new(cache_path, None).await;
 		let cleanup_config = ArtifactsCleanupConfig::new(1500, Duration::from_secs(0));
 
 		artifacts.insert_prepared(
@@ -378,18 +604,21 @@ mod tests {
 			path1.clone(),
 			mock_now - Duration::from_secs(5),
 			1024,
+			String::new(),
 		);
 		artifacts.insert_prepared(
 			artifact_id2.clone(),
 			path2.clone(),
 			mock_now - Duration::from_secs(10),
 			1024,
+			String::new(),
 		);
 		artifacts.insert_prepared(
 			artifact_id3.clone(),
 			path3.clone(),
 			mock_now - Duration::from_secs(15),
 			1024,
+			String::new(),
 		);
 
 		let pruned = artifacts.prune(&cleanup_config);
@@ -415,7 +644,7 @@ mod tests {
 		let artifact_id2 = artifact_id(2);
 		let artifact_id3 = artifact_id(3);
 
-		let mut artifacts = Artifacts::new(cache_path).await;
+		let mut artifacts = Artifacts::new(cache_path, None).await;
 		let cleanup_config = ArtifactsCleanupConfig::new(1500, Duration::from_secs(12));
 
 		artifacts.insert_prepared(
@@ -423,18 +652,21 @@ mod tests {
 			path1.clone(),
 			mock_now - Duration::from_secs(5),
 			1024,
+			String::new(),
 		);
 		artifacts.insert_prepared(
 			artifact_id2.clone(),
 			path2.clone(),
 			mock_now - Duration::from_secs(10),
 			1024,
+			String::new(),
 		);
 		artifacts.insert_prepared(
 			artifact_id3.clone(),
 			path3.clone(),
 			mock_now - Duration::from_secs(15),
 			1024,
+			String::new(),
 		);
 
 		let pruned = artifacts.prune(&cleanup_config);
@@ -446,4 +678,122 @@ mod tests {
 		assert!(!artifacts.artifact_ids().contains(&artifact_id3));
 		assert!(pruned.contains(&(artifact_id3, path3)));
 	}
+
+	#[tokio::test]
+	async fn test_did_not_prune_artifacts_needed_in_active_sessions() {
+		let mock_now = SystemTime::now();
+		let tempdir = tempfile::tempdir().unwrap();
+		let cache_path = tempdir.path();
+
+		let path1 = generate_artifact_path(cache_path);
+		let path2 = generate_artifact_path(cache_path);
+		let path3 = generate_artifact_path(cache_path);
+		let artifact_id1 = artifact_id(1);
+		let artifact_id2 = artifact_id(2);
+		let artifact_id3 = artifact_id(3);
+
+		let mut artifacts = Artifacts::new(cache_path, None).await;
+		let cleanup_config = ArtifactsCleanupConfig::new(1024, Duration::from_secs(0));
+
+		artifacts.insert_prepared(
+			artifact_id1.clone(),
+			path1.clone(),
+			mock_now - Duration::from_secs(5),
+			1024,
+			String::new(),
+		);
+		artifacts.insert_prepared(
+			artifact_id2.clone(),
+			path2.clone(),
+			mock_now - Duration::from_secs(10),
+			1024,
+			String::new(),
+		);
+		artifacts.insert_prepared(
+			artifact_id3.clone(),
+			path3.clone(),
+			mock_now - Duration::from_secs(15),
+			1024,
+			String::new(),
+		);
+
+		// The least recently needed artifact was needed in the previous session.
+		artifacts.note_needed(&artifact_id3);
+		artifacts.note_new_session();
+
+		let pruned = artifacts.prune(&cleanup_config);
+
+		assert_eq!(pruned, vec![(artifact_id2, path2)]);
+		assert!(artifacts.artifact_ids().contains(&artifact_id1));
+		assert!(artifacts.artifact_ids().contains(&artifact_id3));
+
+		// Still over the limit, but not needed anymore after another session change.
+		artifacts.note_new_session();
+		let pruned = artifacts.prune(&cleanup_config);
+
+		assert_eq!(pruned, vec![(artifact_id3, path3)]);
+		assert_eq!(artifacts.artifact_ids(), vec![artifact_id1]);
+	}
+
+	#[tokio::test]
+	async fn artifacts_restored_from_index() {
+		let mock_now = SystemTime::now();
+		let tempdir = tempfile::tempdir().unwrap();
+		let cache_path = tempdir.path();
+		let node_version = Some("1.0.0".to_owned());
+
+		let path1 = generate_artifact_path(cache_path);
+		let path2 = generate_artifact_path(cache_path);
+		let path3 = generate_artifact_path(cache_path);
+		let checksum = blake3::hash(b"artifact").to_hex().to_string();
+		for path in [&path1, &path2, &path3] {
+			fs::write(path, "artifact").unwrap();
+		}
+
+		let mut artifacts = Artifacts::new(cache_path, node_version.clone()).await;
+		artifacts.insert_prepared(artifact_id(1), path1.clone(), mock_now, 8, checksum.clone());
+		artifacts.insert_prepared(artifact_id(2), path2.clone(), mock_now, 8, checksum.clone());
+		artifacts.insert_prepared(artifact_id(3), path3.clone(), mock_now, 8, checksum);
+		artifacts.persist().unwrap();
+
+		// The second artifact got truncated, the third one corrupted without changing its size,
+		// and there is a leftover of an unknown one.
+		fs::write(&path2, "art").unwrap();
+		fs::write(&path3, "artefact").unwrap();
+		fs::write(cache_path.join("abcd.pvf"), "test").unwrap();
+
+		let mut artifacts = Artifacts::new(cache_path, node_version).await;
+
+		assert_eq!(artifacts.artifact_ids(), vec![artifact_id(1)]);
+		assert_eq!(artifacts.total_size(), 8);
+		assert_matches!(
+			artifacts.artifact_state_mut(&artifact_id(1)),
+			Some(ArtifactState::Prepared { path, last_time_needed, .. })
+				if *path == path1 &&
+					mock_now.duration_since(*last_time_needed).unwrap() < Duration::from_secs(1)
+		);
+		assert!(path1.exists());
+		assert!(!path2.exists());
+		assert!(!path3.exists());
+		assert!(!cache_path.join("abcd.pvf").exists());
+	}
+
+	#[tokio::test]
+	async fn artifacts_not_restored_for_another_node_version() {
+		let tempdir = tempfile::tempdir().unwrap();
+		let cache_path = tempdir.path();
+
+		let path = generate_artifact_path(cache_path);
+		fs::write(&path, "artifact").unwrap();
+
+		let mut artifacts = Artifacts::new(cache_path, Some("1.0.0".to_owned())).await;
+		let checksum = blake3::hash(b"artifact").to_hex().to_string();
+		artifacts.insert_prepared(artifact_id(1), path.clone(), SystemTime::now(), 8, checksum);
+		artifacts.persist().unwrap();
+
+		let artifacts = Artifacts::new(cache_path, Some("1.0.1".to_owned())).await;
+
+		assert_eq!(artifacts.len(), 0);
+		assert!(!path.exists());
+	}
 }
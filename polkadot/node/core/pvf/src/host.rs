@@ -68,6 +68,10 @@ pub const EXECUTE_BINARY_NAME: &str = "polkadot-execute-worker";
 /// The size of incoming message queue
 pub const HOST_MESSAGE_QUEUE_SIZE: usize = 10;
 
+/// The default total size of the prepared artifacts, in bytes, above which the least recently
+/// needed ones are pruned.
+pub const DEFAULT_ARTIFACTS_CACHE_SIZE_LIMIT: u64 = 10 * 1024 * 1024 * 1024;
+
 /// An alias to not spell the type for the oneshot sender for the PVF execution result.
 pub(crate) type ResultSender = oneshot::Sender<Result<ValidationResult, ValidationError>>;
 
@@ -146,6 +150,18 @@ impl ValidationHost {
 			.map_err(|_| "the inner loop hung up".to_string())
 	}
 
+	/// Sends a signal to the validation host that a new session started.
+	///
+	/// Artifacts needed in the current or the previous session are never pruned.
+	///
+	/// Returns an error if the request cannot be sent to the validation host, i.e. if it shut down.
+	pub async fn note_new_session(&mut self) -> Result<(), String> {
+		self.to_host_tx
+			.send(ToHost::NewSession)
+			.await
+			.map_err(|_| "the inner loop hung up".to_string())
+	}
+
 	/// Sends a signal to the validation host requesting to update best block.
 	///
 	/// Returns an error if the request cannot be sent to the validation host, i.e. if it shut down.
@@ -165,6 +181,7 @@ enum ToHost {
 	PrecheckPvf { pvf: PvfPrepData, result_tx: PrecheckResultSender },
 	ExecutePvf(ExecutePvfInputs),
 	HeadsUp { active_pvfs: Vec<PvfPrepData> },
+	NewSession,
 	UpdateActiveLeaves { update: ActiveLeavesUpdate, ancestors: Vec<Hash> },
 }
 
@@ -187,6 +204,9 @@ pub struct Config {
 	pub node_version: Option<String>,
 	/// Whether the node is attempting to run as a secure validator.
 	pub secure_validator_mode: bool,
+	/// The total size of the prepared artifacts, in bytes, above which the least recently needed
+	/// ones are pruned.
+	pub artifacts_cache_size_limit: u64,
 
 	/// The path to the program that can be used to spawn the prepare workers.
 	pub prepare_worker_program_path: PathBuf,
@@ -222,6 +242,7 @@ impl Config {
 			cache_path,
 			node_version,
 			secure_validator_mode,
+			artifacts_cache_size_limit: DEFAULT_ARTIFACTS_CACHE_SIZE_LIMIT,
 
 			prepare_worker_program_path,
 			prepare_worker_spawn_timeout: Duration::from_secs(3),
@@ -250,7 +271,8 @@ pub async fn start(
 	gum::debug!(target: LOG_TARGET, ?config, "starting PVF validation host");
 
 	// Make sure the cache is initialized before doing anything else.
-	let artifacts = Artifacts::new(&config.cache_path).await;
+	let artifacts = Artifacts::new(&config.cache_path, config.node_version.clone()).await;
+	metrics.observe_artifacts_cache_size(artifacts.total_size());
 
 	// Run checks for supported security features once per host startup. If some checks fail, warn
 	// if Secure Validator Mode is disabled and return an error otherwise.
@@ -304,7 +326,7 @@ pub async fn start(
 	);
 
 	let (to_execute_queue_tx, from_execute_queue_rx, run_execute_queue) = execute::start(
-		metrics,
+		metrics.clone(),
 		config.execute_worker_program_path.to_owned(),
 		config.cache_path.clone(),
 		config.execute_workers_max_num,
@@ -319,8 +341,11 @@ pub async fn start(
 	let run_host = async move {
 		run(Inner {
 			cleanup_pulse_interval: Duration::from_secs(3600),
-			cleanup_config: ArtifactsCleanupConfig::default(),
+			cleanup_config: ArtifactsCleanupConfig::with_cache_limit(
+				config.artifacts_cache_size_limit,
+			),
 			artifacts,
+			metrics,
 			to_host_rx,
 			to_prepare_queue_tx,
 			from_prepare_queue_rx,
@@ -365,6 +390,7 @@ struct Inner {
 	cleanup_pulse_interval: Duration,
 	cleanup_config: ArtifactsCleanupConfig,
 	artifacts: Artifacts,
+	metrics: Metrics,
 
 	to_host_rx: mpsc::Receiver<ToHost>,
 
@@ -387,6 +413,7 @@ async fn run(
 		cleanup_pulse_interval,
 		cleanup_config,
 		mut artifacts,
+		metrics,
 		to_host_rx,
 		from_prepare_queue_rx,
 		mut to_prepare_queue_tx,
@@ -442,6 +469,7 @@ async fn run(
 					&mut to_sweeper_tx,
 					&mut artifacts,
 					&cleanup_config,
+					&metrics,
 				).await);
 			},
 			to_host = to_host_rx.next() => {
@@ -458,6 +486,7 @@ async fn run(
 				// the preparation failure cooldown has elapsed.
 				break_if_fatal!(handle_to_host(
 					&mut artifacts,
+					&metrics,
 					&mut to_prepare_queue_tx,
 					&mut to_execute_queue_tx,
 					&mut awaiting_prepare,
@@ -483,13 +512,24 @@ async fn run(
 					&mut awaiting_prepare,
 					from_queue,
 				).await);
+
+				// The new artifact may have pushed the cache over its size limit.
+				break_if_fatal!(handle_cleanup_pulse(
+					&mut to_sweeper_tx,
+					&mut artifacts,
+					&cleanup_config,
+					&metrics,
+				).await);
 			},
 		}
 	}
+
+	persist_artifacts(&artifacts);
 }
 
 async fn handle_to_host(
 	artifacts: &mut Artifacts,
+	metrics: &Metrics,
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
 	execute_queue: &mut mpsc::Sender<execute::ToQueue>,
 	awaiting_prepare: &mut AwaitingPrepare,
@@ -500,11 +540,19 @@ async fn handle_to_host(
 			handle_precheck_pvf(artifacts, prepare_queue, pvf, result_tx).await?;
 		},
 		ToHost::ExecutePvf(inputs) => {
-			handle_execute_pvf(artifacts, prepare_queue, execute_queue, awaiting_prepare, inputs)
-				.await?;
+			handle_execute_pvf(
+				artifacts,
+				metrics,
+				prepare_queue,
+				execute_queue,
+				awaiting_prepare,
+				inputs,
+			)
+			.await?;
 		},
 		ToHost::HeadsUp { active_pvfs } =>
 			handle_heads_up(artifacts, prepare_queue, active_pvfs).await?,
+		ToHost::NewSession => artifacts.note_new_session(),
 		ToHost::UpdateActiveLeaves { update, ancestors } =>
 			handle_update_active_leaves(execute_queue, update, ancestors).await?,
 	}
@@ -526,6 +574,7 @@ async fn handle_precheck_pvf(
 	result_sender: PrecheckResultSender,
 ) -> Result<(), Fatal> {
 	let artifact_id = ArtifactId::from_pvf_prep_data(&pvf);
+	artifacts.note_needed(&artifact_id);
 
 	if let Some(state) = artifacts.artifact_state_mut(&artifact_id) {
 		match state {
@@ -562,6 +611,7 @@ async fn handle_precheck_pvf(
 /// than when prechecking.
 async fn handle_execute_pvf(
 	artifacts: &mut Artifacts,
+	metrics: &Metrics,
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
 	execute_queue: &mut mpsc::Sender<execute::ToQueue>,
 	awaiting_prepare: &mut AwaitingPrepare,
@@ -570,6 +620,7 @@ async fn handle_execute_pvf(
 	let ExecutePvfInputs { pvf, exec_timeout, pvd, pov, priority, exec_kind, result_tx } = inputs;
 	let artifact_id = ArtifactId::from_pvf_prep_data(&pvf);
 	let executor_params = (*pvf.executor_params()).clone();
+	artifacts.note_needed(&artifact_id);
 
 	if let Some(state) = artifacts.artifact_state_mut(&artifact_id) {
 		match state {
@@ -578,6 +629,7 @@ async fn handle_execute_pvf(
 
 				if file_metadata.is_ok() {
 					*last_time_needed = SystemTime::now();
+					metrics.on_artifacts_cache_hit();
 
 					// This artifact has already been prepared, send it to the execute queue.
 					send_execute(
@@ -605,6 +657,7 @@ async fn handle_execute_pvf(
 
 					// The artifact has been prepared previously but the file is missing, prepare it
 					// again.
+					metrics.on_artifacts_cache_miss();
 					*state = ArtifactState::Preparing {
 						waiting_for_response: Vec::new(),
 						num_failures: 0,
@@ -628,6 +681,7 @@ async fn handle_execute_pvf(
 				}
 			},
 			ArtifactState::Preparing { .. } => {
+				metrics.on_artifacts_cache_miss();
 				awaiting_prepare.add(
 					artifact_id,
 					PendingExecutionRequest {
@@ -641,6 +695,7 @@ async fn handle_execute_pvf(
 				);
 			},
 			ArtifactState::FailedToProcess { last_time_failed, num_failures, error } => {
+				metrics.on_artifacts_cache_miss();
 				if can_retry_prepare_after_failure(*last_time_failed, *num_failures, error) {
 					gum::warn!(
 						target: LOG_TARGET,
@@ -682,6 +737,7 @@ async fn handle_execute_pvf(
 	} else {
 		// Artifact is unknown: register it and enqueue a job with the corresponding priority and
 		// PVF.
+		metrics.on_artifacts_cache_miss();
 		artifacts.insert_preparing(artifact_id.clone(), Vec::new());
 		enqueue_prepare_for_execute(
 			prepare_queue,
@@ -713,6 +769,7 @@ async fn handle_heads_up(
 
 	for active_pvf in active_pvfs {
 		let artifact_id = ArtifactId::from_pvf_prep_data(&active_pvf);
+		artifacts.note_needed(&artifact_id);
 		if let Some(state) = artifacts.artifact_state_mut(&artifact_id) {
 			match state {
 				ArtifactState::Prepared { last_time_needed, .. } => {
@@ -853,8 +910,8 @@ async fn handle_prepare_done(
 	}
 
 	*state = match result {
-		Ok(PrepareSuccess { path, size, .. }) =>
-			ArtifactState::Prepared { path, last_time_needed: SystemTime::now(), size },
+		Ok(PrepareSuccess { path, size, checksum, .. }) =>
+			ArtifactState::Prepared { path, last_time_needed: SystemTime::now(), size, checksum },
 		Err(error) => {
 			let last_time_failed = SystemTime::now();
 			let num_failures = *num_failures + 1;
@@ -918,6 +975,7 @@ async fn handle_cleanup_pulse(
 	sweeper_tx: &mut mpsc::Sender<PathBuf>,
 	artifacts: &mut Artifacts,
 	cleanup_config: &ArtifactsCleanupConfig,
+	metrics: &Metrics,
 ) -> Result<(), Fatal> {
 	let to_remove = artifacts.prune(cleanup_config);
 	gum::debug!(
@@ -925,6 +983,9 @@ async fn handle_cleanup_pulse(
 		"PVF pruning: {} artifacts reached their end of life",
 		to_remove.len(),
 	);
+	metrics.on_artifacts_pruned(to_remove.len());
+	metrics.observe_artifacts_cache_size(artifacts.total_size());
+	persist_artifacts(artifacts);
 	for (artifact_id, path) in to_remove {
 		gum::debug!(
 			target: LOG_TARGET,
@@ -937,6 +998,14 @@ async fn handle_cleanup_pulse(
 	Ok(())
 }
 
+/// Persist the artifacts index. Failing to do so only means the artifacts will be prepared again
+/// after a restart, so errors are just logged.
+fn persist_artifacts(artifacts: &Artifacts) {
+	if let Err(err) = artifacts.persist() {
+		gum::warn!(target: LOG_TARGET, ?err, "failed to persist the PVF artifacts index");
+	}
+}
+
 async fn handle_artifact_removal(
 	sweeper_tx: &mut mpsc::Sender<PathBuf>,
 	artifacts: &mut Artifacts,
@@ -1087,6 +1156,7 @@ pub(crate) mod tests {
 				cleanup_pulse_interval,
 				cleanup_config,
 				artifacts,
+				metrics: Metrics::default(),
 				to_host_rx,
 				to_prepare_queue_tx,
 				from_prepare_queue_rx,
@@ -1239,8 +1309,8 @@ pub(crate) mod tests {
 		builder.cleanup_config = ArtifactsCleanupConfig::new(1024, Duration::from_secs(0));
 		let path1 = generate_artifact_path(cache_path);
 		let path2 = generate_artifact_path(cache_path);
-		builder.artifacts.insert_prepared(artifact_id(1), path1.clone(), mock_now, 1024);
-		builder.artifacts.insert_prepared(artifact_id(2), path2.clone(), mock_now, 1024);
+		builder.artifacts.insert_prepared(artifact_id(1), path1.clone(), mock_now, 1024, String::new());
+		builder.artifacts.insert_prepared(artifact_id(2), path2.clone(), mock_now, 1024, String::new());
 		let mut test = builder.build();
 		let mut host = test.host_handle();
 
@@ -106,8 +106,8 @@ pub mod testing;
 
 pub use error::{InvalidCandidate, PossiblyInvalidError, ValidationError};
 pub use host::{
-	start, Config, ValidationHost, EXECUTE_BINARY_NAME, HOST_MESSAGE_QUEUE_SIZE,
-	PREPARE_BINARY_NAME,
+	start, Config, ValidationHost, DEFAULT_ARTIFACTS_CACHE_SIZE_LIMIT, EXECUTE_BINARY_NAME,
+	HOST_MESSAGE_QUEUE_SIZE, PREPARE_BINARY_NAME,
 };
 pub use metrics::Metrics;
 pub use priority::Priority;
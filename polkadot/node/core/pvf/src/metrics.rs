@@ -122,6 +122,34 @@ impl Metrics {
 		}
 	}
 
+	/// Observe the total size of the prepared artifacts.
+	pub(crate) fn observe_artifacts_cache_size(&self, size: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics.artifacts_cache_size.set(size);
+		}
+	}
+
+	/// When an execution request found its artifact prepared.
+	pub(crate) fn on_artifacts_cache_hit(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.artifacts_cache_lookups.with_label_values(&["hit"]).inc();
+		}
+	}
+
+	/// When an execution request had to wait for its artifact to be prepared.
+	pub(crate) fn on_artifacts_cache_miss(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.artifacts_cache_lookups.with_label_values(&["miss"]).inc();
+		}
+	}
+
+	/// When artifacts were pruned from the cache.
+	pub(crate) fn on_artifacts_pruned(&self, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.artifacts_pruned.inc_by(count as u64);
+		}
+	}
+
 	/// When preparation pipeline concluded working on an item.
 	pub(crate) fn on_execute_kind(&self, kind: PvfExecKind) {
 		if let Some(metrics) = &self.0 {
@@ -155,6 +183,9 @@ struct MetricsInner {
 	pov_size: prometheus::HistogramVec,
 	code_size: prometheus::Histogram,
 	exec_kind_selected: prometheus::CounterVec<prometheus::U64>,
+	artifacts_cache_size: prometheus::Gauge<prometheus::U64>,
+	artifacts_cache_lookups: prometheus::CounterVec<prometheus::U64>,
+	artifacts_pruned: prometheus::Counter<prometheus::U64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -388,6 +419,30 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			artifacts_cache_size: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_pvf_artifacts_cache_size",
+					"The total size of the prepared PVF artifacts in bytes",
+				)?,
+				registry,
+			)?,
+			artifacts_cache_lookups: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_artifacts_cache_lookups",
+						"The total number of execution requests which found their artifact prepared or not",
+					),
+					&["result"],
+				)?,
+				registry,
+			)?,
+			artifacts_pruned: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_pvf_artifacts_pruned",
+					"The total number of artifacts pruned from the cache",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(inner)))
 	}
@@ -47,6 +47,16 @@ where
 		Some(msg)
 	}
 
+	/// Like `intercept_incoming`, but allows to replace a message by multiple
+	/// messages, which are received by the subsystem in order.
+	fn intercept_incoming_many(
+		&self,
+		sender: &mut Sender,
+		msg: FromOrchestra<Self::Message>,
+	) -> Vec<FromOrchestra<Self::Message>> {
+		self.intercept_incoming(sender, msg).into_iter().collect()
+	}
+
 	/// Specifies if we need to replace some outgoing message with another (potentially empty)
 	/// message
 	fn need_intercept_outgoing(
@@ -231,15 +241,19 @@ where
 	type Signal = OverseerSignal;
 
 	async fn try_recv(&mut self) -> Result<Option<FromOrchestra<Self::Message>>, ()> {
+		if let Some(msg) = self.message_buffer.pop_front() {
+			return Ok(Some(msg))
+		}
 		loop {
 			match self.inner.try_recv().await? {
 				None => return Ok(None),
-				Some(msg) =>
-					if let Some(msg) =
-						self.message_filter.intercept_incoming(self.inner.sender(), msg)
-					{
+				Some(msg) => {
+					let msgs = self.message_filter.intercept_incoming_many(self.inner.sender(), msg);
+					self.message_buffer.extend(msgs);
+					if let Some(msg) = self.message_buffer.pop_front() {
 						return Ok(Some(msg))
-					},
+					}
+				},
 			}
 		}
 	}
@@ -250,7 +264,9 @@ where
 		}
 		loop {
 			let msg = self.inner.recv().await?;
-			if let Some(msg) = self.message_filter.intercept_incoming(self.inner.sender(), msg) {
+			let msgs = self.message_filter.intercept_incoming_many(self.inner.sender(), msg);
+			self.message_buffer.extend(msgs);
+			if let Some(msg) = self.message_buffer.pop_front() {
 				return Ok(msg)
 			}
 		}
@@ -259,13 +275,16 @@ where
 	async fn recv_signal(&mut self) -> SubsystemResult<Self::Signal> {
 		loop {
 			let msg = self.inner.recv().await?;
-			if let Some(msg) = self.message_filter.intercept_incoming(self.inner.sender(), msg) {
-				if let FromOrchestra::Signal(sig) = msg {
-					return Ok(sig)
-				} else {
-					self.message_buffer.push_back(msg)
+			let mut signal = None;
+			for msg in self.message_filter.intercept_incoming_many(self.inner.sender(), msg) {
+				match msg {
+					FromOrchestra::Signal(sig) if signal.is_none() => signal = Some(sig),
+					msg => self.message_buffer.push_back(msg),
 				}
 			}
+			if let Some(sig) = signal {
+				return Ok(sig)
+			}
 		}
 	}
 
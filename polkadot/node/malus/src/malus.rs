@@ -42,6 +42,12 @@ enum NemesisVariant {
 	DisputeFinalizedCandidates(DisputeFinalizedCandidatesOptions),
 	/// Spam many request statements instead of sending a single one.
 	SpamStatementRequests(SpamStatementRequestsOptions),
+	/// Do not show up for approval checks, or show up late.
+	ApprovalNoShow(ApprovalNoShowOptions),
+	/// Withhold availability chunks and available data from other validators.
+	WithholdChunks(WithholdChunksOptions),
+	/// Distribute equivocating approval assignments.
+	EquivocateAssignments(EquivocateAssignmentsOptions),
 }
 
 #[derive(Debug, Parser)]
@@ -105,6 +111,25 @@ impl MalusCli {
 
 				polkadot_cli::run_node(cli, SpamStatementRequests { spam_factor }, finality_delay)?
 			},
+			NemesisVariant::ApprovalNoShow(opts) => {
+				let ApprovalNoShowOptions { percentage, approval_delay, cli } = opts;
+
+				polkadot_cli::run_node(
+					cli,
+					ApprovalNoShow { percentage, approval_delay },
+					finality_delay,
+				)?
+			},
+			NemesisVariant::WithholdChunks(opts) => {
+				let WithholdChunksOptions { percentage, cli } = opts;
+
+				polkadot_cli::run_node(cli, WithholdChunks { percentage }, finality_delay)?
+			},
+			NemesisVariant::EquivocateAssignments(opts) => {
+				let EquivocateAssignmentsOptions { percentage, cli } = opts;
+
+				polkadot_cli::run_node(cli, EquivocateAssignments { percentage }, finality_delay)?
+			},
 		}
 		Ok(())
 	}
@@ -244,4 +269,62 @@ mod tests {
 			assert!(opts.cli.run.base.bob);
 		});
 	}
+
+	#[test]
+	fn approval_no_show_delay_works() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"approval-no-show",
+			"--percentage",
+			"50",
+			"--approval-delay",
+			"6000",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::ApprovalNoShow(opts),
+			..
+		} => {
+			assert_eq!(opts.percentage, 50);
+			assert_eq!(opts.approval_delay, Some(6000));
+			assert!(opts.cli.run.base.bob);
+		});
+	}
+
+	#[test]
+	fn percentage_works_withhold_chunks() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"withhold-chunks",
+			"--percentage",
+			"30",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::WithholdChunks(opts),
+			..
+		} => {
+			assert_eq!(opts.percentage, 30);
+			assert!(opts.cli.run.base.bob);
+		});
+	}
+
+	#[test]
+	fn equivocate_assignments_works() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"equivocate-assignments",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::EquivocateAssignments(opts),
+			..
+		} => {
+			assert_eq!(opts.percentage, 100);
+			assert!(opts.cli.run.base.bob);
+		});
+	}
 }
@@ -0,0 +1,189 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node variant that does not show up for its approval checks, or shows up late.
+//!
+//! This malus variant behaves honestly in everything except when approval voting asks candidate
+//! validation to check a candidate it is assigned to. For a configurable percentage of those
+//! checks, the validation result is either never returned, so that no approval is ever issued
+//! (a no-show), or returned only after a delay, so that the approval is issued late.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use futures::channel::oneshot;
+use polkadot_cli::{
+	service::{
+		AuxStore, Error, ExtendedOverseerGenArgs, Overseer, OverseerConnector, OverseerGen,
+		OverseerGenArgs, OverseerHandle,
+	},
+	validator_overseer_builder, Cli,
+};
+use polkadot_node_subsystem::SpawnGlue;
+use polkadot_node_subsystem_types::{ChainApiBackend, RuntimeApiSubsystemClient};
+use rand::distributions::{Bernoulli, Distribution};
+use sp_core::traits::SpawnNamed;
+
+// Filter wrapping related types.
+use crate::{interceptor::*, shared::MALUS};
+
+use std::{sync::Arc, time::Duration};
+
+/// Wraps around the candidate validation subsystem and stalls approval checks.
+#[derive(Clone)]
+struct ApprovalStaller<Spawner> {
+	spawner: Spawner,
+	distribution: Bernoulli,
+	/// `None` to never return the validation result.
+	approval_delay: Option<Duration>,
+}
+
+impl<Sender, Spawner> MessageInterceptor<Sender> for ApprovalStaller<Spawner>
+where
+	Sender: overseer::CandidateValidationSenderTrait + Clone + Send + 'static,
+	Spawner: overseer::gen::Spawner + Clone + 'static,
+{
+	type Message = CandidateValidationMessage;
+
+	/// Intercept `CandidateValidationMessage::ValidateFromExhaustive` sent for approval checks and
+	/// pass the rest as normal.
+	fn intercept_incoming(
+		&self,
+		_subsystem_sender: &mut Sender,
+		msg: FromOrchestra<Self::Message>,
+	) -> Option<FromOrchestra<Self::Message>> {
+		match msg {
+			FromOrchestra::Communication {
+				msg:
+					CandidateValidationMessage::ValidateFromExhaustive {
+						validation_data,
+						validation_code,
+						candidate_receipt,
+						pov,
+						executor_params,
+						exec_kind: PvfExecKind::Approval,
+						response_sender,
+					},
+			} if self.distribution.sample(&mut rand::thread_rng()) => {
+				let candidate_hash = candidate_receipt.hash();
+				let Some(approval_delay) = self.approval_delay else {
+					gum::info!(
+						target: MALUS,
+						?candidate_hash,
+						"😈 Not showing up for the approval check.",
+					);
+					// Dropping the sender makes approval voting give up on the candidate.
+					drop(response_sender);
+					return None
+				};
+
+				gum::info!(
+					target: MALUS,
+					?candidate_hash,
+					?approval_delay,
+					"😈 Delaying the approval check.",
+				);
+
+				let (tx, rx) = oneshot::channel();
+				self.spawner.spawn(
+					"malus-delay-approval-check",
+					Some("malus"),
+					Box::pin(async move {
+						let Ok(result) = rx.await else { return };
+						futures_timer::Delay::new(approval_delay).await;
+						let _ = response_sender.send(result);
+					}),
+				);
+
+				Some(FromOrchestra::Communication {
+					msg: CandidateValidationMessage::ValidateFromExhaustive {
+						validation_data,
+						validation_code,
+						candidate_receipt,
+						pov,
+						executor_params,
+						exec_kind: PvfExecKind::Approval,
+						response_sender: tx,
+					},
+				})
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+//----------------------------------------------------------------------------------
+
+#[derive(Debug, clap::Parser)]
+#[clap(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct ApprovalNoShowOptions {
+	/// Determines the percentage of approval checks the node does not show up for, or shows up
+	/// late for. Must be in range [0..=100].
+	#[clap(short, long, ignore_case = true, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub percentage: u8,
+
+	/// Issue the approval this many milliseconds late instead of never issuing it.
+	#[clap(long)]
+	pub approval_delay: Option<u64>,
+
+	#[clap(flatten)]
+	pub cli: Cli,
+}
+
+/// ApprovalNoShow implementation wrapper which implements `OverseerGen` glue.
+pub(crate) struct ApprovalNoShow {
+	/// Percentage of the approval checks to stall.
+	pub percentage: u8,
+	/// Delay of the approvals in milliseconds, or `None` to never issue them.
+	pub approval_delay: Option<u64>,
+}
+
+impl OverseerGen for ApprovalNoShow {
+	fn generate<Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'_, Spawner, RuntimeClient>,
+		ext_args: Option<ExtendedOverseerGenArgs>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: RuntimeApiSubsystemClient + ChainApiBackend + AuxStore + 'static,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		gum::info!(
+			target: MALUS,
+			"😈 Started Malus node that stalls {}% of its approval checks with delay = {:?} ms.",
+			&self.percentage,
+			&self.approval_delay,
+		);
+
+		let approval_staller = ApprovalStaller {
+			spawner: SpawnGlue(args.spawner.clone()),
+			distribution: Bernoulli::new(self.percentage as f64 / 100.0)
+				.expect("Invalid probability! Percentage must be in range [0..=100]."),
+			approval_delay: self.approval_delay.map(Duration::from_millis),
+		};
+
+		validator_overseer_builder(
+			args,
+			ext_args.expect("Extended arguments required to build validator overseer are provided"),
+		)?
+		.replace_candidate_validation(move |cv| InterceptedSubsystem::new(cv, approval_staller))
+		.build_with_connector(connector)
+		.map_err(|e| e.into())
+	}
+}
@@ -0,0 +1,154 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node variant that equivocates its approval assignments.
+//!
+//! This malus variant behaves honestly in everything except when distributing its own approval
+//! assignments. For a configurable percentage of them, a second assignment with the same
+//! certificate is distributed, claiming a neighbouring candidate of the same block instead. The
+//! certificate does not cover the neighbouring candidate, so honest peers are expected to reject
+//! the equivocation and lower the reputation of the node.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	service::{
+		AuxStore, Error, ExtendedOverseerGenArgs, Overseer, OverseerConnector, OverseerGen,
+		OverseerGenArgs, OverseerHandle,
+	},
+	validator_overseer_builder, Cli,
+};
+use polkadot_node_primitives::approval::v2::CandidateBitfield;
+use polkadot_node_subsystem::SpawnGlue;
+use polkadot_node_subsystem_types::{ChainApiBackend, RuntimeApiSubsystemClient};
+use polkadot_primitives::CandidateIndex;
+use rand::distributions::{Bernoulli, Distribution};
+use sp_core::traits::SpawnNamed;
+
+// Filter wrapping related types.
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Wraps around the approval distribution subsystem and equivocates our assignments.
+#[derive(Clone)]
+struct AssignmentEquivocator {
+	distribution: Bernoulli,
+}
+
+impl<Sender> MessageInterceptor<Sender> for AssignmentEquivocator
+where
+	Sender: overseer::ApprovalDistributionSenderTrait + Clone + Send + 'static,
+{
+	type Message = ApprovalDistributionMessage;
+
+	/// Intercept `ApprovalDistributionMessage::DistributeAssignment` and add an equivocating
+	/// assignment after it. Pass the rest as normal.
+	fn intercept_incoming_many(
+		&self,
+		_subsystem_sender: &mut Sender,
+		msg: FromOrchestra<Self::Message>,
+	) -> Vec<FromOrchestra<Self::Message>> {
+		match msg {
+			FromOrchestra::Communication {
+				msg: ApprovalDistributionMessage::DistributeAssignment(cert, candidate_indices),
+			} if self.distribution.sample(&mut rand::thread_rng()) => {
+				// Claim the neighbour of the first claimed candidate instead.
+				let equivocating_indices = candidate_indices
+					.first_one()
+					.map(|index| CandidateBitfield::from((index ^ 1) as CandidateIndex));
+
+				gum::info!(
+					target: MALUS,
+					block_hash = ?cert.block_hash,
+					?candidate_indices,
+					?equivocating_indices,
+					"😈 Equivocating approval assignment.",
+				);
+
+				let equivocation = equivocating_indices.map(|indices| {
+					let msg =
+						ApprovalDistributionMessage::DistributeAssignment(cert.clone(), indices);
+					FromOrchestra::Communication { msg }
+				});
+
+				std::iter::once(FromOrchestra::Communication {
+					msg: ApprovalDistributionMessage::DistributeAssignment(cert, candidate_indices),
+				})
+				.chain(equivocation)
+				.collect()
+			},
+			msg => vec![msg],
+		}
+	}
+}
+
+//----------------------------------------------------------------------------------
+
+#[derive(Debug, clap::Parser)]
+#[clap(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct EquivocateAssignmentsOptions {
+	/// Determines the percentage of our approval assignments to equivocate.
+	/// Must be in range [0..=100].
+	#[clap(short, long, ignore_case = true, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub percentage: u8,
+
+	#[clap(flatten)]
+	pub cli: Cli,
+}
+
+/// EquivocateAssignments implementation wrapper which implements `OverseerGen` glue.
+pub(crate) struct EquivocateAssignments {
+	/// Percentage of our approval assignments to equivocate.
+	pub percentage: u8,
+}
+
+impl OverseerGen for EquivocateAssignments {
+	fn generate<Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'_, Spawner, RuntimeClient>,
+		ext_args: Option<ExtendedOverseerGenArgs>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: RuntimeApiSubsystemClient + ChainApiBackend + AuxStore + 'static,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		gum::info!(
+			target: MALUS,
+			"😈 Started Malus node that equivocates {}% of its approval assignments.",
+			&self.percentage,
+		);
+
+		let assignment_equivocator = AssignmentEquivocator {
+			distribution: Bernoulli::new(self.percentage as f64 / 100.0)
+				.expect("Invalid probability! Percentage must be in range [0..=100]."),
+		};
+
+		validator_overseer_builder(
+			args,
+			ext_args.expect("Extended arguments required to build validator overseer are provided"),
+		)?
+		.replace_approval_distribution(move |ad| {
+			InterceptedSubsystem::new(ad, assignment_equivocator)
+		})
+		.build_with_connector(connector)
+		.map_err(|e| e.into())
+	}
+}
@@ -16,20 +16,26 @@
 
 //! Collection of behavior variants.
 
+mod approval_no_show;
 mod back_garbage_candidate;
 mod common;
 mod dispute_finalized_candidates;
 mod dispute_valid_candidates;
+mod equivocate_assignments;
 mod spam_statement_requests;
 mod suggest_garbage_candidate;
 mod support_disabled;
+mod withhold_chunks;
 
 pub(crate) use self::{
+	approval_no_show::{ApprovalNoShow, ApprovalNoShowOptions},
 	back_garbage_candidate::{BackGarbageCandidateOptions, BackGarbageCandidates},
 	dispute_finalized_candidates::{DisputeFinalizedCandidates, DisputeFinalizedCandidatesOptions},
 	dispute_valid_candidates::{DisputeAncestorOptions, DisputeValidCandidates},
+	equivocate_assignments::{EquivocateAssignments, EquivocateAssignmentsOptions},
 	spam_statement_requests::{SpamStatementRequests, SpamStatementRequestsOptions},
 	suggest_garbage_candidate::{SuggestGarbageCandidateOptions, SuggestGarbageCandidates},
 	support_disabled::{SupportDisabled, SupportDisabledOptions},
+	withhold_chunks::{WithholdChunks, WithholdChunksOptions},
 };
 pub(crate) use common::*;
@@ -19,6 +19,7 @@
 #![warn(missing_docs)]
 
 use std::{
+	cmp::Reverse,
 	collections::{BTreeMap, VecDeque},
 	iter::Iterator,
 	num::NonZeroUsize,
@@ -38,7 +39,7 @@ use sc_network::ProtocolName;
 use schnellru::{ByLength, LruMap};
 use task::{
 	FetchChunks, FetchChunksParams, FetchFull, FetchFullParams, FetchSystematicChunks,
-	FetchSystematicChunksParams,
+	FetchSystematicChunksParams, Race,
 };
 
 use polkadot_erasure_coding::{
@@ -48,6 +49,7 @@ use polkadot_erasure_coding::{
 use task::{RecoveryParams, RecoveryStrategy, RecoveryTask};
 
 use error::{log_error, Error, FatalError, Result};
+use peer_stats::PeerStats;
 use polkadot_node_network_protocol::{
 	request_response::{
 		v1 as request_v1, v2 as request_v2, IncomingRequestReceiver, IsRequest, ReqProtocolNames,
@@ -66,13 +68,15 @@ use polkadot_node_subsystem_util::{
 	runtime::{ExtendedSessionInfo, RuntimeInfo},
 };
 use polkadot_primitives::{
-	node_features, vstaging::CandidateReceiptV2 as CandidateReceipt, BlockNumber, CandidateHash,
-	ChunkIndex, CoreIndex, GroupIndex, Hash, SessionIndex, ValidatorIndex,
+	node_features, vstaging::CandidateReceiptV2 as CandidateReceipt, AuthorityDiscoveryId,
+	BlockNumber, CandidateHash, ChunkIndex, CoreIndex, GroupIndex, Hash, SessionIndex,
+	ValidatorIndex,
 };
 
 mod error;
 mod futures_undead;
 mod metrics;
+mod peer_stats;
 mod task;
 pub use metrics::Metrics;
 
@@ -117,6 +121,12 @@ pub enum RecoveryStrategyKind {
 	/// Always recover using systematic chunks, fall back to regular chunks.
 	#[allow(dead_code)]
 	SystematicChunks,
+
+	/// Pick and order the strategies of every candidate based on the latency and failures we
+	/// observed so far from the backers and chunk holders. If the PoV size is not lower than
+	/// specified, fetching from backers races against chunk recovery. Strategies involving
+	/// unreliable backers are only used as a last resort.
+	Adaptive(usize),
 }
 
 /// The Availability Recovery Subsystem.
@@ -299,6 +309,9 @@ struct State {
 
 	/// Cached runtime info.
 	runtime_info: RuntimeInfo,
+
+	/// Latency and failure statistics of the validators we request data from.
+	peer_stats: PeerStats,
 }
 
 impl Default for State {
@@ -308,6 +321,7 @@ impl Default for State {
 			live_block: (0, Hash::default()),
 			availability_lru: LruMap::new(ByLength::new(LRU_SIZE)),
 			runtime_info: RuntimeInfo::new(None),
+			peer_stats: PeerStats::default(),
 		}
 	}
 }
@@ -407,6 +421,7 @@ async fn handle_recover<Context>(
 	match session_info_res {
 		Ok(ExtendedSessionInfo { session_info, node_features, .. }) => {
 			let mut backer_group = None;
+			let mut small_pov_size = true;
 			let n_validators = session_info.validators.len();
 			let systematic_threshold = systematic_recovery_threshold(n_validators)?;
 			let mut recovery_strategies: VecDeque<
@@ -415,13 +430,12 @@ async fn handle_recover<Context>(
 
 			if let Some(backing_group) = backing_group {
 				if let Some(backing_validators) = session_info.validator_groups.get(backing_group) {
-					let mut small_pov_size = true;
-
 					match recovery_strategy_kind {
 						RecoveryStrategyKind::BackersFirstIfSizeLower(fetch_chunks_threshold) |
 						RecoveryStrategyKind::BackersFirstIfSizeLowerThenSystematicChunks(
 							fetch_chunks_threshold,
-						) => {
+						) |
+						RecoveryStrategyKind::Adaptive(fetch_chunks_threshold) => {
 							// Get our own chunk size to get an estimate of the PoV size.
 							let chunk_size: Result<Option<usize>> =
 								query_chunk_size(ctx, candidate_hash).await;
@@ -474,6 +488,8 @@ async fn handle_recover<Context>(
 				false
 			};
 
+			let mut systematic_params = None;
+
 			// We can only attempt systematic recovery if we received the core index of the
 			// candidate and chunk mapping is enabled.
 			if let Some(core_index) = maybe_core_index {
@@ -481,7 +497,8 @@ async fn handle_recover<Context>(
 					recovery_strategy_kind,
 					RecoveryStrategyKind::BackersThenSystematicChunks |
 						RecoveryStrategyKind::SystematicChunks |
-						RecoveryStrategyKind::BackersFirstIfSizeLowerThenSystematicChunks(_)
+						RecoveryStrategyKind::BackersFirstIfSizeLowerThenSystematicChunks(_) |
+						RecoveryStrategyKind::Adaptive(_)
 				) && chunk_mapping_enabled
 				{
 					let chunk_indices =
@@ -512,18 +529,31 @@ async fn handle_recover<Context>(
 						})
 						.collect();
 
-					recovery_strategies.push_back(Box::new(FetchSystematicChunks::new(
-						FetchSystematicChunksParams {
-							validators,
-							backers: backer_group.map(|v| v.to_vec()).unwrap_or_else(|| vec![]),
-						},
-					)));
+					systematic_params = Some(FetchSystematicChunksParams {
+						validators,
+						backers: backer_group.map(|v| v.to_vec()).unwrap_or_else(|| vec![]),
+					});
 				}
 			}
 
-			recovery_strategies.push_back(Box::new(FetchChunks::new(FetchChunksParams {
-				n_validators: session_info.validators.len(),
-			})));
+			let chunks_params = FetchChunksParams { n_validators: session_info.validators.len() };
+
+			if let RecoveryStrategyKind::Adaptive(_) = recovery_strategy_kind {
+				recovery_strategies.extend(adaptive_recovery_strategies(
+					&state.peer_stats,
+					&session_info.discovery_keys,
+					backer_group,
+					systematic_params,
+					chunks_params,
+					small_pov_size,
+				));
+			} else {
+				if let Some(systematic_params) = systematic_params {
+					recovery_strategies
+						.push_back(Box::new(FetchSystematicChunks::new(systematic_params)));
+				}
+				recovery_strategies.push_back(Box::new(FetchChunks::new(chunks_params)));
+			}
 
 			let session_info = session_info.clone();
 
@@ -549,6 +579,7 @@ async fn handle_recover<Context>(
 					req_v2_protocol_name,
 					chunk_mapping_enabled,
 					erasure_task_tx,
+					peer_stats: state.peer_stats.clone(),
 				},
 			)
 			.await
@@ -563,6 +594,62 @@ async fn handle_recover<Context>(
 	}
 }
 
+/// Pick and order the recovery strategies of a candidate for `RecoveryStrategyKind::Adaptive`.
+///
+/// Regular chunk recovery is always included, as it works as long as enough validators are
+/// honest. Fetching from backers goes first if they are expected to be faster than the chunk
+/// holders, or races against chunk recovery for large PoVs. If all backers failed most of their
+/// recent requests, they are only tried after everything else.
+fn adaptive_recovery_strategies<Sender: overseer::AvailabilityRecoverySenderTrait>(
+	peer_stats: &PeerStats,
+	authority_keys: &[AuthorityDiscoveryId],
+	backers: Option<&Vec<ValidatorIndex>>,
+	systematic_params: Option<FetchSystematicChunksParams>,
+	chunks_params: FetchChunksParams,
+	small_pov_size: bool,
+) -> Vec<Box<dyn RecoveryStrategy<Sender>>> {
+	let chunk_holders_latency = match &systematic_params {
+		Some(params) => peer_stats.mean_expected_latency(
+			params.validators.iter().map(|(_, v_index)| &authority_keys[v_index.0 as usize]),
+		),
+		None => peer_stats.mean_expected_latency(authority_keys.iter()),
+	};
+
+	let mut strategies: Vec<Box<dyn RecoveryStrategy<Sender>>> = Vec::with_capacity(3);
+	if let Some(params) = systematic_params {
+		strategies.push(Box::new(FetchSystematicChunks::new(params)));
+	}
+	strategies.push(Box::new(FetchChunks::new(chunks_params)));
+
+	let Some(backers) = backers.filter(|backers| !backers.is_empty()) else { return strategies };
+
+	let mut backers = backers.clone();
+	backers.sort_by_cached_key(|v_index| {
+		(
+			Reverse(peer_stats.is_reliable(&authority_keys[v_index.0 as usize])),
+			peer_stats.expected_latency(&authority_keys[v_index.0 as usize]),
+		)
+	});
+	let fastest_backer = &authority_keys[backers[0].0 as usize];
+	let backers_reliable = peer_stats.is_reliable(fastest_backer);
+	let backers_latency = peer_stats.expected_latency(fastest_backer);
+	let fetch_full =
+		Box::new(FetchFull::with_preferred_order(FetchFullParams { validators: backers }));
+
+	if !backers_reliable {
+		strategies.push(fetch_full);
+	} else if !small_pov_size {
+		let first_chunk_strategy = strategies.remove(0);
+		strategies.insert(0, Box::new(Race::new(fetch_full, first_chunk_strategy)));
+	} else if backers_latency <= chunk_holders_latency {
+		strategies.insert(0, fetch_full);
+	} else {
+		strategies.insert(1, fetch_full);
+	}
+
+	strategies
+}
+
 /// Queries the full `AvailableData` from av-store.
 #[overseer::contextbounds(AvailabilityRecovery, prefix = self::overseer)]
 async fn query_full_data<Context>(
@@ -645,6 +732,24 @@ impl AvailabilityRecoverySubsystem {
 		}
 	}
 
+	/// Pick and order the recovery strategies of every candidate based on the latency and failures
+	/// observed so far, see [`RecoveryStrategyKind::Adaptive`]. Keeps the PoV size threshold
+	/// configured by the constructor. Does nothing unless `enable` is set.
+	pub fn with_adaptive_strategy(mut self, enable: bool) -> Self {
+		if !enable {
+			return self
+		}
+
+		let fetch_chunks_threshold = match self.recovery_strategy_kind {
+			RecoveryStrategyKind::BackersFirstIfSizeLower(threshold) |
+			RecoveryStrategyKind::BackersFirstIfSizeLowerThenSystematicChunks(threshold) |
+			RecoveryStrategyKind::Adaptive(threshold) => threshold,
+			_ => CONSERVATIVE_FETCH_CHUNKS_THRESHOLD,
+		};
+		self.recovery_strategy_kind = RecoveryStrategyKind::Adaptive(fetch_chunks_threshold);
+		self
+	}
+
 	/// Customise the recovery strategy kind
 	/// Currently only useful for tests.
 	#[cfg(any(test, feature = "subsystem-benchmarks"))]
@@ -0,0 +1,229 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Latency and failure statistics of the validators we request availability data from.
+//!
+//! Used by [`crate::RecoveryStrategyKind::Adaptive`] to pick and order the recovery strategies of
+//! a candidate.
+
+use polkadot_node_network_protocol::request_response::CHUNK_REQUEST_TIMEOUT;
+use polkadot_primitives::AuthorityDiscoveryId;
+use schnellru::{ByLength, LruMap};
+use std::{
+	sync::{Arc, Mutex, PoisonError},
+	time::{Duration, Instant},
+};
+
+/// Maximum number of validators we keep statistics for.
+const PEER_STATS_CAPACITY: u32 = 2048;
+
+/// Weight of a new sample in the exponential moving averages.
+const SMOOTHING_FACTOR: f64 = 0.2;
+
+/// Latency assumed for validators we have not requested anything from yet.
+const UNKNOWN_PEER_LATENCY: Duration = Duration::from_millis(500);
+
+/// Time we expect to lose on a failed request before moving on to the next validator.
+const FAILURE_PENALTY: Duration = CHUNK_REQUEST_TIMEOUT;
+
+/// Validators failing more often than this are only used as a last resort.
+const UNRELIABLE_FAILURE_RATE: f64 = 0.5;
+
+/// Records not updated for this long are treated like unknown validators. Otherwise, a validator
+/// which was unreliable once would only ever be tried as a last resort, and never get the chance
+/// to improve its record.
+const PEER_STATS_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+struct PeerRecord {
+	/// Moving average of the latency of successful requests, in seconds.
+	latency: f64,
+	/// Moving average of the share of failed requests.
+	failure_rate: f64,
+	/// When the record was last updated.
+	updated_at: Instant,
+}
+
+impl Default for PeerRecord {
+	fn default() -> Self {
+		Self {
+			latency: UNKNOWN_PEER_LATENCY.as_secs_f64(),
+			failure_rate: 0.0,
+			updated_at: Instant::now(),
+		}
+	}
+}
+
+impl PeerRecord {
+	fn expected_latency(&self) -> Duration {
+		Duration::from_secs_f64(self.latency + self.failure_rate * FAILURE_PENALTY.as_secs_f64())
+	}
+}
+
+fn moving_average(average: f64, sample: f64) -> f64 {
+	average + SMOOTHING_FACTOR * (sample - average)
+}
+
+/// Shared handle to the statistics, cheap to clone into recovery tasks.
+#[derive(Clone)]
+pub struct PeerStats {
+	records: Arc<Mutex<LruMap<AuthorityDiscoveryId, PeerRecord>>>,
+	max_age: Duration,
+}
+
+impl Default for PeerStats {
+	fn default() -> Self {
+		Self::with_max_age(PEER_STATS_MAX_AGE)
+	}
+}
+
+impl PeerStats {
+	/// Create empty statistics, forgetting records which were not updated for `max_age`.
+	pub fn with_max_age(max_age: Duration) -> Self {
+		Self {
+			records: Arc::new(Mutex::new(LruMap::new(ByLength::new(PEER_STATS_CAPACITY)))),
+			max_age,
+		}
+	}
+
+	fn is_stale(&self, record: &PeerRecord) -> bool {
+		record.updated_at.elapsed() > self.max_age
+	}
+
+	fn update(&self, authority_id: &AuthorityDiscoveryId, f: impl FnOnce(&mut PeerRecord)) {
+		// The statistics are only a hint, so it's fine to keep using them after a panic.
+		let mut records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(record) = records.get_or_insert(authority_id.clone(), Default::default) {
+			if self.is_stale(record) {
+				*record = Default::default();
+			}
+			f(record);
+			record.updated_at = Instant::now();
+		}
+	}
+
+	fn read<R>(&self, authority_id: &AuthorityDiscoveryId, f: impl FnOnce(&PeerRecord) -> R) -> R {
+		let records = self.records.lock().unwrap_or_else(PoisonError::into_inner);
+		match records.peek(authority_id) {
+			Some(record) if !self.is_stale(record) => f(record),
+			_ => f(&Default::default()),
+		}
+	}
+
+	/// Record a successful request, answered after `latency`.
+	pub fn on_success(&self, authority_id: &AuthorityDiscoveryId, latency: Duration) {
+		self.update(authority_id, |record| {
+			record.latency = moving_average(record.latency, latency.as_secs_f64());
+			record.failure_rate = moving_average(record.failure_rate, 0.0);
+		})
+	}
+
+	/// Record a request which failed or did not return the data.
+	pub fn on_failure(&self, authority_id: &AuthorityDiscoveryId) {
+		self.update(authority_id, |record| {
+			record.failure_rate = moving_average(record.failure_rate, 1.0);
+		})
+	}
+
+	/// Expected time until the validator successfully answers a request, accounting for failures.
+	pub fn expected_latency(&self, authority_id: &AuthorityDiscoveryId) -> Duration {
+		self.read(authority_id, |record| record.expected_latency())
+	}
+
+	/// Average expected latency of a set of validators.
+	pub fn mean_expected_latency<'a>(
+		&self,
+		authority_ids: impl Iterator<Item = &'a AuthorityDiscoveryId>,
+	) -> Duration {
+		let (total, count) = authority_ids.fold((Duration::ZERO, 0u32), |(total, count), id| {
+			(total + self.expected_latency(id), count + 1)
+		});
+
+		total.checked_div(count).unwrap_or(UNKNOWN_PEER_LATENCY)
+	}
+
+	/// Whether the validator answered most of our recent requests.
+	pub fn is_reliable(&self, authority_id: &AuthorityDiscoveryId) -> bool {
+		self.read(authority_id, |record| record.failure_rate < UNRELIABLE_FAILURE_RATE)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::Sr25519Keyring;
+
+	#[test]
+	fn unknown_peers_use_default_latency() {
+		let peer_stats = PeerStats::default();
+		let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+
+		assert_eq!(peer_stats.expected_latency(&alice), UNKNOWN_PEER_LATENCY);
+		assert!(peer_stats.is_reliable(&alice));
+		assert_eq!(peer_stats.mean_expected_latency(std::iter::empty()), UNKNOWN_PEER_LATENCY);
+	}
+
+	#[test]
+	fn latency_and_failures_are_tracked() {
+		let peer_stats = PeerStats::default();
+		let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+		let bob: AuthorityDiscoveryId = Sr25519Keyring::Bob.public().into();
+		let charlie: AuthorityDiscoveryId = Sr25519Keyring::Charlie.public().into();
+
+		for _ in 0..10 {
+			peer_stats.on_success(&alice, Duration::from_millis(50));
+			peer_stats.on_success(&bob, Duration::from_secs(1));
+		}
+		assert!(peer_stats.expected_latency(&alice) < UNKNOWN_PEER_LATENCY);
+		assert!(peer_stats.expected_latency(&bob) > UNKNOWN_PEER_LATENCY);
+		assert!(peer_stats.is_reliable(&bob));
+
+		// A single failure makes a fast validator look slower, but not unreliable.
+		peer_stats.on_failure(&alice);
+		assert!(peer_stats.expected_latency(&alice) > Duration::from_millis(50));
+		assert!(peer_stats.is_reliable(&alice));
+
+		for _ in 0..5 {
+			peer_stats.on_failure(&charlie);
+		}
+		assert!(!peer_stats.is_reliable(&charlie));
+		assert!(peer_stats.expected_latency(&charlie) > peer_stats.expected_latency(&bob));
+
+		// Recovers after answering again.
+		for _ in 0..5 {
+			peer_stats.on_success(&charlie, Duration::from_millis(50));
+		}
+		assert!(peer_stats.is_reliable(&charlie));
+	}
+
+	#[test]
+	fn stale_records_are_forgotten() {
+		let peer_stats = PeerStats::with_max_age(Duration::from_millis(50));
+		let alice: AuthorityDiscoveryId = Sr25519Keyring::Alice.public().into();
+
+		for _ in 0..5 {
+			peer_stats.on_failure(&alice);
+		}
+		assert!(!peer_stats.is_reliable(&alice));
+
+		std::thread::sleep(Duration::from_millis(100));
+		assert!(peer_stats.is_reliable(&alice));
+		assert_eq!(peer_stats.expected_latency(&alice), UNKNOWN_PEER_LATENCY);
+
+		// A new sample starts from scratch instead of the stale average.
+		peer_stats.on_failure(&alice);
+		assert!(peer_stats.is_reliable(&alice));
+	}
+}
@@ -22,13 +22,13 @@ mod strategy;
 
 pub use self::strategy::{
 	FetchChunks, FetchChunksParams, FetchFull, FetchFullParams, FetchSystematicChunks,
-	FetchSystematicChunksParams, RecoveryStrategy, State,
+	FetchSystematicChunksParams, Race, RecoveryStrategy, State,
 };
 
 #[cfg(test)]
 pub use self::strategy::{REGULAR_CHUNKS_REQ_RETRY_LIMIT, SYSTEMATIC_CHUNKS_REQ_RETRY_LIMIT};
 
-use crate::{metrics::Metrics, ErasureTask, PostRecoveryCheck, LOG_TARGET};
+use crate::{metrics::Metrics, peer_stats::PeerStats, ErasureTask, PostRecoveryCheck, LOG_TARGET};
 
 use codec::Encode;
 use polkadot_node_primitives::AvailableData;
@@ -83,6 +83,9 @@ pub struct RecoveryParams {
 
 	/// Channel to the erasure task handler.
 	pub erasure_task_tx: mpsc::Sender<ErasureTask>,
+
+	/// Latency and failure statistics of the validators we request data from.
+	pub peer_stats: PeerStats,
 }
 
 /// A stateful reconstruction of availability data in reference to
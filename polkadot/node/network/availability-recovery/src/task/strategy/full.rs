@@ -29,6 +29,7 @@ use sc_network::{IfDisconnected, OutboundFailure, RequestFailure};
 
 use futures::{channel::oneshot, SinkExt};
 use rand::seq::SliceRandom;
+use std::time::Instant;
 
 /// Parameters specific to the `FetchFull` strategy.
 pub struct FetchFullParams {
@@ -48,6 +49,14 @@ impl FetchFull {
 		params.validators.shuffle(&mut rand::thread_rng());
 		Self { params }
 	}
+
+	/// Create a new `FetchFull` recovery strategy which tries the validators in the given order,
+	/// instead of a random one.
+	pub fn with_preferred_order(mut params: FetchFullParams) -> Self {
+		// Validators are popped from the back.
+		params.validators.reverse();
+		Self { params }
+	}
 }
 
 #[async_trait::async_trait]
@@ -73,11 +82,12 @@ impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender>
 			let validator_index =
 				self.params.validators.pop().ok_or_else(|| RecoveryError::Unavailable)?;
 
+			let authority_id =
+				common_params.validator_authority_keys[validator_index.0 as usize].clone();
+
 			// Request data.
 			let (req, response) = OutgoingRequest::new(
-				Recipient::Authority(
-					common_params.validator_authority_keys[validator_index.0 as usize].clone(),
-				),
+				Recipient::Authority(authority_id.clone()),
 				req_res::v1::AvailableDataFetchingRequest {
 					candidate_hash: common_params.candidate_hash,
 				},
@@ -91,9 +101,11 @@ impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender>
 				.await;
 
 			common_params.metrics.on_full_request_issued();
+			let request_start = Instant::now();
 
 			match response.await {
 				Ok(req_res::v1::AvailableDataFetchingResponse::AvailableData(data)) => {
+					let latency = request_start.elapsed();
 					let recovery_duration =
 						common_params.metrics.time_erasure_recovery(strategy_type);
 					let maybe_data = match common_params.post_recovery_check {
@@ -126,10 +138,12 @@ impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender>
 							);
 
 							common_params.metrics.on_full_request_succeeded();
+							common_params.peer_stats.on_success(&authority_id, latency);
 							return Ok(data)
 						},
 						None => {
 							common_params.metrics.on_full_request_invalid();
+							common_params.peer_stats.on_failure(&authority_id);
 							recovery_duration.map(|rd| rd.stop_and_discard());
 
 							gum::debug!(
@@ -146,8 +160,10 @@ impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender>
 				},
 				Ok(req_res::v1::AvailableDataFetchingResponse::NoSuchData) => {
 					common_params.metrics.on_full_request_no_such_data();
+					common_params.peer_stats.on_failure(&authority_id);
 				},
 				Err(e) => {
+					common_params.peer_stats.on_failure(&authority_id);
 					match &e {
 						RequestError::Canceled(_) => common_params.metrics.on_full_request_error(),
 						RequestError::InvalidResponse(_) =>
@@ -18,11 +18,13 @@
 
 mod chunks;
 mod full;
+mod race;
 mod systematic;
 
 pub use self::{
 	chunks::{FetchChunks, FetchChunksParams},
 	full::{FetchFull, FetchFullParams},
+	race::Race,
 	systematic::{FetchSystematicChunks, FetchSystematicChunksParams},
 };
 use crate::{
@@ -46,7 +48,7 @@ use polkadot_primitives::{AuthorityDiscoveryId, BlakeTwo256, ChunkIndex, HashT,
 use sc_network::{IfDisconnected, OutboundFailure, ProtocolName, RequestFailure};
 use std::{
 	collections::{BTreeMap, HashMap, VecDeque},
-	time::Duration,
+	time::{Duration, Instant},
 };
 
 // How many parallel chunk fetching requests should be running at once.
@@ -378,6 +380,8 @@ impl State {
 
 				let chunk_mapping_enabled = params.chunk_mapping_enabled;
 				let authority_id_clone = authority_id.clone();
+				let peer_stats = params.peer_stats.clone();
+				let request_start = Instant::now();
 
 				requesting_chunks.push(Box::pin(async move {
 					let _timer = timer;
@@ -424,6 +428,12 @@ impl State {
 						Err(e) => Err(e),
 					};
 
+					match res {
+						Ok((Some(_), _)) =>
+							peer_stats.on_success(&authority_id, request_start.elapsed()),
+						_ => peer_stats.on_failure(&authority_id),
+					}
+
 					(authority_id, validator_index, res)
 				}));
 			} else {
@@ -683,6 +693,7 @@ mod tests {
 				req_v2_protocol_name: "/req_chunk/2".into(),
 				chunk_mapping_enabled: true,
 				erasure_task_tx,
+				peer_stats: Default::default(),
 			}
 		}
 	}
@@ -1539,6 +1550,66 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_race_strategy_run() {
+		let params = RecoveryParams::default();
+
+		struct FixedStrategy(Option<Result<AvailableData, RecoveryError>>);
+		#[async_trait::async_trait]
+		impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender> for FixedStrategy {
+			fn display_name(&self) -> &'static str {
+				"FixedStrategy"
+			}
+
+			fn strategy_type(&self) -> &'static str {
+				"fixed_strategy"
+			}
+
+			async fn run(
+				mut self: Box<Self>,
+				_state: &mut State,
+				_sender: &mut Sender,
+				_common_params: &RecoveryParams,
+			) -> Result<AvailableData, RecoveryError> {
+				// `None` never concludes.
+				match self.0.take() {
+					Some(res) => res,
+					None => future::pending().await,
+				}
+			}
+		}
+
+		let good = || Box::new(FixedStrategy(Some(Ok(dummy_available_data()))));
+		let unavailable = || Box::new(FixedStrategy(Some(Err(RecoveryError::Unavailable))));
+		let invalid = || Box::new(FixedStrategy(Some(Err(RecoveryError::Invalid))));
+		let pending = || Box::new(FixedStrategy(None));
+
+		let races: Vec<(Race<TestSubsystemSender>, Result<AvailableData, RecoveryError>)> = vec![
+			(Race::new(good(), pending()), Ok(dummy_available_data())),
+			(Race::new(pending(), good()), Ok(dummy_available_data())),
+			// The other strategy is awaited if one of them can't recover the data.
+			(Race::new(unavailable(), good()), Ok(dummy_available_data())),
+			(Race::new(good(), unavailable()), Ok(dummy_available_data())),
+			(Race::new(unavailable(), unavailable()), Err(RecoveryError::Unavailable)),
+			// Invalid data concludes the race.
+			(Race::new(invalid(), pending()), Err(RecoveryError::Invalid)),
+		];
+
+		for (race, expected) in races {
+			let params = params.clone();
+
+			test_harness(
+				|_receiver| async {},
+				|mut sender| async move {
+					let mut state = State::new();
+					let res = Box::new(race).run(&mut state, &mut sender, &params).await;
+
+					assert_eq!(res, expected);
+				},
+			);
+		}
+	}
+
 	#[test]
 	fn test_is_unavailable() {
 		assert_eq!(is_unavailable(0, 0, 0, 0), false);
@@ -0,0 +1,75 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::task::{RecoveryParams, RecoveryStrategy, State};
+
+use polkadot_node_primitives::AvailableData;
+use polkadot_node_subsystem::{overseer, RecoveryError};
+
+use futures::future::{self, Either};
+
+/// `RecoveryStrategy` that runs two strategies concurrently and returns the data of whichever
+/// recovers it first. If one of them concludes the data is unavailable, we keep waiting for the
+/// other one.
+///
+/// Only the second strategy is given the `State` shared with the strategies following the race,
+/// the first one must not rely on it. This makes `FetchFull` a good fit for the first strategy.
+pub struct Race<Sender> {
+	first: Box<dyn RecoveryStrategy<Sender>>,
+	second: Box<dyn RecoveryStrategy<Sender>>,
+}
+
+impl<Sender> Race<Sender> {
+	/// Create a new `Race` recovery strategy.
+	pub fn new(
+		first: Box<dyn RecoveryStrategy<Sender>>,
+		second: Box<dyn RecoveryStrategy<Sender>>,
+	) -> Self {
+		Self { first, second }
+	}
+}
+
+#[async_trait::async_trait]
+impl<Sender: overseer::AvailabilityRecoverySenderTrait> RecoveryStrategy<Sender> for Race<Sender> {
+	fn display_name(&self) -> &'static str {
+		"Race of two recovery strategies"
+	}
+
+	fn strategy_type(&self) -> &'static str {
+		"race"
+	}
+
+	async fn run(
+		self: Box<Self>,
+		state: &mut State,
+		sender: &mut Sender,
+		common_params: &RecoveryParams,
+	) -> Result<AvailableData, RecoveryError> {
+		let Self { first, second } = *self;
+
+		let mut first_state = State::new();
+		let mut first_sender = sender.clone();
+		let first = first.run(&mut first_state, &mut first_sender, common_params);
+		let second = second.run(state, sender, common_params);
+
+		// Dropping the losing strategy cancels its pending requests.
+		match future::select(first, second).await {
+			Either::Left((Err(RecoveryError::Unavailable), second)) => second.await,
+			Either::Right((Err(RecoveryError::Unavailable), first)) => first.await,
+			Either::Left((res, _)) | Either::Right((res, _)) => res,
+		}
+	}
+}
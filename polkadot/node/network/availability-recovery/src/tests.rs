@@ -37,7 +37,7 @@ use polkadot_node_subsystem::messages::{
 };
 use polkadot_node_subsystem_test_helpers::{
 	derive_erasure_chunks_with_proofs_and_root, make_subsystem_context, mock::new_leaf,
-	TestSubsystemContextHandle,
+	TestSubsystemContextHandle, TestSubsystemSender,
 };
 use polkadot_node_subsystem_util::TimeoutExt;
 use polkadot_primitives::{
@@ -141,6 +141,22 @@ fn with_systematic_chunks(
 	)
 }
 
+/// Create a new instance of `AvailabilityRecoverySubsystem` which picks and orders the recovery
+/// strategies based on the observed peer latency.
+fn with_adaptive_strategy(
+	req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+	req_protocol_names: &ReqProtocolNames,
+	metrics: Metrics,
+) -> AvailabilityRecoverySubsystem {
+	AvailabilityRecoverySubsystem::for_validator(
+		Some(FETCH_CHUNKS_THRESHOLD),
+		req_receiver,
+		req_protocol_names,
+		metrics,
+	)
+	.with_adaptive_strategy(true)
+}
+
 // Deterministic genesis hash for protocol names
 const GENESIS_HASH: Hash = Hash::repeat_byte(0xff);
 
@@ -1674,6 +1690,183 @@ fn fast_path_backing_group_recovers_if_pov_small(
 	});
 }
 
+#[test]
+fn adaptive_strategy_recovers_from_backers_if_pov_small() {
+	let test_state = TestState::default();
+	let req_protocol_names = ReqProtocolNames::new(&GENESIS_HASH, None);
+	let subsystem = with_adaptive_strategy(
+		request_receiver(&req_protocol_names),
+		&req_protocol_names,
+		Metrics::new_dummy(),
+	);
+
+	test_harness(subsystem, |mut virtual_overseer| async move {
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(new_leaf(
+				test_state.current,
+				1,
+			))),
+		)
+		.await;
+
+		let (tx, rx) = oneshot::channel();
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::RecoverAvailableData(
+				test_state.candidate.clone(),
+				test_state.session_index,
+				Some(GroupIndex(0)),
+				Some(test_state.core_index),
+				tx,
+			),
+		)
+		.await;
+
+		test_state.test_runtime_api_session_info(&mut virtual_overseer).await;
+
+		test_state.test_runtime_api_node_features(&mut virtual_overseer).await;
+
+		let candidate_hash = test_state.candidate.hash();
+
+		let who_has = |i| match i {
+			3 => Has::Yes,
+			_ => Has::No,
+		};
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::AvailabilityStore(
+				AvailabilityStoreMessage::QueryChunkSize(_, tx)
+			) => {
+				let _ = tx.send(Some(100));
+			}
+		);
+
+		test_state.respond_to_available_data_query(&mut virtual_overseer, false).await;
+
+		// Nothing is known about the validators yet, so backers are tried first.
+		test_state
+			.test_full_data_requests(
+				&req_protocol_names,
+				candidate_hash,
+				&mut virtual_overseer,
+				who_has,
+				GroupIndex(0),
+			)
+			.await;
+
+		// Recovered data should match the original one.
+		assert_eq!(rx.await.unwrap().unwrap(), test_state.available_data);
+		virtual_overseer
+	});
+}
+
+fn adaptive_authority_keys() -> Vec<AuthorityDiscoveryId> {
+	[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie, Sr25519Keyring::Dave]
+		.iter()
+		.map(|keyring| keyring.public().into())
+		.collect()
+}
+
+/// The types of the strategies picked by `RecoveryStrategyKind::Adaptive`, in order. The first two
+/// validators are the backers, the last two hold the systematic chunks.
+fn adaptive_strategy_types(
+	peer_stats: &PeerStats,
+	small_pov_size: bool,
+	with_backers: bool,
+) -> Vec<&'static str> {
+	let authority_keys = adaptive_authority_keys();
+	let backers = vec![ValidatorIndex(0), ValidatorIndex(1)];
+	let systematic_params = FetchSystematicChunksParams {
+		validators: vec![(ChunkIndex(0), ValidatorIndex(2)), (ChunkIndex(1), ValidatorIndex(3))],
+		backers: backers.clone(),
+	};
+
+	adaptive_recovery_strategies::<TestSubsystemSender>(
+		peer_stats,
+		&authority_keys,
+		with_backers.then_some(&backers),
+		Some(systematic_params),
+		FetchChunksParams { n_validators: authority_keys.len() },
+		small_pov_size,
+	)
+	.iter()
+	.map(|strategy| strategy.strategy_type())
+	.collect()
+}
+
+#[test]
+fn adaptive_strategy_without_stats_prefers_backers() {
+	let peer_stats = PeerStats::default();
+
+	// Nothing is known, so the backers are assumed to be as fast as the chunk holders.
+	assert_eq!(
+		adaptive_strategy_types(&peer_stats, true, true),
+		vec!["full_from_backers", "systematic_chunks", "regular_chunks"]
+	);
+	assert_eq!(adaptive_strategy_types(&peer_stats, false, true), vec!["race", "regular_chunks"]);
+	assert_eq!(
+		adaptive_strategy_types(&peer_stats, true, false),
+		vec!["systematic_chunks", "regular_chunks"]
+	);
+}
+
+#[test]
+fn adaptive_strategy_switches_on_latency_and_failures() {
+	let peer_stats = PeerStats::default();
+	let keys = adaptive_authority_keys();
+
+	for _ in 0..10 {
+		peer_stats.on_success(&keys[0], Duration::from_secs(2));
+		peer_stats.on_success(&keys[1], Duration::from_secs(2));
+		peer_stats.on_success(&keys[2], Duration::from_millis(50));
+		peer_stats.on_success(&keys[3], Duration::from_millis(50));
+	}
+
+	// Slow backers are tried after the systematic chunks, unless the PoV is large.
+	assert_eq!(
+		adaptive_strategy_types(&peer_stats, true, true),
+		vec!["systematic_chunks", "full_from_backers", "regular_chunks"]
+	);
+	assert_eq!(adaptive_strategy_types(&peer_stats, false, true), vec!["race", "regular_chunks"]);
+
+	// Unreliable backers are the last resort, whatever the PoV size.
+	for _ in 0..5 {
+		peer_stats.on_failure(&keys[0]);
+		peer_stats.on_failure(&keys[1]);
+	}
+	for small_pov_size in [true, false] {
+		assert_eq!(
+			adaptive_strategy_types(&peer_stats, small_pov_size, true),
+			vec!["systematic_chunks", "regular_chunks", "full_from_backers"]
+		);
+	}
+}
+
+#[test]
+fn adaptive_strategy_ignores_stale_stats() {
+	let peer_stats = PeerStats::with_max_age(Duration::from_millis(50));
+	let keys = adaptive_authority_keys();
+
+	for _ in 0..5 {
+		peer_stats.on_failure(&keys[0]);
+		peer_stats.on_failure(&keys[1]);
+	}
+	assert_eq!(
+		adaptive_strategy_types(&peer_stats, true, true),
+		vec!["systematic_chunks", "regular_chunks", "full_from_backers"]
+	);
+
+	// Once the failures are old enough, the backers get another chance.
+	std::thread::sleep(Duration::from_millis(100));
+	assert_eq!(
+		adaptive_strategy_types(&peer_stats, true, true),
+		vec!["full_from_backers", "systematic_chunks", "regular_chunks"]
+	);
+}
+
 #[rstest]
 #[case(true)]
 #[case(false)]
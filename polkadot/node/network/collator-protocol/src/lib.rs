@@ -21,6 +21,8 @@
 #![deny(unused_crate_dependencies)]
 #![recursion_limit = "256"]
 
+#[cfg(feature = "experimental-collator-protocol")]
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::{
@@ -28,6 +30,8 @@ use futures::{
 	FutureExt, TryFutureExt,
 };
 
+#[cfg(feature = "experimental-collator-protocol")]
+use polkadot_node_subsystem_util::database::Database;
 use polkadot_node_subsystem_util::reputation::ReputationAggregator;
 use sp_keystore::KeystorePtr;
 
@@ -44,6 +48,10 @@ mod validator_side;
 #[cfg(feature = "experimental-collator-protocol")]
 mod validator_side_experimental;
 
+// Only the experimental validator side persists data.
+#[cfg(all(test, not(feature = "experimental-collator-protocol")))]
+use kvdb_memorydb as _;
+
 const LOG_TARGET: &'static str = "parachain::collator-protocol";
 
 /// A collator eviction policy - how fast to evict collators which are inactive.
@@ -82,6 +90,10 @@ pub enum ProtocolSide {
 		keystore: KeystorePtr,
 		/// Prometheus metrics for validators.
 		metrics: validator_side_experimental::Metrics,
+		/// The database the collator reputation is persisted in.
+		db: Arc<dyn Database>,
+		/// The configuration of the reputation database.
+		db_config: validator_side_experimental::DbConfig,
 	},
 	/// Collators operate on a parachain.
 	Collator {
@@ -123,8 +135,8 @@ impl<Context> CollatorProtocolSubsystem {
 					.map_err(|e| SubsystemError::with_origin("collator-protocol", e))
 					.boxed(),
 			#[cfg(feature = "experimental-collator-protocol")]
-			ProtocolSide::ValidatorExperimental { keystore, metrics } =>
-				validator_side_experimental::run(ctx, keystore, metrics)
+			ProtocolSide::ValidatorExperimental { keystore, metrics, db, db_config } =>
+				validator_side_experimental::run(ctx, keystore, metrics, db, db_config)
 					.map_err(|e| SubsystemError::with_origin("collator-protocol", e))
 					.boxed(),
 			ProtocolSide::Collator { peer_id, collator_pair, request_receiver_v2, metrics } =>
@@ -60,9 +60,9 @@ use polkadot_primitives::{
 
 use super::{modify_reputation, tick_stream, LOG_TARGET};
 
-mod claim_queue_state;
-mod collation;
-mod error;
+pub(crate) mod claim_queue_state;
+pub(crate) mod collation;
+pub(crate) mod error;
 mod metrics;
 
 use claim_queue_state::ClaimQueueState;
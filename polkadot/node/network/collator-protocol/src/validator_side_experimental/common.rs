@@ -0,0 +1,115 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types and constants shared by the experimental validator side.
+
+use std::time::{Duration, Instant};
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_primitives::{Hash, Id as ParaId};
+
+use crate::validator_side::collation::ProspectiveCandidate;
+
+/// Maximum reputation score a collator can reach.
+pub const MAX_SCORE: u16 = 10_000;
+
+/// Collators with at least this score get their advertisements fetched right away. The
+/// advertisements of all other collators are only fetched after
+/// [`UNDER_THRESHOLD_FETCH_DELAY`], giving reputable collators a chance to claim the slot first.
+pub const INSTANT_FETCH_REP_THRESHOLD: Score = Score(1_000);
+
+/// How long advertisements of collators below [`INSTANT_FETCH_REP_THRESHOLD`] wait before they can
+/// be fetched.
+#[cfg(not(test))]
+pub const UNDER_THRESHOLD_FETCH_DELAY: Duration = Duration::from_millis(1_000);
+#[cfg(test)]
+pub const UNDER_THRESHOLD_FETCH_DELAY: Duration = Duration::from_millis(100);
+
+/// Bump applied when a fetched collation gets seconded.
+pub const VALID_COLLATION_BUMP: u16 = 100;
+
+/// Slash applied when a collator does not deliver an advertised collation, i.e. the request times
+/// out, fails or the response can't be decoded.
+pub const FAILED_FETCH_SLASH: u16 = 200;
+
+/// Slash applied when a collator delivers a collation which turns out to be invalid or doesn't
+/// match its advertisement.
+pub const INVALID_COLLATION_SLASH: u16 = 2_000;
+
+/// How often all the scores decay.
+pub const REPUTATION_DECAY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How often the changed scores are written to the database.
+pub const REPUTATION_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Every decay takes `1 / REPUTATION_DECAY_DIVISOR` of the score (at least one point).
+pub const REPUTATION_DECAY_DIVISOR: u16 = 20;
+
+/// Maximum number of collators we keep a score for, per para. The lowest scores are dropped
+/// first.
+pub const MAX_STORED_SCORES_PER_PARA: usize = 1_000;
+
+/// Maximum number of collators connected at the same time, per para. When a new collator
+/// declares for a full para, the lowest scored collator is disconnected, unless it scores higher
+/// than the newcomer.
+pub const MAX_CONNECTED_PER_PARA: usize = 30;
+
+/// How often undeclared peers are checked for eviction.
+#[cfg(not(test))]
+pub const ACTIVITY_POLL: Duration = Duration::from_secs(1);
+#[cfg(test)]
+pub const ACTIVITY_POLL: Duration = Duration::from_millis(10);
+
+/// Reputation score of a collator for a specific para.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Score(u16);
+
+impl Score {
+	/// Create a new score, capped at [`MAX_SCORE`].
+	pub fn new(value: u16) -> Self {
+		Self(value.min(MAX_SCORE))
+	}
+
+	/// The raw value.
+	pub fn value(&self) -> u16 {
+		self.0
+	}
+
+	/// Increase the score, capped at [`MAX_SCORE`].
+	pub fn saturating_add(self, amount: u16) -> Self {
+		Self::new(self.0.saturating_add(amount))
+	}
+
+	/// Decrease the score, saturating at zero.
+	pub fn saturating_sub(self, amount: u16) -> Self {
+		Self(self.0.saturating_sub(amount))
+	}
+}
+
+/// A validated advertisement which was not fetched yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Advertisement {
+	/// The advertising peer.
+	pub peer_id: PeerId,
+	/// The para the peer declared for.
+	pub para_id: ParaId,
+	/// Relay parent of the advertised candidate.
+	pub relay_parent: Hash,
+	/// The advertised candidate.
+	pub prospective_candidate: ProspectiveCandidate,
+	/// When the advertisement was received.
+	pub received_at: Instant,
+}
@@ -0,0 +1,237 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence of the collator reputation scores.
+//!
+//! The scores of every para are stored under their own key, along with the time of the last
+//! decay. The changed scores are flushed every [`REPUTATION_FLUSH_INTERVAL`] and on shutdown. On
+//! load, the decays missed while the node was down are applied, so that collators don't keep their
+//! priority just because the node was restarted.
+//!
+//! [`REPUTATION_FLUSH_INTERVAL`]: super::common::REPUTATION_FLUSH_INTERVAL
+
+use std::{
+	sync::Arc,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Decode, Encode};
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_primitives::Id as ParaId;
+
+use super::{
+	common::{Score, MAX_SCORE, REPUTATION_DECAY_INTERVAL},
+	reputation::ReputationStore,
+};
+use crate::LOG_TARGET;
+
+const SCORES_PREFIX: &[u8; 10] = b"Rep_scores";
+const LAST_DECAY_KEY: &[u8; 14] = b"Rep_last_decay";
+
+/// Configuration of the reputation database.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+	/// The column of the database the scores are stored in. It must allow iteration.
+	///
+	/// The parachains database has no such column yet, it is to be added along with the
+	/// wiring of the experimental validator side into the service.
+	pub col_reputation_data: u32,
+}
+
+/// The scores of a para, as stored in the database.
+type StoredScores = Vec<(Vec<u8>, u16)>;
+
+fn scores_key(para_id: ParaId) -> Vec<u8> {
+	(SCORES_PREFIX, para_id).encode()
+}
+
+/// The current time, as a duration since the unix epoch.
+pub fn now() -> Duration {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Loads and flushes the scores of a [`ReputationStore`].
+pub struct ReputationDb {
+	db: Arc<dyn Database>,
+	config: Config,
+	last_decay: Duration,
+}
+
+impl ReputationDb {
+	/// Load the stored scores, applying the decays missed since the last stored one.
+	///
+	/// Entries which can't be decoded are skipped. If the database can't be read at all, the
+	/// store starts empty.
+	pub fn load(
+		db: Arc<dyn Database>,
+		config: Config,
+		max_per_para: usize,
+		now: Duration,
+	) -> (Self, ReputationStore) {
+		let mut store = ReputationStore::new(max_per_para);
+
+		let last_decay = match db.get(config.col_reputation_data, LAST_DECAY_KEY) {
+			Ok(value) => value
+				.and_then(|value| u64::decode(&mut &value[..]).ok())
+				.map(Duration::from_secs),
+			Err(err) => {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to read the collator reputation");
+				None
+			},
+		};
+
+		for entry in db.iter_with_prefix(config.col_reputation_data, SCORES_PREFIX) {
+			let (key, value) = match entry {
+				Ok(entry) => entry,
+				Err(err) => {
+					gum::warn!(target: LOG_TARGET, ?err, "Failed to read the collator reputation");
+					return (
+						Self { db, config, last_decay: now },
+						ReputationStore::new(max_per_para),
+					)
+				},
+			};
+			let Ok(para_id) = ParaId::decode(&mut &key[SCORES_PREFIX.len()..]) else { continue };
+			let Ok(scores) = StoredScores::decode(&mut &value[..]) else { continue };
+
+			for (peer_id, score) in scores {
+				if let Ok(peer_id) = PeerId::from_bytes(&peer_id) {
+					store.restore(para_id, peer_id, Score::new(score));
+				}
+			}
+		}
+
+		let mut this = Self { db, config, last_decay: last_decay.unwrap_or(now) };
+		let missed =
+			now.saturating_sub(this.last_decay).as_secs() / REPUTATION_DECAY_INTERVAL.as_secs();
+		// Every decay takes at least one point, so `MAX_SCORE` decays clear all the scores.
+		for _ in 0..missed.min(MAX_SCORE as u64) {
+			store.decay();
+		}
+		this.last_decay += REPUTATION_DECAY_INTERVAL * missed as u32;
+
+		gum::debug!(
+			target: LOG_TARGET,
+			stored_scores = store.len(),
+			missed_decays = missed,
+			"Loaded collator reputation",
+		);
+
+		(this, store)
+	}
+
+	/// Record that the scores were decayed at `now`.
+	pub fn note_decay(&mut self, now: Duration) {
+		self.last_decay = now;
+	}
+
+	/// Write the changed scores and the time of the last decay to the database.
+	pub fn flush(&mut self, store: &mut ReputationStore) -> std::io::Result<()> {
+		let mut tx = DBTransaction::new();
+		for (para_id, scores) in store.drain_changes() {
+			if scores.is_empty() {
+				tx.delete(self.config.col_reputation_data, &scores_key(para_id));
+			} else {
+				let scores = scores
+					.into_iter()
+					.map(|(peer_id, score)| (peer_id.to_bytes(), score.value()))
+					.collect::<StoredScores>();
+				tx.put_vec(self.config.col_reputation_data, &scores_key(para_id), scores.encode());
+			}
+		}
+		tx.put_vec(
+			self.config.col_reputation_data,
+			LAST_DECAY_KEY,
+			self.last_decay.as_secs().encode(),
+		);
+
+		self.db.write(tx)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter;
+
+	const COL: u32 = 0;
+
+	fn make_db() -> Arc<dyn Database> {
+		Arc::new(DbAdapter::new(kvdb_memorydb::create(1), &[COL]))
+	}
+
+	fn config() -> Config {
+		Config { col_reputation_data: COL }
+	}
+
+	#[test]
+	fn scores_survive_a_restart() {
+		let db = make_db();
+		let (para_a, para_b) = (ParaId::from(1), ParaId::from(2));
+		let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+		let start = Duration::from_secs(1_000_000);
+
+		let (mut rep_db, mut store) = ReputationDb::load(db.clone(), config(), 10, start);
+		assert_eq!(store.len(), 0);
+		store.bump(para_a, peer_a, 100);
+		store.bump(para_b, peer_b, 200);
+		rep_db.flush(&mut store).unwrap();
+
+		// Scores which are gone are removed from the database as well.
+		store.slash(para_b, peer_b, 200);
+		rep_db.flush(&mut store).unwrap();
+
+		let (_, store) = ReputationDb::load(db, config(), 10, start);
+		assert_eq!(store.score(&para_a, &peer_a), Score::new(100));
+		assert_eq!(store.score(&para_b, &peer_b), Score::default());
+		assert_eq!(store.len(), 1);
+	}
+
+	#[test]
+	fn missed_decays_are_applied_on_load() {
+		let db = make_db();
+		let para = ParaId::from(1);
+		let peer = PeerId::random();
+		let start = Duration::from_secs(1_000_000);
+
+		let (mut rep_db, mut store) = ReputationDb::load(db.clone(), config(), 10, start);
+		store.bump(para, peer, 1_000);
+		rep_db.flush(&mut store).unwrap();
+
+		// Less than a decay interval passed.
+		let now = start + REPUTATION_DECAY_INTERVAL / 2;
+		let (_, store) = ReputationDb::load(db.clone(), config(), 10, now);
+		assert_eq!(store.score(&para, &peer), Score::new(1_000));
+
+		// Two decays were missed.
+		let now = start + REPUTATION_DECAY_INTERVAL * 2 + REPUTATION_DECAY_INTERVAL / 2;
+		let (mut rep_db, mut store) = ReputationDb::load(db.clone(), config(), 10, now);
+		assert_eq!(store.score(&para, &peer), Score::new(903));
+
+		// The partial interval is not lost, and the decayed scores are not flushed twice.
+		rep_db.flush(&mut store).unwrap();
+		let now = start + REPUTATION_DECAY_INTERVAL * 3;
+		let (_, store) = ReputationDb::load(db.clone(), config(), 10, now);
+		assert_eq!(store.score(&para, &peer), Score::new(903 - 903 / 20));
+
+		// After a long downtime, all the scores are gone.
+		let now = start + REPUTATION_DECAY_INTERVAL * 100_000;
+		let (_, store) = ReputationDb::load(db, config(), 10, now);
+		assert_eq!(store.len(), 0);
+	}
+}
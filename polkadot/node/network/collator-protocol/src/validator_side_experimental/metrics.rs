@@ -17,12 +17,174 @@
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 
 #[derive(Clone, Default)]
-pub struct Metrics;
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	/// Note the result of a collation request.
+	pub fn on_request(&self, succeeded: std::result::Result<(), ()>) {
+		if let Some(metrics) = &self.0 {
+			match succeeded {
+				Ok(()) => metrics.collation_requests.with_label_values(&["succeeded"]).inc(),
+				Err(()) => metrics.collation_requests.with_label_values(&["failed"]).inc(),
+			}
+		}
+	}
+
+	/// Note a collation fetch was started from a collator whose score is in `bucket`.
+	pub fn on_fetch(&self, bucket: &'static str) {
+		self.0
+			.as_ref()
+			.map(|metrics| metrics.collation_fetches.with_label_values(&[bucket]).inc());
+	}
+
+	/// Note an advertisement was accepted (`Ok`) or rejected with the given reason.
+	pub fn on_advertisement(&self, result: std::result::Result<(), &'static str>) {
+		if let Some(metrics) = &self.0 {
+			let label = match result {
+				Ok(()) => "accepted",
+				Err(reason) => reason,
+			};
+			metrics.advertisements.with_label_values(&[label]).inc();
+		}
+	}
+
+	/// Note a reputation change, `bump` or `slash`.
+	pub fn on_reputation_change(&self, kind: &'static str) {
+		self.0
+			.as_ref()
+			.map(|metrics| metrics.reputation_changes.with_label_values(&[kind]).inc());
+	}
+
+	/// Note the current number of stored collator scores.
+	pub fn note_stored_scores(&self, count: usize) {
+		self.0.as_ref().map(|metrics| metrics.stored_scores.set(count as u64));
+	}
+
+	/// Note the current number of advertisements waiting to be fetched.
+	pub fn note_pending_advertisements(&self, count: usize) {
+		self.0.as_ref().map(|metrics| metrics.pending_advertisements.set(count as u64));
+	}
+
+	/// Note the current number of collator peers.
+	pub fn note_collator_peer_count(&self, collator_peers: usize) {
+		self.0
+			.as_ref()
+			.map(|metrics| metrics.collator_peer_count.set(collator_peers as u64));
+	}
+
+	/// Provide a timer for `process_msg` which observes on drop.
+	pub fn time_process_msg(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
+		self.0.as_ref().map(|metrics| metrics.process_msg.start_timer())
+	}
+
+	/// Provide a timer for `CollationFetchRequest` structure which observes on drop.
+	pub fn time_collation_request_duration(
+		&self,
+	) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
+		self.0.as_ref().map(|metrics| metrics.collation_request_duration.start_timer())
+	}
+}
+
+#[derive(Clone)]
+struct MetricsInner {
+	collation_requests: prometheus::CounterVec<prometheus::U64>,
+	collation_fetches: prometheus::CounterVec<prometheus::U64>,
+	advertisements: prometheus::CounterVec<prometheus::U64>,
+	reputation_changes: prometheus::CounterVec<prometheus::U64>,
+	stored_scores: prometheus::Gauge<prometheus::U64>,
+	pending_advertisements: prometheus::Gauge<prometheus::U64>,
+	collator_peer_count: prometheus::Gauge<prometheus::U64>,
+	process_msg: prometheus::Histogram,
+	collation_request_duration: prometheus::Histogram,
+}
 
 impl metrics::Metrics for Metrics {
 	fn try_register(
-		_registry: &prometheus::Registry,
+		registry: &prometheus::Registry,
 	) -> std::result::Result<Self, prometheus::PrometheusError> {
-		Ok(Metrics)
+		let metrics = MetricsInner {
+			collation_requests: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collation_requests_total",
+						"Number of collations requested from Collators.",
+					),
+					&["success"],
+				)?,
+				registry,
+			)?,
+			collation_fetches: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_protocol_validator_collation_fetches_total",
+						"Number of collation fetches started, by the score of the collator.",
+					),
+					&["score"],
+				)?,
+				registry,
+			)?,
+			advertisements: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_protocol_validator_advertisements_total",
+						"Number of advertisements received, by outcome.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			reputation_changes: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collator_protocol_validator_reputation_changes_total",
+						"Number of collator score changes, by kind.",
+					),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			stored_scores: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_collator_protocol_validator_stored_scores",
+					"Number of collator scores kept by the validator",
+				)?,
+				registry,
+			)?,
+			pending_advertisements: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_collator_protocol_validator_pending_advertisements",
+					"Number of accepted advertisements waiting to be fetched",
+				)?,
+				registry,
+			)?,
+			collator_peer_count: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_collator_peer_count",
+					"Amount of collator peers connected",
+				)?,
+				registry,
+			)?,
+			process_msg: prometheus::register(
+				prometheus::Histogram::with_opts(prometheus::HistogramOpts::new(
+					"polkadot_parachain_collator_protocol_validator_process_msg",
+					"Time spent within `collator_protocol_validator::process_msg`",
+				))?,
+				registry,
+			)?,
+			collation_request_duration: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_collator_protocol_validator_collation_request_duration",
+						"Lifetime of the `CollationFetchRequest` structure",
+					)
+					.buckets(vec![
+						0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.75, 0.9, 1.0, 1.2, 1.5, 1.75,
+					]),
+				)?,
+				registry,
+			)?,
+		};
+
+		Ok(Metrics(Some(metrics)))
 	}
 }
@@ -14,19 +14,1385 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Experimental validator side of the collator protocol, based on collator reputation.
+//!
+//! Collators earn a per-para score when the collations fetched from them get seconded, and lose it
+//! when they fail to deliver advertised collations or deliver invalid ones. The scores are kept
+//! across disconnects, sessions and restarts (see [`reputation`] and [`db`]) and drive which
+//! advertisement is fetched when a claim queue spot is free (see [`state`]).
+//!
+//! Only the v2 collation protocol is supported.
+
+mod common;
+mod db;
+mod error;
 mod metrics;
+mod reputation;
+mod state;
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+	collections::{hash_map::Entry, HashMap, VecDeque},
+	future::Future,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use futures::{channel::oneshot, select, FutureExt, StreamExt};
+use futures_timer::Delay;
+use tokio_util::sync::CancellationToken;
 
-use polkadot_node_subsystem::overseer;
 use sp_keystore::KeystorePtr;
 
+use polkadot_node_network_protocol::{
+	self as net_protocol,
+	peer_set::{CollationVersion, PeerSet},
+	request_response::{
+		outgoing::{Recipient, RequestError},
+		v2 as request_v2, OutgoingRequest, Requests,
+	},
+	v1 as protocol_v1, v2 as protocol_v2, CollationProtocols, OurView, PeerId,
+	UnifiedReputationChange as Rep,
+};
+use polkadot_node_primitives::{SignedFullStatement, Statement};
+use polkadot_node_subsystem::{
+	messages::{
+		CanSecondRequest, CandidateBackingMessage, CollatorProtocolMessage, IfDisconnected,
+		NetworkBridgeEvent, NetworkBridgeTxMessage, ParentHeadData, ProspectiveParachainsMessage,
+		ProspectiveValidationDataRequest,
+	},
+	overseer, CollatorProtocolSenderTrait, FromOrchestra, OverseerSignal,
+};
+use polkadot_node_subsystem_util::{
+	database::Database,
+	reputation::{ReputationAggregator, REPUTATION_CHANGE_INTERVAL},
+	request_claim_queue, request_node_features, request_session_index_for_child,
+};
+use polkadot_primitives::{
+	node_features,
+	vstaging::{CandidateDescriptorV2, CandidateDescriptorVersion},
+	CandidateHash, Hash, HeadData, Id as ParaId, PersistedValidationData, SessionIndex,
+};
+
+use super::{modify_reputation, tick_stream, CollatorEvictionPolicy, LOG_TARGET};
+use crate::validator_side::{
+	collation::{
+		fetched_collation_sanity_check, BlockedCollationId, CollationEvent, CollationFetchError,
+		CollationFetchRequest, FetchedCollation, PendingCollation, PendingCollationFetch,
+		ProspectiveCandidate,
+	},
+	error::SecondingError,
+};
+use common::{
+	Advertisement, ACTIVITY_POLL, FAILED_FETCH_SLASH, INVALID_COLLATION_SLASH,
+	MAX_STORED_SCORES_PER_PARA, REPUTATION_DECAY_INTERVAL, REPUTATION_FLUSH_INTERVAL,
+	UNDER_THRESHOLD_FETCH_DELAY, VALID_COLLATION_BUMP,
+};
+use db::ReputationDb;
+use error::{Error, FetchError, Result};
+use state::{score_bucket, AdvertisementError, PeerState, PerRelayParent, State};
+
+pub use db::Config as DbConfig;
 pub use metrics::Metrics;
 
-/// The main run loop
+const COST_UNEXPECTED_MESSAGE: Rep = Rep::CostMinor("An unexpected message");
+/// Message could not be decoded properly.
+const COST_CORRUPTED_MESSAGE: Rep = Rep::CostMinor("Message was corrupt");
+/// Network errors that originated at the remote host should have same cost as timeout.
+const COST_NETWORK_ERROR: Rep = Rep::CostMinor("Some network error");
+const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Invalid network message signature");
+const COST_REPORT_BAD: Rep = Rep::Malicious("A collator was reported by another subsystem");
+const COST_WRONG_PARA: Rep = Rep::Malicious("A collator provided a collation for the wrong para");
+const COST_PROTOCOL_MISUSE: Rep =
+	Rep::Malicious("A collator advertising a collation using an unsupported protocol version");
+const COST_UNNEEDED_COLLATOR: Rep = Rep::CostMinor("An unneeded collator connected");
+
+impl AdvertisementError {
+	fn reputation_changes(&self) -> Option<Rep> {
+		use AdvertisementError::*;
+		match self {
+			InvalidAssignment => Some(COST_WRONG_PARA),
+			RelayParentUnknown | UndeclaredCollator | Duplicate | PeerLimitReached =>
+				Some(COST_UNEXPECTED_MESSAGE),
+			UnknownPeer | SecondedLimitReached | BlockedByBacking => None,
+		}
+	}
+
+	fn metric_label(&self) -> &'static str {
+		use AdvertisementError::*;
+		match self {
+			RelayParentUnknown => "relay_parent_unknown",
+			UnknownPeer => "unknown_peer",
+			UndeclaredCollator => "undeclared_collator",
+			InvalidAssignment => "invalid_assignment",
+			Duplicate => "duplicate",
+			PeerLimitReached => "peer_limit_reached",
+			SecondedLimitReached => "seconded_limit_reached",
+			BlockedByBacking => "blocked_by_backing",
+		}
+	}
+}
+
+async fn disconnect_peer(sender: &mut impl overseer::CollatorProtocolSenderTrait, peer_id: PeerId) {
+	sender
+		.send_message(NetworkBridgeTxMessage::DisconnectPeer(peer_id, PeerSet::Collation))
+		.await
+}
+
+/// Bump the score of a collator, e.g. because one of its collations got seconded.
+fn bump_collator(state: &mut State, para_id: ParaId, peer_id: PeerId, amount: u16) {
+	let score = state.reputation.bump(para_id, peer_id, amount);
+	gum::debug!(target: LOG_TARGET, %para_id, ?peer_id, ?score, "Bumped collator score");
+
+	state.metrics.on_reputation_change("bump");
+	state.metrics.note_stored_scores(state.reputation.len());
+}
+
+/// Slash the score of a collator and optionally change its network reputation.
+async fn slash_collator(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+	para_id: ParaId,
+	peer_id: PeerId,
+	amount: u16,
+	rep: Option<Rep>,
+) {
+	let score = state.reputation.slash(para_id, peer_id, amount);
+	gum::debug!(target: LOG_TARGET, %para_id, ?peer_id, ?score, "Slashed collator score");
+
+	state.metrics.on_reputation_change("slash");
+	state.metrics.note_stored_scores(state.reputation.len());
+
+	if let Some(rep) = rep {
+		modify_reputation(&mut state.network_reputation, sender, peer_id, rep).await;
+	}
+}
+
+/// Notify a collator that its collation got seconded.
+async fn notify_collation_seconded(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	peer_id: PeerId,
+	relay_parent: Hash,
+	statement: SignedFullStatement,
+) {
+	let wire_message = CollationProtocols::V2(protocol_v2::CollationProtocol::CollatorProtocol(
+		protocol_v2::CollatorProtocolMessage::CollationSeconded(relay_parent, statement.into()),
+	));
+	sender
+		.send_message(NetworkBridgeTxMessage::SendCollationMessage(vec![peer_id], wire_message))
+		.await;
+}
+
+/// Request an advertised collation from the network.
+async fn request_collation(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+	advertisement: Advertisement,
+) -> std::result::Result<(), FetchError> {
+	let Advertisement { peer_id, para_id, relay_parent, prospective_candidate, .. } = advertisement;
+
+	if !state.per_relay_parent.contains_key(&relay_parent) {
+		return Err(FetchError::RelayParentOutOfView)
+	}
+	let collator_id = state.collator_id(&peer_id).ok_or(FetchError::UnknownPeer)?.clone();
+
+	let pending_collation =
+		PendingCollation::new(relay_parent, para_id, &peer_id, Some(prospective_candidate));
+	if state.collation_requests_cancel_handles.contains_key(&pending_collation) {
+		return Err(FetchError::AlreadyRequested)
+	}
+
+	let (req, response_recv) = OutgoingRequest::new(
+		Recipient::Peer(peer_id),
+		request_v2::CollationFetchingRequest {
+			relay_parent,
+			para_id,
+			candidate_hash: prospective_candidate.candidate_hash,
+		},
+	);
+
+	let cancellation_token = CancellationToken::new();
+	state.collation_requests.push(CollationFetchRequest {
+		pending_collation,
+		collator_id,
+		collator_protocol_version: CollationVersion::V2,
+		from_collator: response_recv.boxed(),
+		cancellation_token: cancellation_token.clone(),
+		_lifetime_timer: state.metrics.time_collation_request_duration(),
+	});
+	state
+		.collation_requests_cancel_handles
+		.insert(pending_collation, cancellation_token);
+
+	let score = state.reputation.score(&para_id, &peer_id);
+	state.metrics.on_fetch(score_bucket(score));
+
+	gum::debug!(
+		target: LOG_TARGET,
+		peer_id = %peer_id,
+		%para_id,
+		?relay_parent,
+		?score,
+		"Requesting collation",
+	);
+
+	sender
+		.send_message(NetworkBridgeTxMessage::SendRequests(
+			vec![Requests::CollationFetchingV2(req)],
+			IfDisconnected::ImmediateError,
+		))
+		.await;
+	Ok(())
+}
+
+/// Fetch the best eligible advertisements at every relay parent with free claims.
+async fn fetch_eligible_collations(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+) {
+	let now = Instant::now();
+	let relay_parents = state
+		.per_relay_parent
+		.iter()
+		.filter(|(_, rp_state)| !rp_state.advertisements.is_empty())
+		.map(|(relay_parent, _)| *relay_parent)
+		.collect::<Vec<_>>();
+
+	for relay_parent in relay_parents {
+		while let Some(advertisement) = state.next_fetch(&relay_parent, now) {
+			let peer_id = advertisement.peer_id;
+			if let Err(err) = request_collation(sender, state, advertisement).await {
+				gum::debug!(
+					target: LOG_TARGET,
+					?relay_parent,
+					?peer_id,
+					error = %err,
+					"Failed to request a collation, trying the next one",
+				);
+			}
+		}
+	}
+
+	state.metrics.note_pending_advertisements(state.pending_advertisements());
+}
+
+async fn construct_per_relay_parent<Sender>(
+	sender: &mut Sender,
+	current_assignments: &mut HashMap<ParaId, usize>,
+	keystore: &KeystorePtr,
+	relay_parent: Hash,
+	v2_receipts: bool,
+	session_index: SessionIndex,
+) -> Result<Option<PerRelayParent>>
+where
+	Sender: CollatorProtocolSenderTrait,
+{
+	let validators = polkadot_node_subsystem_util::request_validators(relay_parent, sender)
+		.await
+		.await
+		.map_err(Error::CancelledActiveValidators)??;
+
+	let (groups, rotation_info) =
+		polkadot_node_subsystem_util::request_validator_groups(relay_parent, sender)
+			.await
+			.await
+			.map_err(Error::CancelledValidatorGroups)??;
+
+	let core_now = if let Some(group) =
+		polkadot_node_subsystem_util::signing_key_and_index(&validators, keystore).and_then(
+			|(_, index)| polkadot_node_subsystem_util::find_validator_group(&groups, index),
+		) {
+		rotation_info.core_for_group(group, groups.len())
+	} else {
+		gum::trace!(target: LOG_TARGET, ?relay_parent, "Not a validator");
+		return Ok(None)
+	};
+
+	let mut claim_queue = request_claim_queue(relay_parent, sender)
+		.await
+		.await
+		.map_err(Error::CancelledClaimQueue)??;
+
+	let assigned_paras = claim_queue.remove(&core_now).unwrap_or_else(|| VecDeque::new());
+
+	for para_id in assigned_paras.iter() {
+		let entry = current_assignments.entry(*para_id).or_default();
+		*entry += 1;
+		if *entry == 1 {
+			gum::debug!(
+				target: LOG_TARGET,
+				?relay_parent,
+				para_id = ?para_id,
+				"Assigned to a parachain",
+			);
+		}
+	}
+
+	Ok(Some(PerRelayParent::new(
+		assigned_paras.into_iter().collect(),
+		core_now,
+		session_index,
+		v2_receipts,
+	)))
+}
+
+fn remove_outgoing(
+	current_assignments: &mut HashMap<ParaId, usize>,
+	per_relay_parent: PerRelayParent,
+) {
+	for cur in per_relay_parent.assignments {
+		if let Entry::Occupied(mut occupied) = current_assignments.entry(cur) {
+			*occupied.get_mut() -= 1;
+			if *occupied.get() == 0 {
+				occupied.remove_entry();
+				gum::debug!(
+					target: LOG_TARGET,
+					para_id = ?cur,
+					"Unassigned from a parachain",
+				);
+			}
+		}
+	}
+}
+
+/// Our view has changed.
+async fn handle_our_view_change<Sender>(
+	sender: &mut Sender,
+	state: &mut State,
+	keystore: &KeystorePtr,
+	view: OurView,
+) -> Result<()>
+where
+	Sender: CollatorProtocolSenderTrait,
+{
+	let current_leaves = state.active_leaves.clone();
+
+	let removed = current_leaves.iter().filter(|h| !view.contains(h));
+	let added = view.iter().filter(|h| !current_leaves.contains(h));
+
+	for leaf in added {
+		let session_index = request_session_index_for_child(*leaf, sender)
+			.await
+			.await
+			.map_err(Error::CancelledSessionIndex)??;
+
+		let v2_receipts = request_node_features(*leaf, session_index, sender)
+			.await
+			.await
+			.map_err(Error::CancelledNodeFeatures)??
+			.get(node_features::FeatureIndex::CandidateReceiptV2 as usize)
+			.map(|b| *b)
+			.unwrap_or(false);
+
+		let Some(per_relay_parent) = construct_per_relay_parent(
+			sender,
+			&mut state.current_assignments,
+			keystore,
+			*leaf,
+			v2_receipts,
+			session_index,
+		)
+		.await?
+		else {
+			continue
+		};
+
+		state.active_leaves.insert(*leaf);
+		state.per_relay_parent.insert(*leaf, per_relay_parent);
+
+		state
+			.implicit_view
+			.activate_leaf(sender, *leaf)
+			.await
+			.map_err(Error::ImplicitViewFetchError)?;
+
+		// Order is always descending.
+		let allowed_ancestry = state
+			.implicit_view
+			.known_allowed_relay_parents_under(leaf, None)
+			.unwrap_or_default();
+		for block_hash in allowed_ancestry {
+			if let Entry::Vacant(entry) = state.per_relay_parent.entry(*block_hash) {
+				// Safe to use the same v2 receipts config for the allowed relay parents as well
+				// as the same session index since they must be in the same session.
+				if let Some(per_relay_parent) = construct_per_relay_parent(
+					sender,
+					&mut state.current_assignments,
+					keystore,
+					*block_hash,
+					v2_receipts,
+					session_index,
+				)
+				.await?
+				{
+					entry.insert(per_relay_parent);
+				}
+			}
+		}
+	}
+
+	for removed in removed {
+		state.active_leaves.remove(removed);
+		// If the leaf is deactivated it still may stay in the view as a part
+		// of implicit ancestry. Only update the state after the hash is actually
+		// pruned from the block info storage.
+		let pruned = state.implicit_view.deactivate_leaf(*removed);
+
+		for removed in pruned {
+			if let Some(per_relay_parent) = state.per_relay_parent.remove(&removed) {
+				remove_outgoing(&mut state.current_assignments, per_relay_parent);
+			}
+
+			state.collation_requests_cancel_handles.retain(|pc, handle| {
+				let keep = pc.relay_parent != removed;
+				if !keep {
+					handle.cancel();
+				}
+				keep
+			});
+			state.fetched_candidates.retain(|k, _| k.relay_parent != removed);
+		}
+	}
+
+	// Remove blocked seconding requests that left the view.
+	state.blocked_from_seconding.retain(|_, collations| {
+		collations.retain(|collation| {
+			state
+				.per_relay_parent
+				.contains_key(&collation.candidate_receipt.descriptor.relay_parent())
+		});
+
+		!collations.is_empty()
+	});
+
+	// Disconnect peers who are not relevant to our current or next para. Their scores are kept.
+	for (peer_id, peer_state) in state.peers.iter() {
+		if let PeerState::Collating(_, para_id) = peer_state {
+			if !state.current_assignments.contains_key(para_id) {
+				gum::trace!(
+					target: LOG_TARGET,
+					?peer_id,
+					?para_id,
+					"Disconnecting peer on view change (not current parachain id)"
+				);
+				disconnect_peer(sender, *peer_id).await;
+			}
+		}
+	}
+
+	// New relay parents may have freed claims.
+	fetch_eligible_collations(sender, state).await;
+
+	Ok(())
+}
+
+// Requests backing to sanity check the advertisement.
+async fn can_second<Sender>(
+	sender: &mut Sender,
+	candidate_para_id: ParaId,
+	candidate_relay_parent: Hash,
+	candidate_hash: CandidateHash,
+	parent_head_data_hash: Hash,
+) -> bool
+where
+	Sender: CollatorProtocolSenderTrait,
+{
+	let request = CanSecondRequest {
+		candidate_para_id,
+		candidate_relay_parent,
+		candidate_hash,
+		parent_head_data_hash,
+	};
+	let (tx, rx) = oneshot::channel();
+	sender.send_message(CandidateBackingMessage::CanSecond(request, tx)).await;
+
+	rx.await.unwrap_or_else(|err| {
+		gum::warn!(
+			target: LOG_TARGET,
+			?err,
+			?candidate_relay_parent,
+			?candidate_para_id,
+			?candidate_hash,
+			"CanSecond-request responder was dropped",
+		);
+
+		false
+	})
+}
+
+async fn handle_advertisement<Sender>(
+	sender: &mut Sender,
+	state: &mut State,
+	relay_parent: Hash,
+	peer_id: PeerId,
+	prospective_candidate: ProspectiveCandidate,
+) -> std::result::Result<(), AdvertisementError>
+where
+	Sender: CollatorProtocolSenderTrait,
+{
+	let para_id = state.collating_para(&peer_id).ok_or(AdvertisementError::UndeclaredCollator)?;
+	let ProspectiveCandidate { candidate_hash, parent_head_data_hash } = prospective_candidate;
+
+	let now = Instant::now();
+	let instant = state.insert_advertisement(peer_id, relay_parent, prospective_candidate, now)?;
+
+	if !can_second(sender, para_id, relay_parent, candidate_hash, parent_head_data_hash).await {
+		if let Some(rp_state) = state.per_relay_parent.get_mut(&relay_parent) {
+			rp_state
+				.advertisements
+				.retain(|adv| adv.prospective_candidate.candidate_hash != candidate_hash);
+		}
+		return Err(AdvertisementError::BlockedByBacking)
+	}
+
+	gum::debug!(
+		target: LOG_TARGET,
+		?peer_id,
+		%para_id,
+		?relay_parent,
+		?candidate_hash,
+		instant,
+		"Accepted advertisement",
+	);
+
+	if !instant {
+		state.fetch_delays.push(Delay::new(UNDER_THRESHOLD_FETCH_DELAY));
+	}
+	fetch_eligible_collations(sender, state).await;
+
+	Ok(())
+}
+
+/// Networking message has been received.
+#[overseer::contextbounds(CollatorProtocol, prefix = overseer)]
+async fn process_incoming_peer_message<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	origin: PeerId,
+	msg: CollationProtocols<
+		protocol_v1::CollatorProtocolMessage,
+		protocol_v2::CollatorProtocolMessage,
+	>,
+) {
+	use protocol_v1::CollatorProtocolMessage as V1;
+	use protocol_v2::CollatorProtocolMessage as V2;
+	use sp_runtime::traits::AppVerify;
+
+	match msg {
+		CollationProtocols::V2(V2::Declare(collator_id, para_id, signature)) => {
+			let unexpected = match state.peers.get(&origin) {
+				None | Some(PeerState::Collating(..)) => true,
+				Some(PeerState::Connected(_)) => state.collator_peer_id(&collator_id).is_some(),
+			};
+			if unexpected {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = ?origin,
+					?para_id,
+					"Unexpected declare message",
+				);
+				modify_reputation(
+					&mut state.network_reputation,
+					ctx.sender(),
+					origin,
+					COST_UNEXPECTED_MESSAGE,
+				)
+				.await;
+				return
+			}
+
+			if !signature.verify(&*protocol_v1::declare_signature_payload(&origin), &collator_id) {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = ?origin,
+					?para_id,
+					"Signature verification failure",
+				);
+				modify_reputation(
+					&mut state.network_reputation,
+					ctx.sender(),
+					origin,
+					COST_INVALID_SIGNATURE,
+				)
+				.await;
+				return
+			}
+
+			if !state.current_assignments.contains_key(&para_id) {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = ?origin,
+					?collator_id,
+					?para_id,
+					"Declared as collator for unneeded para",
+				);
+				modify_reputation(
+					&mut state.network_reputation,
+					ctx.sender(),
+					origin,
+					COST_UNNEEDED_COLLATOR,
+				)
+				.await;
+				disconnect_peer(ctx.sender(), origin).await;
+				return
+			}
+
+			match state.make_room_for_collator(para_id, &origin) {
+				Ok(None) => {},
+				Ok(Some(evicted)) => {
+					gum::debug!(
+						target: LOG_TARGET,
+						peer_id = ?evicted,
+						?para_id,
+						"Disconnecting lowest scored collator to make room",
+					);
+					disconnect_peer(ctx.sender(), evicted).await;
+				},
+				Err(()) => {
+					gum::debug!(
+						target: LOG_TARGET,
+						peer_id = ?origin,
+						?para_id,
+						"No room for another collator",
+					);
+					disconnect_peer(ctx.sender(), origin).await;
+					return
+				},
+			}
+
+			gum::debug!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				?collator_id,
+				?para_id,
+				score = ?state.reputation.score(&para_id, &origin),
+				"Declared as collator for current para",
+			);
+			state.peers.insert(origin, PeerState::Collating(collator_id, para_id));
+		},
+		CollationProtocols::V2(V2::AdvertiseCollation {
+			relay_parent,
+			candidate_hash,
+			parent_head_data_hash,
+		}) => {
+			let result = handle_advertisement(
+				ctx.sender(),
+				state,
+				relay_parent,
+				origin,
+				ProspectiveCandidate { candidate_hash, parent_head_data_hash },
+			)
+			.await;
+			state.metrics.on_advertisement(result.map_err(|err| err.metric_label()));
+
+			if let Err(err) = result {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = ?origin,
+					?relay_parent,
+					?candidate_hash,
+					error = ?err,
+					"Rejected advertisement",
+				);
+
+				if let Some(rep) = err.reputation_changes() {
+					modify_reputation(&mut state.network_reputation, ctx.sender(), origin, rep)
+						.await;
+				}
+			}
+		},
+		CollationProtocols::V1(_) => {
+			// The network bridge only hands us v1 messages from v1 peers, which we disconnect
+			// right away.
+			modify_reputation(
+				&mut state.network_reputation,
+				ctx.sender(),
+				origin,
+				COST_PROTOCOL_MISUSE,
+			)
+			.await;
+		},
+		CollationProtocols::V2(V2::CollationSeconded(..)) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				"Unexpected `CollationSeconded` message, decreasing reputation",
+			);
+
+			modify_reputation(
+				&mut state.network_reputation,
+				ctx.sender(),
+				origin,
+				COST_UNEXPECTED_MESSAGE,
+			)
+			.await;
+		},
+	}
+}
+
+/// Bridge event switch.
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn handle_network_msg<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	keystore: &KeystorePtr,
+	bridge_message: NetworkBridgeEvent<net_protocol::CollatorProtocolMessage>,
+) -> Result<()> {
+	use NetworkBridgeEvent::*;
+
+	match bridge_message {
+		PeerConnected(peer_id, observed_role, protocol_version, _) => {
+			match CollationVersion::try_from(protocol_version) {
+				Ok(CollationVersion::V2) => {},
+				Ok(CollationVersion::V1) => {
+					gum::debug!(target: LOG_TARGET, ?peer_id, "Disconnecting v1 collator");
+					disconnect_peer(ctx.sender(), peer_id).await;
+					return Ok(())
+				},
+				Err(err) => {
+					// Network bridge is expected to handle this.
+					gum::error!(
+						target: LOG_TARGET,
+						?peer_id,
+						?observed_role,
+						?err,
+						"Unsupported protocol version"
+					);
+					return Ok(())
+				},
+			}
+			state.peers.entry(peer_id).or_insert(PeerState::Connected(Instant::now()));
+			state.metrics.note_collator_peer_count(state.peers.len());
+		},
+		PeerDisconnected(peer_id) => {
+			state.peers.remove(&peer_id);
+			state.remove_advertisements_of(&peer_id);
+			state.metrics.note_collator_peer_count(state.peers.len());
+			state.metrics.note_pending_advertisements(state.pending_advertisements());
+		},
+		NewGossipTopology { .. } => {
+			// impossible!
+		},
+		PeerViewChange(..) => {
+			// Advertisements are pruned when the relay parent leaves our view.
+		},
+		OurViewChange(view) => {
+			handle_our_view_change(ctx.sender(), state, keystore, view).await?;
+		},
+		PeerMessage(remote, msg) => {
+			process_incoming_peer_message(ctx, state, remote, msg).await;
+		},
+		UpdatedAuthorityIds { .. } => {
+			// The validator side doesn't deal with `AuthorityDiscoveryId`s.
+		},
+	}
+
+	Ok(())
+}
+
+// Try seconding any collations which were waiting on the validation of their parent
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn second_unblocked_collations<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	para_id: ParaId,
+	head_data: HeadData,
+	head_data_hash: Hash,
+) {
+	let Some(unblocked_collations) = state
+		.blocked_from_seconding
+		.remove(&BlockedCollationId { para_id, parent_head_data_hash: head_data_hash })
+	else {
+		return
+	};
+
+	for mut unblocked_collation in unblocked_collations {
+		unblocked_collation.maybe_parent_head_data = Some(head_data.clone());
+		let peer_id = unblocked_collation.collation_event.pending_collation.peer_id;
+
+		if let Err(err) = kick_off_seconding(ctx, state, unblocked_collation).await {
+			gum::warn!(
+				target: LOG_TARGET,
+				?para_id,
+				?peer_id,
+				error = %err,
+				"Seconding aborted due to an error",
+			);
+
+			if err.is_malicious() {
+				slash_collator(
+					ctx.sender(),
+					state,
+					para_id,
+					peer_id,
+					INVALID_COLLATION_SLASH,
+					Some(COST_REPORT_BAD),
+				)
+				.await;
+			}
+		}
+	}
+}
+
+/// The main message receiver switch.
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn process_msg<Context>(
+	ctx: &mut Context,
+	keystore: &KeystorePtr,
+	msg: CollatorProtocolMessage,
+	state: &mut State,
+) {
+	use CollatorProtocolMessage::*;
+
+	let _timer = state.metrics.time_process_msg();
+
+	match msg {
+		CollateOn(id) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				para_id = %id,
+				"CollateOn message is not expected on the validator side of the protocol",
+			);
+		},
+		DistributeCollation { .. } => {
+			gum::warn!(
+				target: LOG_TARGET,
+				"DistributeCollation message is not expected on the validator side of the protocol",
+			);
+		},
+		NetworkBridgeUpdate(event) => {
+			if let Err(e) = handle_network_msg(ctx, state, keystore, event).await {
+				gum::warn!(
+					target: LOG_TARGET,
+					err = ?e,
+					"Failed to handle incoming network message",
+				);
+			}
+		},
+		Seconded(parent, stmt) => {
+			let receipt = match stmt.payload() {
+				Statement::Seconded(receipt) => receipt,
+				Statement::Valid(_) => {
+					gum::warn!(
+						target: LOG_TARGET,
+						?stmt,
+						relay_parent = %parent,
+						"Seconded message received with a `Valid` statement",
+					);
+					return
+				},
+			};
+			let output_head_data = receipt.commitments.head_data.clone();
+			let output_head_data_hash = receipt.descriptor.para_head();
+			let fetched_collation = FetchedCollation::from(&receipt.to_plain());
+			let Some(CollationEvent { pending_collation, .. }) =
+				state.fetched_candidates.remove(&fetched_collation)
+			else {
+				gum::debug!(
+					target: LOG_TARGET,
+					relay_parent = ?parent,
+					"Collation has been seconded, but the relay parent is deactivated",
+				);
+				return
+			};
+
+			let PendingCollation { relay_parent, peer_id, para_id, .. } = pending_collation;
+			bump_collator(state, para_id, peer_id, VALID_COLLATION_BUMP);
+			if state.peers.contains_key(&peer_id) {
+				notify_collation_seconded(ctx.sender(), peer_id, relay_parent, stmt).await;
+			}
+
+			if let Some(rp_state) = state.per_relay_parent.get_mut(&parent) {
+				*rp_state.seconded.entry(para_id).or_default() += 1;
+			}
+
+			// See if we've unblocked other collations for seconding.
+			second_unblocked_collations(
+				ctx,
+				state,
+				fetched_collation.para_id,
+				output_head_data,
+				output_head_data_hash,
+			)
+			.await;
+
+			fetch_eligible_collations(ctx.sender(), state).await;
+		},
+		Invalid(parent, candidate_receipt) => {
+			// Remove collations which were blocked from seconding and had this candidate as parent.
+			state.blocked_from_seconding.remove(&BlockedCollationId {
+				para_id: candidate_receipt.descriptor.para_id(),
+				parent_head_data_hash: candidate_receipt.descriptor.para_head(),
+			});
+
+			let fetched_collation = FetchedCollation::from(&candidate_receipt);
+			let pending_collation = match state.fetched_candidates.entry(fetched_collation) {
+				Entry::Occupied(entry)
+					if entry.get().pending_collation.commitments_hash ==
+						Some(candidate_receipt.commitments_hash) =>
+					entry.remove().pending_collation,
+				Entry::Occupied(_) => {
+					gum::error!(
+						target: LOG_TARGET,
+						relay_parent = ?parent,
+						candidate = ?candidate_receipt.hash(),
+						"Reported invalid candidate for unknown `pending_candidate`!",
+					);
+					return
+				},
+				Entry::Vacant(_) => return,
+			};
+
+			slash_collator(
+				ctx.sender(),
+				state,
+				pending_collation.para_id,
+				pending_collation.peer_id,
+				INVALID_COLLATION_SLASH,
+				Some(COST_REPORT_BAD),
+			)
+			.await;
+
+			fetch_eligible_collations(ctx.sender(), state).await;
+		},
+	}
+}
+
+/// The main run loop.
 #[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
 pub(crate) async fn run<Context>(
-	_ctx: Context,
-	_keystore: KeystorePtr,
-	_metrics: Metrics,
+	ctx: Context,
+	keystore: KeystorePtr,
+	metrics: Metrics,
+	db: Arc<dyn Database>,
+	db_config: DbConfig,
 ) -> std::result::Result<(), std::convert::Infallible> {
+	run_inner(
+		ctx,
+		keystore,
+		metrics,
+		db,
+		db_config,
+		ReputationAggregator::default(),
+		REPUTATION_CHANGE_INTERVAL,
+	)
+	.await
+}
+
+/// Write the changed scores to the database. Failures are logged, the scores are kept in memory.
+fn flush_reputation(reputation_db: &mut ReputationDb, state: &mut State) {
+	if let Err(err) = reputation_db.flush(&mut state.reputation) {
+		gum::warn!(target: LOG_TARGET, ?err, "Failed to persist the collator reputation");
+	}
+}
+
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn run_inner<Context>(
+	mut ctx: Context,
+	keystore: KeystorePtr,
+	metrics: Metrics,
+	db: Arc<dyn Database>,
+	db_config: DbConfig,
+	network_reputation: ReputationAggregator,
+	reputation_interval: Duration,
+) -> std::result::Result<(), std::convert::Infallible> {
+	let new_reputation_delay = || futures_timer::Delay::new(reputation_interval).fuse();
+	let mut reputation_delay = new_reputation_delay();
+
+	let (mut reputation_db, reputation) =
+		ReputationDb::load(db, db_config, MAX_STORED_SCORES_PER_PARA, db::now());
+	let mut state = State::new(metrics, network_reputation, reputation);
+	state.metrics.note_stored_scores(state.reputation.len());
+
+	let next_inactivity_stream = tick_stream(ACTIVITY_POLL);
+	futures::pin_mut!(next_inactivity_stream);
+
+	let decay_stream = tick_stream(REPUTATION_DECAY_INTERVAL);
+	futures::pin_mut!(decay_stream);
+
+	let flush_stream = tick_stream(REPUTATION_FLUSH_INTERVAL);
+	futures::pin_mut!(flush_stream);
+
+	let mut network_error_freq = gum::Freq::new();
+	let mut canceled_freq = gum::Freq::new();
+
+	loop {
+		select! {
+			_ = reputation_delay => {
+				state.network_reputation.send(ctx.sender()).await;
+				reputation_delay = new_reputation_delay();
+			},
+			res = ctx.recv().fuse() => {
+				match res {
+					Ok(FromOrchestra::Communication { msg }) => {
+						gum::trace!(target: LOG_TARGET, msg = ?msg, "received a message");
+						process_msg(
+							&mut ctx,
+							&keystore,
+							msg,
+							&mut state,
+						).await;
+					}
+					Ok(FromOrchestra::Signal(OverseerSignal::Conclude)) | Err(_) => break,
+					Ok(FromOrchestra::Signal(_)) => continue,
+				}
+			},
+			_ = next_inactivity_stream.next() => {
+				disconnect_undeclared_peers(ctx.sender(), &state).await;
+			},
+			_ = decay_stream.next() => {
+				state.reputation.decay();
+				reputation_db.note_decay(db::now());
+				state.metrics.note_stored_scores(state.reputation.len());
+			},
+			_ = flush_stream.next() => {
+				flush_reputation(&mut reputation_db, &mut state);
+			},
+			_ = state.fetch_delays.select_next_some() => {
+				fetch_eligible_collations(ctx.sender(), &mut state).await;
+			},
+			resp = state.collation_requests.select_next_some() => {
+				let fetched = handle_collation_fetch_response(
+					ctx.sender(),
+					&mut state,
+					resp,
+					&mut network_error_freq,
+					&mut canceled_freq,
+				).await;
+
+				if let Some(fetched) = fetched {
+					let PendingCollation { relay_parent, para_id, peer_id, .. } =
+						fetched.collation_event.pending_collation;
+					if let Err(err) = kick_off_seconding(&mut ctx, &mut state, fetched).await {
+						gum::warn!(
+							target: LOG_TARGET,
+							?relay_parent,
+							?para_id,
+							?peer_id,
+							error = %err,
+							"Seconding aborted due to an error",
+						);
+
+						if err.is_malicious() {
+							slash_collator(
+								ctx.sender(),
+								&mut state,
+								para_id,
+								peer_id,
+								INVALID_COLLATION_SLASH,
+								Some(COST_REPORT_BAD),
+							).await;
+						}
+					}
+				}
+
+				// Either a claim was freed or the collation was sent to backing and the claim is
+				// kept. Both ways, we may be able to fetch something else.
+				fetch_eligible_collations(ctx.sender(), &mut state).await;
+			},
+		}
+	}
+
+	flush_reputation(&mut reputation_db, &mut state);
+
 	Ok(())
 }
+
+// Disconnect peers which did not declare in time. Their metadata is cleaned up upon receipt of the
+// `PeerDisconnected` event.
+async fn disconnect_undeclared_peers(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &State,
+) {
+	let undeclared = CollatorEvictionPolicy::default().undeclared;
+	for (peer, peer_state) in &state.peers {
+		if matches!(peer_state, PeerState::Connected(since) if since.elapsed() >= undeclared) {
+			gum::trace!(target: LOG_TARGET, ?peer, "Disconnecting undeclared peer");
+			disconnect_peer(sender, *peer).await;
+		}
+	}
+}
+
+/// Handle a collation fetch response, adjusting the collator's score on failure.
+async fn handle_collation_fetch_response(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	state: &mut State,
+	response: <CollationFetchRequest as Future>::Output,
+	network_error_freq: &mut gum::Freq,
+	canceled_freq: &mut gum::Freq,
+) -> Option<PendingCollationFetch> {
+	let (collation_event, response) = response;
+	let pending_collation = collation_event.pending_collation;
+	// Remove the cancellation handle, as the future already completed.
+	state.collation_requests_cancel_handles.remove(&pending_collation);
+
+	let PendingCollation { relay_parent, para_id, peer_id, .. } = pending_collation;
+
+	let (slash, rep) = match response {
+		Err(CollationFetchError::Cancelled) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				hash = ?relay_parent,
+				?para_id,
+				?peer_id,
+				"Request was cancelled from the validator side"
+			);
+			return None
+		},
+		Err(CollationFetchError::Request(RequestError::InvalidResponse(err))) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				hash = ?relay_parent,
+				?para_id,
+				?peer_id,
+				err = ?err,
+				"Collator provided response that could not be decoded"
+			);
+			(FAILED_FETCH_SLASH, Some(COST_CORRUPTED_MESSAGE))
+		},
+		Err(CollationFetchError::Request(err)) if err.is_timed_out() => {
+			gum::debug!(
+				target: LOG_TARGET,
+				hash = ?relay_parent,
+				?para_id,
+				?peer_id,
+				"Request timed out"
+			);
+			// The collator took a claim queue spot and didn't deliver. Only the score is
+			// affected, the network reputation is not.
+			(FAILED_FETCH_SLASH, None)
+		},
+		Err(CollationFetchError::Request(RequestError::NetworkError(err))) => {
+			gum::warn_if_frequent!(
+				freq: network_error_freq,
+				max_rate: gum::Times::PerHour(100),
+				target: LOG_TARGET,
+				hash = ?relay_parent,
+				?para_id,
+				?peer_id,
+				err = ?err,
+				"Fetching collation failed due to network error"
+			);
+			(FAILED_FETCH_SLASH, Some(COST_NETWORK_ERROR))
+		},
+		Err(CollationFetchError::Request(RequestError::Canceled(err))) => {
+			gum::warn_if_frequent!(
+				freq: canceled_freq,
+				max_rate: gum::Times::PerHour(100),
+				target: LOG_TARGET,
+				hash = ?relay_parent,
+				?para_id,
+				?peer_id,
+				err = ?err,
+				"Canceled should be handled by `is_timed_out` above - this is a bug!"
+			);
+			state.metrics.on_request(Err(()));
+			return None
+		},
+		Ok(
+			request_v2::CollationFetchingResponse::Collation(receipt, _) |
+			request_v2::CollationFetchingResponse::CollationWithParentHeadData { receipt, .. },
+		) if receipt.descriptor().para_id() != para_id => {
+			gum::debug!(
+				target: LOG_TARGET,
+				expected_para_id = ?para_id,
+				got_para_id = ?receipt.descriptor().para_id(),
+				?peer_id,
+				"Got wrong para ID for requested collation."
+			);
+			(INVALID_COLLATION_SLASH, Some(COST_WRONG_PARA))
+		},
+		Ok(request_v2::CollationFetchingResponse::Collation(candidate_receipt, pov)) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				%para_id,
+				hash = ?relay_parent,
+				candidate_hash = ?candidate_receipt.hash(),
+				"Received collation",
+			);
+			state.metrics.on_request(Ok(()));
+			return Some(PendingCollationFetch {
+				collation_event,
+				candidate_receipt,
+				pov,
+				maybe_parent_head_data: None,
+			})
+		},
+		Ok(request_v2::CollationFetchingResponse::CollationWithParentHeadData {
+			receipt,
+			pov,
+			parent_head_data,
+		}) => {
+			gum::debug!(
+				target: LOG_TARGET,
+				%para_id,
+				hash = ?relay_parent,
+				candidate_hash = ?receipt.hash(),
+				"Received collation (v3)",
+			);
+			state.metrics.on_request(Ok(()));
+			return Some(PendingCollationFetch {
+				collation_event,
+				candidate_receipt: receipt,
+				pov,
+				maybe_parent_head_data: Some(parent_head_data),
+			})
+		},
+	};
+
+	state.metrics.on_request(Err(()));
+	slash_collator(sender, state, para_id, peer_id, slash, rep).await;
+	None
+}
+
+async fn request_prospective_validation_data<Sender>(
+	sender: &mut Sender,
+	candidate_relay_parent: Hash,
+	parent_head_data_hash: Hash,
+	para_id: ParaId,
+	maybe_parent_head_data: Option<HeadData>,
+) -> std::result::Result<Option<PersistedValidationData>, SecondingError>
+where
+	Sender: CollatorProtocolSenderTrait,
+{
+	let (tx, rx) = oneshot::channel();
+
+	let parent_head_data = if let Some(head_data) = maybe_parent_head_data {
+		ParentHeadData::WithData { head_data, hash: parent_head_data_hash }
+	} else {
+		ParentHeadData::OnlyHash(parent_head_data_hash)
+	};
+
+	let request =
+		ProspectiveValidationDataRequest { para_id, candidate_relay_parent, parent_head_data };
+
+	sender
+		.send_message(ProspectiveParachainsMessage::GetProspectiveValidationData(request, tx))
+		.await;
+
+	rx.await.map_err(SecondingError::CancelledProspectiveValidationData)
+}
+
+/// Handle a fetched collation result.
+#[overseer::contextbounds(CollatorProtocol, prefix = self::overseer)]
+async fn kick_off_seconding<Context>(
+	ctx: &mut Context,
+	state: &mut State,
+	PendingCollationFetch { mut collation_event, candidate_receipt, pov, maybe_parent_head_data }: PendingCollationFetch,
+) -> std::result::Result<(), SecondingError> {
+	let pending_collation = collation_event.pending_collation;
+	let relay_parent = pending_collation.relay_parent;
+
+	let Some(per_relay_parent) = state.per_relay_parent.get(&relay_parent) else {
+		// Relay parent went out of view, not an error.
+		gum::trace!(
+			target: LOG_TARGET,
+			relay_parent = ?relay_parent,
+			"Fetched collation for a parent out of view",
+		);
+		return Ok(())
+	};
+
+	// Sanity check of the candidate receipt version.
+	descriptor_version_sanity_check(candidate_receipt.descriptor(), per_relay_parent)?;
+
+	let fetched_collation = FetchedCollation::from(&candidate_receipt);
+	if state.fetched_candidates.contains_key(&fetched_collation) {
+		return Err(SecondingError::Duplicate)
+	}
+
+	collation_event.pending_collation.commitments_hash = Some(candidate_receipt.commitments_hash);
+
+	// `request_collation` only issues requests for v2 advertisements.
+	let Some(ProspectiveCandidate { parent_head_data_hash, .. }) =
+		pending_collation.prospective_candidate
+	else {
+		return Ok(())
+	};
+
+	let maybe_pvd = request_prospective_validation_data(
+		ctx.sender(),
+		relay_parent,
+		parent_head_data_hash,
+		pending_collation.para_id,
+		maybe_parent_head_data.clone(),
+	)
+	.await?;
+
+	let pvd = match (maybe_pvd, maybe_parent_head_data.clone()) {
+		(Some(pvd), _) => pvd,
+		(None, None) => {
+			// Neither the collator nor prospective-parachains could supply the parent head
+			// data. Wait until we second the parent.
+			gum::debug!(
+				target: LOG_TARGET,
+				candidate_hash = ?candidate_receipt.hash(),
+				?relay_parent,
+				"Collation having parent head data hash {} is blocked from seconding. Waiting on its parent to be validated.",
+				parent_head_data_hash
+			);
+			state
+				.blocked_from_seconding
+				.entry(BlockedCollationId {
+					para_id: candidate_receipt.descriptor.para_id(),
+					parent_head_data_hash,
+				})
+				.or_insert_with(Vec::new)
+				.push(PendingCollationFetch {
+					collation_event,
+					candidate_receipt,
+					pov,
+					maybe_parent_head_data: None,
+				});
+			return Ok(())
+		},
+		(None, Some(_)) => return Err(SecondingError::PersistedValidationDataNotFound),
+	};
+
+	fetched_collation_sanity_check(
+		&collation_event.pending_collation,
+		&candidate_receipt,
+		&pvd,
+		maybe_parent_head_data.map(|head| (head, parent_head_data_hash)),
+	)?;
+
+	ctx.send_message(CandidateBackingMessage::Second(relay_parent, candidate_receipt, pvd, pov))
+		.await;
+	state.fetched_candidates.insert(fetched_collation, collation_event);
+
+	Ok(())
+}
+
+// Sanity check the candidate descriptor version.
+fn descriptor_version_sanity_check(
+	descriptor: &CandidateDescriptorV2,
+	per_relay_parent: &PerRelayParent,
+) -> std::result::Result<(), SecondingError> {
+	match descriptor.version() {
+		CandidateDescriptorVersion::V1 => Ok(()),
+		CandidateDescriptorVersion::V2 if per_relay_parent.v2_receipts => {
+			if let Some(core_index) = descriptor.core_index() {
+				if core_index != per_relay_parent.current_core {
+					return Err(SecondingError::InvalidCoreIndex(
+						core_index.0,
+						per_relay_parent.current_core.0,
+					))
+				}
+			}
+
+			if let Some(session_index) = descriptor.session_index() {
+				if session_index != per_relay_parent.session_index {
+					return Err(SecondingError::InvalidSessionIndex(
+						session_index,
+						per_relay_parent.session_index,
+					))
+				}
+			}
+
+			Ok(())
+		},
+		descriptor_version => Err(SecondingError::InvalidReceiptVersion(descriptor_version)),
+	}
+}
@@ -0,0 +1,242 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-para collator reputation.
+//!
+//! Unlike the network level reputation, which is reset once a peer disconnects, these scores are
+//! kept for the lifetime of the subsystem, regardless of connections, relay chain leaves or session
+//! changes. A collator which builds valid blocks earns priority over newcomers, while a collator
+//! which advertises and then withholds, or sends invalid collations, loses it. Scores slowly decay,
+//! so a collator which stops collating eventually has to earn its priority again.
+//!
+//! The scores are persisted (see [`super::db`]), so they also survive restarts. The store keeps
+//! track of the paras whose scores changed since the last flush.
+
+use std::collections::{HashMap, HashSet};
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_primitives::Id as ParaId;
+
+use super::common::{Score, REPUTATION_DECAY_DIVISOR};
+
+/// Stores the reputation scores of collators per para.
+///
+/// Collators which have no entry have a score of zero.
+pub struct ReputationStore {
+	scores: HashMap<ParaId, HashMap<PeerId, Score>>,
+	max_per_para: usize,
+	changed: HashSet<ParaId>,
+}
+
+impl ReputationStore {
+	/// Create an empty store keeping at most `max_per_para` scores per para.
+	pub fn new(max_per_para: usize) -> Self {
+		Self { scores: HashMap::new(), max_per_para, changed: HashSet::new() }
+	}
+
+	/// Restore a persisted score, without marking the para as changed.
+	pub fn restore(&mut self, para_id: ParaId, peer_id: PeerId, score: Score) {
+		let scores = self.scores.entry(para_id).or_default();
+		if score != Score::default() && scores.len() < self.max_per_para {
+			scores.insert(peer_id, score);
+		}
+		if scores.is_empty() {
+			self.scores.remove(&para_id);
+		}
+	}
+
+	/// The score of `peer_id` for `para_id`.
+	pub fn score(&self, para_id: &ParaId, peer_id: &PeerId) -> Score {
+		self.scores
+			.get(para_id)
+			.and_then(|scores| scores.get(peer_id))
+			.copied()
+			.unwrap_or_default()
+	}
+
+	/// Increase the score of `peer_id` for `para_id` and return the new score.
+	///
+	/// If the para already has the maximum number of entries, the lowest score is dropped to make
+	/// room, unless it is not lower than the new one.
+	pub fn bump(&mut self, para_id: ParaId, peer_id: PeerId, amount: u16) -> Score {
+		let scores = self.scores.entry(para_id).or_default();
+		if let Some(score) = scores.get_mut(&peer_id) {
+			*score = score.saturating_add(amount);
+			self.changed.insert(para_id);
+			return *score
+		}
+
+		let new_score = Score::default().saturating_add(amount);
+		if scores.len() >= self.max_per_para {
+			let lowest = scores.iter().min_by_key(|(_, score)| **score).map(|(p, s)| (*p, *s));
+			match lowest {
+				Some((lowest_peer, lowest_score)) if lowest_score < new_score => {
+					scores.remove(&lowest_peer);
+				},
+				_ => {
+					if scores.is_empty() {
+						self.scores.remove(&para_id);
+					}
+					return new_score
+				},
+			}
+		}
+		scores.insert(peer_id, new_score);
+		self.changed.insert(para_id);
+		new_score
+	}
+
+	/// Decrease the score of `peer_id` for `para_id` and return the new score.
+	pub fn slash(&mut self, para_id: ParaId, peer_id: PeerId, amount: u16) -> Score {
+		let Some(scores) = self.scores.get_mut(&para_id) else { return Score::default() };
+		let Some(score) = scores.get_mut(&peer_id) else { return Score::default() };
+
+		*score = score.saturating_sub(amount);
+		let new_score = *score;
+		self.changed.insert(para_id);
+		if new_score == Score::default() {
+			scores.remove(&peer_id);
+			if scores.is_empty() {
+				self.scores.remove(&para_id);
+			}
+		}
+		new_score
+	}
+
+	/// Decay all the scores, dropping the ones reaching zero.
+	pub fn decay(&mut self) {
+		self.changed.extend(self.scores.keys().copied());
+		self.scores.retain(|_, scores| {
+			scores.retain(|_, score| {
+				let decrease = (score.value() / REPUTATION_DECAY_DIVISOR).max(1);
+				*score = score.saturating_sub(decrease);
+				*score != Score::default()
+			});
+			!scores.is_empty()
+		});
+	}
+
+	/// Number of stored scores, over all paras.
+	pub fn len(&self) -> usize {
+		self.scores.values().map(HashMap::len).sum()
+	}
+
+	/// The paras whose scores changed since the last call, with all their current scores.
+	///
+	/// Paras without any score left are returned with no scores.
+	pub fn drain_changes(&mut self) -> Vec<(ParaId, Vec<(PeerId, Score)>)> {
+		self.changed
+			.drain()
+			.map(|para_id| {
+				let scores = self
+					.scores
+					.get(&para_id)
+					.into_iter()
+					.flatten()
+					.map(|(peer_id, score)| (*peer_id, *score))
+					.collect();
+				(para_id, scores)
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::validator_side_experimental::common::MAX_SCORE;
+
+	#[test]
+	fn unknown_collators_have_zero_score() {
+		let mut store = ReputationStore::new(10);
+		let peer = PeerId::random();
+
+		assert_eq!(store.score(&ParaId::from(1), &peer), Score::default());
+		assert_eq!(store.slash(ParaId::from(1), peer, 100), Score::default());
+		assert_eq!(store.len(), 0);
+	}
+
+	#[test]
+	fn scores_are_per_para_and_capped() {
+		let mut store = ReputationStore::new(10);
+		let peer = PeerId::random();
+
+		assert_eq!(store.bump(ParaId::from(1), peer, 100), Score::new(100));
+		assert_eq!(store.bump(ParaId::from(1), peer, u16::MAX), Score::new(MAX_SCORE));
+		assert_eq!(store.score(&ParaId::from(2), &peer), Score::default());
+
+		assert_eq!(store.slash(ParaId::from(1), peer, 400), Score::new(MAX_SCORE - 400));
+		assert_eq!(store.slash(ParaId::from(1), peer, u16::MAX), Score::default());
+		assert_eq!(store.len(), 0);
+	}
+
+	#[test]
+	fn lowest_score_is_evicted_when_full() {
+		let mut store = ReputationStore::new(2);
+		let para = ParaId::from(1);
+		let (low, high, new) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+		store.bump(para, low, 10);
+		store.bump(para, high, 500);
+
+		// A newcomer not scoring higher than the lowest entry is not stored.
+		assert_eq!(store.bump(para, new, 10), Score::new(10));
+		assert_eq!(store.score(&para, &new), Score::default());
+
+		store.bump(para, new, 100);
+		assert_eq!(store.score(&para, &new), Score::new(100));
+		assert_eq!(store.score(&para, &low), Score::default());
+		assert_eq!(store.score(&para, &high), Score::new(500));
+		assert_eq!(store.len(), 2);
+	}
+
+	#[test]
+	fn decay_works() {
+		let mut store = ReputationStore::new(10);
+		let para = ParaId::from(1);
+		let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+
+		store.bump(para, peer_a, 1_000);
+		store.bump(para, peer_b, 1);
+
+		store.decay();
+		assert_eq!(store.score(&para, &peer_a), Score::new(950));
+		assert_eq!(store.score(&para, &peer_b), Score::default());
+		assert_eq!(store.len(), 1);
+	}
+
+	#[test]
+	fn changes_are_tracked_per_para() {
+		let mut store = ReputationStore::new(10);
+		let (para_a, para_b) = (ParaId::from(1), ParaId::from(2));
+		let peer = PeerId::random();
+
+		store.restore(para_a, peer, Score::new(10));
+		assert_eq!(store.score(&para_a, &peer), Score::new(10));
+		assert!(store.drain_changes().is_empty());
+
+		store.bump(para_b, peer, 20);
+		assert_eq!(store.drain_changes(), vec![(para_b, vec![(peer, Score::new(20))])]);
+		assert!(store.drain_changes().is_empty());
+
+		// Paras which lost all their scores are reported empty.
+		store.slash(para_a, peer, 10);
+		assert_eq!(store.drain_changes(), vec![(para_a, vec![])]);
+
+		store.decay();
+		assert_eq!(store.drain_changes(), vec![(para_b, vec![(peer, Score::new(19))])]);
+	}
+}
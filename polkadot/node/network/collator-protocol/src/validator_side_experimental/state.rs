@@ -0,0 +1,440 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Subsystem state and the collation selection policy.
+//!
+//! An advertisement is accepted only if the advertising collator declared for a para which is in
+//! the claim queue of our core at the advertised relay parent, and if there is still an unclaimed
+//! spot for the para in the claim queue. Collations being fetched, waiting for validation, blocked
+//! from seconding or seconded all count as claims.
+//!
+//! Accepted advertisements are not fetched in the order they arrive. Whenever a claim is free, the
+//! unclaimed claim queue entries are visited in order and, for the first para with an eligible
+//! advertisement, the advertisement of the highest scored collator is fetched. Advertisements
+//! from collators scoring below [`INSTANT_FETCH_REP_THRESHOLD`] only become eligible after
+//! [`UNDER_THRESHOLD_FETCH_DELAY`], so newcomers can't steal a slot from reputable collators.
+
+use std::{
+	cmp::Reverse,
+	collections::{HashMap, HashSet},
+	time::Instant,
+};
+
+use futures::stream::FuturesUnordered;
+use futures_timer::Delay;
+use tokio_util::sync::CancellationToken;
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_node_subsystem_util::{
+	backing_implicit_view::View as ImplicitView, reputation::ReputationAggregator,
+};
+use polkadot_primitives::{CollatorId, CoreIndex, Hash, Id as ParaId, SessionIndex};
+
+use super::{
+	common::{
+		Advertisement, Score, INSTANT_FETCH_REP_THRESHOLD, MAX_CONNECTED_PER_PARA,
+		UNDER_THRESHOLD_FETCH_DELAY,
+	},
+	error::{Error, Result},
+	reputation::ReputationStore,
+	Metrics,
+};
+use crate::validator_side::{
+	claim_queue_state::ClaimQueueState,
+	collation::{
+		BlockedCollationId, CollationEvent, CollationFetchRequest, FetchedCollation,
+		PendingCollation, PendingCollationFetch, ProspectiveCandidate,
+	},
+};
+
+/// State of a connected peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerState {
+	/// Connected, but not declared yet.
+	Connected(Instant),
+	/// Declared as a collator for the given para.
+	Collating(CollatorId, ParaId),
+}
+
+/// State tracked per relay parent in our view.
+#[derive(Debug)]
+pub struct PerRelayParent {
+	/// The claim queue of our core at this relay parent.
+	pub assignments: Vec<ParaId>,
+	/// Our core at this relay parent.
+	pub current_core: CoreIndex,
+	/// Session of the child of the relay parent.
+	pub session_index: SessionIndex,
+	/// Whether v2 candidate receipts are enabled.
+	pub v2_receipts: bool,
+	/// Number of collations seconded per para.
+	pub seconded: HashMap<ParaId, usize>,
+	/// Accepted advertisements which were not fetched yet.
+	pub advertisements: Vec<Advertisement>,
+}
+
+impl PerRelayParent {
+	pub fn new(
+		assignments: Vec<ParaId>,
+		current_core: CoreIndex,
+		session_index: SessionIndex,
+		v2_receipts: bool,
+	) -> Self {
+		Self {
+			assignments,
+			current_core,
+			session_index,
+			v2_receipts,
+			seconded: HashMap::new(),
+			advertisements: Vec::new(),
+		}
+	}
+}
+
+/// Reasons for rejecting an advertisement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvertisementError {
+	/// Relay parent is not in our view.
+	RelayParentUnknown,
+	/// Peer is not connected.
+	UnknownPeer,
+	/// Peer has not declared its para id.
+	UndeclaredCollator,
+	/// The para is not in our claim queue at the relay parent.
+	InvalidAssignment,
+	/// The candidate was already advertised, fetched or seconded.
+	Duplicate,
+	/// The peer advertised more candidates than there are claims for its para.
+	PeerLimitReached,
+	/// All the claims for the para are taken.
+	SecondedLimitReached,
+	/// The backing subsystem doesn't allow seconding the candidate.
+	BlockedByBacking,
+}
+
+/// All state relevant for the experimental validator side.
+pub struct State {
+	/// Implicit view of the active leaves.
+	pub implicit_view: ImplicitView,
+	/// All active leaves observed by us.
+	pub active_leaves: HashSet<Hash>,
+	/// State tracked per relay parent.
+	pub per_relay_parent: HashMap<Hash, PerRelayParent>,
+	/// Connected peers.
+	pub peers: HashMap<PeerId, PeerState>,
+	/// Paras in the claim queue of our core at any relay parent in view, with the number of
+	/// relay parents they appear at.
+	pub current_assignments: HashMap<ParaId, usize>,
+	/// Collator scores.
+	pub reputation: ReputationStore,
+	/// The collations being fetched.
+	pub collation_requests: FuturesUnordered<CollationFetchRequest>,
+	/// Cancellation handles for the collations being fetched.
+	pub collation_requests_cancel_handles: HashMap<PendingCollation, CancellationToken>,
+	/// Collations sent to backing and waiting for validation.
+	pub fetched_candidates: HashMap<FetchedCollation, CollationEvent>,
+	/// Collations which can't be seconded until their parent is.
+	pub blocked_from_seconding: HashMap<BlockedCollationId, Vec<PendingCollationFetch>>,
+	/// Timers for advertisements which are not eligible for fetching yet. When one fires, the
+	/// pending advertisements are checked again.
+	pub fetch_delays: FuturesUnordered<Delay>,
+	/// Aggregated network reputation changes.
+	pub network_reputation: ReputationAggregator,
+	/// Metrics.
+	pub metrics: Metrics,
+}
+
+impl State {
+	pub fn new(
+		metrics: Metrics,
+		network_reputation: ReputationAggregator,
+		reputation: ReputationStore,
+	) -> Self {
+		Self {
+			implicit_view: Default::default(),
+			active_leaves: Default::default(),
+			per_relay_parent: Default::default(),
+			peers: Default::default(),
+			current_assignments: Default::default(),
+			reputation,
+			collation_requests: Default::default(),
+			collation_requests_cancel_handles: Default::default(),
+			fetched_candidates: Default::default(),
+			blocked_from_seconding: Default::default(),
+			fetch_delays: Default::default(),
+			network_reputation,
+			metrics,
+		}
+	}
+
+	/// The para the peer declared for, if any.
+	pub fn collating_para(&self, peer_id: &PeerId) -> Option<ParaId> {
+		match self.peers.get(peer_id) {
+			Some(PeerState::Collating(_, para_id)) => Some(*para_id),
+			_ => None,
+		}
+	}
+
+	/// The collator id the peer declared with, if any.
+	pub fn collator_id(&self, peer_id: &PeerId) -> Option<&CollatorId> {
+		match self.peers.get(peer_id) {
+			Some(PeerState::Collating(collator_id, _)) => Some(collator_id),
+			_ => None,
+		}
+	}
+
+	/// Find the peer which declared with `collator_id`.
+	pub fn collator_peer_id(&self, collator_id: &CollatorId) -> Option<PeerId> {
+		self.peers.iter().find_map(|(peer_id, state)| match state {
+			PeerState::Collating(id, _) if id == collator_id => Some(*peer_id),
+			_ => None,
+		})
+	}
+
+	/// Decide whether a collator which wants to declare for `para_id` may stay connected.
+	///
+	/// Returns `Ok(None)` if there is room, `Ok(Some(peer))` if `peer` has to be disconnected to
+	/// make room and `Err(())` if the newcomer has to be disconnected.
+	pub fn make_room_for_collator(
+		&self,
+		para_id: ParaId,
+		peer_id: &PeerId,
+	) -> std::result::Result<Option<PeerId>, ()> {
+		let connected = self
+			.peers
+			.iter()
+			.filter(|(_, state)| matches!(state, PeerState::Collating(_, para) if *para == para_id))
+			.map(|(peer, _)| (*peer, self.reputation.score(&para_id, peer)))
+			.collect::<Vec<_>>();
+
+		if connected.len() < MAX_CONNECTED_PER_PARA {
+			return Ok(None)
+		}
+
+		let score = self.reputation.score(&para_id, peer_id);
+		match connected.into_iter().min_by_key(|(_, score)| *score) {
+			Some((lowest, lowest_score)) if lowest_score < score => Ok(Some(lowest)),
+			_ => Err(()),
+		}
+	}
+
+	/// The number of collations for `para_id` at `relay_parent` which hold a claim: fetching,
+	/// waiting for validation, blocked from seconding or seconded.
+	fn claims_for_para(&self, relay_parent: &Hash, para_id: &ParaId) -> usize {
+		let seconded = self
+			.per_relay_parent
+			.get(relay_parent)
+			.and_then(|rp_state| rp_state.seconded.get(para_id))
+			.copied()
+			.unwrap_or_default();
+
+		let fetching = self
+			.collation_requests_cancel_handles
+			.keys()
+			.filter(|pc| pc.relay_parent == *relay_parent && pc.para_id == *para_id)
+			.count();
+
+		let waiting_for_validation = self
+			.fetched_candidates
+			.keys()
+			.filter(|fc| fc.relay_parent == *relay_parent && fc.para_id == *para_id)
+			.count();
+
+		let blocked_from_seconding = self
+			.blocked_from_seconding
+			.values()
+			.flatten()
+			.filter(|pc| {
+				pc.candidate_receipt.descriptor.para_id() == *para_id &&
+					pc.candidate_receipt.descriptor.relay_parent() == *relay_parent
+			})
+			.count();
+
+		seconded + fetching + waiting_for_validation + blocked_from_seconding
+	}
+
+	/// Build the claim queue state for every path through `relay_parent`, with the existing
+	/// claims of `paras` applied.
+	fn claim_queue_states(
+		&self,
+		relay_parent: &Hash,
+		paras: &HashSet<ParaId>,
+	) -> Result<Vec<ClaimQueueState>> {
+		let mut states = Vec::new();
+		for path in self.implicit_view.paths_via_relay_parent(relay_parent) {
+			let mut cq_state = ClaimQueueState::new();
+			for ancestor in &path {
+				let rp_state =
+					self.per_relay_parent.get(ancestor).ok_or(Error::RelayParentStateNotFound)?;
+				cq_state.add_leaf(ancestor, &rp_state.assignments);
+				for para_id in paras {
+					for _ in 0..self.claims_for_para(ancestor, para_id) {
+						cq_state.claim_at(ancestor, para_id);
+					}
+				}
+			}
+			states.push(cq_state);
+		}
+		Ok(states)
+	}
+
+	/// Whether there is still a free claim for `para_id` at `relay_parent` on at least one path.
+	pub fn can_claim(&self, relay_parent: &Hash, para_id: ParaId) -> Result<bool> {
+		let paras = [para_id].into_iter().collect();
+		Ok(self
+			.claim_queue_states(relay_parent, &paras)?
+			.iter_mut()
+			.any(|cq_state| cq_state.can_claim_at(relay_parent, &para_id)))
+	}
+
+	/// The unclaimed claim queue entries at `relay_parent`, in claim queue order.
+	///
+	/// Of all the paths through the relay parent the one with most unclaimed entries is used.
+	pub fn unfulfilled_claims(&self, relay_parent: &Hash) -> Result<Vec<ParaId>> {
+		let rp_state =
+			self.per_relay_parent.get(relay_parent).ok_or(Error::RelayParentStateNotFound)?;
+		let paras = rp_state.assignments.iter().copied().collect();
+
+		Ok(self
+			.claim_queue_states(relay_parent, &paras)?
+			.iter_mut()
+			.map(|cq_state| cq_state.unclaimed_at(relay_parent))
+			.max_by_key(|unclaimed| unclaimed.len())
+			.unwrap_or_default())
+	}
+
+	/// Check an advertisement against the claim queue and the peer's state and store it for
+	/// fetching.
+	///
+	/// Returns whether the advertisement is eligible for an instant fetch.
+	pub fn insert_advertisement(
+		&mut self,
+		peer_id: PeerId,
+		relay_parent: Hash,
+		prospective_candidate: ProspectiveCandidate,
+		now: Instant,
+	) -> std::result::Result<bool, AdvertisementError> {
+		let para_id = match self.peers.get(&peer_id) {
+			None => return Err(AdvertisementError::UnknownPeer),
+			Some(PeerState::Connected(_)) => return Err(AdvertisementError::UndeclaredCollator),
+			Some(PeerState::Collating(_, para_id)) => *para_id,
+		};
+
+		let rp_state = self
+			.per_relay_parent
+			.get(&relay_parent)
+			.ok_or(AdvertisementError::RelayParentUnknown)?;
+
+		let claims = rp_state.assignments.iter().filter(|para| **para == para_id).count();
+		if claims == 0 {
+			return Err(AdvertisementError::InvalidAssignment)
+		}
+
+		let candidate_hash = prospective_candidate.candidate_hash;
+		let known = rp_state
+			.advertisements
+			.iter()
+			.any(|adv| adv.prospective_candidate.candidate_hash == candidate_hash) ||
+			self.collation_requests_cancel_handles.keys().any(|pc| {
+				pc.prospective_candidate.map(|pc| pc.candidate_hash) == Some(candidate_hash)
+			}) || self.fetched_candidates.keys().any(|fc| fc.candidate_hash == candidate_hash);
+		if known {
+			return Err(AdvertisementError::Duplicate)
+		}
+
+		let by_peer = rp_state.advertisements.iter().filter(|adv| adv.peer_id == peer_id).count();
+		if by_peer >= claims {
+			return Err(AdvertisementError::PeerLimitReached)
+		}
+
+		if !self.can_claim(&relay_parent, para_id).unwrap_or(false) {
+			return Err(AdvertisementError::SecondedLimitReached)
+		}
+
+		let instant = self.reputation.score(&para_id, &peer_id) >= INSTANT_FETCH_REP_THRESHOLD;
+		if let Some(rp_state) = self.per_relay_parent.get_mut(&relay_parent) {
+			rp_state.advertisements.push(Advertisement {
+				peer_id,
+				para_id,
+				relay_parent,
+				prospective_candidate,
+				received_at: now,
+			});
+		}
+
+		Ok(instant)
+	}
+
+	/// Remove and return the next advertisement to fetch at `relay_parent`, if any.
+	pub fn next_fetch(&mut self, relay_parent: &Hash, now: Instant) -> Option<Advertisement> {
+		let unfulfilled = self.unfulfilled_claims(relay_parent).ok()?;
+		let rp_state = self.per_relay_parent.get_mut(relay_parent)?;
+		let index =
+			pick_advertisement(&unfulfilled, &rp_state.advertisements, &self.reputation, now)?;
+		Some(rp_state.advertisements.remove(index))
+	}
+
+	/// Drop all pending advertisements of `peer_id`.
+	pub fn remove_advertisements_of(&mut self, peer_id: &PeerId) {
+		for rp_state in self.per_relay_parent.values_mut() {
+			rp_state.advertisements.retain(|adv| adv.peer_id != *peer_id);
+		}
+	}
+
+	/// Number of advertisements waiting to be fetched.
+	pub fn pending_advertisements(&self) -> usize {
+		self.per_relay_parent
+			.values()
+			.map(|rp_state| rp_state.advertisements.len())
+			.sum()
+	}
+}
+
+/// The collation selection policy.
+///
+/// Visits `unfulfilled_claims` in order and, for the first para with at least one eligible
+/// advertisement, returns the index of the eligible advertisement from the highest scored
+/// collator. Ties are broken in favour of the oldest advertisement.
+pub fn pick_advertisement(
+	unfulfilled_claims: &[ParaId],
+	advertisements: &[Advertisement],
+	reputation: &ReputationStore,
+	now: Instant,
+) -> Option<usize> {
+	let score_of = |adv: &Advertisement| reputation.score(&adv.para_id, &adv.peer_id);
+	let is_eligible = |adv: &Advertisement| {
+		score_of(adv) >= INSTANT_FETCH_REP_THRESHOLD ||
+			now.saturating_duration_since(adv.received_at) >= UNDER_THRESHOLD_FETCH_DELAY
+	};
+
+	unfulfilled_claims.iter().find_map(|para_id| {
+		advertisements
+			.iter()
+			.enumerate()
+			.filter(|(_, adv)| adv.para_id == *para_id && is_eligible(adv))
+			.max_by_key(|(_, adv)| (score_of(adv), Reverse(adv.received_at)))
+			.map(|(index, _)| index)
+	})
+}
+
+/// Score of a collator, as reported in the metrics.
+pub fn score_bucket(score: Score) -> &'static str {
+	if score >= INSTANT_FETCH_REP_THRESHOLD {
+		"above_threshold"
+	} else {
+		"below_threshold"
+	}
+}
@@ -0,0 +1,608 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{
+	common::{
+		Advertisement, INSTANT_FETCH_REP_THRESHOLD, INVALID_COLLATION_SLASH,
+		MAX_CONNECTED_PER_PARA, MAX_STORED_SCORES_PER_PARA, UNDER_THRESHOLD_FETCH_DELAY,
+	},
+	db::{self, ReputationDb},
+	reputation::ReputationStore,
+	run_inner,
+	state::{pick_advertisement, PeerState, State},
+	DbConfig, Metrics, COST_REPORT_BAD,
+};
+use crate::validator_side::collation::ProspectiveCandidate;
+
+use std::{
+	collections::{BTreeMap, VecDeque},
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use assert_matches::assert_matches;
+use codec::Encode;
+use futures::{executor, future, Future};
+use sc_keystore::LocalKeystore;
+use sc_network::ProtocolName;
+use sp_core::crypto::Pair;
+use sp_keyring::Sr25519Keyring;
+use sp_keystore::Keystore;
+
+use polkadot_node_network_protocol::{
+	peer_set::CollationVersion,
+	request_response::{v2 as request_v2, Requests, ResponseSender},
+	v1 as protocol_v1, v2 as protocol_v2, CollationProtocols, ObservedRole, OurView, PeerId,
+};
+use polkadot_node_primitives::{BlockData, PoV};
+use polkadot_node_subsystem::{
+	messages::{
+		AllMessages, CandidateBackingMessage, ChainApiMessage, CollatorProtocolMessage,
+		IfDisconnected, NetworkBridgeEvent, NetworkBridgeTxMessage, ProspectiveParachainsMessage,
+		ReportPeerMessage, RuntimeApiMessage, RuntimeApiRequest,
+	},
+	FromOrchestra, OverseerSignal,
+};
+use polkadot_node_subsystem_test_helpers as test_helpers;
+use polkadot_node_subsystem_util::{
+	database::{kvdb_impl::DbAdapter, Database},
+	reputation::ReputationAggregator,
+	TimeoutExt,
+};
+use polkadot_primitives::{
+	vstaging::CandidateReceiptV2 as CandidateReceipt, CandidateHash, CollatorPair, CoreIndex,
+	GroupRotationInfo, Hash, HeadData, Header, Id as ParaId, NodeFeatures, PersistedValidationData,
+	ValidatorIndex, PARACHAIN_KEY_TYPE_ID,
+};
+use polkadot_primitives_test_helpers::dummy_candidate_receipt_bad_sig;
+
+fn advertisement(peer_id: PeerId, para_id: u32, received_at: Instant) -> Advertisement {
+	Advertisement {
+		peer_id,
+		para_id: ParaId::from(para_id),
+		relay_parent: Hash::repeat_byte(1),
+		prospective_candidate: ProspectiveCandidate {
+			candidate_hash: CandidateHash(Hash::random()),
+			parent_head_data_hash: Hash::repeat_byte(2),
+		},
+		received_at,
+	}
+}
+
+fn store_with(scores: &[(u32, PeerId, u16)]) -> ReputationStore {
+	let mut store = ReputationStore::new(100);
+	for (para_id, peer_id, score) in scores {
+		store.bump(ParaId::from(*para_id), *peer_id, *score);
+	}
+	store
+}
+
+#[test]
+fn nothing_is_picked_without_unfulfilled_claims() {
+	let now = Instant::now();
+	let peer = PeerId::random();
+	let reputation = store_with(&[(1, peer, INSTANT_FETCH_REP_THRESHOLD.value())]);
+	let advertisements = vec![advertisement(peer, 1, now)];
+
+	assert_eq!(pick_advertisement(&[], &advertisements, &reputation, now), None);
+	assert_eq!(pick_advertisement(&[ParaId::from(2)], &advertisements, &reputation, now), None);
+}
+
+#[test]
+fn highest_score_is_picked_first() {
+	let now = Instant::now();
+	let (low, high) = (PeerId::random(), PeerId::random());
+	let reputation = store_with(&[
+		(1, low, INSTANT_FETCH_REP_THRESHOLD.value()),
+		(1, high, INSTANT_FETCH_REP_THRESHOLD.value() + 500),
+	]);
+	let advertisements = vec![advertisement(low, 1, now), advertisement(high, 1, now)];
+
+	assert_eq!(pick_advertisement(&[ParaId::from(1)], &advertisements, &reputation, now), Some(1));
+}
+
+#[test]
+fn ties_are_broken_by_age() {
+	let now = Instant::now();
+	let (first, second) = (PeerId::random(), PeerId::random());
+	let reputation = store_with(&[
+		(1, first, INSTANT_FETCH_REP_THRESHOLD.value()),
+		(1, second, INSTANT_FETCH_REP_THRESHOLD.value()),
+	]);
+	let advertisements = vec![
+		advertisement(second, 1, now),
+		advertisement(first, 1, now - Duration::from_millis(10)),
+	];
+
+	assert_eq!(pick_advertisement(&[ParaId::from(1)], &advertisements, &reputation, now), Some(1));
+}
+
+#[test]
+fn under_threshold_collators_wait() {
+	let now = Instant::now();
+	let (newcomer, reputable) = (PeerId::random(), PeerId::random());
+	let reputation = store_with(&[
+		(1, newcomer, INSTANT_FETCH_REP_THRESHOLD.value() - 1),
+		(1, reputable, INSTANT_FETCH_REP_THRESHOLD.value()),
+	]);
+	let claims = [ParaId::from(1)];
+
+	// The newcomer advertised first, but the reputable collator is fetched right away.
+	let mut advertisements = vec![advertisement(newcomer, 1, now)];
+	assert_eq!(pick_advertisement(&claims, &advertisements, &reputation, now), None);
+
+	advertisements.push(advertisement(reputable, 1, now));
+	assert_eq!(pick_advertisement(&claims, &advertisements, &reputation, now), Some(1));
+
+	// Once the delay passes, the newcomer is eligible as well.
+	let later = now + UNDER_THRESHOLD_FETCH_DELAY;
+	advertisements.remove(1);
+	assert_eq!(pick_advertisement(&claims, &advertisements, &reputation, later), Some(0));
+}
+
+#[test]
+fn claim_queue_order_comes_before_score() {
+	let now = Instant::now();
+	let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+	let reputation = store_with(&[
+		(1, peer_a, INSTANT_FETCH_REP_THRESHOLD.value()),
+		(2, peer_b, INSTANT_FETCH_REP_THRESHOLD.value() + 5_000),
+	]);
+	let advertisements = vec![advertisement(peer_b, 2, now), advertisement(peer_a, 1, now)];
+
+	assert_eq!(
+		pick_advertisement(&[ParaId::from(1), ParaId::from(2)], &advertisements, &reputation, now),
+		Some(1)
+	);
+	assert_eq!(
+		pick_advertisement(&[ParaId::from(2), ParaId::from(1)], &advertisements, &reputation, now),
+		Some(0)
+	);
+
+	// A para without an eligible advertisement doesn't block the next one.
+	let advertisements =
+		vec![advertisement(PeerId::random(), 1, now), advertisement(peer_b, 2, now)];
+	assert_eq!(
+		pick_advertisement(&[ParaId::from(1), ParaId::from(2)], &advertisements, &reputation, now),
+		Some(1)
+	);
+}
+
+#[test]
+fn lowest_scored_collator_makes_room() {
+	let para_id = ParaId::from(1);
+	let mut state = State::new(
+		Metrics::default(),
+		ReputationAggregator::default(),
+		ReputationStore::new(MAX_STORED_SCORES_PER_PARA),
+	);
+
+	let collators = (0..MAX_CONNECTED_PER_PARA).map(|_| PeerId::random()).collect::<Vec<_>>();
+	for (i, peer_id) in collators.iter().enumerate() {
+		state.reputation.bump(para_id, *peer_id, 10 * (i as u16 + 1));
+		state
+			.peers
+			.insert(*peer_id, PeerState::Collating(Sr25519Keyring::Alice.public().into(), para_id));
+	}
+
+	// A collator for another para is not affected.
+	assert_eq!(state.make_room_for_collator(ParaId::from(2), &PeerId::random()), Ok(None));
+
+	// A newcomer can't replace anyone.
+	let newcomer = PeerId::random();
+	assert_eq!(state.make_room_for_collator(para_id, &newcomer), Err(()));
+
+	// A collator scoring higher than the lowest one replaces it.
+	state.reputation.bump(para_id, newcomer, 15);
+	assert_eq!(state.make_room_for_collator(para_id, &newcomer), Ok(Some(collators[0])));
+}
+
+const TIMEOUT: Duration = Duration::from_millis(200);
+const DB_CONFIG: DbConfig = DbConfig { col_reputation_data: 0 };
+
+type VirtualOverseer = test_helpers::TestSubsystemContextHandle<CollatorProtocolMessage>;
+
+fn para_id() -> ParaId {
+	ParaId::from(1)
+}
+
+fn make_db() -> Arc<dyn Database> {
+	Arc::new(DbAdapter::new(kvdb_memorydb::create(1), &[DB_CONFIG.col_reputation_data]))
+}
+
+/// Store scores for `para_id()`, as if they were kept by a previous run of the subsystem.
+fn store_scores(db: &Arc<dyn Database>, scores: &[(PeerId, u16)]) {
+	let (mut reputation_db, mut store) =
+		ReputationDb::load(db.clone(), DB_CONFIG, MAX_STORED_SCORES_PER_PARA, db::now());
+	for (peer_id, score) in scores {
+		store.bump(para_id(), *peer_id, *score);
+	}
+	reputation_db.flush(&mut store).unwrap();
+}
+
+fn stored_score(db: Arc<dyn Database>, peer_id: &PeerId) -> u16 {
+	let (_, store) = ReputationDb::load(db, DB_CONFIG, MAX_STORED_SCORES_PER_PARA, db::now());
+	store.score(&para_id(), peer_id).value()
+}
+
+fn test_harness<T: Future<Output = VirtualOverseer>>(
+	db: Arc<dyn Database>,
+	test: impl FnOnce(VirtualOverseer) -> T,
+) {
+	sp_tracing::init_for_tests();
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (context, virtual_overseer) = test_helpers::make_subsystem_context(pool);
+
+	let keystore = Arc::new(LocalKeystore::in_memory());
+	Keystore::sr25519_generate_new(
+		&*keystore,
+		PARACHAIN_KEY_TYPE_ID,
+		Some(&Sr25519Keyring::Alice.to_seed()),
+	)
+	.expect("Insert key into keystore");
+
+	let subsystem = run_inner(
+		context,
+		keystore,
+		Metrics::default(),
+		db,
+		DB_CONFIG,
+		ReputationAggregator::new(|_| true),
+		Duration::from_millis(10),
+	);
+
+	let test_fut = test(virtual_overseer);
+
+	futures::pin_mut!(test_fut);
+	futures::pin_mut!(subsystem);
+
+	executor::block_on(future::join(
+		async move {
+			let mut overseer = test_fut.await;
+			overseer
+				.send(FromOrchestra::Signal(OverseerSignal::Conclude))
+				.timeout(TIMEOUT)
+				.await
+				.expect("Sending the conclude signal doesn't time out");
+		},
+		subsystem,
+	))
+	.1
+	.unwrap();
+}
+
+async fn overseer_send(overseer: &mut VirtualOverseer, msg: CollatorProtocolMessage) {
+	overseer
+		.send(FromOrchestra::Communication { msg })
+		.timeout(TIMEOUT)
+		.await
+		.expect(&format!("{:?} is enough for sending messages.", TIMEOUT));
+}
+
+async fn overseer_recv(overseer: &mut VirtualOverseer) -> AllMessages {
+	overseer
+		.recv()
+		.timeout(TIMEOUT)
+		.await
+		.expect(&format!("{:?} is enough to receive messages.", TIMEOUT))
+}
+
+fn dummy_pvd() -> PersistedValidationData {
+	PersistedValidationData {
+		parent_head: HeadData(vec![7, 8, 9]),
+		relay_parent_number: 0,
+		max_pov_size: 1024,
+		relay_parent_storage_root: Default::default(),
+	}
+}
+
+fn dummy_candidate(relay_parent: Hash, commitments_hash: Hash) -> CandidateReceipt {
+	let mut candidate = dummy_candidate_receipt_bad_sig(relay_parent, Some(commitments_hash));
+	candidate.descriptor.para_id = para_id();
+	candidate.descriptor.persisted_validation_data_hash = dummy_pvd().hash();
+	candidate.into()
+}
+
+/// Activate a leaf at height 0, so there is no ancestry to fetch. Alice is assigned to
+/// `para_id()`, with three free claims.
+async fn activate_leaf(virtual_overseer: &mut VirtualOverseer, leaf: Hash) {
+	overseer_send(
+		virtual_overseer,
+		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+			OurView::new([leaf], 0),
+		)),
+	)
+	.await;
+
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			parent,
+			RuntimeApiRequest::SessionIndexForChild(tx),
+		)) if parent == leaf => {
+			tx.send(Ok(1)).unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			_,
+			RuntimeApiRequest::NodeFeatures(_, tx),
+		)) => {
+			tx.send(Ok(NodeFeatures::EMPTY)).unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			parent,
+			RuntimeApiRequest::Validators(tx),
+		)) if parent == leaf => {
+			tx.send(Ok(vec![
+				Sr25519Keyring::Alice.public().into(),
+				Sr25519Keyring::Bob.public().into(),
+			]))
+			.unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			parent,
+			RuntimeApiRequest::ValidatorGroups(tx),
+		)) if parent == leaf => {
+			let rotation_info =
+				GroupRotationInfo { session_start_block: 0, group_rotation_frequency: 1, now: 0 };
+			tx.send(Ok((vec![vec![ValidatorIndex(0), ValidatorIndex(1)]], rotation_info)))
+				.unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+			parent,
+			RuntimeApiRequest::ClaimQueue(tx),
+		)) if parent == leaf => {
+			let claim_queue = BTreeMap::from([(CoreIndex(0), VecDeque::from([para_id(); 3]))]);
+			tx.send(Ok(claim_queue)).unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::ChainApi(ChainApiMessage::BlockHeader(hash, tx)) if hash == leaf => {
+			let header = Header {
+				parent_hash: Hash::zero(),
+				number: 0,
+				state_root: Hash::zero(),
+				extrinsics_root: Hash::zero(),
+				digest: Default::default(),
+			};
+			tx.send(Ok(Some(header))).unwrap();
+		}
+	);
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::ProspectiveParachains(
+			ProspectiveParachainsMessage::GetMinimumRelayParents(parent, tx),
+		) if parent == leaf => {
+			tx.send(vec![(para_id(), 0)]).unwrap();
+		}
+	);
+}
+
+async fn connect_and_declare_collator(
+	virtual_overseer: &mut VirtualOverseer,
+	peer_id: PeerId,
+	collator: &CollatorPair,
+) {
+	overseer_send(
+		virtual_overseer,
+		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerConnected(
+			peer_id,
+			ObservedRole::Full,
+			CollationVersion::V2.into(),
+			None,
+		)),
+	)
+	.await;
+
+	let declare = protocol_v2::CollatorProtocolMessage::Declare(
+		collator.public(),
+		para_id(),
+		collator.sign(&protocol_v1::declare_signature_payload(&peer_id)),
+	);
+	overseer_send(
+		virtual_overseer,
+		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
+			peer_id,
+			CollationProtocols::V2(declare),
+		)),
+	)
+	.await;
+}
+
+/// Advertise a collation and let backing accept it.
+async fn advertise_collation(
+	virtual_overseer: &mut VirtualOverseer,
+	peer_id: PeerId,
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+) {
+	let advertisement = protocol_v2::CollatorProtocolMessage::AdvertiseCollation {
+		relay_parent,
+		candidate_hash,
+		parent_head_data_hash: dummy_pvd().parent_head.hash(),
+	};
+	overseer_send(
+		virtual_overseer,
+		CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(
+			peer_id,
+			CollationProtocols::V2(advertisement),
+		)),
+	)
+	.await;
+
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::CandidateBacking(CandidateBackingMessage::CanSecond(request, tx)) => {
+			assert_eq!(request.candidate_hash, candidate_hash);
+			tx.send(true).unwrap();
+		}
+	);
+}
+
+async fn assert_fetch_collation_request(
+	virtual_overseer: &mut VirtualOverseer,
+	relay_parent: Hash,
+	candidate_hash: CandidateHash,
+) -> ResponseSender {
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(
+			reqs,
+			IfDisconnected::ImmediateError,
+		)) => {
+			let req = reqs.into_iter().next().expect("There should be exactly one request");
+			assert_matches!(req, Requests::CollationFetchingV2(req) => {
+				assert_eq!(req.payload.relay_parent, relay_parent);
+				assert_eq!(req.payload.para_id, para_id());
+				assert_eq!(req.payload.candidate_hash, candidate_hash);
+				req.pending_response
+			})
+		}
+	)
+}
+
+#[test]
+fn stored_reputation_orders_fetches() {
+	let db = make_db();
+	let (newcomer, reputable) = (PeerId::random(), PeerId::random());
+	store_scores(
+		&db,
+		&[
+			(newcomer, INSTANT_FETCH_REP_THRESHOLD.value() - 1),
+			(reputable, INSTANT_FETCH_REP_THRESHOLD.value()),
+		],
+	);
+
+	test_harness(db, |mut virtual_overseer| async move {
+		let relay_parent = Hash::repeat_byte(0xAA);
+		activate_leaf(&mut virtual_overseer, relay_parent).await;
+
+		let newcomer_collator = CollatorPair::generate().0;
+		let reputable_collator = CollatorPair::generate().0;
+		connect_and_declare_collator(&mut virtual_overseer, newcomer, &newcomer_collator).await;
+		connect_and_declare_collator(&mut virtual_overseer, reputable, &reputable_collator).await;
+
+		let newcomer_candidate = CandidateHash(Hash::repeat_byte(1));
+		let reputable_candidate = CandidateHash(Hash::repeat_byte(2));
+
+		// The newcomer advertises first, but only the reputable collator is fetched right away.
+		advertise_collation(&mut virtual_overseer, newcomer, relay_parent, newcomer_candidate)
+			.await;
+		advertise_collation(&mut virtual_overseer, reputable, relay_parent, reputable_candidate)
+			.await;
+		let _reputable_response = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			relay_parent,
+			reputable_candidate,
+		)
+		.await;
+
+		// The newcomer is fetched once its advertisement waited long enough.
+		let _newcomer_response =
+			assert_fetch_collation_request(&mut virtual_overseer, relay_parent, newcomer_candidate)
+				.await;
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn invalid_collation_is_punished() {
+	let db = make_db();
+	let peer_id = PeerId::random();
+	let score = INSTANT_FETCH_REP_THRESHOLD.value() + INVALID_COLLATION_SLASH;
+	store_scores(&db, &[(peer_id, score)]);
+
+	test_harness(db.clone(), |mut virtual_overseer| async move {
+		let relay_parent = Hash::repeat_byte(0xAA);
+		activate_leaf(&mut virtual_overseer, relay_parent).await;
+
+		let collator = CollatorPair::generate().0;
+		connect_and_declare_collator(&mut virtual_overseer, peer_id, &collator).await;
+
+		let candidate = dummy_candidate(relay_parent, Hash::repeat_byte(1));
+		let pov = PoV { block_data: BlockData(vec![1]) };
+		advertise_collation(&mut virtual_overseer, peer_id, relay_parent, candidate.hash()).await;
+
+		let response =
+			assert_fetch_collation_request(&mut virtual_overseer, relay_parent, candidate.hash())
+				.await;
+		response
+			.send(Ok((
+				request_v2::CollationFetchingResponse::Collation(candidate.clone(), pov.clone())
+					.encode(),
+				ProtocolName::from(""),
+			)))
+			.expect("Sending response should succeed");
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ProspectiveParachains(
+				ProspectiveParachainsMessage::GetProspectiveValidationData(request, tx),
+			) => {
+				assert_eq!(request.candidate_relay_parent, relay_parent);
+				assert_eq!(request.para_id, para_id());
+				tx.send(Some(dummy_pvd())).unwrap();
+			}
+		);
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::CandidateBacking(CandidateBackingMessage::Second(
+				parent,
+				receipt,
+				_,
+				incoming_pov,
+			)) => {
+				assert_eq!(parent, relay_parent);
+				assert_eq!(receipt, candidate);
+				assert_eq!(incoming_pov, pov);
+			}
+		);
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::Invalid(relay_parent, candidate),
+		)
+		.await;
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(
+				ReportPeerMessage::Single(reported, rep),
+			)) => {
+				assert_eq!(reported, peer_id);
+				assert_eq!(rep.value, COST_REPORT_BAD.cost_or_benefit());
+			}
+		);
+
+		virtual_overseer
+	});
+
+	// The slash was persisted when the subsystem concluded.
+	assert_eq!(stored_score(db, &peer_id), score - INVALID_COLLATION_SLASH);
+}
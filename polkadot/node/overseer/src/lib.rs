@@ -100,6 +100,9 @@ pub use self::metrics::Metrics as OverseerMetrics;
 pub mod dummy;
 pub use self::dummy::DummySubsystem;
 
+/// Opt-in recording of the events received by subsystems, for post-mortem debugging.
+pub mod recording;
+
 pub use polkadot_node_metrics::{
 	metrics::{prometheus, Metrics as MetricsTrait},
 	Metronome,
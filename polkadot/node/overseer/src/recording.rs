@@ -0,0 +1,1234 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opt-in recording of the signals and messages received by subsystems.
+//!
+//! A subsystem wrapped into a [`RecordingSubsystem`] records every signal and every message it
+//! receives through a [`Recorder`]. The entries are written by a [`RecordingWriter`] to a set of
+//! rotating files, so a recording of a misbehaving node can be fed back into the same subsystem
+//! afterwards, i.e. with the replay harness of `polkadot-node-subsystem-test-helpers`.
+//!
+//! Messages carry response channels, which can not be recorded. Only message types implementing
+//! [`RecordableMessage`] can be recorded, by encoding everything but their response channels.
+//! Replayed messages get fresh response channels, whose receiving ends are dropped.
+//!
+//! The responses to the runtime API, chain API and availability store requests sent by a recorded
+//! subsystem are recorded as well, along with their requests. When replaying, [`RecordedResponses`]
+//! answers the same requests with the recorded responses, so the subsystem sees the same chain
+//! state as the recorded node.
+//!
+//! Each file is a sequence of SCALE encoded [`RecordEntry`]s. Files are named
+//! `overseer-recording-<index>.bin`, with the index increasing on every rotation, and only the
+//! most recent [`RecordingConfig::max_files`] files are kept.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	fs,
+	io::{self, Write},
+	path::{Path, PathBuf},
+	pin::Pin,
+	task::{Context, Poll},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Decode, Encode};
+use futures::{
+	channel::{mpsc, oneshot},
+	future::BoxFuture,
+	select,
+	stream::FuturesUnordered,
+	Future, FutureExt, StreamExt,
+};
+use futures_timer::Delay;
+use orchestra::{
+	FromOrchestra, Priority, SpawnedSubsystem, Subsystem, SubsystemContext, SubsystemSender,
+	TrySendError,
+};
+
+use polkadot_node_primitives::{
+	approval::{
+		v1::DelayTranche,
+		v2::{CandidateBitfield, IndirectAssignmentCertV2, IndirectSignedApprovalVoteV2},
+	},
+	SignedDisputeStatement,
+};
+use polkadot_node_subsystem_types::{
+	errors::{ChainApiError, RuntimeApiError, SubsystemError, SubsystemResult},
+	messages::{
+		ApprovalVotingMessage, AvailabilityStoreMessage, BlockDescription, ChainApiMessage,
+		CheckedIndirectAssignment, CheckedIndirectSignedApprovalVote, DisputeCoordinatorMessage,
+		RuntimeApiMessage, RuntimeApiRequest,
+	},
+	ActivatedLeaf, ActiveLeavesUpdate, OverseerSignal, UnpinHandle,
+};
+use polkadot_primitives::{
+	vstaging::CandidateReceiptV2 as CandidateReceipt, BlockNumber, CandidateHash, DisputeStatement,
+	Hash, SessionIndex, ValidatorId, ValidatorIndex, ValidatorSignature,
+};
+
+use crate::{
+	metrics::{prometheus, MetricsTrait},
+	AllMessages,
+};
+
+const LOG_TARGET: &str = "parachain::overseer-recording";
+
+/// The default size of a recording file, above which a new file is started.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The default number of recording files to keep.
+pub const DEFAULT_MAX_FILES: usize = 8;
+
+/// How many entries can be queued for the writer before new entries are dropped.
+const RECORDING_CHANNEL_SIZE: usize = 4096;
+
+/// How many responses of a subsystem can be awaited before new responses are not recorded.
+const RESPONSES_CHANNEL_SIZE: usize = 1024;
+
+/// How often the written entries are flushed to the recording file.
+const RECORDING_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+const FILE_PREFIX: &str = "overseer-recording-";
+const FILE_EXTENSION: &str = "bin";
+
+/// Configuration of the recording.
+#[derive(Debug, Clone)]
+pub struct RecordingConfig {
+	/// The directory the recording files are written to.
+	pub directory: PathBuf,
+	/// The size of a recording file, above which a new file is started.
+	pub max_file_size: u64,
+	/// The number of recording files to keep. Older files are removed on rotation.
+	pub max_files: usize,
+}
+
+impl RecordingConfig {
+	/// Create a configuration recording into `directory` with the default limits.
+	pub fn new(directory: PathBuf) -> Self {
+		Self { directory, max_file_size: DEFAULT_MAX_FILE_SIZE, max_files: DEFAULT_MAX_FILES }
+	}
+}
+
+/// A signal, as recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RecordedSignal {
+	/// An `ActiveLeaves` signal.
+	ActiveLeaves {
+		/// The hash and number of the activated leaf.
+		activated: Option<(Hash, BlockNumber)>,
+		/// The hashes of the deactivated leaves.
+		deactivated: Vec<Hash>,
+	},
+	/// A `BlockFinalized` signal.
+	BlockFinalized(Hash, BlockNumber),
+	/// A `Conclude` signal.
+	Conclude,
+}
+
+impl From<&OverseerSignal> for RecordedSignal {
+	fn from(signal: &OverseerSignal) -> Self {
+		match signal {
+			OverseerSignal::ActiveLeaves(update) => RecordedSignal::ActiveLeaves {
+				activated: update.activated.as_ref().map(|leaf| (leaf.hash, leaf.number)),
+				deactivated: update.deactivated.to_vec(),
+			},
+			OverseerSignal::BlockFinalized(hash, number) =>
+				RecordedSignal::BlockFinalized(*hash, *number),
+			OverseerSignal::Conclude => RecordedSignal::Conclude,
+		}
+	}
+}
+
+impl RecordedSignal {
+	/// Turn the recorded signal back into a signal.
+	///
+	/// The unpin handle of an activated leaf is not connected to any backend.
+	pub fn into_signal(self) -> OverseerSignal {
+		match self {
+			RecordedSignal::ActiveLeaves { activated, deactivated } =>
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: activated.map(|(hash, number)| ActivatedLeaf {
+						hash,
+						number,
+						unpin_handle: detached_unpin_handle(hash),
+					}),
+					deactivated: deactivated.into(),
+				}),
+			RecordedSignal::BlockFinalized(hash, number) =>
+				OverseerSignal::BlockFinalized(hash, number),
+			RecordedSignal::Conclude => OverseerSignal::Conclude,
+		}
+	}
+}
+
+fn detached_unpin_handle(hash: Hash) -> UnpinHandle {
+	let (unpin_worker_sender, _) = sc_utils::mpsc::tracing_unbounded("mpsc_recorded_unpin", 1);
+	UnpinHandle::new(hash, unpin_worker_sender)
+}
+
+/// What was received by a subsystem.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RecordedEvent {
+	/// A signal.
+	Signal(RecordedSignal),
+	/// A message, encoded with [`RecordableMessage::encode_for_recording`].
+	Message(Vec<u8>),
+	/// The response to a request sent by the subsystem.
+	Response {
+		/// The encoded request, without its response channel.
+		request: Vec<u8>,
+		/// The encoded response.
+		response: Vec<u8>,
+	},
+}
+
+/// A single entry of a recording.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct RecordEntry {
+	/// Milliseconds since the UNIX epoch at which the event was received.
+	pub timestamp: u64,
+	/// The name of the subsystem which received the event.
+	pub subsystem: String,
+	/// The received event.
+	pub event: RecordedEvent,
+}
+
+/// A message which can be recorded and replayed.
+pub trait RecordableMessage: Sized {
+	/// Encode the message without its response channels, or return `None` if the message is not
+	/// worth recording.
+	fn encode_for_recording(&self) -> Option<Vec<u8>>;
+
+	/// Decode a recorded message, providing it with fresh response channels. Returns `None` if
+	/// the message can not be decoded.
+	fn decode_from_recording(encoded: &[u8]) -> Option<Self>;
+}
+
+/// Forwards a response to its requester and records it.
+type ForwardResponse = BoxFuture<'static, ()>;
+
+fn timestamp() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_millis() as u64)
+		.unwrap_or_default()
+}
+
+/// Prometheus metrics of the recording.
+#[derive(Clone)]
+struct RecordingMetricsInner {
+	dropped_total: prometheus::CounterVec<prometheus::U64>,
+}
+
+/// Recording metrics.
+#[derive(Default, Clone)]
+pub struct RecordingMetrics(Option<RecordingMetricsInner>);
+
+impl RecordingMetrics {
+	fn on_dropped(&self, subsystem: &str) {
+		if let Some(metrics) = &self.0 {
+			metrics.dropped_total.with_label_values(&[subsystem]).inc();
+		}
+	}
+}
+
+impl MetricsTrait for RecordingMetrics {
+	fn try_register(registry: &prometheus::Registry) -> Result<Self, prometheus::PrometheusError> {
+		let metrics = RecordingMetricsInner {
+			dropped_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_overseer_recording_dropped_total",
+						"Number of events not recorded because the recording could not keep up.",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+		};
+		Ok(RecordingMetrics(Some(metrics)))
+	}
+}
+
+/// A handle to record the events received by subsystems.
+#[derive(Clone)]
+pub struct Recorder {
+	tx: mpsc::Sender<RecordEntry>,
+	metrics: RecordingMetrics,
+}
+
+impl Recorder {
+	/// Create a new recorder and the writer of its recording, which is expected to be spawned as
+	/// a blocking task.
+	pub fn new(config: RecordingConfig, metrics: RecordingMetrics) -> (Self, RecordingWriter) {
+		let (tx, rx) = mpsc::channel(RECORDING_CHANNEL_SIZE);
+		(Self { tx, metrics }, RecordingWriter { config, rx })
+	}
+
+	/// Record an event received by `subsystem`.
+	///
+	/// Never blocks: the entry is dropped if the writer can not keep up.
+	pub fn record(&mut self, subsystem: &str, event: RecordedEvent) {
+		let entry = RecordEntry { timestamp: timestamp(), subsystem: subsystem.to_owned(), event };
+
+		if let Err(err) = self.tx.try_send(entry) {
+			if err.is_full() {
+				gum::debug!(target: LOG_TARGET, subsystem, "Recording queue full, dropping entry");
+				self.metrics.on_dropped(subsystem);
+			}
+		}
+	}
+
+	/// Record the response to `request`, sent by `subsystem`.
+	///
+	/// Returns the channel to send the request with. The response is forwarded to `tx` by the
+	/// forwarder of `subsystem`, before being recorded. Never blocks: `tx` itself is returned if
+	/// the forwarder can not keep up.
+	fn record_response<R: Send + 'static>(
+		&mut self,
+		forwards: &mut mpsc::Sender<ForwardResponse>,
+		subsystem: &'static str,
+		request: Vec<u8>,
+		tx: oneshot::Sender<R>,
+		encode: fn(&R) -> Vec<u8>,
+	) -> oneshot::Sender<R> {
+		// Make sure the forwarder takes the response channel before replacing it, a dropped
+		// channel would fail the request.
+		let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+		if !matches!(forwards.poll_ready(&mut cx), Poll::Ready(Ok(()))) {
+			gum::debug!(target: LOG_TARGET, subsystem, "Response queue full, not recording response");
+			self.metrics.on_dropped(subsystem);
+			return tx
+		}
+
+		let (recorded_tx, rx) = oneshot::channel();
+		let mut recorder = self.clone();
+		let forward = async move {
+			let Ok(response) = rx.await else { return };
+			let encoded = encode(&response);
+			let _ = tx.send(response);
+			recorder.record(subsystem, RecordedEvent::Response { request, response: encoded });
+		};
+		// Can't fail, the forwarder only stops once the subsystem's sender is dropped.
+		let _ = forwards.start_send(forward.boxed());
+		recorded_tx
+	}
+}
+
+/// Forward the responses to the requests of a recorded subsystem as soon as they arrive, until
+/// the subsystem is gone.
+async fn forward_responses(mut rx: mpsc::Receiver<ForwardResponse>) {
+	let mut forwards = FuturesUnordered::new();
+	loop {
+		select! {
+			forward = rx.next() => match forward {
+				Some(forward) => forwards.push(forward),
+				None => break,
+			},
+			() = forwards.select_next_some() => {},
+		}
+	}
+	while forwards.next().await.is_some() {}
+}
+
+/// Writes the entries recorded by a [`Recorder`] to rotating files.
+pub struct RecordingWriter {
+	config: RecordingConfig,
+	rx: mpsc::Receiver<RecordEntry>,
+}
+
+impl RecordingWriter {
+	/// Write the recorded entries until all recorders are dropped.
+	pub async fn run(mut self) {
+		let mut file = RecordingFile::open_next(&self.config)
+			.map_err(|err| {
+				gum::warn!(
+					target: LOG_TARGET,
+					?err,
+					directory = ?self.config.directory,
+					"Failed to start recording",
+				);
+			})
+			.ok();
+		let mut flush = Delay::new(RECORDING_FLUSH_INTERVAL).fuse();
+
+		loop {
+			let entry = select! {
+				entry = self.rx.next() => match entry {
+					Some(entry) => entry,
+					None => break,
+				},
+				_ = flush => {
+					if let Some(Err(err)) = file.as_mut().map(|file| file.inner.flush()) {
+						gum::warn!(target: LOG_TARGET, ?err, "Failed to flush recording file");
+					}
+					flush = Delay::new(RECORDING_FLUSH_INTERVAL).fuse();
+					continue
+				},
+			};
+
+			let Some(current) = file.as_mut() else { continue };
+			if let Err(err) = current.write(&entry) {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to write recording entry");
+				continue
+			}
+
+			if current.size >= self.config.max_file_size {
+				match RecordingFile::open_next(&self.config) {
+					Ok(next) => file = Some(next),
+					Err(err) => {
+						gum::warn!(target: LOG_TARGET, ?err, "Failed to rotate recording file");
+					},
+				}
+			}
+		}
+
+		if let Some(mut file) = file {
+			let _ = file.inner.flush();
+		}
+	}
+}
+
+struct RecordingFile {
+	inner: io::BufWriter<fs::File>,
+	size: u64,
+}
+
+impl RecordingFile {
+	/// Open the file following the most recent one, and prune the oldest files.
+	fn open_next(config: &RecordingConfig) -> io::Result<Self> {
+		fs::create_dir_all(&config.directory)?;
+		let indices = recording_files(&config.directory)?;
+		let index = indices.last().map_or(0, |(index, _)| index + 1);
+
+		// Keep space for the new file.
+		let keep = config.max_files.saturating_sub(1);
+		for (_, path) in indices.iter().rev().skip(keep) {
+			fs::remove_file(path)?;
+		}
+
+		let path = config.directory.join(format!("{}{:08}.{}", FILE_PREFIX, index, FILE_EXTENSION));
+		let file = fs::OpenOptions::new().create_new(true).write(true).open(path)?;
+		Ok(Self { inner: io::BufWriter::new(file), size: 0 })
+	}
+
+	fn write(&mut self, entry: &RecordEntry) -> io::Result<()> {
+		let encoded = entry.encode();
+		self.inner.write_all(&encoded)?;
+		self.size += encoded.len() as u64;
+		Ok(())
+	}
+}
+
+/// The recording files in `directory`, with their index, sorted by index.
+fn recording_files(directory: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+	let mut files = Vec::new();
+	for entry in fs::read_dir(directory)? {
+		let path = entry?.path();
+		let index = path
+			.file_name()
+			.and_then(|name| name.to_str())
+			.and_then(|name| name.strip_prefix(FILE_PREFIX))
+			.and_then(|name| name.strip_suffix(FILE_EXTENSION))
+			.and_then(|name| name.strip_suffix('.'))
+			.and_then(|index| index.parse::<u64>().ok());
+		if let Some(index) = index {
+			files.push((index, path));
+		}
+	}
+	files.sort_by_key(|(index, _)| *index);
+	Ok(files)
+}
+
+/// Read all entries of the recording in `directory`, oldest first.
+pub fn read_recording(directory: &Path) -> io::Result<Vec<RecordEntry>> {
+	let mut entries = Vec::new();
+	for (_, path) in recording_files(directory)? {
+		let content = fs::read(&path)?;
+		let mut input = &content[..];
+		while !input.is_empty() {
+			// A partially written last entry is expected if the node was killed.
+			let Ok(entry) = RecordEntry::decode(&mut input) else {
+				gum::debug!(target: LOG_TARGET, ?path, "Ignoring undecodable end of recording");
+				break
+			};
+			entries.push(entry);
+		}
+	}
+	Ok(entries)
+}
+
+/// A subsystem which records all signals and messages it receives, and the responses to its
+/// requests.
+pub struct RecordingSubsystem<Sub> {
+	subsystem: Sub,
+	recorder: Recorder,
+	name: &'static str,
+}
+
+impl<Sub> RecordingSubsystem<Sub> {
+	/// Wrap `subsystem`, recording its events as received by `name`.
+	pub fn new(subsystem: Sub, recorder: Recorder, name: &'static str) -> Self {
+		Self { subsystem, recorder, name }
+	}
+}
+
+impl<Context, Sub> Subsystem<Context, SubsystemError> for RecordingSubsystem<Sub>
+where
+	Context: SubsystemContext<Signal = OverseerSignal, Error = SubsystemError>,
+	Context::Message: RecordableMessage,
+	Context::OutgoingMessages: TryFrom<AllMessages>,
+	AllMessages: From<Context::OutgoingMessages>,
+	<Context::OutgoingMessages as TryFrom<AllMessages>>::Error: std::fmt::Debug,
+	Sub: Subsystem<RecordingContext<Context>, SubsystemError>,
+{
+	fn start(self, mut ctx: Context) -> SpawnedSubsystem<SubsystemError> {
+		// Responses are forwarded by a task of their own, so they never wait for the writer.
+		let (forwards, rx) = mpsc::channel(RESPONSES_CHANNEL_SIZE);
+		let forwards = match ctx.spawn("recording-responses", forward_responses(rx).boxed()) {
+			Ok(()) => Some(forwards),
+			Err(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					?err,
+					subsystem = self.name,
+					"Failed to spawn the response forwarder, not recording responses",
+				);
+				None
+			},
+		};
+		let sender = RecordingSender {
+			inner: ctx.sender().clone(),
+			recorder: self.recorder,
+			forwards,
+			name: self.name,
+		};
+		self.subsystem.start(RecordingContext { inner: ctx, sender })
+	}
+}
+
+/// A subsystem context recording all signals and messages received through it.
+pub struct RecordingContext<Context: SubsystemContext> {
+	inner: Context,
+	sender: RecordingSender<Context::Sender>,
+}
+
+impl<Context> RecordingContext<Context>
+where
+	Context: SubsystemContext<Signal = OverseerSignal>,
+	Context::Message: RecordableMessage,
+{
+	fn record(&mut self, msg: &FromOrchestra<Context::Message, OverseerSignal>) {
+		let event = match msg {
+			FromOrchestra::Signal(signal) => RecordedEvent::Signal(signal.into()),
+			FromOrchestra::Communication { msg } => match msg.encode_for_recording() {
+				Some(encoded) => RecordedEvent::Message(encoded),
+				None => return,
+			},
+		};
+		self.sender.recorder.record(self.sender.name, event);
+	}
+}
+
+#[async_trait::async_trait]
+impl<Context> SubsystemContext for RecordingContext<Context>
+where
+	Context: SubsystemContext<Signal = OverseerSignal, Error = SubsystemError>,
+	Context::Message: RecordableMessage,
+	Context::OutgoingMessages: TryFrom<AllMessages>,
+	AllMessages: From<Context::OutgoingMessages>,
+	<Context::OutgoingMessages as TryFrom<AllMessages>>::Error: std::fmt::Debug,
+{
+	type Message = Context::Message;
+	type Signal = OverseerSignal;
+	type OutgoingMessages = Context::OutgoingMessages;
+	type Sender = RecordingSender<Context::Sender>;
+	type Error = SubsystemError;
+
+	async fn try_recv(&mut self) -> Result<Option<FromOrchestra<Self::Message, Self::Signal>>, ()> {
+		let msg = self.inner.try_recv().await?;
+		if let Some(msg) = &msg {
+			self.record(msg);
+		}
+		Ok(msg)
+	}
+
+	async fn recv(&mut self) -> SubsystemResult<FromOrchestra<Self::Message, Self::Signal>> {
+		let msg = self.inner.recv().await?;
+		self.record(&msg);
+		Ok(msg)
+	}
+
+	async fn recv_signal(&mut self) -> SubsystemResult<Self::Signal> {
+		let signal = self.inner.recv_signal().await?;
+		let event = RecordedEvent::Signal((&signal).into());
+		self.sender.recorder.record(self.sender.name, event);
+		Ok(signal)
+	}
+
+	fn spawn(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> SubsystemResult<()> {
+		self.inner.spawn(name, s)
+	}
+
+	fn spawn_blocking(
+		&mut self,
+		name: &'static str,
+		s: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> SubsystemResult<()> {
+		self.inner.spawn_blocking(name, s)
+	}
+
+	fn sender(&mut self) -> &mut Self::Sender {
+		&mut self.sender
+	}
+}
+
+/// A sender recording the responses to the runtime API, chain API and availability store
+/// requests sent through it.
+#[derive(Clone)]
+pub struct RecordingSender<Sender> {
+	inner: Sender,
+	recorder: Recorder,
+	forwards: Option<mpsc::Sender<ForwardResponse>>,
+	name: &'static str,
+}
+
+impl<Sender> RecordingSender<Sender> {
+	fn record_request<M>(&mut self, msg: M) -> M
+	where
+		M: TryFrom<AllMessages>,
+		AllMessages: From<M>,
+		<M as TryFrom<AllMessages>>::Error: std::fmt::Debug,
+	{
+		let Some(forwards) = self.forwards.as_mut() else { return msg };
+		let mut hook =
+			RecordResponses { recorder: &mut self.recorder, forwards, subsystem: self.name };
+		let msg = hook_request(AllMessages::from(msg), &mut hook)
+			.expect("responses are never answered while recording; qed");
+		M::try_from(msg).expect("only the response channel of the message was replaced; qed")
+	}
+}
+
+#[async_trait::async_trait]
+impl<OutgoingMessage, Sender> SubsystemSender<OutgoingMessage> for RecordingSender<Sender>
+where
+	OutgoingMessage: Send + 'static + TryFrom<AllMessages>,
+	AllMessages: From<OutgoingMessage>,
+	<OutgoingMessage as TryFrom<AllMessages>>::Error: std::fmt::Debug,
+	Sender: SubsystemSender<OutgoingMessage>,
+{
+	async fn send_message(&mut self, msg: OutgoingMessage) {
+		let msg = self.record_request(msg);
+		self.inner.send_message(msg).await
+	}
+
+	async fn send_message_with_priority<P: Priority>(&mut self, msg: OutgoingMessage) {
+		let msg = self.record_request(msg);
+		self.inner.send_message_with_priority::<P>(msg).await
+	}
+
+	fn try_send_message(
+		&mut self,
+		msg: OutgoingMessage,
+	) -> Result<(), TrySendError<OutgoingMessage>> {
+		let msg = self.record_request(msg);
+		self.inner.try_send_message(msg)
+	}
+
+	fn try_send_message_with_priority<P: Priority>(
+		&mut self,
+		msg: OutgoingMessage,
+	) -> Result<(), TrySendError<OutgoingMessage>> {
+		let msg = self.record_request(msg);
+		self.inner.try_send_message_with_priority::<P>(msg)
+	}
+
+	async fn send_messages<T>(&mut self, msgs: T)
+	where
+		T: IntoIterator<Item = OutgoingMessage> + Send,
+		T::IntoIter: Send,
+	{
+		for msg in msgs {
+			self.send_message(msg).await;
+		}
+	}
+
+	fn send_unbounded_message(&mut self, msg: OutgoingMessage) {
+		let msg = self.record_request(msg);
+		self.inner.send_unbounded_message(msg)
+	}
+}
+
+/// An error response, as recorded.
+#[derive(Encode, Decode)]
+enum RecordedError {
+	/// The runtime API is not supported by the runtime.
+	NotSupported,
+	/// Any other error, with its description.
+	Other(String),
+}
+
+/// How the responses of a request are recorded and replayed.
+struct ResponseCodec<R> {
+	encode: fn(&R) -> Vec<u8>,
+	decode: fn(&[u8]) -> Option<R>,
+}
+
+impl<T: Encode + Decode> ResponseCodec<Result<T, RuntimeApiError>> {
+	fn runtime_api() -> Self {
+		Self {
+			encode: |response| {
+				response
+					.as_ref()
+					.map_err(|err| match err {
+						RuntimeApiError::NotSupported { .. } => RecordedError::NotSupported,
+						err => RecordedError::Other(err.to_string()),
+					})
+					.encode()
+			},
+			decode: |mut encoded| {
+				let response = Result::<T, RecordedError>::decode(&mut encoded).ok()?;
+				Some(response.map_err(|err| match err {
+					RecordedError::NotSupported =>
+						RuntimeApiError::NotSupported { runtime_api_name: "recorded" },
+					RecordedError::Other(err) => RuntimeApiError::Execution {
+						runtime_api_name: "recorded",
+						source: std::sync::Arc::new(io::Error::other(err)),
+					},
+				}))
+			},
+		}
+	}
+}
+
+impl<T: Encode + Decode> ResponseCodec<Result<T, ChainApiError>> {
+	fn chain_api() -> Self {
+		Self {
+			encode: |response| response.as_ref().map_err(|err| err.to_string()).encode(),
+			decode: |mut encoded| {
+				let response = Result::<T, String>::decode(&mut encoded).ok()?;
+				Some(response.map_err(ChainApiError::from))
+			},
+		}
+	}
+}
+
+impl<T: Encode + Decode> ResponseCodec<T> {
+	fn plain() -> Self {
+		Self {
+			encode: |response| response.encode(),
+			decode: |mut encoded| T::decode(&mut encoded).ok(),
+		}
+	}
+}
+
+/// Hooks into the response channels of the recordable requests.
+trait ResponseHook {
+	/// Called with every recordable request and its response channel. Returns the channel to send
+	/// the request with, or `None` if the request was answered.
+	fn hook<R: Send + 'static>(
+		&mut self,
+		request: Vec<u8>,
+		tx: oneshot::Sender<R>,
+		codec: ResponseCodec<R>,
+	) -> Option<oneshot::Sender<R>>;
+}
+
+/// Pass the requests with a response channel of the variants listed to `hook`. The request is
+/// identified by the name of the variant, `prefix` and the other fields of the variant.
+macro_rules! hook_requests {
+	(
+		$msg:expr, $hook:expr, $prefix:expr, $codec:ident,
+		$enum:ident { $($variant:ident($($field:ident),*)),* $(,)? }
+	) => {
+		match $msg {
+			$($enum::$variant($($field,)* tx) => {
+				let name = concat!(stringify!($enum), "::", stringify!($variant));
+				let request = (name, &$prefix, $(&$field,)*).encode();
+				$enum::$variant($($field,)* $hook.hook(request, tx, ResponseCodec::$codec())?)
+			},)*
+			msg => msg,
+		}
+	};
+}
+
+/// Pass the response channel of `msg` to `hook`, if it is a recordable request. Returns `None` if
+/// the request was answered by `hook`.
+fn hook_request<H: ResponseHook>(msg: AllMessages, hook: &mut H) -> Option<AllMessages> {
+	let msg = match msg {
+		AllMessages::RuntimeApi(RuntimeApiMessage::Request(relay_parent, request)) => {
+			let request = hook_requests!(request, hook, relay_parent, runtime_api, RuntimeApiRequest {
+				Version(),
+				Authorities(),
+				Validators(),
+				ValidatorGroups(),
+				AvailabilityCores(),
+				PersistedValidationData(para_id, assumption),
+				SessionIndexForChild(),
+				ValidationCode(para_id, assumption),
+				ValidationCodeByHash(code_hash),
+				CandidatePendingAvailability(para_id),
+				CandidateEvents(),
+				SessionExecutorParams(session_index),
+				SessionInfo(session_index),
+				CurrentBabeEpoch(),
+				FetchOnChainVotes(),
+				PvfsRequirePrecheck(),
+				ValidationCodeHash(para_id, assumption),
+				Disputes(),
+				UnappliedSlashes(),
+				KeyOwnershipProof(validator_id),
+				MinimumBackingVotes(session_index),
+				DisabledValidators(),
+				AsyncBackingParams(),
+				NodeFeatures(session_index),
+				ApprovalVotingParams(session_index),
+				ClaimQueue(),
+				CandidatesPendingAvailability(para_id),
+				SchedulingLookahead(session_index),
+				ValidationCodeBombLimit(session_index),
+			});
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(relay_parent, request))
+		},
+		AllMessages::ChainApi(ChainApiMessage::Ancestors { hash, k, response_channel }) => {
+			let request = ("ChainApiMessage::Ancestors", &hash, k as u64).encode();
+			let response_channel =
+				hook.hook(request, response_channel, ResponseCodec::chain_api())?;
+			AllMessages::ChainApi(ChainApiMessage::Ancestors { hash, k, response_channel })
+		},
+		AllMessages::ChainApi(msg) => AllMessages::ChainApi(hook_requests!(
+			msg, hook, (), chain_api, ChainApiMessage {
+				BlockNumber(hash),
+				BlockHeader(hash),
+				BlockWeight(hash),
+				FinalizedBlockHash(number),
+				FinalizedBlockNumber(),
+			}
+		)),
+		AllMessages::AvailabilityStore(msg) => AllMessages::AvailabilityStore(hook_requests!(
+			msg, hook, (), plain, AvailabilityStoreMessage {
+				QueryAvailableData(candidate_hash),
+				QueryDataAvailability(candidate_hash),
+				QueryChunk(candidate_hash, validator_index),
+				QueryAllChunks(candidate_hash),
+				QueryChunkAvailability(candidate_hash, validator_index),
+			}
+		)),
+		msg => msg,
+	};
+	Some(msg)
+}
+
+/// Records the responses of the requests of a subsystem.
+struct RecordResponses<'a> {
+	recorder: &'a mut Recorder,
+	forwards: &'a mut mpsc::Sender<ForwardResponse>,
+	subsystem: &'static str,
+}
+
+impl ResponseHook for RecordResponses<'_> {
+	fn hook<R: Send + 'static>(
+		&mut self,
+		request: Vec<u8>,
+		tx: oneshot::Sender<R>,
+		codec: ResponseCodec<R>,
+	) -> Option<oneshot::Sender<R>> {
+		Some(self.recorder.record_response(
+			self.forwards,
+			self.subsystem,
+			request,
+			tx,
+			codec.encode,
+		))
+	}
+}
+
+/// The responses recorded for the requests of a subsystem, answering the same requests when the
+/// recording is replayed.
+#[derive(Debug, Default)]
+pub struct RecordedResponses {
+	responses: HashMap<Vec<u8>, VecDeque<Vec<u8>>>,
+}
+
+impl RecordedResponses {
+	/// Collect the responses to the requests of `subsystem` in `recording`.
+	pub fn new<'a>(recording: impl IntoIterator<Item = &'a RecordEntry>, subsystem: &str) -> Self {
+		let mut responses = HashMap::<_, VecDeque<_>>::new();
+		for entry in recording.into_iter().filter(|entry| entry.subsystem == subsystem) {
+			if let RecordedEvent::Response { request, response } = &entry.event {
+				responses.entry(request.clone()).or_default().push_back(response.clone());
+			}
+		}
+		Self { responses }
+	}
+
+	/// Answer `msg` with the next response recorded for the same request. The last response is
+	/// reused once all the others were used.
+	///
+	/// Returns `msg` back if no response was recorded for it.
+	pub fn answer(&mut self, msg: AllMessages) -> Option<AllMessages> {
+		hook_request(msg, self)
+	}
+}
+
+impl ResponseHook for RecordedResponses {
+	fn hook<R: Send + 'static>(
+		&mut self,
+		request: Vec<u8>,
+		tx: oneshot::Sender<R>,
+		codec: ResponseCodec<R>,
+	) -> Option<oneshot::Sender<R>> {
+		let Some(responses) = self.responses.get_mut(&request) else { return Some(tx) };
+		let Some(response) = responses.front().and_then(|encoded| (codec.decode)(encoded)) else {
+			return Some(tx)
+		};
+		if responses.len() > 1 {
+			responses.pop_front();
+		}
+		let _ = tx.send(response);
+		None
+	}
+}
+
+/// A dispute statement, as recorded. Statements are checked before they are sent to the dispute
+/// coordinator, so they are trusted when replayed.
+#[derive(Encode, Decode)]
+struct RecordedDisputeStatement {
+	statement: DisputeStatement,
+	candidate_hash: CandidateHash,
+	session_index: SessionIndex,
+	validator_public: ValidatorId,
+	validator_signature: ValidatorSignature,
+}
+
+#[derive(Encode, Decode)]
+enum RecordedDisputeCoordinatorMessage {
+	ImportStatements {
+		candidate_receipt: CandidateReceipt,
+		session: SessionIndex,
+		statements: Vec<(RecordedDisputeStatement, ValidatorIndex)>,
+	},
+	RecentDisputes,
+	ActiveDisputes,
+	QueryCandidateVotes(Vec<(SessionIndex, CandidateHash)>),
+	IssueLocalStatement(SessionIndex, CandidateHash, CandidateReceipt, bool),
+	DetermineUndisputedChain {
+		base: (BlockNumber, Hash),
+		block_descriptions: Vec<(Hash, SessionIndex, Vec<CandidateHash>)>,
+	},
+	ExportEvidence(Vec<(SessionIndex, CandidateHash)>),
+}
+
+impl RecordableMessage for DisputeCoordinatorMessage {
+	fn encode_for_recording(&self) -> Option<Vec<u8>> {
+		let recorded = match self {
+			DisputeCoordinatorMessage::ImportStatements {
+				candidate_receipt,
+				session,
+				statements,
+				..
+			} => RecordedDisputeCoordinatorMessage::ImportStatements {
+				candidate_receipt: candidate_receipt.clone(),
+				session: *session,
+				statements: statements
+					.iter()
+					.map(|(statement, index)| {
+						let statement = RecordedDisputeStatement {
+							statement: statement.statement().clone(),
+							candidate_hash: *statement.candidate_hash(),
+							session_index: statement.session_index(),
+							validator_public: statement.validator_public().clone(),
+							validator_signature: statement.validator_signature().clone(),
+						};
+						(statement, *index)
+					})
+					.collect(),
+			},
+			DisputeCoordinatorMessage::RecentDisputes(_) =>
+				RecordedDisputeCoordinatorMessage::RecentDisputes,
+			DisputeCoordinatorMessage::ActiveDisputes(_) =>
+				RecordedDisputeCoordinatorMessage::ActiveDisputes,
+			DisputeCoordinatorMessage::QueryCandidateVotes(query, _) =>
+				RecordedDisputeCoordinatorMessage::QueryCandidateVotes(query.clone()),
+			DisputeCoordinatorMessage::IssueLocalStatement(session, hash, receipt, valid) =>
+				RecordedDisputeCoordinatorMessage::IssueLocalStatement(
+					*session,
+					*hash,
+					receipt.clone(),
+					*valid,
+				),
+			DisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions,
+				..
+			} => RecordedDisputeCoordinatorMessage::DetermineUndisputedChain {
+				base: *base,
+				block_descriptions: block_descriptions
+					.iter()
+					.map(|desc| (desc.block_hash, desc.session, desc.candidates.clone()))
+					.collect(),
+			},
+			DisputeCoordinatorMessage::ExportEvidence(query, _) =>
+				RecordedDisputeCoordinatorMessage::ExportEvidence(query.clone()),
+		};
+		Some(recorded.encode())
+	}
+
+	fn decode_from_recording(mut encoded: &[u8]) -> Option<Self> {
+		let msg = match RecordedDisputeCoordinatorMessage::decode(&mut encoded).ok()? {
+			RecordedDisputeCoordinatorMessage::ImportStatements {
+				candidate_receipt,
+				session,
+				statements,
+			} => DisputeCoordinatorMessage::ImportStatements {
+				candidate_receipt,
+				session,
+				statements: statements
+					.into_iter()
+					.map(|(statement, index)| {
+						let statement = SignedDisputeStatement::new_unchecked_from_trusted_source(
+							statement.statement,
+							statement.candidate_hash,
+							statement.session_index,
+							statement.validator_public,
+							statement.validator_signature,
+						);
+						(statement, index)
+					})
+					.collect(),
+				pending_confirmation: None,
+			},
+			RecordedDisputeCoordinatorMessage::RecentDisputes =>
+				DisputeCoordinatorMessage::RecentDisputes(oneshot::channel().0),
+			RecordedDisputeCoordinatorMessage::ActiveDisputes =>
+				DisputeCoordinatorMessage::ActiveDisputes(oneshot::channel().0),
+			RecordedDisputeCoordinatorMessage::QueryCandidateVotes(query) =>
+				DisputeCoordinatorMessage::QueryCandidateVotes(query, oneshot::channel().0),
+			RecordedDisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				hash,
+				receipt,
+				valid,
+			) => DisputeCoordinatorMessage::IssueLocalStatement(session, hash, receipt, valid),
+			RecordedDisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions,
+			} => DisputeCoordinatorMessage::DetermineUndisputedChain {
+				base,
+				block_descriptions: block_descriptions
+					.into_iter()
+					.map(|(block_hash, session, candidates)| BlockDescription {
+						block_hash,
+						session,
+						candidates,
+					})
+					.collect(),
+				tx: oneshot::channel().0,
+			},
+			RecordedDisputeCoordinatorMessage::ExportEvidence(query) =>
+				DisputeCoordinatorMessage::ExportEvidence(query, oneshot::channel().0),
+		};
+		Some(msg)
+	}
+}
+
+#[derive(Encode, Decode)]
+enum RecordedApprovalVotingMessage {
+	ImportAssignment(IndirectAssignmentCertV2, CandidateBitfield, DelayTranche),
+	ImportApproval(IndirectSignedApprovalVoteV2),
+	ApprovedAncestor(Hash, BlockNumber),
+	GetApprovalSignaturesForCandidate(CandidateHash),
+}
+
+impl RecordableMessage for ApprovalVotingMessage {
+	fn encode_for_recording(&self) -> Option<Vec<u8>> {
+		let recorded = match self {
+			ApprovalVotingMessage::ImportAssignment(assignment, _) =>
+				RecordedApprovalVotingMessage::ImportAssignment(
+					assignment.assignment().clone(),
+					assignment.candidate_indices().clone(),
+					assignment.tranche(),
+				),
+			ApprovalVotingMessage::ImportApproval(vote, _) =>
+				RecordedApprovalVotingMessage::ImportApproval((**vote).clone()),
+			ApprovalVotingMessage::ApprovedAncestor(hash, number, _) =>
+				RecordedApprovalVotingMessage::ApprovedAncestor(*hash, *number),
+			ApprovalVotingMessage::GetApprovalSignaturesForCandidate(candidate_hash, _) =>
+				RecordedApprovalVotingMessage::GetApprovalSignaturesForCandidate(*candidate_hash),
+		};
+		Some(recorded.encode())
+	}
+
+	fn decode_from_recording(mut encoded: &[u8]) -> Option<Self> {
+		let msg = match RecordedApprovalVotingMessage::decode(&mut encoded).ok()? {
+			RecordedApprovalVotingMessage::ImportAssignment(assignment, candidates, tranche) =>
+				ApprovalVotingMessage::ImportAssignment(
+					CheckedIndirectAssignment::from_checked(assignment, candidates, tranche),
+					None,
+				),
+			RecordedApprovalVotingMessage::ImportApproval(vote) =>
+				ApprovalVotingMessage::ImportApproval(
+					CheckedIndirectSignedApprovalVote::from_checked(vote),
+					None,
+				),
+			RecordedApprovalVotingMessage::ApprovedAncestor(hash, number) =>
+				ApprovalVotingMessage::ApprovedAncestor(hash, number, oneshot::channel().0),
+			RecordedApprovalVotingMessage::GetApprovalSignaturesForCandidate(candidate_hash) =>
+				ApprovalVotingMessage::GetApprovalSignaturesForCandidate(
+					candidate_hash,
+					oneshot::channel().0,
+				),
+		};
+		Some(msg)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_matches::assert_matches;
+
+	fn config(directory: &Path) -> RecordingConfig {
+		RecordingConfig { directory: directory.to_owned(), max_file_size: 64, max_files: 2 }
+	}
+
+	fn record(config: RecordingConfig, events: Vec<RecordedEvent>) {
+		let (mut recorder, writer) = Recorder::new(config, Default::default());
+		for event in events {
+			recorder.record("test-subsystem", event);
+		}
+		drop(recorder);
+		futures::executor::block_on(writer.run());
+	}
+
+	#[test]
+	fn signals_roundtrip() {
+		let signal = RecordedSignal::ActiveLeaves {
+			activated: Some((Hash::repeat_byte(1), 1)),
+			deactivated: vec![Hash::repeat_byte(2)],
+		};
+
+		assert_matches!(signal.clone().into_signal(), OverseerSignal::ActiveLeaves(update) => {
+			assert_eq!(update.activated.as_ref().map(|leaf| (leaf.hash, leaf.number)), Some((Hash::repeat_byte(1), 1)));
+			assert_eq!(update.deactivated.to_vec(), vec![Hash::repeat_byte(2)]);
+			assert_eq!(RecordedSignal::from(&OverseerSignal::ActiveLeaves(update)), signal);
+		});
+	}
+
+	#[test]
+	fn messages_roundtrip() {
+		let base = (1, Hash::repeat_byte(1));
+		let descriptions = vec![BlockDescription {
+			block_hash: Hash::repeat_byte(2),
+			session: 3,
+			candidates: vec![CandidateHash(Hash::repeat_byte(4))],
+		}];
+		let msg = DisputeCoordinatorMessage::DetermineUndisputedChain {
+			base,
+			block_descriptions: descriptions.clone(),
+			tx: oneshot::channel().0,
+		};
+
+		let encoded = msg.encode_for_recording().unwrap();
+		assert_matches!(
+			DisputeCoordinatorMessage::decode_from_recording(&encoded),
+			Some(DisputeCoordinatorMessage::DetermineUndisputedChain { base: b, block_descriptions, .. }) => {
+				assert_eq!(b, base);
+				assert_eq!(block_descriptions.len(), 1);
+				assert_eq!(block_descriptions[0].block_hash, descriptions[0].block_hash);
+				assert_eq!(block_descriptions[0].candidates, descriptions[0].candidates);
+			}
+		);
+
+		let msg =
+			ApprovalVotingMessage::ApprovedAncestor(Hash::repeat_byte(5), 6, oneshot::channel().0);
+		let encoded = msg.encode_for_recording().unwrap();
+		assert_matches!(
+			ApprovalVotingMessage::decode_from_recording(&encoded),
+			Some(ApprovalVotingMessage::ApprovedAncestor(hash, 6, _)) if hash == Hash::repeat_byte(5)
+		);
+		assert!(ApprovalVotingMessage::decode_from_recording(&[0xff]).is_none());
+	}
+
+	#[test]
+	fn recording_is_written_and_read_back_in_order() {
+		let dir = tempfile::tempdir().unwrap();
+		let events: Vec<_> = (0..4u32)
+			.map(|n| {
+				RecordedEvent::Signal(RecordedSignal::BlockFinalized(Hash::repeat_byte(n as u8), n))
+			})
+			.collect();
+
+		record(RecordingConfig::new(dir.path().to_owned()), events.clone());
+
+		let entries = read_recording(dir.path()).unwrap();
+		assert_eq!(entries.iter().map(|entry| entry.event.clone()).collect::<Vec<_>>(), events);
+		assert!(entries.iter().all(|entry| entry.subsystem == "test-subsystem"));
+	}
+
+	#[test]
+	fn responses_are_forwarded_before_being_written() {
+		let dir = tempfile::tempdir().unwrap();
+		let (mut recorder, writer) = Recorder::new(config(dir.path()), Default::default());
+		let (mut forwards, rx) = mpsc::channel(RESPONSES_CHANNEL_SIZE);
+		let (tx, response) = oneshot::channel::<u32>();
+		let recorded_tx =
+			recorder.record_response(&mut forwards, "test-subsystem", vec![1], tx, |response| {
+				response.encode()
+			});
+		drop((recorder, forwards));
+		recorded_tx.send(7).unwrap();
+
+		// The requester gets the response while nothing is written.
+		futures::executor::block_on(forward_responses(rx));
+		assert_eq!(futures::executor::block_on(response), Ok(7));
+
+		futures::executor::block_on(writer.run());
+		let entries = read_recording(dir.path()).unwrap();
+		assert_eq!(
+			entries.into_iter().map(|entry| entry.event).collect::<Vec<_>>(),
+			vec![RecordedEvent::Response { request: vec![1], response: 7u32.encode() }],
+		);
+	}
+
+	#[test]
+	fn recording_files_are_rotated() {
+		let dir = tempfile::tempdir().unwrap();
+		let events: Vec<_> =
+			(0..32u32).map(|n| RecordedEvent::Message(vec![n as u8; 32])).collect();
+
+		record(config(dir.path()), events.clone());
+
+		let files = recording_files(dir.path()).unwrap();
+		assert_eq!(files.len(), 2);
+
+		// Only the most recent entries are kept, in order.
+		let entries = read_recording(dir.path()).unwrap();
+		assert!(!entries.is_empty() && entries.len() < events.len());
+		let kept = events[events.len() - entries.len()..].to_vec();
+		assert_eq!(entries.into_iter().map(|entry| entry.event).collect::<Vec<_>>(), kept);
+
+		// A new recording continues after the existing files.
+		record(config(dir.path()), vec![RecordedEvent::Message(vec![])]);
+		let files = recording_files(dir.path()).unwrap();
+		assert_eq!(files.len(), 2);
+		assert_eq!(files.last().unwrap().0, 17);
+	}
+}
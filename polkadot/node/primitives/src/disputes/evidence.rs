@@ -0,0 +1,224 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `DisputeEvidence` and associated types.
+//!
+//! Dispute evidence is a self-contained record of a dispute, as exported by the dispute
+//! coordinator: the disputed candidate, every signed statement about it and the validator set of
+//! its session. It can be verified offline, i.e. to attach it to governance proposals. The
+//! validator set contained in the evidence is never trusted: it has to match the validator set of
+//! the session as obtained from the chain, or a hash of it obtained from a trusted source.
+
+use std::collections::BTreeSet;
+
+use codec::{Decode, Encode};
+use thiserror::Error;
+
+use super::DisputeStatus;
+use polkadot_primitives::{
+	supermajority_threshold, vstaging::CandidateReceiptV2 as CandidateReceipt, BlakeTwo256,
+	CandidateHash, DisputeStatement, Hash, HashT, SessionIndex, ValidatorId, ValidatorIndex,
+	ValidatorSignature,
+};
+
+/// A signed statement of a validator about a disputed candidate.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct EvidenceStatement {
+	/// The index of the validator in the validator set of the session.
+	pub validator_index: ValidatorIndex,
+	/// The statement.
+	pub statement: DisputeStatement,
+	/// The signature of the validator on the statement.
+	pub signature: ValidatorSignature,
+}
+
+/// All the information known about a dispute.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct DisputeEvidence {
+	/// The session the candidate appears in.
+	pub session_index: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_receipt: CandidateReceipt,
+	/// The validators of the session, in session order.
+	pub validators: Vec<ValidatorId>,
+	/// All signed statements about the candidate.
+	pub statements: Vec<EvidenceStatement>,
+	/// The status of the dispute, as seen by the exporting node.
+	pub status: DisputeStatus,
+}
+
+/// Versioned `DisputeEvidence`, which is the exported format.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum VersionedDisputeEvidence {
+	/// The first version.
+	#[codec(index = 1)]
+	V1(DisputeEvidence),
+}
+
+impl From<DisputeEvidence> for VersionedDisputeEvidence {
+	fn from(evidence: DisputeEvidence) -> Self {
+		VersionedDisputeEvidence::V1(evidence)
+	}
+}
+
+impl From<VersionedDisputeEvidence> for DisputeEvidence {
+	fn from(evidence: VersionedDisputeEvidence) -> Self {
+		match evidence {
+			VersionedDisputeEvidence::V1(evidence) => evidence,
+		}
+	}
+}
+
+/// The outcome of a dispute, as determined by its votes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeOutcome {
+	/// A supermajority of validators voted for the validity of the candidate.
+	Valid,
+	/// A supermajority of validators voted against the validity of the candidate.
+	Invalid,
+	/// The dispute did not conclude.
+	Unconcluded,
+}
+
+/// Dispute evidence with all signatures checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedDisputeEvidence {
+	/// The hash of the disputed candidate.
+	pub candidate_hash: CandidateHash,
+	/// Validators which voted for the validity of the candidate.
+	pub valid_votes: BTreeSet<ValidatorIndex>,
+	/// Validators which voted against the validity of the candidate.
+	pub invalid_votes: BTreeSet<ValidatorIndex>,
+	/// The number of votes needed to conclude the dispute.
+	pub threshold: usize,
+	/// The outcome of the dispute.
+	pub outcome: DisputeOutcome,
+}
+
+/// Things that can go wrong when verifying dispute evidence.
+#[derive(Error, Debug, PartialEq)]
+pub enum EvidenceError {
+	/// The validators of the evidence are not the trusted validator set of the session.
+	#[error("The validators of the evidence are not the validators of the session")]
+	ValidatorSetMismatch,
+
+	/// A statement refers to a validator which is not part of the session.
+	#[error("Validator index {0:?} is not part of the session")]
+	ValidatorIndexOutOfBounds(ValidatorIndex),
+
+	/// A signature did not check out.
+	#[error("Invalid signature of validator {0:?}")]
+	InvalidSignature(ValidatorIndex),
+
+	/// A validator voted more than once on the same side.
+	#[error("Duplicate vote of validator {0:?}")]
+	DuplicateVote(ValidatorIndex),
+
+	/// The recorded status claims an outcome the votes do not support.
+	#[error("Dispute status {status:?} is not supported by the votes, outcome is {outcome:?}")]
+	StatusMismatch {
+		/// The status of the evidence.
+		status: DisputeStatus,
+		/// The outcome according to the votes.
+		outcome: DisputeOutcome,
+	},
+}
+
+impl DisputeEvidence {
+	/// The hash of the disputed candidate.
+	pub fn candidate_hash(&self) -> CandidateHash {
+		self.candidate_receipt.hash()
+	}
+
+	/// The hash of the validator set contained in the evidence.
+	pub fn validators_hash(&self) -> Hash {
+		BlakeTwo256::hash_of(&self.validators)
+	}
+
+	/// Check all signatures and determine the outcome of the dispute, given the validator set of
+	/// the session as obtained from the chain.
+	pub fn verify(
+		&self,
+		validators: &[ValidatorId],
+	) -> Result<VerifiedDisputeEvidence, EvidenceError> {
+		if self.validators != validators {
+			return Err(EvidenceError::ValidatorSetMismatch)
+		}
+		self.verify_votes()
+	}
+
+	/// Like [`Self::verify`], but given the hash of the validator set of the session, as obtained
+	/// from a trusted source.
+	pub fn verify_against_hash(
+		&self,
+		validators_hash: Hash,
+	) -> Result<VerifiedDisputeEvidence, EvidenceError> {
+		if self.validators_hash() != validators_hash {
+			return Err(EvidenceError::ValidatorSetMismatch)
+		}
+		self.verify_votes()
+	}
+
+	fn verify_votes(&self) -> Result<VerifiedDisputeEvidence, EvidenceError> {
+		let candidate_hash = self.candidate_hash();
+		let mut valid_votes = BTreeSet::new();
+		let mut invalid_votes = BTreeSet::new();
+
+		for EvidenceStatement { validator_index, statement, signature } in &self.statements {
+			let validator_public = self
+				.validators
+				.get(validator_index.0 as usize)
+				.ok_or(EvidenceError::ValidatorIndexOutOfBounds(*validator_index))?;
+
+			statement
+				.check_signature(validator_public, candidate_hash, self.session_index, signature)
+				.map_err(|()| EvidenceError::InvalidSignature(*validator_index))?;
+
+			let votes =
+				if statement.indicates_validity() { &mut valid_votes } else { &mut invalid_votes };
+			if !votes.insert(*validator_index) {
+				return Err(EvidenceError::DuplicateVote(*validator_index))
+			}
+		}
+
+		let threshold = supermajority_threshold(self.validators.len());
+		// Concluding against takes precedence, like in the dispute coordinator.
+		let outcome = if invalid_votes.len() >= threshold {
+			DisputeOutcome::Invalid
+		} else if valid_votes.len() >= threshold {
+			DisputeOutcome::Valid
+		} else {
+			DisputeOutcome::Unconcluded
+		};
+
+		let consistent = match self.status {
+			DisputeStatus::ConcludedFor(_) => outcome == DisputeOutcome::Valid,
+			DisputeStatus::ConcludedAgainst(_) => outcome == DisputeOutcome::Invalid,
+			DisputeStatus::Active | DisputeStatus::Confirmed => true,
+		};
+		if !consistent {
+			return Err(EvidenceError::StatusMismatch { status: self.status, outcome })
+		}
+
+		Ok(VerifiedDisputeEvidence {
+			candidate_hash,
+			valid_votes,
+			invalid_votes,
+			threshold,
+			outcome,
+		})
+	}
+}
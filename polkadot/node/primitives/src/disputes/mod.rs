@@ -31,6 +31,11 @@ use polkadot_primitives::{
 };
 
 /// `DisputeMessage` and related types.
+mod evidence;
+pub use evidence::{
+	DisputeEvidence, DisputeOutcome, EvidenceError, EvidenceStatement, VerifiedDisputeEvidence,
+	VersionedDisputeEvidence,
+};
 mod message;
 pub use message::{DisputeMessage, Error as DisputeMessageCheckError, UncheckedDisputeMessage};
 mod status;
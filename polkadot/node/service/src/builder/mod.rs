@@ -24,7 +24,7 @@ pub(crate) use partial::{new_partial, new_partial_basics};
 
 use crate::{
 	grandpa_support, open_database,
-	overseer::{ExtendedOverseerGenArgs, OverseerGen, OverseerGenArgs},
+	overseer::{ExtendedOverseerGenArgs, OverseerGen, OverseerGenArgs, RecordingConfig},
 	parachains_db,
 	relay_chain_selection::SelectRelayChain,
 	workers, Chain, Error, FullBackend, FullClient, IdentifyVariant, IsParachainNode,
@@ -83,8 +83,14 @@ pub struct NewFullParams<OverseerGenerator: OverseerGen> {
 	pub prepare_workers_soft_max_num: Option<usize>,
 	/// An optional absolute number of pvf workers that can be spawned in the pvf prepare pool.
 	pub prepare_workers_hard_max_num: Option<usize>,
+	/// An optional total size of the prepared pvf artifacts, in bytes, above which the least
+	/// recently needed ones are pruned.
+	pub pvf_artifacts_cache_size_limit: Option<u64>,
 	/// How long finalized data should be kept in the availability store (in hours)
 	pub keep_finalized_for: Option<u32>,
+	/// An optional directory to record the messages received by some subsystems to, for
+	/// post-mortem debugging.
+	pub overseer_recording_path: Option<std::path::PathBuf>,
 	pub overseer_gen: OverseerGenerator,
 	pub overseer_message_channel_capacity_override: Option<usize>,
 	#[allow(dead_code)]
@@ -92,6 +98,9 @@ pub struct NewFullParams<OverseerGenerator: OverseerGen> {
 	pub hwbench: Option<sc_sysinfo::HwBench>,
 	/// Enable approval voting processing in parallel.
 	pub enable_approval_voting_parallel: bool,
+	/// Pick and order the availability recovery strategies based on the latency and failures
+	/// observed from the validators.
+	pub adaptive_availability_recovery: bool,
 }
 
 /// Completely built polkadot node service.
@@ -150,8 +159,14 @@ where
 			SelectRelayChain::new_longest_chain(basics.backend.clone())
 		};
 
-		let partial_components =
-			new_partial::<SelectRelayChain<_>>(&mut config, basics, select_chain)?;
+		// Only validators run the subsystems the operator RPCs talk to.
+		let rpc_overseer_handle = config.role.is_authority().then(|| overseer_handle.clone());
+		let partial_components = new_partial::<SelectRelayChain<_>>(
+			&mut config,
+			basics,
+			select_chain,
+			rpc_overseer_handle,
+		)?;
 
 		let net_config = sc_network::config::FullNetworkConfiguration::<_, _, Network>::new(
 			&config.network,
@@ -201,8 +216,11 @@ where
 					execute_workers_max_num,
 					prepare_workers_soft_max_num,
 					prepare_workers_hard_max_num,
+					pvf_artifacts_cache_size_limit,
 					keep_finalized_for,
+					overseer_recording_path,
 					enable_approval_voting_parallel,
+					adaptive_availability_recovery,
 				},
 			overseer_connector,
 			partial_components:
@@ -393,6 +411,7 @@ where
 					pvf_execute_workers_max_num: execute_workers_max_num.unwrap_or(4),
 					pvf_prepare_workers_soft_max_num: prepare_workers_soft_max_num.unwrap_or(1),
 					pvf_prepare_workers_hard_max_num: prepare_workers_hard_max_num.unwrap_or(2),
+					pvf_artifacts_cache_size_limit,
 				})
 			} else {
 				None
@@ -445,6 +464,7 @@ where
 				chain_selection_config,
 				fetch_chunks_threshold,
 				enable_approval_voting_parallel,
+				overseer_recording: overseer_recording_path.map(RecordingConfig::new),
 			})
 		};
 
@@ -617,6 +637,7 @@ where
 						req_protocol_names,
 						peerset_protocol_names,
 						notification_services,
+						adaptive_availability_recovery,
 					},
 					ext_overseer_args,
 				)
@@ -74,7 +74,9 @@ use prometheus_endpoint::Registry;
 use sc_service::SpawnTaskHandle;
 
 pub use chain_spec::{GenericChainSpec, RococoChainSpec, WestendChainSpec};
-pub use polkadot_primitives::{Block, BlockId, BlockNumber, CollatorPair, Hash, Id as ParaId};
+pub use polkadot_primitives::{
+	Block, BlockId, BlockNumber, CandidateHash, CollatorPair, Hash, Id as ParaId,
+};
 pub use sc_client_api::{Backend, CallExecutor};
 pub use sc_consensus::{BlockImport, LongestChain};
 pub use sc_executor::NativeExecutionDispatch;
@@ -417,7 +419,7 @@ macro_rules! chain_ops {
 		let chain_selection = LongestChain::new(basics.backend.clone());
 
 		let sc_service::PartialComponents { client, backend, import_queue, task_manager, .. } =
-			new_partial::<LongestChain<_, Block>>(&mut config, basics, chain_selection)?;
+			new_partial::<LongestChain<_, Block>>(&mut config, basics, chain_selection, None)?;
 		Ok((client, backend, import_queue, task_manager))
 	}};
 }
@@ -521,7 +523,11 @@ fn revert_chain_selection(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::R
 		stagnant_check_mode: chain_selection_subsystem::StagnantCheckMode::PruneOnly,
 	};
 
-	let chain_selection = chain_selection_subsystem::ChainSelectionSubsystem::new(config, db);
+	let chain_selection = chain_selection_subsystem::ChainSelectionSubsystem::new(
+		config,
+		db,
+		chain_selection_subsystem::Metrics::default(),
+	);
 
 	chain_selection
 		.revert_to(hash)
@@ -551,3 +557,17 @@ fn revert_approval_voting(
 		.revert_to(hash)
 		.map_err(|err| sp_blockchain::Error::Backend(err.to_string()))
 }
+
+/// Opens the approval-voting data in the parachains-db for offline inspection and repair.
+///
+/// The node must not be running.
+#[cfg(feature = "full-node")]
+pub fn approval_db_inspector(
+	config: &Configuration,
+) -> Result<approval_voting_subsystem::inspect::ApprovalDbInspector, Error> {
+	let parachains_db = open_database(&config.database)?;
+	Ok(approval_voting_subsystem::inspect::ApprovalDbInspector::new(
+		parachains_db,
+		parachains_db::REAL_COLUMNS.col_approval_data,
+	))
+}
@@ -16,6 +16,7 @@
 
 use super::{Error, IsParachainNode, Registry};
 use polkadot_node_subsystem_types::{ChainApiBackend, RuntimeApiSubsystemClient};
+pub use polkadot_overseer::recording::RecordingConfig;
 use polkadot_overseer::{DummySubsystem, InitializedOverseerBuilder, SubsystemError};
 use sp_core::traits::SpawnNamed;
 
@@ -34,8 +35,9 @@ use polkadot_node_network_protocol::{
 #[cfg(any(feature = "malus", test))]
 pub use polkadot_overseer::{dummy::dummy_overseer_builder, HeadSupportsParachains};
 use polkadot_overseer::{
-	metrics::Metrics as OverseerMetrics, MetricsTrait, Overseer, OverseerConnector, OverseerHandle,
-	SpawnGlue,
+	metrics::Metrics as OverseerMetrics,
+	recording::{Recorder, RecordingMetrics, RecordingSubsystem},
+	MetricsTrait, Overseer, OverseerConnector, OverseerHandle, SpawnGlue,
 };
 
 use parking_lot::Mutex;
@@ -74,6 +76,8 @@ pub use polkadot_node_core_pvf_checker::PvfCheckerSubsystem;
 pub use polkadot_node_core_runtime_api::RuntimeApiSubsystem;
 pub use polkadot_statement_distribution::StatementDistributionSubsystem;
 
+const LOG_TARGET: &str = "parachain::overseer-gen";
+
 /// Arguments passed for overseer construction.
 pub struct OverseerGenArgs<'a, Spawner, RuntimeClient>
 where
@@ -108,6 +112,9 @@ where
 	pub peerset_protocol_names: PeerSetProtocolNames,
 	/// Notification services for validation/collation protocols.
 	pub notification_services: HashMap<PeerSet, Box<dyn NotificationService>>,
+	/// Pick and order the availability recovery strategies based on the latency and failures
+	/// observed from the validators.
+	pub adaptive_availability_recovery: bool,
 }
 
 pub struct ExtendedOverseerGenArgs {
@@ -142,6 +149,9 @@ pub struct ExtendedOverseerGenArgs {
 	/// Enable approval-voting-parallel subsystem and disable the standalone approval-voting and
 	/// approval-distribution subsystems.
 	pub enable_approval_voting_parallel: bool,
+	/// Record the signals and messages received by the dispute-coordinator and approval-voting
+	/// subsystems, for post-mortem debugging.
+	pub overseer_recording: Option<RecordingConfig>,
 }
 
 /// Obtain a prepared validator `Overseer`, that is initialized with all default values.
@@ -165,6 +175,7 @@ pub fn validator_overseer_builder<Spawner, RuntimeClient>(
 		req_protocol_names,
 		peerset_protocol_names,
 		notification_services,
+		adaptive_availability_recovery,
 	}: OverseerGenArgs<Spawner, RuntimeClient>,
 	ExtendedOverseerGenArgs {
 		keystore,
@@ -181,6 +192,7 @@ pub fn validator_overseer_builder<Spawner, RuntimeClient>(
 		chain_selection_config,
 		fetch_chunks_threshold,
 		enable_approval_voting_parallel,
+		overseer_recording: _,
 	}: ExtendedOverseerGenArgs,
 ) -> Result<
 	InitializedOverseerBuilder<
@@ -263,12 +275,15 @@ where
 			req_protocol_names.clone(),
 			Metrics::register(registry)?,
 		))
-		.availability_recovery(AvailabilityRecoverySubsystem::for_validator(
-			fetch_chunks_threshold,
-			available_data_req_receiver,
-			&req_protocol_names,
-			Metrics::register(registry)?,
-		))
+		.availability_recovery(
+			AvailabilityRecoverySubsystem::for_validator(
+				fetch_chunks_threshold,
+				available_data_req_receiver,
+				&req_protocol_names,
+				Metrics::register(registry)?,
+			)
+			.with_adaptive_strategy(adaptive_availability_recovery),
+		)
 		.availability_store(AvailabilityStoreSubsystem::new(
 			parachains_db.clone(),
 			availability_config,
@@ -350,7 +365,11 @@ where
 			authority_discovery_service.clone(),
 			Metrics::register(registry)?,
 		))
-		.chain_selection(ChainSelectionSubsystem::new(chain_selection_config, parachains_db))
+		.chain_selection(ChainSelectionSubsystem::new(
+			chain_selection_config,
+			parachains_db,
+			Metrics::register(registry)?,
+		))
 		.prospective_parachains(ProspectiveParachainsSubsystem::new(Metrics::register(registry)?))
 		.activation_external_listeners(Default::default())
 		.active_leaves(Default::default())
@@ -387,6 +406,7 @@ pub fn validator_with_parallel_overseer_builder<Spawner, RuntimeClient>(
 		req_protocol_names,
 		peerset_protocol_names,
 		notification_services,
+		adaptive_availability_recovery,
 	}: OverseerGenArgs<Spawner, RuntimeClient>,
 	ExtendedOverseerGenArgs {
 		keystore,
@@ -403,6 +423,7 @@ pub fn validator_with_parallel_overseer_builder<Spawner, RuntimeClient>(
 		chain_selection_config,
 		fetch_chunks_threshold,
 		enable_approval_voting_parallel,
+		overseer_recording: _,
 	}: ExtendedOverseerGenArgs,
 ) -> Result<
 	InitializedOverseerBuilder<
@@ -484,12 +505,15 @@ where
 			req_protocol_names.clone(),
 			Metrics::register(registry)?,
 		))
-		.availability_recovery(AvailabilityRecoverySubsystem::for_validator(
-			fetch_chunks_threshold,
-			available_data_req_receiver,
-			&req_protocol_names,
-			Metrics::register(registry)?,
-		))
+		.availability_recovery(
+			AvailabilityRecoverySubsystem::for_validator(
+				fetch_chunks_threshold,
+				available_data_req_receiver,
+				&req_protocol_names,
+				Metrics::register(registry)?,
+			)
+			.with_adaptive_strategy(adaptive_availability_recovery),
+		)
 		.availability_store(AvailabilityStoreSubsystem::new(
 			parachains_db.clone(),
 			availability_config,
@@ -568,7 +592,11 @@ where
 			authority_discovery_service.clone(),
 			Metrics::register(registry)?,
 		))
-		.chain_selection(ChainSelectionSubsystem::new(chain_selection_config, parachains_db))
+		.chain_selection(ChainSelectionSubsystem::new(
+			chain_selection_config,
+			parachains_db,
+			Metrics::register(registry)?,
+		))
 		.prospective_parachains(ProspectiveParachainsSubsystem::new(Metrics::register(registry)?))
 		.activation_external_listeners(Default::default())
 		.active_leaves(Default::default())
@@ -601,6 +629,7 @@ pub fn collator_overseer_builder<Spawner, RuntimeClient>(
 		req_protocol_names,
 		peerset_protocol_names,
 		notification_services,
+		adaptive_availability_recovery,
 	}: OverseerGenArgs<Spawner, RuntimeClient>,
 ) -> Result<
 	InitializedOverseerBuilder<
@@ -671,12 +700,15 @@ where
 			false,
 		))
 		.availability_distribution(DummySubsystem)
-		.availability_recovery(AvailabilityRecoverySubsystem::for_collator(
-			None,
-			available_data_req_receiver,
-			&req_protocol_names,
-			Metrics::register(registry)?,
-		))
+		.availability_recovery(
+			AvailabilityRecoverySubsystem::for_collator(
+				None,
+				available_data_req_receiver,
+				&req_protocol_names,
+				Metrics::register(registry)?,
+			)
+			.with_adaptive_strategy(adaptive_availability_recovery),
+		)
 		.availability_store(DummySubsystem)
 		.bitfield_distribution(DummySubsystem)
 		.bitfield_signing(DummySubsystem)
@@ -766,14 +798,54 @@ impl OverseerGen for ValidatorOverseerGen {
 			"create validator overseer as mandatory extended arguments were not provided"
 				.to_owned(),
 		)))?;
+		let recorder = match ext_args.overseer_recording.clone() {
+			Some(config) => {
+				let metrics = RecordingMetrics::register(args.registry)?;
+				let (recorder, writer) = Recorder::new(config, metrics);
+				args.spawner.spawn_blocking(
+					"overseer-recording-writer",
+					Some("overseer"),
+					Box::pin(writer.run()),
+				);
+				Some(recorder)
+			},
+			None => None,
+		};
+
 		if ext_args.enable_approval_voting_parallel {
-			validator_with_parallel_overseer_builder(args, ext_args)?
-				.build_with_connector(connector)
-				.map_err(|e| e.into())
+			let builder = validator_with_parallel_overseer_builder(args, ext_args)?;
+			match recorder {
+				Some(recorder) => {
+					// The messages of approval-voting-parallel can't be recorded yet. The CLI rejects
+					// this combination, only other users of the service get here.
+					gum::error!(
+						target: LOG_TARGET,
+						"Overseer recording does not support approval-voting-parallel, only the \
+						dispute-coordinator is recorded",
+					);
+					builder
+						.replace_dispute_coordinator(|dc| {
+							RecordingSubsystem::new(dc, recorder, "dispute-coordinator")
+						})
+						.build_with_connector(connector)
+				},
+				None => builder.build_with_connector(connector),
+			}
+			.map_err(|e| e.into())
 		} else {
-			validator_overseer_builder(args, ext_args)?
-				.build_with_connector(connector)
-				.map_err(|e| e.into())
+			let builder = validator_overseer_builder(args, ext_args)?;
+			match recorder {
+				Some(recorder) => builder
+					.replace_dispute_coordinator(|dc| {
+						RecordingSubsystem::new(dc, recorder.clone(), "dispute-coordinator")
+					})
+					.replace_approval_voting(|av| {
+						RecordingSubsystem::new(av, recorder, "approval-voting")
+					})
+					.build_with_connector(connector),
+				None => builder.build_with_connector(connector),
+			}
+			.map_err(|e| e.into())
 		}
 	}
 }
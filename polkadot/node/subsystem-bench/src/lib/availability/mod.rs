@@ -36,7 +36,9 @@ use polkadot_availability_bitfield_distribution::BitfieldDistribution;
 use polkadot_availability_distribution::{
 	AvailabilityDistributionSubsystem, IncomingRequestReceivers,
 };
-use polkadot_availability_recovery::{AvailabilityRecoverySubsystem, RecoveryStrategyKind};
+use polkadot_availability_recovery::{
+	AvailabilityRecoverySubsystem, RecoveryStrategyKind, FETCH_CHUNKS_THRESHOLD,
+};
 use polkadot_node_core_av_store::AvailabilityStoreSubsystem;
 use polkadot_node_metrics::metrics::Metrics;
 use polkadot_node_network_protocol::{
@@ -75,6 +77,9 @@ pub enum Strategy {
 	/// Fetch the full availability datafrom backers first. Saves CPU as we don't need to
 	/// re-construct from chunks. Typically this is only faster if nodes have enough bandwidth.
 	FullFromBackers,
+	/// Pick and order the strategies above based on the latency observed from backers and chunk
+	/// holders, racing them for large PoVs.
+	Adaptive,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, clap::Parser)]
@@ -239,6 +244,12 @@ pub fn prepare_test(
 					Metrics::try_register(&dependencies.registry).unwrap(),
 					RecoveryStrategyKind::SystematicChunks,
 				),
+				Strategy::Adaptive => AvailabilityRecoverySubsystem::with_recovery_strategy_kind(
+					collation_req_receiver,
+					&state.req_protocol_names,
+					Metrics::try_register(&dependencies.registry).unwrap(),
+					RecoveryStrategyKind::Adaptive(FETCH_CHUNKS_THRESHOLD),
+				),
 			};
 
 			// Use a mocked av-store.
@@ -0,0 +1,175 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	collator_protocol::CollatorProtocolOptions,
+	configuration::{TestAuthorities, TestConfiguration},
+	network::{HandleNetworkMessage, NetworkMessage},
+};
+use codec::Encode;
+use polkadot_node_network_protocol::request_response::{v2::CollationFetchingResponse, Requests};
+use polkadot_node_primitives::{BlockData, PoV};
+use polkadot_node_subsystem_test_helpers::mock::new_block_import_info;
+use polkadot_overseer::BlockInfo;
+use polkadot_primitives::{
+	vstaging::{CandidateReceiptV2 as CandidateReceipt, MutateDescriptorV2},
+	BlockNumber, CandidateCommitments, CollatorPair, CoreIndex, Hash, Header, Id,
+	PersistedValidationData,
+};
+use polkadot_primitives_test_helpers::{dummy_candidate_receipt_v2, dummy_head_data, dummy_pvd};
+use sc_network::ProtocolName;
+use sp_core::{Pair, H256};
+use std::collections::HashMap;
+
+const SESSION_INDEX: u32 = 0;
+
+#[derive(Clone)]
+pub struct TestState {
+	// Full test config
+	pub config: TestConfiguration,
+	// Collator protocol specific options
+	pub options: CollatorProtocolOptions,
+	// Authority keys for the network emulation.
+	pub test_authorities: TestAuthorities,
+	// Relay chain block infos
+	pub block_infos: Vec<BlockInfo>,
+	// Relay chain block headers
+	pub block_headers: HashMap<H256, Header>,
+	// PersistedValidationData, we use one for all candidates
+	pub pvd: PersistedValidationData,
+	// Candidates scheduled on each core, they define the claim queue
+	pub candidate_receipts: HashMap<H256, Vec<CandidateReceipt>>,
+	// Minimum relay parents of the para the node under test is assigned to
+	pub min_relay_parents: HashMap<H256, Vec<(Id, BlockNumber)>>,
+	// The para the node under test is assigned to
+	pub para_id: Id,
+	// Keys of the emulated collators
+	pub collator_pairs: Vec<CollatorPair>,
+	// PoV of every collator, reused across blocks
+	pub povs: Vec<PoV>,
+	// Collations advertised at every block, one per collator
+	pub collations: HashMap<H256, Vec<CandidateReceipt>>,
+}
+
+impl TestState {
+	pub fn new(config: &TestConfiguration, options: &CollatorProtocolOptions) -> Self {
+		let test_authorities = config.generate_authorities();
+		let block_infos: Vec<BlockInfo> =
+			(1..=config.num_blocks).map(generate_block_info).collect();
+		let block_headers = block_infos.iter().map(generate_block_header).collect();
+		let pvd = dummy_pvd(dummy_head_data(), 0);
+		// The node under test is in the first backing group, which is assigned to the first core.
+		let para_id = Id::new(1);
+		let pov_size = *config.pov_sizes().first().expect("There is at least one core; qed");
+
+		let mut state = Self {
+			config: config.clone(),
+			options: options.clone(),
+			test_authorities,
+			block_infos,
+			block_headers,
+			pvd,
+			candidate_receipts: Default::default(),
+			min_relay_parents: Default::default(),
+			para_id,
+			collator_pairs: (0..options.n_collators).map(|_| CollatorPair::generate().0).collect(),
+			povs: (0..options.n_collators)
+				.map(|index| PoV { block_data: BlockData(vec![index as u8; pov_size]) })
+				.collect(),
+			collations: Default::default(),
+		};
+
+		for block_info in state.block_infos.iter() {
+			let receipts = (0..config.n_cores)
+				.map(|core_idx| {
+					generate_candidate(block_info.hash, Id::new(core_idx as u32 + 1), core_idx)
+				})
+				.collect();
+			let collations = state
+				.povs
+				.iter()
+				.map(|pov| {
+					let mut receipt = generate_candidate(block_info.hash, para_id, 0);
+					receipt.descriptor.set_pov_hash(pov.hash());
+					receipt.descriptor.set_persisted_validation_data_hash(state.pvd.hash());
+					receipt
+				})
+				.collect();
+
+			state.candidate_receipts.insert(block_info.hash, receipts);
+			state.collations.insert(block_info.hash, collations);
+			state
+				.min_relay_parents
+				.insert(block_info.hash, vec![(para_id, block_info.number)]);
+		}
+
+		state
+	}
+}
+
+fn generate_candidate(relay_parent: Hash, para_id: Id, core_idx: usize) -> CandidateReceipt {
+	let mut receipt = dummy_candidate_receipt_v2(relay_parent);
+	receipt.descriptor.set_para_id(para_id);
+	receipt.descriptor.set_core_index(CoreIndex(core_idx as u32));
+	receipt.descriptor.set_session_index(SESSION_INDEX);
+	// The backing mock seconds candidates with empty commitments.
+	receipt.commitments_hash = CandidateCommitments::default().hash();
+	receipt
+}
+
+fn generate_block_info(block_num: usize) -> BlockInfo {
+	new_block_import_info(Hash::repeat_byte(block_num as u8), block_num as BlockNumber)
+}
+
+fn generate_block_header(info: &BlockInfo) -> (H256, Header) {
+	(
+		info.hash,
+		Header {
+			digest: Default::default(),
+			number: info.number,
+			parent_hash: info.parent_hash,
+			extrinsics_root: Default::default(),
+			state_root: Default::default(),
+		},
+	)
+}
+
+#[async_trait::async_trait]
+impl HandleNetworkMessage for TestState {
+	async fn handle(
+		&self,
+		message: NetworkMessage,
+		_node_sender: &mut futures::channel::mpsc::UnboundedSender<NetworkMessage>,
+	) -> Option<NetworkMessage> {
+		match message {
+			NetworkMessage::RequestFromNode(_authority_id, Requests::CollationFetchingV2(req)) => {
+				let payload = req.payload;
+				let collations = self.collations.get(&payload.relay_parent).unwrap();
+				let index = collations
+					.iter()
+					.position(|receipt| receipt.hash() == payload.candidate_hash)
+					.unwrap();
+				let res = CollationFetchingResponse::Collation(
+					collations[index].clone(),
+					self.povs[index].clone(),
+				);
+				let _ = req.pending_response.send(Ok((res.encode(), ProtocolName::from(""))));
+				None
+			},
+			_ => Some(message),
+		}
+	}
+}
@@ -0,0 +1,282 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	configuration::{TestAuthorities, TestConfiguration},
+	disputes::DisputesOptions,
+	mock::runtime_api::session_info_for_peers,
+	network::{HandleNetworkMessage, NetworkMessage},
+	NODE_UNDER_TEST,
+};
+use codec::Encode;
+use polkadot_node_network_protocol::request_response::{
+	v1::{DisputeRequest, DisputeResponse},
+	Requests,
+};
+use polkadot_node_primitives::{DisputeMessage, SignedDisputeStatement};
+use polkadot_node_subsystem_test_helpers::mock::new_block_import_info;
+use polkadot_overseer::BlockInfo;
+use polkadot_primitives::{
+	vstaging::{CandidateEvent, CandidateReceiptV2 as CandidateReceipt, MutateDescriptorV2},
+	BlockNumber, CandidateHash, CoreIndex, DisputeStatement, ExplicitDisputeStatement, GroupIndex,
+	Hash, Header, Id, InvalidDisputeStatementKind, SessionInfo, ValidDisputeStatementKind,
+	ValidatorIndex, ValidatorPair,
+};
+use polkadot_primitives_test_helpers::dummy_candidate_receipt_v2;
+use sc_network::ProtocolName;
+use sp_core::{Pair, H256};
+use std::collections::HashMap;
+
+const SESSION_INDEX: u32 = 0;
+
+// Para ids of spam candidates start here, so they never collide with the included ones.
+const SPAM_PARA_ID_OFFSET: u32 = 1000;
+
+#[derive(Clone)]
+pub struct TestState {
+	// Full test config
+	pub config: TestConfiguration,
+	// Dispute specific options
+	pub options: DisputesOptions,
+	// Authority keys for the network emulation.
+	pub test_authorities: TestAuthorities,
+	// Relay chain block infos
+	pub block_infos: Vec<BlockInfo>,
+	// Relay chain block headers
+	pub block_headers: HashMap<H256, Header>,
+	// Session info
+	pub session_info: SessionInfo,
+	// Candidates backed in each block, one per core
+	pub candidate_receipts: HashMap<H256, Vec<CandidateReceipt>>,
+	// Inclusion events for all backed candidates
+	pub candidate_events: HashMap<H256, Vec<CandidateEvent>>,
+	// Pregenerated dispute requests per block, with the validator sending them
+	pub dispute_requests: HashMap<H256, Vec<(ValidatorIndex, DisputeRequest)>>,
+}
+
+impl TestState {
+	pub fn new(config: &TestConfiguration, options: &DisputesOptions) -> Self {
+		let test_authorities = config.generate_authorities();
+		let session_info = session_info_for_peers(config, &test_authorities);
+		let block_infos: Vec<BlockInfo> =
+			(1..=config.num_blocks).map(generate_block_info).collect();
+		let block_headers = block_infos.iter().map(generate_block_header).collect();
+
+		let mut state = Self {
+			config: config.clone(),
+			options: options.clone(),
+			test_authorities,
+			block_infos,
+			block_headers,
+			session_info,
+			candidate_receipts: Default::default(),
+			candidate_events: Default::default(),
+			dispute_requests: Default::default(),
+		};
+
+		let voters = state.voters();
+		for block_info in state.block_infos.iter() {
+			let receipts = (0..config.n_cores)
+				.map(|core_idx| {
+					generate_candidate(block_info.hash, Id::new(core_idx as u32 + 1), core_idx)
+				})
+				.collect::<Vec<_>>();
+			let events = receipts
+				.iter()
+				.enumerate()
+				.map(|(core_idx, receipt)| {
+					CandidateEvent::CandidateIncluded(
+						receipt.clone(),
+						Vec::new().into(),
+						CoreIndex(core_idx as u32),
+						GroupIndex(core_idx as u32),
+					)
+				})
+				.collect();
+
+			let mut requests = vec![];
+			for receipt in receipts.iter().take(options.n_disputes) {
+				requests.extend(state.generate_dispute_requests(receipt, &voters));
+			}
+			for spam_idx in 0..options.n_spam_disputes {
+				let receipt = generate_candidate(
+					block_info.hash,
+					Id::new(SPAM_PARA_ID_OFFSET + spam_idx as u32),
+					0,
+				);
+				requests.push(state.generate_spam_request(&receipt, spam_idx));
+			}
+
+			state.candidate_receipts.insert(block_info.hash, receipts);
+			state.candidate_events.insert(block_info.hash, events);
+			state.dispute_requests.insert(block_info.hash, requests);
+		}
+
+		state
+	}
+
+	// Validators casting votes in every dispute, the first one initiates it.
+	fn voters(&self) -> Vec<ValidatorIndex> {
+		let n_voters = ((self.config.n_validators - 1) * self.options.participation / 100).max(2);
+
+		(0..self.config.n_validators as u32)
+			.filter(|index| *index != NODE_UNDER_TEST)
+			.take(n_voters)
+			.map(ValidatorIndex)
+			.collect()
+	}
+
+	// Every voter sends its own vote to the node, paired with a vote on the opposite side.
+	fn generate_dispute_requests(
+		&self,
+		receipt: &CandidateReceipt,
+		voters: &[ValidatorIndex],
+	) -> Vec<(ValidatorIndex, DisputeRequest)> {
+		let candidate_hash = receipt.hash();
+		let initiator = voters[0];
+		let invalid_vote = self.sign_dispute_statement(candidate_hash, initiator, false);
+		let first_valid_vote = self.sign_dispute_statement(candidate_hash, voters[1], true);
+
+		voters
+			.iter()
+			.map(|&voter| {
+				let (valid_index, valid_vote) = if voter == initiator {
+					(voters[1], first_valid_vote.clone())
+				} else {
+					(voter, self.sign_dispute_statement(candidate_hash, voter, true))
+				};
+				let request = self.dispute_request(
+					receipt,
+					(valid_index, valid_vote),
+					(initiator, invalid_vote.clone()),
+				);
+
+				(voter, request)
+			})
+			.collect()
+	}
+
+	// A spammer disputes a candidate nobody has ever seen, with the help of the next validator.
+	fn generate_spam_request(
+		&self,
+		receipt: &CandidateReceipt,
+		spam_idx: usize,
+	) -> (ValidatorIndex, DisputeRequest) {
+		let candidate_hash = receipt.hash();
+		let n_validators = self.config.n_validators as u32;
+		let spammer = ValidatorIndex(1 + (spam_idx % self.options.n_spammers) as u32);
+		let accomplice = ValidatorIndex(1 + spammer.0 % (n_validators - 1));
+		let invalid_vote = self.sign_dispute_statement(candidate_hash, spammer, false);
+		let valid_vote = self.sign_dispute_statement(candidate_hash, accomplice, true);
+
+		(spammer, self.dispute_request(receipt, (accomplice, valid_vote), (spammer, invalid_vote)))
+	}
+
+	fn dispute_request(
+		&self,
+		receipt: &CandidateReceipt,
+		valid: (ValidatorIndex, SignedDisputeStatement),
+		invalid: (ValidatorIndex, SignedDisputeStatement),
+	) -> DisputeRequest {
+		DisputeMessage::from_signed_statements(
+			valid.1,
+			valid.0,
+			invalid.1,
+			invalid.0,
+			receipt.clone(),
+			&self.session_info,
+		)
+		.expect("Generated dispute statements are consistent")
+		.into()
+	}
+
+	fn sign_dispute_statement(
+		&self,
+		candidate_hash: CandidateHash,
+		validator_index: ValidatorIndex,
+		valid: bool,
+	) -> SignedDisputeStatement {
+		let pair = self.test_authorities.validator_pairs.get(validator_index.0 as usize).unwrap();
+
+		sign_dispute_statement(candidate_hash, valid, pair)
+	}
+}
+
+fn sign_dispute_statement(
+	candidate_hash: CandidateHash,
+	valid: bool,
+	pair: &ValidatorPair,
+) -> SignedDisputeStatement {
+	let payload = ExplicitDisputeStatement { valid, candidate_hash, session: SESSION_INDEX }
+		.signing_payload();
+	let dispute_statement = if valid {
+		DisputeStatement::Valid(ValidDisputeStatementKind::Explicit)
+	} else {
+		DisputeStatement::Invalid(InvalidDisputeStatementKind::Explicit)
+	};
+
+	SignedDisputeStatement::new_unchecked_from_trusted_source(
+		dispute_statement,
+		candidate_hash,
+		SESSION_INDEX,
+		pair.public(),
+		pair.sign(&payload[..]),
+	)
+}
+
+fn generate_candidate(relay_parent: Hash, para_id: Id, core_idx: usize) -> CandidateReceipt {
+	let mut receipt = dummy_candidate_receipt_v2(relay_parent);
+	receipt.descriptor.set_para_id(para_id);
+	receipt.descriptor.set_core_index(CoreIndex(core_idx as u32));
+	receipt.descriptor.set_session_index(SESSION_INDEX);
+	receipt
+}
+
+fn generate_block_info(block_num: usize) -> BlockInfo {
+	new_block_import_info(Hash::repeat_byte(block_num as u8), block_num as BlockNumber)
+}
+
+fn generate_block_header(info: &BlockInfo) -> (H256, Header) {
+	(
+		info.hash,
+		Header {
+			digest: Default::default(),
+			number: info.number,
+			parent_hash: info.parent_hash,
+			extrinsics_root: Default::default(),
+			state_root: Default::default(),
+		},
+	)
+}
+
+#[async_trait::async_trait]
+impl HandleNetworkMessage for TestState {
+	async fn handle(
+		&self,
+		message: NetworkMessage,
+		_node_sender: &mut futures::channel::mpsc::UnboundedSender<NetworkMessage>,
+	) -> Option<NetworkMessage> {
+		match message {
+			NetworkMessage::RequestFromNode(_authority_id, Requests::DisputeSendingV1(req)) => {
+				let _ = req
+					.pending_response
+					.send(Ok((DisputeResponse::Confirmed.encode(), ProtocolName::from(""))));
+				None
+			},
+			_ => Some(message),
+		}
+	}
+}
@@ -20,7 +20,7 @@ use crate::{
 	configuration::{TestAuthorities, TestConfiguration},
 	mock::AlwaysSupportsParachains,
 	network::NetworkEmulatorHandle,
-	usage::{BenchmarkUsage, ResourceUsage},
+	usage::{BenchmarkUsage, LatencyUsage, ResourceUsage},
 };
 use core::time::Duration;
 use futures::{Future, FutureExt};
@@ -359,6 +359,7 @@ impl TestEnvironment {
 		BenchmarkUsage {
 			network_usage: self.network_usage(),
 			cpu_usage: self.cpu_usage(subsystems_under_test, break_down_cpu_usage_per_task),
+			message_latency: self.message_latency(subsystems_under_test),
 		}
 	}
 
@@ -430,4 +431,31 @@ impl TestEnvironment {
 
 		usage
 	}
+	fn message_latency(&self, subsystems_under_test: &[&str]) -> Vec<LatencyUsage> {
+		let test_metrics = super::display::parse_metrics(self.registry());
+
+		subsystems_under_test
+			.iter()
+			.map(|subsystem| {
+				// Overseer metrics are labeled with the name of the spawned subsystem.
+				let subsystem_metrics = test_metrics
+					.subset_with_label_value("subsystem_name", &format!("{}-subsystem", subsystem));
+				let tof_sum = subsystem_metrics
+					.sum_by("polkadot_parachain_subsystem_bounded_tof_sum") +
+					subsystem_metrics.sum_by("polkadot_parachain_subsystem_unbounded_tof_sum");
+				let tof_count = subsystem_metrics
+					.sum_by("polkadot_parachain_subsystem_bounded_tof_count") +
+					subsystem_metrics.sum_by("polkadot_parachain_subsystem_unbounded_tof_count");
+				let messages = subsystem_metrics
+					.sum_by("polkadot_parachain_subsystem_bounded_received") +
+					subsystem_metrics.sum_by("polkadot_parachain_subsystem_unbounded_received");
+
+				LatencyUsage {
+					resource_name: subsystem.to_string(),
+					messages: messages as u64,
+					average_ms: if tof_count > 0.0 { tof_sum * 1000.0 / tof_count } else { 0.0 },
+				}
+			})
+			.collect()
+	}
 }
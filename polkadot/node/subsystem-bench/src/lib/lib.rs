@@ -19,7 +19,9 @@ pub const NODE_UNDER_TEST: u32 = 0;
 
 pub mod approval;
 pub mod availability;
+pub mod collator_protocol;
 pub mod configuration;
+pub mod disputes;
 pub(crate) mod display;
 pub(crate) mod environment;
 pub(crate) mod keyring;
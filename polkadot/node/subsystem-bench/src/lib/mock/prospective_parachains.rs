@@ -21,13 +21,34 @@ use polkadot_node_subsystem::{
 	messages::ProspectiveParachainsMessage, overseer, SpawnedSubsystem, SubsystemError,
 };
 use polkadot_node_subsystem_types::OverseerSignal;
-use polkadot_primitives::Hash;
+use polkadot_primitives::{BlockNumber, Hash, Id as ParaId, PersistedValidationData};
+use std::collections::HashMap;
 
-pub struct MockProspectiveParachains {}
+pub struct MockProspectiveParachains {
+	// Minimum relay parents per para for each relay chain block
+	min_relay_parents: HashMap<Hash, Vec<(ParaId, BlockNumber)>>,
+	// Validation data returned for all prospective validation data requests
+	pvd: Option<PersistedValidationData>,
+}
 
 impl MockProspectiveParachains {
 	pub fn new() -> Self {
-		Self {}
+		Self { min_relay_parents: Default::default(), pvd: None }
+	}
+
+	/// Answers minimum relay parents requests from `min_relay_parents`.
+	pub fn with_min_relay_parents(
+		mut self,
+		min_relay_parents: HashMap<Hash, Vec<(ParaId, BlockNumber)>>,
+	) -> Self {
+		self.min_relay_parents = min_relay_parents;
+		self
+	}
+
+	/// Answers all prospective validation data requests with `pvd`.
+	pub fn with_validation_data(mut self, pvd: PersistedValidationData) -> Self {
+		self.pvd = Some(pvd);
+		self
 	}
 }
 
@@ -51,8 +72,11 @@ impl MockProspectiveParachains {
 						return
 					},
 				orchestra::FromOrchestra::Communication { msg } => match msg {
-					ProspectiveParachainsMessage::GetMinimumRelayParents(_relay_parent, tx) => {
-						tx.send(vec![]).unwrap();
+					ProspectiveParachainsMessage::GetMinimumRelayParents(relay_parent, tx) => {
+						tx.send(
+							self.min_relay_parents.get(&relay_parent).cloned().unwrap_or_default(),
+						)
+						.unwrap();
 					},
 					ProspectiveParachainsMessage::GetHypotheticalMembership(req, tx) => {
 						tx.send(
@@ -64,6 +88,9 @@ impl MockProspectiveParachains {
 						)
 						.unwrap();
 					},
+					ProspectiveParachainsMessage::GetProspectiveValidationData(_request, tx) => {
+						tx.send(self.pvd.clone()).unwrap();
+					},
 					_ => {
 						unimplemented!("Unexpected chain-api message")
 					},
@@ -330,6 +330,19 @@ impl MockRuntimeApi {
 						RuntimeApiMessage::Request(_parent, RuntimeApiRequest::ClaimQueue(tx)) => {
 							tx.send(Ok(self.state.claim_queue.clone())).unwrap();
 						},
+						RuntimeApiMessage::Request(
+							_parent,
+							RuntimeApiRequest::FetchOnChainVotes(tx),
+						) => {
+							// No disputes are ever imported on chain.
+							let _ = tx.send(Ok(None));
+						},
+						RuntimeApiMessage::Request(
+							_parent,
+							RuntimeApiRequest::UnappliedSlashes(tx),
+						) => {
+							let _ = tx.send(Ok(vec![]));
+						},
 						// Long term TODO: implement more as needed.
 						message => {
 							unimplemented!("Unexpected runtime-api message: {:?}", message)
@@ -25,13 +25,15 @@ use std::collections::HashMap;
 pub struct BenchmarkUsage {
 	pub network_usage: Vec<ResourceUsage>,
 	pub cpu_usage: Vec<ResourceUsage>,
+	#[serde(default)]
+	pub message_latency: Vec<LatencyUsage>,
 }
 
 impl std::fmt::Display for BenchmarkUsage {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
 		write!(
 			f,
-			"\n{}\n{}\n\n{}\n{}\n",
+			"\n{}\n{}\n\n{}\n{}\n\n{}\n{}\n",
 			format!("{:<64}{:>12}{:>12}", "Network usage, KiB", "total", "per block").blue(),
 			self.network_usage
 				.iter()
@@ -41,6 +43,13 @@ impl std::fmt::Display for BenchmarkUsage {
 				.join("\n"),
 			format!("{:<64}{:>12}{:>12}", "CPU usage, seconds", "total", "per block").blue(),
 			self.cpu_usage
+				.iter()
+				.map(|v| v.to_string())
+				.sorted()
+				.collect::<Vec<String>>()
+				.join("\n"),
+			format!("{:<64}{:>12}{:>12}", "Message latency, ms", "messages", "average").blue(),
+			self.message_latency
 				.iter()
 				.map(|v| v.to_string())
 				.sorted()
@@ -55,10 +64,13 @@ impl BenchmarkUsage {
 		let all_network_usages: Vec<&ResourceUsage> =
 			usages.iter().flat_map(|v| &v.network_usage).collect();
 		let all_cpu_usage: Vec<&ResourceUsage> = usages.iter().flat_map(|v| &v.cpu_usage).collect();
+		let all_message_latency: Vec<&LatencyUsage> =
+			usages.iter().flat_map(|v| &v.message_latency).collect();
 
 		Self {
 			network_usage: ResourceUsage::average_by_resource_name(&all_network_usages),
 			cpu_usage: ResourceUsage::average_by_resource_name(&all_cpu_usage),
+			message_latency: LatencyUsage::average_by_resource_name(&all_message_latency),
 		}
 	}
 
@@ -96,6 +108,11 @@ impl BenchmarkUsage {
 				unit: "seconds".to_string(),
 				value: v.per_block,
 			}))
+			.chain(self.message_latency.iter().map(|v| ChartItem {
+				name: format!("{} latency", v.resource_name),
+				unit: "ms".to_string(),
+				value: v.average_ms,
+			}))
 			.collect::<Vec<_>>();
 
 		Ok(serde_json::to_string(&chart)?)
@@ -165,6 +182,40 @@ impl ResourceUsage {
 	}
 }
 
+/// Time messages spent in the queues of a subsystem before being processed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LatencyUsage {
+	pub resource_name: String,
+	/// Number of messages received by the subsystem.
+	pub messages: u64,
+	/// Average time of flight of the sampled messages, in milliseconds.
+	pub average_ms: f64,
+}
+
+impl std::fmt::Display for LatencyUsage {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{:<64}{:>12}{:>12.4}", self.resource_name.cyan(), self.messages, self.average_ms)
+	}
+}
+
+impl LatencyUsage {
+	fn average_by_resource_name(usages: &[&Self]) -> Vec<Self> {
+		let mut by_name: HashMap<String, Vec<&Self>> = Default::default();
+		for usage in usages {
+			by_name.entry(usage.resource_name.clone()).or_default().push(usage);
+		}
+		by_name
+			.into_iter()
+			.map(|(resource_name, values)| {
+				let messages = values.iter().map(|v| v.messages).sum::<u64>() / values.len() as u64;
+				let average_ms =
+					values.iter().map(|v| v.average_ms).sum::<f64>() / values.len() as f64;
+				Self { resource_name, messages, average_ms }
+			})
+			.collect()
+	}
+}
+
 type ResourceUsageCheck<'a> = (&'a str, f64, f64);
 
 #[derive(Debug, Serialize)]
@@ -44,6 +44,9 @@ use std::{
 /// Generally useful mock data providers for unit tests.
 pub mod mock;
 
+/// Replay of overseer recordings into a subsystem under test.
+pub mod replay;
+
 enum SinkState<T> {
 	Empty { read_waker: Option<Waker> },
 	Item { item: T, ready_waker: Option<Waker>, flush_waker: Option<Waker> },
@@ -0,0 +1,320 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replay of an overseer recording into a single subsystem under test.
+//!
+//! A recording is obtained from a node running with overseer recording enabled, and read with
+//! [`read_recording`]. The subsystem under test is started on a [`TestSubsystemContext`], and
+//! [`replay_recording`] feeds it the recorded signals and messages in their original order.
+//!
+//! [`TestSubsystemContext`]: crate::TestSubsystemContext
+
+use crate::TestSubsystemContextHandle;
+use polkadot_node_subsystem::{
+	messages::AllMessages,
+	overseer::recording::{RecordEntry, RecordableMessage, RecordedEvent, RecordedResponses},
+	FromOrchestra,
+};
+
+use futures::{pin_mut, prelude::*, select};
+
+pub use polkadot_node_subsystem::overseer::recording::read_recording;
+
+/// Statistics of a replay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStats {
+	/// The number of replayed signals.
+	pub signals: usize,
+	/// The number of replayed messages.
+	pub messages: usize,
+	/// The number of recorded messages which could not be decoded.
+	pub skipped: usize,
+	/// The number of requests answered with a recorded response.
+	pub responses: usize,
+}
+
+/// Answer `msg` from `responses` if possible, pass it to `respond` otherwise.
+fn dispatch<R: FnMut(AllMessages)>(
+	msg: AllMessages,
+	responses: &mut RecordedResponses,
+	stats: &mut ReplayStats,
+	respond: &mut R,
+) {
+	match responses.answer(msg) {
+		Some(msg) => respond(msg),
+		None => stats.responses += 1,
+	}
+}
+
+/// Feed the signals and messages `subsystem` received in `recording` to the subsystem under test.
+///
+/// The runtime API, chain API and availability store requests sent by the subsystem under test
+/// are answered with the responses recorded for the same requests. All other messages are passed
+/// to `respond`, which is expected to answer the requests the subsystem depends on in place of the
+/// rest of the node. Entries of other subsystems are ignored.
+pub async fn replay_recording<M, R>(
+	virtual_overseer: &mut TestSubsystemContextHandle<M>,
+	recording: impl IntoIterator<Item = RecordEntry>,
+	subsystem: &str,
+	mut respond: R,
+) -> ReplayStats
+where
+	M: RecordableMessage,
+	R: FnMut(AllMessages),
+{
+	let mut stats = ReplayStats::default();
+	let recording: Vec<_> =
+		recording.into_iter().filter(|entry| entry.subsystem == subsystem).collect();
+	let mut responses = RecordedResponses::new(&recording, subsystem);
+
+	if let Some(msg) = virtual_overseer.message_buffer.take() {
+		dispatch(msg, &mut responses, &mut stats, &mut respond);
+	}
+
+	for entry in recording {
+		let from_overseer = match entry.event {
+			RecordedEvent::Signal(signal) => {
+				stats.signals += 1;
+				FromOrchestra::Signal(signal.into_signal())
+			},
+			RecordedEvent::Message(encoded) => match M::decode_from_recording(&encoded) {
+				Some(msg) => {
+					stats.messages += 1;
+					FromOrchestra::Communication { msg }
+				},
+				None => {
+					stats.skipped += 1;
+					continue;
+				},
+			},
+			RecordedEvent::Response { .. } => continue,
+		};
+
+		// The subsystem might wait for responses before reading the next message.
+		let send = virtual_overseer.tx.send(from_overseer).fuse();
+		pin_mut!(send);
+		loop {
+			select! {
+				res = send => {
+					res.expect("Test subsystem no longer live");
+					break
+				},
+				msg = virtual_overseer.rx.next() => dispatch(
+					msg.expect("Test subsystem no longer live"),
+					&mut responses,
+					&mut stats,
+					&mut respond,
+				),
+			}
+		}
+	}
+
+	stats
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::make_subsystem_context;
+	use polkadot_node_subsystem::{
+		messages::{ApprovalVotingMessage, ChainApiMessage},
+		overseer::{
+			recording::{RecordedSignal, Recorder, RecordingConfig, RecordingSubsystem},
+			Subsystem, SubsystemContext, SubsystemSender,
+		},
+		OverseerSignal, SpawnedSubsystem, SubsystemError,
+	};
+	use polkadot_primitives::{BlockNumber, Hash};
+	use sp_core::testing::TaskExecutor;
+
+	fn entry(subsystem: &str, event: RecordedEvent) -> RecordEntry {
+		RecordEntry { timestamp: 0, subsystem: subsystem.to_owned(), event }
+	}
+
+	/// Asks the chain API for the number of each block it is asked about, until concluded.
+	async fn block_numbers<Context>(mut ctx: Context) -> Vec<Option<BlockNumber>>
+	where
+		Context: SubsystemContext<Message = ApprovalVotingMessage, Signal = OverseerSignal>,
+		Context::Sender: SubsystemSender<ChainApiMessage>,
+	{
+		let mut numbers = Vec::new();
+		loop {
+			match ctx.recv().await.unwrap() {
+				FromOrchestra::Signal(OverseerSignal::Conclude) => return numbers,
+				FromOrchestra::Signal(_) => {},
+				FromOrchestra::Communication {
+					msg: ApprovalVotingMessage::ApprovedAncestor(hash, _, _),
+				} => {
+					let (tx, rx) = futures::channel::oneshot::channel();
+					ctx.sender().send_message(ChainApiMessage::BlockNumber(hash, tx)).await;
+					numbers.push(rx.await.unwrap().unwrap());
+				},
+				FromOrchestra::Communication { .. } => {},
+			}
+		}
+	}
+
+	struct BlockNumbers(futures::channel::oneshot::Sender<Vec<Option<BlockNumber>>>);
+
+	impl<Context> Subsystem<Context, SubsystemError> for BlockNumbers
+	where
+		Context: SubsystemContext<Message = ApprovalVotingMessage, Signal = OverseerSignal>,
+		Context::Sender: SubsystemSender<ChainApiMessage>,
+	{
+		fn start(self, ctx: Context) -> SpawnedSubsystem {
+			let future = async move {
+				let _ = self.0.send(block_numbers(ctx).await);
+				Ok(())
+			}
+			.boxed();
+			SpawnedSubsystem { name: "block-numbers", future }
+		}
+	}
+
+	fn approved_ancestor(hash: Hash) -> RecordedEvent {
+		let (tx, _) = futures::channel::oneshot::channel();
+		let msg = ApprovalVotingMessage::ApprovedAncestor(hash, 0, tx);
+		RecordedEvent::Message(msg.encode_for_recording().unwrap())
+	}
+
+	#[test]
+	fn replays_entries_of_the_subsystem_in_order() {
+		let (mut ctx, mut virtual_overseer) =
+			make_subsystem_context::<ApprovalVotingMessage, _>(TaskExecutor::new());
+
+		let recording = vec![
+			entry(
+				"approval-voting",
+				RecordedEvent::Signal(RecordedSignal::BlockFinalized(Hash::zero(), 1)),
+			),
+			entry("approval-voting", approved_ancestor(Hash::repeat_byte(1))),
+			entry("dispute-coordinator", approved_ancestor(Hash::repeat_byte(2))),
+			entry("approval-voting", RecordedEvent::Message(vec![0xff])),
+			entry("approval-voting", approved_ancestor(Hash::repeat_byte(3))),
+			entry("approval-voting", RecordedEvent::Signal(RecordedSignal::Conclude)),
+		];
+
+		// Asks the chain API for the number of each block it is asked about.
+		let subsystem = async move {
+			let mut numbers = Vec::new();
+			loop {
+				match ctx.recv().await.unwrap() {
+					FromOrchestra::Signal(OverseerSignal::Conclude) => return numbers,
+					FromOrchestra::Signal(_) => {},
+					FromOrchestra::Communication {
+						msg: ApprovalVotingMessage::ApprovedAncestor(hash, _, _),
+					} => {
+						let (tx, rx) = futures::channel::oneshot::channel();
+						ctx.sender().send_message(ChainApiMessage::BlockNumber(hash, tx)).await;
+						numbers.push(rx.await.unwrap().unwrap());
+					},
+					FromOrchestra::Communication { .. } => {},
+				}
+			}
+		};
+
+		let replay = async move {
+			replay_recording(&mut virtual_overseer, recording, "approval-voting", |msg| match msg {
+				AllMessages::ChainApi(ChainApiMessage::BlockNumber(hash, tx)) => {
+					let _ = tx.send(Ok(Some(hash.as_bytes()[0] as BlockNumber)));
+				},
+				msg => panic!("Unexpected message: {:?}", msg),
+			})
+			.await
+		};
+
+		let (numbers, stats) = futures::executor::block_on(future::join(subsystem, replay));
+		assert_eq!(numbers, vec![Some(1), Some(3)]);
+		assert_eq!(stats, ReplayStats { signals: 2, messages: 2, skipped: 1, responses: 0 });
+	}
+
+	#[test]
+	fn replays_a_written_recording() {
+		let dir = tempfile::tempdir().unwrap();
+		let (mut recorder, writer) =
+			Recorder::new(RecordingConfig::new(dir.path().to_owned()), Default::default());
+		recorder.record("approval-voting", approved_ancestor(Hash::repeat_byte(1)));
+		recorder.record("approval-voting", RecordedEvent::Signal(RecordedSignal::Conclude));
+		drop(recorder);
+		futures::executor::block_on(writer.run());
+
+		let recording = read_recording(dir.path()).unwrap();
+		let (mut ctx, mut virtual_overseer) =
+			make_subsystem_context::<ApprovalVotingMessage, _>(TaskExecutor::new());
+
+		let subsystem = async move {
+			let mut received = Vec::new();
+			loop {
+				match ctx.recv().await.unwrap() {
+					FromOrchestra::Signal(OverseerSignal::Conclude) => return received,
+					FromOrchestra::Communication {
+						msg: ApprovalVotingMessage::ApprovedAncestor(hash, _, _),
+					} => received.push(hash),
+					_ => {},
+				}
+			}
+		};
+		let replay = replay_recording(&mut virtual_overseer, recording, "approval-voting", |_| {});
+
+		let (received, stats) = futures::executor::block_on(future::join(subsystem, replay));
+		assert_eq!(received, vec![Hash::repeat_byte(1)]);
+		assert_eq!(stats, ReplayStats { signals: 1, messages: 1, skipped: 0, responses: 0 });
+	}
+
+	#[test]
+	fn replays_recorded_responses() {
+		let dir = tempfile::tempdir().unwrap();
+		let (recorder, writer) =
+			Recorder::new(RecordingConfig::new(dir.path().to_owned()), Default::default());
+		let (ctx, mut virtual_overseer) =
+			make_subsystem_context::<ApprovalVotingMessage, _>(TaskExecutor::new());
+		let (numbers_tx, numbers_rx) = futures::channel::oneshot::channel();
+		let subsystem =
+			RecordingSubsystem::new(BlockNumbers(numbers_tx), recorder, "approval-voting")
+				.start(ctx)
+				.future;
+
+		let node = async move {
+			for n in 1..=2u8 {
+				let (tx, _) = futures::channel::oneshot::channel();
+				let msg = ApprovalVotingMessage::ApprovedAncestor(Hash::repeat_byte(n), 0, tx);
+				virtual_overseer.send(FromOrchestra::Communication { msg }).await;
+				match virtual_overseer.recv().await {
+					AllMessages::ChainApi(ChainApiMessage::BlockNumber(hash, tx)) => {
+						let _ = tx.send(Ok(Some(hash.as_bytes()[0] as BlockNumber * 10)));
+					},
+					msg => panic!("Unexpected message: {:?}", msg),
+				}
+			}
+			virtual_overseer.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+		};
+		futures::executor::block_on(future::join3(subsystem, node, writer.run()));
+		assert_eq!(futures::executor::block_on(numbers_rx).unwrap(), vec![Some(10), Some(20)]);
+
+		// The subsystem gets the recorded block numbers without anyone answering its requests.
+		let recording = read_recording(dir.path()).unwrap();
+		let (ctx, mut virtual_overseer) =
+			make_subsystem_context::<ApprovalVotingMessage, _>(TaskExecutor::new());
+		let replay = replay_recording(&mut virtual_overseer, recording, "approval-voting", |msg| {
+			panic!("Unexpected message: {:?}", msg)
+		});
+
+		let (numbers, stats) =
+			futures::executor::block_on(future::join(block_numbers(ctx), replay));
+		assert_eq!(numbers, vec![Some(10), Some(20)]);
+		assert_eq!(stats, ReplayStats { signals: 1, messages: 2, skipped: 0, responses: 2 });
+	}
+}
@@ -37,7 +37,7 @@ use polkadot_node_primitives::{
 		v2::{CandidateBitfield, IndirectAssignmentCertV2, IndirectSignedApprovalVoteV2},
 	},
 	AvailableData, BabeEpoch, BlockWeight, CandidateVotes, CollationGenerationConfig,
-	CollationSecondedSignal, DisputeMessage, DisputeStatus, ErasureChunk, PoV,
+	CollationSecondedSignal, DisputeEvidence, DisputeMessage, DisputeStatus, ErasureChunk, PoV,
 	SignedDisputeStatement, SignedFullStatement, SignedFullStatementWithPVD, SubmitCollationParams,
 	ValidationResult,
 };
@@ -327,6 +327,10 @@ pub enum DisputeCoordinatorMessage {
 		Vec<(SessionIndex, CandidateHash)>,
 		oneshot::Sender<Vec<(SessionIndex, CandidateHash, CandidateVotes)>>,
 	),
+	/// Export everything needed to verify the given disputes offline.
+	///
+	/// Disputes the coordinator has no votes for, or no session info for, are omitted.
+	ExportEvidence(Vec<(SessionIndex, CandidateHash)>, oneshot::Sender<Vec<DisputeEvidence>>),
 	/// Sign and issue local dispute votes. A value of `true` indicates validity, and `false`
 	/// invalidity.
 	IssueLocalStatement(SessionIndex, CandidateHash, CandidateReceipt, bool),
@@ -652,6 +656,15 @@ pub enum ChainSelectionMessage {
 	/// The passed blocks must be marked as reverted, and their children must be marked
 	/// as non-viable.
 	RevertBlocks(Vec<(BlockNumber, Hash)>),
+	/// Operator override of the viability of a block.
+	///
+	/// With `false` the block is marked as reverted, which makes it and its descendants
+	/// non-viable. With `true` reversions and stagnation are cleared for the block and all its
+	/// descendants. Responds with whether the block is known.
+	SetViability(Hash, bool, oneshot::Sender<bool>),
+	/// Operator override to check for stagnant blocks right away, even if stagnant checks are
+	/// disabled. Responds once the check is done.
+	CheckStagnant(oneshot::Sender<()>),
 }
 
 /// A sender for the result of a runtime API request.
@@ -96,9 +96,12 @@ fn main() -> Result<()> {
 						hwbench: None,
 						execute_workers_max_num: None,
 						prepare_workers_hard_max_num: None,
+						pvf_artifacts_cache_size_limit: None,
 						prepare_workers_soft_max_num: None,
 						enable_approval_voting_parallel: false,
 						keep_finalized_for: None,
+						overseer_recording_path: None,
+						adaptive_availability_recovery: false,
 					},
 				)
 				.map_err(|e| e.to_string())?;
@@ -0,0 +1,134 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC api for operator overrides of the chain selection subsystem.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::async_trait,
+	proc_macros::rpc,
+	types::{ErrorObject, ErrorObjectOwned},
+	Extensions,
+};
+use polkadot_node_subsystem_types::messages::ChainSelectionMessage;
+use polkadot_overseer::Handle;
+use polkadot_primitives::Hash;
+use sc_rpc_api::{check_if_safe, UnsafeRpcError};
+
+const CHAIN_SELECTION_ERROR: i32 = 9600;
+
+/// Provides rpc methods for overriding chain selection.
+#[rpc(client, server)]
+pub trait ChainSelectionApi {
+	/// Returns the viable leaves in descending order by score, or the finalized block if
+	/// there are none.
+	#[method(name = "chainSelection_viableLeaves", with_extensions)]
+	async fn viable_leaves(&self) -> Result<Vec<Hash>, Error>;
+
+	/// Marks the given block and its descendants as non-viable, or as viable again.
+	///
+	/// Marking a block viable clears reversions and stagnation of the block and all its
+	/// descendants, including those caused by disputes.
+	#[method(name = "chainSelection_setViability", with_extensions)]
+	async fn set_viability(&self, block_hash: Hash, viable: bool) -> Result<(), Error>;
+
+	/// Checks for stagnant blocks right away, even if stagnant checks are disabled.
+	#[method(name = "chainSelection_checkStagnant", with_extensions)]
+	async fn check_stagnant(&self) -> Result<(), Error>;
+}
+
+/// Provides RPC methods for overriding chain selection.
+pub struct ChainSelection {
+	overseer_handle: Handle,
+}
+
+impl ChainSelection {
+	/// Creates a new instance of the chain selection RPC handler.
+	pub fn new(overseer_handle: Handle) -> Self {
+		Self { overseer_handle }
+	}
+
+	async fn request<T>(
+		&self,
+		msg: impl FnOnce(oneshot::Sender<T>) -> ChainSelectionMessage,
+	) -> Result<T, Error> {
+		let (tx, rx) = oneshot::channel();
+		self.overseer_handle
+			.clone()
+			.send_msg(msg(tx), std::any::type_name::<Self>())
+			.await;
+		rx.await.map_err(|_| Error::ChainSelectionUnavailable)
+	}
+}
+
+#[async_trait]
+impl ChainSelectionApiServer for ChainSelection {
+	async fn viable_leaves(&self, ext: &Extensions) -> Result<Vec<Hash>, Error> {
+		check_if_safe(ext)?;
+
+		self.request(ChainSelectionMessage::Leaves).await
+	}
+
+	async fn set_viability(
+		&self,
+		ext: &Extensions,
+		block_hash: Hash,
+		viable: bool,
+	) -> Result<(), Error> {
+		check_if_safe(ext)?;
+
+		let known = self
+			.request(|tx| ChainSelectionMessage::SetViability(block_hash, viable, tx))
+			.await?;
+		if known {
+			Ok(())
+		} else {
+			Err(Error::UnknownBlock(block_hash))
+		}
+	}
+
+	async fn check_stagnant(&self, ext: &Extensions) -> Result<(), Error> {
+		check_if_safe(ext)?;
+
+		self.request(ChainSelectionMessage::CheckStagnant).await
+	}
+}
+
+/// Errors encountered by the RPC
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// The chain selection subsystem did not answer.
+	#[error("The chain selection subsystem is not available")]
+	ChainSelectionUnavailable,
+	/// The block is not known to chain selection, e.g. because it is finalized.
+	#[error("Block {0:?} is not known to chain selection")]
+	UnknownBlock(Hash),
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] UnsafeRpcError),
+}
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(error: Error) -> Self {
+		match error {
+			Error::ChainSelectionUnavailable =>
+				ErrorObject::owned(CHAIN_SELECTION_ERROR + 1, error.to_string(), None::<()>),
+			Error::UnknownBlock(_) =>
+				ErrorObject::owned(CHAIN_SELECTION_ERROR + 2, error.to_string(), None::<()>),
+			Error::UnsafeRpcCalled(e) => e.into(),
+		}
+	}
+}
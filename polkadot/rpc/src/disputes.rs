@@ -0,0 +1,155 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC api for exporting dispute evidence from the dispute coordinator.
+
+use codec::Encode;
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::async_trait,
+	proc_macros::rpc,
+	types::{ErrorObject, ErrorObjectOwned},
+	Extensions,
+};
+use polkadot_node_primitives::{DisputeStatus, VersionedDisputeEvidence};
+use polkadot_node_subsystem_types::messages::DisputeCoordinatorMessage;
+use polkadot_overseer::Handle;
+use polkadot_primitives::{CandidateHash, SessionIndex};
+use sc_rpc_api::{check_if_safe, UnsafeRpcError};
+use serde::{Deserialize, Serialize};
+use sp_core::Bytes;
+
+const DISPUTES_ERROR: i32 = 9500;
+
+/// A dispute known to the dispute coordinator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentDispute {
+	/// The session the disputed candidate appears in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: CandidateHash,
+	/// The status of the dispute, one of `active`, `confirmed`, `concludedFor` or
+	/// `concludedAgainst`.
+	pub status: String,
+	/// The time the dispute concluded at, as seconds since the UNIX epoch.
+	pub concluded_at: Option<u64>,
+}
+
+impl RecentDispute {
+	fn new(session: SessionIndex, candidate_hash: CandidateHash, status: DisputeStatus) -> Self {
+		let (status, concluded_at) = match status {
+			DisputeStatus::Active => ("active", None),
+			DisputeStatus::Confirmed => ("confirmed", None),
+			DisputeStatus::ConcludedFor(at) => ("concludedFor", Some(at)),
+			DisputeStatus::ConcludedAgainst(at) => ("concludedAgainst", Some(at)),
+		};
+		Self { session, candidate_hash, status: status.into(), concluded_at }
+	}
+}
+
+/// Provides rpc methods for exporting disputes.
+#[rpc(client, server)]
+pub trait DisputesApi {
+	/// Returns all disputes of recent sessions, including concluded ones.
+	#[method(name = "disputes_recent", with_extensions)]
+	async fn recent(&self) -> Result<Vec<RecentDispute>, Error>;
+
+	/// Returns the SCALE encoded `VersionedDisputeEvidence` of each of the given disputes.
+	///
+	/// Disputes the node has no record of are omitted.
+	#[method(name = "disputes_exportEvidence", with_extensions)]
+	async fn export_evidence(
+		&self,
+		disputes: Vec<(SessionIndex, CandidateHash)>,
+	) -> Result<Vec<Bytes>, Error>;
+}
+
+/// Provides RPC methods for exporting disputes.
+pub struct Disputes {
+	overseer_handle: Handle,
+}
+
+impl Disputes {
+	/// Creates a new instance of the disputes RPC handler.
+	pub fn new(overseer_handle: Handle) -> Self {
+		Self { overseer_handle }
+	}
+
+	async fn request<T>(
+		&self,
+		msg: impl FnOnce(oneshot::Sender<T>) -> DisputeCoordinatorMessage,
+	) -> Result<T, Error> {
+		let (tx, rx) = oneshot::channel();
+		self.overseer_handle
+			.clone()
+			.send_msg(msg(tx), std::any::type_name::<Self>())
+			.await;
+		rx.await.map_err(|_| Error::DisputeCoordinatorUnavailable)
+	}
+}
+
+#[async_trait]
+impl DisputesApiServer for Disputes {
+	async fn recent(&self, ext: &Extensions) -> Result<Vec<RecentDispute>, Error> {
+		check_if_safe(ext)?;
+
+		let disputes = self.request(DisputeCoordinatorMessage::RecentDisputes).await?;
+		Ok(disputes
+			.into_iter()
+			.map(|(session, candidate_hash, status)| {
+				RecentDispute::new(session, candidate_hash, status)
+			})
+			.collect())
+	}
+
+	async fn export_evidence(
+		&self,
+		ext: &Extensions,
+		disputes: Vec<(SessionIndex, CandidateHash)>,
+	) -> Result<Vec<Bytes>, Error> {
+		check_if_safe(ext)?;
+
+		let evidence = self
+			.request(|tx| DisputeCoordinatorMessage::ExportEvidence(disputes, tx))
+			.await?;
+		Ok(evidence
+			.into_iter()
+			.map(|evidence| VersionedDisputeEvidence::from(evidence).encode().into())
+			.collect())
+	}
+}
+
+/// Errors encountered by the RPC
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// The dispute coordinator did not answer.
+	#[error("The dispute coordinator is not available")]
+	DisputeCoordinatorUnavailable,
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] UnsafeRpcError),
+}
+
+impl From<Error> for ErrorObjectOwned {
+	fn from(error: Error) -> Self {
+		match error {
+			Error::DisputeCoordinatorUnavailable =>
+				ErrorObject::owned(DISPUTES_ERROR + 1, error.to_string(), None::<()>),
+			Error::UnsafeRpcCalled(e) => e.into(),
+		}
+	}
+}
@@ -38,6 +38,9 @@ use sp_consensus_babe::BabeApi;
 use sp_consensus_beefy::AuthorityIdBound;
 use sp_keystore::KeystorePtr;
 
+pub mod chain_selection;
+pub mod disputes;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
@@ -91,17 +94,23 @@ pub struct FullDeps<C, P, SC, B, AuthorityId: AuthorityIdBound> {
 	pub beefy: BeefyDeps<AuthorityId>,
 	/// Backend used by the node.
 	pub backend: Arc<B>,
+	/// A handle to the overseer, if the node runs the dispute coordinator and chain selection.
+	pub overseer_handle: Option<polkadot_overseer::Handle>,
 }
 
 /// Instantiate all RPC extensions.
 pub fn create_full<C, P, SC, B, AuthorityId>(
-	FullDeps { client, pool, select_chain, chain_spec, babe, grandpa, beefy, backend }: FullDeps<
-		C,
-		P,
-		SC,
-		B,
-		AuthorityId,
-	>,
+	FullDeps {
+		client,
+		pool,
+		select_chain,
+		chain_spec,
+		babe,
+		grandpa,
+		beefy,
+		backend,
+		overseer_handle,
+	}: FullDeps<C, P, SC, B, AuthorityId>,
 ) -> Result<RpcExtension, Box<dyn std::error::Error + Send + Sync>>
 where
 	C: ProvideRuntimeApi<Block>
@@ -123,6 +132,8 @@ where
 	AuthorityId: AuthorityIdBound,
 	<AuthorityId as RuntimeAppPublic>::Signature: Send + Sync,
 {
+	use chain_selection::{ChainSelection, ChainSelectionApiServer};
+	use disputes::{Disputes, DisputesApiServer};
 	use mmr_rpc::{Mmr, MmrApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
@@ -180,5 +191,10 @@ where
 		.into_rpc(),
 	)?;
 
+	if let Some(overseer_handle) = overseer_handle {
+		io.merge(ChainSelection::new(overseer_handle.clone()).into_rpc())?;
+		io.merge(Disputes::new(overseer_handle).into_rpc())?;
+	}
+
 	Ok(io)
 }
@@ -0,0 +1,235 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export dispute evidence from a validator and verify it offline.
+//!
+//! `export` fetches the evidence of disputes from the `disputes_exportEvidence` RPC of a
+//! validator, which has to be started with `--rpc-methods unsafe`, and writes one SCALE encoded
+//! file per dispute. `verify` checks the signatures and the outcome of such files. The validator
+//! set contained in the evidence is checked against the session info of a node given with
+//! `--uri`, or against a validator set hash obtained from a trusted source, in which case no node
+//! is needed.
+
+use clap::{Args, Parser, Subcommand};
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::client::ClientT,
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use polkadot_node_primitives::{
+	disputes::{EvidenceError, VerifiedDisputeEvidence},
+	DisputeEvidence, VersionedDisputeEvidence,
+};
+use polkadot_primitives::{CandidateHash, Hash, SessionIndex, SessionInfo};
+use polkadot_rpc::disputes::DisputesApiClient;
+use sp_core::Bytes;
+use std::{error::Error, path::PathBuf, str::FromStr};
+
+#[derive(Debug, Parser)]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// List the disputes of recent sessions known to a validator.
+	List {
+		/// The websocket RPC endpoint of the validator.
+		#[arg(long, default_value = "ws://127.0.0.1:9944")]
+		uri: String,
+	},
+	/// Export dispute evidence from a validator.
+	Export {
+		/// The websocket RPC endpoint of the validator.
+		#[arg(long, default_value = "ws://127.0.0.1:9944")]
+		uri: String,
+		/// The disputes to export as `<session>:<candidate hash>`, all recent disputes if none is
+		/// given.
+		#[arg(long = "dispute", value_parser = parse_dispute)]
+		disputes: Vec<(SessionIndex, CandidateHash)>,
+		/// The directory to write the evidence files to.
+		#[arg(long, default_value = ".")]
+		output: PathBuf,
+	},
+	/// Verify exported dispute evidence.
+	Verify {
+		#[command(flatten)]
+		trusted: TrustedValidators,
+		/// The evidence files to verify.
+		#[arg(required = true)]
+		files: Vec<PathBuf>,
+	},
+}
+
+/// Where the validator set the evidence is checked against comes from.
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+struct TrustedValidators {
+	/// The websocket RPC endpoint of a node, to fetch the validator set of the session from.
+	///
+	/// The session info is only kept for recent sessions.
+	#[arg(long)]
+	uri: Option<String>,
+	/// The hash of the validator set of the session, as obtained from a trusted source.
+	#[arg(long)]
+	validators_hash: Option<Hash>,
+}
+
+fn parse_dispute(s: &str) -> Result<(SessionIndex, CandidateHash), String> {
+	let (session, candidate_hash) = s
+		.split_once(':')
+		.ok_or_else(|| format!("expected `<session>:<candidate hash>`: {s}"))?;
+	let session = session.parse().map_err(|e| format!("invalid session `{session}`: {e}"))?;
+	let candidate_hash = Hash::from_str(candidate_hash)
+		.map_err(|e| format!("invalid candidate hash `{candidate_hash}`: {e}"))?;
+	Ok((session, CandidateHash(candidate_hash)))
+}
+
+async fn list(uri: String) -> Result<(), Box<dyn Error>> {
+	let client = WsClientBuilder::default().build(&uri).await?;
+	for dispute in client.recent().await? {
+		println!("{}:{:?} {}", dispute.session, dispute.candidate_hash.0, dispute.status);
+	}
+	Ok(())
+}
+
+async fn export(
+	uri: String,
+	mut disputes: Vec<(SessionIndex, CandidateHash)>,
+	output: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+	let client = WsClientBuilder::default().build(&uri).await?;
+	if disputes.is_empty() {
+		disputes = client
+			.recent()
+			.await?
+			.into_iter()
+			.map(|dispute| (dispute.session, dispute.candidate_hash))
+			.collect();
+	}
+
+	let requested = disputes.len();
+	let exported = client.export_evidence(disputes).await?;
+	for encoded in &exported {
+		let evidence: DisputeEvidence = VersionedDisputeEvidence::decode(&mut &encoded[..])?.into();
+		let path = output.join(format!(
+			"dispute-{}-{:?}.evidence",
+			evidence.session_index,
+			evidence.candidate_hash().0
+		));
+		std::fs::write(&path, &encoded[..])?;
+		println!("Wrote {} (validators hash {:?})", path.display(), evidence.validators_hash());
+	}
+	if exported.len() < requested {
+		eprintln!("{} of {requested} disputes are unknown to the node", requested - exported.len());
+	}
+	Ok(())
+}
+
+/// Fetch the validator set of `session` from the session info stored on chain.
+async fn session_validators(
+	client: &WsClient,
+	session: SessionIndex,
+) -> Result<Vec<polkadot_primitives::ValidatorId>, Box<dyn Error>> {
+	let encoded: Bytes = client
+		.request("state_call", rpc_params!["ParachainHost_session_info", Bytes(session.encode())])
+		.await?;
+	let session_info = Option::<SessionInfo>::decode(&mut &encoded[..])?
+		.ok_or_else(|| format!("session info of session {session} is not available anymore"))?;
+	Ok(session_info.validators.to_vec())
+}
+
+async fn verify_evidence(
+	evidence: &DisputeEvidence,
+	trusted: &TrustedValidators,
+	client: Option<&WsClient>,
+) -> Result<Result<VerifiedDisputeEvidence, EvidenceError>, Box<dyn Error>> {
+	Ok(match (client, trusted.validators_hash) {
+		(Some(client), _) =>
+			evidence.verify(&session_validators(client, evidence.session_index).await?),
+		(None, Some(validators_hash)) => evidence.verify_against_hash(validators_hash),
+		(None, None) => unreachable!("clap requires either `--uri` or `--validators-hash`; qed"),
+	})
+}
+
+async fn verify(trusted: TrustedValidators, files: Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+	let client = match &trusted.uri {
+		Some(uri) => Some(WsClientBuilder::default().build(uri).await?),
+		None => None,
+	};
+
+	let mut failed = 0;
+	for path in files {
+		let encoded = std::fs::read(&path)?;
+		let evidence: DisputeEvidence = match VersionedDisputeEvidence::decode(&mut &encoded[..]) {
+			Ok(evidence) => evidence.into(),
+			Err(err) => {
+				eprintln!("{}: cannot decode evidence: {err}", path.display());
+				failed += 1;
+				continue
+			},
+		};
+
+		let verified = match verify_evidence(&evidence, &trusted, client.as_ref()).await {
+			Ok(verified) => verified,
+			Err(err) => {
+				eprintln!("{}: cannot get the validators of the session: {err}", path.display());
+				failed += 1;
+				continue
+			},
+		};
+		match verified {
+			Ok(verified) => println!(
+				"{}: session {}, candidate {:?}: {:?}, {} valid and {} invalid votes of {} \
+				validators, threshold {}",
+				path.display(),
+				evidence.session_index,
+				verified.candidate_hash.0,
+				verified.outcome,
+				verified.valid_votes.len(),
+				verified.invalid_votes.len(),
+				evidence.validators.len(),
+				verified.threshold,
+			),
+			Err(err) => {
+				eprintln!("{}: invalid evidence: {err}", path.display());
+				failed += 1;
+			},
+		}
+
+		// Re-encoding must not lose anything, otherwise the file is not what we checked.
+		if VersionedDisputeEvidence::from(evidence).encode() != encoded {
+			eprintln!("{}: trailing or non-canonical data", path.display());
+			failed += 1;
+		}
+	}
+
+	if failed > 0 {
+		return Err(format!("{failed} evidence checks failed").into())
+	}
+	Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+	match Cli::parse().command {
+		Command::List { uri } => list(uri).await,
+		Command::Export { uri, disputes, output } => export(uri, disputes, output).await,
+		Command::Verify { trusted, files } => verify(trusted, files).await,
+	}
+}
@@ -42,7 +42,7 @@ use frame_support::{
 	derive_impl,
 	dispatch::{DispatchClass, DispatchInfo},
 	dynamic_params::{dynamic_pallet_params, dynamic_params},
-	genesis_builder_helper::{build_state, get_preset},
+	genesis_builder_helper::{build_state, genesis_config_schema, get_preset},
 	instances::{Instance1, Instance2},
 	ord_parameter_types,
 	pallet_prelude::Get,
@@ -2868,6 +2868,7 @@ type Migrations = (
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
 	pallet_identity::migration::versioned::V0ToV1<Runtime, IDENTITY_MIGRATION_KEY_LIMIT>,
+	pallet_assets_freezer::migration::v1::MigrateV0ToV1<Runtime, AssetsFreezerInstance>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2965,6 +2966,7 @@ mod benches {
 		[frame_benchmarking_pallet_pov, Pov]
 		[pallet_alliance, Alliance]
 		[pallet_assets, Assets]
+		[pallet_assets_freezer, AssetsFreezer]
 		[pallet_babe, Babe]
 		[pallet_bags_list, VoterList]
 		[pallet_balances, Balances]
@@ -3268,6 +3270,21 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_system_rpc_runtime_api::ExtrinsicSimulationApi<Block, RuntimeEvent, Balance> for Runtime {
+		fn simulate_extrinsic(
+			extrinsic: <Block as BlockT>::Extrinsic,
+		) -> frame_system_rpc_runtime_api::ExtrinsicEffects<RuntimeEvent, Balance> {
+			let (execution_result, events) = Executive::simulate_extrinsic(extrinsic);
+			let emitted_events: Vec<_> = events.into_iter().map(|record| record.event).collect();
+			let actual_fee = TransactionPayment::actual_fee_paid(emitted_events.iter().cloned());
+			frame_system_rpc_runtime_api::ExtrinsicEffects {
+				execution_result,
+				emitted_events,
+				actual_fee,
+			}
+		}
+	}
+
 	impl assets_api::AssetsApi<
 		Block,
 		AccountId,
@@ -3866,6 +3883,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_state::<RuntimeGenesisConfig>(config)
@@ -3878,6 +3896,9 @@ impl_runtime_apis! {
 		fn preset_names() -> Vec<sp_genesis_builder::PresetId> {
 			genesis_config_presets::preset_names()
 		}
+		fn genesis_config_schema() -> Vec<u8> {
+			genesis_config_schema::<RuntimeGenesisConfig>()
+		}
 	}
 }
 
@@ -32,6 +32,10 @@ use std::{
 	path::{Path, PathBuf},
 };
 
+mod topology;
+
+pub use topology::generate_chain_specs_for_topology;
+
 /// A utility to easily create a chain spec definition.
 #[derive(Debug, Parser)]
 #[command(rename_all = "kebab-case", version, about)]
@@ -53,6 +57,9 @@ pub enum ChainSpecBuilderCmd {
 	ListPresets(ListPresetsCmd),
 	DisplayPreset(DisplayPresetCmd),
 	AddCodeSubstitute(AddCodeSubstituteCmd),
+	GenesisSchema(GenesisSchemaCmd),
+	Validate(ValidateCmd),
+	Topology(TopologyCmd),
 }
 
 /// Create a new chain spec by interacting with the provided runtime wasm blob.
@@ -83,6 +90,11 @@ pub struct CreateCmd {
 	/// errors will be reported.
 	#[arg(long, short = 'v')]
 	verify: bool,
+	/// Validate the provided genesis config patch or full config against the JSON Schema of the
+	/// runtime's `RuntimeGenesisConfig` before building the chain spec. All unknown fields and
+	/// type mismatches are reported together with their path.
+	#[arg(long)]
+	validate_schema: bool,
 	/// Chain properties in `KEY=VALUE` format.
 	///
 	/// Multiple `KEY=VALUE` entries can be specified and separated by a comma.
@@ -201,6 +213,68 @@ pub struct DisplayPresetCmd {
 	pub preset_name: Option<String>,
 }
 
+/// Displays the JSON Schema of the runtime's `RuntimeGenesisConfig`.
+///
+/// The schema can be used by external tools (e.g. editors) to validate genesis config patches.
+#[derive(Parser, Debug, Clone)]
+pub struct GenesisSchemaCmd {
+	/// The path to runtime wasm blob.
+	#[arg(long, short, alias = "runtime-wasm-path")]
+	pub runtime: PathBuf,
+}
+
+/// Validates a genesis config against the JSON Schema of the runtime's `RuntimeGenesisConfig`.
+///
+/// Reports all unknown fields, missing fields (for full configs) and type mismatches, each with
+/// its JSON pointer, without building the genesis state. Nothing is written to the file given as
+/// `-c/--chain-spec-path` command line argument.
+#[derive(Parser, Debug, Clone)]
+pub struct ValidateCmd {
+	/// The path to runtime wasm blob.
+	///
+	/// Required for patches and full configs. For chain specs the code embedded in the chain spec
+	/// is used if this is not given.
+	#[arg(long, short, alias = "runtime-wasm-path")]
+	pub runtime: Option<PathBuf>,
+	#[command(subcommand)]
+	input: ValidateInput,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ValidateInput {
+	Patch(PatchCmd),
+	Full(FullCmd),
+	ChainSpec(ChainSpecInputCmd),
+}
+
+/// Validates the genesis config contained in a plain chain spec.
+#[derive(Parser, Debug, Clone)]
+struct ChainSpecInputCmd {
+	/// Chain spec to be validated.
+	input_chain_spec: PathBuf,
+}
+
+/// Generates the chain specs of a relay chain and of the parachains registered at its genesis.
+///
+/// The topology file declares the relay chain, the parachains with their para IDs and collators,
+/// and the HRMP channels between them. The genesis head and runtime of every parachain are added
+/// to the `paras` genesis config of the relay chain and the channels to its `hrmp` genesis config,
+/// so that a local network can be started from the generated chain specs.
+///
+/// Every chain spec is written to `<output-dir>/<chain id>.json`. Nothing is written to the file
+/// given as `-c/--chain-spec-path` command line argument.
+#[derive(Parser, Debug, Clone)]
+pub struct TopologyCmd {
+	/// The path to the topology file.
+	pub topology: PathBuf,
+	/// The directory the chain specs are written to.
+	#[arg(long, short, default_value = ".")]
+	pub output_dir: PathBuf,
+	/// Export the chain specs as raw storage.
+	#[arg(long, short = 's')]
+	pub raw_storage: bool,
+}
+
 /// Verifies the provided input chain spec.
 ///
 /// Silently checks if given input chain spec can be converted to raw. It allows to check if all
@@ -317,6 +391,27 @@ impl ChainSpecBuilder {
 					.map_err(|e| format!("getting default config from runtime should work: {e}"))?;
 				println!("{preset}");
 			},
+			ChainSpecBuilderCmd::GenesisSchema(GenesisSchemaCmd { runtime }) => {
+				let code = fs::read(runtime.as_path())
+					.map_err(|e| format!("wasm blob shall be readable {e}"))?;
+				let caller: GenesisConfigBuilderRuntimeCaller =
+					GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+				let schema = caller.get_config_schema().map_err(|e| {
+					format!("getting genesis config schema from runtime should work: {e}")
+				})?;
+				let schema = serde_json::to_string_pretty(&schema)
+					.map_err(|e| format!("to pretty failed: {e}"))?;
+				println!("{schema}");
+			},
+			ChainSpecBuilderCmd::Validate(cmd) => {
+				validate_genesis_config(cmd)?;
+				println!("Genesis config validation: OK");
+			},
+			ChainSpecBuilderCmd::Topology(cmd) => {
+				for path in generate_chain_specs_for_topology(cmd)? {
+					println!("{}", path.display());
+				}
+			},
 		}
 		Ok(())
 	}
@@ -342,6 +437,18 @@ fn process_action<T: Serialize + Clone + Sync + 'static>(
 	code: &[u8],
 	builder: sc_chain_spec::ChainSpecBuilder<T>,
 ) -> Result<String, String> {
+	if cmd.validate_schema {
+		let caller: GenesisConfigBuilderRuntimeCaller =
+			GenesisConfigBuilderRuntimeCaller::new(code);
+		match cmd.action {
+			GenesisBuildAction::Patch(PatchCmd { ref patch_path }) =>
+				caller.validate_patch(&read_json_file(patch_path, "patch")?)?,
+			GenesisBuildAction::Full(FullCmd { ref config_path }) =>
+				caller.validate_config(&read_json_file(config_path, "config")?)?,
+			GenesisBuildAction::Default(_) | GenesisBuildAction::NamedPreset(_) => {},
+		}
+	}
+
 	let builder = match cmd.action {
 		GenesisBuildAction::NamedPreset(NamedPresetCmd { ref preset_name }) =>
 			builder.with_genesis_config_preset_name(&preset_name),
@@ -459,6 +566,70 @@ pub fn generate_chain_spec_for_runtime(cmd: &CreateCmd) -> Result<String, String
 	}
 }
 
+/// Reads the JSON file containing a genesis config `kind` (patch or full config).
+fn read_json_file(path: &Path, kind: &str) -> Result<Value, String> {
+	let json =
+		fs::read(path).map_err(|e| format!("{kind} file {path:?} shall be readable: {e}"))?;
+	serde_json::from_slice::<Value>(&json[..])
+		.map_err(|e| format!("{kind} file {path:?} shall contain a valid json: {e}"))
+}
+
+/// Processes `ValidateCmd`, returning all the schema violations as the error.
+pub fn validate_genesis_config(cmd: &ValidateCmd) -> Result<(), String> {
+	let mut code = cmd
+		.runtime
+		.as_ref()
+		.map(|runtime| {
+			fs::read(runtime.as_path()).map_err(|e| format!("wasm blob shall be readable {e}"))
+		})
+		.transpose()?;
+
+	let (json, is_patch) = match cmd.input {
+		ValidateInput::Patch(PatchCmd { ref patch_path }) =>
+			(read_json_file(patch_path, "patch")?, true),
+		ValidateInput::Full(FullCmd { ref config_path }) =>
+			(read_json_file(config_path, "config")?, false),
+		ValidateInput::ChainSpec(ChainSpecInputCmd { ref input_chain_spec }) => {
+			let mut chain_spec_json = extract_chain_spec_json(input_chain_spec.as_path())?;
+			let runtime_genesis = chain_spec_json
+				.pointer_mut("/genesis/runtimeGenesis")
+				.and_then(Value::as_object_mut)
+				.ok_or("Only plain chain specs, with a `runtimeGenesis`, can be validated")?;
+
+			if code.is_none() {
+				let embedded = runtime_genesis
+					.get("code")
+					.and_then(Value::as_str)
+					.ok_or("The chain spec does not contain the runtime code")?;
+				code = Some(
+					array_bytes::hex2bytes(embedded)
+						.map_err(|e| format!("Invalid runtime code in chain spec: {e:?}"))?,
+				);
+			}
+
+			if let Some(patch) = runtime_genesis.remove("patch") {
+				(patch, true)
+			} else if let Some(config) = runtime_genesis.remove("config") {
+				(config, false)
+			} else {
+				return Err(
+					"The chain spec contains neither a genesis config patch nor a full config"
+						.into(),
+				)
+			}
+		},
+	};
+
+	let code = code.ok_or("The runtime wasm blob is required to validate a genesis config")?;
+	let caller: GenesisConfigBuilderRuntimeCaller =
+		GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+	if is_patch {
+		caller.validate_patch(&json)
+	} else {
+		caller.validate_config(&json)
+	}
+}
+
 /// Extract any chain spec and convert it to JSON
 fn extract_chain_spec_json(input_chain_spec: &Path) -> Result<serde_json::Value, String> {
 	let chain_spec = &fs::read(input_chain_spec)
@@ -0,0 +1,383 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Generation of the chain specs of a relay chain and its parachains from a topology file.
+
+use crate::{ChainSpec, TopologyCmd};
+use codec::Encode;
+use sc_chain_spec::{
+	construct_genesis_block, json_patch, ChainType, GenesisConfigBuilderRuntimeCaller,
+};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sp_runtime::{
+	generic,
+	traits::{BlakeTwo256, Block as BlockT, Hash as HashT},
+	BuildStorage, OpaqueExtrinsic, StateVersion, Storage,
+};
+use std::{
+	collections::BTreeSet,
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// The genesis block of a parachain.
+///
+/// The encoding of a genesis header does not depend on the type of the block number.
+type ParachainBlock = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+
+/// A relay chain along with the parachains registered at its genesis.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Topology {
+	/// The relay chain.
+	pub relay_chain: ChainTopology,
+	/// The parachains registered at the genesis of the relay chain.
+	#[serde(default)]
+	pub parachains: Vec<ParachainTopology>,
+	/// The HRMP channels opened at the genesis of the relay chain.
+	#[serde(default)]
+	pub hrmp_channels: Vec<HrmpChannelTopology>,
+}
+
+/// A chain of the topology.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainTopology {
+	/// The runtime wasm blob, relative to the topology file.
+	pub runtime: PathBuf,
+	/// The chain id, also used as the name of the chain spec file.
+	pub id: String,
+	/// The name of the chain. Defaults to the chain id.
+	pub name: Option<String>,
+	/// The chain type.
+	#[serde(default = "local_chain_type")]
+	pub chain_type: ChainType,
+	/// The genesis config preset of the runtime the genesis config is based on.
+	pub preset: Option<String>,
+	/// A patch applied on top of the preset.
+	pub patch: Option<Value>,
+	/// The chain properties.
+	pub properties: Option<Map<String, Value>>,
+}
+
+/// A parachain of the topology.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParachainTopology {
+	/// The para ID, also set as the `parachainId` of `parachainInfo`.
+	pub para_id: u32,
+	/// The collators of the parachain, as SS58 addresses of their account and Aura key.
+	///
+	/// They are set as the invulnerables of `collatorSelection` and as the Aura session keys of
+	/// `session`, before the patch is applied.
+	#[serde(default)]
+	pub collators: Vec<String>,
+	/// Whether the para is onboarded as a lease holding parachain, or else as an on-demand
+	/// parachain.
+	#[serde(default = "default_true")]
+	pub parachain: bool,
+	/// The chain of the parachain.
+	#[serde(flatten)]
+	pub chain: ChainTopology,
+}
+
+/// An HRMP channel opened at the genesis of the relay chain.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HrmpChannelTopology {
+	/// The para ID of the sender.
+	pub sender: u32,
+	/// The para ID of the recipient.
+	pub recipient: u32,
+	/// The maximum number of messages in the channel.
+	#[serde(default = "default_max_capacity")]
+	pub max_capacity: u32,
+	/// The maximum size of a message in the channel.
+	#[serde(default = "default_max_message_size")]
+	pub max_message_size: u32,
+}
+
+fn local_chain_type() -> ChainType {
+	ChainType::Local
+}
+
+fn default_true() -> bool {
+	true
+}
+
+fn default_max_capacity() -> u32 {
+	8
+}
+
+fn default_max_message_size() -> u32 {
+	512
+}
+
+impl Topology {
+	/// Reads the topology file.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let topology =
+			fs::read(path).map_err(|e| format!("topology file {path:?} shall be readable: {e}"))?;
+		serde_json::from_slice(&topology[..])
+			.map_err(|e| format!("topology file {path:?} shall contain a valid topology: {e}"))
+	}
+
+	/// Checks that the chain and para IDs are unique and that the HRMP channels connect known
+	/// parachains.
+	pub fn validate(&self) -> Result<(), String> {
+		let mut chain_ids = BTreeSet::from([&self.relay_chain.id]);
+		let mut para_ids = BTreeSet::new();
+		for parachain in &self.parachains {
+			if !chain_ids.insert(&parachain.chain.id) {
+				return Err(format!("Chain id {} is used more than once", parachain.chain.id))
+			}
+			if !para_ids.insert(parachain.para_id) {
+				return Err(format!("Para ID {} is used more than once", parachain.para_id))
+			}
+		}
+
+		let mut channels = BTreeSet::new();
+		for channel in &self.hrmp_channels {
+			let (sender, recipient) = (channel.sender, channel.recipient);
+			if sender == recipient {
+				return Err(format!("HRMP channel from para {sender} to itself"))
+			}
+			if let Some(unknown) = [sender, recipient].into_iter().find(|id| !para_ids.contains(id))
+			{
+				return Err(format!(
+					"HRMP channel from para {sender} to para {recipient} uses unknown para {unknown}"
+				))
+			}
+			if !channels.insert((sender, recipient)) {
+				return Err(format!(
+					"HRMP channel from para {sender} to para {recipient} is declared more than once"
+				))
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl ChainTopology {
+	/// Builds the chain spec, with `extra_patch` applied on top of the preset and the patch.
+	fn build(&self, base_dir: &Path, extra_patch: Value) -> Result<Chain, String> {
+		let runtime = base_dir.join(&self.runtime);
+		let code = fs::read(&runtime).map_err(|e| {
+			format!("wasm blob {runtime:?} of chain {} shall be readable: {e}", self.id)
+		})?;
+		let caller: GenesisConfigBuilderRuntimeCaller =
+			GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+		let state_version = caller.get_state_version()?;
+
+		let mut patch = match &self.preset {
+			Some(preset) => caller.get_named_preset(Some(preset))?,
+			None => json!({}),
+		};
+		if let Some(user_patch) = &self.patch {
+			json_patch::merge(&mut patch, user_patch.clone());
+		}
+		json_patch::merge(&mut patch, extra_patch);
+
+		let chain_spec = ChainSpec::builder(&code[..], Default::default())
+			.with_name(self.name.as_deref().unwrap_or(&self.id))
+			.with_id(&self.id)
+			.with_chain_type(self.chain_type.clone())
+			.with_properties(self.properties.clone().unwrap_or_default())
+			.with_genesis_config_patch(patch)
+			.build();
+
+		Ok(Chain { chain_spec, state_version, code })
+	}
+}
+
+impl ParachainTopology {
+	/// The genesis config patch setting the para ID and the collators of the parachain.
+	fn genesis_patch(&self) -> Value {
+		let mut patch = json!({ "parachainInfo": { "parachainId": self.para_id } });
+		json_patch::merge(&mut patch, self.collators_patch());
+		patch
+	}
+
+	/// The genesis config patch setting the collators of the parachain.
+	///
+	/// It follows the layout of `cumulus-pallet-collator-selection` and `pallet-session` with Aura
+	/// session keys, as used by the parachain template and the system parachains.
+	fn collators_patch(&self) -> Value {
+		if self.collators.is_empty() {
+			return json!({})
+		}
+
+		let session_keys = self
+			.collators
+			.iter()
+			.map(|collator| json!([collator, collator, { "aura": collator }]))
+			.collect::<Vec<_>>();
+		json!({
+			"collatorSelection": { "invulnerables": self.collators },
+			"session": { "keys": session_keys },
+		})
+	}
+}
+
+/// A chain spec along with what is needed to register it on the relay chain.
+struct Chain {
+	chain_spec: ChainSpec,
+	state_version: StateVersion,
+	code: Vec<u8>,
+}
+
+impl Chain {
+	/// The head data of the genesis block.
+	fn genesis_head(&self) -> Result<Vec<u8>, String> {
+		let storage = self.chain_spec.build_storage()?;
+		let state_root = storage_root(&storage, self.state_version);
+		let block = construct_genesis_block::<ParachainBlock>(state_root, self.state_version);
+		Ok(block.header().encode())
+	}
+
+	/// Writes the chain spec to `path`, with `extension` merged into it.
+	fn write(&self, path: &Path, raw: bool, extension: Option<Value>) -> Result<(), String> {
+		let mut chain_spec_json = serde_json::from_str::<Value>(&self.chain_spec.as_json(raw)?)
+			.map_err(|e| format!("deserialization a json failed {e}"))?;
+		if let Some(extension) = extension {
+			json_patch::merge(&mut chain_spec_json, extension);
+		}
+		let chain_spec_json = serde_json::to_string_pretty(&chain_spec_json)
+			.map_err(|e| format!("to pretty failed: {e}"))?;
+		fs::write(path, chain_spec_json).map_err(|e| format!("{path:?} could not be written: {e}"))
+	}
+}
+
+/// Computes the state root of the genesis storage.
+fn storage_root(storage: &Storage, state_version: StateVersion) -> <BlakeTwo256 as HashT>::Output {
+	let child_roots = storage.children_default.values().map(|child| {
+		let root = BlakeTwo256::trie_root(child.data.clone().into_iter().collect(), state_version);
+		(child.child_info.prefixed_storage_key().into_inner(), root.encode())
+	});
+	BlakeTwo256::trie_root(
+		storage.top.clone().into_iter().chain(child_roots).collect(),
+		state_version,
+	)
+}
+
+/// Processes `TopologyCmd`, writing the chain specs to the output directory.
+///
+/// Returns the paths of the written chain specs, the one of the relay chain first.
+pub fn generate_chain_specs_for_topology(cmd: &TopologyCmd) -> Result<Vec<PathBuf>, String> {
+	let topology = Topology::load(&cmd.topology)?;
+	topology.validate()?;
+
+	let base_dir = cmd.topology.parent().unwrap_or(Path::new("."));
+	fs::create_dir_all(&cmd.output_dir)
+		.map_err(|e| format!("output directory {:?} could not be created: {e}", cmd.output_dir))?;
+	let relay_chain_id = &topology.relay_chain.id;
+
+	let mut written = Vec::new();
+	let mut paras = Vec::new();
+	for parachain in &topology.parachains {
+		let chain = parachain.chain.build(base_dir, parachain.genesis_patch())?;
+		let genesis_head = chain.genesis_head()?;
+
+		let path = cmd.output_dir.join(format!("{}.json", parachain.chain.id));
+		let extension = json!({ "relay_chain": relay_chain_id, "para_id": parachain.para_id });
+		chain.write(&path, cmd.raw_storage, Some(extension))?;
+		written.push(path);
+
+		paras.push(json!([
+			parachain.para_id,
+			{
+				"genesis_head": array_bytes::bytes2hex("0x", &genesis_head),
+				"validation_code": array_bytes::bytes2hex("0x", &chain.code),
+				"parachain": parachain.parachain,
+			}
+		]));
+	}
+
+	let mut relay_patch = json!({});
+	if !paras.is_empty() {
+		json_patch::merge(&mut relay_patch, json!({ "paras": { "paras": paras } }));
+	}
+	if !topology.hrmp_channels.is_empty() {
+		let channels = topology
+			.hrmp_channels
+			.iter()
+			.map(|c| json!([c.sender, c.recipient, c.max_capacity, c.max_message_size]))
+			.collect::<Vec<_>>();
+		json_patch::merge(&mut relay_patch, json!({ "hrmp": { "preopenHrmpChannels": channels } }));
+	}
+
+	let relay_chain = topology.relay_chain.build(base_dir, relay_patch)?;
+	let path = cmd.output_dir.join(format!("{relay_chain_id}.json"));
+	relay_chain.write(&path, cmd.raw_storage, None)?;
+	written.insert(0, path);
+
+	Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_chain_spec::{BuildGenesisBlock, GenesisBlockBuilder};
+	use sc_client_api::in_mem;
+	use sc_executor::WasmExecutor;
+	use std::sync::Arc;
+
+	#[test]
+	fn parachain_genesis_patch_sets_the_para_id_and_the_collators() {
+		let collator = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+		let parachain: ParachainTopology = serde_json::from_value(json!({
+			"paraId": 2000,
+			"collators": [collator],
+			"runtime": "para.wasm",
+			"id": "para",
+		}))
+		.unwrap();
+
+		assert_eq!(
+			parachain.genesis_patch(),
+			json!({
+				"parachainInfo": { "parachainId": 2000 },
+				"collatorSelection": { "invulnerables": [collator] },
+				"session": { "keys": [[collator, collator, { "aura": collator }]] },
+			})
+		);
+	}
+
+	#[test]
+	fn genesis_head_is_the_header_of_the_genesis_block() {
+		let runtime = substrate_test_runtime::WASM_BINARY_PATH.expect("to be a valid path. qed.");
+		let chain: ChainTopology =
+			serde_json::from_value(json!({ "runtime": runtime, "id": "test" })).unwrap();
+		let chain = chain.build(Path::new("."), json!({})).unwrap();
+
+		// The genesis block the node builds from the chain spec.
+		let (genesis_block, _) = GenesisBlockBuilder::<substrate_test_runtime::Block, _, _>::new(
+			&chain.chain_spec,
+			false,
+			Arc::new(in_mem::Backend::new()),
+			WasmExecutor::<sp_io::SubstrateHostFunctions>::builder().build(),
+		)
+		.unwrap()
+		.build_genesis_block()
+		.unwrap();
+
+		assert_eq!(chain.genesis_head().unwrap(), genesis_block.header().encode());
+	}
+}
@@ -257,6 +257,129 @@ fn test_create_with_properties() {
 	assert_output_eq_expected(true, SUFFIX, "tests/expected/create_with_properties.json");
 }
 
+#[test]
+fn test_create_with_patch_validates_schema() {
+	const SUFFIX: &str = "12";
+	let mut builder = get_builder(
+		SUFFIX,
+		vec![
+			"create",
+			"-r",
+			DUMMY_PATH,
+			"--validate-schema",
+			"patch",
+			"tests/input/patch_with_typo.json",
+		],
+	);
+	builder.set_create_cmd_runtime_code(substrate_test_runtime::WASM_BINARY.unwrap().into());
+	assert_eq!(
+		builder.run(),
+		Err("Genesis config does not match the runtime's schema:\n\
+			/balances/balance: unknown field `balance`, expected one of `balances`, `devAccounts`\n\
+			/substrateTest/authorities: expected array, found string"
+			.to_string())
+	);
+	assert!(!std::path::Path::new(&(OUTPUT_FILE.to_string() + SUFFIX)).exists());
+}
+
+#[test]
+fn test_validate() {
+	let runtime_path = substrate_test_runtime::WASM_BINARY_PATH.expect("to be a valid path. qed.");
+	let validate = |args: Vec<&str>| {
+		let mut base_args = vec!["dummy", "validate"];
+		base_args.extend(args);
+		ChainSpecBuilder::parse_from(base_args).run()
+	};
+
+	assert_eq!(validate(vec!["-r", runtime_path, "patch", "tests/input/patch.json"]), Ok(()));
+	assert_eq!(validate(vec!["-r", runtime_path, "full", "tests/input/full.json"]), Ok(()));
+	assert!(validate(vec!["-r", runtime_path, "patch", "tests/input/patch_with_typo.json"])
+		.unwrap_err()
+		.contains("/balances/balance: unknown field `balance`"));
+	// The chain spec embeds a dummy code blob, so the runtime has to be given explicitly.
+	assert_eq!(
+		validate(vec!["-r", runtime_path, "chain-spec", "tests/input/chain_spec_plain.json"]),
+		Ok(())
+	);
+}
+
+fn run_topology(suffix: &str, topology: Value) -> (std::path::PathBuf, Result<(), String>) {
+	let output_dir = std::path::PathBuf::from(OUTPUT_FILE.to_string() + suffix + ".d");
+	let topology_path = OUTPUT_FILE.to_string() + suffix;
+	std::fs::write(&topology_path, topology.to_string()).unwrap();
+	let result = ChainSpecBuilder::parse_from(vec![
+		"dummy",
+		"topology",
+		topology_path.as_str(),
+		"-o",
+		output_dir.to_str().unwrap(),
+	])
+	.run();
+	std::fs::remove_file(topology_path).expect("Failed to delete file");
+	(output_dir, result)
+}
+
+#[test]
+fn test_topology_relay_chain_only() {
+	let runtime_path = substrate_test_runtime::WASM_BINARY_PATH.expect("to be a valid path. qed.");
+	let (output_dir, result) = run_topology(
+		"13",
+		serde_json::json!({
+			"relayChain": { "runtime": runtime_path, "id": "test-relay", "preset": "staging" }
+		}),
+	);
+	assert_eq!(result, Ok(()));
+
+	let chain_spec: Value =
+		from_reader(File::open(output_dir.join("test-relay.json")).unwrap()).unwrap();
+	assert_eq!(chain_spec["id"], "test-relay");
+	assert_eq!(chain_spec["name"], "test-relay");
+	assert_eq!(chain_spec["chainType"], "Local");
+	assert!(chain_spec["genesis"]["runtimeGenesis"]["patch"]["substrateTest"].is_object());
+	std::fs::remove_dir_all(output_dir).expect("Failed to delete directory");
+}
+
+#[test]
+fn test_topology_validation() {
+	let chain = |id: &str| serde_json::json!({ "runtime": DUMMY_PATH, "id": id });
+	let parachain = |id: &str, para_id: u32| {
+		let mut parachain = chain(id);
+		parachain["paraId"] = para_id.into();
+		parachain
+	};
+
+	let (_, result) = run_topology(
+		"14",
+		serde_json::json!({
+			"relayChain": chain("relay"),
+			"parachains": [parachain("a", 1000), parachain("b", 1000)],
+		}),
+	);
+	assert_eq!(result, Err("Para ID 1000 is used more than once".to_string()));
+
+	let (_, result) = run_topology(
+		"15",
+		serde_json::json!({
+			"relayChain": chain("relay"),
+			"parachains": [parachain("relay", 1000)],
+		}),
+	);
+	assert_eq!(result, Err("Chain id relay is used more than once".to_string()));
+
+	let (_, result) = run_topology(
+		"16",
+		serde_json::json!({
+			"relayChain": chain("relay"),
+			"parachains": [parachain("a", 1000)],
+			"hrmpChannels": [{ "sender": 1000, "recipient": 2000 }],
+		}),
+	);
+	assert_eq!(
+		result,
+		Err("HRMP channel from para 1000 to para 2000 uses unknown para 2000".to_string())
+	);
+}
+
 #[docify::export_content]
 fn cmd_create_default(runtime_path: &str) -> String {
 	bash!(
@@ -418,3 +541,18 @@ fn create_full_raw() {
 		true,
 	);
 }
+
+#[docify::export_content]
+fn cmd_validate_patch(runtime_path: &str) -> String {
+	bash!(
+		chain-spec-builder validate -r $runtime_path patch "tests/input/patch.json"
+	)
+}
+
+#[test]
+fn validate_patch() {
+	let output = cmd_validate_patch(
+		substrate_test_runtime::WASM_BINARY_PATH.expect("to be a valid path. qed"),
+	);
+	assert_eq!(output.trim(), "Genesis config validation: OK");
+}
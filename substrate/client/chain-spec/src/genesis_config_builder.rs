@@ -23,10 +23,12 @@ pub use sc_executor::sp_wasm_interface::HostFunctions;
 use sc_executor::{error::Result, WasmExecutor};
 use serde_json::{from_slice, Value};
 use sp_core::{
-	storage::Storage,
-	traits::{CallContext, CodeExecutor, Externalities, FetchRuntimeCode, RuntimeCode},
+	storage::{StateVersion, Storage},
+	traits::{
+		CallContext, CodeExecutor, Externalities, FetchRuntimeCode, RuntimeCode, RuntimeVersionOf,
+	},
 };
-use sp_genesis_builder::{PresetId, Result as BuildResult};
+use sp_genesis_builder::{schema, PresetId, Result as BuildResult};
 pub use sp_genesis_builder::{DEV_RUNTIME_PRESET, LOCAL_TESTNET_RUNTIME_PRESET};
 use sp_state_machine::BasicExternalities;
 use std::borrow::Cow;
@@ -160,6 +162,43 @@ where
 		self.get_storage_for_patch(self.get_named_preset(name)?)
 	}
 
+	/// Returns the JSON Schema of the `RuntimeGenesisConfig` of the `runtime`.
+	///
+	/// Calls [`GenesisBuilder::genesis_config_schema`](sp_genesis_builder::GenesisBuilder::genesis_config_schema)
+	/// provided by the `runtime`. Fails for runtimes which do not implement version 2 of the API
+	/// yet, as their schema can not be generated from their type information.
+	pub fn get_config_schema(&self) -> core::result::Result<Value, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result =
+			self.call(&mut t, "GenesisBuilder_genesis_config_schema", &[]).map_err(|e| {
+				format!(
+					"The runtime does not provide the genesis config schema, \
+					`GenesisBuilder::genesis_config_schema` (version 2 of the API) is required: {e}"
+				)
+			})?;
+
+		let schema = Vec::<u8>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+
+		from_slice(&schema[..]).map_err(|e| format!("schema is not a valid json: {e}"))
+	}
+
+	/// Validates a patch for the default `RuntimeGenesisConfig` against the schema returned by
+	/// [`Self::get_config_schema`].
+	///
+	/// Each offending value is reported on its own line, prefixed with its JSON pointer.
+	pub fn validate_patch(&self, patch: &Value) -> core::result::Result<(), String> {
+		schema::validate_patch(&self.get_config_schema()?, patch).map_err(format_schema_errors)
+	}
+
+	/// Validates a full `RuntimeGenesisConfig` against the schema returned by
+	/// [`Self::get_config_schema`].
+	///
+	/// Each offending value is reported on its own line, prefixed with its JSON pointer.
+	pub fn validate_config(&self, config: &Value) -> core::result::Result<(), String> {
+		schema::validate_config(&self.get_config_schema()?, config).map_err(format_schema_errors)
+	}
+
 	pub fn preset_names(&self) -> core::result::Result<Vec<PresetId>, String> {
 		let mut t = BasicExternalities::new_empty();
 		let call_result = self
@@ -171,6 +210,22 @@ where
 
 		Ok(preset_names)
 	}
+
+	/// Returns the state version of the `runtime`, as given by its runtime version.
+	pub fn get_state_version(&self) -> core::result::Result<StateVersion, String> {
+		let mut ext = BasicExternalities::new_empty();
+		let runtime_code =
+			RuntimeCode { heap_pages: None, code_fetcher: self, hash: self.code_hash.clone() };
+		self.executor
+			.runtime_version(&mut ext, &runtime_code)
+			.map(|version| version.state_version())
+			.map_err(|e| format!("reading the runtime version failed: {e}"))
+	}
+}
+
+fn format_schema_errors(errors: Vec<schema::SchemaError>) -> String {
+	let errors = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+	format!("Genesis config does not match the runtime's schema:\n{}", errors.join("\n"))
 }
 
 #[cfg(test)]
@@ -190,6 +245,15 @@ mod tests {
 		assert_eq!(presets, vec![PresetId::from("foobar"), PresetId::from("staging"),]);
 	}
 
+	#[test]
+	fn get_state_version_works() {
+		let state_version =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap())
+				.get_state_version()
+				.unwrap();
+		assert_eq!(state_version, StateVersion::V1);
+	}
+
 	#[test]
 	fn get_default_config_works() {
 		let config =
@@ -211,6 +275,55 @@ mod tests {
 		assert_eq!(from_str::<Value>(expected).unwrap(), config);
 	}
 
+	#[test]
+	fn get_config_schema_works() {
+		sp_tracing::try_init_simple();
+		let schema =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap())
+				.get_config_schema()
+				.unwrap();
+		assert_eq!(
+			schema["properties"]["babe"]["properties"]["epochConfig"]["properties"]["c"],
+			json!({ "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "integer" }] })
+		);
+	}
+
+	#[test]
+	fn validate_patch_reports_typos() {
+		sp_tracing::try_init_simple();
+		let caller =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap());
+
+		let patch = json!({ "babe": { "epochConfig": { "c": [69, 696] } } });
+		assert_eq!(caller.validate_patch(&patch), Ok(()));
+
+		let patch = json!({ "babe": { "epochConfg": {} }, "balances": { "balances": 7 } });
+		assert_eq!(
+			caller.validate_patch(&patch),
+			Err("Genesis config does not match the runtime's schema:\n\
+				/babe/epochConfg: unknown field `epochConfg`, expected one of `authorities`, `epochConfig`\n\
+				/balances/balances: expected array, found integer"
+				.to_string())
+		);
+	}
+
+	#[test]
+	fn validate_config_requires_all_pallet_configs() {
+		sp_tracing::try_init_simple();
+		let caller =
+			<GenesisConfigBuilderRuntimeCaller>::new(substrate_test_runtime::wasm_binary_unwrap());
+
+		let mut config = caller.get_default_config().unwrap();
+		assert_eq!(caller.validate_config(&config), Ok(()));
+
+		config.as_object_mut().unwrap().remove("balances");
+		assert_eq!(
+			caller.validate_config(&config),
+			Err("Genesis config does not match the runtime's schema:\n/: missing field `balances`"
+				.to_string())
+		);
+	}
+
 	#[test]
 	fn get_storage_for_patch_works() {
 		let patch = json!({
@@ -0,0 +1,92 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: MIT-0
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+// of the Software, and to permit persons to whom the Software is furnished to do
+// so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Assets freezer pallet benchmarking.
+//!
+//! The pallet has no dispatchables: the benchmarks measure the `fungibles::freeze` operations
+//! other pallets use to freeze and thaw assets.
+
+use super::*;
+use frame::{benchmarking::prelude::*, deps::frame_support::assert_ok, traits::fungibles::Create};
+use pallet_assets::BenchmarkHelper;
+
+/// The first variant of the overarching freeze reason.
+fn freeze_reason<T: Config<I>, I: 'static>() -> T::RuntimeFreezeReason {
+	(0..=u8::MAX)
+		.find_map(|index| {
+			T::RuntimeFreezeReason::decode(&mut TrailingZeroInput::new(&[index])).ok()
+		})
+		.expect("the overarching freeze reason has at least one variant; qed")
+}
+
+/// Creates an asset and funds an account with it.
+fn setup<T: Config<I>, I: 'static>() -> (T::AssetId, T::AccountId, T::RuntimeFreezeReason) {
+	let asset: T::AssetId =
+		<T as pallet_assets::Config<I>>::BenchmarkHelper::create_asset_id_parameter(0).into();
+	let who: T::AccountId = whitelisted_caller();
+	assert_ok!(<pallet_assets::Pallet<T, I> as Create<_>>::create(
+		asset.clone(),
+		who.clone(),
+		true,
+		1u32.into(),
+	));
+	assert_ok!(<pallet_assets::Pallet<T, I> as Mutate<_>>::mint_into(
+		asset.clone(),
+		&who,
+		1_000_000u32.into(),
+	));
+	(asset, who, freeze_reason::<T, I>())
+}
+
+#[instance_benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn set_freeze() {
+		let (asset, who, reason) = setup::<T, I>();
+		let amount = 1_000u32.into();
+
+		#[block]
+		{
+			assert_ok!(Pallet::<T, I>::set_freeze(asset.clone(), &reason, &who, amount));
+		}
+
+		assert_eq!(FrozenBalances::<T, I>::get(asset, &who), Some(amount));
+	}
+
+	#[benchmark]
+	fn thaw() {
+		let (asset, who, reason) = setup::<T, I>();
+		assert_ok!(Pallet::<T, I>::set_freeze(asset.clone(), &reason, &who, 1_000u32.into()));
+
+		#[block]
+		{
+			assert_ok!(Pallet::<T, I>::thaw(asset.clone(), &reason, &who));
+		}
+
+		assert!(FrozenBalances::<T, I>::get(asset, &who).is_none());
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(|| {}), crate::mock::Test);
+}
@@ -109,6 +109,90 @@ impl<T: Config<I>, I: 'static> Inspect<T::AccountId> for Pallet<T, I> {
 	}
 }
 
+impl<T: Config<I>, I: 'static> Unbalanced<T::AccountId> for Pallet<T, I> {
+	fn handle_dust(dust: Dust<T::AccountId, Self>) {
+		let Dust(id, balance) = dust;
+		pallet_assets::Pallet::<T, I>::handle_dust(Dust(id, balance));
+	}
+
+	fn write_balance(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> Result<Option<Self::Balance>, DispatchError> {
+		pallet_assets::Pallet::<T, I>::write_balance(asset, who, amount)
+	}
+
+	fn set_total_issuance(asset: Self::AssetId, amount: Self::Balance) {
+		pallet_assets::Pallet::<T, I>::set_total_issuance(asset, amount)
+	}
+
+	fn decrease_balance(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+		preservation: Preservation,
+		force: Fortitude,
+	) -> Result<Self::Balance, DispatchError> {
+		pallet_assets::Pallet::<T, I>::decrease_balance(
+			asset,
+			who,
+			amount,
+			precision,
+			preservation,
+			force,
+		)
+	}
+
+	fn increase_balance(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+	) -> Result<Self::Balance, DispatchError> {
+		pallet_assets::Pallet::<T, I>::increase_balance(asset, who, amount, precision)
+	}
+}
+
+// Implement [`fungibles::Mutate`](Mutate) and [`fungibles::Balanced`](Balanced) on top of the
+// `Unbalanced` implementation above, the same way `pallet-assets-holder` does.
+impl<T: Config<I>, I: 'static> Mutate<T::AccountId> for Pallet<T, I> {
+	fn done_mint_into(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_mint_into(asset_id, who, amount)
+	}
+
+	fn done_burn_from(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_burn_from(asset_id, who, amount)
+	}
+
+	fn done_transfer(
+		asset_id: Self::AssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: Self::Balance,
+	) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_transfer(
+			asset_id, source, dest, amount,
+		)
+	}
+}
+
+impl<T: Config<I>, I: 'static> Balanced<T::AccountId> for Pallet<T, I> {
+	type OnDropCredit = DecreaseIssuance<T::AccountId, Self>;
+	type OnDropDebt = IncreaseIssuance<T::AccountId, Self>;
+
+	fn done_deposit(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Balanced<T::AccountId>>::done_deposit(asset_id, who, amount)
+	}
+
+	fn done_withdraw(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Balanced<T::AccountId>>::done_withdraw(
+			asset_id, who, amount,
+		)
+	}
+}
+
 impl<T: Config<I>, I: 'static> InspectFreeze<T::AccountId> for Pallet<T, I> {
 	type Id = T::RuntimeFreezeReason;
 
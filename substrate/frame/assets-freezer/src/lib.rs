@@ -44,30 +44,47 @@
 //! - An implementation of [`fungibles::freeze::Inspect`](InspectFreeze) and
 //!   [`fungibles::freeze::Mutate`](MutateFreeze), allowing other pallets to manage freezes for the
 //!   `pallet-assets` assets.
+//! - An implementation of [`fungibles::Mutate`](Mutate) and [`fungibles::Balanced`](Balanced)
+//!   forwarding to `pallet-assets`, so that a single type can be used by pallets which both move
+//!   and freeze funds.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
 use frame::{
 	prelude::*,
 	traits::{
-		fungibles::{Inspect, InspectFreeze, MutateFreeze},
+		fungibles::{
+			Balanced, DecreaseIssuance, Dust, IncreaseIssuance, Inspect, InspectFreeze, Mutate,
+			MutateFreeze, Unbalanced,
+		},
 		tokens::{
-			DepositConsequence, Fortitude, IdAmount, Preservation, Provenance, WithdrawConsequence,
+			DepositConsequence, Fortitude, IdAmount, Precision, Preservation, Provenance,
+			WithdrawConsequence,
 		},
 	},
 };
 
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 #[cfg(feature = "try-runtime")]
 use frame::try_runtime::TryRuntimeError;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
 mod tests;
 
 mod impls;
+pub mod migration;
+pub mod weights;
+
+/// The in-code storage version.
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 #[frame::pallet]
 pub mod pallet {
@@ -92,6 +109,7 @@ pub mod pallet {
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T, I = ()>(_);
 
 	#[pallet::event]
@@ -168,7 +186,23 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 
 	#[cfg(feature = "try-runtime")]
 	fn do_try_state() -> Result<(), TryRuntimeError> {
+		for (asset, who, freezes) in Freezes::<T, I>::iter() {
+			ensure!(!freezes.is_empty(), "empty freezes must not be in state");
+			ensure!(
+				freezes.iter().all(|freeze| !freeze.amount.is_zero()),
+				"zero freezes must not be in state"
+			);
+			ensure!(
+				FrozenBalances::<T, I>::contains_key(asset.clone(), who.clone()),
+				"`Freezes` of (`asset`, `who`) without a `FrozenBalances` entry"
+			);
+		}
+
 		for (asset, who, _) in FrozenBalances::<T, I>::iter() {
+			ensure!(
+				pallet_assets::Account::<T, I>::contains_key(asset.clone(), who.clone()),
+				"a frozen balance requires the asset account of `who` to exist"
+			);
 			let max_frozen_amount =
 				Freezes::<T, I>::get(asset.clone(), who.clone()).iter().map(|l| l.amount).max();
 
@@ -0,0 +1,94 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: MIT-0
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies
+// of the Software, and to permit persons to whom the Software is furnished to do
+// so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Storage migrations for the assets freezer pallet.
+
+use super::*;
+use frame::deps::frame_support::{
+	migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade,
+};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+
+pub mod v1 {
+	use super::*;
+
+	/// Restores the invariants checked by `try_state` on the freezes of every account.
+	///
+	/// Zero freezes are dropped, `FrozenBalances` is set to the largest of the `Freezes` of the
+	/// account, and accounts left without freezes lose both entries. Use [`MigrateV0ToV1`]
+	/// instead.
+	pub struct UncheckedMigrateV0ToV1<T, I = ()>(PhantomData<(T, I)>);
+
+	impl<T: Config<I>, I: 'static> UncheckedOnRuntimeUpgrade for UncheckedMigrateV0ToV1<T, I> {
+		fn on_runtime_upgrade() -> Weight {
+			let (mut balances, mut freezes) = (0u64, 0u64);
+
+			FrozenBalances::<T, I>::translate::<T::Balance, _>(|asset, who, frozen| {
+				balances.saturating_inc();
+				Freezes::<T, I>::contains_key(asset, who).then_some(frozen)
+			});
+
+			Freezes::<T, I>::translate::<
+				BoundedVec<
+					IdAmount<T::RuntimeFreezeReason, T::Balance>,
+					VariantCountOf<T::RuntimeFreezeReason>,
+				>,
+				_,
+			>(|asset, who, mut account_freezes| {
+				freezes.saturating_inc();
+				account_freezes.retain(|freeze| !freeze.amount.is_zero());
+				match account_freezes.iter().map(|freeze| freeze.amount).max() {
+					Some(frozen) => {
+						FrozenBalances::<T, I>::insert(asset, who, frozen);
+						Some(account_freezes)
+					},
+					None => {
+						FrozenBalances::<T, I>::remove(asset, who);
+						None
+					},
+				}
+			});
+
+			T::DbWeight::get().reads_writes(
+				balances.saturating_mul(2).saturating_add(freezes),
+				balances.saturating_add(freezes.saturating_mul(2)),
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_: Vec<u8>) -> Result<(), TryRuntimeError> {
+			Pallet::<T, I>::do_try_state()
+		}
+	}
+
+	/// Migrates the pallet to storage version 1 with [`UncheckedMigrateV0ToV1`].
+	pub type MigrateV0ToV1<T, I = ()> = VersionedMigration<
+		0,
+		1,
+		UncheckedMigrateV0ToV1<T, I>,
+		Pallet<T, I>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
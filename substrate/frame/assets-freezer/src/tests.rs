@@ -309,4 +309,88 @@ mod with_pallet_assets {
 			assert_ok!(Assets::transfer(RuntimeOrigin::signed(WHO), Compact(ASSET_ID), 2, 80));
 		});
 	}
+
+	#[test]
+	fn frozen_balance_affects_fungibles_transferring() {
+		new_test_ext(|| {
+			assert_ok!(AssetsFreezer::set_freeze(
+				ASSET_ID,
+				&DummyFreezeReason::Governance,
+				&WHO,
+				20
+			));
+			assert_noop!(
+				<AssetsFreezer as fungibles::Mutate<_>>::transfer(
+					ASSET_ID,
+					&WHO,
+					&2,
+					81,
+					Preservation::Expendable
+				),
+				TokenError::Frozen
+			);
+			assert_ok!(<AssetsFreezer as fungibles::Mutate<_>>::transfer(
+				ASSET_ID,
+				&WHO,
+				&2,
+				80,
+				Preservation::Expendable
+			));
+			assert_eq!(Assets::balance(ASSET_ID, 2), 80);
+			System::assert_has_event(
+				pallet_assets::Event::<Test>::Transferred {
+					asset_id: ASSET_ID,
+					from: WHO,
+					to: 2,
+					amount: 80,
+				}
+				.into(),
+			);
+		});
+	}
+}
+
+mod migration {
+	use super::*;
+
+	#[test]
+	fn migrate_v0_to_v1_restores_invariants() {
+		new_test_ext(|| {
+			StorageVersion::new(0).put::<AssetsFreezer>();
+
+			// A zero freeze next to a non-zero one, with a stale frozen balance.
+			Freezes::<Test>::insert(
+				ASSET_ID,
+				WHO,
+				BoundedVec::truncate_from(vec![
+					IdAmount { id: DummyFreezeReason::Governance, amount: 0 },
+					IdAmount { id: DummyFreezeReason::Staking, amount: 5 },
+				]),
+			);
+			FrozenBalances::<Test>::insert(ASSET_ID, WHO, 7);
+			// Only zero freezes.
+			Freezes::<Test>::insert(
+				ASSET_ID,
+				3,
+				BoundedVec::truncate_from(vec![IdAmount {
+					id: DummyFreezeReason::Other,
+					amount: 0,
+				}]),
+			);
+			// A frozen balance without freezes.
+			FrozenBalances::<Test>::insert(ASSET_ID, 2, 4);
+
+			crate::migration::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+			assert_eq!(StorageVersion::get::<AssetsFreezer>(), 1);
+			assert_eq!(
+				Freezes::<Test>::get(ASSET_ID, WHO).into_inner(),
+				vec![IdAmount { id: DummyFreezeReason::Staking, amount: 5 }]
+			);
+			assert_eq!(FrozenBalances::<Test>::get(ASSET_ID, WHO), Some(5));
+			assert!(!Freezes::<Test>::contains_key(ASSET_ID, 3));
+			assert!(!FrozenBalances::<Test>::contains_key(ASSET_ID, 3));
+			assert!(!FrozenBalances::<Test>::contains_key(ASSET_ID, 2));
+		});
+	}
 }
@@ -0,0 +1,137 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Assets holder pallet benchmarking.
+//!
+//! The pallet has no dispatchables: the benchmarks measure the `fungibles::hold` operations
+//! other pallets use to hold, release, burn and slash assets.
+
+use super::*;
+use frame_benchmarking::v2::*;
+use frame_support::{
+	assert_ok,
+	traits::{
+		fungibles::{BalancedHold, Create, InspectHold, Mutate, MutateHold},
+		tokens::{Fortitude, Precision},
+	},
+};
+use pallet_assets::BenchmarkHelper;
+use sp_runtime::traits::TrailingZeroInput;
+
+/// The first variant of the overarching hold reason.
+fn hold_reason<T: Config<I>, I: 'static>() -> T::RuntimeHoldReason {
+	(0..=u8::MAX)
+		.find_map(|index| T::RuntimeHoldReason::decode(&mut TrailingZeroInput::new(&[index])).ok())
+		.expect("the overarching hold reason has at least one variant; qed")
+}
+
+/// Creates an asset and funds an account with it.
+fn setup<T: Config<I>, I: 'static>() -> (T::AssetId, T::AccountId, T::RuntimeHoldReason) {
+	let asset: T::AssetId =
+		<T as pallet_assets::Config<I>>::BenchmarkHelper::create_asset_id_parameter(0).into();
+	let who: T::AccountId = whitelisted_caller();
+	assert_ok!(<pallet_assets::Pallet<T, I> as Create<_>>::create(
+		asset.clone(),
+		who.clone(),
+		true,
+		1u32.into(),
+	));
+	assert_ok!(<pallet_assets::Pallet<T, I> as Mutate<_>>::mint_into(
+		asset.clone(),
+		&who,
+		1_000_000u32.into(),
+	));
+	(asset, who, hold_reason::<T, I>())
+}
+
+#[instance_benchmarks]
+mod benchmarks {
+	use super::*;
+
+	#[benchmark]
+	fn hold() {
+		let (asset, who, reason) = setup::<T, I>();
+		let amount = 1_000u32.into();
+
+		#[block]
+		{
+			assert_ok!(Pallet::<T, I>::hold(asset.clone(), &reason, &who, amount));
+		}
+
+		assert_eq!(Pallet::<T, I>::balance_on_hold(asset, &reason, &who), amount);
+	}
+
+	#[benchmark]
+	fn release() {
+		let (asset, who, reason) = setup::<T, I>();
+		let amount = 1_000u32.into();
+		assert_ok!(Pallet::<T, I>::hold(asset.clone(), &reason, &who, amount));
+
+		#[block]
+		{
+			assert_ok!(Pallet::<T, I>::release(
+				asset.clone(),
+				&reason,
+				&who,
+				amount,
+				Precision::Exact
+			));
+		}
+
+		assert!(BalancesOnHold::<T, I>::get(asset, &who).is_none());
+	}
+
+	#[benchmark]
+	fn burn_held() {
+		let (asset, who, reason) = setup::<T, I>();
+		let amount = 1_000u32.into();
+		assert_ok!(Pallet::<T, I>::hold(asset.clone(), &reason, &who, amount));
+
+		#[block]
+		{
+			assert_ok!(Pallet::<T, I>::burn_held(
+				asset.clone(),
+				&reason,
+				&who,
+				amount,
+				Precision::Exact,
+				Fortitude::Force,
+			));
+		}
+
+		assert!(BalancesOnHold::<T, I>::get(asset, &who).is_none());
+	}
+
+	#[benchmark]
+	fn slash() {
+		let (asset, who, reason) = setup::<T, I>();
+		let amount = 1_000u32.into();
+		assert_ok!(Pallet::<T, I>::hold(asset.clone(), &reason, &who, amount));
+
+		#[block]
+		{
+			// Dropping the credit decreases the total issuance.
+			let (credit, _) =
+				<Pallet<T, I> as BalancedHold<_>>::slash(asset.clone(), &reason, &who, amount);
+			drop(credit);
+		}
+
+		assert!(BalancesOnHold::<T, I>::get(asset, &who).is_none());
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(|| {}), crate::mock::Test);
+}
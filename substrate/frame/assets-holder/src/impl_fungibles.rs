@@ -18,7 +18,10 @@
 use super::*;
 
 use frame_support::traits::{
-	fungibles::{Dust, Inspect, InspectHold, MutateHold, Unbalanced, UnbalancedHold},
+	fungibles::{
+		hold::DoneSlash, Balanced, BalancedHold, DecreaseIssuance, Dust, IncreaseIssuance, Inspect,
+		InspectHold, Mutate, MutateHold, Unbalanced, UnbalancedHold,
+	},
 	tokens::{
 		DepositConsequence, Fortitude, Precision, Preservation, Provenance, WithdrawConsequence,
 	},
@@ -180,6 +183,46 @@ impl<T: Config<I>, I: 'static> Unbalanced<T::AccountId> for Pallet<T, I> {
 	}
 }
 
+// Implement [`fungibles::Mutate`](frame_support::traits::fungibles::Mutate) and
+// [`fungibles::Balanced`](frame_support::traits::fungibles::Balanced) on top of the `Unbalanced`
+// implementation above, forwarding the bookkeeping callbacks so `pallet-assets` emits the same
+// events as when the operations are done on it directly.
+impl<T: Config<I>, I: 'static> Mutate<T::AccountId> for Pallet<T, I> {
+	fn done_mint_into(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_mint_into(asset_id, who, amount)
+	}
+
+	fn done_burn_from(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_burn_from(asset_id, who, amount)
+	}
+
+	fn done_transfer(
+		asset_id: Self::AssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: Self::Balance,
+	) {
+		<pallet_assets::Pallet<T, I> as Mutate<T::AccountId>>::done_transfer(
+			asset_id, source, dest, amount,
+		)
+	}
+}
+
+impl<T: Config<I>, I: 'static> Balanced<T::AccountId> for Pallet<T, I> {
+	type OnDropCredit = DecreaseIssuance<T::AccountId, Self>;
+	type OnDropDebt = IncreaseIssuance<T::AccountId, Self>;
+
+	fn done_deposit(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Balanced<T::AccountId>>::done_deposit(asset_id, who, amount)
+	}
+
+	fn done_withdraw(asset_id: Self::AssetId, who: &T::AccountId, amount: Self::Balance) {
+		<pallet_assets::Pallet<T, I> as Balanced<T::AccountId>>::done_withdraw(
+			asset_id, who, amount,
+		)
+	}
+}
+
 impl<T: Config<I>, I: 'static> UnbalancedHold<T::AccountId> for Pallet<T, I> {
 	fn set_balance_on_hold(
 		asset: Self::AssetId,
@@ -288,3 +331,23 @@ impl<T: Config<I>, I: 'static> MutateHold<T::AccountId> for Pallet<T, I> {
 		});
 	}
 }
+
+impl<T: Config<I>, I: 'static> BalancedHold<T::AccountId> for Pallet<T, I> {}
+
+impl<T: Config<I>, I: 'static> DoneSlash<T::AssetId, T::RuntimeHoldReason, T::AccountId, T::Balance>
+	for Pallet<T, I>
+{
+	fn done_slash(
+		asset_id: T::AssetId,
+		reason: &T::RuntimeHoldReason,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) {
+		Self::deposit_event(Event::<T, I>::Slashed {
+			asset_id,
+			who: who.clone(),
+			reason: *reason,
+			amount,
+		});
+	}
+}
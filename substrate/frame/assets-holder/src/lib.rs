@@ -21,7 +21,8 @@
 //! `pallet-assets`, wrapping [`fungibles::Inspect`](`frame_support::traits::fungibles::Inspect`).
 //! It implements both
 //! [`fungibles::hold::Inspect`](frame_support::traits::fungibles::hold::Inspect),
-//! [`fungibles::hold::Mutate`](frame_support::traits::fungibles::hold::Mutate), and especially
+//! [`fungibles::hold::Mutate`](frame_support::traits::fungibles::hold::Mutate),
+//! [`fungibles::hold::Balanced`](frame_support::traits::fungibles::hold::Balanced), and especially
 //! [`fungibles::hold::Unbalanced`](frame_support::traits::fungibles::hold::Unbalanced). The
 //! complexity of the operations is `O(1)`.
 //!
@@ -38,12 +39,19 @@
 //!   asset (see [`pallet_assets::BalanceOnHold`]).
 //! - An implementation of
 //!   [`fungibles::hold::Inspect`](frame_support::traits::fungibles::hold::Inspect),
-//!   [`fungibles::hold::Mutate`](frame_support::traits::fungibles::hold::Mutate) and
+//!   [`fungibles::hold::Mutate`](frame_support::traits::fungibles::hold::Mutate),
+//!   [`fungibles::hold::Balanced`](frame_support::traits::fungibles::hold::Balanced) and
 //!   [`fungibles::hold::Unbalanced`](frame_support::traits::fungibles::hold::Unbalanced), allowing
-//!   other pallets to manage holds for the `pallet-assets` assets.
+//!   other pallets to manage and slash holds for the `pallet-assets` assets.
+//! - An implementation of [`fungibles::Mutate`](frame_support::traits::fungibles::Mutate) and
+//!   [`fungibles::Balanced`](frame_support::traits::fungibles::Balanced) forwarding to
+//!   `pallet-assets`, so that a single type can be used by pallets which both move and hold funds,
+//!   e.g. through [`fungible::ItemOf`](frame_support::traits::fungible::ItemOf).
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
 use frame_support::{
 	pallet_prelude::*,
 	traits::{tokens::IdAmount, VariantCount, VariantCountOf},
@@ -52,13 +60,21 @@ use frame_support::{
 use frame_system::pallet_prelude::BlockNumberFor;
 
 pub use pallet::*;
+pub use weights::WeightInfo;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
 mod tests;
 
 mod impl_fungibles;
+pub mod migration;
+pub mod weights;
+
+/// The in-code storage version.
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -85,6 +101,7 @@ pub mod pallet {
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T, I = ()>(_);
 
 	#[pallet::event]
@@ -111,6 +128,13 @@ pub mod pallet {
 			reason: T::RuntimeHoldReason,
 			amount: T::Balance,
 		},
+		/// `who`s balance on hold was slashed by `amount`.
+		Slashed {
+			who: T::AccountId,
+			asset_id: T::AssetId,
+			reason: T::RuntimeHoldReason,
+			amount: T::Balance,
+		},
 	}
 
 	/// A map that stores holds applied on an account for a given AssetId.
@@ -156,8 +180,20 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 			ArithmeticError,
 		};
 
+		for (asset, who, holds) in Holds::<T, I>::iter() {
+			ensure!(!holds.is_empty(), "empty holds must not be in state");
+			ensure!(
+				BalancesOnHold::<T, I>::contains_key(asset.clone(), who.clone()),
+				"`Holds` of (`asset`, `who`) without a `BalancesOnHold` entry"
+			);
+		}
+
 		for (asset, who, balance_on_hold) in BalancesOnHold::<T, I>::iter() {
 			ensure!(balance_on_hold != Zero::zero(), "zero on hold must not be in state");
+			ensure!(
+				pallet_assets::Account::<T, I>::contains_key(asset.clone(), who.clone()),
+				"a balance on hold requires the asset account of `who` to exist"
+			);
 
 			let mut amount_from_holds: T::Balance = Zero::zero();
 			for l in Holds::<T, I>::get(asset.clone(), who.clone()).iter() {
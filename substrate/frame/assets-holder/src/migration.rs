@@ -0,0 +1,86 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the assets holder pallet.
+
+use super::*;
+use frame_support::{migrations::VersionedMigration, traits::UncheckedOnRuntimeUpgrade};
+use sp_runtime::traits::{Saturating, Zero};
+
+#[cfg(feature = "try-runtime")]
+use alloc::vec::Vec;
+
+pub mod v1 {
+	use super::*;
+
+	/// Restores the invariants checked by `try_state` on the holds of every account.
+	///
+	/// Zero holds are dropped, `BalancesOnHold` is set to the sum of the `Holds` of the account,
+	/// and accounts left without holds lose both entries. Use [`MigrateV0ToV1`] instead.
+	pub struct UncheckedMigrateV0ToV1<T, I = ()>(PhantomData<(T, I)>);
+
+	impl<T: Config<I>, I: 'static> UncheckedOnRuntimeUpgrade for UncheckedMigrateV0ToV1<T, I> {
+		fn on_runtime_upgrade() -> Weight {
+			let (mut balances, mut holds) = (0u64, 0u64);
+
+			BalancesOnHold::<T, I>::translate::<T::Balance, _>(|asset, who, on_hold| {
+				balances.saturating_inc();
+				Holds::<T, I>::contains_key(asset, who).then_some(on_hold)
+			});
+
+			Holds::<T, I>::translate::<
+				BoundedVec<
+					IdAmount<T::RuntimeHoldReason, T::Balance>,
+					VariantCountOf<T::RuntimeHoldReason>,
+				>,
+				_,
+			>(|asset, who, mut account_holds| {
+				holds.saturating_inc();
+				account_holds.retain(|hold| !hold.amount.is_zero());
+				let on_hold = account_holds
+					.iter()
+					.fold(T::Balance::zero(), |total, hold| total.saturating_add(hold.amount));
+				if account_holds.is_empty() {
+					BalancesOnHold::<T, I>::remove(asset, who);
+					None
+				} else {
+					BalancesOnHold::<T, I>::insert(asset, who, on_hold);
+					Some(account_holds)
+				}
+			});
+
+			T::DbWeight::get().reads_writes(
+				balances.saturating_mul(2).saturating_add(holds),
+				balances.saturating_add(holds.saturating_mul(2)),
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			Pallet::<T, I>::do_try_state()
+		}
+	}
+
+	/// Migrates the pallet to storage version 1 with [`UncheckedMigrateV0ToV1`].
+	pub type MigrateV0ToV1<T, I = ()> = VersionedMigration<
+		0,
+		1,
+		UncheckedMigrateV0ToV1<T, I>,
+		Pallet<T, I>,
+		<T as frame_system::Config>::DbWeight,
+	>;
+}
@@ -556,3 +556,134 @@ mod impl_hold_mutate {
 		});
 	}
 }
+
+mod impl_hold_balanced {
+	use super::*;
+	use frame_support::traits::tokens::fungibles::BalancedHold;
+
+	#[test]
+	fn slash_works() {
+		new_test_ext(|| {
+			assert_ok!(AssetsHolder::hold(ASSET_ID, &DummyHoldReason::Governance, &WHO, 30));
+			assert_ok!(AssetsHolder::hold(ASSET_ID, &DummyHoldReason::Staking, &WHO, 20));
+
+			// Slashing over the amount on hold slashes everything on hold for the given reason,
+			// and returns the amount that could not be slashed.
+			let (credit, missing) =
+				AssetsHolder::slash(ASSET_ID, &DummyHoldReason::Governance, &WHO, 40);
+			assert_eq!(credit.peek(), 30);
+			assert_eq!(missing, 10);
+			assert_eq!(
+				<AssetsHolder as InspectHold<_>>::balance_on_hold(
+					ASSET_ID,
+					&DummyHoldReason::Governance,
+					&WHO
+				),
+				0
+			);
+			assert_eq!(AssetsHolder::total_balance_on_hold(ASSET_ID, &WHO), 20);
+			assert_eq!(Assets::balance(ASSET_ID, WHO), 50);
+			assert_eq!(Assets::total_balance(ASSET_ID, &WHO), 70);
+			System::assert_has_event(
+				Event::<Test>::Slashed {
+					who: WHO,
+					asset_id: ASSET_ID,
+					reason: DummyHoldReason::Governance,
+					amount: 30,
+				}
+				.into(),
+			);
+
+			// Total issuance is only reduced once the credit is dropped.
+			assert_eq!(Assets::total_issuance(ASSET_ID), 100);
+			drop(credit);
+			assert_eq!(Assets::total_issuance(ASSET_ID), 70);
+		});
+	}
+}
+
+mod impl_mutate {
+	use super::*;
+	use frame_support::traits::tokens::{fungibles::Mutate, Preservation};
+
+	#[test]
+	fn transfer_respects_holds_and_emits_assets_events() {
+		new_test_ext(|| {
+			assert_ok!(AssetsHolder::hold(ASSET_ID, &DummyHoldReason::Governance, &WHO, 60));
+
+			assert_noop!(
+				<AssetsHolder as Mutate<_>>::transfer(
+					ASSET_ID,
+					&WHO,
+					&2,
+					50,
+					Preservation::Expendable
+				),
+				sp_runtime::TokenError::FundsUnavailable
+			);
+			assert_ok!(<AssetsHolder as Mutate<_>>::transfer(
+				ASSET_ID,
+				&WHO,
+				&2,
+				20,
+				Preservation::Expendable
+			));
+			assert_eq!(Assets::balance(ASSET_ID, WHO), 20);
+			assert_eq!(Assets::balance(ASSET_ID, 2), 20);
+			System::assert_has_event(
+				pallet_assets::Event::<Test>::Transferred {
+					asset_id: ASSET_ID,
+					from: WHO,
+					to: 2,
+					amount: 20,
+				}
+				.into(),
+			);
+		});
+	}
+}
+
+mod migration {
+	use super::*;
+	use frame_support::traits::{tokens::IdAmount, OnRuntimeUpgrade};
+
+	#[test]
+	fn migrate_v0_to_v1_restores_invariants() {
+		new_test_ext(|| {
+			StorageVersion::new(0).put::<AssetsHolder>();
+
+			// A zero hold next to a non-zero one, with a stale total.
+			Holds::<Test>::insert(
+				ASSET_ID,
+				WHO,
+				BoundedVec::truncate_from(vec![
+					IdAmount { id: DummyHoldReason::Governance, amount: 0 },
+					IdAmount { id: DummyHoldReason::Staking, amount: 5 },
+				]),
+			);
+			BalancesOnHold::<Test>::insert(ASSET_ID, WHO, 7);
+			// Only zero holds.
+			Holds::<Test>::insert(
+				ASSET_ID,
+				3,
+				BoundedVec::truncate_from(vec![IdAmount { id: DummyHoldReason::Other, amount: 0 }]),
+			);
+			// A total without holds.
+			BalancesOnHold::<Test>::insert(ASSET_ID, 2, 4);
+			assert!(AssetsHolder::do_try_state().is_err());
+
+			crate::migration::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+			assert_eq!(StorageVersion::get::<AssetsHolder>(), 1);
+			assert_eq!(
+				Holds::<Test>::get(ASSET_ID, WHO).into_inner(),
+				vec![IdAmount { id: DummyHoldReason::Staking, amount: 5 }]
+			);
+			assert_eq!(BalancesOnHold::<Test>::get(ASSET_ID, WHO), Some(5));
+			assert!(!Holds::<Test>::contains_key(ASSET_ID, 3));
+			assert!(!BalancesOnHold::<Test>::contains_key(ASSET_ID, 3));
+			assert!(!BalancesOnHold::<Test>::contains_key(ASSET_ID, 2));
+			assert_ok!(AssetsHolder::do_try_state());
+		});
+	}
+}
@@ -311,7 +311,8 @@ pub mod pallet {
 	pub type SkippedEpochs<T> =
 		StorageValue<_, BoundedVec<(u64, SessionIndex), ConstU32<100>>, ValueQuery>;
 
-	#[derive(frame_support::DefaultNoBound)]
+	#[derive(frame_support::DefaultNoBound, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub authorities: Vec<(AuthorityId, BabeAuthorityWeight)>,
@@ -514,6 +514,8 @@ pub mod pallet {
 	>;
 
 	#[pallet::genesis_config]
+	#[derive(TypeInfo)]
+	#[scale_info(skip_type_params(T, I))]
 	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
 		pub balances: Vec<(T::AccountId, T::Balance)>,
 		/// Derived development accounts(Optional):
@@ -153,6 +153,7 @@ mod tests;
 
 extern crate alloc;
 
+use alloc::vec::Vec;
 use codec::{Codec, Encode};
 use core::marker::PhantomData;
 use frame_support::{
@@ -167,7 +168,7 @@ use frame_support::{
 	},
 	weights::{Weight, WeightMeter},
 };
-use frame_system::pallet_prelude::BlockNumberFor;
+use frame_system::{pallet_prelude::BlockNumberFor, EventRecord};
 use sp_runtime::{
 	generic::Digest,
 	traits::{
@@ -812,6 +813,26 @@ where
 		Ok(r.map(|_| ()).map_err(|e| e.error))
 	}
 
+	/// Apply an extrinsic on top of the current state and return the result together with the
+	/// events it emitted.
+	///
+	/// Events that are already in storage are left untouched and only the ones deposited by `uxt`
+	/// are returned. This is meant to back simulation runtime APIs such as
+	/// `frame_system_rpc_runtime_api::ExtrinsicSimulationApi`; as with
+	/// [`Self::validate_transaction`], changes made to storage should be discarded.
+	pub fn simulate_extrinsic(
+		uxt: Block::Extrinsic,
+	) -> (ApplyExtrinsicResult, Vec<EventRecord<System::RuntimeEvent, System::Hash>>) {
+		let previous_events = <frame_system::Pallet<System>>::read_events_no_consensus().count();
+		let result = Self::apply_extrinsic(uxt);
+		let events = <frame_system::Pallet<System>>::read_events_no_consensus()
+			.skip(previous_events)
+			.map(|record| *record)
+			.collect();
+
+		(result, events)
+	}
+
 	fn final_checks(header: &frame_system::pallet_prelude::HeaderFor<System>) {
 		sp_tracing::enter_span!(sp_tracing::Level::TRACE, "final_checks");
 		// remove temporaries
@@ -607,6 +607,36 @@ fn balance_transfer_dispatch_works() {
 	});
 }
 
+#[test]
+fn simulate_extrinsic_returns_own_events_and_fee() {
+	let xt = UncheckedXt::new_signed(call_transfer(2, 69), 1, 1.into(), tx_ext(0, 0));
+	new_test_ext(1).execute_with(|| {
+		Executive::initialize_block(&Header::new_from_number(1));
+		// An event deposited before the simulation must not be reported.
+		<frame_system::Pallet<Runtime>>::deposit_event(frame_system::Event::<Runtime>::CodeUpdated);
+
+		let (result, events) = Executive::simulate_extrinsic(xt);
+		assert_eq!(result, Ok(Ok(())));
+
+		let events = events.into_iter().map(|record| record.event).collect::<Vec<_>>();
+		assert!(!events.contains(&RuntimeEvent::System(frame_system::Event::CodeUpdated)));
+		// ...and must be kept in storage.
+		assert!(<frame_system::Pallet<Runtime>>::read_events_no_consensus()
+			.any(|record| record.event == RuntimeEvent::System(frame_system::Event::CodeUpdated)));
+		assert!(events.contains(&RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+			from: 1,
+			to: 2,
+			amount: 69,
+		})));
+
+		let paid = 111 - 69 - <pallet_balances::Pallet<Runtime>>::total_balance(&1);
+		assert_eq!(
+			<pallet_transaction_payment::Pallet<Runtime>>::actual_fee_paid(events),
+			Some(paid)
+		);
+	});
+}
+
 fn new_test_ext(balance_factor: Balance) -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::<Runtime>::default().build_storage().unwrap();
 	pallet_balances::GenesisConfig::<Runtime> {
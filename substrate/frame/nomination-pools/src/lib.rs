@@ -1660,6 +1660,13 @@ pub mod pallet {
 		type WeightInfo: weights::WeightInfo;
 
 		/// The currency type used for nomination pool.
+		///
+		/// This must be the same asset as the one staked through [`Config::StakeAdapter`]. A
+		/// `fungibles` asset can be used by identifying it with
+		/// [`ItemOf`](frame_support::traits::fungible::ItemOf). For `pallet-assets`,
+		/// [`HoldAndFreeze`](frame_support::traits::fungibles::HoldAndFreeze) combines
+		/// `pallet-assets-holder` and `pallet-assets-freezer` so that the same type can serve as
+		/// the staking currency. As for staking, the asset is fixed by the runtime.
 		type Currency: Mutate<Self::AccountId>
 			+ MutateFreeze<Self::AccountId, Id = Self::RuntimeFreezeReason>;
 
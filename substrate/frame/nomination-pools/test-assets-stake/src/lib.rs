@@ -0,0 +1,227 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for staking and nomination pools using a `pallet-assets` asset as the staking currency.
+//!
+//! Staking and delegated staking hold the asset through `pallet-assets-holder`, while pools freeze
+//! it through `pallet-assets-freezer`, both adapted to `fungible` with
+//! [`ItemOf`](frame_support::traits::fungible::ItemOf).
+
+#![cfg(test)]
+
+// We do not declare all features used by `construct_runtime`
+#[allow(unexpected_cfgs)]
+mod mock;
+
+use frame_support::{
+	assert_ok,
+	traits::{
+		fungible::{Inspect, InspectFreeze, InspectHold, MutateFreeze},
+		tokens::{Fortitude, Preservation},
+	},
+};
+use mock::*;
+use pallet_nomination_pools::{Event as PoolsEvent, LastPoolId, PoolMembers};
+use pallet_staking::{CurrentEra, Event as StakingEvent, Payee, RewardDestination};
+use sp_staking::StakingInterface;
+
+fn delegation_held(who: AccountId) -> Balance {
+	StakingAsset::balance_on_hold(
+		&pallet_delegated_staking::HoldReason::StakingDelegation.into(),
+		&who,
+	)
+}
+
+fn staking_held(who: AccountId) -> Balance {
+	StakingAsset::balance_on_hold(&pallet_staking::HoldReason::Staking.into(), &who)
+}
+
+#[test]
+fn solo_staking_holds_and_slashes_the_asset() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(StakingAsset::minimum_balance(), STAKING_ASSET_MIN_BALANCE);
+		let issuance = Assets::total_supply(STAKING_ASSET);
+
+		assert_ok!(Staking::bond(RuntimeOrigin::signed(30), 50, RewardDestination::Stash));
+		assert_eq!(
+			staking_events_since_last_call(),
+			vec![StakingEvent::Bonded { stash: 30, amount: 50 }]
+		);
+
+		// the stake is held in the asset, the native currency is untouched.
+		assert_eq!(staking_held(30), 50);
+		assert_eq!(Assets::balance(STAKING_ASSET, 30), 50);
+		assert_eq!(StakingAsset::total_balance(&30), 100);
+		assert_eq!(Balances::free_balance(30), 100);
+		assert_eq!(<Staking as StakingInterface>::total_stake(&30), Ok(50));
+
+		pallet_staking::slashing::do_slash::<Runtime>(
+			&30,
+			10,
+			&mut Default::default(),
+			&mut Default::default(),
+			0,
+		);
+
+		assert_eq!(
+			staking_events_since_last_call(),
+			vec![StakingEvent::Slashed { staker: 30, amount: 10 }]
+		);
+		System::assert_has_event(
+			pallet_assets_holder::Event::<Runtime>::Slashed {
+				who: 30,
+				asset_id: STAKING_ASSET,
+				reason: pallet_staking::HoldReason::Staking.into(),
+				amount: 10,
+			}
+			.into(),
+		);
+		assert_eq!(staking_held(30), 40);
+		assert_eq!(<Staking as StakingInterface>::total_stake(&30), Ok(40));
+		assert_eq!(Assets::total_supply(STAKING_ASSET), issuance - 10);
+		assert_eq!(Balances::free_balance(30), 100);
+	});
+}
+
+#[test]
+fn holds_and_freezes_share_the_asset() {
+	new_test_ext().execute_with(|| {
+		let freeze_id = pallet_nomination_pools::FreezeReason::PoolMinBalance.into();
+		let reducible =
+			|| StakingAsset::reducible_balance(&30, Preservation::Expendable, Fortitude::Polite);
+
+		assert_ok!(StakingAsset::set_freeze(&freeze_id, &30, 60));
+		assert_eq!(StakingAsset::balance_frozen(&freeze_id, &30), 60);
+		assert_eq!(reducible(), 40);
+
+		// frozen funds can still be held.
+		assert_ok!(Staking::bond(RuntimeOrigin::signed(30), 30, RewardDestination::Stash));
+		assert_eq!(staking_held(30), 30);
+		assert_eq!(Assets::balance(STAKING_ASSET, 30), 70);
+		assert_eq!(reducible(), 40);
+
+		// once thawed, only the minimum balance stays untouchable.
+		assert_ok!(StakingAsset::thaw(&freeze_id, &30));
+		assert_eq!(StakingAsset::balance_frozen(&freeze_id, &30), 0);
+		assert_eq!(reducible(), 70 - STAKING_ASSET_MIN_BALANCE);
+	});
+}
+
+#[test]
+fn pool_lifecycle_with_asset_stake() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(CurrentEra::<T>::get(), None);
+
+		// create the pool, we know this has id 1.
+		assert_ok!(Pools::create(RuntimeOrigin::signed(10), 50, 10, 10, 10));
+		assert_eq!(LastPoolId::<Runtime>::get(), 1);
+		assert_ok!(Pools::nominate(RuntimeOrigin::signed(10), 1, vec![1, 2, 3]));
+
+		assert_eq!(
+			staking_events_since_last_call(),
+			vec![StakingEvent::Bonded { stash: POOL1_BONDED, amount: 50 }]
+		);
+		assert_eq!(
+			pool_events_since_last_call(),
+			vec![
+				PoolsEvent::Created { depositor: 10, pool_id: 1 },
+				PoolsEvent::Bonded { member: 10, pool_id: 1, bonded: 50, joined: true },
+				PoolsEvent::PoolNominationMade { pool_id: 1, caller: 10 },
+			]
+		);
+		assert_eq!(
+			Payee::<Runtime>::get(POOL1_BONDED),
+			Some(RewardDestination::Account(POOL1_REWARD))
+		);
+
+		// the depositor's stake is held in the asset, and it paid the reward account's minimum
+		// balance, which is frozen by the pool.
+		assert_eq!(delegation_held(10), 50);
+		assert_eq!(Assets::balance(STAKING_ASSET, 10), 100 - 50 - STAKING_ASSET_MIN_BALANCE);
+		assert_eq!(Assets::balance(STAKING_ASSET, POOL1_REWARD), STAKING_ASSET_MIN_BALANCE);
+		assert_eq!(
+			pallet_assets_freezer::FrozenBalances::<Runtime>::get(STAKING_ASSET, POOL1_REWARD),
+			Some(STAKING_ASSET_MIN_BALANCE)
+		);
+
+		// a member joins.
+		assert_ok!(Pools::join(RuntimeOrigin::signed(20), 10, 1));
+		assert_eq!(delegation_held(20), 10);
+		assert_eq!(Assets::balance(STAKING_ASSET, 20), 90);
+		assert_eq!(<Staking as StakingInterface>::total_stake(&POOL1_BONDED), Ok(60));
+
+		// and leaves again.
+		assert_ok!(Pools::unbond(RuntimeOrigin::signed(20), 20, 10));
+		assert_eq!(PoolMembers::<Runtime>::get(20).unwrap().unbonding_eras.len(), 1);
+		CurrentEra::<Runtime>::set(Some(BondingDuration::get()));
+		assert_ok!(Pools::withdraw_unbonded(RuntimeOrigin::signed(20), 20, 0));
+		assert!(PoolMembers::<Runtime>::get(20).is_none());
+
+		// all of the asset is released, the native currency was never touched.
+		assert_eq!(delegation_held(20), 0);
+		assert_eq!(Assets::balance(STAKING_ASSET, 20), 100);
+		assert_eq!(Balances::free_balance(20), 100);
+		assert_eq!(Balances::free_balance(10), 100);
+	});
+}
+
+#[test]
+fn pool_slash_is_applied_to_asset_holds() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pools::create(RuntimeOrigin::signed(10), 40, 10, 10, 10));
+		assert_ok!(Pools::join(RuntimeOrigin::signed(20), 40, 1));
+		assert_eq!(<Staking as StakingInterface>::total_stake(&POOL1_BONDED), Ok(80));
+		let issuance = Assets::total_supply(STAKING_ASSET);
+
+		CurrentEra::<Runtime>::set(Some(1));
+
+		// slash half of the pool.
+		pallet_staking::slashing::do_slash::<Runtime>(
+			&POOL1_BONDED,
+			40,
+			&mut Default::default(),
+			&mut Default::default(),
+			1,
+		);
+		assert!(staking_events_since_last_call()
+			.contains(&StakingEvent::Slashed { staker: POOL1_BONDED, amount: 40 }));
+
+		// the pool is a virtual staker, so nothing is slashed until the slash is applied to
+		// members.
+		assert_eq!(Pools::api_member_pending_slash(20), 20);
+		assert_eq!(delegation_held(20), 40);
+		assert_eq!(Assets::total_supply(STAKING_ASSET), issuance);
+
+		assert_ok!(Pools::apply_slash(RuntimeOrigin::signed(10), 20));
+		assert_eq!(Pools::api_member_pending_slash(20), 0);
+		assert_eq!(delegation_held(20), 20);
+		assert_eq!(StakingAsset::total_balance(&20), 80);
+		assert_eq!(Assets::total_supply(STAKING_ASSET), issuance - 20);
+		System::assert_has_event(
+			pallet_assets_holder::Event::<Runtime>::Slashed {
+				who: 20,
+				asset_id: STAKING_ASSET,
+				reason: pallet_delegated_staking::HoldReason::StakingDelegation.into(),
+				amount: 20,
+			}
+			.into(),
+		);
+
+		// native currency is unaffected.
+		assert_eq!(Balances::free_balance(20), 100);
+	});
+}
@@ -100,6 +100,12 @@ pub mod pallet {
 		>;
 
 		/// The staking balance.
+		///
+		/// This is usually the native currency, but any `fungibles` asset can be used by
+		/// identifying it with [`ItemOf`](frame_support::traits::fungible::ItemOf), e.g. an asset
+		/// of `pallet-assets` through
+		/// [`HoldAndFreeze`](frame_support::traits::fungibles::HoldAndFreeze). The staked asset is
+		/// fixed by the runtime, the pallet is not generic over an asset id.
 		#[pallet::no_default]
 		type Currency: FunHoldMutate<
 				Self::AccountId,
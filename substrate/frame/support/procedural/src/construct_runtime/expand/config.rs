@@ -29,6 +29,7 @@ pub fn expand_outer_config(
 	let mut types = TokenStream::new();
 	let mut fields = TokenStream::new();
 	let mut genesis_build_calls = TokenStream::new();
+	let mut genesis_config_types = TokenStream::new();
 	let mut query_genesis_config_part_macros = Vec::new();
 
 	for decl in pallet_decls {
@@ -46,6 +47,7 @@ pub fn expand_outer_config(
 			fields.extend(quote!(#attr pub #field_name: #config,));
 			genesis_build_calls
 				.extend(expand_config_build_storage_call(scrate, &config, attr, field_name));
+			genesis_config_types.extend(expand_config_type_info(scrate, &config, attr, field_name));
 			query_genesis_config_part_macros.push(quote! {
 				#path::__substrate_genesis_config_check::is_genesis_config_defined!(#pallet_name);
 				#[cfg(feature = "std")]
@@ -88,6 +90,20 @@ pub fn expand_outer_config(
 			}
 		}
 
+		impl #scrate::genesis_builder_helper::GenesisConfigTypes for RuntimeGenesisConfig {
+			fn genesis_config_types() -> #scrate::__private::Vec<(
+				&'static str,
+				Option<#scrate::__private::scale_info::MetaType>,
+			)> {
+				#[allow(unused_imports)]
+				use #scrate::genesis_builder_helper::{WithTypeInfo as _, WithoutTypeInfo as _};
+				#[allow(unused_mut)]
+				let mut types = #scrate::__private::Vec::new();
+				#genesis_config_types
+				types
+			}
+		}
+
 		/// Test the `Default` derive impl of the `RuntimeGenesisConfig`.
 		#[cfg(test)]
 		#[test]
@@ -137,3 +153,22 @@ fn expand_config_build_storage_call(
 		<#pallet_genesis_config as #scrate::traits::BuildGenesisConfig>::build(&self.#field_name);
 	}
 }
+
+fn expand_config_type_info(
+	scrate: &TokenStream,
+	pallet_genesis_config: &Ident,
+	attr: &TokenStream,
+	field_name: &Ident,
+) -> TokenStream {
+	let name = field_name.to_string();
+	quote! {
+		#attr
+		types.push((
+			#name,
+			(&#scrate::genesis_builder_helper::MaybeTypeInfo::<#pallet_genesis_config>(
+				core::marker::PhantomData,
+			))
+				.maybe_meta_type(),
+		));
+	}
+}
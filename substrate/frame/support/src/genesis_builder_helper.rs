@@ -22,7 +22,9 @@
 extern crate alloc;
 
 use alloc::{format, vec::Vec};
+use core::marker::PhantomData;
 use frame_support::traits::BuildGenesisConfig;
+use scale_info::{MetaType, TypeInfo};
 use sp_genesis_builder::{PresetId, Result as BuildResult};
 
 /// Build `GenesisConfig` from a JSON blob not using any defaults and store it in the storage. For
@@ -54,3 +56,50 @@ where
 		preset_for_name,
 	)
 }
+
+/// The types of the pallet configs a `RuntimeGenesisConfig` is composed of.
+///
+/// Implemented by `construct_runtime!` and `#[frame_support::runtime]` for `RuntimeGenesisConfig`.
+pub trait GenesisConfigTypes {
+	/// The name of every field of the config, with the type of the pallet `GenesisConfig` if it
+	/// implements [`TypeInfo`](scale_info::TypeInfo).
+	fn genesis_config_types() -> Vec<(&'static str, Option<MetaType>)>;
+}
+
+/// Get the JSON Schema of `GenesisConfig`, generated from the type information of the pallet
+/// configs. For more info refer to
+/// [`sp_genesis_builder::GenesisBuilder::genesis_config_schema`].
+pub fn genesis_config_schema<GC: GenesisConfigTypes>() -> Vec<u8> {
+	let schema = sp_genesis_builder::schema::schema_for_genesis_config(GC::genesis_config_types());
+	serde_json::to_string(&schema)
+		.expect("serialization to json is expected to work. qed.")
+		.into_bytes()
+}
+
+/// Resolves to the [`MetaType`] of `T` if it implements [`TypeInfo`](scale_info::TypeInfo), using
+/// [`WithTypeInfo`] and [`WithoutTypeInfo`]. Must be called on a reference, at a concrete type:
+/// `(&MaybeTypeInfo::<T>(PhantomData)).maybe_meta_type()`.
+#[doc(hidden)]
+pub struct MaybeTypeInfo<T>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait WithTypeInfo {
+	fn maybe_meta_type(&self) -> Option<MetaType>;
+}
+
+impl<T: TypeInfo + 'static> WithTypeInfo for MaybeTypeInfo<T> {
+	fn maybe_meta_type(&self) -> Option<MetaType> {
+		Some(MetaType::new::<T>())
+	}
+}
+
+#[doc(hidden)]
+pub trait WithoutTypeInfo {
+	fn maybe_meta_type(&self) -> Option<MetaType>;
+}
+
+impl<T> WithoutTypeInfo for &MaybeTypeInfo<T> {
+	fn maybe_meta_type(&self) -> Option<MetaType> {
+		None
+	}
+}
@@ -2361,6 +2361,10 @@ pub mod pallet_macros {
 	/// Item is defined as either an enum or a struct. It needs to be public and implement the
 	/// trait [`frame_support::traits::BuildGenesisConfig`].
 	///
+	/// The config is only described by the JSON Schema of the runtime genesis config if it
+	/// derives [`scale_info::TypeInfo`], e.g. with `#[scale_info(skip_type_params(T))]`. Otherwise
+	/// any value is accepted for it, see [`sp_genesis_builder::schema`].
+	///
 	/// See [`genesis_build`] for an example.
 	pub use frame_support_procedural::genesis_config;
 
@@ -28,6 +28,7 @@ mod double_map;
 mod key;
 mod map;
 mod nmap;
+mod rent;
 mod value;
 
 pub use counted_map::{CountedStorageMap, CountedStorageMapInstance, Counter};
@@ -39,6 +40,7 @@ pub use key::{
 };
 pub use map::StorageMap;
 pub use nmap::StorageNMap;
+pub use rent::{BurnOnReap, EitherOnReap, OnReap, RefundOnReap, RentError, Rented};
 pub use value::StorageValue;
 
 /// Trait implementing how the storage optional value is converted into the queried type.
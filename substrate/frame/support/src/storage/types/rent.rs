@@ -0,0 +1,482 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deposit-backed storage with optional expiry.
+//!
+//! A [`StorageMap`] whose value is [`Rented`] stores, next to the value itself, the account which
+//! pays for it, the [`Consideration`] ticket representing the deposit and an optional expiry.
+//! The map then gains a set of `*_rented` methods which keep the ticket in sync with the encoded
+//! size of the value, and a permissionless [`reap`](StorageMap::reap) which removes expired
+//! entries and hands the ticket to an [`OnReap`] hook.
+//!
+//! # Example
+//!
+//! ```
+//! #[frame_support::pallet]
+//! mod pallet {
+//!     # use frame_support::{pallet_prelude::*, storage::types::Rented, traits::{Consideration, Footprint}};
+//!     # use frame_system::pallet_prelude::BlockNumberFor;
+//!     #[pallet::config]
+//!     pub trait Config: frame_system::Config {
+//!         type Consideration: Consideration<Self::AccountId, Footprint>;
+//!     }
+//!     # #[pallet::pallet]
+//!     # pub struct Pallet<T>(_);
+//!     #[pallet::storage]
+//!     pub type Notes<T: Config> = StorageMap<
+//!         _,
+//!         Blake2_128Concat,
+//!         u32,
+//!         Rented<BoundedVec<u8, ConstU32<64>>, T::AccountId, T::Consideration, BlockNumberFor<T>>,
+//!     >;
+//! }
+//! ```
+
+use crate::{
+	storage::{
+		types::{OptionQuery, StorageMap},
+		with_storage_layer,
+	},
+	traits::{Consideration, Footprint, Get, GetDefault, StorageInstance},
+};
+use codec::{Decode, Encode, EncodeLike, FullCodec, MaxEncodedLen};
+use core::marker::PhantomData;
+use scale_info::TypeInfo;
+use sp_runtime::{DispatchError, RuntimeDebug};
+
+/// A value held in storage together with the deposit that pays for it.
+///
+/// The `ticket` is a [`Consideration`] and, as such, must be treated as a linear type: it is only
+/// ever created, updated and consumed by the `*_rented` methods and [`reap`](StorageMap::reap)
+/// of the map that holds it.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Rented<Value, Payer, Ticket, Moment> {
+	/// The stored value.
+	pub value: Value,
+	/// The account which holds the deposit for this entry.
+	pub payer: Payer,
+	/// The moment from which the entry may be reaped by anyone, if any.
+	pub expiry: Option<Moment>,
+	/// The deposit held for this entry.
+	ticket: Ticket,
+}
+
+impl<Value, Payer, Ticket, Moment: PartialOrd> Rented<Value, Payer, Ticket, Moment> {
+	/// The deposit held for this entry.
+	pub fn ticket(&self) -> &Ticket {
+		&self.ticket
+	}
+
+	/// Whether the entry has expired at `now`.
+	///
+	/// Entries without an expiry never expire.
+	pub fn is_expired(&self, now: &Moment) -> bool {
+		self.expiry.as_ref().map_or(false, |expiry| expiry <= now)
+	}
+}
+
+/// An error returned when operating on a rented storage entry.
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum RentError {
+	/// There is no entry under the given key.
+	Missing,
+	/// The entry has not expired yet and cannot be reaped.
+	NotExpired,
+	/// The deposit could not be created, updated or released.
+	Consideration(DispatchError),
+}
+
+impl From<DispatchError> for RentError {
+	fn from(e: DispatchError) -> Self {
+		Self::Consideration(e)
+	}
+}
+
+impl From<RentError> for DispatchError {
+	fn from(e: RentError) -> Self {
+		match e {
+			RentError::Missing => DispatchError::Other("RentedEntryMissing"),
+			RentError::NotExpired => DispatchError::Other("RentedEntryNotExpired"),
+			RentError::Consideration(e) => e,
+		}
+	}
+}
+
+/// What to do with the deposit of an entry removed by [`reap`](StorageMap::reap).
+pub trait OnReap<Payer, Ticket> {
+	/// Consume the `ticket` held by `payer`.
+	fn on_reap(payer: &Payer, ticket: Ticket) -> Result<(), DispatchError>;
+}
+
+/// Release the deposit of a reaped entry back to its payer.
+pub struct RefundOnReap;
+impl<Payer, Ticket: Consideration<Payer, Footprint>> OnReap<Payer, Ticket> for RefundOnReap {
+	fn on_reap(payer: &Payer, ticket: Ticket) -> Result<(), DispatchError> {
+		ticket.drop(payer)
+	}
+}
+
+/// Sacrifice the deposit of a reaped entry.
+pub struct BurnOnReap;
+impl<Payer, Ticket: Consideration<Payer, Footprint>> OnReap<Payer, Ticket> for BurnOnReap {
+	fn on_reap(payer: &Payer, ticket: Ticket) -> Result<(), DispatchError> {
+		ticket.burn(payer);
+		Ok(())
+	}
+}
+
+/// Handle the deposit of a reaped entry with `A` if `Get` returns `true`, otherwise with `B`.
+pub struct EitherOnReap<Switch, A, B>(PhantomData<(Switch, A, B)>);
+impl<Payer, Ticket, Switch, A, B> OnReap<Payer, Ticket> for EitherOnReap<Switch, A, B>
+where
+	Switch: Get<bool>,
+	A: OnReap<Payer, Ticket>,
+	B: OnReap<Payer, Ticket>,
+{
+	fn on_reap(payer: &Payer, ticket: Ticket) -> Result<(), DispatchError> {
+		if Switch::get() {
+			A::on_reap(payer, ticket)
+		} else {
+			B::on_reap(payer, ticket)
+		}
+	}
+}
+
+/// The [`Footprint`] of a rented value.
+fn footprint_of<Value: Encode>(value: &Value) -> Footprint {
+	Footprint::from_encodable(value)
+}
+
+/// Methods to manage a map of rented values.
+///
+/// Every method that touches a deposit runs in its own storage layer: if any of its deposit
+/// operations fails, the ones that already succeeded are rolled back along with the entry.
+impl<Prefix, Hasher, Key, Value, Payer, Ticket, Moment, MaxValues>
+	StorageMap<
+		Prefix,
+		Hasher,
+		Key,
+		Rented<Value, Payer, Ticket, Moment>,
+		OptionQuery,
+		GetDefault,
+		MaxValues,
+	>
+where
+	Prefix: StorageInstance,
+	Hasher: crate::hash::StorageHasher,
+	Key: FullCodec,
+	Value: FullCodec,
+	Payer: FullCodec + PartialEq,
+	Ticket: Consideration<Payer, Footprint>,
+	Moment: FullCodec + PartialOrd,
+	MaxValues: Get<Option<u32>>,
+{
+	/// Get the value stored under `key`, ignoring its deposit and expiry.
+	pub fn get_value<KeyArg: EncodeLike<Key>>(key: KeyArg) -> Option<Value> {
+		Self::get(key).map(|rented| rented.value)
+	}
+
+	/// Store `value` under `key`, paid for by `payer`, optionally expiring at `expiry`.
+	///
+	/// If an entry already exists, its deposit is updated if the payer is the same, or released
+	/// back to the previous payer otherwise.
+	pub fn insert_rented<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+		value: Value,
+		payer: Payer,
+		expiry: Option<Moment>,
+	) -> Result<(), RentError> {
+		with_storage_layer(|| {
+			Self::try_mutate_exists(key, |maybe_rented| {
+				let footprint = footprint_of(&value);
+				let ticket = match maybe_rented.take() {
+					Some(old) if old.payer == payer => old.ticket.update(&payer, footprint)?,
+					Some(old) => {
+						let ticket = Ticket::new(&payer, footprint)?;
+						old.ticket.drop(&old.payer)?;
+						ticket
+					},
+					None => Ticket::new(&payer, footprint)?,
+				};
+				*maybe_rented = Some(Rented { value, payer, expiry, ticket });
+				Ok(())
+			})
+		})
+	}
+
+	/// Mutate the value stored under `key` and update its deposit to the new size.
+	///
+	/// Nothing is written if `f` returns an error.
+	pub fn try_mutate_rented<KeyArg, R, E, F>(key: KeyArg, f: F) -> Result<R, E>
+	where
+		KeyArg: EncodeLike<Key>,
+		E: From<RentError> + From<DispatchError>,
+		F: FnOnce(&mut Value) -> Result<R, E>,
+	{
+		with_storage_layer(|| {
+			Self::try_mutate_exists(key, |maybe_rented| {
+				let Rented { mut value, payer, expiry, ticket } =
+					maybe_rented.take().ok_or(RentError::Missing)?;
+				let result = f(&mut value)?;
+				let ticket =
+					ticket.update(&payer, footprint_of(&value)).map_err(RentError::from)?;
+				*maybe_rented = Some(Rented { value, payer, expiry, ticket });
+				Ok(result)
+			})
+		})
+	}
+
+	/// Set the expiry of the entry under `key`. `None` makes the entry permanent.
+	pub fn set_expiry<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+		expiry: Option<Moment>,
+	) -> Result<(), RentError> {
+		Self::try_mutate_exists(key, |maybe_rented| {
+			let rented = maybe_rented.as_mut().ok_or(RentError::Missing)?;
+			rented.expiry = expiry;
+			Ok(())
+		})
+	}
+
+	/// Whether the entry under `key` exists and has expired at `now`.
+	pub fn is_expired<KeyArg: EncodeLike<Key>>(key: KeyArg, now: &Moment) -> bool {
+		Self::get(key).map_or(false, |rented| rented.is_expired(now))
+	}
+
+	/// Remove the entry under `key` and release its deposit back to the payer.
+	///
+	/// Returns the removed value and the account which paid for it.
+	pub fn remove_rented<KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+	) -> Result<(Value, Payer), RentError> {
+		with_storage_layer(|| {
+			Self::try_mutate_exists(key, |maybe_rented| {
+				let Rented { value, payer, ticket, .. } =
+					maybe_rented.take().ok_or(RentError::Missing)?;
+				ticket.drop(&payer)?;
+				Ok((value, payer))
+			})
+		})
+	}
+
+	/// Remove the entry under `key` if it has expired at `now`, handing its deposit to `H`.
+	///
+	/// This is meant to be callable by anyone. Returns the removed value and the account which
+	/// paid for it.
+	pub fn reap<H: OnReap<Payer, Ticket>, KeyArg: EncodeLike<Key>>(
+		key: KeyArg,
+		now: &Moment,
+	) -> Result<(Value, Payer), RentError> {
+		with_storage_layer(|| {
+			Self::try_mutate_exists(key, |maybe_rented| match maybe_rented.take() {
+				None => Err(RentError::Missing),
+				Some(rented) if !rented.is_expired(now) => {
+					*maybe_rented = Some(rented);
+					Err(RentError::NotExpired)
+				},
+				Some(Rented { value, payer, ticket, .. }) => {
+					H::on_reap(&payer, ticket)?;
+					Ok((value, payer))
+				},
+			})
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{hash::*, parameter_types, storage::unhashed};
+	use sp_io::TestExternalities;
+
+	// The deposits are kept in storage, so that they are rolled back with it.
+	fn held(who: u64) -> u64 {
+		unhashed::get_or_default(&(b"held", who).encode())
+	}
+
+	fn set_held(who: u64, f: impl FnOnce(&mut u64)) {
+		let mut held = held(who);
+		f(&mut held);
+		unhashed::put(&(b"held", who).encode(), &held);
+	}
+
+	fn burnt() -> u64 {
+		unhashed::get_or_default(b"burnt")
+	}
+
+	/// Holds one unit per byte of the footprint. Account `666` cannot afford anything and the
+	/// deposits of account `13` cannot be released.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	struct TestTicket(u64);
+	impl Consideration<u64, Footprint> for TestTicket {
+		fn new(who: &u64, new: Footprint) -> Result<Self, DispatchError> {
+			if *who == 666 {
+				return Err(DispatchError::Other("poor"))
+			}
+			set_held(*who, |h| *h += new.size);
+			Ok(Self(new.size))
+		}
+		fn update(self, who: &u64, new: Footprint) -> Result<Self, DispatchError> {
+			self.drop(who)?;
+			Self::new(who, new)
+		}
+		fn drop(self, who: &u64) -> Result<(), DispatchError> {
+			if *who == 13 {
+				return Err(DispatchError::Other("frozen"))
+			}
+			set_held(*who, |h| *h -= self.0);
+			Ok(())
+		}
+		fn burn(self, who: &u64) {
+			set_held(*who, |h| *h -= self.0);
+			unhashed::put(b"burnt", &(burnt() + self.0));
+		}
+		#[cfg(feature = "runtime-benchmarks")]
+		fn ensure_successful(_: &u64, _: Footprint) {}
+	}
+
+	struct Prefix;
+	impl StorageInstance for Prefix {
+		fn pallet_prefix() -> &'static str {
+			"test"
+		}
+		const STORAGE_PREFIX: &'static str = "foo";
+	}
+
+	parameter_types! {
+		pub static Refund: bool = true;
+	}
+
+	type Map = StorageMap<Prefix, Blake2_128Concat, u16, Rented<Vec<u8>, u64, TestTicket, u32>>;
+	type Reaper = EitherOnReap<Refund, RefundOnReap, BurnOnReap>;
+
+	#[test]
+	fn insert_and_remove_manage_deposit() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(Map::insert_rented(1, vec![1, 2, 3], 1, None), Ok(()));
+			// 3 bytes plus the compact length prefix.
+			assert_eq!(held(1), 4);
+			assert_eq!(Map::get_value(1), Some(vec![1, 2, 3]));
+
+			// Same payer: the deposit is updated in place.
+			assert_eq!(Map::insert_rented(1, vec![1], 1, None), Ok(()));
+			assert_eq!(held(1), 2);
+
+			// Different payer: the old deposit is released.
+			assert_eq!(Map::insert_rented(1, vec![1, 2], 2, None), Ok(()));
+			assert_eq!((held(1), held(2)), (0, 3));
+
+			// A payer who cannot afford the deposit leaves the entry untouched.
+			assert!(matches!(
+				Map::insert_rented(1, vec![], 666, None),
+				Err(RentError::Consideration(_))
+			));
+			assert_eq!(Map::get(1).map(|r| r.payer), Some(2));
+
+			assert_eq!(Map::remove_rented(1), Ok((vec![1, 2], 2)));
+			assert_eq!(held(2), 0);
+			assert_eq!(Map::remove_rented(1), Err(RentError::Missing));
+		});
+	}
+
+	#[test]
+	fn failed_deposit_operations_are_rolled_back() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(Map::insert_rented(1, vec![1], 13, None), Ok(()));
+			assert_eq!(held(13), 2);
+
+			// The deposit of the new payer is taken before the old one fails to be released.
+			assert!(matches!(
+				Map::insert_rented(1, vec![1, 2], 2, None),
+				Err(RentError::Consideration(_))
+			));
+			assert_eq!((held(2), held(13)), (0, 2));
+			assert_eq!(Map::get(1).map(|r| r.payer), Some(13));
+
+			assert!(matches!(Map::remove_rented(1), Err(RentError::Consideration(_))));
+			assert_eq!(held(13), 2);
+			assert_eq!(Map::get_value(1), Some(vec![1]));
+		});
+	}
+
+	#[test]
+	fn try_mutate_rented_updates_deposit() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(
+				Map::try_mutate_rented(1, |v| -> Result<(), RentError> {
+					v.push(1);
+					Ok(())
+				}),
+				Err(RentError::Missing)
+			);
+
+			assert_eq!(Map::insert_rented(1, vec![], 1, Some(10)), Ok(()));
+			assert_eq!(held(1), 1);
+			assert_eq!(
+				Map::try_mutate_rented(1, |v| -> Result<(), RentError> {
+					v.extend([1, 2, 3]);
+					Ok(())
+				}),
+				Ok(())
+			);
+			assert_eq!(held(1), 4);
+			assert_eq!(Map::get(1).and_then(|r| r.expiry), Some(10));
+
+			// A failing mutation writes nothing.
+			assert_eq!(
+				Map::try_mutate_rented(1, |v| -> Result<(), RentError> {
+					v.clear();
+					Err(RentError::NotExpired)
+				}),
+				Err(RentError::NotExpired)
+			);
+			assert_eq!(Map::get_value(1), Some(vec![1, 2, 3]));
+			assert_eq!(held(1), 4);
+		});
+	}
+
+	#[test]
+	fn reap_only_expired_entries() {
+		TestExternalities::default().execute_with(|| {
+			assert_eq!(Map::reap::<Reaper, _>(1, &0), Err(RentError::Missing));
+
+			assert_eq!(Map::insert_rented(1, vec![1], 1, None), Ok(()));
+			assert_eq!(Map::insert_rented(2, vec![2], 2, Some(10)), Ok(()));
+			assert_eq!(Map::insert_rented(3, vec![3], 3, Some(10)), Ok(()));
+
+			// Permanent entries never expire.
+			assert!(!Map::is_expired(1, &u32::MAX));
+			assert_eq!(Map::reap::<Reaper, _>(1, &u32::MAX), Err(RentError::NotExpired));
+
+			assert!(!Map::is_expired(2, &9));
+			assert_eq!(Map::reap::<Reaper, _>(2, &9), Err(RentError::NotExpired));
+			assert!(Map::is_expired(2, &10));
+			assert_eq!(Map::reap::<Reaper, _>(2, &10), Ok((vec![2], 2)));
+			assert_eq!((held(2), burnt()), (0, 0));
+			assert!(!Map::contains_key(2));
+
+			Refund::set(false);
+			assert_eq!(Map::reap::<Reaper, _>(3, &11), Ok((vec![3], 3)));
+			assert_eq!((held(3), burnt()), (0, 2));
+
+			// Extending the expiry protects the entry again.
+			assert_eq!(Map::set_expiry(1, Some(20)), Ok(()));
+			assert_eq!(Map::reap::<Reaper, _>(1, &19), Err(RentError::NotExpired));
+			assert_eq!(Map::set_expiry(4, None), Err(RentError::Missing));
+		});
+	}
+}
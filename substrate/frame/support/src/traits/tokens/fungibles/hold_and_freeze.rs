@@ -0,0 +1,374 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Type to combine a holding and a freezing `fungibles::*` implementation into one.
+//!
+//! See the [`crate::traits::fungibles`] doc for more information about fungibles traits.
+
+use core::marker::PhantomData;
+
+use crate::traits::tokens::{
+	fungibles::{self, DecreaseIssuance, Dust, IncreaseIssuance},
+	DepositConsequence, Fortitude, Precision, Preservation, Provenance, WithdrawConsequence,
+};
+use sp_runtime::{DispatchError, DispatchResult};
+
+/// Type to combine a `fungibles::*` implementation supporting holds with one supporting freezes
+/// over the same assets, e.g. `pallet-assets-holder` and `pallet-assets-freezer`.
+///
+/// Balances, holds and imbalances are handled by `Holder`; freezes are handled by `Freezer`.
+/// Combined with [`fungible::ItemOf`](crate::traits::fungible::ItemOf), this provides a single
+/// `fungible::*` type for a given asset which can be used as the `Currency` of pallets that both
+/// hold and freeze funds, such as staking and nomination pools:
+///
+/// ```ignore
+/// pub type StakingAsset = fungible::ItemOf<
+/// 	fungibles::HoldAndFreeze<AssetsHolder, AssetsFreezer>,
+/// 	StakingAssetId,
+/// 	AccountId,
+/// >;
+/// ```
+///
+/// ### Parameters:
+/// - `Holder` is the `fungibles::*` implementation used for everything but freezes.
+/// - `Freezer` is the `fungibles::freeze::*` implementation over the same assets and balances.
+pub struct HoldAndFreeze<Holder, Freezer>(PhantomData<(Holder, Freezer)>);
+
+impl<Holder: fungibles::Inspect<AccountId>, Freezer, AccountId> fungibles::Inspect<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	type AssetId = Holder::AssetId;
+	type Balance = Holder::Balance;
+
+	fn total_issuance(asset: Self::AssetId) -> Self::Balance {
+		Holder::total_issuance(asset)
+	}
+	fn active_issuance(asset: Self::AssetId) -> Self::Balance {
+		Holder::active_issuance(asset)
+	}
+	fn minimum_balance(asset: Self::AssetId) -> Self::Balance {
+		Holder::minimum_balance(asset)
+	}
+	fn total_balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		Holder::total_balance(asset, who)
+	}
+	fn balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		Holder::balance(asset, who)
+	}
+	fn reducible_balance(
+		asset: Self::AssetId,
+		who: &AccountId,
+		preservation: Preservation,
+		force: Fortitude,
+	) -> Self::Balance {
+		Holder::reducible_balance(asset, who, preservation, force)
+	}
+	fn can_deposit(
+		asset: Self::AssetId,
+		who: &AccountId,
+		amount: Self::Balance,
+		provenance: Provenance,
+	) -> DepositConsequence {
+		Holder::can_deposit(asset, who, amount, provenance)
+	}
+	fn can_withdraw(
+		asset: Self::AssetId,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> WithdrawConsequence<Self::Balance> {
+		Holder::can_withdraw(asset, who, amount)
+	}
+	fn asset_exists(asset: Self::AssetId) -> bool {
+		Holder::asset_exists(asset)
+	}
+}
+
+impl<Holder: fungibles::Unbalanced<AccountId>, Freezer, AccountId> fungibles::Unbalanced<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	fn handle_dust(dust: Dust<AccountId, Self>) {
+		let Dust(asset, amount) = dust;
+		Holder::handle_dust(Dust(asset, amount))
+	}
+	fn write_balance(
+		asset: Self::AssetId,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> Result<Option<Self::Balance>, DispatchError> {
+		Holder::write_balance(asset, who, amount)
+	}
+	fn set_total_issuance(asset: Self::AssetId, amount: Self::Balance) {
+		Holder::set_total_issuance(asset, amount)
+	}
+	fn decrease_balance(
+		asset: Self::AssetId,
+		who: &AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+		preservation: Preservation,
+		force: Fortitude,
+	) -> Result<Self::Balance, DispatchError> {
+		Holder::decrease_balance(asset, who, amount, precision, preservation, force)
+	}
+	fn increase_balance(
+		asset: Self::AssetId,
+		who: &AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+	) -> Result<Self::Balance, DispatchError> {
+		Holder::increase_balance(asset, who, amount, precision)
+	}
+	fn deactivate(asset: Self::AssetId, amount: Self::Balance) {
+		Holder::deactivate(asset, amount)
+	}
+	fn reactivate(asset: Self::AssetId, amount: Self::Balance) {
+		Holder::reactivate(asset, amount)
+	}
+}
+
+impl<Holder: fungibles::Mutate<AccountId>, Freezer, AccountId: Eq> fungibles::Mutate<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	fn done_mint_into(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_mint_into(asset, who, amount)
+	}
+	fn done_burn_from(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_burn_from(asset, who, amount)
+	}
+	fn done_shelve(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_shelve(asset, who, amount)
+	}
+	fn done_restore(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_restore(asset, who, amount)
+	}
+	fn done_transfer(
+		asset: Self::AssetId,
+		source: &AccountId,
+		dest: &AccountId,
+		amount: Self::Balance,
+	) {
+		Holder::done_transfer(asset, source, dest, amount)
+	}
+}
+
+impl<Holder: fungibles::Balanced<AccountId>, Freezer, AccountId> fungibles::Balanced<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	type OnDropDebt = IncreaseIssuance<AccountId, Self>;
+	type OnDropCredit = DecreaseIssuance<AccountId, Self>;
+
+	fn done_rescind(asset: Self::AssetId, amount: Self::Balance) {
+		Holder::done_rescind(asset, amount)
+	}
+	fn done_issue(asset: Self::AssetId, amount: Self::Balance) {
+		Holder::done_issue(asset, amount)
+	}
+	fn done_deposit(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_deposit(asset, who, amount)
+	}
+	fn done_withdraw(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) {
+		Holder::done_withdraw(asset, who, amount)
+	}
+}
+
+impl<Holder: fungibles::InspectHold<AccountId>, Freezer, AccountId>
+	fungibles::InspectHold<AccountId> for HoldAndFreeze<Holder, Freezer>
+{
+	type Reason = Holder::Reason;
+
+	fn total_balance_on_hold(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		Holder::total_balance_on_hold(asset, who)
+	}
+	fn reducible_total_balance_on_hold(
+		asset: Self::AssetId,
+		who: &AccountId,
+		force: Fortitude,
+	) -> Self::Balance {
+		Holder::reducible_total_balance_on_hold(asset, who, force)
+	}
+	fn balance_on_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+	) -> Self::Balance {
+		Holder::balance_on_hold(asset, reason, who)
+	}
+	fn hold_available(asset: Self::AssetId, reason: &Self::Reason, who: &AccountId) -> bool {
+		Holder::hold_available(asset, reason, who)
+	}
+	fn ensure_can_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Holder::ensure_can_hold(asset, reason, who, amount)
+	}
+	fn can_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> bool {
+		Holder::can_hold(asset, reason, who, amount)
+	}
+}
+
+impl<Holder: fungibles::UnbalancedHold<AccountId>, Freezer, AccountId>
+	fungibles::UnbalancedHold<AccountId> for HoldAndFreeze<Holder, Freezer>
+{
+	fn set_balance_on_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Holder::set_balance_on_hold(asset, reason, who, amount)
+	}
+	fn decrease_balance_on_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+	) -> Result<Self::Balance, DispatchError> {
+		Holder::decrease_balance_on_hold(asset, reason, who, amount, precision)
+	}
+	fn increase_balance_on_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+		precision: Precision,
+	) -> Result<Self::Balance, DispatchError> {
+		Holder::increase_balance_on_hold(asset, reason, who, amount, precision)
+	}
+}
+
+impl<Holder: fungibles::MutateHold<AccountId>, Freezer, AccountId> fungibles::MutateHold<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	fn done_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) {
+		Holder::done_hold(asset, reason, who, amount)
+	}
+	fn done_release(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) {
+		Holder::done_release(asset, reason, who, amount)
+	}
+	fn done_burn_held(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		who: &AccountId,
+		amount: Self::Balance,
+	) {
+		Holder::done_burn_held(asset, reason, who, amount)
+	}
+	fn done_transfer_on_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		source: &AccountId,
+		dest: &AccountId,
+		amount: Self::Balance,
+	) {
+		Holder::done_transfer_on_hold(asset, reason, source, dest, amount)
+	}
+	fn done_transfer_and_hold(
+		asset: Self::AssetId,
+		reason: &Self::Reason,
+		source: &AccountId,
+		dest: &AccountId,
+		transferred: Self::Balance,
+	) {
+		Holder::done_transfer_and_hold(asset, reason, source, dest, transferred)
+	}
+}
+
+impl<Holder: fungibles::BalancedHold<AccountId>, Freezer, AccountId>
+	fungibles::BalancedHold<AccountId> for HoldAndFreeze<Holder, Freezer>
+{
+}
+
+impl<Holder: fungibles::BalancedHold<AccountId>, Freezer, AccountId>
+	fungibles::hold::DoneSlash<Holder::AssetId, Holder::Reason, AccountId, Holder::Balance>
+	for HoldAndFreeze<Holder, Freezer>
+{
+	fn done_slash(
+		asset: Holder::AssetId,
+		reason: &Holder::Reason,
+		who: &AccountId,
+		amount: Holder::Balance,
+	) {
+		Holder::done_slash(asset, reason, who, amount)
+	}
+}
+
+impl<Holder, Freezer, AccountId> fungibles::InspectFreeze<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+where
+	Holder: fungibles::Inspect<AccountId>,
+	Freezer:
+		fungibles::InspectFreeze<AccountId, AssetId = Holder::AssetId, Balance = Holder::Balance>,
+{
+	type Id = Freezer::Id;
+
+	fn balance_frozen(asset: Self::AssetId, id: &Self::Id, who: &AccountId) -> Self::Balance {
+		Freezer::balance_frozen(asset, id, who)
+	}
+	fn balance_freezable(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		Freezer::balance_freezable(asset, who)
+	}
+	fn can_freeze(asset: Self::AssetId, id: &Self::Id, who: &AccountId) -> bool {
+		Freezer::can_freeze(asset, id, who)
+	}
+}
+
+impl<Holder, Freezer, AccountId> fungibles::MutateFreeze<AccountId>
+	for HoldAndFreeze<Holder, Freezer>
+where
+	Holder: fungibles::Inspect<AccountId>,
+	Freezer:
+		fungibles::MutateFreeze<AccountId, AssetId = Holder::AssetId, Balance = Holder::Balance>,
+{
+	fn set_freeze(
+		asset: Self::AssetId,
+		id: &Self::Id,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Freezer::set_freeze(asset, id, who, amount)
+	}
+	fn extend_freeze(
+		asset: Self::AssetId,
+		id: &Self::Id,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Freezer::extend_freeze(asset, id, who, amount)
+	}
+	fn thaw(asset: Self::AssetId, id: &Self::Id, who: &AccountId) -> DispatchResult {
+		Freezer::thaw(asset, id, who)
+	}
+}
@@ -30,6 +30,7 @@ pub mod approvals;
 mod enumerable;
 pub mod freeze;
 pub mod hold;
+mod hold_and_freeze;
 pub(crate) mod imbalance;
 mod lifetime;
 pub mod metadata;
@@ -43,6 +44,7 @@ pub use hold::{
 	Balanced as BalancedHold, Inspect as InspectHold, Mutate as MutateHold,
 	Unbalanced as UnbalancedHold,
 };
+pub use hold_and_freeze::HoldAndFreeze;
 pub use imbalance::{Credit, Debt, HandleImbalanceDrop, Imbalance};
 pub use lifetime::{Create, Destroy, Refund};
 pub use regular::{
@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use frame_support::derive_impl;
+use frame_support::{derive_impl, genesis_builder_helper::genesis_config_schema};
 use frame_system::pallet_prelude::BlockNumberFor;
 use sp_core::sr25519;
 use sp_runtime::{
@@ -96,3 +96,18 @@ impl pallet::Config for Test {}
 fn init_genesis_config() {
 	pallet::GenesisConfig::<Test>::default();
 }
+
+#[test]
+fn genesis_config_schema_only_describes_configs_with_type_info() {
+	let schema = genesis_config_schema::<RuntimeGenesisConfig>();
+	let schema: serde_json::Value = serde_json::from_slice(&schema).unwrap();
+
+	assert_eq!(schema["required"], serde_json::json!(["myPallet", "system"]));
+	// `frame_system::GenesisConfig` implements `TypeInfo`, so its fields are described.
+	assert_eq!(schema["properties"]["system"]["additionalProperties"], serde_json::json!(false));
+	// The config of `pallet` does not, so any value is accepted for it.
+	assert_eq!(
+		schema["properties"]["myPallet"],
+		serde_json::json!({ "description": sp_genesis_builder::schema::UNKNOWN_TYPE_DESCRIPTION }),
+	);
+}
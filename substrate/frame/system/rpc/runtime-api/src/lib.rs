@@ -23,6 +23,27 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Block as BlockT, ApplyExtrinsicResult};
+
+/// Effects of simulating an extrinsic on top of the state of a block.
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
+pub struct ExtrinsicEffects<Event, Balance> {
+	/// The result of applying the extrinsic, including the dispatch outcome.
+	pub execution_result: ApplyExtrinsicResult,
+	/// The list of events fired while applying the extrinsic.
+	pub emitted_events: Vec<Event>,
+	/// The fee that was actually paid, after any refunds.
+	///
+	/// `None` if the extrinsic did not pay a fee, e.g. because it is unsigned or failed
+	/// validation.
+	pub actual_fee: Option<Balance>,
+}
+
 #[docify::export(AccountNonceApi)]
 sp_api::decl_runtime_apis! {
 	/// The API to query account nonce.
@@ -34,3 +55,21 @@ sp_api::decl_runtime_apis! {
 		fn account_nonce(account: AccountId) -> Nonce;
 	}
 }
+
+sp_api::decl_runtime_apis! {
+	/// API for simulating an extrinsic without committing its effects.
+	///
+	/// The extrinsic is applied on top of the state of the block the call is made at. All state
+	/// changes are discarded once the call returns; clients can still collect them from the
+	/// overlay of the runtime API instance to present a storage diff, which is what
+	/// `system_simulate` does.
+	pub trait ExtrinsicSimulationApi<Event, Balance> where
+		Event: codec::Codec,
+		Balance: codec::Codec,
+	{
+		/// Apply `extrinsic` and return its effects.
+		fn simulate_extrinsic(
+			extrinsic: <Block as BlockT>::Extrinsic,
+		) -> ExtrinsicEffects<Event, Balance>;
+	}
+}
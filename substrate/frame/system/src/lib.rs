@@ -1093,7 +1093,8 @@ pub mod pallet {
 	#[pallet::whitelist_storage]
 	pub type ExtrinsicWeightReclaimed<T: Config> = StorageValue<_, Weight, ValueQuery>;
 
-	#[derive(frame_support::DefaultNoBound)]
+	#[derive(frame_support::DefaultNoBound, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		#[serde(skip)]
@@ -682,6 +682,19 @@ impl<T: Config> Pallet<T> {
 	pub fn deposit_fee_paid_event(who: T::AccountId, actual_fee: BalanceOf<T>, tip: BalanceOf<T>) {
 		Self::deposit_event(Event::TransactionFeePaid { who, actual_fee, tip });
 	}
+
+	/// Return the `actual_fee` of the first [`Event::TransactionFeePaid`] found in `events`.
+	///
+	/// Useful for runtime APIs that simulate an extrinsic and need to report the fee it paid after
+	/// refunds.
+	pub fn actual_fee_paid<E: TryInto<Event<T>>>(
+		events: impl IntoIterator<Item = E>,
+	) -> Option<BalanceOf<T>> {
+		events.into_iter().find_map(|event| match event.try_into() {
+			Ok(Event::TransactionFeePaid { actual_fee, .. }) => Some(actual_fee),
+			_ => None,
+		})
+	}
 }
 
 impl<T> Convert<Weight, BalanceOf<T>> for Pallet<T>
@@ -43,6 +43,11 @@
 //! The latter is used when a new blockchain is launched to enact and store the genesis state. See
 //! the documentation of `chain_spec_builder` for more info.
 //!
+//! Since version 2 of the API, [`GenesisBuilder::genesis_config_schema`] additionally exposes a
+//! JSON Schema of the `RuntimeGenesisConfig`, generated from the type information of the pallet
+//! configs, so that patches can be checked for typos and type errors before they reach
+//! `build_state`.
+//!
 //! ## Patching
 //!
 //! The runtime may provide a number of partial predefined `RuntimeGenesisConfig` configurations in
@@ -75,6 +80,8 @@
 extern crate alloc;
 use alloc::{string::String, vec::Vec};
 
+pub mod schema;
+
 /// The result type alias, used in build methods. `Err` contains formatted error message.
 pub type Result = core::result::Result<(), String>;
 
@@ -129,5 +136,16 @@ sp_api::decl_runtime_apis! {
 		/// The presets from the list can be queried with [`GenesisBuilder::get_preset`] method. If
 		/// no named presets are provided by the runtime the list is empty.
 		fn preset_names() -> Vec<PresetId>;
+
+		/// Returns a JSON Schema describing the `RuntimeGenesisConfig` of the runtime.
+		///
+		/// The schema can be used to validate a patch or a full config, with the path of every
+		/// offending value, before they are passed to [`GenesisBuilder::build_state`]. See the
+		/// [`schema`] module for how the schema is derived and validated.
+		///
+		/// Only the pallet configs implementing `TypeInfo` are described, the schema accepts any
+		/// value for the other ones.
+		#[api_version(2)]
+		fn genesis_config_schema() -> Vec<u8>;
 	}
 }
@@ -0,0 +1,617 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON Schema generation and validation for `RuntimeGenesisConfig`.
+//!
+//! The schema is generated from the [`TypeInfo`](scale_info::TypeInfo) of the pallet
+//! `GenesisConfig`s the `RuntimeGenesisConfig` is composed of, following the conventions of their
+//! serde representation, which is what patches and full configs are deserialized with in
+//! [`GenesisBuilder::build_state`](crate::GenesisBuilder::build_state):
+//! - the fields of the `RuntimeGenesisConfig` and of the pallet configs are `camelCase`,
+//! - newtypes are transparent, tuples are arrays and enums are externally tagged,
+//! - byte arrays and sequences are accepted both as arrays and as (e.g. hex or SS58) strings,
+//! - fixed point numbers are accepted both as numbers and as strings,
+//! - zero-sized fields (e.g. `PhantomData`) are not part of the representation.
+//!
+//! The generated schema uses a small subset of [JSON Schema](https://json-schema.org/):
+//! - `type`, with one or more of `object`, `array`, `string`, `integer`, `number`, `boolean` or
+//!   `null`,
+//! - `anyOf`,
+//! - `properties`, `required` and `additionalProperties` for objects,
+//! - `items` and `prefixItems` (for tuples) for arrays.
+//!
+//! The runtime config and the pallet configs are generated with `#[serde(deny_unknown_fields)]`,
+//! so the top two levels of the schema reject unknown fields. Deeper types may use serde
+//! attributes which are not visible in their type information (e.g. renamed fields or variants),
+//! hence unknown fields are allowed there. For the same reason the schema of a pallet config whose
+//! `GenesisConfig` does not implement `TypeInfo` accepts any value.
+//!
+//! Only the pallet configs are marked as `required` in the runtime config: fields of a pallet
+//! config may be `#[serde(default)]`, which is not visible in its type information.
+
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use scale_info::{
+	form::PortableForm, Field, MetaType, PortableRegistry, Registry, TypeDef, TypeDefPrimitive,
+};
+use serde_json::{json, Map, Value};
+
+/// The JSON Schema dialect declared by the generated schema.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The description of the schema of a pallet config whose type information is not known.
+pub const UNKNOWN_TYPE_DESCRIPTION: &str =
+	"The `GenesisConfig` of this pallet does not implement `TypeInfo`, any value is accepted.";
+
+/// The number of object levels (runtime config and pallet configs) that deny unknown fields.
+const STRICT_DEPTH: usize = 2;
+
+/// Recursive types are not expected in genesis configs; stop describing them below this depth.
+const MAX_DEPTH: usize = 32;
+
+/// The identifiers of the fixed point types, which are serialized as strings.
+const FIXED_POINT_TYPES: [&str; 4] = ["FixedI64", "FixedU64", "FixedI128", "FixedU128"];
+
+/// Generate the JSON Schema of a `RuntimeGenesisConfig`.
+///
+/// `pallet_configs` lists the fields of the `RuntimeGenesisConfig` in declaration order, each with
+/// the type of the pallet `GenesisConfig` if it implements [`TypeInfo`](scale_info::TypeInfo).
+/// Field names are converted to `camelCase`, as done by the serde representation of the config.
+pub fn schema_for_genesis_config(pallet_configs: Vec<(&str, Option<MetaType>)>) -> Value {
+	let mut registry = Registry::new();
+	let pallet_configs = pallet_configs
+		.into_iter()
+		.map(|(name, ty)| (camel_case(name), ty.map(|ty| registry.register_type(&ty).id)))
+		.collect::<Vec<_>>();
+	let registry = PortableRegistry::from(registry);
+
+	let mut properties = Map::new();
+	for (name, ty) in pallet_configs {
+		let schema = match ty {
+			Some(ty) => schema_for_type(&registry, ty, 1),
+			None => json!({ "description": UNKNOWN_TYPE_DESCRIPTION }),
+		};
+		properties.insert(name, schema);
+	}
+	let required = properties.keys().cloned().collect::<Vec<_>>();
+
+	json!({
+		"$schema": SCHEMA_DIALECT,
+		"title": "RuntimeGenesisConfig",
+		"type": "object",
+		"properties": properties,
+		"required": required,
+		"additionalProperties": false,
+	})
+}
+
+/// Convert a `snake_case` field name into `camelCase`, as `#[serde(rename_all = "camelCase")]`.
+fn camel_case(name: &str) -> String {
+	let mut camel = String::with_capacity(name.len());
+	let mut capitalize = false;
+	for (i, c) in name.chars().enumerate() {
+		if c == '_' {
+			capitalize = i > 0;
+		} else if capitalize {
+			camel.extend(c.to_uppercase());
+			capitalize = false;
+		} else {
+			camel.push(c);
+		}
+	}
+	camel
+}
+
+fn schema_for_type(registry: &PortableRegistry, id: u32, depth: usize) -> Value {
+	let Some(ty) = registry.resolve(id).filter(|_| depth < MAX_DEPTH) else { return json!({}) };
+	let ident = ty.path.segments.last().map(String::as_str);
+
+	match &ty.type_def {
+		TypeDef::Primitive(primitive) => schema_for_primitive(primitive),
+		TypeDef::Compact(compact) => schema_for_type(registry, compact.type_param.id, depth),
+		TypeDef::Sequence(sequence) => schema_for_items(registry, sequence.type_param.id, depth),
+		TypeDef::Array(array) => schema_for_items(registry, array.type_param.id, depth),
+		TypeDef::BitSequence(_) => json!({ "type": "array" }),
+		TypeDef::Tuple(tuple) => {
+			let ids = tuple.fields.iter().map(|field| field.id).collect::<Vec<_>>();
+			schema_for_tuple(registry, &ids, depth)
+		},
+		TypeDef::Composite(_) if ident == Some("BTreeMap") => json!({ "type": "object" }),
+		TypeDef::Composite(_) if ident.is_some_and(|ident| FIXED_POINT_TYPES.contains(&ident)) => {
+			json!({ "type": ["number", "string"] })
+		},
+		TypeDef::Composite(composite) => schema_for_fields(registry, &composite.fields, depth),
+		TypeDef::Variant(variant) if ident == Some("Option") => {
+			let some = variant
+				.variants
+				.iter()
+				.find(|variant| variant.name == "Some")
+				.and_then(|variant| variant.fields.first())
+				.map_or(json!({}), |field| schema_for_type(registry, field.ty.id, depth));
+			json!({ "anyOf": [{ "type": "null" }, some] })
+		},
+		TypeDef::Variant(variant) => {
+			let (unit, tagged): (Vec<_>, Vec<_>) =
+				variant.variants.iter().partition(|variant| variant.fields.is_empty());
+			let properties = tagged
+				.into_iter()
+				.map(|variant| {
+					(variant.name.clone(), schema_for_fields(registry, &variant.fields, depth + 1))
+				})
+				.collect::<Map<_, _>>();
+			match (unit.is_empty(), properties.is_empty()) {
+				(false, true) => json!({ "type": "string" }),
+				(true, false) => json!({ "type": "object", "properties": properties }),
+				_ => json!({
+					"anyOf": [{ "type": "string" }, { "type": "object", "properties": properties }]
+				}),
+			}
+		},
+	}
+}
+
+fn schema_for_primitive(primitive: &TypeDefPrimitive) -> Value {
+	match primitive {
+		TypeDefPrimitive::Bool => json!({ "type": "boolean" }),
+		TypeDefPrimitive::Char | TypeDefPrimitive::Str => json!({ "type": "string" }),
+		_ => json!({ "type": "integer" }),
+	}
+}
+
+fn schema_for_items(registry: &PortableRegistry, item: u32, depth: usize) -> Value {
+	let is_byte = registry
+		.resolve(item)
+		.is_some_and(|ty| matches!(ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U8)));
+	if is_byte {
+		json!({ "type": ["string", "array"], "items": { "type": "integer" } })
+	} else {
+		json!({ "type": "array", "items": schema_for_type(registry, item, depth + 1) })
+	}
+}
+
+fn schema_for_tuple(registry: &PortableRegistry, ids: &[u32], depth: usize) -> Value {
+	match ids {
+		[] => json!({ "type": "null" }),
+		ids => {
+			let items = ids
+				.iter()
+				.map(|id| schema_for_type(registry, *id, depth + 1))
+				.collect::<Vec<_>>();
+			json!({ "type": "array", "prefixItems": items })
+		},
+	}
+}
+
+/// Whether `id` is a zero-sized type, like `PhantomData`, which is skipped by serde.
+fn is_zero_sized(registry: &PortableRegistry, id: u32) -> bool {
+	registry
+		.resolve(id)
+		.is_some_and(|ty| matches!(&ty.type_def, TypeDef::Tuple(tuple) if tuple.fields.is_empty()))
+}
+
+fn schema_for_fields(
+	registry: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	depth: usize,
+) -> Value {
+	let fields = fields
+		.iter()
+		.filter(|field| !is_zero_sized(registry, field.ty.id))
+		.collect::<Vec<_>>();
+
+	if fields.iter().any(|field| field.name.is_none()) {
+		return match fields[..] {
+			// Newtypes are transparent.
+			[field] => schema_for_type(registry, field.ty.id, depth),
+			_ => {
+				let ids = fields.iter().map(|field| field.ty.id).collect::<Vec<_>>();
+				schema_for_tuple(registry, &ids, depth)
+			},
+		}
+	}
+
+	let properties = fields
+		.iter()
+		.filter_map(|field| {
+			let name = field.name.as_deref()?;
+			// Only the pallet configs are known to rename their fields.
+			let name = if depth == 1 { camel_case(name) } else { name.to_string() };
+			Some((name, schema_for_type(registry, field.ty.id, depth + 1)))
+		})
+		.collect::<Map<_, _>>();
+	let mut schema = json!({ "type": "object", "properties": properties });
+	if depth < STRICT_DEPTH {
+		schema["additionalProperties"] = false.into();
+	}
+	schema
+}
+
+/// A value that does not match the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+	/// [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) to the offending value.
+	pub path: String,
+	/// What is wrong with the value.
+	pub message: String,
+}
+
+impl core::fmt::Display for SchemaError {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let path = if self.path.is_empty() { "/" } else { &self.path };
+		write!(f, "{}: {}", path, self.message)
+	}
+}
+
+/// Validate a full `RuntimeGenesisConfig` against `schema`.
+///
+/// All the pallet configs must be present, as no defaults are used for them when building the state
+/// from a full config.
+pub fn validate_config(schema: &Value, config: &Value) -> Result<(), Vec<SchemaError>> {
+	validate(schema, config, true)
+}
+
+/// Validate a patch for the default `RuntimeGenesisConfig` against `schema`.
+///
+/// Missing fields are fine, they are taken from the default config.
+pub fn validate_patch(schema: &Value, patch: &Value) -> Result<(), Vec<SchemaError>> {
+	validate(schema, patch, false)
+}
+
+fn validate(schema: &Value, value: &Value, check_required: bool) -> Result<(), Vec<SchemaError>> {
+	let mut errors = Vec::new();
+	validate_value(schema, value, check_required, &mut String::new(), &mut errors);
+	errors.sort_by(|a, b| a.path.cmp(&b.path));
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+fn type_name(value: &Value) -> &'static str {
+	match value {
+		Value::Null => "null",
+		Value::Bool(_) => "boolean",
+		Value::Number(n) if n.to_string().contains(['.', 'e', 'E']) => "number",
+		Value::Number(_) => "integer",
+		Value::String(_) => "string",
+		Value::Array(_) => "array",
+		Value::Object(_) => "object",
+	}
+}
+
+/// Escape a reference token of a JSON pointer.
+fn escape(token: &str) -> String {
+	token.replace('~', "~0").replace('/', "~1")
+}
+
+fn validate_value(
+	schema: &Value,
+	value: &Value,
+	check_required: bool,
+	path: &mut String,
+	errors: &mut Vec<SchemaError>,
+) {
+	let error = |message: String| SchemaError { path: path.clone(), message };
+
+	if let Some(expected) = schema.get("type") {
+		let expected = match expected {
+			Value::Array(types) => types.iter().filter_map(Value::as_str).collect::<Vec<_>>(),
+			expected => expected.as_str().into_iter().collect(),
+		};
+		let actual = type_name(value);
+		// Integers are valid numbers.
+		let matches =
+			|expected: &&str| *expected == actual || (*expected == "number" && actual == "integer");
+		if !expected.is_empty() && !expected.iter().any(matches) {
+			let expected = expected.join(" or ");
+			errors.push(error(format!("expected {expected}, found {actual}")));
+			return
+		}
+	}
+
+	if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array) {
+		let mut best_errors = None;
+		for schema in schemas {
+			let mut any_errors = Vec::new();
+			validate_value(schema, value, check_required, path, &mut any_errors);
+			if any_errors.is_empty() {
+				best_errors = None;
+				break
+			}
+			// Report the errors of the schema the value got furthest into.
+			let deepest = |errors: &Vec<SchemaError>| {
+				errors.iter().map(|error| error.path.len()).max().unwrap_or_default()
+			};
+			if best_errors.as_ref().map_or(true, |best| deepest(&any_errors) > deepest(best)) {
+				best_errors = Some(any_errors);
+			}
+		}
+		if let Some(any_errors) = best_errors {
+			errors.extend(any_errors);
+			return
+		}
+	}
+
+	match value {
+		Value::Object(fields) => {
+			let properties = schema.get("properties").and_then(Value::as_object);
+			let deny_unknown = schema.get("additionalProperties") == Some(&Value::Bool(false));
+
+			if check_required {
+				for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+					if let Some(name) = name.as_str().filter(|name| !fields.contains_key(*name)) {
+						errors.push(error(format!("missing field `{name}`")));
+					}
+				}
+			}
+
+			for (name, field) in fields {
+				let len = path.len();
+				path.push('/');
+				path.push_str(&escape(name));
+				match properties.and_then(|properties| properties.get(name)) {
+					Some(field_schema) => {
+						validate_value(field_schema, field, check_required, path, errors)
+					},
+					None if deny_unknown => {
+						let mut expected = properties
+							.into_iter()
+							.flat_map(|properties| properties.keys())
+							.map(|key| format!("`{key}`"))
+							.collect::<Vec<_>>();
+						expected.sort();
+						let expected = expected.join(", ");
+						errors.push(SchemaError {
+							path: path.clone(),
+							message: format!("unknown field `{name}`, expected one of {expected}"),
+						});
+					},
+					None => {},
+				}
+				path.truncate(len);
+			}
+		},
+		Value::Array(items) => {
+			let prefix_items = schema.get("prefixItems").and_then(Value::as_array);
+			for (index, item) in items.iter().enumerate() {
+				let item_schema = prefix_items
+					.and_then(|prefix_items| prefix_items.get(index))
+					.or_else(|| schema.get("items"));
+				if let Some(item_schema) = item_schema {
+					let len = path.len();
+					path.push('/');
+					path.push_str(&index.to_string());
+					validate_value(item_schema, item, check_required, path, errors);
+					path.truncate(len);
+				}
+			}
+		},
+		_ => {},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::marker::PhantomData;
+	use scale_info::TypeInfo;
+
+	#[derive(TypeInfo)]
+	struct AccountId([u8; 32]);
+
+	#[derive(TypeInfo)]
+	struct SystemConfig {
+		_config: PhantomData<u32>,
+	}
+
+	#[derive(TypeInfo)]
+	struct BalancesConfig {
+		balances: Vec<(AccountId, u64)>,
+		dev_accounts: Option<(u32, u64, Option<String>)>,
+	}
+
+	#[derive(TypeInfo)]
+	#[allow(dead_code)]
+	enum AllowedSlots {
+		PrimarySlots,
+		PrimaryAndSecondaryPlainSlots,
+		PrimaryAndSecondaryVRFSlots,
+	}
+
+	#[derive(TypeInfo)]
+	struct EpochConfig {
+		c: (u64, u64),
+		allowed_slots: AllowedSlots,
+	}
+
+	#[derive(TypeInfo)]
+	struct BabeConfig {
+		authorities: Vec<(AccountId, u64)>,
+		epoch_config: EpochConfig,
+		_config: PhantomData<u32>,
+	}
+
+	fn schema() -> Value {
+		schema_for_genesis_config(vec![
+			("system", Some(MetaType::new::<SystemConfig>())),
+			("balances", Some(MetaType::new::<BalancesConfig>())),
+			("babe", Some(MetaType::new::<BabeConfig>())),
+			("transaction_payment", None),
+		])
+	}
+
+	fn default_config() -> Value {
+		json!({
+			"system": {},
+			"balances": { "balances": [["5GrwvaEF", 1000]], "devAccounts": null },
+			"babe": {
+				"authorities": [],
+				"epochConfig": { "c": [1, 4], "allowed_slots": "PrimaryAndSecondaryVRFSlots" }
+			},
+			"transactionPayment": { "multiplier": "1000000000000000000" }
+		})
+	}
+
+	#[test]
+	fn schema_is_derived_from_type_info() {
+		let schema = schema();
+
+		assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+		assert_eq!(schema["additionalProperties"], false);
+		assert_eq!(schema["required"], json!(["babe", "balances", "system", "transactionPayment"]));
+		assert_eq!(
+			schema["properties"]["system"],
+			json!({ "type": "object", "properties": {}, "additionalProperties": false })
+		);
+		assert_eq!(schema["properties"]["balances"]["additionalProperties"], false);
+		assert_eq!(schema["properties"]["balances"].get("required"), None);
+		assert_eq!(
+			schema["properties"]["balances"]["properties"]["balances"],
+			json!({
+				"type": "array",
+				"items": {
+					"type": "array",
+					"prefixItems": [
+						{ "type": ["string", "array"], "items": { "type": "integer" } },
+						{ "type": "integer" }
+					]
+				}
+			})
+		);
+		assert_eq!(
+			schema["properties"]["balances"]["properties"]["devAccounts"],
+			json!({
+				"anyOf": [
+					{ "type": "null" },
+					{
+						"type": "array",
+						"prefixItems": [
+							{ "type": "integer" },
+							{ "type": "integer" },
+							{ "anyOf": [{ "type": "null" }, { "type": "string" }] }
+						]
+					}
+				]
+			})
+		);
+		// nested structs below pallet configs keep their field names and are not strict.
+		assert_eq!(
+			schema["properties"]["babe"]["properties"]["epochConfig"],
+			json!({
+				"type": "object",
+				"properties": {
+					"c": {
+						"type": "array",
+						"prefixItems": [{ "type": "integer" }, { "type": "integer" }]
+					},
+					"allowed_slots": { "type": "string" }
+				}
+			})
+		);
+		assert_eq!(
+			schema["properties"]["transactionPayment"],
+			json!({ "description": UNKNOWN_TYPE_DESCRIPTION })
+		);
+	}
+
+	#[test]
+	fn default_config_is_valid() {
+		let schema = schema();
+		assert_eq!(validate_config(&schema, &default_config()), Ok(()));
+		assert_eq!(validate_patch(&schema, &json!({})), Ok(()));
+	}
+
+	#[test]
+	fn unknown_fields_are_reported_with_path() {
+		let schema = schema();
+		let patch = json!({ "balances": { "balancess": [] }, "sudo": { "key": "5GrwvaEF" } });
+
+		assert_eq!(
+			validate_patch(&schema, &patch),
+			Err(vec![
+				SchemaError {
+					path: "/balances/balancess".into(),
+					message: "unknown field `balancess`, expected one of `balances`, `devAccounts`"
+						.into(),
+				},
+				SchemaError {
+					path: "/sudo".into(),
+					message: "unknown field `sudo`, expected one of `babe`, `balances`, `system`, \
+						`transactionPayment`"
+						.into(),
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn type_mismatches_are_reported_with_path() {
+		let schema = schema();
+		let patch = json!({
+			"balances": { "balances": [["5GrwvaEF", 1000], [7, 1000]], "devAccounts": [1, "x"] },
+			"babe": { "epochConfig": { "c": "1/4", "allowed_slots": 2 } }
+		});
+
+		assert_eq!(
+			validate_patch(&schema, &patch),
+			Err(vec![
+				SchemaError {
+					path: "/babe/epochConfig/allowed_slots".into(),
+					message: "expected string, found integer".into(),
+				},
+				SchemaError {
+					path: "/babe/epochConfig/c".into(),
+					message: "expected array, found string".into(),
+				},
+				SchemaError {
+					path: "/balances/balances/1/0".into(),
+					message: "expected string or array, found integer".into(),
+				},
+				SchemaError {
+					path: "/balances/devAccounts/1".into(),
+					message: "expected integer, found string".into(),
+				},
+			])
+		);
+	}
+
+	#[test]
+	fn full_config_requires_all_pallet_configs() {
+		let schema = schema();
+		let config =
+			json!({ "balances": { "balances": [] }, "babe": {}, "transactionPayment": null });
+
+		assert_eq!(
+			validate_config(&schema, &config),
+			Err(vec![SchemaError { path: "".into(), message: "missing field `system`".into() }])
+		);
+		// the very same blob is a valid patch.
+		assert_eq!(validate_patch(&schema, &config), Ok(()));
+	}
+
+	#[test]
+	fn field_names_are_camel_cased() {
+		assert_eq!(camel_case("system"), "system");
+		assert_eq!(camel_case("transaction_payment"), "transactionPayment");
+		assert_eq!(camel_case("_config"), "config");
+		assert_eq!(camel_case("epoch_config_2"), "epochConfig2");
+	}
+}
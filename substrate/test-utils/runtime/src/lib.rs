@@ -33,7 +33,7 @@ use codec::{Decode, DecodeWithMemTracking, Encode};
 use frame_support::{
 	construct_runtime, derive_impl,
 	dispatch::DispatchClass,
-	genesis_builder_helper::{build_state, get_preset},
+	genesis_builder_helper::{build_state, genesis_config_schema, get_preset},
 	parameter_types,
 	traits::{ConstU32, ConstU64},
 	weights::{
@@ -535,6 +535,20 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_system_rpc_runtime_api::ExtrinsicSimulationApi<Block, RuntimeEvent, Balance> for Runtime {
+		fn simulate_extrinsic(
+			extrinsic: <Block as BlockT>::Extrinsic,
+		) -> frame_system_rpc_runtime_api::ExtrinsicEffects<RuntimeEvent, Balance> {
+			let (execution_result, events) = Executive::simulate_extrinsic(extrinsic);
+			frame_system_rpc_runtime_api::ExtrinsicEffects {
+				execution_result,
+				emitted_events: events.into_iter().map(|record| record.event).collect(),
+				// This runtime does not charge transaction fees.
+				actual_fee: None,
+			}
+		}
+	}
+
 	impl self::TestAPI<Block> for Runtime {
 		fn balance_of(id: AccountId) -> u64 {
 			Balances::free_balance(id)
@@ -724,6 +738,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_state::<RuntimeGenesisConfig>(config)
@@ -762,6 +777,9 @@ impl_runtime_apis! {
 		fn preset_names() -> Vec<PresetId> {
 			vec![PresetId::from("foobar"), PresetId::from("staging")]
 		}
+		fn genesis_config_schema() -> Vec<u8> {
+			genesis_config_schema::<RuntimeGenesisConfig>()
+		}
 	}
 }
 
@@ -1345,6 +1363,32 @@ mod tests {
 			log::info!("r: {:#?}", r);
 		}
 
+		#[test]
+		fn genesis_config_schema_works() {
+			sp_tracing::try_init_simple();
+			let mut t = BasicExternalities::new_empty();
+			let r = executor_call(&mut t, "GenesisBuilder_genesis_config_schema", &vec![]).unwrap();
+			let r = Vec::<u8>::decode(&mut &r[..]).unwrap();
+			let schema: serde_json::Value =
+				serde_json::from_slice(&r[..]).expect("returned value is json. qed.");
+
+			assert_eq!(schema["required"], json!(["babe", "balances", "substrateTest", "system"]));
+			assert_eq!(schema["additionalProperties"], json!(false));
+			assert_eq!(
+				schema["properties"]["balances"]["properties"]["balances"],
+				json!({
+					"type": "array",
+					"items": {
+						"type": "array",
+						"prefixItems": [
+							{ "type": ["string", "array"], "items": { "type": "integer" } },
+							{ "type": "integer" }
+						]
+					}
+				})
+			);
+		}
+
 		#[test]
 		fn named_config_works() {
 			sp_tracing::try_init_simple();
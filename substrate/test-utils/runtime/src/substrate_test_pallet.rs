@@ -55,7 +55,8 @@ pub mod pallet {
 	pub type Authorities<T> = StorageValue<_, Vec<Public>, ValueQuery>;
 
 	#[pallet::genesis_config]
-	#[derive(frame_support::DefaultNoBound)]
+	#[derive(frame_support::DefaultNoBound, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
 	pub struct GenesisConfig<T: Config> {
 		pub authorities: Vec<Public>,
 		#[serde(skip)]
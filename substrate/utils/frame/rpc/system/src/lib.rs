@@ -17,7 +17,7 @@
 
 //! System FRAME specific RPC methods.
 
-use std::{fmt::Display, sync::Arc};
+use std::{cell::RefCell, fmt::Display, marker::PhantomData, sync::Arc};
 
 use codec::{self, Codec, Decode, Encode};
 use jsonrpsee::{
@@ -28,13 +28,16 @@ use jsonrpsee::{
 };
 
 use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
-use sp_api::ApiExt;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, CallApiAt, CallApiAtParams, CallContext};
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::HeaderBackend;
-use sp_core::{hexdisplay::HexDisplay, Bytes};
+use sp_core::{hexdisplay::HexDisplay, storage::ChildInfo, Bytes};
+use sp_externalities::Extensions as RuntimeExtensions;
 use sp_runtime::{legacy, traits};
+use sp_state_machine::{Backend as _, OverlayedChanges, StorageCollection};
 
-pub use frame_system_rpc_runtime_api::AccountNonceApi;
+pub use frame_system_rpc_runtime_api::{AccountNonceApi, ExtrinsicEffects, ExtrinsicSimulationApi};
 
 /// System RPC methods.
 #[docify::export]
@@ -53,6 +56,57 @@ pub trait SystemApi<BlockHash, AccountId, Nonce> {
 	async fn dry_run(&self, extrinsic: Bytes, at: Option<BlockHash>) -> RpcResult<Bytes>;
 }
 
+/// Extrinsic simulation RPC methods.
+#[rpc(client, server)]
+pub trait SimulationApi<BlockHash> {
+	/// Simulate an extrinsic at a given block without committing any of its effects.
+	///
+	/// Returns the SCALE encoded [`ExtrinsicEffects`] reported by the runtime together with the
+	/// storage entries, including those of child tries, the extrinsic changed. Fails with
+	/// [`Error::RuntimeError`] if the runtime does not implement [`ExtrinsicSimulationApi`].
+	#[method(name = "system_simulate", with_extensions)]
+	async fn simulate(
+		&self,
+		extrinsic: Bytes,
+		at: Option<BlockHash>,
+	) -> RpcResult<SimulationResult>;
+}
+
+/// Outcome of [`SimulationApiServer::simulate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationResult {
+	/// SCALE encoded [`ExtrinsicEffects`]: the apply result, the emitted events and the actual
+	/// fee.
+	pub effects: Bytes,
+	/// The top-level storage entries whose value was changed by the extrinsic.
+	pub storage_diff: Vec<StorageDiffItem>,
+	/// The entries of (default) child tries whose value was changed by the extrinsic.
+	pub child_storage_diff: Vec<ChildStorageDiff>,
+}
+
+/// The entries of a child trie changed by a simulated extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildStorageDiff {
+	/// The unprefixed storage key of the child trie.
+	pub child_storage_key: Bytes,
+	/// The entries of the child trie whose value was changed.
+	pub storage_diff: Vec<StorageDiffItem>,
+}
+
+/// A storage entry changed by a simulated extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDiffItem {
+	/// The storage key.
+	pub key: Bytes,
+	/// The value before the extrinsic was applied, `None` if the key did not exist.
+	pub old_value: Option<Bytes>,
+	/// The value after the extrinsic was applied, `None` if the key was removed.
+	pub new_value: Option<Bytes>,
+}
+
 /// Error type of this RPC api.
 pub enum Error {
 	/// The transaction was not decodable.
@@ -176,6 +230,130 @@ where
 	}
 }
 
+/// The runtime function backing [`SimulationApiServer::simulate`], i.e.
+/// [`ExtrinsicSimulationApi::simulate_extrinsic`].
+const SIMULATE_EXTRINSIC: &str = "ExtrinsicSimulationApi_simulate_extrinsic";
+
+/// An implementation of extrinsic simulation RPC methods on full client.
+///
+/// The runtime API is called directly and its SCALE encoded result is passed on as is, so this
+/// does not need to know the event and balance types the runtime uses for
+/// [`ExtrinsicSimulationApi`].
+pub struct Simulation<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Simulation<C, B> {
+	/// Create new `Simulation` given client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+#[async_trait]
+impl<C, Block> SimulationApiServer<<Block as traits::Block>::Hash> for Simulation<C, Block>
+where
+	C: CallApiAt<Block>,
+	C: HeaderBackend<Block>,
+	C: Send + Sync + 'static,
+	Block: traits::Block,
+{
+	async fn simulate(
+		&self,
+		ext: &Extensions,
+		extrinsic: Bytes,
+		at: Option<<Block as traits::Block>::Hash>,
+	) -> RpcResult<SimulationResult> {
+		sc_rpc_api::check_if_safe(ext)?;
+
+		let at = at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash);
+		let runtime_error = |e: String| {
+			ErrorObject::owned(Error::RuntimeError.into(), "Unable to simulate extrinsic.", Some(e))
+		};
+
+		let uxt: <Block as traits::Block>::Extrinsic =
+			Decode::decode(&mut &*extrinsic).map_err(|e| {
+				ErrorObject::owned(
+					Error::DecodeError.into(),
+					"Unable to simulate extrinsic",
+					Some(e.to_string()),
+				)
+			})?;
+
+		let overlay = RefCell::new(OverlayedChanges::default());
+		let mut extensions = RuntimeExtensions::new();
+		self.client
+			.initialize_extensions(at, &mut extensions)
+			.map_err(|e| runtime_error(e.to_string()))?;
+		let effects = self
+			.client
+			.call_api_at(CallApiAtParams {
+				at,
+				function: SIMULATE_EXTRINSIC,
+				arguments: uxt.encode(),
+				overlayed_changes: &overlay,
+				call_context: CallContext::Offchain,
+				recorder: &None,
+				extensions: &RefCell::new(extensions),
+			})
+			.map_err(|e| runtime_error(e.to_string()))?;
+
+		// The overlay still holds the changes made by the extrinsic; compare them against the
+		// state they were applied on.
+		let state = self.client.state_at(at).map_err(|e| runtime_error(e.to_string()))?;
+		let state_version = self
+			.client
+			.runtime_version_at(at)
+			.map_err(|e| runtime_error(e.to_string()))?
+			.state_version();
+		let changes = overlay
+			.borrow_mut()
+			.drain_storage_changes(&state, state_version)
+			.map_err(runtime_error)?;
+
+		let storage_diff = diff_storage(changes.main_storage_changes, |key| state.storage(key))
+			.map_err(runtime_error)?;
+		let mut child_storage_diff = Vec::new();
+		for (child_storage_key, child_changes) in changes.child_storage_changes {
+			let child_info = ChildInfo::new_default(&child_storage_key);
+			let storage_diff =
+				diff_storage(child_changes, |key| state.child_storage(&child_info, key))
+					.map_err(runtime_error)?;
+			if !storage_diff.is_empty() {
+				child_storage_diff.push(ChildStorageDiff {
+					child_storage_key: child_storage_key.into(),
+					storage_diff,
+				});
+			}
+		}
+
+		Ok(SimulationResult { effects: effects.into(), storage_diff, child_storage_diff })
+	}
+}
+
+/// Turn the `changes` of a trie into the entries whose value actually differs from the one
+/// returned by `old_value`.
+fn diff_storage<E: std::fmt::Debug>(
+	changes: StorageCollection,
+	old_value: impl Fn(&[u8]) -> Result<Option<Vec<u8>>, E>,
+) -> Result<Vec<StorageDiffItem>, String> {
+	let mut diff = Vec::new();
+	for (key, new_value) in changes {
+		let old_value = old_value(&key).map_err(|e| format!("{:?}", e))?;
+		if old_value != new_value {
+			diff.push(StorageDiffItem {
+				key: key.into(),
+				old_value: old_value.map(Into::into),
+				new_value: new_value.map(Into::into),
+			});
+		}
+	}
+	Ok(diff)
+}
+
 /// Adjust account nonce from state, so that tx with the nonce will be
 /// placed after all ready txpool transactions.
 fn adjust_nonce<P, AccountId, Nonce>(pool: &P, account: AccountId, nonce: Nonce) -> Nonce
@@ -224,7 +402,10 @@ mod tests {
 		transaction_validity::{InvalidTransaction, TransactionValidityError},
 		ApplyExtrinsicResult,
 	};
-	use substrate_test_runtime_client::{runtime::Transfer, Sr25519Keyring};
+	use substrate_test_runtime_client::{
+		runtime::{RuntimeEvent, Transfer},
+		Sr25519Keyring,
+	};
 
 	fn deny_unsafe() -> Extensions {
 		let mut ext = Extensions::new();
@@ -339,6 +520,94 @@ mod tests {
 		assert_eq!(apply_res, Ok(Ok(())));
 	}
 
+	#[tokio::test]
+	async fn simulate_should_deny_unsafe() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let simulation = Simulation::new(client);
+
+		// when
+		let res = simulation.simulate(&deny_unsafe(), vec![].into(), None).await;
+		assert_matches!(res, Err(e) => {
+			assert!(e.message().contains("RPC call is unsafe to be called externally"));
+		});
+	}
+
+	#[tokio::test]
+	async fn simulate_should_return_effects_and_storage_diff() {
+		sp_tracing::try_init_simple();
+
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let simulation = Simulation::new(client.clone());
+
+		let tx = Transfer {
+			from: Sr25519Keyring::Alice.into(),
+			to: Sr25519Keyring::Bob.into(),
+			amount: 5,
+			nonce: 0,
+		}
+		.into_unchecked_extrinsic();
+
+		// when
+		let result = simulation
+			.simulate(&allow_unsafe(), tx.encode().into(), None)
+			.await
+			.expect("Call is successful");
+
+		// then
+		let effects: ExtrinsicEffects<RuntimeEvent, u64> =
+			Decode::decode(&mut result.effects.as_ref()).unwrap();
+		assert_eq!(effects.execution_result, Ok(Ok(())));
+		assert_eq!(effects.actual_fee, None);
+		assert!(!result.storage_diff.is_empty());
+		assert!(result.storage_diff.iter().all(|item| item.old_value != item.new_value));
+		// a transfer does not touch any child trie.
+		assert!(result.child_storage_diff.is_empty());
+
+		// nothing was committed, so simulating again yields the same outcome.
+		let again = simulation
+			.simulate(&allow_unsafe(), tx.encode().into(), None)
+			.await
+			.expect("Call is successful");
+		assert_eq!(again, result);
+	}
+
+	#[test]
+	fn diff_storage_only_reports_changed_entries() {
+		let state = |key: &[u8]| -> Result<Option<Vec<u8>>, ()> {
+			Ok(match key {
+				b"same" => Some(b"value".to_vec()),
+				b"changed" => Some(b"old".to_vec()),
+				b"removed" => Some(b"gone".to_vec()),
+				_ => None,
+			})
+		};
+		let changes = vec![
+			(b"same".to_vec(), Some(b"value".to_vec())),
+			(b"changed".to_vec(), Some(b"new".to_vec())),
+			(b"removed".to_vec(), None),
+			(b"added".to_vec(), Some(b"fresh".to_vec())),
+			(b"never_existed".to_vec(), None),
+		];
+
+		let item = |key: &[u8], old: Option<&[u8]>, new: Option<&[u8]>| StorageDiffItem {
+			key: key.to_vec().into(),
+			old_value: old.map(|v| v.to_vec().into()),
+			new_value: new.map(|v| v.to_vec().into()),
+		};
+		assert_eq!(
+			diff_storage(changes, state).unwrap(),
+			vec![
+				item(b"changed", Some(b"old"), Some(b"new")),
+				item(b"removed", Some(b"gone"), None),
+				item(b"added", None, Some(b"fresh")),
+			]
+		);
+	}
+
 	#[tokio::test]
 	async fn dry_run_should_indicate_error() {
 		sp_tracing::try_init_simple();
@@ -225,6 +225,23 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl cumulus_primitives_core::UnincludedSegmentApi<Block> for Runtime {
+		fn unincluded_segment() -> cumulus_primitives_core::UnincludedSegmentInfo<<Block as BlockT>::Hash> {
+			ParachainSystem::unincluded_segment_info()
+		}
+
+		fn consensus_hook_capacity() -> cumulus_primitives_core::ConsensusHookCapacity {
+			ConsensusHook::consensus_hook_capacity()
+		}
+
+		fn build_refusal(
+			included_hash: <Block as BlockT>::Hash,
+			relay_slot: cumulus_primitives_aura::Slot,
+		) -> Option<cumulus_primitives_core::BuildRefusal> {
+			ConsensusHook::build_refusal(included_hash, relay_slot)
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {